@@ -0,0 +1,62 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use thiserror::Error;
+
+use lts::LabelledTransitionSystem;
+
+use crate::io_aut::read_aut_file;
+use crate::io_baf::PlainTermFactory;
+use crate::io_fsm::read_fsm;
+use crate::io_lts::read_lts;
+
+#[derive(Error, Debug)]
+pub enum FormatError {
+    #[error("Cannot determine the format of {0}, expected an .aut, .lts or .fsm file")]
+    UnknownFormat(String),
+}
+
+/// The file formats in which a labelled transition system can be stored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LtsFormat {
+    Aut,
+    Lts,
+    Fsm,
+}
+
+impl LtsFormat {
+    /// Returns the format that belongs to the extension of the given path.
+    pub fn from_path(path: &Path) -> Option<LtsFormat> {
+        match path.extension()?.to_str()? {
+            "aut" => Some(LtsFormat::Aut),
+            "lts" => Some(LtsFormat::Lts),
+            "fsm" => Some(LtsFormat::Fsm),
+            _ => None,
+        }
+    }
+}
+
+/// Loads a labelled transition system from the file at the given path, where
+/// the format is determined by the extension of the path.
+pub fn read_lts_file(path: &Path, hidden_labels: Vec<String>) -> Result<LabelledTransitionSystem, Box<dyn Error>> {
+    let format = LtsFormat::from_path(path).ok_or_else(|| FormatError::UnknownFormat(path.display().to_string()))?;
+    read_lts_file_as(path, format, hidden_labels)
+}
+
+/// Loads a labelled transition system in the given format from the file at the given path.
+pub fn read_lts_file_as(
+    path: &Path,
+    format: LtsFormat,
+    hidden_labels: Vec<String>,
+) -> Result<LabelledTransitionSystem, Box<dyn Error>> {
+    match format {
+        LtsFormat::Aut => read_aut_file(path, hidden_labels),
+        LtsFormat::Lts => {
+            let (lts, _) = read_lts(BufReader::new(File::open(path)?), &mut PlainTermFactory, hidden_labels)?;
+            Ok(lts)
+        }
+        LtsFormat::Fsm => read_fsm(BufReader::new(File::open(path)?), hidden_labels),
+    }
+}
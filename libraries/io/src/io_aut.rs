@@ -1,7 +1,12 @@
 use std::collections::HashMap;
 use std::error::Error;
+use std::fs::File;
 use std::io::Read;
 use std::io::Write;
+use std::path::Path;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::thread;
 use std::time::Instant;
 
 use log::debug;
@@ -12,8 +17,11 @@ use thiserror::Error;
 
 use crate::line_iterator::LineIterator;
 use crate::progress::Progress;
+use lts::hide_labels;
 use lts::LabelIndex;
 use lts::LabelledTransitionSystem;
+use lts::StateIndex;
+use unsafety::map_file;
 
 #[derive(Error, Debug)]
 pub enum IOError {
@@ -34,17 +42,37 @@ fn read_transition(input: &str) -> Result<(&str, &str, &str), Box<dyn Error>> {
     let start_second_comma = input.rfind(',').ok_or(IOError::InvalidTransition())?;
     let end_paren = input.rfind(')').ok_or(IOError::InvalidTransition())?;
 
-    let from = &input[start_paren+1..start_comma].trim();
-    let label = &input[start_comma+1..start_second_comma].trim();
-    let to = &input[start_second_comma+1..end_paren].trim();
+    let from = &input[start_paren + 1..start_comma].trim();
+    let label = &input[start_comma + 1..start_second_comma].trim();
+    let to = &input[start_second_comma + 1..end_paren].trim();
     // Handle the special case where it has quotes.
     if label.starts_with('"') && label.ends_with('"') {
-        return Ok((from, &label[1..label.len()-1], to))
+        return Ok((from, &label[1..label.len() - 1], to));
     }
 
     Ok((from, label, to))
 }
 
+/// Parses the header `des (<initial>: Nat, <num_of_transitions>: Nat, <num_of_states>: Nat)`.
+fn read_header(header: &str) -> Result<(usize, usize, usize), Box<dyn Error>> {
+    // Regex for des (<initial>: Nat, <num_of_states>: Nat, <num_of_transitions>: Nat)
+    let header_regex = Regex::new(r#"des\s*\(\s*([0-9]*)\s*,\s*([0-9]*)\s*,\s*([0-9]*)\s*\)\s*"#)
+        .expect("Regex compilation should not fail");
+
+    let (_, [initial_txt, num_of_transitions_txt, num_of_states_txt]) = header_regex
+        .captures(header)
+        .ok_or(IOError::InvalidHeader(
+            "does not match des (<init>, <num_of_transitions>, <num_of_states>)",
+        ))?
+        .extract();
+
+    Ok((
+        initial_txt.parse()?,
+        num_of_transitions_txt.parse()?,
+        num_of_states_txt.parse()?,
+    ))
+}
+
 /// Loads a labelled transition system in the Aldebaran format from the given reader.
 ///
 /// The Aldebaran format consists of a header:
@@ -63,20 +91,7 @@ pub fn read_aut(reader: impl Read, mut hidden_labels: Vec<String>) -> Result<Lab
         .get()
         .ok_or(IOError::InvalidHeader("The first line should be the header"))?;
 
-    // Regex for des (<initial>: Nat, <num_of_states>: Nat, <num_of_transitions>: Nat)
-    let header_regex = Regex::new(r#"des\s*\(\s*([0-9]*)\s*,\s*([0-9]*)\s*,\s*([0-9]*)\s*\)\s*"#)
-        .expect("Regex compilation should not fail");
-
-    let (_, [initial_txt, num_of_transitions_txt, num_of_states_txt]) = header_regex
-        .captures(header)
-        .ok_or(IOError::InvalidHeader(
-            "does not match des (<init>, <num_of_transitions>, <num_of_states>)",
-        ))?
-        .extract();
-
-    let initial_state: usize = initial_txt.parse()?;
-    let num_of_transitions: usize = num_of_transitions_txt.parse()?;
-    let num_of_states: usize = num_of_states_txt.parse()?;
+    let (initial_state, num_of_transitions, num_of_states) = read_header(header)?;
 
     // This is used to keep track of the label to index mapping.
     let mut labels_index: HashMap<String, LabelIndex> = HashMap::new();
@@ -130,6 +145,286 @@ pub fn read_aut(reader: impl Read, mut hidden_labels: Vec<String>) -> Result<Lab
     ))
 }
 
+/// Loads a labelled transition system in the Aldebaran format from the file
+/// at the given path, see [read_aut] for the format.
+///
+/// The file is mapped into memory and the transitions are parsed by multiple
+/// threads in parallel, which is considerably faster for large files.
+pub fn read_aut_file(path: &Path, hidden_labels: Vec<String>) -> Result<LabelledTransitionSystem, Box<dyn Error>> {
+    let file = File::open(path)?;
+    let contents = map_file(&file)?;
+
+    let num_of_threads = thread::available_parallelism().map_or(1, |n| n.get());
+    parse_aut(&contents, hidden_labels, num_of_threads)
+}
+
+/// The transitions of a chunk of the file, where the labels are indices into the labels of the chunk.
+struct Chunk<'a> {
+    labels: Vec<&'a str>,
+    transitions: Vec<(u32, u32, u32)>,
+
+    /// The number of outgoing transitions in this chunk of every state from
+    /// `first_state` onwards, which is replaced by the position of the next
+    /// transition of that state in the result.
+    first_state: StateIndex,
+    degrees: Vec<usize>,
+}
+
+/// Parses the contents of an .aut file, where the transitions are divided
+/// into chunks of lines that are parsed by the given number of threads.
+///
+/// Every chunk counts the outgoing transitions of its states, from which the
+/// offsets of the outgoing transitions of every state are computed. This
+/// allows the chunks to place their transitions in parallel, after which the
+/// outgoing transitions of every state are sorted in parallel.
+fn parse_aut(
+    contents: &[u8],
+    mut hidden_labels: Vec<String>,
+    num_of_threads: usize,
+) -> Result<LabelledTransitionSystem, Box<dyn Error>> {
+    let start = Instant::now();
+    debug!("Reading LTS in .aut format using {num_of_threads} threads...");
+
+    let header_end = contents.iter().position(|c| *c == b'\n').unwrap_or(contents.len());
+    let header = std::str::from_utf8(&contents[..header_end])
+        .map_err(|_| IOError::InvalidHeader("The first line should be the header"))?;
+    let (initial_state, num_of_transitions, num_of_states) = read_header(header)?;
+
+    // Divide the transitions into chunks that end at a line break.
+    let body = &contents[(header_end + 1).min(contents.len())..];
+    let mut chunks = Vec::with_capacity(num_of_threads);
+    let mut chunk_start = 0;
+    for i in 1..=num_of_threads {
+        let mut chunk_end = (body.len() * i / num_of_threads).max(chunk_start);
+        while chunk_end < body.len() && body[chunk_end] != b'\n' {
+            chunk_end += 1;
+        }
+
+        chunks.push(&body[chunk_start..chunk_end]);
+        chunk_start = chunk_end;
+    }
+
+    let mut progress = Progress::new(
+        |value, increment| debug!("Reading transitions {}%...", value / increment),
+        num_of_transitions,
+    );
+
+    let mut chunks: Vec<Chunk> = thread::scope(|scope| {
+        let handles: Vec<_> = chunks.iter().map(|chunk| scope.spawn(|| parse_chunk(chunk))).collect();
+
+        handles
+            .into_iter()
+            .map(|handle| {
+                let chunk = handle.join().expect("The parsing threads should not panic")?;
+                progress.add(chunk.transitions.len());
+                Ok(chunk)
+            })
+            .collect::<Result<_, IOError>>()
+    })?;
+
+    // Merge the labels of all chunks, in the order in which they occur in the file.
+    let mut labels_index: HashMap<&str, LabelIndex> = HashMap::new();
+    let mut labels: Vec<String> = Vec::new();
+    let mut chunk_labels: Vec<Vec<LabelIndex>> = Vec::with_capacity(chunks.len());
+    for chunk in &chunks {
+        let mut label_indices = Vec::with_capacity(chunk.labels.len());
+        for label in &chunk.labels {
+            label_indices.push(*labels_index.entry(label).or_insert_with(|| {
+                labels.push(label.to_string());
+                labels.len() - 1
+            }));
+        }
+
+        chunk_labels.push(label_indices);
+    }
+
+    hidden_labels.push("tau".to_string());
+    let (labels, label_indices) = hide_labels(labels, &hidden_labels);
+    let chunk_labels: Vec<Vec<u32>> = chunk_labels
+        .iter()
+        .map(|indices| indices.iter().map(|label| label_indices[*label] as u32).collect())
+        .collect();
+
+    // Compute the offset of the outgoing transitions of every state, where the count of state i is stored at i + 1.
+    let num_of_states = chunks
+        .iter()
+        .map(|chunk| chunk.first_state + chunk.degrees.len())
+        .fold(num_of_states, usize::max);
+    let mut states = vec![0; num_of_states + 1];
+    for chunk in &chunks {
+        for (index, degree) in chunk.degrees.iter().enumerate() {
+            states[chunk.first_state + index + 1] += degree;
+        }
+    }
+
+    for state_index in 1..states.len() {
+        states[state_index] += states[state_index - 1];
+    }
+
+    // Every chunk places the transitions of a state after the transitions of that state in the previous chunks.
+    let mut next = states.clone();
+    for chunk in &mut chunks {
+        for (index, degree) in chunk.degrees.iter_mut().enumerate() {
+            let count = *degree;
+            *degree = next[chunk.first_state + index];
+            next[chunk.first_state + index] += count;
+        }
+    }
+
+    let scattered: Vec<AtomicU64> = (0..states[num_of_states]).map(|_| AtomicU64::new(0)).collect();
+    thread::scope(|scope| {
+        for (chunk, label_indices) in chunks.iter_mut().zip(&chunk_labels) {
+            let scattered = &scattered;
+            scope.spawn(move || {
+                for &(from, label, to) in &chunk.transitions {
+                    let next = &mut chunk.degrees[from as usize - chunk.first_state];
+                    scattered[*next].store(
+                        (u64::from(label_indices[label as usize]) << 32) | u64::from(to),
+                        Ordering::Relaxed,
+                    );
+                    *next += 1;
+                }
+            });
+        }
+    });
+    drop(chunks);
+
+    // Divide the states into ranges with roughly the same number of transitions, which are sorted in parallel.
+    let mut ranges = vec![0];
+    for i in 1..num_of_threads {
+        let target = scattered.len() * i / num_of_threads;
+        ranges.push(states.partition_point(|offset| *offset < target).min(num_of_states));
+    }
+    ranges.push(num_of_states);
+
+    let mut transitions: Vec<(u32, u32)> = vec![(0, 0); scattered.len()];
+    let mut degrees: Vec<usize> = vec![0; num_of_states];
+    let lengths: Vec<usize> = thread::scope(|scope| {
+        let mut handles = Vec::with_capacity(num_of_threads);
+        let mut remaining_transitions = &mut transitions[..];
+        let mut remaining_degrees = &mut degrees[..];
+        for range in ranges.windows(2) {
+            let (range_transitions, rest) = remaining_transitions.split_at_mut(states[range[1]] - states[range[0]]);
+            remaining_transitions = rest;
+            let (range_degrees, rest) = remaining_degrees.split_at_mut(range[1] - range[0]);
+            remaining_degrees = rest;
+
+            let offsets = &states[range[0]..=range[1]];
+            let scattered = &scattered[states[range[0]]..states[range[1]]];
+            handles.push(scope.spawn(move || sort_transitions(offsets, scattered, range_transitions, range_degrees)));
+        }
+
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("The sorting threads should not panic"))
+            .collect()
+    });
+    drop(scattered);
+
+    // Remove the gaps of duplicated transitions, it is not clear if they are allowed in the .aut format.
+    if lengths.iter().sum::<usize>() != transitions.len() {
+        let mut end = 0;
+        for (range, length) in ranges.windows(2).zip(lengths) {
+            transitions.copy_within(states[range[0]]..states[range[0]] + length, end);
+            end += length;
+        }
+        transitions.truncate(end);
+
+        for (state_index, degree) in degrees.iter().enumerate() {
+            states[state_index + 1] = states[state_index] + degree;
+        }
+    }
+
+    debug!("Finished reading LTS");
+
+    debug!("Time read_aut_file: {:.3}s", start.elapsed().as_secs_f64());
+    Ok(LabelledTransitionSystem::from_csr(
+        initial_state,
+        states,
+        transitions,
+        labels,
+        hidden_labels,
+    ))
+}
+
+/// Parses the transitions on the lines of the given chunk.
+fn parse_chunk(chunk: &[u8]) -> Result<Chunk<'_>, IOError> {
+    let mut labels_index: HashMap<&str, LabelIndex> = HashMap::new();
+    let mut labels = Vec::new();
+    let mut transitions = Vec::new();
+
+    for line in chunk.split(|c| *c == b'\n') {
+        let line = std::str::from_utf8(line).map_err(|_| IOError::InvalidTransition())?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let (from_txt, label_txt, to_txt) = read_transition(line).map_err(|_| IOError::InvalidTransition())?;
+        let from: u32 = from_txt.parse().map_err(|_| IOError::InvalidTransition())?;
+        let to: u32 = to_txt.parse().map_err(|_| IOError::InvalidTransition())?;
+
+        let label_index = *labels_index.entry(label_txt).or_insert_with(|| {
+            labels.push(label_txt);
+            labels.len() - 1
+        });
+
+        transitions.push((from, label_index as u32, to));
+    }
+
+    // Count the outgoing transitions of the states in this chunk, where the target states are included such that the
+    // number of states is at least the largest state index.
+    let first_state = transitions.iter().map(|(from, _, _)| *from).min().unwrap_or(0) as StateIndex;
+    let last_state = transitions
+        .iter()
+        .map(|(from, _, to)| (*from).max(*to) as StateIndex + 1)
+        .max()
+        .unwrap_or(first_state);
+    let mut degrees = vec![0; last_state - first_state];
+    for (from, _, _) in &transitions {
+        degrees[*from as StateIndex - first_state] += 1;
+    }
+
+    Ok(Chunk {
+        labels,
+        transitions,
+        first_state,
+        degrees,
+    })
+}
+
+/// Reads the outgoing transitions of the states with the given offsets from
+/// the scattered transitions, sorts them and removes duplicates. The
+/// transitions are stored consecutively, and their number is returned.
+fn sort_transitions(
+    offsets: &[usize],
+    scattered: &[AtomicU64],
+    transitions: &mut [(u32, u32)],
+    degrees: &mut [usize],
+) -> usize {
+    let mut length = 0;
+    for (state_index, degree) in degrees.iter_mut().enumerate() {
+        let start = offsets[state_index] - offsets[0];
+        let end = offsets[state_index + 1] - offsets[0];
+        for index in start..end {
+            let transition = scattered[index].load(Ordering::Relaxed);
+            transitions[index] = ((transition >> 32) as u32, transition as u32);
+        }
+        transitions[start..end].sort_unstable();
+
+        let first = length;
+        for index in start..end {
+            if length == first || transitions[length - 1] != transitions[index] {
+                transitions[length] = transitions[index];
+                length += 1;
+            }
+        }
+
+        *degree = length - first;
+    }
+
+    length
+}
+
 /// Write a labelled transition system in plain text in Aldebaran format to the given writer.
 pub fn write_aut(writer: &mut impl Write, lts: &LabelledTransitionSystem) -> Result<(), Box<dyn Error>> {
     writeln!(
@@ -146,10 +441,10 @@ pub fn write_aut(writer: &mut impl Write, lts: &LabelledTransitionSystem) -> Res
                 writer,
                 "({}, \"{}\", {})",
                 state_index,
-                if lts.is_hidden_label(*label) {
+                if lts.is_hidden_label(label) {
                     "tau"
                 } else {
-                    &lts.labels()[*label]
+                    &lts.labels()[label]
                 },
                 to
             )?;
@@ -176,6 +471,35 @@ mod tests {
         println!("{}", lts);
     }
 
+    #[test]
+    fn test_reading_aut_parallel() {
+        let file = include_str!("../../../examples/lts/abp.aut");
+        let expected = read_aut(file.as_bytes(), vec![]).unwrap();
+
+        // The result should not depend on the number of chunks.
+        for num_of_threads in 1..8 {
+            let lts = parse_aut(file.as_bytes(), vec![], num_of_threads).unwrap();
+            assert!(
+                lts == expected,
+                "Reading with {num_of_threads} threads gives a different LTS"
+            );
+        }
+
+        let lts = read_aut_file(Path::new("../../examples/lts/abp.aut"), vec![]).unwrap();
+        assert!(lts == expected);
+
+        assert!(parse_aut(b"des (0,1,2)\n(0 \"a\" 1)\n", vec![], 2).is_err());
+
+        // Duplicated transitions are removed, also when they are in different chunks.
+        let duplicates = "des (0,4,3)\n(1,\"b\",0)\n(0,\"a\",1)\n(0,\"a\",1)\n(1,\"b\",0)\n";
+        let expected = read_aut(duplicates.as_bytes(), vec![]).unwrap();
+        for num_of_threads in 1..4 {
+            let lts = parse_aut(duplicates.as_bytes(), vec![], num_of_threads).unwrap();
+            assert!(lts == expected, "Reading with {num_of_threads} threads gives a different LTS");
+            assert_eq!(lts.num_of_transitions(), 2);
+        }
+    }
+
     #[test]
     fn test_lts_failure() {
         let wrong_header = "
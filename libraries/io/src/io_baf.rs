@@ -0,0 +1,500 @@
+//! Reading and writing terms in the binary aterm format (BAF) of the mCRL2
+//! toolset. The format is a stream of packets, where every term is written
+//! after its arguments, which are referred to by their index in the table of
+//! previously written terms. As such, shared subterms are only written once.
+//! The table is shared by all terms of a stream, which is how the mCRL2 file
+//! formats such as .lps, .lts and .pbes store their contents as a sequence of
+//! terms, integers and containers. These can be read and written incrementally.
+//!
+//! This module is independent of the term library, which should implement
+//! [BinaryTerm] for writing and [BinaryTermFactory] for reading terms.
+
+use std::error::Error;
+use std::hash::Hash;
+use std::io::Read;
+use std::io::Write;
+
+use bitstream_io::BigEndian;
+use bitstream_io::BitRead;
+use bitstream_io::BitReader;
+use bitstream_io::BitWrite;
+use bitstream_io::BitWriter;
+use rustc_hash::FxHashMap;
+use thiserror::Error;
+
+use crate::u64_variablelength::read_u64_variablelength;
+use crate::u64_variablelength::write_u64_variablelength;
+
+/// The magic value that starts every binary aterm stream.
+const BAF_MAGIC: u16 = 0x8baf;
+
+/// The version of the binary aterm format that is supported.
+const BAF_VERSION: u16 = 0x8306;
+
+/// The name of the function symbol that is used for integer subterms.
+const INT_SYMBOL: &str = "<aterm_int>";
+
+/// The number of bits used to write the packet type.
+const PACKET_BITS: u32 = 2;
+
+/// The packet types of the stream.
+const PACKET_FUNCTION_SYMBOL: u8 = 0;
+const PACKET_ATERM: u8 = 1;
+const PACKET_ATERM_OUTPUT: u8 = 2;
+const PACKET_ATERM_INT_OUTPUT: u8 = 3;
+
+#[derive(Error, Debug)]
+pub enum BafError {
+    #[error("Missing the BAF_MAGIC control sequence")]
+    InvalidMagic(),
+
+    #[error("Unsupported BAF version {0:#x}, expected version {BAF_VERSION:#x}")]
+    UnsupportedVersion(u16),
+
+    #[error("Function symbol index {0} is out of bounds")]
+    InvalidSymbolIndex(u64),
+
+    #[error("Term index {0} is out of bounds")]
+    InvalidTermIndex(u64),
+
+    #[error("Function symbol name is not valid UTF-8")]
+    InvalidSymbolName(),
+
+    #[error("Expected an integer in the stream")]
+    ExpectedInt(),
+
+    #[error("Unexpected end of the stream")]
+    UnexpectedEnd(),
+}
+
+/// A term that can be written in the binary aterm format.
+pub trait BinaryTerm: Clone + Eq + Hash {
+    /// Returns the value of an integer term, or None for function applications.
+    fn value(&self) -> Option<u64>;
+
+    /// Returns the name and arity of the head symbol of a function application.
+    fn symbol(&self) -> (String, usize);
+
+    /// Returns the arguments of a function application.
+    fn arguments(&self) -> Vec<Self>;
+}
+
+/// Creates the terms that are read from the binary aterm format.
+pub trait BinaryTermFactory {
+    type Term: Clone;
+    type Symbol;
+
+    /// Creates the function symbol with the given name and arity.
+    fn create_symbol(&mut self, name: &str, arity: usize) -> Self::Symbol;
+
+    /// Creates the application of the symbol to the given arguments.
+    fn create_term(&mut self, symbol: &Self::Symbol, arguments: &[Self::Term]) -> Self::Term;
+
+    /// Creates an integer term.
+    fn create_int(&mut self, value: u64) -> Self::Term;
+}
+
+/// A plain term representation that can be used to read and write terms in
+/// the binary aterm format without an ATerm library.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PlainTerm {
+    Int(u64),
+    Appl(String, Vec<PlainTerm>),
+}
+
+impl BinaryTerm for PlainTerm {
+    fn value(&self) -> Option<u64> {
+        match self {
+            PlainTerm::Int(value) => Some(*value),
+            PlainTerm::Appl(_, _) => None,
+        }
+    }
+
+    fn symbol(&self) -> (String, usize) {
+        match self {
+            PlainTerm::Int(_) => panic!("Integers have no function symbol"),
+            PlainTerm::Appl(name, arguments) => (name.clone(), arguments.len()),
+        }
+    }
+
+    fn arguments(&self) -> Vec<Self> {
+        match self {
+            PlainTerm::Int(_) => Vec::new(),
+            PlainTerm::Appl(_, arguments) => arguments.clone(),
+        }
+    }
+}
+
+/// Creates [PlainTerm]s, where function symbols are represented by their name.
+#[derive(Default)]
+pub struct PlainTermFactory;
+
+impl BinaryTermFactory for PlainTermFactory {
+    type Term = PlainTerm;
+    type Symbol = String;
+
+    fn create_symbol(&mut self, name: &str, _arity: usize) -> String {
+        name.to_string()
+    }
+
+    fn create_term(&mut self, symbol: &String, arguments: &[PlainTerm]) -> PlainTerm {
+        PlainTerm::Appl(symbol.clone(), arguments.to_vec())
+    }
+
+    fn create_int(&mut self, value: u64) -> PlainTerm {
+        PlainTerm::Int(value)
+    }
+}
+
+/// Writes terms to a stream in the binary aterm format. The terms written by
+/// one writer share their subterms, so a stream can only be read in full by
+/// a single [BinaryATermReader].
+pub struct BinaryATermWriter<W: Write, T: BinaryTerm> {
+    stream: BitWriter<W, BigEndian>,
+
+    /// The indices of the function symbols written so far, where index zero marks the end of the stream.
+    symbols: FxHashMap<(String, usize), usize>,
+    terms: FxHashMap<T, usize>,
+}
+
+impl<W: Write, T: BinaryTerm> BinaryATermWriter<W, T> {
+    /// Creates a new writer and writes the header of the stream.
+    pub fn new(writer: W) -> Result<Self, Box<dyn Error>> {
+        let mut stream = BitWriter::endian(writer, BigEndian);
+        stream.write(8, 0u8)?;
+        stream.write(16, BAF_MAGIC)?;
+        stream.write(16, BAF_VERSION)?;
+
+        Ok(Self {
+            stream,
+            symbols: FxHashMap::default(),
+            terms: FxHashMap::default(),
+        })
+    }
+
+    /// Writes the given term, where the subterms that were written before are shared.
+    pub fn write(&mut self, term: &T) -> Result<(), Box<dyn Error>> {
+        // The stack of terms to write, which are only written after all their arguments.
+        let mut stack = vec![(term.clone(), false)];
+
+        while let Some((current, arguments_written)) = stack.pop() {
+            // The term itself is always written, even when it occurred before as subterm.
+            let is_output = stack.is_empty();
+            if !is_output && self.terms.contains_key(&current) {
+                continue;
+            }
+
+            if !arguments_written {
+                let arguments = current.arguments();
+                stack.push((current, true));
+                for argument in arguments {
+                    if !self.terms.contains_key(&argument) {
+                        stack.push((argument, false));
+                    }
+                }
+
+                continue;
+            }
+
+            if let Some(value) = current.value() {
+                if is_output {
+                    self.stream.write(PACKET_BITS, PACKET_ATERM_INT_OUTPUT)?;
+                    write_u64_variablelength(&mut self.stream, value)?;
+                } else {
+                    let symbol = self.write_symbol(INT_SYMBOL, 0)?;
+                    self.stream.write(PACKET_BITS, PACKET_ATERM)?;
+                    self.stream.write(self.symbol_index_width(), symbol as u64)?;
+                    write_u64_variablelength(&mut self.stream, value)?;
+                }
+            } else {
+                let (name, arity) = current.symbol();
+                let symbol = self.write_symbol(&name, arity)?;
+
+                self.stream
+                    .write(PACKET_BITS, if is_output { PACKET_ATERM_OUTPUT } else { PACKET_ATERM })?;
+                self.stream.write(self.symbol_index_width(), symbol as u64)?;
+
+                let width = index_width(self.terms.len());
+                for argument in current.arguments() {
+                    let index = self.terms[&argument];
+                    self.stream.write(width, index as u64)?;
+                }
+            }
+
+            if !is_output {
+                // Output terms are not shared, since they are not a subterm of anything written so far.
+                let index = self.terms.len();
+                self.terms.insert(current, index);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes the given integer, which is read by [BinaryATermReader::read_int].
+    pub fn write_int(&mut self, value: u64) -> Result<(), Box<dyn Error>> {
+        self.stream.write(PACKET_BITS, PACKET_ATERM_INT_OUTPUT)?;
+        write_u64_variablelength(&mut self.stream, value)?;
+        Ok(())
+    }
+
+    /// Writes the constant with the given name, which is used by file formats
+    /// to mark the kind of the terms that follow. It is read as a term without
+    /// arguments by [BinaryATermReader::read].
+    pub fn write_constant(&mut self, name: &str) -> Result<(), Box<dyn Error>> {
+        let symbol = self.write_symbol(name, 0)?;
+        self.stream.write(PACKET_BITS, PACKET_ATERM_OUTPUT)?;
+        self.stream.write(self.symbol_index_width(), symbol as u64)?;
+        Ok(())
+    }
+
+    /// Writes the number of terms followed by the terms themselves, which is
+    /// read by [BinaryATermReader::read_container].
+    pub fn write_container<'a, I>(&mut self, terms: I) -> Result<(), Box<dyn Error>>
+    where
+        I: IntoIterator<Item = &'a T>,
+        I::IntoIter: ExactSizeIterator,
+        T: 'a,
+    {
+        let terms = terms.into_iter();
+        self.write_int(terms.len() as u64)?;
+        for term in terms {
+            self.write(term)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the end of the stream and flushes the underlying writer.
+    pub fn finish(mut self) -> Result<(), Box<dyn Error>> {
+        self.stream.write(PACKET_BITS, PACKET_ATERM_OUTPUT)?;
+        self.stream.write(self.symbol_index_width(), 0u64)?;
+        self.stream.byte_align()?;
+        self.stream.flush()?;
+        Ok(())
+    }
+
+    /// Writes the function symbol when it was not written before, and returns its index.
+    fn write_symbol(&mut self, name: &str, arity: usize) -> Result<usize, Box<dyn Error>> {
+        if let Some(&index) = self.symbols.get(&(name.to_string(), arity)) {
+            return Ok(index);
+        }
+
+        self.stream.write(PACKET_BITS, PACKET_FUNCTION_SYMBOL)?;
+        write_u64_variablelength(&mut self.stream, name.len() as u64)?;
+        self.stream.write_bytes(name.as_bytes())?;
+        write_u64_variablelength(&mut self.stream, arity as u64)?;
+
+        // Index zero is reserved for the end of the stream.
+        let index = self.symbols.len() + 1;
+        self.symbols.insert((name.to_string(), arity), index);
+        Ok(index)
+    }
+
+    fn symbol_index_width(&self) -> u32 {
+        index_width(self.symbols.len() + 1)
+    }
+}
+
+/// A function symbol that has been read from the stream.
+enum SymbolEntry<S> {
+    EndOfStream,
+    Int,
+    Function(S, usize),
+}
+
+/// The result of reading the packets up to the next output.
+enum Output<T> {
+    Term(T),
+    Int(u64),
+    End,
+}
+
+/// Reads the terms written by a [BinaryATermWriter], or by the mCRL2 toolset,
+/// one at a time.
+pub struct BinaryATermReader<R: Read, F: BinaryTermFactory> {
+    stream: BitReader<R, BigEndian>,
+    symbols: Vec<SymbolEntry<F::Symbol>>,
+    terms: Vec<F::Term>,
+
+    /// Reused to store the arguments of the term that is read.
+    arguments: Vec<F::Term>,
+}
+
+impl<R: Read, F: BinaryTermFactory> BinaryATermReader<R, F> {
+    /// Creates a new reader and checks the header of the stream.
+    pub fn new(reader: R) -> Result<Self, Box<dyn Error>> {
+        let mut stream = BitReader::endian(reader, BigEndian);
+        if stream.read::<u8>(8)? != 0 || stream.read::<u16>(16)? != BAF_MAGIC {
+            return Err(BafError::InvalidMagic().into());
+        }
+
+        let version = stream.read::<u16>(16)?;
+        if version != BAF_VERSION {
+            return Err(BafError::UnsupportedVersion(version).into());
+        }
+
+        Ok(Self {
+            stream,
+            symbols: vec![SymbolEntry::EndOfStream],
+            terms: Vec::new(),
+            arguments: Vec::new(),
+        })
+    }
+
+    /// Reads the next term, or returns None at the end of the stream.
+    pub fn read(&mut self, factory: &mut F) -> Result<Option<F::Term>, Box<dyn Error>> {
+        match self.read_output(factory)? {
+            Output::Term(term) => Ok(Some(term)),
+            Output::Int(value) => Ok(Some(factory.create_int(value))),
+            Output::End => Ok(None),
+        }
+    }
+
+    /// Reads an integer written by [BinaryATermWriter::write_int].
+    pub fn read_int(&mut self, factory: &mut F) -> Result<u64, Box<dyn Error>> {
+        match self.read_output(factory)? {
+            Output::Int(value) => Ok(value),
+            Output::Term(_) => Err(BafError::ExpectedInt().into()),
+            Output::End => Err(BafError::UnexpectedEnd().into()),
+        }
+    }
+
+    /// Reads the terms written by [BinaryATermWriter::write_container].
+    pub fn read_container(&mut self, factory: &mut F) -> Result<Vec<F::Term>, Box<dyn Error>> {
+        let length = self.read_int(factory)?;
+
+        let mut result = Vec::with_capacity(length as usize);
+        for _ in 0..length {
+            result.push(self.read(factory)?.ok_or(BafError::UnexpectedEnd())?);
+        }
+
+        Ok(result)
+    }
+
+    /// Reads packets until the next output term, integer or the end of the stream.
+    fn read_output(&mut self, factory: &mut F) -> Result<Output<F::Term>, Box<dyn Error>> {
+        loop {
+            let packet = self.stream.read::<u8>(PACKET_BITS)?;
+
+            if packet == PACKET_FUNCTION_SYMBOL {
+                let length = read_u64_variablelength(&mut self.stream)?;
+                let name = String::from_utf8(self.stream.read_to_vec(length as usize)?)
+                    .map_err(|_| BafError::InvalidSymbolName())?;
+                let arity = read_u64_variablelength(&mut self.stream)? as usize;
+
+                if name == INT_SYMBOL && arity == 0 {
+                    self.symbols.push(SymbolEntry::Int);
+                } else {
+                    self.symbols
+                        .push(SymbolEntry::Function(factory.create_symbol(&name, arity), arity));
+                }
+            } else if packet == PACKET_ATERM_INT_OUTPUT {
+                return Ok(Output::Int(read_u64_variablelength(&mut self.stream)?));
+            } else {
+                let is_output = packet == PACKET_ATERM_OUTPUT;
+
+                let index = self.stream.read::<u64>(index_width(self.symbols.len()))?;
+                let term = match self.symbols.get(index as usize) {
+                    None => return Err(BafError::InvalidSymbolIndex(index).into()),
+                    Some(SymbolEntry::EndOfStream) => return Ok(Output::End),
+                    Some(SymbolEntry::Int) => {
+                        let value = read_u64_variablelength(&mut self.stream)?;
+                        factory.create_int(value)
+                    }
+                    Some(SymbolEntry::Function(symbol, arity)) => {
+                        self.arguments.clear();
+                        for _ in 0..*arity {
+                            let index = self.stream.read::<u64>(index_width(self.terms.len()))?;
+                            let argument = self
+                                .terms
+                                .get(index as usize)
+                                .ok_or(BafError::InvalidTermIndex(index))?;
+                            self.arguments.push(argument.clone());
+                        }
+
+                        factory.create_term(symbol, &self.arguments)
+                    }
+                };
+
+                if is_output {
+                    return Ok(Output::Term(term));
+                }
+
+                self.terms.push(term);
+            }
+        }
+    }
+}
+
+/// Returns the number of bits used to write an index into a table of the given size.
+fn index_width(size: usize) -> u32 {
+    usize::BITS - size.leading_zeros()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) use super::PlainTerm as Term;
+    pub(crate) use super::PlainTermFactory as Factory;
+
+    pub(crate) fn appl(name: &str, arguments: &[Term]) -> Term {
+        Term::Appl(name.to_string(), arguments.to_vec())
+    }
+
+    #[test]
+    fn test_baf_round_trip() {
+        let a = appl("a", &[]);
+        let f = appl("f", &[a.clone(), Term::Int(42)]);
+        let terms = vec![
+            appl("g", &[f.clone(), f.clone(), a.clone()]),
+            f.clone(),
+            Term::Int(1 << 40),
+            appl("h", &[f, appl("b", &[]), a]),
+        ];
+
+        let mut buffer = Vec::new();
+        let mut writer = BinaryATermWriter::new(&mut buffer).unwrap();
+        for term in &terms {
+            writer.write(term).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let mut reader = BinaryATermReader::new(&buffer[..]).unwrap();
+        for term in &terms {
+            assert_eq!(reader.read(&mut Factory).unwrap().as_ref(), Some(term));
+        }
+        assert_eq!(reader.read(&mut Factory).unwrap(), None);
+    }
+
+    #[test]
+    fn test_baf_stream() {
+        let a = appl("a", &[]);
+        let states = vec![
+            appl("s", &[a.clone(), Term::Int(0)]),
+            appl("s", &[a.clone(), Term::Int(1)]),
+        ];
+        let label = appl("tau", &[]);
+
+        let mut buffer = Vec::new();
+        let mut writer = BinaryATermWriter::new(&mut buffer).unwrap();
+        writer.write_container(&states).unwrap();
+        writer.write_int(3).unwrap();
+        writer.write(&label).unwrap();
+        writer.finish().unwrap();
+
+        let mut reader = BinaryATermReader::new(&buffer[..]).unwrap();
+        assert_eq!(reader.read_container(&mut Factory).unwrap(), states);
+        assert_eq!(reader.read_int(&mut Factory).unwrap(), 3);
+        assert!(reader.read_int(&mut Factory).is_err());
+        assert_eq!(reader.read(&mut Factory).unwrap(), None);
+    }
+
+    #[test]
+    fn test_baf_invalid_header() {
+        let result: Result<BinaryATermReader<_, Factory>, _> =
+            BinaryATermReader::new(&[0u8, 0x12, 0x34, 0x83, 0x06][..]);
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,218 @@
+use std::error::Error;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Read;
+use std::time::Instant;
+
+use log::debug;
+use thiserror::Error;
+
+use lts::LabelledTransitionSystem;
+use lts::LtsBuilder;
+use lts::StateLabels;
+
+#[derive(Error, Debug)]
+pub enum FsmError {
+    #[error("Expected the sections separated by ---")]
+    MissingSection(),
+
+    #[error("Invalid transition line {0}")]
+    InvalidTransition(String),
+
+    #[error("State numbers start at one, but found state zero")]
+    InvalidState(),
+
+    #[error("Invalid parameter line {0}")]
+    InvalidParameter(String),
+
+    #[error("Invalid state vector {0}")]
+    InvalidStateVector(String),
+
+    #[error("There are {0} state vectors, but {1} states")]
+    MismatchedStates(usize, usize),
+}
+
+/// A process parameter and the values that it can take.
+struct Parameter {
+    name: String,
+    values: Vec<String>,
+}
+
+/// Loads a labelled transition system in the .fsm format from the given reader.
+///
+/// The .fsm format consists of three sections separated by lines `---`. The
+/// first section describes the state parameters, the second section contains
+/// one state vector per line and the last section contains one line for every
+/// transition `<from>: Nat <to>: Nat "<label>": Str`.
+///
+/// A parameter is given by `<name>(<cardinality>: Nat) <sort> "<value>"*`, and
+/// a state vector contains the index of the value of every parameter. The
+/// states are numbered from one, and the first state is the initial state.
+/// When there are parameters the states are labelled by their state vector.
+pub fn read_fsm(reader: impl Read, hidden_labels: Vec<String>) -> Result<LabelledTransitionSystem, Box<dyn Error>> {
+    let start = Instant::now();
+    debug!("Reading LTS in .fsm format...");
+
+    let mut builder = LtsBuilder::new(hidden_labels);
+    let mut section = 0;
+    let mut parameters: Vec<Parameter> = Vec::new();
+    let mut vectors: Vec<Vec<String>> = Vec::new();
+
+    for line in BufReader::new(reader).lines() {
+        let line = line?;
+        let line = line.trim();
+
+        if line == "---" {
+            section += 1;
+            continue;
+        }
+
+        if line.is_empty() {
+            continue;
+        }
+
+        match section {
+            0 => parameters.push(read_parameter(line)?),
+            1 => vectors.push(read_state_vector(line, &parameters)?),
+            2 => {
+                let (from, to, label) = read_transition(line)?;
+                builder.add_transition(from, label, to);
+            }
+            _ => return Err(FsmError::MissingSection().into()),
+        }
+    }
+
+    if section != 2 {
+        return Err(FsmError::MissingSection().into());
+    }
+
+    builder.require_num_of_states(vectors.len());
+    let mut lts = builder.finish(0);
+
+    if !parameters.is_empty() {
+        if vectors.len() != lts.num_of_states() {
+            return Err(FsmError::MismatchedStates(vectors.len(), lts.num_of_states()).into());
+        }
+
+        let names = parameters.into_iter().map(|parameter| parameter.name).collect();
+        lts = lts.with_state_labels(StateLabels::new(names, vectors));
+    }
+
+    debug!("Finished reading LTS in {} ms", start.elapsed().as_millis());
+    Ok(lts)
+}
+
+/// Parses a parameter `<name>(<cardinality>: Nat) <sort> "<value>"*`.
+fn read_parameter(line: &str) -> Result<Parameter, Box<dyn Error>> {
+    let invalid = || FsmError::InvalidParameter(line.to_string());
+
+    let (name, rest) = line.split_once('(').ok_or_else(invalid)?;
+    let (cardinality, rest) = rest.split_once(')').ok_or_else(invalid)?;
+    let cardinality: usize = cardinality.trim().parse()?;
+
+    // The values are the quoted strings after the sort.
+    let values: Vec<String> = rest
+        .split('"')
+        .skip(1)
+        .step_by(2)
+        .map(|value| value.to_string())
+        .collect();
+
+    if values.len() != cardinality {
+        return Err(invalid().into());
+    }
+
+    Ok(Parameter {
+        name: name.trim().to_string(),
+        values,
+    })
+}
+
+/// Parses a state vector, which contains the index of the value of every parameter.
+fn read_state_vector(line: &str, parameters: &[Parameter]) -> Result<Vec<String>, Box<dyn Error>> {
+    let invalid = || FsmError::InvalidStateVector(line.to_string());
+
+    let indices: Vec<&str> = line.split_whitespace().collect();
+    if indices.len() != parameters.len() {
+        return Err(invalid().into());
+    }
+
+    indices
+        .into_iter()
+        .zip(parameters)
+        .map(|(index, parameter)| {
+            let index: usize = index.parse()?;
+            Ok(parameter.values.get(index).ok_or_else(invalid)?.clone())
+        })
+        .collect()
+}
+
+/// Parses a transition `<from>: Nat <to>: Nat "<label>": Str`.
+///
+/// Returns the zero based indices of the states and the label without quotes.
+fn read_transition(line: &str) -> Result<(usize, usize, &str), Box<dyn Error>> {
+    let invalid = || FsmError::InvalidTransition(line.to_string());
+
+    let mut parts = line.splitn(3, char::is_whitespace);
+    let from: usize = parts.next().ok_or_else(invalid)?.parse()?;
+    let to: usize = parts.next().ok_or_else(invalid)?.parse()?;
+    let label = parts.next().ok_or_else(invalid)?.trim();
+
+    let label = label
+        .strip_prefix('"')
+        .and_then(|label| label.strip_suffix('"'))
+        .ok_or_else(invalid)?;
+
+    if from == 0 || to == 0 {
+        return Err(FsmError::InvalidState().into());
+    }
+
+    Ok((from - 1, to - 1, label))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reading_fsm() {
+        let file = "b(2) Bool \"F\" \"T\"\n\
+            n(2) Nat \"1\" \"2\"\n\
+            ---\n\
+            0 0\n\
+            0 1\n\
+            1 0\n\
+            1 1\n\
+            ---\n\
+            1 2 \"increase\"\n\
+            1 3 \"on\"\n\
+            2 4 \"on\"\n\
+            3 1 \"off\"\n\
+            4 2 \"off\"\n";
+
+        let lts = read_fsm(file.as_bytes(), vec![]).unwrap();
+        assert_eq!(lts.num_of_states(), 4);
+        assert_eq!(lts.num_of_transitions(), 5);
+        assert_eq!(lts.initial_state_index(), 0);
+
+        let outgoing: Vec<(&str, usize)> = lts
+            .outgoing_transitions(0)
+            .map(|(label, to)| (lts.labels()[label].as_str(), to))
+            .collect();
+        assert!(outgoing.contains(&("increase", 1)));
+        assert!(outgoing.contains(&("on", 2)));
+
+        let state_labels = lts.state_labels().unwrap();
+        assert_eq!(state_labels.parameters(), ["b", "n"]);
+        assert_eq!(state_labels.state_label(2), [vec!["T".to_string(), "1".to_string()]]);
+    }
+
+    #[test]
+    fn test_reading_invalid_fsm() {
+        assert!(read_fsm("---\n---\n1 2 increase\n".as_bytes(), vec![]).is_err());
+        assert!(read_fsm("---\n1 2 \"a\"\n".as_bytes(), vec![]).is_err());
+        assert!(read_fsm("---\n---\n0 1 \"a\"\n".as_bytes(), vec![]).is_err());
+        assert!(read_fsm("b(2) Bool \"F\" \"T\"\n---\n2\n---\n".as_bytes(), vec![]).is_err());
+        assert!(read_fsm("b(2) Bool \"F\" \"T\"\n---\n0\n---\n1 2 \"a\"\n".as_bytes(), vec![]).is_err());
+    }
+}
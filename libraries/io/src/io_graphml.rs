@@ -0,0 +1,91 @@
+//! Writes labelled transition systems in the GraphML format, which can be
+//! loaded by graph tools such as Gephi, yEd and networkx.
+//!
+//! Every state is a node with identifier `s<index>`, and every transition is a
+//! directed edge with its label stored in the `label` attribute. The initial
+//! state has the `initial` attribute set to true.
+
+use std::error::Error;
+use std::io::Write;
+
+use lts::LabelledTransitionSystem;
+
+/// Write a labelled transition system in the GraphML format to the given writer.
+pub fn write_graphml(writer: &mut impl Write, lts: &LabelledTransitionSystem) -> Result<(), Box<dyn Error>> {
+    writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(writer, r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#)?;
+    writeln!(
+        writer,
+        r#"  <key id="initial" for="node" attr.name="initial" attr.type="boolean"><default>false</default></key>"#
+    )?;
+    writeln!(
+        writer,
+        r#"  <key id="label" for="edge" attr.name="label" attr.type="string"/>"#
+    )?;
+    writeln!(writer, r#"  <graph id="lts" edgedefault="directed">"#)?;
+
+    for state_index in lts.iter_states() {
+        if state_index == lts.initial_state_index() {
+            writeln!(
+                writer,
+                r#"    <node id="s{state_index}"><data key="initial">true</data></node>"#
+            )?;
+        } else {
+            writeln!(writer, r#"    <node id="s{state_index}"/>"#)?;
+        }
+    }
+
+    for state_index in lts.iter_states() {
+        for (label, to) in lts.outgoing_transitions(state_index) {
+            writeln!(
+                writer,
+                r#"    <edge source="s{}" target="s{}"><data key="label">{}</data></edge>"#,
+                state_index,
+                to,
+                escape(&lts.labels()[label])
+            )?;
+        }
+    }
+
+    writeln!(writer, "  </graph>")?;
+    writeln!(writer, "</graphml>")?;
+    Ok(())
+}
+
+/// Replaces the characters that have a special meaning in XML by their entities.
+fn escape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            '\'' => result.push_str("&apos;"),
+            _ => result.push(character),
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::io_aut::read_aut;
+
+    #[test]
+    fn test_writing_graphml() {
+        let lts = read_aut("des (0, 2, 2)\n(0, \"a<b>\", 1)\n(1, \"tau\", 0)\n".as_bytes(), vec![]).unwrap();
+
+        let mut buffer: Vec<u8> = Vec::new();
+        write_graphml(&mut buffer, &lts).unwrap();
+        let text = String::from_utf8(buffer).unwrap();
+
+        assert!(text.contains(r#"<node id="s0"><data key="initial">true</data></node>"#));
+        assert!(text.contains(r#"<node id="s1"/>"#));
+        assert!(text.contains(r#"<edge source="s0" target="s1"><data key="label">a&lt;b&gt;</data></edge>"#));
+        assert!(text.contains(r#"<edge source="s1" target="s0"><data key="label">tau</data></edge>"#));
+    }
+}
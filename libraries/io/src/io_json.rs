@@ -0,0 +1,122 @@
+//! Writes labelled transition systems as JSON, for example to load them in
+//! networkx or D3. The document has the following form:
+//!
+//! ```json
+//! {
+//!   "initial_state": 0,
+//!   "num_of_states": 2,
+//!   "labels": ["tau", "a"],
+//!   "hidden_labels": ["tau"],
+//!   "transitions": [
+//!     { "from": 0, "label": 1, "to": 1 },
+//!     { "from": 1, "label": 0, "to": 0 }
+//!   ]
+//! }
+//! ```
+//!
+//! States are numbered from zero up to `num_of_states`, and the label of a
+//! transition is an index into `labels`. The label with index zero is the
+//! hidden action tau, to which all `hidden_labels` have been renamed.
+//!
+//! When the states are labelled the document additionally contains the
+//! `parameters` and for every state the list of its state vectors in
+//! `state_labels`, i.e., `[[["F", "1"]], [["T", "1"], ["T", "2"]]]`.
+
+use std::error::Error;
+use std::io::Write;
+
+use lts::LabelIndex;
+use lts::LabelledTransitionSystem;
+use lts::StateIndex;
+use serde::Serialize;
+
+/// The JSON representation of a labelled transition system.
+#[derive(Serialize)]
+struct JsonLts<'a> {
+    initial_state: StateIndex,
+    num_of_states: usize,
+    labels: &'a [String],
+    hidden_labels: &'a [String],
+    transitions: Vec<JsonTransition>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parameters: Option<&'a [String]>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    state_labels: Option<Vec<&'a [Vec<String>]>>,
+}
+
+#[derive(Serialize)]
+struct JsonTransition {
+    from: StateIndex,
+    label: LabelIndex,
+    to: StateIndex,
+}
+
+/// Write a labelled transition system as JSON to the given writer.
+pub fn write_json(writer: &mut impl Write, lts: &LabelledTransitionSystem) -> Result<(), Box<dyn Error>> {
+    let transitions = lts
+        .iter_states()
+        .flat_map(|from| {
+            lts.outgoing_transitions(from)
+                .map(move |(label, to)| JsonTransition { from, label, to })
+        })
+        .collect();
+
+    let json = JsonLts {
+        initial_state: lts.initial_state_index(),
+        num_of_states: lts.num_of_states(),
+        labels: lts.labels(),
+        hidden_labels: lts.hidden_labels(),
+        transitions,
+        parameters: lts.state_labels().map(|state_labels| state_labels.parameters()),
+        state_labels: lts.state_labels().map(|state_labels| {
+            lts.iter_states()
+                .map(|state_index| state_labels.state_label(state_index))
+                .collect()
+        }),
+    };
+
+    serde_json::to_writer_pretty(&mut *writer, &json)?;
+    writeln!(writer)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::io_aut::read_aut;
+    use crate::io_fsm::read_fsm;
+
+    #[test]
+    fn test_writing_json() {
+        let lts = read_aut("des (0, 2, 2)\n(0, \"a\", 1)\n(1, \"tau\", 0)\n".as_bytes(), vec![]).unwrap();
+
+        let mut buffer: Vec<u8> = Vec::new();
+        write_json(&mut buffer, &lts).unwrap();
+
+        let json: serde_json::Value = serde_json::from_slice(&buffer).unwrap();
+        assert_eq!(json["initial_state"], 0);
+        assert_eq!(json["num_of_states"], 2);
+        assert_eq!(json["labels"][0], "tau");
+        assert_eq!(
+            json["transitions"],
+            serde_json::json!([{ "from": 0, "label": 1, "to": 1 }, { "from": 1, "label": 0, "to": 0 }])
+        );
+        assert_eq!(json["labels"][1], "a");
+        assert!(json.get("state_labels").is_none());
+    }
+
+    #[test]
+    fn test_writing_json_state_labels() {
+        let lts = read_fsm("b(2) Bool \"F\" \"T\"\n---\n0\n1\n---\n1 2 \"a\"\n".as_bytes(), vec![]).unwrap();
+
+        let mut buffer: Vec<u8> = Vec::new();
+        write_json(&mut buffer, &lts).unwrap();
+
+        let json: serde_json::Value = serde_json::from_slice(&buffer).unwrap();
+        assert_eq!(json["parameters"], serde_json::json!(["b"]));
+        assert_eq!(json["state_labels"], serde_json::json!([[["F"]], [["T"]]]));
+    }
+}
@@ -0,0 +1,593 @@
+//! Reading and writing labelled transition systems in the .lts format of the
+//! mCRL2 toolset, which is for example produced by lps2lts. The file is a
+//! single binary aterm stream, see [crate::io_baf], that consists of a header
+//! followed by the transitions, state labels and initial state in any order:
+//!
+//! - the constant `labelled_transition_system`, followed by the data
+//!   specification, the process parameters and the action label declarations.
+//! - the constant `transition`, followed by the integer source state, the
+//!   multi-action and the integer target state.
+//! - the constant `probabilistic_transition`, followed by the integer source
+//!   state, the multi-action and the probabilistic target state.
+//! - a list, which is the label of the next state.
+//! - the constant `initial_state`, followed by the probabilistic initial state.
+//!
+//! A probabilistic state is written as the number of states, followed by the
+//! state itself when there is only one, or by pairs of a state and its
+//! probability otherwise.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::io::Read;
+use std::io::Write;
+use std::time::Instant;
+
+use log::debug;
+use thiserror::Error;
+
+use crate::io_baf::BinaryATermReader;
+use crate::io_baf::BinaryATermWriter;
+use crate::io_baf::BinaryTerm;
+use crate::io_baf::BinaryTermFactory;
+use lts::LabelIndex;
+use lts::LabelledTransitionSystem;
+use lts::StateIndex;
+
+/// The constants that mark the contents of the stream.
+const LTS_MARK: &str = "labelled_transition_system";
+const TRANSITION_MARK: &str = "transition";
+const PROBABILISTIC_TRANSITION_MARK: &str = "probabilistic_transition";
+const INITIAL_STATE_MARK: &str = "initial_state";
+
+/// The function symbols of the term library that are used for lists.
+const LIST_SYMBOL: &str = "<list_constructor>";
+const EMPTY_LIST_SYMBOL: &str = "<empty_list>";
+
+#[derive(Error, Debug)]
+pub enum LtsError {
+    #[error("The stream does not contain a labelled transition system")]
+    InvalidHeader(),
+
+    #[error("Unexpected term {0} in the labelled transition system")]
+    UnexpectedTerm(String),
+
+    #[error("The labelled transition system has no initial state")]
+    MissingInitialState(),
+
+    #[error("Unexpected end of the labelled transition system")]
+    UnexpectedEnd(),
+
+    #[error("There is no multi-action for label {0}")]
+    UnknownLabel(String),
+}
+
+/// A probabilistic state, which is a distribution over states.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ProbabilisticState<T> {
+    /// A single state with probability one.
+    Single(StateIndex),
+
+    /// The states with their probability, which is a data expression.
+    Distribution(Vec<(StateIndex, T)>),
+}
+
+impl<T> ProbabilisticState<T> {
+    /// Returns an iterator over the states that occur in the distribution.
+    pub fn states(&self) -> impl Iterator<Item = StateIndex> + '_ {
+        let (single, distribution) = match self {
+            ProbabilisticState::Single(state) => (Some(*state), &[][..]),
+            ProbabilisticState::Distribution(states) => (None, &states[..]),
+        };
+
+        single.into_iter().chain(distribution.iter().map(|(state, _)| *state))
+    }
+}
+
+/// The information of an mCRL2 labelled transition system that is not part of
+/// the [LabelledTransitionSystem] itself. The terms are stored as is, so they
+/// can be interpreted by the term library that has read them.
+#[derive(Clone, Debug)]
+pub struct LtsInfo<T> {
+    pub data_specification: T,
+    pub process_parameters: T,
+    pub action_labels: T,
+
+    /// The multi-action of every label of the labelled transition system.
+    pub multi_actions: HashMap<String, T>,
+
+    /// The label of every state, which are lists of the values of the process parameters.
+    pub state_labels: Vec<T>,
+
+    /// When this is not empty the labelled transition system is probabilistic,
+    /// and the target of every transition is an index into this vector.
+    pub probabilistic_states: Vec<ProbabilisticState<T>>,
+
+    /// The initial distribution, of which the first state is the initial state
+    /// of the labelled transition system.
+    pub initial_state: ProbabilisticState<T>,
+}
+
+/// The labelled transition system and its information that are read from a file.
+pub type ReadLts<T> = (LabelledTransitionSystem, LtsInfo<T>);
+
+/// The target of a transition as it is read from the stream.
+enum Target<T> {
+    State(StateIndex),
+    Probabilistic(ProbabilisticState<T>),
+}
+
+/// Loads a labelled transition system in the mCRL2 .lts format from the given
+/// reader, where the terms are created by the given factory. The labels are
+/// the multi-actions printed by [multi_action_name].
+pub fn read_lts<R, F>(
+    reader: R,
+    factory: &mut F,
+    mut hidden_labels: Vec<String>,
+) -> Result<ReadLts<F::Term>, Box<dyn Error>>
+where
+    R: Read,
+    F: BinaryTermFactory,
+    F::Term: BinaryTerm,
+{
+    let start = Instant::now();
+    debug!("Reading LTS in .lts format...");
+
+    let mut stream = BinaryATermReader::new(reader)?;
+
+    let header = stream.read(factory)?.ok_or(LtsError::InvalidHeader())?;
+    if !is_constant(&header, LTS_MARK) {
+        return Err(LtsError::InvalidHeader().into());
+    }
+
+    let data_specification = read_term(&mut stream, factory)?;
+    let process_parameters = read_term(&mut stream, factory)?;
+    let action_labels = read_term(&mut stream, factory)?;
+
+    // This is used to keep track of the label to index mapping.
+    let mut labels_index: HashMap<String, LabelIndex> = HashMap::new();
+    let mut labels: Vec<String> = Vec::new();
+    let mut multi_actions: HashMap<String, F::Term> = HashMap::new();
+
+    let mut transitions: Vec<(StateIndex, LabelIndex, Target<F::Term>)> = Vec::new();
+    let mut state_labels = Vec::new();
+    let mut initial_state = None;
+
+    while let Some(term) = stream.read(factory)? {
+        if is_constant(&term, TRANSITION_MARK) || is_constant(&term, PROBABILISTIC_TRANSITION_MARK) {
+            let from = stream.read_int(factory)? as StateIndex;
+            let multi_action = read_term(&mut stream, factory)?;
+            let to = if is_constant(&term, TRANSITION_MARK) {
+                Target::State(stream.read_int(factory)? as StateIndex)
+            } else {
+                Target::Probabilistic(read_probabilistic_state(&mut stream, factory)?)
+            };
+
+            let name = multi_action_name(&multi_action);
+            let label_index = *labels_index.entry(name.clone()).or_insert_with(|| {
+                labels.push(name.clone());
+                multi_actions.insert(name, multi_action);
+                labels.len() - 1
+            });
+
+            transitions.push((from, label_index, to));
+        } else if is_constant(&term, INITIAL_STATE_MARK) {
+            initial_state = Some(read_probabilistic_state(&mut stream, factory)?);
+        } else if is_list(&term) {
+            state_labels.push(term);
+        } else {
+            return Err(LtsError::UnexpectedTerm(term.symbol().0).into());
+        }
+    }
+
+    let initial_state = initial_state.ok_or(LtsError::MissingInitialState())?;
+    let is_probabilistic = matches!(initial_state, ProbabilisticState::Distribution(_))
+        || transitions
+            .iter()
+            .any(|(_, _, to)| matches!(to, Target::Probabilistic(_)));
+
+    // The targets of a probabilistic transition system refer to the probabilistic states.
+    let mut probabilistic_states = Vec::new();
+    let mut num_of_states = state_labels
+        .len()
+        .max(initial_state.states().max().map_or(0, |state| state + 1));
+    let transitions: Vec<(StateIndex, LabelIndex, StateIndex)> = transitions
+        .into_iter()
+        .map(|(from, label, to)| {
+            num_of_states = num_of_states.max(from + 1);
+
+            let to = match to {
+                Target::State(to) if !is_probabilistic => to,
+                Target::State(to) => {
+                    probabilistic_states.push(ProbabilisticState::Single(to));
+                    probabilistic_states.len() - 1
+                }
+                Target::Probabilistic(state) => {
+                    probabilistic_states.push(state);
+                    probabilistic_states.len() - 1
+                }
+            };
+
+            if !is_probabilistic {
+                num_of_states = num_of_states.max(to + 1);
+            }
+
+            (from, label, to)
+        })
+        .collect();
+
+    for state in &probabilistic_states {
+        num_of_states = num_of_states.max(state.states().max().map_or(0, |state| state + 1));
+    }
+
+    let initial_state_index = initial_state.states().next().unwrap_or_default();
+
+    debug!("Finished reading LTS");
+
+    hidden_labels.push("tau".to_string());
+    debug!("Time read_lts: {:.3}s", start.elapsed().as_secs_f64());
+    Ok((
+        LabelledTransitionSystem::new(
+            initial_state_index,
+            Some(num_of_states),
+            || transitions.iter().cloned(),
+            labels,
+            hidden_labels,
+        ),
+        LtsInfo {
+            data_specification,
+            process_parameters,
+            action_labels,
+            multi_actions,
+            state_labels,
+            probabilistic_states,
+            initial_state,
+        },
+    ))
+}
+
+/// Writes the labelled transition system in the mCRL2 .lts format to the
+/// given writer, where the multi-actions of the labels and the other contents
+/// of the file are taken from the given information.
+pub fn write_lts<W: Write, T: BinaryTerm>(
+    writer: W,
+    lts: &LabelledTransitionSystem,
+    info: &LtsInfo<T>,
+) -> Result<(), Box<dyn Error>> {
+    let mut stream = BinaryATermWriter::new(writer)?;
+
+    stream.write_constant(LTS_MARK)?;
+    stream.write(&info.data_specification)?;
+    stream.write(&info.process_parameters)?;
+    stream.write(&info.action_labels)?;
+
+    for state_index in lts.iter_states() {
+        for (label, to) in lts.outgoing_transitions(state_index) {
+            let name = &lts.labels()[label];
+            let multi_action = info
+                .multi_actions
+                .get(name)
+                .ok_or_else(|| LtsError::UnknownLabel(name.clone()))?;
+
+            let target = if info.probabilistic_states.is_empty() {
+                &ProbabilisticState::Single(to)
+            } else {
+                &info.probabilistic_states[to]
+            };
+
+            match target {
+                ProbabilisticState::Single(to) => {
+                    stream.write_constant(TRANSITION_MARK)?;
+                    stream.write_int(state_index as u64)?;
+                    stream.write(multi_action)?;
+                    stream.write_int(*to as u64)?;
+                }
+                ProbabilisticState::Distribution(_) => {
+                    stream.write_constant(PROBABILISTIC_TRANSITION_MARK)?;
+                    stream.write_int(state_index as u64)?;
+                    stream.write(multi_action)?;
+                    write_probabilistic_state(&mut stream, target)?;
+                }
+            }
+        }
+    }
+
+    // State labels are assigned to the states in the order in which they occur.
+    for label in &info.state_labels {
+        stream.write(label)?;
+    }
+
+    stream.write_constant(INITIAL_STATE_MARK)?;
+    write_probabilistic_state(&mut stream, &info.initial_state)?;
+
+    stream.finish()
+}
+
+/// Returns the multi-action term `TimedMultAct([Action(ActId(a, ...), [d, ...]), ...], t)`
+/// printed as `a(d, ...)|...`, where the empty multi-action is printed as tau.
+/// Data expressions are printed by the name of their function symbols and variables.
+pub fn multi_action_name<T: BinaryTerm>(multi_action: &T) -> String {
+    let actions = match multi_action.arguments().first() {
+        Some(actions) if multi_action.symbol().0 == "TimedMultAct" => list_elements(actions),
+        _ => return print_data_expression(multi_action),
+    };
+
+    if actions.is_empty() {
+        return "tau".to_string();
+    }
+
+    let mut result = Vec::new();
+    for action in &actions {
+        let arguments = action.arguments();
+        let name = arguments
+            .first()
+            .and_then(|id| id.arguments().first().map(|name| name.symbol().0))
+            .unwrap_or_default();
+
+        let values = arguments.get(1).map(list_elements).unwrap_or_default();
+        if values.is_empty() {
+            result.push(name);
+        } else {
+            let values: Vec<String> = values.iter().map(print_data_expression).collect();
+            result.push(format!("{}({})", name, values.join(", ")));
+        }
+    }
+
+    result.join("|")
+}
+
+/// Prints the data expression using the names of function symbols and variables.
+fn print_data_expression<T: BinaryTerm>(term: &T) -> String {
+    if let Some(value) = term.value() {
+        return value.to_string();
+    }
+
+    let (name, _) = term.symbol();
+    let arguments = term.arguments();
+    match name.as_str() {
+        _ if arguments.is_empty() => name,
+        "OpId" | "DataVarId" => arguments[0].symbol().0,
+        "DataAppl" => {
+            let values: Vec<String> = arguments[1..].iter().map(print_data_expression).collect();
+            format!("{}({})", print_data_expression(&arguments[0]), values.join(", "))
+        }
+        _ => {
+            let values: Vec<String> = arguments.iter().map(print_data_expression).collect();
+            format!("{}({})", name, values.join(", "))
+        }
+    }
+}
+
+/// Returns true iff the term is the constant with the given name.
+pub(crate) fn is_constant<T: BinaryTerm>(term: &T, name: &str) -> bool {
+    term.value().is_none() && term.symbol() == (name.to_string(), 0)
+}
+
+/// Returns true iff the term is a list.
+pub(crate) fn is_list<T: BinaryTerm>(term: &T) -> bool {
+    term.value().is_none() && matches!(term.symbol().0.as_str(), LIST_SYMBOL | EMPTY_LIST_SYMBOL)
+}
+
+/// Returns the elements of the given list.
+fn list_elements<T: BinaryTerm>(list: &T) -> Vec<T> {
+    let mut result = Vec::new();
+    let mut current = list.clone();
+    while is_list(&current) {
+        let mut arguments = current.arguments();
+        if arguments.len() != 2 {
+            break;
+        }
+
+        current = arguments.pop().expect("A list constructor has two arguments");
+        result.push(arguments.pop().expect("A list constructor has two arguments"));
+    }
+
+    result
+}
+
+fn read_term<R: Read, F: BinaryTermFactory>(
+    stream: &mut BinaryATermReader<R, F>,
+    factory: &mut F,
+) -> Result<F::Term, Box<dyn Error>> {
+    Ok(stream.read(factory)?.ok_or(LtsError::UnexpectedEnd())?)
+}
+
+fn read_probabilistic_state<R: Read, F: BinaryTermFactory>(
+    stream: &mut BinaryATermReader<R, F>,
+    factory: &mut F,
+) -> Result<ProbabilisticState<F::Term>, Box<dyn Error>> {
+    let size = stream.read_int(factory)?;
+    if size == 1 {
+        return Ok(ProbabilisticState::Single(stream.read_int(factory)? as StateIndex));
+    }
+
+    let mut states = Vec::with_capacity(size as usize);
+    for _ in 0..size {
+        let state = stream.read_int(factory)? as StateIndex;
+        states.push((state, read_term(stream, factory)?));
+    }
+
+    Ok(ProbabilisticState::Distribution(states))
+}
+
+fn write_probabilistic_state<W: Write, T: BinaryTerm>(
+    stream: &mut BinaryATermWriter<W, T>,
+    state: &ProbabilisticState<T>,
+) -> Result<(), Box<dyn Error>> {
+    match state {
+        ProbabilisticState::Single(state) => {
+            stream.write_int(1)?;
+            stream.write_int(*state as u64)?;
+        }
+        ProbabilisticState::Distribution(states) => {
+            stream.write_int(states.len() as u64)?;
+            for (state, probability) in states {
+                stream.write_int(*state as u64)?;
+                stream.write(probability)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    use crate::io_baf::tests::appl;
+    use crate::io_baf::tests::Factory;
+    use crate::io_baf::tests::Term;
+
+    use test_log::test;
+
+    pub(crate) fn list(elements: &[Term]) -> Term {
+        elements
+            .iter()
+            .rev()
+            .fold(appl(EMPTY_LIST_SYMBOL, &[]), |list, element| {
+                appl(LIST_SYMBOL, &[element.clone(), list])
+            })
+    }
+
+    pub(crate) fn multi_action(actions: &[(&str, &[Term])]) -> Term {
+        let actions: Vec<Term> = actions
+            .iter()
+            .map(|(name, values)| appl("Action", &[appl("ActId", &[appl(name, &[]), list(&[])]), list(values)]))
+            .collect();
+
+        appl(
+            "TimedMultAct",
+            &[list(&actions), appl("OpId", &[appl("@undefined_real", &[])])],
+        )
+    }
+
+    /// Returns the information of an LTS with three states, where a(1) leads to a probabilistic state.
+    fn example(probabilistic: bool) -> (Vec<u8>, LtsInfo<Term>) {
+        let one = appl("OpId", &[appl("1", &[])]);
+        let half = appl(
+            "DataAppl",
+            &[
+                appl("OpId", &[appl("/", &[])]),
+                one.clone(),
+                appl("OpId", &[appl("2", &[])]),
+            ],
+        );
+
+        let tau = multi_action(&[]);
+        let a = multi_action(&[("a", std::slice::from_ref(&one))]);
+        let bc = multi_action(&[("b", &[]), ("c", &[])]);
+
+        let info = LtsInfo {
+            data_specification: appl("DataSpec", &[]),
+            process_parameters: list(&[appl("DataVarId", &[appl("x", &[])])]),
+            action_labels: list(&[]),
+            multi_actions: HashMap::from([
+                ("tau".to_string(), tau.clone()),
+                ("a(1)".to_string(), a.clone()),
+                ("b|c".to_string(), bc.clone()),
+            ]),
+            state_labels: (0..3).map(|i| list(&[Term::Int(i)])).collect(),
+            probabilistic_states: Vec::new(),
+            initial_state: ProbabilisticState::Single(0),
+        };
+
+        let mut buffer = Vec::new();
+        let mut writer = BinaryATermWriter::new(&mut buffer).unwrap();
+        writer.write_constant(LTS_MARK).unwrap();
+        writer.write(&info.data_specification).unwrap();
+        writer.write(&info.process_parameters).unwrap();
+        writer.write(&info.action_labels).unwrap();
+
+        writer.write_constant(TRANSITION_MARK).unwrap();
+        writer.write_int(0).unwrap();
+        writer.write(&tau).unwrap();
+        writer.write_int(1).unwrap();
+
+        if probabilistic {
+            writer.write_constant(PROBABILISTIC_TRANSITION_MARK).unwrap();
+            writer.write_int(1).unwrap();
+            writer.write(&a).unwrap();
+            write_probabilistic_state(
+                &mut writer,
+                &ProbabilisticState::Distribution(vec![(0, half.clone()), (2, half)]),
+            )
+            .unwrap();
+        } else {
+            writer.write_constant(TRANSITION_MARK).unwrap();
+            writer.write_int(1).unwrap();
+            writer.write(&a).unwrap();
+            writer.write_int(2).unwrap();
+        }
+
+        writer.write_constant(TRANSITION_MARK).unwrap();
+        writer.write_int(2).unwrap();
+        writer.write(&bc).unwrap();
+        writer.write_int(0).unwrap();
+
+        for label in &info.state_labels {
+            writer.write(label).unwrap();
+        }
+
+        writer.write_constant(INITIAL_STATE_MARK).unwrap();
+        write_probabilistic_state(&mut writer, &info.initial_state).unwrap();
+        writer.finish().unwrap();
+
+        (buffer, info)
+    }
+
+    #[test]
+    fn test_reading_lts() {
+        let (buffer, expected) = example(false);
+        let (lts, info) = read_lts(&buffer[..], &mut Factory, vec![]).unwrap();
+
+        assert_eq!(lts.initial_state_index(), 0);
+        assert_eq!(lts.num_of_states(), 3);
+        assert_eq!(lts.num_of_transitions(), 3);
+        assert_eq!(lts.labels(), &["tau", "a(1)", "b|c"]);
+        assert_eq!(info.multi_actions, expected.multi_actions);
+        assert_eq!(info.state_labels, expected.state_labels);
+        assert_eq!(info.process_parameters, expected.process_parameters);
+        assert!(info.probabilistic_states.is_empty());
+    }
+
+    #[test]
+    fn test_reading_probabilistic_lts() {
+        let (buffer, _) = example(true);
+        let (lts, info) = read_lts(&buffer[..], &mut Factory, vec![]).unwrap();
+
+        assert_eq!(lts.num_of_transitions(), 3);
+        assert_eq!(info.probabilistic_states.len(), 3);
+
+        // The target of a(1) is the distribution over the states zero and two.
+        let (_, to) = lts.outgoing_transitions(1).next().unwrap();
+        assert_eq!(info.probabilistic_states[to].states().collect::<Vec<_>>(), vec![0, 2]);
+    }
+
+    #[test]
+    fn test_writing_lts() {
+        for probabilistic in [false, true] {
+            let (buffer, _) = example(probabilistic);
+            let (lts_original, info_original) = read_lts(&buffer[..], &mut Factory, vec![]).unwrap();
+
+            // Check that it can be read after writing, and results in the same LTS.
+            let mut buffer: Vec<u8> = Vec::new();
+            write_lts(&mut buffer, &lts_original, &info_original).unwrap();
+
+            let (lts, info) = read_lts(&buffer[..], &mut Factory, vec![]).unwrap();
+            assert!(lts == lts_original);
+            assert_eq!(info.state_labels, info_original.state_labels);
+            assert_eq!(info.probabilistic_states, info_original.probabilistic_states);
+            assert_eq!(info.initial_state, info_original.initial_state);
+        }
+    }
+
+    #[test]
+    fn test_lts_failure() {
+        let mut buffer = Vec::new();
+        let mut writer: BinaryATermWriter<_, Term> = BinaryATermWriter::new(&mut buffer).unwrap();
+        writer.write_constant("linear_process").unwrap();
+        writer.finish().unwrap();
+
+        assert!(read_lts(&buffer[..], &mut Factory, vec![]).is_err());
+    }
+}
@@ -0,0 +1,277 @@
+//! Reading and writing traces, such as counterexamples and witnesses, such
+//! that they can be replayed in the simulator, see [lts::Trace::replay]. Two
+//! formats are supported:
+//!
+//! - the .trc format of the mCRL2 toolset, which is for example produced by
+//!   lps2lts and read by tracepp. The file is a single binary aterm stream,
+//!   see [crate::io_baf], that starts with the constant `mCRL2_trace`
+//!   followed by the states and multi-actions in the order in which they
+//!   occur on the trace. A state is a list of the values of the process
+//!   parameters, and the states are optional.
+//! - a plain textual format with one multi-action per line, as printed by
+//!   [lts::MultiAction]. Empty lines are ignored.
+
+use std::error::Error;
+use std::fs::File;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Read;
+use std::io::Write;
+use std::path::Path;
+
+use thiserror::Error;
+
+use crate::io_baf::BinaryATermReader;
+use crate::io_baf::BinaryATermWriter;
+use crate::io_baf::BinaryTerm;
+use crate::io_baf::BinaryTermFactory;
+use crate::io_baf::PlainTermFactory;
+use crate::io_lts::is_constant;
+use crate::io_lts::is_list;
+use crate::io_lts::multi_action_name;
+use crate::io_lts::LtsInfo;
+use lts::LabelledTransitionSystem;
+use lts::Trace;
+
+/// The constant that marks the start of a trace.
+const TRACE_MARK: &str = "mCRL2_trace";
+
+#[derive(Error, Debug)]
+pub enum TraceError {
+    #[error("The stream does not contain a trace")]
+    InvalidHeader(),
+
+    #[error("Unexpected term {0} in the trace")]
+    UnexpectedTerm(String),
+
+    #[error("There is no multi-action for label {0}")]
+    UnknownLabel(String),
+
+    #[error("There is no label for state {0}")]
+    UnknownState(usize),
+}
+
+/// A trace in the mCRL2 .trc format. The terms are stored as is, so they can
+/// be interpreted by the term library that has read them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TrcTrace<T> {
+    /// The multi-actions of the trace in order.
+    pub multi_actions: Vec<T>,
+
+    /// The label of every state on the trace, which is either empty or has
+    /// one more element than the multi-actions.
+    pub states: Vec<T>,
+}
+
+impl<T: BinaryTerm> TrcTrace<T> {
+    /// Returns the multi-actions of the trace printed by [multi_action_name],
+    /// which are the labels of a labelled transition system read by [crate::io_lts::read_lts].
+    pub fn actions(&self) -> Vec<String> {
+        self.multi_actions.iter().map(multi_action_name).collect()
+    }
+
+    /// Converts a trace of the given labelled transition system, where the
+    /// multi-actions and state labels are taken from the given information.
+    /// The states are only included when the information has state labels.
+    pub fn from_trace(
+        lts: &LabelledTransitionSystem,
+        trace: &Trace,
+        info: &LtsInfo<T>,
+    ) -> Result<TrcTrace<T>, TraceError> {
+        let multi_actions = trace
+            .actions(lts)
+            .into_iter()
+            .map(|name| {
+                info.multi_actions
+                    .get(name)
+                    .cloned()
+                    .ok_or_else(|| TraceError::UnknownLabel(name.to_string()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let states = if info.state_labels.is_empty() {
+            Vec::new()
+        } else {
+            std::iter::once(trace.initial_state())
+                .chain(trace.steps().iter().map(|&(_, to)| to))
+                .map(|state_index| {
+                    info.state_labels
+                        .get(state_index)
+                        .cloned()
+                        .ok_or(TraceError::UnknownState(state_index))
+                })
+                .collect::<Result<Vec<_>, _>>()?
+        };
+
+        Ok(TrcTrace { multi_actions, states })
+    }
+}
+
+/// Loads a trace in the mCRL2 .trc format from the given reader, where the
+/// terms are created by the given factory.
+pub fn read_trc<R, F>(reader: R, factory: &mut F) -> Result<TrcTrace<F::Term>, Box<dyn Error>>
+where
+    R: Read,
+    F: BinaryTermFactory,
+    F::Term: BinaryTerm,
+{
+    let mut stream = BinaryATermReader::new(reader)?;
+
+    let header = stream.read(factory)?.ok_or(TraceError::InvalidHeader())?;
+    if !is_constant(&header, TRACE_MARK) {
+        return Err(TraceError::InvalidHeader().into());
+    }
+
+    let mut multi_actions = Vec::new();
+    let mut states = Vec::new();
+    while let Some(term) = stream.read(factory)? {
+        if is_list(&term) {
+            states.push(term);
+        } else if term.value().is_none() && term.symbol().0 == "TimedMultAct" {
+            multi_actions.push(term);
+        } else {
+            return Err(TraceError::UnexpectedTerm(multi_action_name(&term)).into());
+        }
+    }
+
+    Ok(TrcTrace { multi_actions, states })
+}
+
+/// Writes the trace in the mCRL2 .trc format to the given writer.
+pub fn write_trc<W: Write, T: BinaryTerm>(writer: W, trace: &TrcTrace<T>) -> Result<(), Box<dyn Error>> {
+    let mut stream = BinaryATermWriter::new(writer)?;
+    stream.write_constant(TRACE_MARK)?;
+
+    for (index, multi_action) in trace.multi_actions.iter().enumerate() {
+        if let Some(state) = trace.states.get(index) {
+            stream.write(state)?;
+        }
+        stream.write(multi_action)?;
+    }
+
+    if let Some(state) = trace.states.get(trace.multi_actions.len()) {
+        stream.write(state)?;
+    }
+
+    stream.finish()
+}
+
+/// Loads a trace in the plain textual format from the given reader, and
+/// returns its multi-actions.
+pub fn read_plain_trace<R: Read>(reader: R) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut actions = Vec::new();
+    for line in BufReader::new(reader).lines() {
+        let line = line?;
+        let action = line.trim();
+        if !action.is_empty() {
+            actions.push(action.to_string());
+        }
+    }
+
+    Ok(actions)
+}
+
+/// Writes the actions of the trace of the given labelled transition system in
+/// the plain textual format to the given writer.
+pub fn write_plain_trace<W: Write>(
+    mut writer: W,
+    lts: &LabelledTransitionSystem,
+    trace: &Trace,
+) -> Result<(), Box<dyn Error>> {
+    for action in trace.actions(lts) {
+        writeln!(writer, "{action}")?;
+    }
+
+    Ok(())
+}
+
+/// Loads the multi-actions of the trace in the file at the given path, where
+/// files with the .trc extension are in the mCRL2 format and all other files
+/// in the plain textual format.
+pub fn read_trace_file(path: &Path) -> Result<Vec<String>, Box<dyn Error>> {
+    let file = File::open(path)?;
+    if path.extension().is_some_and(|extension| extension == "trc") {
+        Ok(read_trc(BufReader::new(file), &mut PlainTermFactory)?.actions())
+    } else {
+        read_plain_trace(file)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashMap;
+
+    use crate::io_baf::tests::appl;
+    use crate::io_baf::tests::Factory;
+    use crate::io_baf::tests::Term;
+    use crate::io_lts::tests::list;
+    use crate::io_lts::tests::multi_action;
+    use crate::io_lts::ProbabilisticState;
+    use lts::LtsBuilder;
+
+    use test_log::test;
+
+    #[test]
+    fn test_trc_round_trip() {
+        let mut builder = LtsBuilder::new(vec![]);
+        builder.add_transition(0, "a(1)", 1);
+        builder.add_transition(1, "b|c", 0);
+        let lts = builder.finish(0);
+
+        let one = appl("OpId", &[appl("1", &[])]);
+        let info = LtsInfo {
+            data_specification: appl("DataSpec", &[]),
+            process_parameters: list(&[]),
+            action_labels: list(&[]),
+            multi_actions: HashMap::from([
+                ("tau".to_string(), multi_action(&[])),
+                ("a(1)".to_string(), multi_action(&[("a", std::slice::from_ref(&one))])),
+                ("b|c".to_string(), multi_action(&[("b", &[]), ("c", &[])])),
+            ]),
+            state_labels: (0..2).map(|i| list(&[Term::Int(i)])).collect(),
+            probabilistic_states: Vec::new(),
+            initial_state: ProbabilisticState::Single(0),
+        };
+
+        let trace = Trace::replay(&lts, &["a(1)", "b|c", "a(1)"]).unwrap();
+        let trc = TrcTrace::from_trace(&lts, &trace, &info).unwrap();
+        assert_eq!(trc.states.len(), 4);
+
+        let mut buffer = Vec::new();
+        write_trc(&mut buffer, &trc).unwrap();
+
+        let result = read_trc(&buffer[..], &mut Factory).unwrap();
+        assert_eq!(result, trc);
+        assert_eq!(result.actions(), ["a(1)", "b|c", "a(1)"]);
+        assert_eq!(Trace::replay(&lts, &result.actions()), Ok(trace));
+    }
+
+    #[test]
+    fn test_plain_trace() {
+        let mut builder = LtsBuilder::new(vec![]);
+        builder.add_transition(0, "a", 1);
+        builder.add_transition(1, "tau", 2);
+        let lts = builder.finish(0);
+
+        let trace = Trace::replay(&lts, &["a", "tau"]).unwrap();
+        let mut buffer = Vec::new();
+        write_plain_trace(&mut buffer, &lts, &trace).unwrap();
+        assert_eq!(String::from_utf8(buffer.clone()).unwrap(), "a\ntau\n");
+
+        let actions = read_plain_trace(&b"a\n\n  tau\n"[..]).unwrap();
+        assert_eq!(actions, ["a", "tau"]);
+        assert_eq!(Trace::replay(&lts, &actions), Ok(trace));
+    }
+
+    #[test]
+    fn test_trc_failure() {
+        let mut buffer = Vec::new();
+        let mut writer: BinaryATermWriter<_, Term> = BinaryATermWriter::new(&mut buffer).unwrap();
+        writer.write_constant("labelled_transition_system").unwrap();
+        writer.finish().unwrap();
+
+        assert!(read_trc(&buffer[..], &mut Factory).is_err());
+    }
+}
@@ -1,6 +1,9 @@
 //!
 //! A crate containing IO related functionality. This includes the reading of
-//! .aut (Aldebaran) lts formats, and reading encoded integers.
+//! .aut (Aldebaran), .lts (mCRL2) and .fsm lts formats, exporting them to
+//! GraphML and JSON, traces in the .trc (mCRL2) and plain formats, the binary
+//! aterm format, reading encoded integers and the project files that describe
+//! a verification run.
 //!
 //! This crate does not use unsafe code.
 
@@ -9,5 +12,13 @@
 mod line_iterator;
 mod progress;
 
+pub mod formats;
 pub mod io_aut;
+pub mod io_baf;
+pub mod io_fsm;
+pub mod io_graphml;
+pub mod io_json;
+pub mod io_lts;
+pub mod io_trc;
+pub mod project;
 pub mod u64_variablelength;
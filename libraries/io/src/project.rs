@@ -0,0 +1,201 @@
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use serde::Serialize;
+use thiserror::Error;
+
+/// The current version of the project format.
+pub const PROJECT_VERSION: u32 = 1;
+
+#[derive(Error, Debug)]
+pub enum ProjectError {
+    #[error("Cannot read project {0}: {1}")]
+    Read(PathBuf, std::io::Error),
+
+    #[error("Invalid project {0}: {1}")]
+    Parse(PathBuf, toml::de::Error),
+
+    #[error("Unsupported project version {0}, expected at most {PROJECT_VERSION}")]
+    UnsupportedVersion(u32),
+
+    #[error("Cannot write project: {0}")]
+    Write(#[from] toml::ser::Error),
+}
+
+/// A project describes a complete verification run: the model, the
+/// properties that must be checked, the reduction that is applied to the
+/// state space and the options of the tools. It is stored as a TOML file, for
+/// example:
+///
+/// ```toml
+/// version = 1
+/// model = "abp.lps"
+///
+/// [[property]]
+/// name = "no_deadlock"
+/// formula = "properties/no_deadlock.mcf"
+///
+/// [reduction]
+/// equivalence = "branching-bisim"
+/// tau = ["i"]
+///
+/// [engine]
+/// rewriter = "innermost"
+/// ```
+///
+/// All paths are relative to the directory containing the project file.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Project {
+    /// The version of the format, used to detect projects written by newer versions.
+    #[serde(default = "default_version")]
+    pub version: u32,
+
+    /// The model, either a linear process in the .lps format or an .aut file.
+    pub model: PathBuf,
+
+    /// The properties that must be checked.
+    #[serde(default, rename = "property")]
+    pub properties: Vec<Property>,
+
+    #[serde(default)]
+    pub reduction: Reduction,
+
+    #[serde(default)]
+    pub engine: Engine,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Property {
+    /// A name to identify the property in the results.
+    pub name: String,
+
+    /// The file containing the modal formula.
+    pub formula: PathBuf,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Reduction {
+    /// The equivalence used to reduce the state space.
+    #[serde(default)]
+    pub equivalence: Equivalence,
+
+    /// The actions that are hidden before the reduction.
+    #[serde(default)]
+    pub tau: Vec<String>,
+}
+
+impl Default for Reduction {
+    fn default() -> Self {
+        Reduction {
+            equivalence: Equivalence::BranchingBisim,
+            tau: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Equivalence {
+    None,
+    StrongBisim,
+    #[default]
+    BranchingBisim,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Engine {
+    /// The rewriter that is used to evaluate the data expressions when the
+    /// state space of the model is explored, one of innermost, outermost, lazy
+    /// or jitty. Uses the default of the tool when omitted.
+    pub rewriter: Option<String>,
+
+    /// The directory in which intermediate results are cached, relative to the project.
+    pub workspace: Option<PathBuf>,
+}
+
+fn default_version() -> u32 {
+    PROJECT_VERSION
+}
+
+impl Project {
+    /// Reads the project from the given file, and makes all paths relative to the current directory.
+    pub fn read(path: &Path) -> Result<Project, ProjectError> {
+        let text = fs::read_to_string(path).map_err(|error| ProjectError::Read(path.to_path_buf(), error))?;
+        let mut project = Project::parse(&text).map_err(|error| match error {
+            ProjectError::Parse(_, error) => ProjectError::Parse(path.to_path_buf(), error),
+            error => error,
+        })?;
+
+        let directory = path.parent().unwrap_or(Path::new(""));
+        project.model = directory.join(&project.model);
+        for property in &mut project.properties {
+            property.formula = directory.join(&property.formula);
+        }
+        if let Some(workspace) = &mut project.engine.workspace {
+            *workspace = directory.join(&*workspace);
+        }
+
+        Ok(project)
+    }
+
+    /// Parses a project from the given text, the paths are kept as is.
+    pub fn parse(text: &str) -> Result<Project, ProjectError> {
+        let project: Project = toml::from_str(text).map_err(|error| ProjectError::Parse(PathBuf::new(), error))?;
+        if project.version > PROJECT_VERSION {
+            return Err(ProjectError::UnsupportedVersion(project.version));
+        }
+
+        Ok(project)
+    }
+
+    /// Returns the project in the TOML format.
+    pub fn to_toml(&self) -> Result<String, ProjectError> {
+        Ok(toml::to_string_pretty(self)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use indoc::indoc;
+
+    #[test]
+    fn test_parse_project() {
+        let project = Project::parse(indoc! {r#"
+            model = "abp.lps"
+
+            [[property]]
+            name = "no_deadlock"
+            formula = "no_deadlock.mcf"
+
+            [reduction]
+            equivalence = "strong-bisim"
+            tau = ["i"]
+        "#})
+        .unwrap();
+
+        assert_eq!(project.version, PROJECT_VERSION);
+        assert_eq!(project.model, PathBuf::from("abp.lps"));
+        assert_eq!(project.properties.len(), 1);
+        assert_eq!(project.reduction.equivalence, Equivalence::StrongBisim);
+        assert_eq!(project.reduction.tau, vec!["i".to_string()]);
+        assert_eq!(project.engine, Engine::default());
+
+        // Writing and reading the project should result in the same project.
+        let text = project.to_toml().unwrap();
+        assert_eq!(Project::parse(&text).unwrap(), project);
+    }
+
+    #[test]
+    fn test_invalid_project() {
+        assert!(Project::parse("model = \"abp.lps\"\nunknown = 1").is_err());
+        assert!(Project::parse("version = 1000\nmodel = \"abp.lps\"").is_err());
+    }
+}
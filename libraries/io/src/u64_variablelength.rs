@@ -6,7 +6,7 @@ use bitstream_io::BitRead;
 use bitstream_io::BitReader;
 use bitstream_io::BitWrite;
 use bitstream_io::BitWriter;
-use bitstream_io::LittleEndian;
+use bitstream_io::Endianness;
 
 /// The number of bits needed to represent a value of type T in most significant bit encoding.
 #[allow(unused)]
@@ -21,8 +21,8 @@ pub fn encoding_size<T>() -> usize {
 /// \returns The number of bytes used in the output.
 /// \details Implementation taken from <https://techoverflow.net/2013/01/25/efficiently-encoding-variable-length-integers-in-cc/>
 #[allow(unused)]
-pub fn write_u64_variablelength<W: Write>(
-    stream: &mut BitWriter<W, LittleEndian>,
+pub fn write_u64_variablelength<W: Write, E: Endianness>(
+    stream: &mut BitWriter<W, E>,
     mut value: u64,
 ) -> Result<(), Box<dyn Error>> {
     // While more than 7 bits of data are left, occupy the last output byte
@@ -40,7 +40,7 @@ pub fn write_u64_variablelength<W: Write>(
 
 ///  Decodes an unsigned variable-length integer using the MSB algorithm.
 #[allow(unused)]
-pub fn read_u64_variablelength<R: Read>(stream: &mut BitReader<R, LittleEndian>) -> Result<u64, Box<dyn Error>> {
+pub fn read_u64_variablelength<R: Read, E: Endianness>(stream: &mut BitReader<R, E>) -> Result<u64, Box<dyn Error>> {
     let mut value: u64 = 0;
     for i in 0..encoding_size::<u64>() {
         let byte = stream.read::<u8>(8)?;
@@ -59,18 +59,20 @@ pub fn read_u64_variablelength<R: Read>(stream: &mut BitReader<R, LittleEndian>)
 
 #[cfg(test)]
 mod tests {
+    use bitstream_io::LittleEndian;
+
     use super::*;
 
     #[test]
     fn test_integer_encoding() {
         let mut stream: [u8; 10] = [0; 10];
-        let mut writer = BitWriter::new(&mut stream[0..]);
+        let mut writer = BitWriter::endian(&mut stream[0..], LittleEndian);
 
         let value = 234678;
         write_u64_variablelength(&mut writer, value).unwrap();
         writer.write(32, 0 as u64).unwrap();
 
-        let mut reader = BitReader::new(&stream[0..]);
+        let mut reader = BitReader::endian(&stream[0..], LittleEndian);
         let result = read_u64_variablelength(&mut reader).unwrap();
 
         assert_eq!(result, value);
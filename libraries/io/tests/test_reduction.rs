@@ -1,7 +1,11 @@
+use lts::branching_bisim_constellation;
 use lts::branching_bisim_sigref;
 use lts::branching_bisim_sigref_naive;
+use lts::branching_bisim_sigref_parallel;
+use lts::strong_bisim_paige_tarjan;
 use lts::strong_bisim_sigref;
 use lts::strong_bisim_sigref_naive;
+use lts::strong_bisim_sigref_parallel;
 use test_case::test_case;
 use utilities::Timing;
 
@@ -24,8 +28,15 @@ fn test_strong_bisimilation_reduction(input: &str) {
 
     let reduced = strong_bisim_sigref(&lts, &mut timing);
     let naive_reduced = strong_bisim_sigref_naive(&lts, &mut timing);
+    let parallel_reduced = strong_bisim_sigref_parallel(&lts, &mut timing);
+    let paige_tarjan_reduced = strong_bisim_paige_tarjan(&lts, &mut timing);
 
     assert_eq!(reduced, naive_reduced, "The partitions are not equal");
+    assert_eq!(parallel_reduced, naive_reduced, "The parallel partition is not equal");
+    assert_eq!(
+        paige_tarjan_reduced, naive_reduced,
+        "The Paige-Tarjan partition is not equal"
+    );
 }
 
 #[test_case(include_str!("../../../examples/lts/abp.aut") ; "abp.aut")]
@@ -45,6 +56,13 @@ fn test_branching_bisimilation_reduction(input: &str) {
 
     let reduced = branching_bisim_sigref(&lts, &mut timing);
     let naive_reduced = branching_bisim_sigref_naive(&lts, &mut timing);
+    let parallel_reduced = branching_bisim_sigref_parallel(&lts, &mut timing);
+    let constellation_reduced = branching_bisim_constellation(&lts, &mut timing);
 
     assert_eq!(reduced, naive_reduced, "The partitions are not equal");
+    assert_eq!(parallel_reduced, naive_reduced, "The parallel partition is not equal");
+    assert_eq!(
+        constellation_reduced, naive_reduced,
+        "The constellation partition is not equal"
+    );
 }
@@ -0,0 +1,289 @@
+use ahash::AHashSet;
+use log::debug;
+use log::info;
+
+use mcrl2::data::BoolSort;
+use mcrl2::data::DataExpression;
+use mcrl2::data::DataVariable;
+use mcrl2::lps::ActionSummand;
+use sabre::for_each_instance;
+use sabre::RewriteEngine;
+use sabre::Substitution;
+
+use crate::NextStateError;
+use crate::NextStateGenerator;
+use crate::State;
+
+/// Returns the indices of the tau-summands of the process that are confluent,
+/// which means that every enabled tau-transition of such a summand commutes
+/// with every other enabled transition, similar to lpsconfcheck of mCRL2.
+///
+/// For every instance of the sum variables, a tau-summand `c_t -> tau . P(g_t)`
+/// commutes with a summand `c_s -> a(f_s) . P(g_s)` when, for all values of the
+/// process parameters, `c_t` and `c_s` imply that `c_s(g_t)` and `c_t(g_s)`
+/// hold, `f_s = f_s(g_t)` and `g_s(g_t) = g_t(g_s)`. These formulas are decided
+/// by rewriting them with the process parameters as free variables. This is
+/// sound but incomplete, since the implications only hold when the condition
+/// after the step rewrites to true or to one of the conditions before the step,
+/// and the equalities only hold when both sides have the same normal form.
+/// Summands with sum variables of sorts that cannot be enumerated are never
+/// considered to commute.
+pub fn confluent_summands(generator: &mut NextStateGenerator) -> Vec<usize> {
+    let summands = generator.summands().to_vec();
+    let parameters = generator.parameters().to_vec();
+    let instances: Vec<Option<Vec<Vec<DataExpression>>>> = (0..summands.len())
+        .map(|summand_index| {
+            let domain: Vec<&[DataExpression]> = generator
+                .sum_variable_values(summand_index)?
+                .iter()
+                .map(|values| &values[..])
+                .collect();
+
+            let mut result = Vec::new();
+            for_each_instance(&domain, |instance| {
+                result.push(instance.to_vec());
+                true
+            });
+            Some(result)
+        })
+        .collect();
+
+    let mut prover = Prover {
+        rewriter: generator.rewriter(),
+        parameters: &parameters,
+        true_term: BoolSort::true_term(),
+        false_term: BoolSort::false_term(),
+    };
+
+    let result: Vec<usize> = (0..summands.len())
+        .filter(|&tau_index| {
+            summands[tau_index].actions.is_empty()
+                && (0..summands.len()).all(|other_index| {
+                    let (Some(tau_instances), Some(other_instances)) = (&instances[tau_index], &instances[other_index])
+                    else {
+                        debug!(
+                            "Summand {tau_index} or summand {other_index} has sum variables that cannot be enumerated"
+                        );
+                        return false;
+                    };
+
+                    let commutes = prover.commutes(
+                        &summands[tau_index],
+                        tau_instances,
+                        &summands[other_index],
+                        other_instances,
+                        tau_index == other_index,
+                    );
+
+                    if !commutes {
+                        debug!("Summand {tau_index} does not commute with summand {other_index}");
+                    }
+                    commutes
+                })
+        })
+        .collect();
+
+    info!(
+        "Found {} confluent tau-summands out of {} summands",
+        result.len(),
+        summands.len()
+    );
+    result
+}
+
+/// Returns the state that represents the given state, which is reached by
+/// repeatedly taking the first transition of the given confluent summands.
+/// When these transitions form a cycle, the first state that is visited twice
+/// is the representative.
+///
+/// Since the confluent tau-transitions are inert, the state is branching
+/// bisimilar to its representative. As such, the state space that is explored
+/// from the representatives instead of the states is branching bisimilar, but
+/// it contains fewer states.
+pub fn confluent_representative(
+    generator: &mut NextStateGenerator,
+    state: State,
+    confluent: &[usize],
+) -> Result<State, NextStateError> {
+    let mut visited: AHashSet<State> = AHashSet::new();
+    let mut state = state;
+
+    loop {
+        let mut next = None;
+        for &summand_index in confluent {
+            generator.for_each_summand_transition(summand_index, &state, |transition| {
+                if next.is_none() {
+                    next = Some(transition.target);
+                }
+            })?;
+
+            if next.is_some() {
+                break;
+            }
+        }
+
+        match next {
+            Some(next) if visited.insert(state.clone()) => state = next,
+            _ => return Ok(state),
+        }
+    }
+}
+
+/// Decides whether the transitions of two summands commute by rewriting.
+struct Prover<'a> {
+    rewriter: &'a mut dyn RewriteEngine,
+    parameters: &'a [DataVariable],
+    true_term: DataExpression,
+    false_term: DataExpression,
+}
+
+impl Prover<'_> {
+    /// Returns true iff every instance of the tau-summand commutes with every
+    /// instance of the other summand, where the instances are the values of
+    /// their sum variables. When both summands are the same, the transitions of
+    /// the same instance trivially commute.
+    fn commutes(
+        &mut self,
+        tau: &ActionSummand,
+        tau_instances: &[Vec<DataExpression>],
+        other: &ActionSummand,
+        other_instances: &[Vec<DataExpression>],
+        same_summand: bool,
+    ) -> bool {
+        for tau_instance in tau_instances {
+            let tau_substitution: Substitution = tau
+                .variables
+                .iter()
+                .cloned()
+                .zip(tau_instance.iter().cloned())
+                .collect();
+            let tau_condition = self
+                .rewriter
+                .rewrite_with_substitution(&tau.condition, &tau_substitution);
+            if tau_condition == self.false_term {
+                continue;
+            }
+            let tau_next_state = self.rewrite_all(&tau.next_state, &tau_substitution);
+
+            for other_instance in other_instances {
+                if same_summand && tau_instance == other_instance {
+                    continue;
+                }
+
+                let other_substitution: Substitution = other
+                    .variables
+                    .iter()
+                    .cloned()
+                    .zip(other_instance.iter().cloned())
+                    .collect();
+                let other_condition = self
+                    .rewriter
+                    .rewrite_with_substitution(&other.condition, &other_substitution);
+                if other_condition == self.false_term {
+                    continue;
+                }
+                let other_next_state = self.rewrite_all(&other.next_state, &other_substitution);
+
+                // The substitutions after taking the tau-transition, or the other transition, first.
+                let after_tau = self.after(&tau_next_state, &other_substitution);
+                let after_other = self.after(&other_next_state, &tau_substitution);
+
+                let conditions = [&tau_condition, &other_condition];
+                if !self.implied(&other.condition, &after_tau, &conditions)
+                    || !self.implied(&tau.condition, &after_other, &conditions)
+                {
+                    return false;
+                }
+
+                // The action must be the same after the tau-transition.
+                let mut other_label: Vec<&DataExpression> = other
+                    .actions
+                    .iter()
+                    .flat_map(|action| action.arguments.iter())
+                    .collect();
+                other_label.push(&other.time);
+                for expression in other_label {
+                    if self.rewriter.rewrite_with_substitution(expression, &other_substitution)
+                        != self.rewriter.rewrite_with_substitution(expression, &after_tau)
+                    {
+                        return false;
+                    }
+                }
+
+                if self.rewrite_all(&other.next_state, &after_tau) != self.rewrite_all(&tau.next_state, &after_other) {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+
+    /// Returns true iff the condition rewritten under the substitution is true
+    /// or one of the given conditions, which are assumed to hold.
+    fn implied(
+        &mut self,
+        condition: &DataExpression,
+        substitution: &Substitution,
+        assumptions: &[&DataExpression],
+    ) -> bool {
+        let result = self.rewriter.rewrite_with_substitution(condition, substitution);
+        result == self.true_term || assumptions.iter().any(|assumption| **assumption == result)
+    }
+
+    /// Returns the substitution in which the process parameters are replaced
+    /// by the given next state, extended with the given substitution.
+    fn after(&self, next_state: &[DataExpression], substitution: &Substitution) -> Substitution {
+        self.parameters
+            .iter()
+            .cloned()
+            .zip(next_state.iter().cloned())
+            .chain(substitution.iter().cloned())
+            .collect()
+    }
+
+    fn rewrite_all(&mut self, expressions: &[DataExpression], substitution: &Substitution) -> Vec<DataExpression> {
+        expressions
+            .iter()
+            .map(|expression| self.rewriter.rewrite_with_substitution(expression, substitution))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use mcrl2::aterm::TermPool;
+    use mcrl2::lps::LinearProcessSpecification;
+    use sabre::Strategy;
+
+    use super::*;
+
+    use test_log::test;
+
+    #[test]
+    fn test_confluent_summands() {
+        let lps = LinearProcessSpecification::read("../../examples/lps/abp.lps").unwrap();
+        let tp = Rc::new(RefCell::new(TermPool::new()));
+        let mut generator = NextStateGenerator::new(&lps, tp, Strategy::Outermost).unwrap();
+
+        let confluent = confluent_summands(&mut generator);
+        assert!(confluent
+            .iter()
+            .all(|&summand_index| generator.summands()[summand_index].actions.is_empty()));
+
+        // The representative of a state has no enabled confluent transitions, unless they form a cycle.
+        let initial_state = generator.initial_state();
+        let representative = confluent_representative(&mut generator, initial_state, &confluent).unwrap();
+        let again = confluent_representative(&mut generator, representative.clone(), &confluent).unwrap();
+        let transitions = generator.transitions(&representative).unwrap();
+        assert!(
+            again == representative
+                || transitions
+                    .iter()
+                    .any(|transition| confluent.contains(&transition.summand_index))
+        );
+    }
+}
@@ -0,0 +1,236 @@
+use std::fmt;
+
+use ahash::AHashMap;
+use mcrl2::aterm::ATerm;
+use mcrl2::data::is_data_variable;
+use mcrl2::data::DataExpression;
+use mcrl2::data::DataVariable;
+use mcrl2::lps::LinearProcessSpecification;
+
+/// The process parameters that every summand of a linear process reads and
+/// writes, which determines the variable ordering for symbolic exploration.
+///
+/// A summand reads a parameter when it occurs in its condition, actions or
+/// time, or in the next state of a parameter that it writes. A summand writes
+/// a parameter when its next state is not the parameter itself.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DependencyMatrix {
+    parameters: Vec<DataVariable>,
+    read: Vec<Vec<bool>>,
+    write: Vec<Vec<bool>>,
+}
+
+impl DependencyMatrix {
+    /// Computes the dependencies of the summands of the given linear process.
+    pub fn new(lps: &LinearProcessSpecification) -> DependencyMatrix {
+        let parameters = lps.process_parameters();
+        let indices: AHashMap<ATerm, usize> = parameters
+            .iter()
+            .enumerate()
+            .map(|(index, parameter)| (parameter.clone().into(), index))
+            .collect();
+
+        let add_reads = |expression: &DataExpression, read: &mut [bool]| {
+            for term in expression.iter() {
+                if is_data_variable(&term) {
+                    if let Some(&index) = indices.get(&term.protect()) {
+                        read[index] = true;
+                    }
+                }
+            }
+        };
+
+        let mut matrix = DependencyMatrix {
+            parameters: parameters.clone(),
+            read: Vec::new(),
+            write: Vec::new(),
+        };
+
+        for summand in lps.action_summands() {
+            let mut read = vec![false; parameters.len()];
+            let mut write = vec![false; parameters.len()];
+
+            add_reads(&summand.condition, &mut read);
+            add_reads(&summand.time, &mut read);
+            for action in &summand.actions {
+                for argument in &action.arguments {
+                    add_reads(argument, &mut read);
+                }
+            }
+
+            for (index, (parameter, next)) in parameters.iter().zip(summand.next_state.iter()).enumerate() {
+                if *next != DataExpression::from(parameter.clone()) {
+                    write[index] = true;
+                    add_reads(next, &mut read);
+                }
+            }
+
+            matrix.read.push(read);
+            matrix.write.push(write);
+        }
+
+        matrix
+    }
+
+    pub fn parameters(&self) -> &[DataVariable] {
+        &self.parameters
+    }
+
+    pub fn num_of_summands(&self) -> usize {
+        self.read.len()
+    }
+
+    /// Returns true iff the summand with the given index reads the parameter with the given index.
+    pub fn reads(&self, summand_index: usize, parameter_index: usize) -> bool {
+        self.read[summand_index][parameter_index]
+    }
+
+    /// Returns true iff the summand with the given index writes the parameter with the given index.
+    pub fn writes(&self, summand_index: usize, parameter_index: usize) -> bool {
+        self.write[summand_index][parameter_index]
+    }
+
+    /// Returns true iff the summand with the given index reads or writes the parameter with the given index.
+    pub fn depends(&self, summand_index: usize, parameter_index: usize) -> bool {
+        self.reads(summand_index, parameter_index) || self.writes(summand_index, parameter_index)
+    }
+
+    /// Returns the sum over all summands of the distance between the first and
+    /// last parameter that the summand depends on, where the order contains the
+    /// parameter indices from the first to the last variable. A smaller span
+    /// typically results in smaller decision diagrams for the transition relations.
+    pub fn span(&self, order: &[usize]) -> usize {
+        let mut positions = vec![0; order.len()];
+        for (position, &parameter_index) in order.iter().enumerate() {
+            positions[parameter_index] = position;
+        }
+
+        (0..self.num_of_summands())
+            .map(|summand_index| {
+                let dependent = (0..self.parameters.len())
+                    .filter(|&parameter_index| self.depends(summand_index, parameter_index))
+                    .map(|parameter_index| positions[parameter_index]);
+
+                match (dependent.clone().min(), dependent.max()) {
+                    (Some(min), Some(max)) => max - min,
+                    _ => 0,
+                }
+            })
+            .sum()
+    }
+
+    /// Computes a variable order with the FORCE heuristic of Aloul, Markov and
+    /// Sakallah, where the summands are the hyperedges between the parameters
+    /// that they depend on. Every iteration moves every parameter to the average
+    /// of the centers of gravity of its summands, and the order with the
+    /// smallest [Self::span] is returned.
+    pub fn force_order(&self, max_iterations: usize) -> Vec<usize> {
+        let num_of_parameters = self.parameters.len();
+        let mut order: Vec<usize> = (0..num_of_parameters).collect();
+        let mut best = (self.span(&order), order.clone());
+
+        let hyperedges: Vec<Vec<usize>> = (0..self.num_of_summands())
+            .map(|summand_index| {
+                (0..num_of_parameters)
+                    .filter(|&parameter_index| self.depends(summand_index, parameter_index))
+                    .collect()
+            })
+            .collect();
+
+        for _ in 0..max_iterations {
+            let mut positions = vec![0.0; num_of_parameters];
+            for (position, &parameter_index) in order.iter().enumerate() {
+                positions[parameter_index] = position as f64;
+            }
+
+            // The parameters that no summand depends on keep their position.
+            let mut sums = positions.clone();
+            let mut counts = vec![1usize; num_of_parameters];
+            for edge in hyperedges.iter().filter(|edge| !edge.is_empty()) {
+                let center = edge
+                    .iter()
+                    .map(|&parameter_index| positions[parameter_index])
+                    .sum::<f64>()
+                    / edge.len() as f64;
+                for &parameter_index in edge {
+                    sums[parameter_index] += center;
+                    counts[parameter_index] += 1;
+                }
+            }
+
+            let mut next = order.clone();
+            next.sort_by(|&left, &right| {
+                let left = sums[left] / counts[left] as f64;
+                let right = sums[right] / counts[right] as f64;
+                left.total_cmp(&right)
+            });
+
+            if next == order {
+                break;
+            }
+
+            order = next;
+            let span = self.span(&order);
+            if span < best.0 {
+                best = (span, order.clone());
+            }
+        }
+
+        best.1
+    }
+}
+
+impl fmt::Display for DependencyMatrix {
+    /// Prints a row for every summand, with for every parameter whether it is
+    /// read (r), written (w), both (+) or neither (-) as done by mCRL2.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for summand_index in 0..self.num_of_summands() {
+            if summand_index > 0 {
+                writeln!(f)?;
+            }
+
+            for parameter_index in 0..self.parameters.len() {
+                let symbol = match (
+                    self.reads(summand_index, parameter_index),
+                    self.writes(summand_index, parameter_index),
+                ) {
+                    (true, true) => '+',
+                    (true, false) => 'r',
+                    (false, true) => 'w',
+                    (false, false) => '-',
+                };
+                write!(f, "{symbol}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use test_log::test;
+
+    #[test]
+    fn test_dependency_matrix() {
+        let lps = LinearProcessSpecification::read("../../examples/lps/abp.lps").unwrap();
+        let matrix = DependencyMatrix::new(&lps);
+
+        assert_eq!(matrix.num_of_summands(), lps.action_summands().len());
+        assert_eq!(matrix.parameters().len(), lps.process_parameters().len());
+        assert_eq!(
+            matrix.to_string().lines().count(),
+            matrix.num_of_summands(),
+            "Every summand is printed on a single line"
+        );
+
+        // Every parameter occurs exactly once in the order, which is not worse than the initial order.
+        let order = matrix.force_order(10);
+        let mut sorted = order.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, (0..matrix.parameters().len()).collect::<Vec<_>>());
+        assert!(matrix.span(&order) <= matrix.span(&sorted));
+    }
+}
@@ -0,0 +1,22 @@
+//!
+//! A crate containing the functionality for linear process specifications
+//! that is shared by the exploration tools, such as computing the outgoing
+//! transitions of a state, storing the visited states, detecting confluent
+//! tau-summands, computing statistics and the dependencies of the summands on
+//! the process parameters.
+//!
+//! This crate does not use unsafe code.
+
+#![forbid(unsafe_code)]
+
+mod confluence;
+mod dependencies;
+mod next_state;
+mod statistics;
+mod storage;
+
+pub use confluence::*;
+pub use dependencies::*;
+pub use next_state::*;
+pub use statistics::*;
+pub use storage::*;
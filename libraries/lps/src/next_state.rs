@@ -0,0 +1,537 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use log::debug;
+use thiserror::Error;
+
+use mcrl2::aterm::ATerm;
+use mcrl2::aterm::ATermGlobal;
+use mcrl2::aterm::TermPool;
+use mcrl2::data::BoolSort;
+use mcrl2::data::DataExpression;
+use mcrl2::data::DataVariable;
+use mcrl2::lps::Action;
+use mcrl2::lps::ActionSummand;
+use mcrl2::lps::LinearProcessSpecification;
+use sabre::for_each_instance;
+use sabre::Enumerator;
+use sabre::RewriteEngine;
+use sabre::RewriteSpecification;
+use sabre::SharedRewriteSpecification;
+use sabre::Strategy;
+use sabre::Substitution;
+
+/// A state of a linear process, which is the value of every process parameter.
+pub type State = Vec<DataExpression>;
+
+#[derive(Error, Debug)]
+pub enum NextStateError {
+    #[error("Cannot enumerate the values of sum variable {0} of sort {1}")]
+    InfiniteSort(String, String),
+
+    #[error("The condition {0} rewrites to {1} instead of true or false")]
+    UndecidedCondition(String, String),
+}
+
+/// An outgoing transition of a state, which is generated by an instance of a summand.
+#[derive(Clone, Debug)]
+pub struct Transition {
+    /// The index of the summand in [NextStateGenerator::summands].
+    pub summand_index: usize,
+
+    /// The actions of the multi-action, where the arguments are in normal form.
+    pub actions: Vec<Action>,
+
+    /// The time of the multi-action, which is `@undefined_real` for untimed summands.
+    pub time: DataExpression,
+
+    pub target: State,
+}
+
+impl Transition {
+    /// Returns the label `a(d_0, ..., d_n)|...` of the multi-action, where the
+    /// actions are sorted and the empty multi-action is tau.
+    pub fn label(&self) -> String {
+        if self.actions.is_empty() {
+            return "tau".to_string();
+        }
+
+        let mut result: Vec<String> = self
+            .actions
+            .iter()
+            .map(|action| {
+                if action.arguments.is_empty() {
+                    action.name.clone()
+                } else {
+                    let arguments: Vec<String> = action.arguments.iter().map(|argument| argument.to_string()).collect();
+                    format!("{}({})", action.name, arguments.join(", "))
+                }
+            })
+            .collect();
+
+        result.sort_unstable();
+        result.join("|")
+    }
+
+    /// Returns the term `TimedMultAct([Action(ActId(a, ...), [d_0, ...]), ...], time)`
+    /// that is used to store the multi-action in .lts and .trc files.
+    pub fn multi_action_term(&self, tp: &mut TermPool) -> ATerm {
+        let action_symbol = tp.create_symbol("Action", 2);
+        let actions: Vec<ATerm> = self
+            .actions
+            .iter()
+            .map(|action| {
+                let arguments: Vec<ATerm> = action
+                    .arguments
+                    .iter()
+                    .map(|argument| argument.clone().into())
+                    .collect();
+                let arguments = tp.create_list(&arguments);
+                tp.create(&action_symbol, &[action.label.copy(), arguments.copy()])
+            })
+            .collect();
+
+        let actions = tp.create_list(&actions);
+        let time: ATerm = self.time.clone().into();
+        let symbol = tp.create_symbol("TimedMultAct", 2);
+        tp.create(&symbol, &[actions.copy(), time.copy()])
+    }
+}
+
+/// Computes the outgoing transitions of the states of a linear process, which
+/// is shared by the tools that explore, simulate or analyse its state space.
+///
+/// The data expressions are evaluated by the given rewriter, and the sum
+/// variables of the summands are instantiated with all values of their sort
+/// when these are finite, see [Enumerator]. Otherwise, the sum variables are
+/// instantiated with the solutions of the condition in every state, see
+/// [Enumerator::solutions].
+pub struct NextStateGenerator {
+    rewriter: Box<dyn RewriteEngine>,
+    enumerator: Enumerator,
+    tp: TermPool,
+    parameters: Vec<DataVariable>,
+    initial_state: State,
+    summands: Vec<ActionSummand>,
+
+    /// The values of the sum variables of every summand, which is None when
+    /// one of the sum variables has a sort that cannot be enumerated.
+    domains: Vec<Option<Vec<Vec<DataExpression>>>>,
+
+    true_term: DataExpression,
+    false_term: DataExpression,
+}
+
+impl NextStateGenerator {
+    /// Creates a generator that uses a rewriter with the given strategy for the data specification of the process.
+    pub fn new(
+        lps: &LinearProcessSpecification,
+        tp: Rc<RefCell<TermPool>>,
+        strategy: Strategy,
+    ) -> Result<NextStateGenerator, NextStateError> {
+        let spec = RewriteSpecification::from(lps.data_specification());
+        let enumerator = Enumerator::new(&mut tp.borrow_mut(), &spec);
+        let rewriter = strategy.rewriter(tp, &spec);
+
+        NextStateGenerator::with_rewriter(lps, rewriter, &enumerator)
+    }
+
+    /// Creates a generator for the given shared process, with a rewriter
+    /// that uses the given strategy. This is used by every thread that
+    /// explores the same process, see [SharedLinearProcess]. The set automaton
+    /// of the rewriter is constructed once and shared by all threads.
+    pub fn from_shared(
+        process: &SharedLinearProcess,
+        tp: Rc<RefCell<TermPool>>,
+        strategy: Strategy,
+    ) -> Result<NextStateGenerator, NextStateError> {
+        let spec = process.spec.local();
+        let enumerator = Enumerator::new(&mut tp.borrow_mut(), &spec);
+        let rewriter = strategy.shared_rewriter(tp, &process.spec);
+
+        NextStateGenerator::from_parts(
+            process
+                .parameters
+                .iter()
+                .map(|parameter| parameter.protect().into())
+                .collect(),
+            process
+                .initial_state
+                .iter()
+                .map(|value| value.protect().into())
+                .collect(),
+            process.summands.iter().map(SharedSummand::local).collect(),
+            rewriter,
+            &enumerator,
+        )
+    }
+
+    /// Creates a generator with the given rewriter, where the sum variables
+    /// are instantiated with the values of the given enumerator.
+    pub fn with_rewriter(
+        lps: &LinearProcessSpecification,
+        rewriter: Box<dyn RewriteEngine>,
+        enumerator: &Enumerator,
+    ) -> Result<NextStateGenerator, NextStateError> {
+        NextStateGenerator::from_parts(
+            lps.process_parameters(),
+            lps.initial_state(),
+            lps.action_summands(),
+            rewriter,
+            enumerator,
+        )
+    }
+
+    fn from_parts(
+        parameters: Vec<DataVariable>,
+        initial_state: State,
+        summands: Vec<ActionSummand>,
+        rewriter: Box<dyn RewriteEngine>,
+        enumerator: &Enumerator,
+    ) -> Result<NextStateGenerator, NextStateError> {
+        let domains = summands
+            .iter()
+            .map(|summand| {
+                summand
+                    .variables
+                    .iter()
+                    .map(|variable| {
+                        enumerator
+                            .values(&variable.sort().protect())
+                            .map(|values| values.to_vec())
+                    })
+                    .collect::<Option<Vec<_>>>()
+            })
+            .collect();
+
+        debug!("Created next state generator for {} summands", summands.len());
+        Ok(NextStateGenerator {
+            rewriter,
+            enumerator: enumerator.clone(),
+            tp: TermPool::new(),
+            parameters,
+            initial_state,
+            summands,
+            domains,
+            true_term: BoolSort::true_term(),
+            false_term: BoolSort::false_term(),
+        })
+    }
+
+    /// Returns the process parameters, which determine the order of the values in a [State].
+    pub fn parameters(&self) -> &[DataVariable] {
+        &self.parameters
+    }
+
+    /// Returns the action summands of the process.
+    pub fn summands(&self) -> &[ActionSummand] {
+        &self.summands
+    }
+
+    /// Returns the values of every sum variable of the summand with the given
+    /// index, or None when one of their sorts cannot be enumerated.
+    pub fn sum_variable_values(&self, summand_index: usize) -> Option<&[Vec<DataExpression>]> {
+        self.domains[summand_index].as_deref()
+    }
+
+    /// Returns the rewriter that is used to evaluate the data expressions.
+    pub fn rewriter(&mut self) -> &mut dyn RewriteEngine {
+        self.rewriter.as_mut()
+    }
+
+    /// Returns the initial state, where the values are in normal form.
+    pub fn initial_state(&mut self) -> State {
+        self.initial_state
+            .iter()
+            .map(|value| self.rewriter.rewrite(value.clone()))
+            .collect()
+    }
+
+    /// Returns the outgoing transitions of the given state, ordered by summand.
+    pub fn transitions(&mut self, state: &[DataExpression]) -> Result<Vec<Transition>, NextStateError> {
+        let mut result = Vec::new();
+        self.for_each_transition(state, |transition| result.push(transition))?;
+        Ok(result)
+    }
+
+    /// Calls the function for every outgoing transition of the given state, ordered by summand.
+    pub fn for_each_transition<F>(&mut self, state: &[DataExpression], mut function: F) -> Result<(), NextStateError>
+    where
+        F: FnMut(Transition),
+    {
+        for summand_index in 0..self.summands.len() {
+            self.for_each_summand_transition(summand_index, state, &mut function)?;
+        }
+
+        Ok(())
+    }
+
+    /// Calls the function for every outgoing transition of the given state
+    /// that is generated by the summand with the given index.
+    pub fn for_each_summand_transition<F>(
+        &mut self,
+        summand_index: usize,
+        state: &[DataExpression],
+        mut function: F,
+    ) -> Result<(), NextStateError>
+    where
+        F: FnMut(Transition),
+    {
+        let summand = &self.summands[summand_index];
+        let rewriter = &mut self.rewriter;
+
+        // The parameters are followed by the sum variables of the summand.
+        let mut substitution: Substitution = self.parameters.iter().cloned().zip(state.iter().cloned()).collect();
+        let num_of_parameters = substitution.len();
+
+        let Some(domain) = &self.domains[summand_index] else {
+            // The sum variables are instantiated with the solutions of the condition instead.
+            let condition = rewriter.rewrite_with_substitution(&summand.condition, &substitution);
+            let solutions = self
+                .enumerator
+                .solutions(&mut self.tp, &summand.variables, &condition, |term| {
+                    rewriter.rewrite(term)
+                })
+                .ok_or_else(|| {
+                    let variable = summand
+                        .variables
+                        .iter()
+                        .find(|variable| self.enumerator.values(&variable.sort().protect()).is_none())
+                        .expect("One of the sum variables cannot be enumerated");
+                    NextStateError::InfiniteSort(variable.to_string(), variable.sort().to_string())
+                })?;
+
+            for solution in solutions {
+                substitution.truncate(num_of_parameters);
+                substitution.extend(summand.variables.iter().cloned().zip(solution));
+                function(summand_transition(rewriter, summand_index, summand, &substitution));
+            }
+
+            return Ok(());
+        };
+
+        let domain: Vec<&[DataExpression]> = domain.iter().map(|values| &values[..]).collect();
+        let mut result = Ok(());
+        for_each_instance(&domain, |instance| {
+            substitution.truncate(num_of_parameters);
+            substitution.extend(summand.variables.iter().cloned().zip(instance.iter().cloned()));
+
+            let condition = rewriter.rewrite_with_substitution(&summand.condition, &substitution);
+            if condition == self.false_term {
+                return true;
+            } else if condition != self.true_term {
+                result = Err(NextStateError::UndecidedCondition(
+                    summand.condition.to_string(),
+                    condition.to_string(),
+                ));
+                return false;
+            }
+
+            function(summand_transition(rewriter, summand_index, summand, &substitution));
+            true
+        });
+
+        result
+    }
+}
+
+/// Returns the transition of the given summand, where the process parameters
+/// and sum variables are replaced by their values in the substitution.
+fn summand_transition(
+    rewriter: &mut Box<dyn RewriteEngine>,
+    summand_index: usize,
+    summand: &ActionSummand,
+    substitution: &Substitution,
+) -> Transition {
+    let actions = summand
+        .actions
+        .iter()
+        .map(|action| Action {
+            name: action.name.clone(),
+            label: action.label.clone(),
+            arguments: action
+                .arguments
+                .iter()
+                .map(|argument| rewriter.rewrite_with_substitution(argument, substitution))
+                .collect(),
+        })
+        .collect();
+
+    Transition {
+        summand_index,
+        actions,
+        time: rewriter.rewrite_with_substitution(&summand.time, substitution),
+        target: summand
+            .next_state
+            .iter()
+            .map(|value| rewriter.rewrite_with_substitution(value, substitution))
+            .collect(),
+    }
+}
+
+/// A linear process where all terms are protected on the global protection
+/// set, such that it can be shared between threads. Every thread creates its
+/// own generator using [NextStateGenerator::from_shared], without reading the
+/// specification again.
+#[derive(Clone, Debug)]
+pub struct SharedLinearProcess {
+    spec: SharedRewriteSpecification,
+    parameters: Arc<Vec<ATermGlobal>>,
+    initial_state: Arc<Vec<ATermGlobal>>,
+    summands: Arc<Vec<SharedSummand>>,
+}
+
+impl SharedLinearProcess {
+    /// Returns the linear process of the given specification, with its rewrite specification.
+    pub fn new(lps: &LinearProcessSpecification) -> SharedLinearProcess {
+        SharedLinearProcess {
+            spec: RewriteSpecification::from(lps.data_specification()).share(),
+            parameters: Arc::new(
+                lps.process_parameters()
+                    .iter()
+                    .map(|parameter| parameter.protect_global())
+                    .collect(),
+            ),
+            initial_state: Arc::new(lps.initial_state().iter().map(|value| value.protect_global()).collect()),
+            summands: Arc::new(lps.action_summands().iter().map(SharedSummand::new).collect()),
+        }
+    }
+}
+
+/// An [ActionSummand] where all terms are protected globally.
+#[derive(Debug)]
+struct SharedSummand {
+    variables: Vec<ATermGlobal>,
+    condition: ATermGlobal,
+
+    /// The name, label and arguments of every action.
+    actions: Vec<(String, ATermGlobal, Vec<ATermGlobal>)>,
+    time: ATermGlobal,
+    next_state: Vec<ATermGlobal>,
+}
+
+impl SharedSummand {
+    fn new(summand: &ActionSummand) -> SharedSummand {
+        SharedSummand {
+            variables: summand
+                .variables
+                .iter()
+                .map(|variable| variable.protect_global())
+                .collect(),
+            condition: summand.condition.protect_global(),
+            actions: summand
+                .actions
+                .iter()
+                .map(|action| {
+                    (
+                        action.name.clone(),
+                        action.label.protect_global(),
+                        action
+                            .arguments
+                            .iter()
+                            .map(|argument| argument.protect_global())
+                            .collect(),
+                    )
+                })
+                .collect(),
+            time: summand.time.protect_global(),
+            next_state: summand.next_state.iter().map(|value| value.protect_global()).collect(),
+        }
+    }
+
+    /// Returns the summand with its terms protected by the current thread.
+    fn local(&self) -> ActionSummand {
+        ActionSummand {
+            variables: self
+                .variables
+                .iter()
+                .map(|variable| variable.protect().into())
+                .collect(),
+            condition: self.condition.protect().into(),
+            actions: self
+                .actions
+                .iter()
+                .map(|(name, label, arguments)| Action {
+                    name: name.clone(),
+                    label: label.protect(),
+                    arguments: arguments.iter().map(|argument| argument.protect().into()).collect(),
+                })
+                .collect(),
+            time: self.time.protect().into(),
+            next_state: self.next_state.iter().map(|value| value.protect().into()).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use test_log::test;
+
+    #[test]
+    fn test_next_state_generator() {
+        let lps = LinearProcessSpecification::read("../../examples/lps/abp.lps").unwrap();
+        let tp = Rc::new(RefCell::new(TermPool::new()));
+        let mut generator = NextStateGenerator::new(&lps, tp, Strategy::Outermost).unwrap();
+
+        let initial_state = generator.initial_state();
+        assert_eq!(initial_state.len(), generator.parameters().len());
+
+        let transitions = generator.transitions(&initial_state).unwrap();
+        assert!(!transitions.is_empty());
+
+        for transition in &transitions {
+            assert_eq!(transition.target.len(), initial_state.len());
+            assert!(!transition.label().is_empty());
+
+            // The transitions of a single summand are a subset of all transitions.
+            let summand_transitions = {
+                let mut result = Vec::new();
+                generator
+                    .for_each_summand_transition(transition.summand_index, &initial_state, |transition| {
+                        result.push(transition.target)
+                    })
+                    .unwrap();
+                result
+            };
+            assert!(summand_transitions.contains(&transition.target));
+        }
+    }
+
+    #[test]
+    fn test_shared_linear_process() {
+        let lps = LinearProcessSpecification::read("../../examples/lps/abp.lps").unwrap();
+        let process = SharedLinearProcess::new(&lps);
+
+        let tp = Rc::new(RefCell::new(TermPool::new()));
+        let mut generator = NextStateGenerator::new(&lps, tp, Strategy::Outermost).unwrap();
+        let initial_state = generator.initial_state();
+        let expected: Vec<String> = generator
+            .transitions(&initial_state)
+            .unwrap()
+            .iter()
+            .map(|transition| transition.label())
+            .collect();
+
+        // Every thread creates its own generator for the shared process.
+        std::thread::scope(|s| {
+            for _ in 0..2 {
+                s.spawn(|| {
+                    let tp = Rc::new(RefCell::new(TermPool::new()));
+                    let mut generator = NextStateGenerator::from_shared(&process, tp, Strategy::Outermost).unwrap();
+
+                    let initial_state = generator.initial_state();
+                    let labels: Vec<String> = generator
+                        .transitions(&initial_state)
+                        .unwrap()
+                        .iter()
+                        .map(|transition| transition.label())
+                        .collect();
+                    assert_eq!(labels, expected);
+                });
+            }
+        });
+    }
+}
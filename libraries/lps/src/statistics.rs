@@ -0,0 +1,96 @@
+use std::collections::BTreeSet;
+use std::fmt;
+
+use mcrl2::aterm::ATerm;
+use mcrl2::aterm::ATermList;
+use mcrl2::data::is_data_function_symbol;
+use mcrl2::data::DataExpression;
+use mcrl2::data::DataFunctionSymbolRef;
+use mcrl2::lps::LinearProcessSpecification;
+
+/// Various statistics of a linear process specification.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LpsStatistics {
+    pub num_of_summands: usize,
+
+    /// The number of summands with an empty multi-action.
+    pub num_of_tau_summands: usize,
+
+    /// The total number of sum variables of all summands.
+    pub num_of_sum_variables: usize,
+
+    pub num_of_parameters: usize,
+    pub num_of_action_labels: usize,
+    pub num_of_sorts: usize,
+
+    /// The names of the function symbols that occur in the process, sorted and without duplicates.
+    pub used_functions: Vec<String>,
+}
+
+impl LpsStatistics {
+    /// Computes the statistics of the given linear process specification.
+    pub fn new(lps: &LinearProcessSpecification) -> LpsStatistics {
+        let summands = lps.action_summands();
+        let action_labels: ATermList<ATerm> = lps.action_labels().into();
+
+        let mut used_functions = BTreeSet::new();
+        let mut add_functions = |expression: &DataExpression| {
+            for term in expression.iter() {
+                if is_data_function_symbol(&term) {
+                    used_functions.insert(DataFunctionSymbolRef::from(term).name().to_string());
+                }
+            }
+        };
+
+        lps.initial_state().iter().for_each(&mut add_functions);
+        for summand in &summands {
+            add_functions(&summand.condition);
+            add_functions(&summand.time);
+            summand.next_state.iter().for_each(&mut add_functions);
+            for action in &summand.actions {
+                action.arguments.iter().for_each(&mut add_functions);
+            }
+        }
+
+        LpsStatistics {
+            num_of_summands: summands.len(),
+            num_of_tau_summands: summands.iter().filter(|summand| summand.actions.is_empty()).count(),
+            num_of_sum_variables: summands.iter().map(|summand| summand.variables.len()).sum(),
+            num_of_parameters: lps.process_parameters().len(),
+            num_of_action_labels: action_labels.iter().count(),
+            num_of_sorts: lps.data_specification().sorts().len(),
+            used_functions: used_functions.into_iter().collect(),
+        }
+    }
+}
+
+impl fmt::Display for LpsStatistics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Number of summands: {}", self.num_of_summands)?;
+        writeln!(f, "Number of tau summands: {}", self.num_of_tau_summands)?;
+        writeln!(f, "Number of sum variables: {}", self.num_of_sum_variables)?;
+        writeln!(f, "Number of process parameters: {}", self.num_of_parameters)?;
+        writeln!(f, "Number of declared action labels: {}", self.num_of_action_labels)?;
+        writeln!(f, "Number of sorts: {}", self.num_of_sorts)?;
+        write!(f, "Number of used functions: {}", self.used_functions.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use test_log::test;
+
+    #[test]
+    fn test_lps_statistics() {
+        let lps = LinearProcessSpecification::read("../../examples/lps/abp.lps").unwrap();
+        let statistics = LpsStatistics::new(&lps);
+
+        assert_eq!(statistics.num_of_summands, lps.action_summands().len());
+        assert_eq!(statistics.num_of_parameters, lps.process_parameters().len());
+        assert!(statistics.num_of_tau_summands <= statistics.num_of_summands);
+        assert!(statistics.num_of_action_labels > 0);
+        assert!(statistics.used_functions.windows(2).all(|names| names[0] < names[1]));
+    }
+}
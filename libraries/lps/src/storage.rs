@@ -0,0 +1,587 @@
+use std::cell::RefCell;
+use std::fs;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::hash::Hash;
+use std::io;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+use ahash::AHashMap;
+use ahash::RandomState;
+
+use mcrl2::data::DataExpression;
+
+use crate::State;
+
+/// Stores the states that have been visited during an exploration, where
+/// every state is assigned the next index when it is inserted. The
+/// implementations trade the time to insert and retrieve states for the
+/// memory that is used per state.
+pub trait StateStorage {
+    /// Inserts the state when it has not been visited before, and returns its
+    /// index together with whether it was inserted.
+    fn insert(&mut self, state: &[DataExpression]) -> (usize, bool);
+
+    /// Returns the index of the given state when it has been visited.
+    fn find(&self, state: &[DataExpression]) -> Option<usize>;
+
+    /// Returns the state with the given index.
+    fn get(&self, state_index: usize) -> State;
+
+    /// Returns the number of visited states.
+    fn len(&self) -> usize;
+
+    /// Returns true iff no states have been visited.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Stores every state as a vector in a hash map, which is the fastest but
+/// requires the most memory.
+#[derive(Default)]
+pub struct HashStorage {
+    states: IndexedSet<State>,
+}
+
+impl HashStorage {
+    pub fn new() -> HashStorage {
+        HashStorage::default()
+    }
+}
+
+impl StateStorage for HashStorage {
+    fn insert(&mut self, state: &[DataExpression]) -> (usize, bool) {
+        match self.states.find(state) {
+            Some(state_index) => (state_index, false),
+            None => self.states.insert(state.to_vec()),
+        }
+    }
+
+    fn find(&self, state: &[DataExpression]) -> Option<usize> {
+        self.states.find(state)
+    }
+
+    fn get(&self, state_index: usize) -> State {
+        self.states.get(state_index).clone()
+    }
+
+    fn len(&self) -> usize {
+        self.states.len()
+    }
+}
+
+/// Stores the values of every process parameter in a separate table, and
+/// every state as the vector of the indices of its values in these tables.
+/// Since process parameters typically have few values, the indices are much
+/// smaller than the values themselves.
+pub struct CollapsedStorage {
+    values: Vec<IndexedSet<DataExpression>>,
+    states: IndexedSet<Box<[u32]>>,
+}
+
+impl CollapsedStorage {
+    /// Creates an empty storage for states with the given number of process parameters.
+    pub fn new(num_of_parameters: usize) -> CollapsedStorage {
+        CollapsedStorage {
+            values: (0..num_of_parameters).map(|_| IndexedSet::default()).collect(),
+            states: IndexedSet::default(),
+        }
+    }
+}
+
+impl StateStorage for CollapsedStorage {
+    fn insert(&mut self, state: &[DataExpression]) -> (usize, bool) {
+        debug_assert_eq!(
+            state.len(),
+            self.values.len(),
+            "The state has a value for every parameter"
+        );
+        let indices: Box<[u32]> = state
+            .iter()
+            .zip(self.values.iter_mut())
+            .map(|(value, values)| {
+                let (index, _) = values.insert_ref(value);
+                u32::try_from(index).expect("The number of values of a parameter should fit in 32 bits")
+            })
+            .collect();
+
+        self.states.insert(indices)
+    }
+
+    fn find(&self, state: &[DataExpression]) -> Option<usize> {
+        let indices = state
+            .iter()
+            .zip(self.values.iter())
+            .map(|(value, values)| values.find(value).map(|index| index as u32))
+            .collect::<Option<Box<[u32]>>>()?;
+
+        self.states.find(&indices)
+    }
+
+    fn get(&self, state_index: usize) -> State {
+        self.states
+            .get(state_index)
+            .iter()
+            .zip(self.values.iter())
+            .map(|(&index, values)| values.get(index as usize).clone())
+            .collect()
+    }
+
+    fn len(&self) -> usize {
+        self.states.len()
+    }
+}
+
+/// Stores the states by tree compression, where the state vector is split
+/// recursively into two halves. Every half is identified by an index in a
+/// table of the pairs of indices of its own halves, and the values at the
+/// leaves are indexed as in [CollapsedStorage]. The index of a state is its
+/// index in the table of the root.
+///
+/// States that share a half, which happens often when only few parameters
+/// change per transition, share the entries of that half. This requires the
+/// least memory, but every lookup consists of a hash map lookup per node.
+pub struct TreeStorage {
+    /// The nodes of the tree, where the root is the first node.
+    nodes: Vec<TreeNode>,
+
+    values: Vec<IndexedSet<DataExpression>>,
+    pairs: Vec<IndexedSet<(usize, usize)>>,
+
+    /// Only used when there are no process parameters, in which case there is at most one state.
+    has_empty_state: bool,
+}
+
+/// A node of the tree in [TreeStorage].
+#[derive(Clone, Copy)]
+enum TreeNode {
+    /// The value of the process parameter at the given position.
+    Leaf(usize),
+
+    /// The indices of the left and right children and of the table of pairs.
+    Node(usize, usize, usize),
+}
+
+impl TreeStorage {
+    /// Creates an empty storage for states with the given number of process parameters.
+    pub fn new(num_of_parameters: usize) -> TreeStorage {
+        let mut storage = TreeStorage {
+            nodes: Vec::new(),
+            values: (0..num_of_parameters).map(|_| IndexedSet::default()).collect(),
+            pairs: Vec::new(),
+            has_empty_state: false,
+        };
+
+        if num_of_parameters > 0 {
+            storage.build(0, num_of_parameters);
+        }
+        storage
+    }
+
+    /// Adds the nodes for the parameters in the range [begin, end) and returns the index of its root.
+    fn build(&mut self, begin: usize, end: usize) -> usize {
+        let node_index = self.nodes.len();
+        if end - begin == 1 {
+            self.nodes.push(TreeNode::Leaf(begin));
+        } else {
+            // The children are only known after they have been added.
+            self.nodes.push(TreeNode::Leaf(begin));
+
+            let middle = begin + (end - begin) / 2;
+            let left = self.build(begin, middle);
+            let right = self.build(middle, end);
+
+            self.nodes[node_index] = TreeNode::Node(left, right, self.pairs.len());
+            self.pairs.push(IndexedSet::default());
+        }
+
+        node_index
+    }
+
+    fn insert_node(&mut self, node_index: usize, state: &[DataExpression]) -> (usize, bool) {
+        match self.nodes[node_index] {
+            TreeNode::Leaf(position) => self.values[position].insert_ref(&state[position]),
+            TreeNode::Node(left, right, table) => {
+                let (left, _) = self.insert_node(left, state);
+                let (right, _) = self.insert_node(right, state);
+                self.pairs[table].insert((left, right))
+            }
+        }
+    }
+
+    fn find_node(&self, node_index: usize, state: &[DataExpression]) -> Option<usize> {
+        match self.nodes[node_index] {
+            TreeNode::Leaf(position) => self.values[position].find(&state[position]),
+            TreeNode::Node(left, right, table) => {
+                let left = self.find_node(left, state)?;
+                let right = self.find_node(right, state)?;
+                self.pairs[table].find(&(left, right))
+            }
+        }
+    }
+
+    fn get_node(&self, node_index: usize, index: usize, state: &mut State) {
+        match self.nodes[node_index] {
+            TreeNode::Leaf(position) => state.push(self.values[position].get(index).clone()),
+            TreeNode::Node(left, right, table) => {
+                let &(left_index, right_index) = self.pairs[table].get(index);
+                self.get_node(left, left_index, state);
+                self.get_node(right, right_index, state);
+            }
+        }
+    }
+}
+
+impl StateStorage for TreeStorage {
+    fn insert(&mut self, state: &[DataExpression]) -> (usize, bool) {
+        debug_assert_eq!(
+            state.len(),
+            self.values.len(),
+            "The state has a value for every parameter"
+        );
+        if self.nodes.is_empty() {
+            let inserted = !self.has_empty_state;
+            self.has_empty_state = true;
+            return (0, inserted);
+        }
+
+        self.insert_node(0, state)
+    }
+
+    fn find(&self, state: &[DataExpression]) -> Option<usize> {
+        if self.nodes.is_empty() {
+            return self.has_empty_state.then_some(0);
+        }
+
+        self.find_node(0, state)
+    }
+
+    fn get(&self, state_index: usize) -> State {
+        let mut state = Vec::with_capacity(self.values.len());
+        if !self.nodes.is_empty() {
+            self.get_node(0, state_index, &mut state);
+        }
+
+        state
+    }
+
+    fn len(&self) -> usize {
+        match self.nodes.first() {
+            None => self.has_empty_state as usize,
+            Some(TreeNode::Leaf(position)) => self.values[*position].len(),
+            Some(TreeNode::Node(_, _, table)) => self.pairs[*table].len(),
+        }
+    }
+}
+
+/// The number of states that are buffered before they are written to disk.
+const DISK_BUFFER_SIZE: usize = 1 << 16;
+
+/// Stores the states as in [CollapsedStorage], but the vectors of indices are
+/// written to a file instead of being kept in memory. Only the values of the
+/// process parameters and a hash of every state remain in memory, so a state
+/// is read back from disk to compare it when its hash matches.
+///
+/// The file is removed when the storage is dropped. Since the trait cannot
+/// report errors, failing to read or write the file results in a panic.
+pub struct DiskStorage {
+    values: Vec<IndexedSet<DataExpression>>,
+    hasher: RandomState,
+
+    /// Maps the hash of the indices of a state to the last state with that
+    /// hash, where the earlier states are chained through `collisions`.
+    buckets: AHashMap<u64, usize>,
+    collisions: Vec<usize>,
+
+    path: PathBuf,
+    file: RefCell<File>,
+
+    /// The indices of the states that have not yet been written to the file.
+    buffer: Vec<u32>,
+    num_of_written: usize,
+}
+
+impl DiskStorage {
+    /// Creates an empty storage for states with the given number of process
+    /// parameters, which are written to the file at the given path.
+    pub fn new(num_of_parameters: usize, path: &Path) -> io::Result<DiskStorage> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+
+        Ok(DiskStorage {
+            values: (0..num_of_parameters).map(|_| IndexedSet::default()).collect(),
+            hasher: RandomState::new(),
+            buckets: AHashMap::new(),
+            collisions: Vec::new(),
+            path: path.to_path_buf(),
+            file: RefCell::new(file),
+            buffer: Vec::new(),
+            num_of_written: 0,
+        })
+    }
+
+    /// Returns the index of the state with the given indices of values and their hash.
+    fn find_indices(&self, indices: &[u32], hash: u64) -> Option<usize> {
+        let mut current = self.buckets.get(&hash).copied();
+        while let Some(state_index) = current {
+            if self.read_indices(state_index) == indices {
+                return Some(state_index);
+            }
+
+            current = (self.collisions[state_index] != usize::MAX).then_some(self.collisions[state_index]);
+        }
+
+        None
+    }
+
+    /// Returns the indices of the values of the given state, which are either buffered or read from disk.
+    fn read_indices(&self, state_index: usize) -> Vec<u32> {
+        let length = self.values.len();
+        if state_index >= self.num_of_written {
+            let start = (state_index - self.num_of_written) * length;
+            return self.buffer[start..start + length].to_vec();
+        }
+
+        let mut bytes = vec![0u8; length * 4];
+        let mut file = self.file.borrow_mut();
+        file.seek(SeekFrom::Start((state_index * length * 4) as u64))
+            .and_then(|_| file.read_exact(&mut bytes))
+            .expect("Failed to read a state from disk");
+
+        bytes
+            .chunks_exact(4)
+            .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()))
+            .collect()
+    }
+
+    /// Writes the buffered states to the end of the file.
+    fn flush(&mut self) {
+        let bytes: Vec<u8> = self.buffer.iter().flat_map(|index| index.to_le_bytes()).collect();
+        let file = self.file.get_mut();
+        file.seek(SeekFrom::End(0))
+            .and_then(|_| file.write_all(&bytes))
+            .expect("Failed to write the states to disk");
+
+        self.num_of_written = self.len();
+        self.buffer.clear();
+    }
+}
+
+impl StateStorage for DiskStorage {
+    fn insert(&mut self, state: &[DataExpression]) -> (usize, bool) {
+        debug_assert_eq!(
+            state.len(),
+            self.values.len(),
+            "The state has a value for every parameter"
+        );
+        let indices: Vec<u32> = state
+            .iter()
+            .zip(self.values.iter_mut())
+            .map(|(value, values)| {
+                let (index, _) = values.insert_ref(value);
+                u32::try_from(index).expect("The number of values of a parameter should fit in 32 bits")
+            })
+            .collect();
+
+        let hash = self.hasher.hash_one(&indices);
+        if let Some(state_index) = self.find_indices(&indices, hash) {
+            return (state_index, false);
+        }
+
+        let state_index = self.len();
+        self.collisions
+            .push(self.buckets.insert(hash, state_index).unwrap_or(usize::MAX));
+        self.buffer.extend(indices);
+
+        if self.buffer.len() >= DISK_BUFFER_SIZE * self.values.len().max(1) {
+            self.flush();
+        }
+
+        (state_index, true)
+    }
+
+    fn find(&self, state: &[DataExpression]) -> Option<usize> {
+        let indices = state
+            .iter()
+            .zip(self.values.iter())
+            .map(|(value, values)| values.find(value).map(|index| index as u32))
+            .collect::<Option<Vec<u32>>>()?;
+
+        self.find_indices(&indices, self.hasher.hash_one(&indices))
+    }
+
+    fn get(&self, state_index: usize) -> State {
+        self.read_indices(state_index)
+            .into_iter()
+            .zip(self.values.iter())
+            .map(|(index, values)| values.get(index as usize).clone())
+            .collect()
+    }
+
+    fn len(&self) -> usize {
+        self.collisions.len()
+    }
+}
+
+impl Drop for DiskStorage {
+    fn drop(&mut self) {
+        // The states are only meaningful together with the values that are kept in memory.
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// A set in which every element is assigned the next index when it is inserted.
+struct IndexedSet<T> {
+    elements: Vec<T>,
+    indices: AHashMap<T, usize>,
+}
+
+impl<T> Default for IndexedSet<T> {
+    fn default() -> Self {
+        IndexedSet {
+            elements: Vec::new(),
+            indices: AHashMap::new(),
+        }
+    }
+}
+
+impl<T: Clone + Eq + Hash> IndexedSet<T> {
+    /// Inserts the element when it is not yet in the set, and returns its index together with whether it was inserted.
+    fn insert(&mut self, element: T) -> (usize, bool) {
+        if let Some(&index) = self.indices.get(&element) {
+            return (index, false);
+        }
+
+        let index = self.elements.len();
+        self.elements.push(element.clone());
+        self.indices.insert(element, index);
+        (index, true)
+    }
+
+    /// The same as [Self::insert], but only clones the element when it is inserted.
+    fn insert_ref(&mut self, element: &T) -> (usize, bool) {
+        match self.indices.get(element) {
+            Some(&index) => (index, false),
+            None => self.insert(element.clone()),
+        }
+    }
+
+    fn find<Q>(&self, element: &Q) -> Option<usize>
+    where
+        T: std::borrow::Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.indices.get(element).copied()
+    }
+
+    fn get(&self, index: usize) -> &T {
+        &self.elements[index]
+    }
+
+    fn len(&self) -> usize {
+        self.elements.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use mcrl2::aterm::TermPool;
+    use mcrl2::lps::LinearProcessSpecification;
+    use sabre::Strategy;
+
+    use crate::NextStateGenerator;
+
+    use super::*;
+
+    use test_log::test;
+
+    #[test]
+    fn test_state_storage() {
+        let lps = LinearProcessSpecification::read("../../examples/lps/abp.lps").unwrap();
+        let tp = Rc::new(RefCell::new(TermPool::new()));
+        let mut generator = NextStateGenerator::new(&lps, tp, Strategy::Outermost).unwrap();
+        let num_of_parameters = generator.parameters().len();
+
+        let mut storages: Vec<Box<dyn StateStorage>> = vec![
+            Box::new(HashStorage::new()),
+            Box::new(CollapsedStorage::new(num_of_parameters)),
+            Box::new(TreeStorage::new(num_of_parameters)),
+            Box::new(
+                DiskStorage::new(
+                    num_of_parameters,
+                    &std::env::temp_dir().join("test_state_storage.states"),
+                )
+                .unwrap(),
+            ),
+        ];
+
+        // Explore the state space in breadth first order, where every storage should assign the same indices.
+        let initial_state = generator.initial_state();
+        for storage in &mut storages {
+            assert_eq!(storage.insert(&initial_state), (0, true));
+        }
+
+        let mut state_index = 0;
+        while state_index < storages[0].len() {
+            let state = storages[0].get(state_index);
+            for transition in generator.transitions(&state).unwrap() {
+                let expected = storages[0].insert(&transition.target);
+                for storage in &mut storages[1..] {
+                    assert_eq!(storage.find(&transition.target), (!expected.1).then_some(expected.0));
+                    assert_eq!(storage.insert(&transition.target), expected);
+                }
+            }
+
+            for storage in &storages[1..] {
+                assert_eq!(storage.get(state_index), state);
+            }
+            state_index += 1;
+        }
+
+        assert!(storages.iter().all(|storage| storage.len() == storages[0].len()));
+    }
+
+    #[test]
+    fn test_disk_storage_flush() {
+        let lps = LinearProcessSpecification::read("../../examples/lps/abp.lps").unwrap();
+        let tp = Rc::new(RefCell::new(TermPool::new()));
+        let mut generator = NextStateGenerator::new(&lps, tp, Strategy::Outermost).unwrap();
+        let state = generator.initial_state();
+
+        let path = std::env::temp_dir().join("test_disk_storage_flush.states");
+        let mut storage = DiskStorage::new(state.len(), &path).unwrap();
+        assert_eq!(storage.insert(&state), (0, true));
+
+        // The state must also be found after it has been written to disk.
+        storage.flush();
+        assert_eq!(storage.find(&state), Some(0));
+        assert_eq!(storage.insert(&state), (0, false));
+        assert_eq!(storage.get(0), state);
+
+        drop(storage);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_tree_storage_empty() {
+        let mut storage = TreeStorage::new(0);
+        assert!(storage.is_empty());
+        assert_eq!(storage.insert(&[]), (0, true));
+        assert_eq!(storage.insert(&[]), (0, false));
+        assert_eq!(storage.len(), 1);
+        assert!(storage.get(0).is_empty());
+    }
+}
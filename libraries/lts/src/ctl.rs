@@ -0,0 +1,344 @@
+use std::fmt;
+
+use crate::evaluate_action_formula;
+use crate::ActionFormula;
+use crate::FormulaParseError;
+use crate::IncomingTransitions;
+use crate::LabelledTransitionSystem;
+use crate::Parser;
+
+/// A formula in computation tree logic, where the atomic proposition `{a}`
+/// holds in the states that have an outgoing transition satisfying the action
+/// formula a.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CtlFormula {
+    True,
+    False,
+    Enabled(ActionFormula),
+    Not(Box<CtlFormula>),
+    And(Box<CtlFormula>, Box<CtlFormula>),
+    Or(Box<CtlFormula>, Box<CtlFormula>),
+    Implies(Box<CtlFormula>, Box<CtlFormula>),
+    ExistsNext(Box<CtlFormula>),
+    AllNext(Box<CtlFormula>),
+    ExistsEventually(Box<CtlFormula>),
+    AllEventually(Box<CtlFormula>),
+    ExistsAlways(Box<CtlFormula>),
+    AllAlways(Box<CtlFormula>),
+    ExistsUntil(Box<CtlFormula>, Box<CtlFormula>),
+    AllUntil(Box<CtlFormula>, Box<CtlFormula>),
+}
+
+impl CtlFormula {
+    /// Parses a CTL formula, for example `AG ({send} => AF {receive})`.
+    ///
+    /// The temporal operators are `EX`, `AX`, `EF`, `AF`, `EG`, `AG`,
+    /// `E[f U g]` and `A[f U g]`, and the atomic propositions are action
+    /// formulas between braces.
+    pub fn parse(input: &str) -> Result<CtlFormula, FormulaParseError> {
+        let mut parser = Parser::new(
+            input,
+            &["true", "false", "EX", "AX", "EF", "AF", "EG", "AG", "E", "A", "U"],
+        )?;
+        let formula = parser.ctl_formula()?;
+        parser.finish()?;
+        Ok(formula)
+    }
+}
+
+impl fmt::Display for CtlFormula {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CtlFormula::True => write!(f, "true"),
+            CtlFormula::False => write!(f, "false"),
+            CtlFormula::Enabled(formula) => write!(f, "{{{formula}}}"),
+            CtlFormula::Not(formula) => write!(f, "!{formula}"),
+            CtlFormula::And(left, right) => write!(f, "({left} && {right})"),
+            CtlFormula::Or(left, right) => write!(f, "({left} || {right})"),
+            CtlFormula::Implies(left, right) => write!(f, "({left} => {right})"),
+            CtlFormula::ExistsNext(formula) => write!(f, "EX {formula}"),
+            CtlFormula::AllNext(formula) => write!(f, "AX {formula}"),
+            CtlFormula::ExistsEventually(formula) => write!(f, "EF {formula}"),
+            CtlFormula::AllEventually(formula) => write!(f, "AF {formula}"),
+            CtlFormula::ExistsAlways(formula) => write!(f, "EG {formula}"),
+            CtlFormula::AllAlways(formula) => write!(f, "AG {formula}"),
+            CtlFormula::ExistsUntil(left, right) => write!(f, "E[{left} U {right}]"),
+            CtlFormula::AllUntil(left, right) => write!(f, "A[{left} U {right}]"),
+        }
+    }
+}
+
+/// Returns true iff the given CTL formula holds in the initial state of the LTS.
+pub fn check_ctl(lts: &LabelledTransitionSystem, formula: &CtlFormula) -> bool {
+    evaluate_ctl(lts, formula)[lts.initial_state_index()]
+}
+
+/// Returns for every state of the LTS whether it satisfies the given CTL formula.
+///
+/// The path quantifiers range over the maximal paths, which are either
+/// infinite or end in a deadlock state. As such `AX f` holds in a deadlock
+/// state, and `EG f` holds in a deadlock state that satisfies f.
+pub fn evaluate_ctl(lts: &LabelledTransitionSystem, formula: &CtlFormula) -> Vec<bool> {
+    let incoming = IncomingTransitions::new(lts);
+    evaluate(lts, &incoming, formula)
+}
+
+fn evaluate(lts: &LabelledTransitionSystem, incoming: &IncomingTransitions, formula: &CtlFormula) -> Vec<bool> {
+    let num_of_states = lts.num_of_states();
+
+    match formula {
+        CtlFormula::True => vec![true; num_of_states],
+        CtlFormula::False => vec![false; num_of_states],
+        CtlFormula::Enabled(formula) => {
+            let labels = evaluate_action_formula(lts, formula);
+            lts.iter_states()
+                .map(|state_index| {
+                    lts.outgoing_transitions(state_index)
+                        .any(|(label_index, _)| labels[label_index])
+                })
+                .collect()
+        }
+        CtlFormula::Not(formula) => evaluate(lts, incoming, formula).iter().map(|value| !value).collect(),
+        CtlFormula::And(left, right) => {
+            let right = evaluate(lts, incoming, right);
+            zip_with(evaluate(lts, incoming, left), &right, |left, right| left && right)
+        }
+        CtlFormula::Or(left, right) => {
+            let right = evaluate(lts, incoming, right);
+            zip_with(evaluate(lts, incoming, left), &right, |left, right| left || right)
+        }
+        CtlFormula::Implies(left, right) => {
+            let right = evaluate(lts, incoming, right);
+            zip_with(evaluate(lts, incoming, left), &right, |left, right| !left || right)
+        }
+        CtlFormula::ExistsNext(formula) => exists_next(lts, &evaluate(lts, incoming, formula)),
+        CtlFormula::AllNext(formula) => {
+            let states = evaluate(lts, incoming, formula);
+            lts.iter_states()
+                .map(|state_index| lts.outgoing_transitions(state_index).all(|(_, to)| states[to]))
+                .collect()
+        }
+        CtlFormula::ExistsEventually(formula) => {
+            exists_until(lts, incoming, &vec![true; num_of_states], evaluate(lts, incoming, formula))
+        }
+        CtlFormula::AllEventually(formula) => {
+            all_until(lts, incoming, &vec![true; num_of_states], &evaluate(lts, incoming, formula))
+        }
+        CtlFormula::ExistsAlways(formula) => exists_always(lts, evaluate(lts, incoming, formula)),
+        CtlFormula::AllAlways(formula) => {
+            // AG f is equivalent to !EF !f.
+            let states: Vec<bool> = evaluate(lts, incoming, formula).iter().map(|value| !value).collect();
+            exists_until(lts, incoming, &vec![true; num_of_states], states)
+                .iter()
+                .map(|value| !value)
+                .collect()
+        }
+        CtlFormula::ExistsUntil(left, right) => {
+            let left = evaluate(lts, incoming, left);
+            exists_until(lts, incoming, &left, evaluate(lts, incoming, right))
+        }
+        CtlFormula::AllUntil(left, right) => {
+            let left = evaluate(lts, incoming, left);
+            all_until(lts, incoming, &left, &evaluate(lts, incoming, right))
+        }
+    }
+}
+
+/// Returns the states with a successor in the given set.
+fn exists_next(lts: &LabelledTransitionSystem, states: &[bool]) -> Vec<bool> {
+    lts.iter_states()
+        .map(|state_index| lts.outgoing_transitions(state_index).any(|(_, to)| states[to]))
+        .collect()
+}
+
+/// Computes E[left U right] by a backwards search from the right states through the left states.
+fn exists_until(
+    lts: &LabelledTransitionSystem,
+    incoming: &IncomingTransitions,
+    left: &[bool],
+    mut right: Vec<bool>,
+) -> Vec<bool> {
+    let mut stack: Vec<usize> = lts.iter_states().filter(|&state_index| right[state_index]).collect();
+
+    while let Some(state_index) = stack.pop() {
+        for &(_, from) in incoming.incoming_transitions(state_index) {
+            if !right[from] && left[from] {
+                right[from] = true;
+                stack.push(from);
+            }
+        }
+    }
+
+    right
+}
+
+/// Computes EG states as the greatest fixpoint Z = states && (EX Z || deadlock).
+fn exists_always(lts: &LabelledTransitionSystem, mut states: Vec<bool>) -> Vec<bool> {
+    loop {
+        let next: Vec<bool> = lts
+            .iter_states()
+            .map(|state_index| {
+                let mut outgoing = lts.outgoing_transitions(state_index).peekable();
+                states[state_index] && (outgoing.peek().is_none() || outgoing.any(|(_, to)| states[to]))
+            })
+            .collect();
+
+        if next == states {
+            return states;
+        }
+        states = next;
+    }
+}
+
+/// Computes A[left U right] by counting for every left state the number of
+/// successors that do not satisfy the until yet, such that a state satisfies
+/// it when all its successors do.
+fn all_until(lts: &LabelledTransitionSystem, incoming: &IncomingTransitions, left: &[bool], right: &[bool]) -> Vec<bool> {
+    let mut result = right.to_vec();
+    let mut remaining: Vec<usize> = lts
+        .iter_states()
+        .map(|state_index| lts.outgoing_transitions(state_index).len())
+        .collect();
+    let mut stack: Vec<usize> = lts.iter_states().filter(|&state_index| right[state_index]).collect();
+
+    while let Some(state_index) = stack.pop() {
+        for &(_, from) in incoming.incoming_transitions(state_index) {
+            if !result[from] && left[from] {
+                remaining[from] -= 1;
+                if remaining[from] == 0 {
+                    result[from] = true;
+                    stack.push(from);
+                }
+            }
+        }
+    }
+
+    result
+}
+
+fn zip_with(mut states: Vec<bool>, other: &[bool], function: impl Fn(bool, bool) -> bool) -> Vec<bool> {
+    states
+        .iter_mut()
+        .zip(other)
+        .for_each(|(value, other)| *value = function(*value, *other));
+    states
+}
+
+impl Parser {
+    /// Parses a CTL formula, where the operators are ordered by increasing
+    /// priority: `=>`, `||`, `&&` and finally the prefix operators.
+    fn ctl_formula(&mut self) -> Result<CtlFormula, FormulaParseError> {
+        let left = self.ctl_or()?;
+        if self.next_symbol("=>") {
+            return Ok(CtlFormula::Implies(Box::new(left), Box::new(self.ctl_formula()?)));
+        }
+
+        Ok(left)
+    }
+
+    fn ctl_or(&mut self) -> Result<CtlFormula, FormulaParseError> {
+        let left = self.ctl_and()?;
+        if self.next_symbol("||") {
+            return Ok(CtlFormula::Or(Box::new(left), Box::new(self.ctl_or()?)));
+        }
+
+        Ok(left)
+    }
+
+    fn ctl_and(&mut self) -> Result<CtlFormula, FormulaParseError> {
+        let left = self.ctl_prefix()?;
+        if self.next_symbol("&&") {
+            return Ok(CtlFormula::And(Box::new(left), Box::new(self.ctl_and()?)));
+        }
+
+        Ok(left)
+    }
+
+    fn ctl_prefix(&mut self) -> Result<CtlFormula, FormulaParseError> {
+        if self.next_symbol("!") {
+            return Ok(CtlFormula::Not(Box::new(self.ctl_prefix()?)));
+        } else if self.next_symbol("(") {
+            let formula = self.ctl_formula()?;
+            self.expect(")")?;
+            return Ok(formula);
+        } else if self.next_symbol("{") {
+            let formula = self.action_formula()?;
+            self.expect("}")?;
+            return Ok(CtlFormula::Enabled(formula));
+        }
+
+        match self.next_keyword(&["true", "false", "EX", "AX", "EF", "AF", "EG", "AG", "E", "A"]) {
+            Some("true") => Ok(CtlFormula::True),
+            Some("false") => Ok(CtlFormula::False),
+            Some("EX") => Ok(CtlFormula::ExistsNext(Box::new(self.ctl_prefix()?))),
+            Some("AX") => Ok(CtlFormula::AllNext(Box::new(self.ctl_prefix()?))),
+            Some("EF") => Ok(CtlFormula::ExistsEventually(Box::new(self.ctl_prefix()?))),
+            Some("AF") => Ok(CtlFormula::AllEventually(Box::new(self.ctl_prefix()?))),
+            Some("EG") => Ok(CtlFormula::ExistsAlways(Box::new(self.ctl_prefix()?))),
+            Some("AG") => Ok(CtlFormula::AllAlways(Box::new(self.ctl_prefix()?))),
+            Some(quantifier) => {
+                self.expect("[")?;
+                let left = self.ctl_formula()?;
+                if self.next_keyword(&["U"]).is_none() {
+                    self.expect("U")?;
+                }
+                let right = self.ctl_formula()?;
+                self.expect("]")?;
+
+                if quantifier == "E" {
+                    Ok(CtlFormula::ExistsUntil(Box::new(left), Box::new(right)))
+                } else {
+                    Ok(CtlFormula::AllUntil(Box::new(left), Box::new(right)))
+                }
+            }
+            None => Err(match self.identifier() {
+                Ok(identifier) => FormulaParseError::new(
+                    format!("Unexpected identifier {identifier}, actions must be written between braces"),
+                    0,
+                ),
+                Err(error) => error,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use crate::random_lts;
+    use crate::LtsBuilder;
+
+    use super::*;
+
+    #[test]
+    fn test_check_ctl() {
+        let mut builder = LtsBuilder::new(vec![]);
+        builder.add_transition(0, "send", 1);
+        builder.add_transition(1, "receive", 0);
+        builder.add_transition(1, "error", 2);
+        let lts = builder.finish(0);
+
+        let check = |formula: &str| check_ctl(&lts, &CtlFormula::parse(formula).unwrap());
+        assert!(check("{send} && !{receive}"));
+        assert!(check("AX {receive}"));
+        assert!(check("EF !{true}"));
+        assert!(!check("AF !{true}"));
+        assert!(check("EG {true}"));
+        assert!(!check("AG {true}"));
+        assert!(check("E[{send || receive} U !{true}]"));
+        assert!(!check("A[{send || receive} U !{true}]"));
+        assert!(check("AG ({send} => AX ({receive} && {error}))"));
+    }
+
+    #[test]
+    fn test_random_ctl_dualities() {
+        let lts = random_lts(30, 3, 2);
+
+        let evaluate = |formula: &str| evaluate_ctl(&lts, &CtlFormula::parse(formula).unwrap());
+        assert_eq!(evaluate("AF {a}"), evaluate("!EG !{a}"));
+        assert_eq!(evaluate("AG {b}"), evaluate("!EF !{b}"));
+        assert_eq!(evaluate("EF {a}"), evaluate("E[true U {a}]"));
+        assert_eq!(evaluate("AF {a}"), evaluate("A[true U {a}]"));
+    }
+}
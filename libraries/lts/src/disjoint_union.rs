@@ -0,0 +1,67 @@
+use crate::LabelledTransitionSystem;
+use crate::LtsBuilder;
+use crate::StateIndex;
+
+/// Returns the disjoint union of both labelled transition systems, where the
+/// labels are related by their name, together with the offset of the states
+/// of the right LTS. The initial state is the initial state of the left LTS.
+pub fn disjoint_union(
+    left: &LabelledTransitionSystem,
+    right: &LabelledTransitionSystem,
+) -> (LabelledTransitionSystem, StateIndex) {
+    let mut builder = LtsBuilder::new(vec![]);
+    let offset = left.num_of_states();
+
+    for (lts, offset) in [(left, 0), (right, offset)] {
+        for state_index in lts.iter_states() {
+            for (label_index, to) in lts.outgoing_transitions(state_index) {
+                let label = if lts.is_hidden_label(label_index) {
+                    "tau"
+                } else {
+                    &lts.labels()[label_index]
+                };
+
+                builder.add_transition(state_index + offset, label, to + offset);
+            }
+        }
+    }
+
+    builder.require_num_of_states(offset + right.num_of_states());
+    (builder.finish(left.initial_state_index()), offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::random_lts;
+
+    use super::*;
+
+    #[test]
+    fn test_disjoint_union() {
+        let left = random_lts(10, 3, 3);
+        let right = random_lts(5, 2, 3);
+
+        let (union, offset) = disjoint_union(&left, &right);
+        assert_eq!(offset, 10);
+        assert_eq!(union.num_of_states(), 15);
+        assert_eq!(
+            union.num_of_transitions(),
+            left.num_of_transitions() + right.num_of_transitions()
+        );
+
+        for state_index in right.iter_states() {
+            let mut expected: Vec<(&str, StateIndex)> = right
+                .outgoing_transitions(state_index)
+                .map(|(label, to)| (right.labels()[label].as_str(), to + offset))
+                .collect();
+            let mut result: Vec<(&str, StateIndex)> = union
+                .outgoing_transitions(state_index + offset)
+                .map(|(label, to)| (union.labels()[label].as_str(), to))
+                .collect();
+
+            result.sort_unstable();
+            expected.sort_unstable();
+            assert_eq!(result, expected);
+        }
+    }
+}
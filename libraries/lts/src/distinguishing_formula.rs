@@ -0,0 +1,196 @@
+use std::fmt;
+
+use rustc_hash::FxHashMap;
+
+use crate::disjoint_union;
+use crate::LabelIndex;
+use crate::LabelledTransitionSystem;
+use crate::StateIndex;
+
+/// A formula in Hennessy-Milner logic, where actions are given by their name.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HmlFormula {
+    True,
+    Diamond(String, Box<HmlFormula>),
+    Not(Box<HmlFormula>),
+    And(Vec<HmlFormula>),
+}
+
+impl HmlFormula {
+    /// Returns true iff the formula holds in the given state.
+    pub fn holds(&self, lts: &LabelledTransitionSystem, state_index: StateIndex) -> bool {
+        match self {
+            HmlFormula::True => true,
+            HmlFormula::Diamond(action, formula) => lts
+                .outgoing_transitions(state_index)
+                .any(|(label_index, to)| lts.labels()[label_index] == *action && formula.holds(lts, to)),
+            HmlFormula::Not(formula) => !formula.holds(lts, state_index),
+            HmlFormula::And(formulas) => formulas.iter().all(|formula| formula.holds(lts, state_index)),
+        }
+    }
+}
+
+impl fmt::Display for HmlFormula {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HmlFormula::True => write!(f, "true"),
+            HmlFormula::Diamond(action, formula) => write!(f, "<{action}>{formula}"),
+            HmlFormula::Not(formula) => write!(f, "!{formula}"),
+            HmlFormula::And(formulas) => match formulas.as_slice() {
+                [] => write!(f, "true"),
+                [formula] => write!(f, "{formula}"),
+                formulas => {
+                    write!(f, "(")?;
+                    for (index, formula) in formulas.iter().enumerate() {
+                        if index > 0 {
+                            write!(f, " && ")?;
+                        }
+                        write!(f, "{formula}")?;
+                    }
+                    write!(f, ")")
+                }
+            },
+        }
+    }
+}
+
+/// Returns a Hennessy-Milner formula that holds in the initial state of the
+/// left LTS and not in the initial state of the right LTS, or None iff both
+/// initial states are strongly bisimilar.
+///
+/// The formula is derived from the partitions computed by the rounds of a
+/// naive partition refinement, where two states that are separated in a
+/// round are distinguished by a transition into a block of the previous
+/// round.
+pub fn distinguishing_formula(left: &LabelledTransitionSystem, right: &LabelledTransitionSystem) -> Option<HmlFormula> {
+    let (lts, offset) = disjoint_union(left, right);
+    let rounds = refinement_rounds(&lts);
+
+    let state_index = left.initial_state_index();
+    let other_state_index = right.initial_state_index() + offset;
+    let last = rounds.last().expect("There is at least one round");
+    if last[state_index] == last[other_state_index] {
+        return None;
+    }
+
+    Some(distinguish(&lts, &rounds, state_index, other_state_index))
+}
+
+/// Returns the block numbers of every state for every round of the naive
+/// partition refinement, until the partition is stable.
+fn refinement_rounds(lts: &LabelledTransitionSystem) -> Vec<Vec<usize>> {
+    let mut rounds = vec![vec![0; lts.num_of_states()]];
+    let mut num_of_blocks = 1;
+
+    loop {
+        let previous = rounds.last().expect("There is at least one round");
+        let mut id: FxHashMap<Vec<(usize, usize)>, usize> = FxHashMap::default();
+
+        let next: Vec<usize> = lts
+            .iter_states()
+            .map(|state_index| {
+                let mut signature: Vec<(usize, usize)> = lts
+                    .outgoing_transitions(state_index)
+                    .map(|(label_index, to)| (label_index, previous[to]))
+                    .collect();
+                signature.sort_unstable();
+                signature.dedup();
+
+                let number = id.len();
+                *id.entry(signature).or_insert(number)
+            })
+            .collect();
+
+        if id.len() == num_of_blocks {
+            return rounds;
+        }
+
+        num_of_blocks = id.len();
+        rounds.push(next);
+    }
+}
+
+/// Returns a formula that holds in the first state and not in the second
+/// state, which must be in different blocks in the last round.
+fn distinguish(
+    lts: &LabelledTransitionSystem,
+    rounds: &[Vec<usize>],
+    state_index: StateIndex,
+    other_state_index: StateIndex,
+) -> HmlFormula {
+    // The first round in which the states are separated, which is never the initial round.
+    let round = rounds
+        .iter()
+        .position(|blocks| blocks[state_index] != blocks[other_state_index])
+        .expect("The states should be in different blocks");
+    let previous = &rounds[round - 1];
+
+    // One of the states has a transition into a block that the other state cannot match.
+    let unmatched = |state_index: StateIndex, other_state_index: StateIndex| {
+        lts.outgoing_transitions(state_index).find(|&(label_index, to)| {
+            !lts.outgoing_transitions(other_state_index)
+                .any(|(other_label_index, other_to)| {
+                    other_label_index == label_index && previous[other_to] == previous[to]
+                })
+        })
+    };
+
+    // Every transition with the same label from the other state leads to a different block.
+    let diamond = |label_index: LabelIndex, to: StateIndex, other_state_index: StateIndex| {
+        HmlFormula::Diamond(
+            lts.labels()[label_index].clone(),
+            Box::new(HmlFormula::And(
+                lts.outgoing_transitions(other_state_index)
+                    .filter(|(other_label_index, _)| *other_label_index == label_index)
+                    .map(|(_, other_to)| distinguish(lts, rounds, to, other_to))
+                    .collect(),
+            )),
+        )
+    };
+
+    if let Some((label_index, to)) = unmatched(state_index, other_state_index) {
+        diamond(label_index, to, other_state_index)
+    } else if let Some((label_index, to)) = unmatched(other_state_index, state_index) {
+        HmlFormula::Not(Box::new(diamond(label_index, to, state_index)))
+    } else {
+        unreachable!("States that are separated should have an unmatched transition")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::random_lts;
+    use crate::LtsBuilder;
+
+    use super::*;
+
+    #[test]
+    fn test_distinguishing_formula() {
+        let left = LtsBuilder::from_transitions(&[(0, "a", 1), (1, "b", 2), (0, "a", 3), (3, "c", 4)]).finish(0);
+        let right = LtsBuilder::from_transitions(&[(0, "a", 1), (1, "b", 2), (1, "c", 3)]).finish(0);
+
+        let formula = distinguishing_formula(&left, &right).unwrap();
+        assert!(formula.holds(&left, 0));
+        assert!(!formula.holds(&right, 0));
+        assert_eq!(distinguishing_formula(&left, &left), None);
+    }
+
+    #[test]
+    fn test_random_distinguishing_formula() {
+        for _ in 0..20 {
+            let left = random_lts(10, 3, 3);
+            let right = random_lts(10, 3, 3);
+
+            if let Some(formula) = distinguishing_formula(&left, &right) {
+                assert!(
+                    formula.holds(&left, left.initial_state_index()),
+                    "{formula} should hold in the left LTS"
+                );
+                assert!(
+                    !formula.holds(&right, right.initial_state_index()),
+                    "{formula} should not hold in the right LTS"
+                );
+            }
+        }
+    }
+}
@@ -1,5 +1,7 @@
 use std::fmt;
 
+use crate::StateLabels;
+
 /// The index type for a label.
 pub type LabelIndex = usize;
 
@@ -8,128 +10,162 @@ pub type StateIndex = usize;
 
 /// Represents a labelled transition system consisting of states with directed
 /// labelled edges.
+///
+/// The transitions are stored in a compressed sparse row layout, where the
+/// outgoing transitions of every state are stored consecutively and sorted by
+/// their label and target state. The labels and target states of transitions
+/// are stored as 32 bit indices to save memory, which limits the number of
+/// states and labels to 2^32.
 #[derive(PartialEq, Eq)]
 pub struct LabelledTransitionSystem {
-    states: Vec<State>,
-    transitions: Vec<(LabelIndex, StateIndex)>,
+    /// The offset of the outgoing transitions of every state, followed by the number of transitions.
+    states: Vec<usize>,
+    transitions: Vec<(u32, u32)>,
 
     labels: Vec<String>,
     hidden_labels: Vec<String>,
 
     initial_state: StateIndex,
 
-    num_of_transitions: usize,
+    /// The optional state vectors of every state.
+    state_labels: Option<StateLabels>,
 }
 
 impl LabelledTransitionSystem {
-
     /// Creates a new a labelled transition system with the given transitions, labels, and hidden labels.
-    /// 
+    ///
     /// The initial state is the state with the given index.
     /// num_of_states is the number of states in the LTS, if known. If None then deadlock states without incoming transitions are removed.
     pub fn new<I, F>(
         initial_state: StateIndex,
         num_of_states: Option<usize>,
         transition_iter: F,
-        mut labels: Vec<String>,
+        labels: Vec<String>,
         hidden_labels: Vec<String>,
-    ) -> LabelledTransitionSystem 
-    where F: Fn() -> I,
-          I:Iterator<Item = (StateIndex, LabelIndex, StateIndex)> {
-
-        let mut states = Vec::new();
-        if let Some(num_of_states) = num_of_states {
-            states.resize_with(num_of_states, Default::default);
-        }
-
-        // Count the number of transitions for every state
-        let mut num_of_transitions = 0;
+    ) -> LabelledTransitionSystem
+    where
+        F: Fn() -> I,
+        I: Iterator<Item = (StateIndex, LabelIndex, StateIndex)>,
+    {
+        // Count the number of transitions for every state, where the count of state i is stored at i + 1.
+        let mut states = vec![0; num_of_states.unwrap_or(0) + 1];
         for (from, _, to) in transition_iter() {
             // Ensure that the states vector is large enough.
-            while states.len() <= from.max(to) {
-                states.push(State::default());
+            if states.len() <= from.max(to) + 1 {
+                states.resize(from.max(to) + 2, 0);
             }
 
-            states[from].outgoing_end += 1;
-            num_of_transitions += 1;
+            states[from + 1] += 1;
         }
 
+        assert!(
+            states.len() - 1 <= u32::MAX as usize && labels.len() <= u32::MAX as usize,
+            "The number of states and labels should fit in 32 bits"
+        );
+
         // Track the number of transitions before every state.
-        states.iter_mut().fold(0, |count, state| {
-            let result = count + state.outgoing_end;
-            state.outgoing_start = count;
-            state.outgoing_end = count;
-            result
-        });
-        
-        // Place the transitions, and increment the end for every state.
-        let mut transitions = vec![(0, 0); num_of_transitions];
+        for state_index in 1..states.len() {
+            states[state_index] += states[state_index - 1];
+        }
+
+        let (labels, label_indices) = hide_labels(labels, &hidden_labels);
+
+        // Place the transitions, where all hidden actions are remapped to zero.
+        let mut next = states.clone();
+        let mut transitions = vec![(0, 0); states[states.len() - 1]];
         for (from, label, to) in transition_iter() {
-            transitions[states[from].outgoing_end] = (label, to);
-            states[from].outgoing_end += 1;
+            transitions[next[from]] = (label_indices[label] as u32, to as u32);
+            next[from] += 1;
         }
 
-        // Keep track of which label indexes are hidden labels.
-        let mut hidden_indices: Vec<usize> = Vec::new();
-        for label in &hidden_labels {
-            if let Some(index) = labels.iter().position(|other| other == label) {
-                hidden_indices.push(index);
-            }
+        // Sort the outgoing transitions of every state by their label.
+        for state in states.windows(2) {
+            transitions[state[0]..state[1]].sort_unstable();
         }
-        hidden_indices.sort();
-
-        // Make an implicit tau label the first label.
-        let introduced_tau = if hidden_indices.contains(&0) {
-            labels[0] = "tau".to_string();
-            false
-        } else {
-            labels.insert(0, "tau".to_string());
-            true
-        };
-
-        // Remap all hidden actions to zero.
-        for state in &mut states {
-            for (label, _) in &mut transitions[state.outgoing_start..state.outgoing_end] {
-                if hidden_indices.binary_search(label).is_ok() {
-                    *label = 0;
-                } 
-                else if introduced_tau
-                {
-                    // Remap the zero action to the original first hidden index.
-                    *label += 1;
-                }
-            }
-        } 
 
         LabelledTransitionSystem {
             initial_state,
             labels,
             hidden_labels,
             states,
-            num_of_transitions: transitions.len(),
             transitions,
+            state_labels: None,
+        }
+    }
+
+    /// Creates a labelled transition system from its compressed sparse row
+    /// layout, where `states` contains the offset of the outgoing transitions
+    /// of every state followed by the number of transitions. The outgoing
+    /// transitions of every state must be sorted by label and target state,
+    /// and the labels must be obtained by [hide_labels].
+    pub fn from_csr(
+        initial_state: StateIndex,
+        states: Vec<usize>,
+        transitions: Vec<(u32, u32)>,
+        labels: Vec<String>,
+        hidden_labels: Vec<String>,
+    ) -> LabelledTransitionSystem {
+        assert!(
+            states.len() - 1 <= u32::MAX as usize && labels.len() <= u32::MAX as usize,
+            "The number of states and labels should fit in 32 bits"
+        );
+        assert_eq!(
+            states.last(),
+            Some(&transitions.len()),
+            "The last offset should be the number of transitions"
+        );
+        debug_assert!(
+            states.windows(2).all(|state| transitions[state[0]..state[1]]
+                .windows(2)
+                .all(|pair| pair[0] <= pair[1])),
+            "The outgoing transitions of every state should be sorted"
+        );
+
+        LabelledTransitionSystem {
+            initial_state,
+            labels,
+            hidden_labels,
+            states,
+            transitions,
+            state_labels: None,
         }
     }
 
+    /// Returns the labelled transition system in which the states are labelled by the given state labels.
+    pub fn with_state_labels(mut self, state_labels: StateLabels) -> LabelledTransitionSystem {
+        assert_eq!(
+            state_labels.len(),
+            self.num_of_states(),
+            "Every state should have a state label"
+        );
+
+        self.state_labels = Some(state_labels);
+        self
+    }
+
     /// Returns the index of the initial state
     pub fn initial_state_index(&self) -> StateIndex {
         self.initial_state
     }
 
-    /// Returns the set of outgoing transitions for the given state.
-    pub fn outgoing_transitions(&self, state_index: usize) -> impl Iterator<Item = &(LabelIndex, StateIndex)> {
-        let state = &self.states[state_index];
-        self.transitions[state.outgoing_start..state.outgoing_end].iter()
+    /// Returns the set of outgoing transitions for the given state, sorted by label.
+    pub fn outgoing_transitions(
+        &self,
+        state_index: StateIndex,
+    ) -> impl DoubleEndedIterator<Item = (LabelIndex, StateIndex)> + ExactSizeIterator + '_ {
+        self.transitions[self.states[state_index]..self.states[state_index + 1]]
+            .iter()
+            .map(|&(label, to)| (label as LabelIndex, to as StateIndex))
     }
 
     /// Iterate over all state_index in the labelled transition system
     pub fn iter_states(&self) -> impl Iterator<Item = StateIndex> {
-        0..self.states.len()
+        0..self.num_of_states()
     }
 
     /// Returns the number of states.
     pub fn num_of_states(&self) -> StateIndex {
-        self.states.len()
+        self.states.len() - 1
     }
 
     /// Returns the number of labels.
@@ -139,7 +175,7 @@ impl LabelledTransitionSystem {
 
     /// Returns the number of transitions.
     pub fn num_of_transitions(&self) -> usize {
-        self.num_of_transitions
+        self.transitions.len()
     }
 
     /// Returns the list of labels.
@@ -152,25 +188,62 @@ impl LabelledTransitionSystem {
         &self.hidden_labels[0..]
     }
 
+    /// Returns the state labels, if the states are labelled.
+    pub fn state_labels(&self) -> Option<&StateLabels> {
+        self.state_labels.as_ref()
+    }
+
     /// Returns true iff the given label index is a hidden label.
     pub fn is_hidden_label(&self, label_index: LabelIndex) -> bool {
         label_index == 0
     }
 }
 
-/// A single state in the LTS, containing a vector of outgoing edges.
-#[derive(Clone, Default, PartialEq, Eq)]
-struct State {
-    outgoing_start: usize,
-    outgoing_end: usize,
+/// Returns the labels of a labelled transition system where the first label is
+/// tau, together with the index in these labels of every given label, where the
+/// hidden labels are mapped to tau.
+pub fn hide_labels(mut labels: Vec<String>, hidden_labels: &[String]) -> (Vec<String>, Vec<LabelIndex>) {
+    // Keep track of which label indexes are hidden labels.
+    let mut hidden_indices: Vec<usize> = Vec::new();
+    for label in hidden_labels {
+        if let Some(index) = labels.iter().position(|other| other == label) {
+            hidden_indices.push(index);
+        }
+    }
+    hidden_indices.sort();
+
+    // Make an implicit tau label the first label.
+    let introduced_tau = if hidden_indices.contains(&0) {
+        labels[0] = "tau".to_string();
+        false
+    } else {
+        labels.insert(0, "tau".to_string());
+        true
+    };
+
+    let num_of_labels = if introduced_tau { labels.len() - 1 } else { labels.len() };
+    let label_indices = (0..num_of_labels)
+        .map(|label| {
+            if hidden_indices.binary_search(&label).is_ok() {
+                0
+            } else if introduced_tau {
+                // Remap the zero action to the original first hidden index.
+                label + 1
+            } else {
+                label
+            }
+        })
+        .collect();
+
+    (labels, label_indices)
 }
 
 impl fmt::Display for LabelledTransitionSystem {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         // Print some information about the LTS.
-        writeln!(f, "Number of states: {}", self.states.len())?;
+        writeln!(f, "Number of states: {}", self.num_of_states())?;
         writeln!(f, "Number of action labels: {}", self.labels.len())?;
-        write!(f, "Number of transitions: {}", self.num_of_transitions)
+        write!(f, "Number of transitions: {}", self.num_of_transitions())
     }
 }
 
@@ -181,7 +254,7 @@ impl fmt::Debug for LabelledTransitionSystem {
         writeln!(f, "Hidden labels: {:?}", self.hidden_labels)?;
 
         for state_index in self.iter_states() {
-            for &(label, to) in self.outgoing_transitions(state_index) {
+            for (label, to) in self.outgoing_transitions(state_index) {
                 let label_name = &self.labels[label];
 
                 writeln!(f, "{state_index} --[{label_name}]-> {to}")?;
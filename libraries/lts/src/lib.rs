@@ -6,13 +6,41 @@
 //#![forbid(unsafe_code)]
 
 //mod strong_bisim_partition;
+mod ctl;
+mod disjoint_union;
+mod distinguishing_formula;
 mod incoming_transitions;
 mod labelled_transition_system;
+mod ltl;
+mod lts_builder;
+mod modal_formula;
+mod model_checking;
+mod multi_action;
+mod on_the_fly;
+mod operations;
 mod random_lts;
 mod reduction;
+mod refinement;
+mod simulator;
+mod state_labels;
+mod statistics;
 
 //pub use strong_bisim_partition::*;
+pub use ctl::*;
+pub use disjoint_union::*;
+pub use distinguishing_formula::*;
 pub use incoming_transitions::*;
 pub use labelled_transition_system::*;
+pub use ltl::*;
+pub use lts_builder::*;
+pub use modal_formula::*;
+pub use model_checking::*;
+pub use multi_action::*;
+pub use on_the_fly::*;
+pub use operations::*;
 pub use random_lts::*;
 pub use reduction::*;
+pub use refinement::*;
+pub use simulator::*;
+pub use state_labels::*;
+pub use statistics::*;
@@ -0,0 +1,851 @@
+use std::collections::VecDeque;
+use std::fmt;
+
+use log::debug;
+use rustc_hash::FxHashMap;
+
+use crate::evaluate_action_formula;
+use crate::ActionFormula;
+use crate::FormulaParseError;
+use crate::LabelIndex;
+use crate::LabelledTransitionSystem;
+use crate::Parser;
+use crate::StateIndex;
+use crate::Trace;
+
+/// A formula in linear temporal logic, where the atomic propositions are
+/// action formulas that should hold for the action of the current transition.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LtlFormula {
+    True,
+    False,
+    Action(ActionFormula),
+    Not(Box<LtlFormula>),
+    And(Box<LtlFormula>, Box<LtlFormula>),
+    Or(Box<LtlFormula>, Box<LtlFormula>),
+    Implies(Box<LtlFormula>, Box<LtlFormula>),
+    Next(Box<LtlFormula>),
+    Eventually(Box<LtlFormula>),
+    Always(Box<LtlFormula>),
+    Until(Box<LtlFormula>, Box<LtlFormula>),
+    Release(Box<LtlFormula>, Box<LtlFormula>),
+    WeakUntil(Box<LtlFormula>, Box<LtlFormula>),
+}
+
+impl LtlFormula {
+    /// Parses an LTL formula, for example `G (send => F receive)`.
+    ///
+    /// The temporal operators are `X`, `F`, `G`, `U`, `R` and `W`, and these
+    /// cannot be used as action names. A single action is written by its
+    /// name, and an arbitrary action formula can be written between braces,
+    /// i.e., `{!tau && !error}`.
+    pub fn parse(input: &str) -> Result<LtlFormula, FormulaParseError> {
+        let mut parser = Parser::new(input, &["true", "false", "X", "F", "G", "U", "R", "W"])?;
+        let formula = parser.ltl_formula()?;
+        parser.finish()?;
+        Ok(formula)
+    }
+}
+
+impl fmt::Display for LtlFormula {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LtlFormula::True => write!(f, "true"),
+            LtlFormula::False => write!(f, "false"),
+            LtlFormula::Action(ActionFormula::Action(action)) => write!(f, "{action}"),
+            LtlFormula::Action(formula) => write!(f, "{{{formula}}}"),
+            LtlFormula::Not(formula) => write!(f, "!{formula}"),
+            LtlFormula::And(left, right) => write!(f, "({left} && {right})"),
+            LtlFormula::Or(left, right) => write!(f, "({left} || {right})"),
+            LtlFormula::Implies(left, right) => write!(f, "({left} => {right})"),
+            LtlFormula::Next(formula) => write!(f, "X {formula}"),
+            LtlFormula::Eventually(formula) => write!(f, "F {formula}"),
+            LtlFormula::Always(formula) => write!(f, "G {formula}"),
+            LtlFormula::Until(left, right) => write!(f, "({left} U {right})"),
+            LtlFormula::Release(left, right) => write!(f, "({left} R {right})"),
+            LtlFormula::WeakUntil(left, right) => write!(f, "({left} W {right})"),
+        }
+    }
+}
+
+/// An infinite run of an LTS that consists of a finite prefix followed by a
+/// cycle that is repeated forever. An empty cycle indicates that the run ends
+/// in the deadlock state at the end of the prefix.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Lasso {
+    prefix: Trace,
+    cycle: Vec<(LabelIndex, StateIndex)>,
+}
+
+impl Lasso {
+    /// Returns the path from the initial state to the start of the cycle.
+    pub fn prefix(&self) -> &Trace {
+        &self.prefix
+    }
+
+    /// Returns the transitions of the cycle, which starts and ends in the last state of the prefix.
+    pub fn cycle(&self) -> &[(LabelIndex, StateIndex)] {
+        &self.cycle
+    }
+}
+
+/// Returns true iff every infinite run from the initial state of the LTS satisfies the formula.
+pub fn check_ltl(lts: &LabelledTransitionSystem, formula: &LtlFormula) -> bool {
+    ltl_counterexample(lts, formula).is_none()
+}
+
+/// Returns a run from the initial state of the LTS that violates the given
+/// formula, or None when the formula holds.
+///
+/// The negation of the formula is translated into a generalised Büchi
+/// automaton using the tableau construction of Gerth, Peled, Vardi and
+/// Wolper, after which an accepting strongly connected component is searched
+/// for in the product with the LTS. A deadlock is treated as an infinite
+/// repetition of a step that matches none of the actions of the LTS, so
+/// finite runs are also considered.
+pub fn ltl_counterexample(lts: &LabelledTransitionSystem, formula: &LtlFormula) -> Option<Lasso> {
+    let mut automaton = Automaton::default();
+    let root = automaton.normalize(formula, true);
+
+    let product = Product::new(lts, &mut automaton, root);
+    debug!(
+        "Product with {} automaton states has {} states",
+        automaton.states.len(),
+        product.nodes.len()
+    );
+
+    product.accepting_lasso(&automaton.acceptance_mask())
+}
+
+/// The positive normal form of an LTL formula, where the negations only occur
+/// in the action formulas and the subformulas are shared.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum Node {
+    True,
+    False,
+    Action(ActionFormula),
+    And(usize, usize),
+    Or(usize, usize),
+    Next(usize),
+    Until(usize, usize),
+    Release(usize, usize),
+}
+
+/// A single way to satisfy a set of obligations in the current step.
+struct Cover {
+    /// The action formulas that the current action should satisfy.
+    actions: Vec<usize>,
+
+    /// The automaton state with the obligations for the next step.
+    next: usize,
+
+    /// The untils that are not waiting for their right hand side.
+    fulfilled: AcceptanceSets,
+}
+
+/// A set of acceptance sets of the automaton, where every until operator has
+/// its own acceptance set.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct AcceptanceSets {
+    words: Vec<u64>,
+}
+
+impl AcceptanceSets {
+    /// Returns the set that contains the first `len` acceptance sets.
+    fn all(len: usize) -> AcceptanceSets {
+        let mut words = vec![u64::MAX; len / 64];
+        if len % 64 != 0 {
+            words.push((1 << (len % 64)) - 1);
+        }
+        AcceptanceSets { words }
+    }
+
+    fn insert(&mut self, index: usize) {
+        if self.words.len() <= index / 64 {
+            self.words.resize(index / 64 + 1, 0);
+        }
+        self.words[index / 64] |= 1 << (index % 64);
+    }
+
+    fn union_with(&mut self, other: &AcceptanceSets) {
+        if self.words.len() < other.words.len() {
+            self.words.resize(other.words.len(), 0);
+        }
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            *word |= other;
+        }
+    }
+
+    fn difference_with(&mut self, other: &AcceptanceSets) {
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            *word &= !other;
+        }
+    }
+
+    fn intersects(&self, other: &AcceptanceSets) -> bool {
+        self.words
+            .iter()
+            .zip(&other.words)
+            .any(|(word, other)| word & other != 0)
+    }
+
+    fn is_subset(&self, other: &AcceptanceSets) -> bool {
+        self.words
+            .iter()
+            .enumerate()
+            .all(|(index, word)| word & !other.words.get(index).unwrap_or(&0) == 0)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.words.iter().all(|word| *word == 0)
+    }
+}
+
+/// The generalised Büchi automaton, where every state is a set of obligations
+/// and the transitions are given by the covers of every state.
+#[derive(Default)]
+struct Automaton {
+    nodes: Vec<Node>,
+    node_indices: FxHashMap<Node, usize>,
+
+    /// The acceptance set of every until operator.
+    untils: FxHashMap<usize, usize>,
+
+    states: Vec<Vec<usize>>,
+    state_indices: FxHashMap<Vec<usize>, usize>,
+    covers: Vec<Option<Vec<Cover>>>,
+}
+
+impl Automaton {
+    fn insert(&mut self, node: Node) -> usize {
+        if let Some(index) = self.node_indices.get(&node) {
+            return *index;
+        }
+
+        let index = self.nodes.len();
+        if let Node::Until(_, _) = node {
+            let acceptance = self.untils.len();
+            self.untils.insert(index, acceptance);
+        }
+
+        self.nodes.push(node.clone());
+        self.node_indices.insert(node, index);
+        index
+    }
+
+    /// Returns the positive normal form of the (negated) formula.
+    fn normalize(&mut self, formula: &LtlFormula, negated: bool) -> usize {
+        let node = match formula {
+            LtlFormula::True | LtlFormula::False => {
+                if matches!(formula, LtlFormula::True) != negated {
+                    Node::True
+                } else {
+                    Node::False
+                }
+            }
+            LtlFormula::Action(action) => {
+                if negated {
+                    Node::Action(ActionFormula::Not(Box::new(action.clone())))
+                } else {
+                    Node::Action(action.clone())
+                }
+            }
+            LtlFormula::Not(formula) => return self.normalize(formula, !negated),
+            LtlFormula::And(left, right) | LtlFormula::Or(left, right) => {
+                let left = self.normalize(left, negated);
+                let right = self.normalize(right, negated);
+                if matches!(formula, LtlFormula::And(_, _)) != negated {
+                    Node::And(left, right)
+                } else {
+                    Node::Or(left, right)
+                }
+            }
+            LtlFormula::Implies(left, right) => {
+                let left = self.normalize(left, !negated);
+                let right = self.normalize(right, negated);
+                if negated {
+                    Node::And(left, right)
+                } else {
+                    Node::Or(left, right)
+                }
+            }
+            LtlFormula::Next(formula) => Node::Next(self.normalize(formula, negated)),
+            LtlFormula::Eventually(inner) | LtlFormula::Always(inner) => {
+                // F f is equivalent to true U f, and G f to false R f.
+                let eventually = matches!(formula, LtlFormula::Eventually(_)) != negated;
+                let formula = self.normalize(inner, negated);
+                if eventually {
+                    let left = self.insert(Node::True);
+                    Node::Until(left, formula)
+                } else {
+                    let left = self.insert(Node::False);
+                    Node::Release(left, formula)
+                }
+            }
+            LtlFormula::Until(left, right) | LtlFormula::Release(left, right) => {
+                let left = self.normalize(left, negated);
+                let right = self.normalize(right, negated);
+                if matches!(formula, LtlFormula::Until(_, _)) != negated {
+                    Node::Until(left, right)
+                } else {
+                    Node::Release(left, right)
+                }
+            }
+            LtlFormula::WeakUntil(left, right) => {
+                // f W g is equivalent to g R (g || f), and its negation to !g U (!g && !f).
+                let left = self.normalize(left, negated);
+                let right = self.normalize(right, negated);
+                if negated {
+                    let both = self.insert(Node::And(right, left));
+                    Node::Until(right, both)
+                } else {
+                    let either = self.insert(Node::Or(right, left));
+                    Node::Release(right, either)
+                }
+            }
+        };
+
+        self.insert(node)
+    }
+
+    /// Returns the mask in which every acceptance set is included.
+    fn acceptance_mask(&self) -> AcceptanceSets {
+        AcceptanceSets::all(self.untils.len())
+    }
+
+    /// Returns the index of the automaton state with the given obligations.
+    fn state(&mut self, mut obligations: Vec<usize>) -> usize {
+        obligations.sort_unstable();
+        obligations.dedup();
+
+        if let Some(index) = self.state_indices.get(&obligations) {
+            return *index;
+        }
+
+        let index = self.states.len();
+        self.states.push(obligations.clone());
+        self.state_indices.insert(obligations, index);
+        self.covers.push(None);
+        index
+    }
+
+    /// Returns the covers of the given automaton state, which are computed on demand.
+    fn covers(&mut self, state: usize) -> &[Cover] {
+        if self.covers[state].is_none() {
+            let mut expansions = Vec::new();
+            self.expand(
+                self.states[state].clone(),
+                Vec::new(),
+                Vec::new(),
+                Vec::new(),
+                AcceptanceSets::default(),
+                &mut expansions,
+            );
+
+            let mask = self.acceptance_mask();
+            let covers = expansions
+                .into_iter()
+                .map(|(actions, next, pending)| {
+                    let mut fulfilled = mask.clone();
+                    fulfilled.difference_with(&pending);
+                    Cover {
+                        actions,
+                        next: self.state(next),
+                        fulfilled,
+                    }
+                })
+                .collect();
+            self.covers[state] = Some(covers);
+        }
+
+        self.covers[state].as_deref().unwrap_or_default()
+    }
+
+    /// Splits the obligations in the todo list into the action formulas that
+    /// must hold now and the obligations for the next step, where the
+    /// disjunctions result in multiple expansions. The processed obligations
+    /// are only expanded once per expansion.
+    fn expand(
+        &self,
+        mut todo: Vec<usize>,
+        mut processed: Vec<usize>,
+        mut actions: Vec<usize>,
+        mut next: Vec<usize>,
+        pending: AcceptanceSets,
+        expansions: &mut Vec<(Vec<usize>, Vec<usize>, AcceptanceSets)>,
+    ) {
+        while let Some(index) = todo.pop() {
+            if processed.contains(&index) {
+                continue;
+            }
+            processed.push(index);
+
+            match self.nodes[index] {
+                Node::True => {}
+                Node::False => return,
+                Node::Action(_) => actions.push(index),
+                Node::And(left, right) => {
+                    todo.push(left);
+                    todo.push(right);
+                }
+                Node::Or(left, right) => {
+                    let mut other = todo.clone();
+                    other.push(right);
+                    self.expand(
+                        other,
+                        processed.clone(),
+                        actions.clone(),
+                        next.clone(),
+                        pending.clone(),
+                        expansions,
+                    );
+                    todo.push(left);
+                }
+                Node::Next(formula) => next.push(formula),
+                Node::Until(left, right) => {
+                    // f U g is equivalent to g || (f && X (f U g)), where the second case is pending.
+                    let mut other = todo.clone();
+                    other.push(left);
+                    let mut other_next = next.clone();
+                    other_next.push(index);
+                    let mut other_pending = pending.clone();
+                    other_pending.insert(self.untils[&index]);
+                    self.expand(
+                        other,
+                        processed.clone(),
+                        actions.clone(),
+                        other_next,
+                        other_pending,
+                        expansions,
+                    );
+                    todo.push(right);
+                }
+                Node::Release(left, right) => {
+                    // f R g is equivalent to g && (f || X (f R g)).
+                    let mut other = todo.clone();
+                    other.push(right);
+                    let mut other_next = next.clone();
+                    other_next.push(index);
+                    self.expand(
+                        other,
+                        processed.clone(),
+                        actions.clone(),
+                        other_next,
+                        pending.clone(),
+                        expansions,
+                    );
+                    todo.push(left);
+                    todo.push(right);
+                }
+            }
+        }
+
+        expansions.push((actions, next, pending));
+    }
+}
+
+/// An edge in the product of the LTS and the automaton, where the label is
+/// None for the step that is repeated in a deadlock.
+struct Edge {
+    label: Option<LabelIndex>,
+    to: usize,
+    fulfilled: AcceptanceSets,
+}
+
+/// The reachable part of the product of the LTS and the automaton.
+struct Product {
+    /// The LTS state and the automaton state of every node.
+    nodes: Vec<(StateIndex, usize)>,
+    edges: Vec<Vec<Edge>>,
+}
+
+impl Product {
+    fn new(lts: &LabelledTransitionSystem, automaton: &mut Automaton, root: usize) -> Product {
+        // Determine for every action formula which labels, and whether the deadlock step, satisfy it.
+        let mut actions: FxHashMap<usize, (Vec<bool>, bool)> = FxHashMap::default();
+        for (index, node) in automaton.nodes.iter().enumerate() {
+            if let Node::Action(formula) = node {
+                actions.insert(
+                    index,
+                    (evaluate_action_formula(lts, formula), matches_deadlock(formula)),
+                );
+            }
+        }
+
+        let initial = automaton.state(vec![root]);
+        let mut nodes = vec![(lts.initial_state_index(), initial)];
+        let mut node_indices: FxHashMap<(StateIndex, usize), usize> = FxHashMap::default();
+        node_indices.insert(nodes[0], 0);
+        let mut edges = Vec::new();
+
+        let mut index = 0;
+        while index < nodes.len() {
+            let (state_index, state) = nodes[index];
+            let mut transitions: Vec<(Option<LabelIndex>, StateIndex)> = lts
+                .outgoing_transitions(state_index)
+                .map(|(label_index, to)| (Some(label_index), to))
+                .collect();
+            if transitions.is_empty() {
+                transitions.push((None, state_index));
+            }
+
+            let mut outgoing = Vec::new();
+            for cover in automaton.covers(state) {
+                for &(label, to) in &transitions {
+                    let enabled = cover.actions.iter().all(|action| {
+                        let (labels, deadlock) = &actions[action];
+                        label.map_or(*deadlock, |label_index| labels[label_index])
+                    });
+
+                    if enabled {
+                        let target = *node_indices.entry((to, cover.next)).or_insert_with(|| {
+                            nodes.push((to, cover.next));
+                            nodes.len() - 1
+                        });
+
+                        outgoing.push(Edge {
+                            label,
+                            to: target,
+                            fulfilled: cover.fulfilled.clone(),
+                        });
+                    }
+                }
+            }
+
+            // The new automaton states of the covers must also be expanded.
+            edges.push(outgoing);
+            index += 1;
+        }
+
+        Product { nodes, edges }
+    }
+
+    /// Returns a lasso that visits every acceptance set infinitely often, or None if it does not exist.
+    fn accepting_lasso(&self, mask: &AcceptanceSets) -> Option<Lasso> {
+        let components = self.strongly_connected_components();
+        let num_of_components = components.iter().map(|component| component + 1).max().unwrap_or(0);
+
+        // Determine the acceptance sets that are visited by the edges within every component.
+        let mut fulfilled = vec![AcceptanceSets::default(); num_of_components];
+        let mut entries = vec![None; num_of_components];
+        for (node, edges) in self.edges.iter().enumerate() {
+            for edge in edges.iter().filter(|edge| components[edge.to] == components[node]) {
+                fulfilled[components[node]].union_with(&edge.fulfilled);
+                entries[components[node]].get_or_insert(node);
+            }
+        }
+
+        let component = (0..num_of_components)
+            .find(|&component| entries[component].is_some() && mask.is_subset(&fulfilled[component]))?;
+        let entry = entries[component]?;
+        let in_component = |node: usize| components[node] == component;
+
+        let prefix = if entry == 0 {
+            Vec::new()
+        } else {
+            self.find_path(0, |_| true, |edge| edge.to == entry)
+                .expect("Every node is reachable from the initial node")
+        };
+
+        // Visit every acceptance set once, starting with an arbitrary edge to make the cycle non-empty.
+        let mut cycle = Vec::new();
+        let mut current = entry;
+        let mut remaining = mask.clone();
+        loop {
+            let path = self
+                .find_path(current, in_component, |edge| {
+                    in_component(edge.to) && (remaining.is_empty() || edge.fulfilled.intersects(&remaining))
+                })
+                .expect("The acceptance sets are reachable within the component");
+
+            for &(node, edge_index) in &path {
+                remaining.difference_with(&self.edges[node][edge_index].fulfilled);
+            }
+            current = path
+                .last()
+                .map_or(current, |&(node, edge_index)| self.edges[node][edge_index].to);
+            cycle.extend(path);
+
+            if remaining.is_empty() {
+                break;
+            }
+        }
+
+        if current != entry {
+            cycle.extend(
+                self.find_path(current, in_component, |edge| edge.to == entry)
+                    .expect("The entry is reachable within the component"),
+            );
+        }
+
+        let mut trace = Trace::new(self.nodes[0].0);
+        for (label, to) in self.steps(&prefix) {
+            trace.push(label, to);
+        }
+
+        Some(Lasso {
+            prefix: trace,
+            cycle: self.steps(&cycle).collect(),
+        })
+    }
+
+    /// Returns the LTS transitions of the given edges, where the deadlock steps are omitted.
+    fn steps<'a>(&'a self, path: &'a [(usize, usize)]) -> impl Iterator<Item = (LabelIndex, StateIndex)> + 'a {
+        path.iter().filter_map(|&(node, edge_index)| {
+            let edge = &self.edges[node][edge_index];
+            edge.label.map(|label_index| (label_index, self.nodes[edge.to].0))
+        })
+    }
+
+    /// Returns the shortest path of (node, edge index) pairs from the given
+    /// node through the nodes in scope that ends with an edge satisfying the
+    /// target, or None if no such path exists.
+    fn find_path(
+        &self,
+        from: usize,
+        in_scope: impl Fn(usize) -> bool,
+        target: impl Fn(&Edge) -> bool,
+    ) -> Option<Vec<(usize, usize)>> {
+        let mut parent: FxHashMap<usize, (usize, usize)> = FxHashMap::default();
+        let mut queue = VecDeque::from([from]);
+
+        while let Some(node) = queue.pop_front() {
+            for (edge_index, edge) in self.edges[node].iter().enumerate() {
+                if target(edge) {
+                    // Reconstruct the path by following the parents back to the start.
+                    let mut path = vec![(node, edge_index)];
+                    let mut current = node;
+                    while current != from {
+                        let step = parent[&current];
+                        path.push(step);
+                        current = step.0;
+                    }
+
+                    path.reverse();
+                    return Some(path);
+                }
+
+                if edge.to != from && in_scope(edge.to) && !parent.contains_key(&edge.to) {
+                    parent.insert(edge.to, (node, edge_index));
+                    queue.push_back(edge.to);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Returns the strongly connected component of every node, computed by
+    /// an iterative version of Tarjan's algorithm.
+    fn strongly_connected_components(&self) -> Vec<usize> {
+        let num_of_nodes = self.nodes.len();
+        let mut index = vec![usize::MAX; num_of_nodes];
+        let mut lowlink = vec![0; num_of_nodes];
+        let mut on_stack = vec![false; num_of_nodes];
+        let mut components = vec![usize::MAX; num_of_nodes];
+
+        let mut stack = Vec::new();
+        let mut next_index = 0;
+        let mut next_component = 0;
+
+        for root in 0..num_of_nodes {
+            if index[root] != usize::MAX {
+                continue;
+            }
+
+            // The depth first search stack of (node, next edge index) pairs.
+            let mut work = vec![(root, 0)];
+            while let Some((node, edge_index)) = work.pop() {
+                if edge_index == 0 {
+                    index[node] = next_index;
+                    lowlink[node] = next_index;
+                    next_index += 1;
+                    stack.push(node);
+                    on_stack[node] = true;
+                } else {
+                    // Returned from the successor of the previous edge.
+                    let to = self.edges[node][edge_index - 1].to;
+                    lowlink[node] = lowlink[node].min(lowlink[to]);
+                }
+
+                let mut recurse = false;
+                for (offset, edge) in self.edges[node][edge_index..].iter().enumerate() {
+                    if index[edge.to] == usize::MAX {
+                        work.push((node, edge_index + offset + 1));
+                        work.push((edge.to, 0));
+                        recurse = true;
+                        break;
+                    } else if on_stack[edge.to] {
+                        lowlink[node] = lowlink[node].min(index[edge.to]);
+                    }
+                }
+
+                if !recurse && lowlink[node] == index[node] {
+                    while let Some(other) = stack.pop() {
+                        on_stack[other] = false;
+                        components[other] = next_component;
+                        if other == node {
+                            break;
+                        }
+                    }
+                    next_component += 1;
+                }
+            }
+        }
+
+        components
+    }
+}
+
+/// Returns true iff the step that is repeated in a deadlock satisfies the
+/// action formula, which matches none of the actions.
+fn matches_deadlock(formula: &ActionFormula) -> bool {
+    match formula {
+        ActionFormula::True => true,
+        ActionFormula::False | ActionFormula::Action(_) => false,
+        ActionFormula::Not(formula) => !matches_deadlock(formula),
+        ActionFormula::And(left, right) => matches_deadlock(left) && matches_deadlock(right),
+        ActionFormula::Or(left, right) => matches_deadlock(left) || matches_deadlock(right),
+        ActionFormula::Implies(left, right) => !matches_deadlock(left) || matches_deadlock(right),
+    }
+}
+
+impl Parser {
+    /// Parses an LTL formula, where the operators are ordered by increasing
+    /// priority: `=>`, `||`, `&&`, the binary temporal operators and finally
+    /// the prefix operators.
+    fn ltl_formula(&mut self) -> Result<LtlFormula, FormulaParseError> {
+        let left = self.ltl_or()?;
+        if self.next_symbol("=>") {
+            return Ok(LtlFormula::Implies(Box::new(left), Box::new(self.ltl_formula()?)));
+        }
+
+        Ok(left)
+    }
+
+    fn ltl_or(&mut self) -> Result<LtlFormula, FormulaParseError> {
+        let left = self.ltl_and()?;
+        if self.next_symbol("||") {
+            return Ok(LtlFormula::Or(Box::new(left), Box::new(self.ltl_or()?)));
+        }
+
+        Ok(left)
+    }
+
+    fn ltl_and(&mut self) -> Result<LtlFormula, FormulaParseError> {
+        let left = self.ltl_binary()?;
+        if self.next_symbol("&&") {
+            return Ok(LtlFormula::And(Box::new(left), Box::new(self.ltl_and()?)));
+        }
+
+        Ok(left)
+    }
+
+    fn ltl_binary(&mut self) -> Result<LtlFormula, FormulaParseError> {
+        let left = self.ltl_prefix()?;
+        match self.next_keyword(&["U", "R", "W"]) {
+            Some("U") => Ok(LtlFormula::Until(Box::new(left), Box::new(self.ltl_binary()?))),
+            Some("R") => Ok(LtlFormula::Release(Box::new(left), Box::new(self.ltl_binary()?))),
+            Some(_) => Ok(LtlFormula::WeakUntil(Box::new(left), Box::new(self.ltl_binary()?))),
+            None => Ok(left),
+        }
+    }
+
+    fn ltl_prefix(&mut self) -> Result<LtlFormula, FormulaParseError> {
+        if self.next_symbol("!") {
+            Ok(LtlFormula::Not(Box::new(self.ltl_prefix()?)))
+        } else if self.next_symbol("(") {
+            let formula = self.ltl_formula()?;
+            self.expect(")")?;
+            Ok(formula)
+        } else if self.next_symbol("{") {
+            let formula = self.action_formula()?;
+            self.expect("}")?;
+            Ok(LtlFormula::Action(formula))
+        } else if let Some(keyword) = self.next_keyword(&["true", "false", "X", "F", "G"]) {
+            Ok(match keyword {
+                "true" => LtlFormula::True,
+                "false" => LtlFormula::False,
+                "X" => LtlFormula::Next(Box::new(self.ltl_prefix()?)),
+                "F" => LtlFormula::Eventually(Box::new(self.ltl_prefix()?)),
+                _ => LtlFormula::Always(Box::new(self.ltl_prefix()?)),
+            })
+        } else {
+            Ok(LtlFormula::Action(ActionFormula::Action(self.identifier()?)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use crate::LtsBuilder;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_ltl() {
+        let formula = LtlFormula::parse("G (send => F receive) && {!error} U done").unwrap();
+        assert_eq!(formula.to_string(), "(G (send => F receive) && ({!error} U done))");
+
+        assert!(LtlFormula::parse("G").is_err());
+        assert!(LtlFormula::parse("send U").is_err());
+    }
+
+    #[test]
+    fn test_check_ltl() {
+        // A system that sends and receives forever, or gets stuck after an error.
+        let mut builder = LtsBuilder::new(vec![]);
+        builder.add_transition(0, "send", 1);
+        builder.add_transition(1, "receive", 0);
+        builder.add_transition(1, "error", 2);
+        let lts = builder.finish(0);
+
+        let check = |formula: &str| check_ltl(&lts, &LtlFormula::parse(formula).unwrap());
+        assert!(check("send"));
+        assert!(check("X (receive || error)"));
+        assert!(check("G (send => X (receive || error))"));
+        assert!(check("G F send || F error"));
+        assert!(!check("G F send"));
+        assert!(!check("F error"));
+        assert!(check("!receive W send"));
+
+        // The counterexample to eventually error repeats the send and receive cycle.
+        let lasso = ltl_counterexample(&lts, &LtlFormula::parse("F error").unwrap()).unwrap();
+        let actions: Vec<&str> = lasso
+            .cycle()
+            .iter()
+            .map(|&(label_index, _)| lts.labels()[label_index].as_str())
+            .collect();
+        assert!(actions.contains(&"send") && actions.contains(&"receive"));
+        assert!(!actions.contains(&"error"));
+
+        // The counterexample to infinitely often send ends in the deadlock.
+        let lasso = ltl_counterexample(&lts, &LtlFormula::parse("G F send").unwrap()).unwrap();
+        assert!(lasso.cycle().is_empty());
+        assert_eq!(lasso.prefix().last_state(), 2);
+    }
+
+    #[test]
+    fn test_check_ltl_many_untils() {
+        // A cycle that performs every action once, with an escape to a deadlock at the end.
+        let mut builder = LtsBuilder::new(vec![]);
+        for index in 0..70 {
+            builder.add_transition(index, &format!("a{index}"), (index + 1) % 70);
+        }
+        builder.add_transition(69, "stop", 70);
+        let lts = builder.finish(0);
+
+        // The negation of the formula requires more acceptance sets than fit in a single word.
+        let formula = (0..70)
+            .map(|index| format!("G F a{index}"))
+            .collect::<Vec<_>>()
+            .join(" && ");
+        assert!(!check_ltl(&lts, &LtlFormula::parse(&formula).unwrap()));
+        assert!(check_ltl(
+            &lts,
+            &LtlFormula::parse(&format!("F stop || ({formula})")).unwrap()
+        ));
+    }
+}
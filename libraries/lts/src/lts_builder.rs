@@ -0,0 +1,132 @@
+use rustc_hash::FxHashMap;
+
+use crate::LabelIndex;
+use crate::LabelledTransitionSystem;
+use crate::StateIndex;
+
+/// Incrementally constructs a [LabelledTransitionSystem], for example while
+/// reading it from a file or exploring a state space, where the labels are
+/// given by their name.
+#[derive(Default)]
+pub struct LtsBuilder {
+    transitions: Vec<(u32, u32, u32)>,
+
+    labels: Vec<String>,
+    labels_index: FxHashMap<String, LabelIndex>,
+    hidden_labels: Vec<String>,
+
+    num_of_states: usize,
+}
+
+impl LtsBuilder {
+    /// Creates a builder for an LTS where the given labels are hidden.
+    pub fn new(hidden_labels: Vec<String>) -> LtsBuilder {
+        LtsBuilder {
+            hidden_labels,
+            ..Default::default()
+        }
+    }
+
+    /// Creates a builder that contains the given transitions, where no labels
+    /// other than tau are hidden.
+    pub fn from_transitions(transitions: &[(StateIndex, &str, StateIndex)]) -> LtsBuilder {
+        let mut builder = LtsBuilder::new(vec![]);
+        for &(from, label, to) in transitions {
+            builder.add_transition(from, label, to);
+        }
+        builder
+    }
+
+    /// Adds a transition from the given state to the given state with the given label.
+    pub fn add_transition(&mut self, from: StateIndex, label: &str, to: StateIndex) {
+        let label_index = self.add_label(label);
+        self.add_transition_index(from, label_index, to);
+    }
+
+    /// Adds a transition with a label that was returned by [LtsBuilder::add_label].
+    pub fn add_transition_index(&mut self, from: StateIndex, label: LabelIndex, to: StateIndex) {
+        debug_assert!(label < self.labels.len(), "The label {label} has not been added");
+        self.num_of_states = self.num_of_states.max(from.max(to) + 1);
+        self.transitions.push((
+            u32::try_from(from).expect("The state index should fit in 32 bits"),
+            label as u32,
+            u32::try_from(to).expect("The state index should fit in 32 bits"),
+        ));
+    }
+
+    /// Returns the index of the label with the given name, which is added when it does not exist yet.
+    pub fn add_label(&mut self, label: &str) -> LabelIndex {
+        if let Some(index) = self.labels_index.get(label) {
+            return *index;
+        }
+
+        let index = self.labels.len();
+        self.labels.push(label.to_string());
+        self.labels_index.insert(label.to_string(), index);
+        index
+    }
+
+    /// Ensures that the LTS has at least the given number of states, which
+    /// includes the states without incoming and outgoing transitions.
+    pub fn require_num_of_states(&mut self, num_of_states: usize) {
+        self.num_of_states = self.num_of_states.max(num_of_states);
+    }
+
+    /// Returns the number of transitions that have been added.
+    pub fn num_of_transitions(&self) -> usize {
+        self.transitions.len()
+    }
+
+    /// Returns the labelled transition system with the given initial state,
+    /// where duplicated transitions are removed.
+    pub fn finish(mut self, initial_state: StateIndex) -> LabelledTransitionSystem {
+        self.require_num_of_states(initial_state + 1);
+
+        self.transitions.sort_unstable();
+        self.transitions.dedup();
+
+        let mut hidden_labels = self.hidden_labels;
+        hidden_labels.push("tau".to_string());
+        LabelledTransitionSystem::new(
+            initial_state,
+            Some(self.num_of_states),
+            || {
+                self.transitions
+                    .iter()
+                    .map(|&(from, label, to)| (from as StateIndex, label as LabelIndex, to as StateIndex))
+            },
+            self.labels,
+            hidden_labels,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lts_builder() {
+        let mut builder = LtsBuilder::new(vec!["i".to_string()]);
+        builder.add_transition(0, "b", 1);
+        builder.add_transition(0, "a", 2);
+        builder.add_transition(0, "a", 2);
+        builder.add_transition(1, "i", 0);
+        builder.add_transition(2, "tau", 0);
+        builder.require_num_of_states(4);
+
+        let lts = builder.finish(0);
+        assert_eq!(lts.num_of_states(), 4);
+        assert_eq!(lts.num_of_transitions(), 4);
+
+        // The hidden labels are renamed to tau, and the outgoing transitions are sorted by label.
+        let outgoing: Vec<(&str, StateIndex)> = lts
+            .outgoing_transitions(0)
+            .map(|(label, to)| (lts.labels()[label].as_str(), to))
+            .collect();
+        assert_eq!(outgoing, vec![("b", 1), ("a", 2)]);
+        assert!(lts.outgoing_transitions(1).all(|(label, _)| lts.is_hidden_label(label)));
+        assert!(lts.outgoing_transitions(2).all(|(label, _)| lts.is_hidden_label(label)));
+        assert_eq!(lts.outgoing_transitions(3).count(), 0);
+    }
+}
@@ -0,0 +1,559 @@
+use std::error::Error;
+use std::fmt;
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+/// An action formula, which describes a set of action labels.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ActionFormula {
+    True,
+    False,
+    Action(String),
+    Not(Box<ActionFormula>),
+    And(Box<ActionFormula>, Box<ActionFormula>),
+    Or(Box<ActionFormula>, Box<ActionFormula>),
+    Implies(Box<ActionFormula>, Box<ActionFormula>),
+}
+
+/// A regular formula, which describes a set of sequences of actions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RegularFormula {
+    Action(ActionFormula),
+    Sequence(Box<RegularFormula>, Box<RegularFormula>),
+    Choice(Box<RegularFormula>, Box<RegularFormula>),
+
+    /// Zero or more repetitions.
+    Star(Box<RegularFormula>),
+
+    /// One or more repetitions.
+    Plus(Box<RegularFormula>),
+}
+
+/// A formula in the modal mu-calculus with regular formulas in the modalities,
+/// but without data.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StateFormula {
+    True,
+    False,
+    Not(Box<StateFormula>),
+    And(Box<StateFormula>, Box<StateFormula>),
+    Or(Box<StateFormula>, Box<StateFormula>),
+    Implies(Box<StateFormula>, Box<StateFormula>),
+    Diamond(RegularFormula, Box<StateFormula>),
+    Box(RegularFormula, Box<StateFormula>),
+    Mu(String, Box<StateFormula>),
+    Nu(String, Box<StateFormula>),
+    Variable(String),
+}
+
+/// The error that is returned when a formula cannot be parsed.
+#[derive(Debug, PartialEq, Eq)]
+pub struct FormulaParseError {
+    message: String,
+    position: usize,
+}
+
+impl fmt::Display for FormulaParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl Error for FormulaParseError {}
+
+impl FormulaParseError {
+    pub(crate) fn new(message: String, position: usize) -> FormulaParseError {
+        FormulaParseError { message, position }
+    }
+}
+
+impl StateFormula {
+    /// Parses a state formula in the mCRL2 syntax, for example `nu X. [true]X && <true>true`.
+    ///
+    /// The formula should be closed, and every fixpoint variable should occur
+    /// under an even number of negations in its body such that the fixpoints
+    /// are well-defined. Comments start with `%` and last until the end of
+    /// the line.
+    pub fn parse(input: &str) -> Result<StateFormula, FormulaParseError> {
+        let mut parser = Parser::new(input, &["true", "false", "mu", "nu"])?;
+        let formula = parser.state_formula()?;
+        parser.finish()?;
+
+        formula.check_variables(&mut Vec::new(), false)?;
+        Ok(formula)
+    }
+
+    /// Checks that every variable is bound by a fixpoint, and that it occurs
+    /// positively, where bound stores whether the variables were bound under
+    /// a negation.
+    fn check_variables(&self, bound: &mut Vec<(String, bool)>, negated: bool) -> Result<(), FormulaParseError> {
+        match self {
+            StateFormula::True | StateFormula::False => Ok(()),
+            StateFormula::Not(formula) => formula.check_variables(bound, !negated),
+            StateFormula::And(left, right) | StateFormula::Or(left, right) => {
+                left.check_variables(bound, negated)?;
+                right.check_variables(bound, negated)
+            }
+            StateFormula::Implies(left, right) => {
+                left.check_variables(bound, !negated)?;
+                right.check_variables(bound, negated)
+            }
+            StateFormula::Diamond(_, formula) | StateFormula::Box(_, formula) => {
+                formula.check_variables(bound, negated)
+            }
+            StateFormula::Mu(variable, formula) | StateFormula::Nu(variable, formula) => {
+                bound.push((variable.clone(), negated));
+                let result = formula.check_variables(bound, negated);
+                bound.pop();
+                result
+            }
+            StateFormula::Variable(variable) => match bound.iter().rev().find(|(name, _)| name == variable) {
+                Some((_, bound_negated)) if *bound_negated == negated => Ok(()),
+                Some(_) => Err(FormulaParseError {
+                    message: format!("Variable {variable} occurs under an odd number of negations"),
+                    position: 0,
+                }),
+                None => Err(FormulaParseError {
+                    message: format!("Variable {variable} is not bound by a fixpoint"),
+                    position: 0,
+                }),
+            },
+        }
+    }
+}
+
+impl fmt::Display for ActionFormula {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ActionFormula::True => write!(f, "true"),
+            ActionFormula::False => write!(f, "false"),
+            ActionFormula::Action(action) => write!(f, "{action}"),
+            ActionFormula::Not(formula) => write!(f, "!{formula}"),
+            ActionFormula::And(left, right) => write!(f, "({left} && {right})"),
+            ActionFormula::Or(left, right) => write!(f, "({left} || {right})"),
+            ActionFormula::Implies(left, right) => write!(f, "({left} => {right})"),
+        }
+    }
+}
+
+impl fmt::Display for RegularFormula {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegularFormula::Action(formula) => write!(f, "{formula}"),
+            RegularFormula::Sequence(left, right) => write!(f, "({left} . {right})"),
+            RegularFormula::Choice(left, right) => write!(f, "({left} + {right})"),
+            RegularFormula::Star(formula) => write!(f, "({formula})*"),
+            RegularFormula::Plus(formula) => write!(f, "({formula})+"),
+        }
+    }
+}
+
+impl fmt::Display for StateFormula {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StateFormula::True => write!(f, "true"),
+            StateFormula::False => write!(f, "false"),
+            StateFormula::Not(formula) => write!(f, "!{formula}"),
+            StateFormula::And(left, right) => write!(f, "({left} && {right})"),
+            StateFormula::Or(left, right) => write!(f, "({left} || {right})"),
+            StateFormula::Implies(left, right) => write!(f, "({left} => {right})"),
+            StateFormula::Diamond(regular, formula) => write!(f, "<{regular}>{formula}"),
+            StateFormula::Box(regular, formula) => write!(f, "[{regular}]{formula}"),
+            StateFormula::Mu(variable, formula) => write!(f, "(mu {variable}. {formula})"),
+            StateFormula::Nu(variable, formula) => write!(f, "(nu {variable}. {formula})"),
+            StateFormula::Variable(variable) => write!(f, "{variable}"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Token {
+    Identifier(String),
+    Symbol(&'static str),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Identifier(identifier) => write!(f, "identifier {identifier}"),
+            Token::Symbol(symbol) => write!(f, "symbol {symbol}"),
+        }
+    }
+}
+
+/// The symbols of the formula syntax, where longer symbols come first.
+const SYMBOLS: [&str; 15] = [
+    "&&", "||", "=>", "!", "<", ">", "[", "]", "(", ")", "{", "}", ".", "*", "+",
+];
+
+/// Splits the input into identifiers and symbols. The arguments of an action,
+/// i.e., `r1(d1, true)`, are part of its identifier with the whitespace removed,
+/// unless the identifier is one of the given keywords.
+fn tokenize(input: &str, keywords: &[&str]) -> Result<Vec<(Token, usize)>, FormulaParseError> {
+    let mut tokens = Vec::new();
+    let mut chars: Peekable<CharIndices> = input.char_indices().peekable();
+
+    while let Some(&(position, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '%' {
+            while chars.next_if(|&(_, c)| c != '\n').is_some() {}
+        } else if c.is_alphanumeric() || c == '_' {
+            let mut identifier = String::new();
+            while let Some((_, c)) = chars.next_if(|&(_, c)| c.is_alphanumeric() || c == '_' || c == '\'') {
+                identifier.push(c);
+            }
+
+            // Include the arguments of an action, which must be balanced.
+            if chars.peek().is_some_and(|&(_, c)| c == '(') && !keywords.contains(&identifier.as_str()) {
+                let mut depth = 0;
+                for (argument_position, c) in chars.by_ref() {
+                    match c {
+                        '(' => depth += 1,
+                        ')' => depth -= 1,
+                        _ => {}
+                    }
+
+                    if !c.is_whitespace() {
+                        identifier.push(c);
+                    }
+
+                    if depth == 0 {
+                        break;
+                    } else if argument_position + 1 == input.len() {
+                        return Err(FormulaParseError {
+                            message: "Unbalanced parentheses in the arguments of an action".to_string(),
+                            position,
+                        });
+                    }
+                }
+            }
+
+            tokens.push((Token::Identifier(identifier), position));
+        } else if let Some(symbol) = SYMBOLS.into_iter().find(|symbol| input[position..].starts_with(symbol)) {
+            for _ in 0..symbol.len() {
+                chars.next();
+            }
+            tokens.push((Token::Symbol(symbol), position));
+        } else {
+            return Err(FormulaParseError {
+                message: format!("Unexpected character {c}"),
+                position,
+            });
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// A recursive descent parser for state formulas, where the operators are
+/// ordered by increasing priority: the fixpoints, `=>`, `||`, `&&` and
+/// finally the prefix operators `!`, `<R>` and `[R]`. Within regular formulas
+/// `+` binds weaker than `.`, which binds weaker than the postfix `*` and `+`.
+///
+/// The helper functions are shared with the parsers of the other temporal logics.
+pub(crate) struct Parser {
+    tokens: Vec<(Token, usize)>,
+    index: usize,
+    length: usize,
+    keywords: &'static [&'static str],
+}
+
+impl Parser {
+    /// Creates a parser for the given input, where the keywords cannot be used as identifiers.
+    pub(crate) fn new(input: &str, keywords: &'static [&'static str]) -> Result<Parser, FormulaParseError> {
+        Ok(Parser {
+            tokens: tokenize(input, keywords)?,
+            index: 0,
+            length: input.len(),
+            keywords,
+        })
+    }
+
+    /// Returns an error when not all input has been parsed.
+    pub(crate) fn finish(&self) -> Result<(), FormulaParseError> {
+        match self.tokens.get(self.index) {
+            Some((token, position)) => Err(FormulaParseError::new(format!("Unexpected {token}"), *position)),
+            None => Ok(()),
+        }
+    }
+
+    fn state_formula(&mut self) -> Result<StateFormula, FormulaParseError> {
+        if let Some(fixpoint) = self.next_keyword(&["mu", "nu"]) {
+            let variable = self.identifier()?;
+            self.expect(".")?;
+            let body = Box::new(self.state_formula()?);
+
+            return Ok(if fixpoint == "mu" {
+                StateFormula::Mu(variable, body)
+            } else {
+                StateFormula::Nu(variable, body)
+            });
+        }
+
+        let left = self.state_or()?;
+        if self.next_symbol("=>") {
+            // The right hand side of an implication can again be a fixpoint.
+            return Ok(StateFormula::Implies(Box::new(left), Box::new(self.state_formula()?)));
+        }
+
+        Ok(left)
+    }
+
+    fn state_or(&mut self) -> Result<StateFormula, FormulaParseError> {
+        let left = self.state_and()?;
+        if self.next_symbol("||") {
+            return Ok(StateFormula::Or(Box::new(left), Box::new(self.state_or()?)));
+        }
+
+        Ok(left)
+    }
+
+    fn state_and(&mut self) -> Result<StateFormula, FormulaParseError> {
+        let left = self.state_prefix()?;
+        if self.next_symbol("&&") {
+            return Ok(StateFormula::And(Box::new(left), Box::new(self.state_and()?)));
+        }
+
+        Ok(left)
+    }
+
+    fn state_prefix(&mut self) -> Result<StateFormula, FormulaParseError> {
+        if self.next_symbol("!") {
+            Ok(StateFormula::Not(Box::new(self.state_prefix()?)))
+        } else if self.next_symbol("<") {
+            let regular = self.regular_formula()?;
+            self.expect(">")?;
+            Ok(StateFormula::Diamond(regular, Box::new(self.state_prefix()?)))
+        } else if self.next_symbol("[") {
+            let regular = self.regular_formula()?;
+            self.expect("]")?;
+            Ok(StateFormula::Box(regular, Box::new(self.state_prefix()?)))
+        } else if self.next_symbol("(") {
+            let formula = self.state_formula()?;
+            self.expect(")")?;
+            Ok(formula)
+        } else if let Some(keyword) = self.next_keyword(&["true", "false"]) {
+            Ok(if keyword == "true" {
+                StateFormula::True
+            } else {
+                StateFormula::False
+            })
+        } else if self.peek_keyword(&["mu", "nu"]) {
+            self.state_formula()
+        } else {
+            Ok(StateFormula::Variable(self.identifier()?))
+        }
+    }
+
+    fn regular_formula(&mut self) -> Result<RegularFormula, FormulaParseError> {
+        let left = self.regular_sequence()?;
+        if self.next_symbol("+") {
+            return Ok(RegularFormula::Choice(
+                Box::new(left),
+                Box::new(self.regular_formula()?),
+            ));
+        }
+
+        Ok(left)
+    }
+
+    fn regular_sequence(&mut self) -> Result<RegularFormula, FormulaParseError> {
+        let left = self.regular_postfix()?;
+        if self.next_symbol(".") {
+            return Ok(RegularFormula::Sequence(
+                Box::new(left),
+                Box::new(self.regular_sequence()?),
+            ));
+        }
+
+        Ok(left)
+    }
+
+    fn regular_postfix(&mut self) -> Result<RegularFormula, FormulaParseError> {
+        let mut formula = if self.peek_symbol("(") && self.is_regular_parenthesis() {
+            self.next_symbol("(");
+            let formula = self.regular_formula()?;
+            self.expect(")")?;
+            formula
+        } else {
+            RegularFormula::Action(self.action_formula()?)
+        };
+
+        loop {
+            if self.next_symbol("*") {
+                formula = RegularFormula::Star(Box::new(formula));
+            } else if self.peek_symbol("+") && self.is_postfix_plus() {
+                self.next_symbol("+");
+                formula = RegularFormula::Plus(Box::new(formula));
+            } else {
+                return Ok(formula);
+            }
+        }
+    }
+
+    pub(crate) fn action_formula(&mut self) -> Result<ActionFormula, FormulaParseError> {
+        let left = self.action_or()?;
+        if self.next_symbol("=>") {
+            return Ok(ActionFormula::Implies(Box::new(left), Box::new(self.action_formula()?)));
+        }
+
+        Ok(left)
+    }
+
+    fn action_or(&mut self) -> Result<ActionFormula, FormulaParseError> {
+        let left = self.action_and()?;
+        if self.next_symbol("||") {
+            return Ok(ActionFormula::Or(Box::new(left), Box::new(self.action_or()?)));
+        }
+
+        Ok(left)
+    }
+
+    fn action_and(&mut self) -> Result<ActionFormula, FormulaParseError> {
+        let left = self.action_prefix()?;
+        if self.next_symbol("&&") {
+            return Ok(ActionFormula::And(Box::new(left), Box::new(self.action_and()?)));
+        }
+
+        Ok(left)
+    }
+
+    fn action_prefix(&mut self) -> Result<ActionFormula, FormulaParseError> {
+        if self.next_symbol("!") {
+            Ok(ActionFormula::Not(Box::new(self.action_prefix()?)))
+        } else if self.next_symbol("(") {
+            let formula = self.action_formula()?;
+            self.expect(")")?;
+            Ok(formula)
+        } else if let Some(keyword) = self.next_keyword(&["true", "false"]) {
+            Ok(if keyword == "true" {
+                ActionFormula::True
+            } else {
+                ActionFormula::False
+            })
+        } else {
+            Ok(ActionFormula::Action(self.identifier()?))
+        }
+    }
+
+    /// Returns true iff the parenthesis at the current position encloses a
+    /// regular formula instead of an action formula, which is the case when
+    /// it contains one of the regular operators at the top level.
+    fn is_regular_parenthesis(&self) -> bool {
+        let mut depth = 0;
+        for (token, _) in &self.tokens[self.index..] {
+            match token {
+                Token::Symbol("(") => depth += 1,
+                Token::Symbol(")") => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return false;
+                    }
+                }
+                Token::Symbol(".") | Token::Symbol("*") | Token::Symbol("+") if depth == 1 => return true,
+                _ => {}
+            }
+        }
+
+        false
+    }
+
+    /// Returns true iff the `+` at the current position is the postfix
+    /// transitive closure, i.e., it is not followed by an operand.
+    fn is_postfix_plus(&self) -> bool {
+        matches!(
+            self.tokens.get(self.index + 1),
+            None | Some((Token::Symbol(">" | "]" | ")" | "." | "*" | "+"), _))
+        )
+    }
+
+    pub(crate) fn identifier(&mut self) -> Result<String, FormulaParseError> {
+        match self.tokens.get(self.index) {
+            Some((Token::Identifier(identifier), _)) if !self.keywords.contains(&identifier.as_str()) => {
+                self.index += 1;
+                Ok(identifier.clone())
+            }
+            Some((token, position)) => Err(FormulaParseError {
+                message: format!("Expected an identifier, but found {token}"),
+                position: *position,
+            }),
+            None => Err(self.unexpected_end()),
+        }
+    }
+
+    pub(crate) fn expect(&mut self, symbol: &'static str) -> Result<(), FormulaParseError> {
+        if self.next_symbol(symbol) {
+            Ok(())
+        } else if let Some((token, position)) = self.tokens.get(self.index) {
+            Err(FormulaParseError {
+                message: format!("Expected {symbol}, but found {token}"),
+                position: *position,
+            })
+        } else {
+            Err(self.unexpected_end())
+        }
+    }
+
+    pub(crate) fn peek_symbol(&self, symbol: &str) -> bool {
+        matches!(self.tokens.get(self.index), Some((Token::Symbol(other), _)) if *other == symbol)
+    }
+
+    pub(crate) fn next_symbol(&mut self, symbol: &str) -> bool {
+        let result = self.peek_symbol(symbol);
+        if result {
+            self.index += 1;
+        }
+        result
+    }
+
+    pub(crate) fn peek_keyword(&self, keywords: &[&'static str]) -> bool {
+        matches!(self.tokens.get(self.index), Some((Token::Identifier(identifier), _)) if keywords.contains(&identifier.as_str()))
+    }
+
+    pub(crate) fn next_keyword(&mut self, keywords: &[&'static str]) -> Option<&'static str> {
+        if let Some((Token::Identifier(identifier), _)) = self.tokens.get(self.index) {
+            if let Some(keyword) = keywords.iter().copied().find(|keyword| *keyword == identifier.as_str()) {
+                self.index += 1;
+                return Some(keyword);
+            }
+        }
+
+        None
+    }
+
+    pub(crate) fn unexpected_end(&self) -> FormulaParseError {
+        FormulaParseError {
+            message: "Unexpected end of input".to_string(),
+            position: self.length,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_formula() {
+        let formula = StateFormula::parse("nu X. [true]X && <true>true").unwrap();
+        assert_eq!(formula.to_string(), "(nu X. ([true]X && <true>true))");
+
+        let formula = StateFormula::parse("[true*. r1(d1, true)]mu Y. <!tau>true || <tau>Y").unwrap();
+        assert_eq!(
+            formula.to_string(),
+            "[((true)* . r1(d1,true))](mu Y. (<!tau>true || <tau>Y))"
+        );
+
+        let formula = StateFormula::parse("<(a . b)+ + c>true % comment").unwrap();
+        assert_eq!(formula.to_string(), "<(((a . b))+ + c)>true");
+    }
+
+    #[test]
+    fn test_parse_invalid_formula() {
+        assert!(StateFormula::parse("mu X. !X").is_err());
+        assert!(StateFormula::parse("<a>X").is_err());
+        assert!(StateFormula::parse("<a>true &&").is_err());
+        assert!(StateFormula::parse("[a(b]true").is_err());
+    }
+}
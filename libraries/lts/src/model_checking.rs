@@ -0,0 +1,238 @@
+use log::debug;
+use log::trace;
+
+use crate::ActionFormula;
+use crate::LabelTable;
+use crate::LabelledTransitionSystem;
+use crate::MultiAction;
+use crate::RegularFormula;
+use crate::StateFormula;
+
+/// Returns true iff the given closed state formula holds in the initial state of the LTS.
+pub fn check_formula(lts: &LabelledTransitionSystem, formula: &StateFormula) -> bool {
+    evaluate_formula(lts, formula)[lts.initial_state_index()]
+}
+
+/// Returns for every state of the LTS whether it satisfies the given closed
+/// state formula.
+///
+/// The fixpoints are computed by naive iteration, where nested fixpoints
+/// are recomputed for every iteration of the enclosing fixpoint. As such the
+/// running time is exponential in the nesting depth of the fixpoints.
+pub fn evaluate_formula(lts: &LabelledTransitionSystem, formula: &StateFormula) -> Vec<bool> {
+    let mut evaluator = Evaluator {
+        lts,
+        environment: Vec::new(),
+    };
+
+    evaluator.evaluate(formula)
+}
+
+/// Returns for every label whether it satisfies the given action formula,
+/// where the actions are compared as multi-actions, see [MultiAction], and
+/// the hidden label matches the action tau.
+pub fn evaluate_action_formula(lts: &LabelledTransitionSystem, formula: &ActionFormula) -> Vec<bool> {
+    evaluate_action_formula_with(&LabelTable::new(lts), formula)
+}
+
+fn evaluate_action_formula_with(table: &LabelTable, formula: &ActionFormula) -> Vec<bool> {
+    match formula {
+        ActionFormula::True => table.matching(|_| true),
+        ActionFormula::False => table.matching(|_| false),
+        ActionFormula::Action(action) => {
+            let action = MultiAction::parse(action);
+            table.matching(|multi_action| *multi_action == action)
+        }
+        ActionFormula::Not(formula) => complement(evaluate_action_formula_with(table, formula)),
+        ActionFormula::And(left, right) => intersection(
+            evaluate_action_formula_with(table, left),
+            &evaluate_action_formula_with(table, right),
+        ),
+        ActionFormula::Or(left, right) => union(
+            evaluate_action_formula_with(table, left),
+            &evaluate_action_formula_with(table, right),
+        ),
+        ActionFormula::Implies(left, right) => union(
+            complement(evaluate_action_formula_with(table, left)),
+            &evaluate_action_formula_with(table, right),
+        ),
+    }
+}
+
+/// Evaluates state formulas, where the environment stores the current
+/// approximation of every bound fixpoint variable.
+struct Evaluator<'a> {
+    lts: &'a LabelledTransitionSystem,
+    environment: Vec<(String, Vec<bool>)>,
+}
+
+impl Evaluator<'_> {
+    fn evaluate(&mut self, formula: &StateFormula) -> Vec<bool> {
+        let num_of_states = self.lts.num_of_states();
+
+        match formula {
+            StateFormula::True => vec![true; num_of_states],
+            StateFormula::False => vec![false; num_of_states],
+            StateFormula::Not(formula) => complement(self.evaluate(formula)),
+            StateFormula::And(left, right) => {
+                let left = self.evaluate(left);
+                intersection(left, &self.evaluate(right))
+            }
+            StateFormula::Or(left, right) => {
+                let left = self.evaluate(left);
+                union(left, &self.evaluate(right))
+            }
+            StateFormula::Implies(left, right) => {
+                let left = complement(self.evaluate(left));
+                union(left, &self.evaluate(right))
+            }
+            StateFormula::Diamond(regular, formula) => {
+                let states = self.evaluate(formula);
+                self.diamond(regular, states)
+            }
+            StateFormula::Box(regular, formula) => {
+                // [R]f is equivalent to !<R>!f.
+                let states = complement(self.evaluate(formula));
+                complement(self.diamond(regular, states))
+            }
+            StateFormula::Mu(variable, formula) => self.fixpoint(variable, formula, vec![false; num_of_states]),
+            StateFormula::Nu(variable, formula) => self.fixpoint(variable, formula, vec![true; num_of_states]),
+            StateFormula::Variable(variable) => self
+                .environment
+                .iter()
+                .rev()
+                .find(|(name, _)| name == variable)
+                .map(|(_, states)| states.clone())
+                .unwrap_or_else(|| panic!("Variable {variable} is not bound by a fixpoint")),
+        }
+    }
+
+    /// Computes the fixpoint of the given formula starting from the given approximation.
+    fn fixpoint(&mut self, variable: &str, formula: &StateFormula, initial: Vec<bool>) -> Vec<bool> {
+        self.environment.push((variable.to_string(), initial));
+
+        let mut iteration = 0;
+        loop {
+            let next = self.evaluate(formula);
+            iteration += 1;
+
+            let (_, current) = self.environment.last_mut().expect("The variable was pushed before");
+            if *current == next {
+                break;
+            }
+
+            trace!("Iteration {iteration} of {variable}");
+            *current = next;
+        }
+
+        debug!("Fixpoint {variable} stable after {iteration} iterations");
+        self.environment.pop().map(|(_, states)| states).unwrap_or_default()
+    }
+
+    /// Returns the states that can reach one of the given states by a path
+    /// that matches the given regular formula.
+    fn diamond(&self, regular: &RegularFormula, states: Vec<bool>) -> Vec<bool> {
+        match regular {
+            RegularFormula::Action(formula) => {
+                let labels = evaluate_action_formula(self.lts, formula);
+                self.lts
+                    .iter_states()
+                    .map(|state_index| {
+                        self.lts
+                            .outgoing_transitions(state_index)
+                            .any(|(label_index, to)| labels[label_index] && states[to])
+                    })
+                    .collect()
+            }
+            RegularFormula::Sequence(left, right) => {
+                let states = self.diamond(right, states);
+                self.diamond(left, states)
+            }
+            RegularFormula::Choice(left, right) => {
+                let left = self.diamond(left, states.clone());
+                union(left, &self.diamond(right, states))
+            }
+            RegularFormula::Star(formula) => {
+                // <R*>f is the least fixpoint of X = f || <R>X.
+                let mut result = states.clone();
+                loop {
+                    let next = union(self.diamond(formula, result.clone()), &states);
+                    if next == result {
+                        return result;
+                    }
+                    result = next;
+                }
+            }
+            RegularFormula::Plus(formula) => {
+                // <R+>f is equivalent to <R><R*>f.
+                let states = self.diamond(&RegularFormula::Star(formula.clone()), states);
+                self.diamond(formula, states)
+            }
+        }
+    }
+}
+
+fn complement(mut states: Vec<bool>) -> Vec<bool> {
+    states.iter_mut().for_each(|value| *value = !*value);
+    states
+}
+
+fn intersection(mut states: Vec<bool>, other: &[bool]) -> Vec<bool> {
+    states.iter_mut().zip(other).for_each(|(value, other)| *value &= other);
+    states
+}
+
+fn union(mut states: Vec<bool>, other: &[bool]) -> Vec<bool> {
+    states.iter_mut().zip(other).for_each(|(value, other)| *value |= other);
+    states
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use crate::random_lts;
+    use crate::LtsBuilder;
+
+    use super::*;
+
+    fn check(lts: &LabelledTransitionSystem, formula: &str) -> bool {
+        check_formula(lts, &StateFormula::parse(formula).unwrap())
+    }
+
+    #[test]
+    fn test_check_formula() {
+        let mut builder = LtsBuilder::new(vec!["i".to_string()]);
+        builder.add_transition(0, "a", 1);
+        builder.add_transition(1, "i", 2);
+        builder.add_transition(2, "b", 0);
+        builder.add_transition(0, "c", 3);
+        let lts = builder.finish(0);
+
+        assert!(check(&lts, "<a>true && !<b>true"));
+        assert!(check(&lts, "<a . tau . b>true"));
+        assert!(check(&lts, "<true*>[true]false"));
+        assert!(!check(&lts, "[true*]<true>true"));
+        assert!(check(&lts, "nu X. <a . tau . b>X"));
+        assert!(!check(&lts, "mu X. <a . tau . b>X"));
+        assert!(check(&lts, "[(a . tau . b)+]<c>true"));
+        assert!(check(&lts, "mu X. [!c]X && <true>true || <c>true"));
+    }
+
+    #[test]
+    fn test_random_deadlock_freedom() {
+        let lts = random_lts(20, 3, 2);
+        let deadlocks: Vec<bool> = lts
+            .iter_states()
+            .map(|state_index| lts.outgoing_transitions(state_index).next().is_none())
+            .collect();
+
+        let formula = StateFormula::parse("[true]false").unwrap();
+        assert_eq!(evaluate_formula(&lts, &formula), deadlocks);
+
+        // Deadlock freedom is equivalent to the expanded box of the regular formula.
+        let regular = StateFormula::parse("[true*]<true>true").unwrap();
+        let fixpoint = StateFormula::parse("nu X. [true]X && <true>true").unwrap();
+        assert_eq!(evaluate_formula(&lts, &regular), evaluate_formula(&lts, &fixpoint));
+    }
+}
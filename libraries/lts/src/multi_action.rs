@@ -0,0 +1,342 @@
+use std::fmt;
+
+use rustc_hash::FxHashMap;
+
+use crate::LabelIndex;
+use crate::LabelledTransitionSystem;
+
+/// A single action with its data arguments, i.e., `r1(d1, true)`. The
+/// arguments are stored as written, but without whitespace.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Action {
+    name: String,
+    arguments: Vec<String>,
+}
+
+impl Action {
+    /// Creates an action with the given name and arguments.
+    pub fn new(name: impl Into<String>, arguments: Vec<String>) -> Action {
+        Action {
+            name: name.into(),
+            arguments,
+        }
+    }
+
+    /// Returns the name of the action.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the data arguments of the action.
+    pub fn arguments(&self) -> &[String] {
+        &self.arguments
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        if !self.arguments.is_empty() {
+            write!(f, "({})", self.arguments.join(", "))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A multi-action `a(1)|b`, which is a multiset of actions that happen
+/// simultaneously. The empty multi-action is the hidden action tau.
+///
+/// The actions are kept sorted such that multi-actions that only differ in
+/// the order of their actions, or in whitespace, are equal.
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MultiAction {
+    actions: Vec<Action>,
+}
+
+impl MultiAction {
+    /// Creates a multi-action from the given actions.
+    pub fn new(mut actions: Vec<Action>) -> MultiAction {
+        actions.sort_unstable();
+        MultiAction { actions }
+    }
+
+    /// Returns the empty multi-action tau.
+    pub fn tau() -> MultiAction {
+        MultiAction::default()
+    }
+
+    /// Parses a label of an LTS into a multi-action, where `tau` and the
+    /// empty label are the empty multi-action. This never fails, since the
+    /// data arguments are not interpreted.
+    pub fn parse(label: &str) -> MultiAction {
+        let label = label.trim();
+        if label.is_empty() || label == "tau" {
+            return MultiAction::tau();
+        }
+
+        MultiAction::new(
+            split_top_level(label, '|')
+                .into_iter()
+                .map(|action| {
+                    let action = action.trim();
+                    match action.find('(') {
+                        Some(position) if action.ends_with(')') => Action::new(
+                            action[..position].trim(),
+                            split_top_level(&action[position + 1..action.len() - 1], ',')
+                                .into_iter()
+                                .map(|argument| argument.chars().filter(|c| !c.is_whitespace()).collect())
+                                .collect(),
+                        ),
+                        _ => Action::new(action, Vec::new()),
+                    }
+                })
+                .collect(),
+        )
+    }
+
+    /// Returns the actions of the multi-action, sorted by name and arguments.
+    pub fn actions(&self) -> &[Action] {
+        &self.actions
+    }
+
+    /// Returns true iff this is the empty multi-action tau.
+    pub fn is_tau(&self) -> bool {
+        self.actions.is_empty()
+    }
+
+    /// Returns true iff one of the actions has the given name.
+    pub fn contains_action(&self, name: &str) -> bool {
+        self.actions.iter().any(|action| action.name == name)
+    }
+
+    /// Returns the multi-action without the actions for which the predicate holds.
+    pub fn remove_actions(&self, mut predicate: impl FnMut(&Action) -> bool) -> MultiAction {
+        MultiAction {
+            actions: self
+                .actions
+                .iter()
+                .filter(|action| !predicate(action))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Returns the multi-action in which the names of the actions are renamed by the given function.
+    pub fn rename_actions(&self, mut rename: impl FnMut(&str) -> Option<String>) -> MultiAction {
+        MultiAction::new(
+            self.actions
+                .iter()
+                .map(|action| Action {
+                    name: rename(&action.name).unwrap_or_else(|| action.name.clone()),
+                    arguments: action.arguments.clone(),
+                })
+                .collect(),
+        )
+    }
+}
+
+impl fmt::Display for MultiAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.actions.is_empty() {
+            return write!(f, "tau");
+        }
+
+        for (index, action) in self.actions.iter().enumerate() {
+            if index > 0 {
+                write!(f, "|")?;
+            }
+            write!(f, "{action}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Splits the input on the given separator, but not within parentheses,
+/// brackets or braces such as the ones of lists, sets and bags.
+fn split_top_level(input: &str, separator: char) -> Vec<&str> {
+    let mut result = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+
+    for (position, c) in input.char_indices() {
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth = depth.saturating_sub(1),
+            c if c == separator && depth == 0 => {
+                result.push(&input[start..position]);
+                start = position + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+
+    result.push(&input[start..]);
+    result
+}
+
+/// The multi-action of every label of an LTS, together with an index to find
+/// the label of a multi-action. The hidden label is the multi-action tau.
+pub struct LabelTable {
+    multi_actions: Vec<MultiAction>,
+    indices: FxHashMap<MultiAction, LabelIndex>,
+}
+
+impl LabelTable {
+    /// Parses the labels of the given LTS.
+    pub fn new(lts: &LabelledTransitionSystem) -> LabelTable {
+        let multi_actions: Vec<MultiAction> = lts
+            .labels()
+            .iter()
+            .enumerate()
+            .map(|(label_index, label)| {
+                if lts.is_hidden_label(label_index) {
+                    MultiAction::tau()
+                } else {
+                    MultiAction::parse(label)
+                }
+            })
+            .collect();
+
+        let mut indices = FxHashMap::default();
+        for (label_index, multi_action) in multi_actions.iter().enumerate() {
+            indices.entry(multi_action.clone()).or_insert(label_index);
+        }
+
+        LabelTable { multi_actions, indices }
+    }
+
+    /// Returns the multi-action of the given label.
+    pub fn multi_action(&self, label_index: LabelIndex) -> &MultiAction {
+        &self.multi_actions[label_index]
+    }
+
+    /// Returns the first label that is equal to the given multi-action, if it exists.
+    pub fn label_index(&self, multi_action: &MultiAction) -> Option<LabelIndex> {
+        self.indices.get(multi_action).copied()
+    }
+
+    /// Returns the labels that satisfy the given predicate.
+    pub fn matching(&self, mut predicate: impl FnMut(&MultiAction) -> bool) -> Vec<bool> {
+        self.multi_actions.iter().map(&mut predicate).collect()
+    }
+}
+
+/// Returns the LTS in which the actions with the given names are removed from
+/// every multi-action, as in the hiding operator of mCRL2. The multi-actions
+/// that become empty are hidden.
+pub fn hide_actions(lts: &LabelledTransitionSystem, names: &[String]) -> LabelledTransitionSystem {
+    relabel(lts, |multi_action| {
+        multi_action.remove_actions(|action| names.contains(&action.name))
+    })
+}
+
+/// Returns the LTS in which the actions are renamed according to the given
+/// map from old to new action names, where the arguments are kept.
+pub fn rename_actions(
+    lts: &LabelledTransitionSystem,
+    renaming: &FxHashMap<String, String>,
+) -> LabelledTransitionSystem {
+    relabel(lts, |multi_action| {
+        multi_action.rename_actions(|name| renaming.get(name).cloned())
+    })
+}
+
+/// Returns the LTS in which every multi-action is replaced by the result of the
+/// given function, where labels that become equal are merged.
+fn relabel(
+    lts: &LabelledTransitionSystem,
+    mut function: impl FnMut(&MultiAction) -> MultiAction,
+) -> LabelledTransitionSystem {
+    let table = LabelTable::new(lts);
+
+    // The hidden label stays at index zero.
+    let mut labels = vec!["tau".to_string()];
+    let mut indices: FxHashMap<MultiAction, LabelIndex> = FxHashMap::default();
+    indices.insert(MultiAction::tau(), 0);
+
+    let mapping: Vec<LabelIndex> = (0..lts.num_of_labels())
+        .map(|label_index| {
+            let multi_action = function(table.multi_action(label_index));
+            *indices.entry(multi_action).or_insert_with_key(|multi_action| {
+                labels.push(multi_action.to_string());
+                labels.len() - 1
+            })
+        })
+        .collect();
+
+    let mapping = &mapping;
+    let result = LabelledTransitionSystem::new(
+        lts.initial_state_index(),
+        Some(lts.num_of_states()),
+        || {
+            lts.iter_states().flat_map(move |state_index| {
+                lts.outgoing_transitions(state_index)
+                    .map(move |(label_index, to)| (state_index, mapping[label_index], to))
+            })
+        },
+        labels,
+        vec!["tau".to_string()],
+    );
+
+    match lts.state_labels() {
+        Some(state_labels) => result.with_state_labels(state_labels.clone()),
+        None => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::LtsBuilder;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_multi_action() {
+        let multi_action = MultiAction::parse("s3(d1, true) | r2(e)|b");
+        assert_eq!(multi_action.to_string(), "b|r2(e)|s3(d1, true)");
+        assert_eq!(multi_action, MultiAction::parse("r2( e )|b|s3(d1,true)"));
+        assert_eq!(multi_action.actions()[2].arguments(), ["d1", "true"]);
+        assert!(multi_action.contains_action("r2"));
+
+        assert!(MultiAction::parse("tau").is_tau());
+        assert_eq!(
+            MultiAction::parse("f(g(1, 2), 3)").actions()[0].arguments(),
+            ["g(1,2)", "3"]
+        );
+        assert_eq!(MultiAction::parse("r([d1, d2])").actions()[0].arguments(), ["[d1,d2]"]);
+        assert_eq!(
+            MultiAction::parse("s({1: 2, 3: 4}, {d1})|r").actions()[1].arguments(),
+            ["{1:2,3:4}", "{d1}"]
+        );
+    }
+
+    #[test]
+    fn test_hide_and_rename_actions() {
+        let mut builder = LtsBuilder::new(vec![]);
+        builder.add_transition(0, "a(1)|b", 1);
+        builder.add_transition(1, "b", 2);
+        builder.add_transition(2, "c(2)", 0);
+        let lts = builder.finish(0);
+
+        let hidden = hide_actions(&lts, &["b".to_string()]);
+        assert_eq!(hidden.num_of_labels(), 3);
+        assert!(hidden
+            .outgoing_transitions(1)
+            .all(|(label_index, _)| hidden.is_hidden_label(label_index)));
+        assert!(hidden
+            .outgoing_transitions(0)
+            .all(|(label_index, _)| hidden.labels()[label_index] == "a(1)"));
+
+        let mut renaming = FxHashMap::default();
+        renaming.insert("a".to_string(), "c".to_string());
+        renaming.insert("b".to_string(), "c".to_string());
+        let renamed = rename_actions(&lts, &renaming);
+        let table = LabelTable::new(&renamed);
+        assert!(renamed
+            .outgoing_transitions(1)
+            .all(|(label_index, _)| renamed.labels()[label_index] == "c"));
+        assert!(table.label_index(&MultiAction::parse("c(1)|c")).is_some());
+    }
+}
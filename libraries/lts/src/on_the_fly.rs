@@ -0,0 +1,238 @@
+use log::debug;
+use rustc_hash::FxHashMap;
+
+use crate::LabelIndex;
+use crate::LabelledTransitionSystem;
+use crate::StateIndex;
+
+/// Returns true iff the initial states of both labelled transition systems
+/// are strongly bisimilar, where labels are related by their name.
+///
+/// Instead of reducing both LTSs this explores the bisimulation game on pairs
+/// of states from the pair of initial states onwards. For every pair the
+/// other LTS must be able to mimic every transition of one LTS, called a
+/// challenge, by a transition with the same label to a pair that is not
+/// distinguished. Pairs for which a challenge has no remaining responses are
+/// distinguished, which is propagated backwards, and the exploration
+/// terminates as soon as the initial pair is distinguished. As such only the
+/// reachable pairs are explored, and typically far fewer when both are not
+/// bisimilar.
+pub fn strong_bisim_on_the_fly(left: &LabelledTransitionSystem, right: &LabelledTransitionSystem) -> bool {
+    let mut game = BisimulationGame::new(left, right);
+    let related = game.solve();
+
+    debug!(
+        "Explored {} pairs of states on-the-fly, the initial states are {}bisimilar",
+        game.pairs.len(),
+        if related { "" } else { "not " }
+    );
+    related
+}
+
+/// The state of the on-the-fly bisimulation game.
+struct BisimulationGame<'a> {
+    left: &'a LabelledTransitionSystem,
+    right: &'a LabelledTransitionSystem,
+
+    /// For every label of the right LTS the label of the left LTS with the same name.
+    label_map: Vec<Option<LabelIndex>>,
+
+    /// Every pair of states that has been encountered is stored once.
+    pairs: Vec<(StateIndex, StateIndex)>,
+    pair_index: FxHashMap<(StateIndex, StateIndex), usize>,
+
+    /// The pairs that have been encountered, but not yet explored.
+    queue: Vec<usize>,
+
+    /// Pairs that are known to be distinguished.
+    distinguished: Vec<bool>,
+
+    /// The pairs that have been distinguished, but not yet propagated.
+    worklist: Vec<usize>,
+
+    /// For every challenge its pair and the number of responses that are not yet distinguished.
+    challenges: Vec<(usize, usize)>,
+
+    /// For every pair the challenges in which it is a response.
+    dependents: Vec<Vec<usize>>,
+}
+
+impl<'a> BisimulationGame<'a> {
+    fn new(left: &'a LabelledTransitionSystem, right: &'a LabelledTransitionSystem) -> BisimulationGame<'a> {
+        // Hidden labels are always named tau, and are as such related.
+        let label_map = right
+            .labels()
+            .iter()
+            .map(|name| left.labels().iter().position(|other| other == name))
+            .collect();
+
+        BisimulationGame {
+            left,
+            right,
+            label_map,
+            pairs: Vec::new(),
+            pair_index: FxHashMap::default(),
+            queue: Vec::new(),
+            distinguished: Vec::new(),
+            worklist: Vec::new(),
+            challenges: Vec::new(),
+            dependents: Vec::new(),
+        }
+    }
+
+    /// Explores the game until the initial pair is distinguished, or all reachable pairs have been explored.
+    fn solve(&mut self) -> bool {
+        let initial = self.intern((self.left.initial_state_index(), self.right.initial_state_index()));
+
+        while let Some(pair) = self.queue.pop() {
+            self.explore(pair);
+            self.propagate();
+
+            if self.distinguished[initial] {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Adds the challenges of the given pair of states.
+    fn explore(&mut self, pair: usize) {
+        let (state_index, other_state_index) = self.pairs[pair];
+        let mut responses = Vec::new();
+
+        // The right LTS must mimic every transition of the left LTS.
+        for (label_index, to) in self.left.outgoing_transitions(state_index) {
+            responses.clear();
+            responses.extend(
+                self.right
+                    .outgoing_transitions(other_state_index)
+                    .filter(|(other_label_index, _)| self.label_map[*other_label_index] == Some(label_index))
+                    .map(|(_, other_to)| (to, other_to)),
+            );
+            self.add_challenge(pair, &responses);
+        }
+
+        // The left LTS must mimic every transition of the right LTS.
+        for (other_label_index, other_to) in self.right.outgoing_transitions(other_state_index) {
+            responses.clear();
+            if let Some(label_index) = self.label_map[other_label_index] {
+                responses.extend(
+                    self.left
+                        .outgoing_transitions(state_index)
+                        .filter(|(label, _)| *label == label_index)
+                        .map(|(_, to)| (to, other_to)),
+                );
+            }
+            self.add_challenge(pair, &responses);
+        }
+    }
+
+    /// Adds a challenge for the given pair that can be answered by the given pairs of states.
+    fn add_challenge(&mut self, pair: usize, responses: &[(StateIndex, StateIndex)]) {
+        let challenge = self.challenges.len();
+        let mut remaining = 0;
+
+        for &response in responses {
+            let response = self.intern(response);
+            if !self.distinguished[response] {
+                self.dependents[response].push(challenge);
+                remaining += 1;
+            }
+        }
+
+        self.challenges.push((pair, remaining));
+        if remaining == 0 {
+            self.distinguish(pair);
+        }
+    }
+
+    /// Propagates the distinguished pairs to the challenges in which they are a response.
+    fn propagate(&mut self) {
+        while let Some(pair) = self.worklist.pop() {
+            for index in 0..self.dependents[pair].len() {
+                let challenge = self.dependents[pair][index];
+                let (owner, remaining) = &mut self.challenges[challenge];
+
+                *remaining -= 1;
+                if *remaining == 0 {
+                    let owner = *owner;
+                    self.distinguish(owner);
+                }
+            }
+
+            self.dependents[pair].clear();
+        }
+    }
+
+    /// Marks the given pair as distinguished.
+    fn distinguish(&mut self, pair: usize) {
+        if !self.distinguished[pair] {
+            self.distinguished[pair] = true;
+            self.worklist.push(pair);
+        }
+    }
+
+    /// Returns the index of the given pair, which is queued for exploration when it is new.
+    fn intern(&mut self, pair: (StateIndex, StateIndex)) -> usize {
+        *self.pair_index.entry(pair).or_insert_with(|| {
+            self.pairs.push(pair);
+            self.distinguished.push(false);
+            self.dependents.push(Vec::new());
+            self.queue.push(self.pairs.len() - 1);
+            self.pairs.len() - 1
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+    use utilities::Timing;
+
+    use crate::disjoint_union;
+    use crate::quotient;
+    use crate::random_lts;
+    use crate::strong_bisim_sigref;
+    use crate::LtsBuilder;
+    use crate::Partition;
+
+    use super::*;
+
+    #[test]
+    fn test_strong_bisim_on_the_fly() {
+        let left = LtsBuilder::from_transitions(&[(0, "a", 1), (1, "b", 2), (0, "a", 3), (3, "b", 4)]).finish(0);
+        let right = LtsBuilder::from_transitions(&[(0, "a", 1), (1, "b", 0)]).finish(0);
+        assert!(!strong_bisim_on_the_fly(&left, &right));
+
+        let right = LtsBuilder::from_transitions(&[(0, "a", 1), (1, "b", 2)]).finish(0);
+        assert!(strong_bisim_on_the_fly(&left, &right));
+        assert!(strong_bisim_on_the_fly(&right, &left));
+
+        // Loops are bisimilar to their unfolding.
+        let left = LtsBuilder::from_transitions(&[(0, "a", 0)]).finish(0);
+        let right = LtsBuilder::from_transitions(&[(0, "a", 1), (1, "a", 0)]).finish(0);
+        assert!(strong_bisim_on_the_fly(&left, &right));
+    }
+
+    #[test]
+    fn test_random_strong_bisim_on_the_fly() {
+        for _ in 0..20 {
+            let left = random_lts(10, 3, 2);
+            let right = random_lts(10, 3, 2);
+
+            let (lts, offset) = disjoint_union(&left, &right);
+            let partition = strong_bisim_sigref(&lts, &mut Timing::new());
+
+            assert_eq!(
+                strong_bisim_on_the_fly(&left, &right),
+                partition.block_number(left.initial_state_index())
+                    == partition.block_number(right.initial_state_index() + offset)
+            );
+
+            // Every LTS is bisimilar to its quotient.
+            let partition = strong_bisim_sigref(&left, &mut Timing::new());
+            assert!(strong_bisim_on_the_fly(&left, &quotient(&left, &partition)));
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use crate::LabelIndex;
+use crate::LabelledTransitionSystem;
+use crate::StateIndex;
+
+/// Returns the LTS that only contains the states that are reachable from the
+/// initial state, where the states are numbered in the order in which they
+/// are found by a breadth first search. As such the initial state becomes
+/// state zero.
+pub fn remove_unreachable_states(lts: &LabelledTransitionSystem) -> LabelledTransitionSystem {
+    let mut state_map: Vec<Option<StateIndex>> = vec![None; lts.num_of_states()];
+    let mut queue = vec![lts.initial_state_index()];
+    state_map[lts.initial_state_index()] = Some(0);
+
+    let mut transitions: Vec<(StateIndex, LabelIndex, StateIndex)> = Vec::new();
+    let mut index = 0;
+    while index < queue.len() {
+        let state_index = queue[index];
+        for (label_index, to) in lts.outgoing_transitions(state_index) {
+            let to_index = *state_map[to].get_or_insert_with(|| {
+                queue.push(to);
+                queue.len() - 1
+            });
+
+            transitions.push((index, label_index, to_index));
+        }
+
+        index += 1;
+    }
+
+    let result = LabelledTransitionSystem::new(
+        0,
+        Some(queue.len()),
+        || transitions.iter().cloned(),
+        lts.labels().into(),
+        lts.hidden_labels().into(),
+    );
+
+    match lts.state_labels() {
+        Some(state_labels) => {
+            result.with_state_labels(state_labels.map_states(queue.len(), |state_index| state_map[state_index]))
+        }
+        None => result,
+    }
+}
+
+/// Returns the LTS that only contains the transitions of which the label is
+/// in the given set of labels. Hidden transitions are always kept, and the
+/// states that become unreachable are not removed, see [remove_unreachable_states].
+pub fn restrict(lts: &LabelledTransitionSystem, labels: &[String]) -> LabelledTransitionSystem {
+    let allowed: Vec<bool> = lts
+        .labels()
+        .iter()
+        .enumerate()
+        .map(|(label_index, name)| lts.is_hidden_label(label_index) || labels.contains(name))
+        .collect();
+
+    let result = LabelledTransitionSystem::new(
+        lts.initial_state_index(),
+        Some(lts.num_of_states()),
+        || {
+            lts.iter_states().flat_map(|state_index| {
+                lts.outgoing_transitions(state_index)
+                    .filter(|(label_index, _)| allowed[*label_index])
+                    .map(move |(label_index, to)| (state_index, label_index, to))
+            })
+        },
+        lts.labels().into(),
+        lts.hidden_labels().into(),
+    );
+
+    with_same_state_labels(result, lts)
+}
+
+/// Returns the LTS in which the given labels are hidden, i.e., renamed to tau.
+pub fn hide(lts: &LabelledTransitionSystem, labels: &[String]) -> LabelledTransitionSystem {
+    let mut hidden_labels: Vec<String> = lts.hidden_labels().into();
+    hidden_labels.extend(labels.iter().cloned());
+
+    let result = LabelledTransitionSystem::new(
+        lts.initial_state_index(),
+        Some(lts.num_of_states()),
+        || {
+            lts.iter_states().flat_map(|state_index| {
+                lts.outgoing_transitions(state_index)
+                    .map(move |(label_index, to)| (state_index, label_index, to))
+            })
+        },
+        lts.labels().into(),
+        hidden_labels,
+    );
+
+    with_same_state_labels(result, lts)
+}
+
+/// Returns the result labelled by the state labels of the given LTS, which has the same states.
+fn with_same_state_labels(
+    result: LabelledTransitionSystem,
+    lts: &LabelledTransitionSystem,
+) -> LabelledTransitionSystem {
+    match lts.state_labels() {
+        Some(state_labels) => result.with_state_labels(state_labels.clone()),
+        None => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::LtsBuilder;
+
+    use super::*;
+
+    /// Returns the transitions of the LTS with the names of the labels.
+    fn transitions(lts: &LabelledTransitionSystem) -> Vec<(StateIndex, String, StateIndex)> {
+        let mut result: Vec<(StateIndex, String, StateIndex)> = lts
+            .iter_states()
+            .flat_map(|state_index| {
+                lts.outgoing_transitions(state_index)
+                    .map(move |(label_index, to)| (state_index, lts.labels()[label_index].clone(), to))
+            })
+            .collect();
+        result.sort_unstable();
+        result
+    }
+
+    #[test]
+    fn test_remove_unreachable_states() {
+        let lts = LtsBuilder::from_transitions(&[(0, "a", 1), (1, "b", 2), (2, "c", 1), (3, "a", 0)]).finish(1);
+        let result = remove_unreachable_states(&lts);
+
+        assert_eq!(result.num_of_states(), 2);
+        assert_eq!(result.initial_state_index(), 0);
+        assert_eq!(
+            transitions(&result),
+            vec![(0, "b".to_string(), 1), (1, "c".to_string(), 0)]
+        );
+    }
+
+    #[test]
+    fn test_restrict() {
+        let lts = LtsBuilder::from_transitions(&[(1, "a", 0), (1, "b", 2), (2, "tau", 3), (3, "c", 1)]).finish(1);
+        let result = restrict(&lts, &["a".to_string(), "c".to_string()]);
+
+        assert_eq!(result.num_of_states(), 4);
+        assert_eq!(result.initial_state_index(), 1);
+        assert_eq!(
+            transitions(&result),
+            vec![
+                (1, "a".to_string(), 0),
+                (2, "tau".to_string(), 3),
+                (3, "c".to_string(), 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hide() {
+        let lts = LtsBuilder::from_transitions(&[(1, "a", 0), (1, "b", 2), (2, "c", 1)]).finish(1);
+        let result = hide(&lts, &["b".to_string()]);
+
+        assert_eq!(result.initial_state_index(), 1);
+        assert_eq!(
+            transitions(&result),
+            vec![
+                (1, "a".to_string(), 0),
+                (1, "tau".to_string(), 2),
+                (2, "c".to_string(), 1)
+            ]
+        );
+        assert!(result
+            .outgoing_transitions(1)
+            .any(|(label_index, to)| result.is_hidden_label(label_index) && to == 2));
+    }
+}
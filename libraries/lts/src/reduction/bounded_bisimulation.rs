@@ -0,0 +1,173 @@
+use log::debug;
+use log::trace;
+use rustc_hash::FxHashMap;
+
+use crate::strong_bisim_signature;
+use crate::IndexedPartition;
+use crate::LabelledTransitionSystem;
+use crate::Partition;
+use crate::SignatureBuilder;
+use crate::StateIndex;
+
+/// Computes the k-bounded strong bisimulation partitioning, in which two
+/// states are related iff they cannot be distinguished by observing at most
+/// k transitions. For k = 0 this is the partition with a single block, and
+/// for k large enough it coincides with strong bisimulation.
+pub fn strong_bisim_bounded(lts: &LabelledTransitionSystem, depth: usize) -> IndexedPartition {
+    let mut partition = IndexedPartition::new(lts.num_of_states());
+    let mut builder = SignatureBuilder::default();
+
+    for _ in 0..depth {
+        let (next_partition, stable) = refine(lts, &partition, &mut builder);
+        partition = next_partition;
+
+        if stable {
+            break;
+        }
+    }
+
+    partition
+}
+
+/// The sequence of partitions ~0, ~1, ..., ~n of k-bounded strong
+/// bisimulation, where ~n is the first partition that is stable and as such
+/// is strong bisimulation. This stratification can be used to determine after
+/// how many steps two states can first be distinguished, for example to
+/// understand why a quotient is larger than expected.
+pub struct BisimulationStratification {
+    partitions: Vec<IndexedPartition>,
+}
+
+impl BisimulationStratification {
+    /// Computes the stratification of strong bisimulation for the given LTS,
+    /// where at most max_depth refinement steps are performed when given.
+    pub fn new(lts: &LabelledTransitionSystem, max_depth: Option<usize>) -> BisimulationStratification {
+        let mut partitions = vec![IndexedPartition::new(lts.num_of_states())];
+        let mut builder = SignatureBuilder::default();
+
+        while max_depth.map_or(true, |max_depth| partitions.len() <= max_depth) {
+            let (next_partition, stable) = refine(lts, &partitions[partitions.len() - 1], &mut builder);
+            if stable {
+                break;
+            }
+
+            debug!(
+                "Depth {}, found {} blocks",
+                partitions.len(),
+                next_partition.num_of_blocks()
+            );
+            partitions.push(next_partition);
+        }
+
+        BisimulationStratification { partitions }
+    }
+
+    /// Returns the largest depth k for which the partition ~k was computed.
+    pub fn depth(&self) -> usize {
+        self.partitions.len() - 1
+    }
+
+    /// Returns the partition of k-bounded bisimulation, where the last
+    /// computed partition is returned when k exceeds the depth.
+    pub fn partition(&self, depth: usize) -> &IndexedPartition {
+        &self.partitions[depth.min(self.depth())]
+    }
+
+    /// Returns the number of blocks of the partition at every depth.
+    pub fn num_of_blocks(&self) -> Vec<usize> {
+        self.partitions
+            .iter()
+            .map(|partition| partition.num_of_blocks())
+            .collect()
+    }
+
+    /// Returns the smallest depth k at which the given states are no longer
+    /// related by k-bounded bisimulation, or None when they are related at
+    /// every computed depth.
+    pub fn distinguishing_depth(&self, state_index: StateIndex, other_state_index: StateIndex) -> Option<usize> {
+        // The partitions are successive refinements, so a binary search finds the first split.
+        let depth = self.partitions.partition_point(|partition| {
+            partition.block_number(state_index) == partition.block_number(other_state_index)
+        });
+
+        if depth < self.partitions.len() {
+            Some(depth)
+        } else {
+            None
+        }
+    }
+}
+
+/// Performs a single refinement step of strong bisimulation, and returns the
+/// next partition together with whether the given partition was already stable.
+fn refine(
+    lts: &LabelledTransitionSystem,
+    partition: &IndexedPartition,
+    builder: &mut SignatureBuilder,
+) -> (IndexedPartition, bool) {
+    // The block of a state is part of its signature such that the next partition is a refinement.
+    let mut id: FxHashMap<(usize, Vec<(usize, usize)>), usize> = FxHashMap::default();
+    let mut next_partition = IndexedPartition::new(lts.num_of_states());
+
+    for state_index in lts.iter_states() {
+        strong_bisim_signature(state_index, lts, partition, builder);
+        trace!("State {state_index} signature {:?}", builder);
+
+        let number = id.len();
+        let block_number = *id
+            .entry((partition.block_number(state_index), builder.clone()))
+            .or_insert(number);
+        next_partition.set_block(state_index, block_number);
+    }
+
+    let stable = id.len() == partition.num_of_blocks();
+    (next_partition, stable)
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+    use utilities::Timing;
+
+    use crate::random_lts;
+    use crate::strong_bisim_sigref;
+    use crate::LtsBuilder;
+
+    use super::*;
+
+    #[test]
+    fn test_distinguishing_depth() {
+        // The states 0 and 3 can only be distinguished after two steps.
+        let mut builder = LtsBuilder::new(vec![]);
+        builder.add_transition(0, "a", 1);
+        builder.add_transition(1, "b", 2);
+        builder.add_transition(3, "a", 4);
+        builder.add_transition(4, "c", 5);
+        let lts = builder.finish(0);
+
+        let stratification = BisimulationStratification::new(&lts, None);
+        assert_eq!(stratification.distinguishing_depth(0, 3), Some(2));
+        assert_eq!(stratification.distinguishing_depth(0, 1), Some(1));
+        assert_eq!(stratification.distinguishing_depth(2, 5), None);
+        assert_eq!(stratification.num_of_blocks(), [1, 4, 5]);
+
+        let bounded = BisimulationStratification::new(&lts, Some(1));
+        assert_eq!(bounded.depth(), 1);
+        assert_eq!(bounded.distinguishing_depth(0, 3), None);
+    }
+
+    #[test]
+    fn test_random_strong_bisim_bounded() {
+        let lts = random_lts(10, 3, 3);
+        let mut timing = Timing::new();
+
+        let stratification = BisimulationStratification::new(&lts, None);
+        let partition = strong_bisim_sigref(&lts, &mut timing);
+        assert_eq!(stratification.partition(stratification.depth()), &partition);
+        assert_eq!(
+            strong_bisim_bounded(&lts, lts.num_of_states()),
+            partition,
+            "The bounded bisimulation should coincide with strong bisimulation for a large enough depth"
+        );
+    }
+}
@@ -0,0 +1,932 @@
+use log::debug;
+use log::trace;
+use rustc_hash::FxHashMap;
+use utilities::Timing;
+
+use crate::branching_bisim_sigref_naive;
+use crate::combine_partition;
+use crate::preprocess_branching;
+use crate::IndexedPartition;
+use crate::LabelIndex;
+use crate::LabelledTransitionSystem;
+use crate::StateIndex;
+
+/// Computes a branching bisimulation partitioning using the partition
+/// refinement algorithm of Groote, Jansen, Keiren and Wijs, which runs in
+/// O(m log n) time.
+///
+/// The blocks are grouped into constellations, and every block is kept stable
+/// with respect to every constellation. A non-trivial constellation is split
+/// by moving one of its blocks, with at most half its states, into a new
+/// constellation, after which only the transitions into that block have to be
+/// considered. The transitions are grouped per source block, label and target
+/// constellation, such that the blocks that must be split are found without
+/// considering the other transitions. Blocks are split by searching both parts
+/// in lockstep, such that a split takes time proportional to the smaller part.
+///
+/// States that become bottom states by a split have to be checked for all the
+/// transitions of their block, which takes time proportional to their outgoing
+/// transitions and additionally to their number for every split that they
+/// cause.
+pub fn branching_bisim_constellation(lts: &LabelledTransitionSystem, timing: &mut Timing) -> IndexedPartition {
+    let mut timepre = timing.start("preprocess");
+    let (preprocessed_lts, preprocess_partition) = preprocess_branching(lts);
+    timepre.finish();
+
+    let mut time = timing.start("reduction");
+    let mut refinement = Refinement::new(&preprocessed_lts);
+    refinement.run();
+    let partition = refinement.partition();
+    time.finish();
+
+    // Combine the SCC partition with the branching bisimulation partition.
+    let combined_partition = combine_partition(preprocess_partition, &partition);
+
+    debug_assert_eq!(
+        combined_partition,
+        branching_bisim_sigref_naive(lts, timing),
+        "The resulting partition is not a branching bisimulation partition."
+    );
+
+    trace!("Final partition {combined_partition}");
+    combined_partition
+}
+
+/// The lists in which the states of a block are stored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Kind {
+    /// The bottom states that have a transition in every BLC set of their block.
+    Bottom,
+
+    /// The bottom states for which this has not been checked yet.
+    Unverified,
+
+    /// The states that have an inert outgoing transition.
+    Other,
+}
+
+/// The part of a split that a state has been assigned to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Side {
+    None,
+
+    /// The states that can reach a transition of the splitter by inert transitions.
+    Reach,
+
+    /// The states that cannot reach a transition of the splitter.
+    Avoid,
+}
+
+/// A block of the partition.
+struct Block {
+    /// The states of the block for every kind.
+    states: [Vec<StateIndex>; 3],
+
+    /// The constellation that the block belongs to.
+    constellation: usize,
+
+    /// The BLC set of the transitions from this block for every label and target constellation.
+    sets: FxHashMap<(LabelIndex, usize), usize>,
+
+    /// Indicates that the block is in the list of blocks that must be stabilised.
+    queued: bool,
+}
+
+impl Block {
+    fn new(constellation: usize) -> Block {
+        Block {
+            states: [Vec::new(), Vec::new(), Vec::new()],
+            constellation,
+            sets: FxHashMap::default(),
+            queued: false,
+        }
+    }
+
+    /// Returns the number of states in the block.
+    fn len(&self) -> usize {
+        self.states.iter().map(Vec::len).sum()
+    }
+}
+
+/// The non-inert transitions from a block with a given label into a given
+/// constellation, where the hidden transitions within the constellation of the
+/// block are omitted.
+struct BlcSet {
+    block: usize,
+    key: (LabelIndex, usize),
+    transitions: Vec<usize>,
+
+    /// Set when the block must still be split by this set, where the flag
+    /// indicates that it must also be split by the transitions with the same
+    /// label into the remainder of the constellation that was split.
+    splitter: Option<bool>,
+}
+
+/// The states from which the search for one part of a split starts.
+enum Seeds {
+    /// The sources of the transitions in the given BLC set.
+    Sources(usize),
+
+    /// The given states.
+    States(Vec<StateIndex>),
+
+    /// The bottom and unverified states of the block from the given positions onwards.
+    Bottom(usize, usize),
+}
+
+/// The search for one part of a split, which is performed in lockstep with the
+/// search for the other part.
+struct Search {
+    side: Side,
+    seeds: Seeds,
+
+    /// The number of seeds that have been considered.
+    seed: usize,
+
+    /// The states that have been found, where the incoming inert transitions of
+    /// the states before `current` have been considered, and those of the
+    /// current state up to `transition`.
+    states: Vec<StateIndex>,
+    current: usize,
+    transition: usize,
+
+    /// Set when the part has more than half the states of the block.
+    aborted: bool,
+}
+
+impl Search {
+    fn new(side: Side, seeds: Seeds) -> Search {
+        Search {
+            side,
+            seeds,
+            seed: 0,
+            states: Vec::new(),
+            current: 0,
+            transition: 0,
+            aborted: false,
+        }
+    }
+}
+
+/// The state of the partition refinement, which requires that the LTS has no
+/// cycles of hidden transitions.
+struct Refinement<'a> {
+    lts: &'a LabelledTransitionSystem,
+
+    /// The transitions (from, label, to) sorted by their source state, where the
+    /// outgoing transitions of state s start at `outgoing_start[s]`.
+    transitions: Vec<(StateIndex, LabelIndex, StateIndex)>,
+    outgoing_start: Vec<usize>,
+
+    /// The indices of the incoming transitions of every state, where those of
+    /// state s start at `incoming_start[s]` and the hidden ones are stored
+    /// first, up to `silent_end[s]`.
+    incoming: Vec<usize>,
+    incoming_start: Vec<usize>,
+    silent_end: Vec<usize>,
+
+    /// For every transition (s, a, t) the index of the counter that stores the
+    /// number of a-transitions from s into the constellation of t, and the
+    /// counter of every state, label and constellation.
+    counter: Vec<usize>,
+    counts: Vec<usize>,
+    counters: FxHashMap<(StateIndex, LabelIndex, usize), usize>,
+
+    /// The BLC set of every transition and its position in that set.
+    set: Vec<Option<usize>>,
+    set_position: Vec<usize>,
+    sets: Vec<BlcSet>,
+
+    /// The block of every state, its kind and position in the corresponding
+    /// list of that block, and its number of inert outgoing transitions.
+    block: Vec<usize>,
+    kind: Vec<Kind>,
+    position: Vec<usize>,
+    inert: Vec<usize>,
+    blocks: Vec<Block>,
+
+    /// The blocks in every constellation.
+    constellations: Vec<Vec<usize>>,
+
+    /// The constellations that consist of more than one block.
+    worklist: Vec<usize>,
+
+    /// The BLC sets that must be used as splitter.
+    splitters: Vec<usize>,
+
+    /// The blocks that have unverified bottom states.
+    unstable: Vec<usize>,
+
+    /// The part of every state during a split, and for the states without that
+    /// part the number of inert outgoing transitions that are not known to lead
+    /// into the states that cannot reach the splitter.
+    side: Vec<Side>,
+    remaining: Vec<usize>,
+    touched: Vec<StateIndex>,
+
+    /// Marks the unverified states that are counted while stabilising a block.
+    counted: Vec<bool>,
+}
+
+impl<'a> Refinement<'a> {
+    /// Initially all states are in a single block and constellation, and all
+    /// bottom states are unverified.
+    fn new(lts: &'a LabelledTransitionSystem) -> Refinement<'a> {
+        // Hidden self loops are irrelevant for branching bisimulation.
+        let mut transitions = Vec::with_capacity(lts.num_of_transitions());
+        let mut outgoing_start = Vec::with_capacity(lts.num_of_states() + 1);
+        for state_index in lts.iter_states() {
+            outgoing_start.push(transitions.len());
+            for (label_index, to) in lts.outgoing_transitions(state_index) {
+                if !lts.is_hidden_label(label_index) || to != state_index {
+                    transitions.push((state_index, label_index, to));
+                }
+            }
+        }
+        outgoing_start.push(transitions.len());
+
+        // Sort the transitions by their target state, with the hidden transitions first.
+        let mut incoming_start = vec![0; lts.num_of_states() + 1];
+        let mut num_of_silent = vec![0; lts.num_of_states()];
+        for &(_, label_index, to) in &transitions {
+            incoming_start[to + 1] += 1;
+            if lts.is_hidden_label(label_index) {
+                num_of_silent[to] += 1;
+            }
+        }
+
+        for state_index in lts.iter_states() {
+            incoming_start[state_index + 1] += incoming_start[state_index];
+        }
+
+        let silent_end: Vec<usize> = lts
+            .iter_states()
+            .map(|state_index| incoming_start[state_index] + num_of_silent[state_index])
+            .collect();
+
+        let mut incoming = vec![0; transitions.len()];
+        let mut silent_offset = incoming_start.clone();
+        let mut offset = silent_end.clone();
+        for (transition_index, &(_, label_index, to)) in transitions.iter().enumerate() {
+            if lts.is_hidden_label(label_index) {
+                incoming[silent_offset[to]] = transition_index;
+                silent_offset[to] += 1;
+            } else {
+                incoming[offset[to]] = transition_index;
+                offset[to] += 1;
+            }
+        }
+
+        let mut refinement = Refinement {
+            lts,
+            counter: vec![0; transitions.len()],
+            counts: Vec::new(),
+            counters: FxHashMap::default(),
+            set: vec![None; transitions.len()],
+            set_position: vec![0; transitions.len()],
+            sets: Vec::new(),
+            block: vec![0; lts.num_of_states()],
+            kind: vec![Kind::Other; lts.num_of_states()],
+            position: vec![0; lts.num_of_states()],
+            inert: vec![0; lts.num_of_states()],
+            blocks: vec![Block::new(0)],
+            constellations: vec![vec![0]],
+            worklist: Vec::new(),
+            splitters: Vec::new(),
+            unstable: Vec::new(),
+            side: vec![Side::None; lts.num_of_states()],
+            remaining: vec![usize::MAX; lts.num_of_states()],
+            touched: Vec::new(),
+            counted: vec![false; lts.num_of_states()],
+            transitions,
+            outgoing_start,
+            incoming,
+            incoming_start,
+            silent_end,
+        };
+
+        // Every state has a counter per outgoing label for the initial constellation.
+        for transition_index in 0..refinement.transitions.len() {
+            let (from, label_index, _) = refinement.transitions[transition_index];
+            let counter = refinement.counter_index(from, label_index, 0);
+            refinement.counts[counter] += 1;
+            refinement.counter[transition_index] = counter;
+
+            if lts.is_hidden_label(label_index) {
+                refinement.inert[from] += 1;
+            } else {
+                refinement.add_to_set(transition_index, 0, (label_index, 0), None);
+            }
+        }
+
+        for state_index in lts.iter_states() {
+            let kind = if refinement.inert[state_index] == 0 {
+                Kind::Unverified
+            } else {
+                Kind::Other
+            };
+            refinement.insert(0, state_index, kind);
+        }
+
+        refinement.queue(0);
+        refinement
+    }
+
+    /// Refines the partition until every constellation consists of a single block.
+    fn run(&mut self) {
+        self.stabilise_all();
+
+        let mut iteration = 0usize;
+        while let Some(constellation) = self.worklist.pop() {
+            if self.constellations[constellation].len() <= 1 {
+                continue;
+            }
+
+            // Move the smaller of the first two blocks into its own constellation.
+            let position = if self.blocks[self.constellations[constellation][0]].len()
+                <= self.blocks[self.constellations[constellation][1]].len()
+            {
+                0
+            } else {
+                1
+            };
+            let splitter_block = self.constellations[constellation].swap_remove(position);
+            if self.constellations[constellation].len() > 1 {
+                self.worklist.push(constellation);
+            }
+
+            let new_constellation = self.constellations.len();
+            self.constellations.push(vec![splitter_block]);
+            self.blocks[splitter_block].constellation = new_constellation;
+
+            let splitter_states: Vec<StateIndex> = self.blocks[splitter_block].states.concat();
+            for &state_index in &splitter_states {
+                for index in self.incoming_start[state_index]..self.incoming_start[state_index + 1] {
+                    let transition_index = self.incoming[index];
+                    let (from, label_index, _) = self.transitions[transition_index];
+
+                    // Move the transition to the counter of the new constellation.
+                    let counter = self.counter_index(from, label_index, new_constellation);
+                    self.counts[self.counter[transition_index]] -= 1;
+                    self.counts[counter] += 1;
+                    self.counter[transition_index] = counter;
+
+                    let from_block = self.block[from];
+                    if self.set[transition_index].is_some() {
+                        // The block must also be split by the remainder of the old constellation.
+                        self.remove_from_set(transition_index);
+                        self.add_to_set(
+                            transition_index,
+                            from_block,
+                            (label_index, new_constellation),
+                            Some(true),
+                        );
+                    } else if self.lts.is_hidden_label(label_index) && from_block != splitter_block {
+                        // A hidden transition within the old constellation is no longer constellation inert.
+                        self.add_to_set(
+                            transition_index,
+                            from_block,
+                            (label_index, new_constellation),
+                            Some(false),
+                        );
+                    }
+                }
+
+                // The hidden transitions into the remainder of the old constellation are no longer constellation inert.
+                for transition_index in self.outgoing_start[state_index]..self.outgoing_start[state_index + 1] {
+                    let (_, label_index, to) = self.transitions[transition_index];
+                    if self.lts.is_hidden_label(label_index)
+                        && self.block[to] != splitter_block
+                        && self.blocks[self.block[to]].constellation == constellation
+                    {
+                        self.add_to_set(
+                            transition_index,
+                            splitter_block,
+                            (label_index, constellation),
+                            Some(false),
+                        );
+                    }
+                }
+            }
+
+            while let Some(set_index) = self.splitters.pop() {
+                if let Some(co_split) = self.sets[set_index].splitter.take() {
+                    if !self.sets[set_index].transitions.is_empty() {
+                        self.split_splitter(set_index, co_split, constellation);
+                    }
+                }
+            }
+
+            self.stabilise_all();
+
+            debug!("Iteration {iteration}, found {} blocks", self.blocks.len());
+            iteration += 1;
+        }
+    }
+
+    /// Splits the block of the given BLC set into the states that can reach
+    /// its transitions and the other states. If `co_split` is set the former
+    /// part is also split by the transitions with the same label into the given
+    /// old constellation.
+    fn split_splitter(&mut self, set_index: usize, co_split: bool, old_constellation: usize) {
+        let block_index = self.sets[set_index].block;
+        let (label_index, constellation) = self.sets[set_index].key;
+
+        // Mark the sources, and move the bottom states among them to the front of their lists.
+        let mut sources = Vec::new();
+        let mut bottom = Vec::new();
+        let mut front = [0; 3];
+        for index in 0..self.sets[set_index].transitions.len() {
+            let from = self.transitions[self.sets[set_index].transitions[index]].0;
+            if self.side[from] == Side::None {
+                self.side[from] = Side::Reach;
+                sources.push(from);
+
+                let kind = self.kind[from];
+                if kind != Kind::Other {
+                    self.swap_to(from, front[kind as usize]);
+                    front[kind as usize] += 1;
+                    bottom.push(from);
+                }
+            }
+        }
+
+        for &state_index in &sources {
+            self.side[state_index] = Side::None;
+        }
+
+        let (reach_block, new_bottom) = self.split(
+            block_index,
+            (label_index, constellation),
+            Seeds::States(sources),
+            Seeds::Bottom(front[Kind::Bottom as usize], front[Kind::Unverified as usize]),
+        );
+
+        if co_split {
+            let key = (label_index, old_constellation);
+            if let Some(&co_set) = self.blocks[reach_block].sets.get(&key) {
+                // All bottom states of the block are either sources of the splitter or new bottom states.
+                let avoid: Vec<StateIndex> = bottom
+                    .into_iter()
+                    .chain(new_bottom)
+                    .filter(|&state_index| !self.has_transition(state_index, key))
+                    .collect();
+                self.split(reach_block, key, Seeds::Sources(co_set), Seeds::States(avoid));
+            }
+        }
+    }
+
+    /// Stabilises the queued blocks with respect to their unverified bottom states.
+    fn stabilise_all(&mut self) {
+        while let Some(block_index) = self.unstable.pop() {
+            self.blocks[block_index].queued = false;
+            self.stabilise(block_index);
+        }
+    }
+
+    /// Splits the given block until all its unverified bottom states have a
+    /// transition in every BLC set of the block, after which they are verified.
+    fn stabilise(&mut self, block_index: usize) {
+        // For every key the number of unverified states with a transition with that key, and those states.
+        let mut holders: FxHashMap<(LabelIndex, usize), (usize, Vec<StateIndex>)> = FxHashMap::default();
+        let unverified = self.blocks[block_index].states[Kind::Unverified as usize].clone();
+        self.count_sets(&unverified, &mut holders, true);
+
+        let mut pending: Vec<usize> = self.blocks[block_index].sets.values().copied().collect();
+        while let Some(set_index) = pending.pop() {
+            let key = self.sets[set_index].key;
+            let num_of_unverified = self.blocks[block_index].states[Kind::Unverified as usize].len();
+            if self.sets[set_index].transitions.is_empty()
+                || holders.get(&key).map_or(0, |(count, _)| *count) == num_of_unverified
+            {
+                continue;
+            }
+
+            // Move the unverified states with a transition in the set to the
+            // front, such that the remaining ones are the seeds of the other part.
+            let (_, states) = holders.entry(key).or_default();
+            states.retain(|&state_index| self.counted[state_index]);
+            for (position, &state_index) in states.iter().enumerate() {
+                self.swap_to(state_index, position);
+            }
+
+            let avoid = Seeds::Bottom(
+                self.blocks[block_index].states[Kind::Bottom as usize].len(),
+                states.len(),
+            );
+            let num_of_blocks = self.blocks.len();
+            let (_, new_bottom) = self.split(block_index, key, Seeds::Sources(set_index), avoid);
+
+            if self.blocks.len() > num_of_blocks {
+                // The states that were moved to the new block are no longer counted for this block.
+                let moved: Vec<StateIndex> = self.blocks[num_of_blocks].states[Kind::Unverified as usize]
+                    .iter()
+                    .copied()
+                    .filter(|&state_index| self.counted[state_index])
+                    .collect();
+                self.count_sets(&moved, &mut holders, false);
+            }
+
+            let new_bottom: Vec<StateIndex> = new_bottom
+                .into_iter()
+                .filter(|&state_index| self.block[state_index] == block_index)
+                .collect();
+            if !new_bottom.is_empty() {
+                self.count_sets(&new_bottom, &mut holders, true);
+                pending.extend(self.blocks[block_index].sets.values().copied());
+            }
+        }
+
+        for state_index in std::mem::take(&mut self.blocks[block_index].states[Kind::Unverified as usize]) {
+            self.counted[state_index] = false;
+            self.insert(block_index, state_index, Kind::Bottom);
+        }
+    }
+
+    /// Adds (or removes) the given states to the unverified states that have a
+    /// transition with the key of every BLC set, where removed states are only
+    /// unmarked.
+    fn count_sets(
+        &mut self,
+        states: &[StateIndex],
+        holders: &mut FxHashMap<(LabelIndex, usize), (usize, Vec<StateIndex>)>,
+        add: bool,
+    ) {
+        let mut keys = Vec::new();
+        for &state_index in states {
+            self.counted[state_index] = add;
+
+            keys.clear();
+            keys.extend(
+                (self.outgoing_start[state_index]..self.outgoing_start[state_index + 1]).filter_map(
+                    |transition_index| self.set[transition_index].map(|set_index| self.sets[set_index].key),
+                ),
+            );
+            keys.sort_unstable();
+            keys.dedup();
+
+            for &key in &keys {
+                let (count, holder_states) = holders.entry(key).or_default();
+                if add {
+                    *count += 1;
+                    holder_states.push(state_index);
+                } else {
+                    *count -= 1;
+                }
+            }
+        }
+    }
+
+    /// Splits the given block into the states that can reach a transition with
+    /// the given key by inert transitions and the other states, by searching
+    /// for both parts in lockstep from the given seeds. The part that is found
+    /// first is moved to a new block. Returns the block of the former part and
+    /// the states that became bottom states.
+    fn split(
+        &mut self,
+        block_index: usize,
+        key: (LabelIndex, usize),
+        reach: Seeds,
+        avoid: Seeds,
+    ) -> (usize, Vec<StateIndex>) {
+        let half = self.blocks[block_index].len() / 2;
+        let mut reach = Search::new(Side::Reach, reach);
+        let mut avoid = Search::new(Side::Avoid, avoid);
+
+        let finished = loop {
+            if !reach.aborted && self.step(&mut reach, block_index, key, half) {
+                break Side::Reach;
+            }
+
+            if !avoid.aborted && self.step(&mut avoid, block_index, key, half) {
+                break Side::Avoid;
+            }
+        };
+
+        for &state_index in reach.states.iter().chain(&avoid.states) {
+            self.side[state_index] = Side::None;
+        }
+
+        for state_index in std::mem::take(&mut self.touched) {
+            self.remaining[state_index] = usize::MAX;
+        }
+
+        let part = if finished == Side::Reach {
+            reach.states
+        } else {
+            avoid.states
+        };
+        if part.is_empty() {
+            return (block_index, Vec::new());
+        }
+
+        let (new_block_index, new_bottom) = self.move_part(block_index, &part);
+        if finished == Side::Reach {
+            (new_block_index, new_bottom)
+        } else {
+            (block_index, new_bottom)
+        }
+    }
+
+    /// Performs a constant amount of work for the given search, and returns
+    /// true iff it has found all the states of its part.
+    fn step(&mut self, search: &mut Search, block_index: usize, key: (LabelIndex, usize), half: usize) -> bool {
+        if let Some(&state_index) = search.states.get(search.current) {
+            if search.transition < self.silent_end[state_index] {
+                // Consider the next incoming inert transition of a state in this part.
+                let from = self.transitions[self.incoming[search.transition]].0;
+                search.transition += 1;
+
+                if self.block[from] == block_index && self.side[from] == Side::None {
+                    if search.side == Side::Reach {
+                        self.push(search, from, half);
+                    } else {
+                        if self.remaining[from] == usize::MAX {
+                            self.remaining[from] = self.inert[from];
+                            self.touched.push(from);
+                        }
+
+                        self.remaining[from] -= 1;
+                        if self.remaining[from] == 0 && !self.has_transition(from, key) {
+                            self.push(search, from, half);
+                        }
+                    }
+                }
+            } else {
+                search.current += 1;
+                if let Some(&next) = search.states.get(search.current) {
+                    search.transition = self.incoming_start[next];
+                }
+            }
+
+            return false;
+        }
+
+        match self.next_seed(search, block_index) {
+            Some(state_index) => {
+                debug_assert!(
+                    self.side[state_index] == Side::None || self.side[state_index] == search.side,
+                    "The parts of a split must be disjoint"
+                );
+
+                if self.side[state_index] == Side::None {
+                    self.push(search, state_index, half);
+                }
+                false
+            }
+            None => true,
+        }
+    }
+
+    /// Adds the given state to the part of the search.
+    fn push(&mut self, search: &mut Search, state_index: StateIndex, half: usize) {
+        self.side[state_index] = search.side;
+        if search.current == search.states.len() {
+            search.transition = self.incoming_start[state_index];
+        }
+
+        search.states.push(state_index);
+        search.aborted = search.states.len() > half;
+    }
+
+    /// Returns the next seed of the given search.
+    fn next_seed(&self, search: &mut Search, block_index: usize) -> Option<StateIndex> {
+        let index = search.seed;
+        search.seed += 1;
+
+        match &search.seeds {
+            Seeds::Sources(set_index) => self.sets[*set_index]
+                .transitions
+                .get(index)
+                .map(|&transition_index| self.transitions[transition_index].0),
+            Seeds::States(states) => states.get(index).copied(),
+            Seeds::Bottom(bottom, unverified) => {
+                let block = &self.blocks[block_index];
+                let num_of_bottom = block.states[Kind::Bottom as usize].len() - bottom;
+                if index < num_of_bottom {
+                    Some(block.states[Kind::Bottom as usize][bottom + index])
+                } else {
+                    block.states[Kind::Unverified as usize]
+                        .get(unverified + index - num_of_bottom)
+                        .copied()
+                }
+            }
+        }
+    }
+
+    /// Moves the given states of a block into a new block, and returns the new
+    /// block together with the states that became bottom states.
+    fn move_part(&mut self, block_index: usize, part: &[StateIndex]) -> (usize, Vec<StateIndex>) {
+        let constellation = self.blocks[block_index].constellation;
+        let new_block_index = self.blocks.len();
+        self.blocks.push(Block::new(constellation));
+
+        self.constellations[constellation].push(new_block_index);
+        if self.constellations[constellation].len() == 2 {
+            self.worklist.push(constellation);
+        }
+
+        for &state_index in part {
+            let kind = self.kind[state_index];
+            self.remove(state_index);
+            self.insert(new_block_index, state_index, kind);
+        }
+
+        // The hidden transitions between both parts are no longer inert.
+        let mut new_bottom = Vec::new();
+        for &state_index in part {
+            for transition_index in self.outgoing_start[state_index]..self.outgoing_start[state_index + 1] {
+                let (_, label_index, to) = self.transitions[transition_index];
+                if self.lts.is_hidden_label(label_index) && self.block[to] == block_index {
+                    self.remove_inert(state_index, &mut new_bottom);
+                }
+            }
+
+            for index in self.incoming_start[state_index]..self.silent_end[state_index] {
+                let from = self.transitions[self.incoming[index]].0;
+                if self.block[from] == block_index {
+                    self.remove_inert(from, &mut new_bottom);
+                }
+            }
+        }
+
+        // Move the transitions of the part into the BLC sets of the new block.
+        for &state_index in part {
+            for transition_index in self.outgoing_start[state_index]..self.outgoing_start[state_index + 1] {
+                if self.set[transition_index].is_some() {
+                    let (key, splitter) = self.remove_from_set(transition_index);
+                    self.add_to_set(transition_index, new_block_index, key, splitter);
+                }
+            }
+        }
+
+        for index in [block_index, new_block_index] {
+            if !self.blocks[index].states[Kind::Unverified as usize].is_empty() {
+                self.queue(index);
+            }
+        }
+
+        (new_block_index, new_bottom)
+    }
+
+    /// Removes an inert outgoing transition of the given state, which becomes
+    /// an unverified bottom state when it has no inert transitions left.
+    fn remove_inert(&mut self, state_index: StateIndex, new_bottom: &mut Vec<StateIndex>) {
+        self.inert[state_index] -= 1;
+        if self.inert[state_index] == 0 {
+            self.remove(state_index);
+            self.insert(self.block[state_index], state_index, Kind::Unverified);
+            new_bottom.push(state_index);
+        }
+    }
+
+    /// Adds the block to the blocks that must be stabilised.
+    fn queue(&mut self, block_index: usize) {
+        if !self.blocks[block_index].queued {
+            self.blocks[block_index].queued = true;
+            self.unstable.push(block_index);
+        }
+    }
+
+    /// Returns the index of the counter for the given state, label and constellation.
+    fn counter_index(&mut self, state_index: StateIndex, label_index: LabelIndex, constellation: usize) -> usize {
+        *self
+            .counters
+            .entry((state_index, label_index, constellation))
+            .or_insert_with(|| {
+                self.counts.push(0);
+                self.counts.len() - 1
+            })
+    }
+
+    /// Returns true iff the state has a transition with the given label into the given constellation.
+    fn has_transition(&self, state_index: StateIndex, (label_index, constellation): (LabelIndex, usize)) -> bool {
+        self.counters
+            .get(&(state_index, label_index, constellation))
+            .is_some_and(|&counter| self.counts[counter] > 0)
+    }
+
+    /// Adds the transition to the BLC set of the given block with the given key,
+    /// which is created with the given splitter flag when it does not exist.
+    fn add_to_set(
+        &mut self,
+        transition_index: usize,
+        block_index: usize,
+        key: (LabelIndex, usize),
+        splitter: Option<bool>,
+    ) {
+        let set_index = match self.blocks[block_index].sets.get(&key) {
+            Some(&set_index) => set_index,
+            None => {
+                let set_index = self.sets.len();
+                self.sets.push(BlcSet {
+                    block: block_index,
+                    key,
+                    transitions: Vec::new(),
+                    splitter,
+                });
+                self.blocks[block_index].sets.insert(key, set_index);
+
+                if splitter.is_some() {
+                    self.splitters.push(set_index);
+                }
+                set_index
+            }
+        };
+
+        self.set[transition_index] = Some(set_index);
+        self.set_position[transition_index] = self.sets[set_index].transitions.len();
+        self.sets[set_index].transitions.push(transition_index);
+    }
+
+    /// Removes the transition from its BLC set, and returns the key and splitter flag of that set.
+    fn remove_from_set(&mut self, transition_index: usize) -> ((LabelIndex, usize), Option<bool>) {
+        let set_index = self.set[transition_index]
+            .take()
+            .expect("The transition should be in a BLC set");
+        let set = &mut self.sets[set_index];
+
+        let position = self.set_position[transition_index];
+        set.transitions.swap_remove(position);
+        if let Some(&moved) = set.transitions.get(position) {
+            self.set_position[moved] = position;
+        }
+
+        if set.transitions.is_empty() {
+            self.blocks[set.block].sets.remove(&set.key);
+        }
+
+        (set.key, set.splitter)
+    }
+
+    /// Inserts the state into the list of the given kind of the given block.
+    fn insert(&mut self, block_index: usize, state_index: StateIndex, kind: Kind) {
+        let states = &mut self.blocks[block_index].states[kind as usize];
+        self.block[state_index] = block_index;
+        self.kind[state_index] = kind;
+        self.position[state_index] = states.len();
+        states.push(state_index);
+    }
+
+    /// Removes the state from the list of its block in constant time.
+    fn remove(&mut self, state_index: StateIndex) {
+        let states = &mut self.blocks[self.block[state_index]].states[self.kind[state_index] as usize];
+        let position = self.position[state_index];
+        states.swap_remove(position);
+        if let Some(&moved) = states.get(position) {
+            self.position[moved] = position;
+        }
+    }
+
+    /// Swaps the state with the state at the given position in the list of its block.
+    fn swap_to(&mut self, state_index: StateIndex, position: usize) {
+        let states = &mut self.blocks[self.block[state_index]].states[self.kind[state_index] as usize];
+        let other = states[position];
+        states.swap(position, self.position[state_index]);
+        self.position[other] = self.position[state_index];
+        self.position[state_index] = position;
+    }
+
+    /// Returns the resulting partition.
+    fn partition(self) -> IndexedPartition {
+        let num_of_blocks = self.blocks.len();
+        IndexedPartition::with_partition(self.block, num_of_blocks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+    use utilities::Timing;
+
+    use crate::branching_bisim_sigref;
+    use crate::random_lts;
+    use crate::LtsBuilder;
+    use crate::Partition;
+
+    use super::*;
+
+    #[test]
+    fn test_random_branching_bisim_constellation() {
+        for _ in 0..20 {
+            let lts = random_lts(50, 3, 3);
+            let mut timing = Timing::new();
+
+            assert_eq!(
+                branching_bisim_constellation(&lts, &mut timing),
+                branching_bisim_sigref(&lts, &mut timing)
+            );
+        }
+    }
+
+    #[test]
+    fn test_branching_bisim_constellation_new_bottom_states() {
+        // State 0 becomes a bottom state when it is split from state 1 by its b-transition.
+        let lts = LtsBuilder::from_transitions(&[(0, "tau", 1), (0, "b", 2), (1, "a", 2)]).finish(0);
+        let mut timing = Timing::new();
+
+        let partition = branching_bisim_constellation(&lts, &mut timing);
+        assert_eq!(partition, branching_bisim_sigref(&lts, &mut timing));
+        assert_eq!(partition.num_of_blocks(), 3);
+    }
+}
@@ -8,18 +8,30 @@
 
 //mod strong_bisim_partition;
 mod block_partition;
+mod bounded_bisimulation;
+mod constellation_refinement;
 mod indexed_partition;
+mod paige_tarjan;
 mod quotient;
 mod scc_decomposition;
 mod signature_refinement;
+mod signature_refinement_parallel;
 mod signatures;
+mod simulation;
 mod sort_topological;
+mod weak_bisimulation;
 
 //pub use strong_bisim_partition::*;
 pub use block_partition::*;
+pub use bounded_bisimulation::*;
+pub use constellation_refinement::*;
 pub use indexed_partition::*;
+pub use paige_tarjan::*;
 pub use quotient::*;
 pub use scc_decomposition::*;
 pub use signature_refinement::*;
+pub use signature_refinement_parallel::*;
 pub use signatures::*;
+pub use simulation::*;
 pub use sort_topological::*;
+pub use weak_bisimulation::*;
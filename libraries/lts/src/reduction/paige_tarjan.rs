@@ -0,0 +1,271 @@
+use log::debug;
+use log::trace;
+use rustc_hash::FxHashMap;
+use utilities::Timing;
+
+use crate::IndexedPartition;
+use crate::LabelIndex;
+use crate::LabelledTransitionSystem;
+use crate::StateIndex;
+
+/// Computes a strong bisimulation partitioning using the three-way splitting
+/// algorithm of Paige and Tarjan, which runs in O(m log n) time.
+///
+/// This is an independent implementation from the signature refinement, and
+/// as such is also used to check the results of the latter.
+pub fn strong_bisim_paige_tarjan(lts: &LabelledTransitionSystem, timing: &mut Timing) -> IndexedPartition {
+    let mut time = timing.start("reduction");
+    let mut refinement = PaigeTarjan::new(lts);
+    refinement.run();
+    let partition = refinement.partition();
+    time.finish();
+
+    trace!("Final partition {partition}");
+    partition
+}
+
+/// The state of the Paige-Tarjan partition refinement.
+struct PaigeTarjan<'a> {
+    lts: &'a LabelledTransitionSystem,
+
+    /// The incoming transitions (label, from) of every state, stored
+    /// consecutively where the incoming transitions of state s start at
+    /// `incoming_start[s]`.
+    incoming: Vec<(LabelIndex, StateIndex)>,
+    incoming_start: Vec<usize>,
+
+    /// For every incoming transition (s, a, t) the index of the counter that
+    /// stores the number of a-transitions from s into the constellation of t.
+    counter: Vec<usize>,
+    counts: Vec<usize>,
+
+    /// The block of every state and its position in that block.
+    block: Vec<usize>,
+    position: Vec<usize>,
+
+    /// The states in every block.
+    blocks: Vec<Vec<StateIndex>>,
+
+    /// The constellation of every block.
+    constellation: Vec<usize>,
+
+    /// The blocks in every constellation.
+    constellations: Vec<Vec<usize>>,
+
+    /// The constellations that consist of more than one block.
+    worklist: Vec<usize>,
+}
+
+impl<'a> PaigeTarjan<'a> {
+    /// Initially all states are in a single block and constellation.
+    fn new(lts: &'a LabelledTransitionSystem) -> PaigeTarjan<'a> {
+        // Sort the transitions by their target state.
+        let mut incoming_start = vec![0; lts.num_of_states() + 1];
+        for state_index in lts.iter_states() {
+            for (_, to) in lts.outgoing_transitions(state_index) {
+                incoming_start[to + 1] += 1;
+            }
+        }
+
+        for state_index in lts.iter_states() {
+            incoming_start[state_index + 1] += incoming_start[state_index];
+        }
+
+        let mut incoming = vec![(0, 0); lts.num_of_transitions()];
+        let mut counter = vec![0; lts.num_of_transitions()];
+        let mut counts = Vec::new();
+        let mut offset = incoming_start.clone();
+
+        // Every state has a counter per outgoing label for the initial constellation.
+        let mut state_counter: FxHashMap<LabelIndex, usize> = FxHashMap::default();
+        for state_index in lts.iter_states() {
+            state_counter.clear();
+
+            for (label_index, to) in lts.outgoing_transitions(state_index) {
+                let index = *state_counter.entry(label_index).or_insert_with(|| {
+                    counts.push(0);
+                    counts.len() - 1
+                });
+                counts[index] += 1;
+
+                incoming[offset[to]] = (label_index, state_index);
+                counter[offset[to]] = index;
+                offset[to] += 1;
+            }
+        }
+
+        PaigeTarjan {
+            lts,
+            incoming,
+            incoming_start,
+            counter,
+            counts,
+            block: vec![0; lts.num_of_states()],
+            position: lts.iter_states().collect(),
+            blocks: vec![lts.iter_states().collect()],
+            constellation: vec![0],
+            constellations: vec![vec![0]],
+            worklist: Vec::new(),
+        }
+    }
+
+    /// Refines the partition until every constellation consists of a single block.
+    fn run(&mut self) {
+        // Make the initial block stable with respect to the initial constellation.
+        let mut sources: Vec<Vec<StateIndex>> = vec![Vec::new(); self.lts.num_of_labels()];
+        for state_index in self.lts.iter_states() {
+            for (label_index, _) in self.lts.outgoing_transitions(state_index) {
+                if sources[label_index].last() != Some(&state_index) {
+                    sources[label_index].push(state_index);
+                }
+            }
+        }
+
+        for states in &sources {
+            self.split_marked(states);
+        }
+
+        let mut iteration = 0usize;
+        let mut new_counter: Vec<Option<usize>> = vec![None; self.lts.num_of_states()];
+        let mut old_counter: Vec<usize> = vec![0; self.lts.num_of_states()];
+
+        while let Some(constellation) = self.worklist.pop() {
+            if self.constellations[constellation].len() <= 1 {
+                continue;
+            }
+
+            // Move the smaller of the first two blocks into its own constellation.
+            let position = if self.blocks[self.constellations[constellation][0]].len()
+                <= self.blocks[self.constellations[constellation][1]].len()
+            {
+                0
+            } else {
+                1
+            };
+            let splitter = self.constellations[constellation].swap_remove(position);
+
+            let new_constellation = self.constellations.len();
+            self.constellations.push(vec![splitter]);
+            self.constellation[splitter] = new_constellation;
+
+            if self.constellations[constellation].len() > 1 {
+                self.worklist.push(constellation);
+            }
+
+            // Gather the incoming transitions of the splitter per label.
+            let mut transitions: FxHashMap<LabelIndex, Vec<usize>> = FxHashMap::default();
+            for &state_index in &self.blocks[splitter] {
+                for transition in self.incoming_start[state_index]..self.incoming_start[state_index + 1] {
+                    transitions
+                        .entry(self.incoming[transition].0)
+                        .or_default()
+                        .push(transition);
+                }
+            }
+
+            for transitions in transitions.values() {
+                // Move the transitions into the splitter to new counters.
+                let mut states = Vec::new();
+                for &transition in transitions {
+                    let from = self.incoming[transition].1;
+                    let index = *new_counter[from].get_or_insert_with(|| {
+                        states.push(from);
+                        old_counter[from] = self.counter[transition];
+                        self.counts.push(0);
+                        self.counts.len() - 1
+                    });
+
+                    self.counts[self.counter[transition]] -= 1;
+                    self.counts[index] += 1;
+                    self.counter[transition] = index;
+                }
+
+                // Split into the states that only reach the splitter, and the
+                // states that also reach the remainder of the constellation.
+                let counts = &self.counts;
+                let (reach_both, reach_splitter): (Vec<StateIndex>, Vec<StateIndex>) = states
+                    .iter()
+                    .partition(|&&state_index| counts[old_counter[state_index]] > 0);
+
+                self.split_marked(&reach_both);
+                self.split_marked(&reach_splitter);
+
+                for state_index in states {
+                    new_counter[state_index] = None;
+                }
+            }
+
+            iteration += 1;
+            debug!("Iteration {iteration}, found {} blocks", self.blocks.len());
+        }
+    }
+
+    /// Splits every block that contains some of the given states into the
+    /// given states and the remaining states.
+    fn split_marked(&mut self, states: &[StateIndex]) {
+        let mut per_block: FxHashMap<usize, Vec<StateIndex>> = FxHashMap::default();
+        for &state_index in states {
+            per_block.entry(self.block[state_index]).or_default().push(state_index);
+        }
+
+        for (block_index, states) in per_block {
+            if states.len() == self.blocks[block_index].len() {
+                // All states in the block are marked.
+                continue;
+            }
+
+            let new_block_index = self.blocks.len();
+            let mut new_block = Vec::with_capacity(states.len());
+            for state_index in states {
+                // Remove the state from its current block.
+                let position = self.position[state_index];
+                self.blocks[block_index].swap_remove(position);
+                if let Some(&moved) = self.blocks[block_index].get(position) {
+                    self.position[moved] = position;
+                }
+
+                self.block[state_index] = new_block_index;
+                self.position[state_index] = new_block.len();
+                new_block.push(state_index);
+            }
+            self.blocks.push(new_block);
+
+            let constellation = self.constellation[block_index];
+            self.constellation.push(constellation);
+            self.constellations[constellation].push(new_block_index);
+            if self.constellations[constellation].len() == 2 {
+                self.worklist.push(constellation);
+            }
+        }
+    }
+
+    /// Returns the resulting partition.
+    fn partition(self) -> IndexedPartition {
+        let num_of_blocks = self.blocks.len();
+        IndexedPartition::with_partition(self.block, num_of_blocks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+    use utilities::Timing;
+
+    use crate::random_lts;
+    use crate::strong_bisim_sigref_naive;
+
+    use super::*;
+
+    #[test]
+    fn test_random_strong_bisim_paige_tarjan() {
+        for _ in 0..20 {
+            let lts = random_lts(50, 3, 3);
+            let mut timing = Timing::new();
+
+            assert_eq!(
+                strong_bisim_paige_tarjan(&lts, &mut timing),
+                strong_bisim_sigref_naive(&lts, &mut timing)
+            );
+        }
+    }
+}
@@ -60,9 +60,16 @@ pub trait Partition {
     }
 }
 
+/// Returns the quotient of the LTS for the given partition, in which every
+/// block is replaced by a single state and duplicate transitions are removed.
+pub fn quotient(lts: &LabelledTransitionSystem, partition: &impl Partition) -> LabelledTransitionSystem {
+    quotient_lts(lts, partition, false)
+}
+
 /// Returns a new LTS based on the given partition.
 ///
-/// All states in a single block are replaced by a single representative state.
+/// All states in a single block are replaced by a single representative state,
+/// which is labelled by the state labels of all states in the block.
 pub fn quotient_lts(
     lts: &LabelledTransitionSystem,
     partition: &impl Partition,
@@ -73,7 +80,7 @@ pub fn quotient_lts(
     let mut transitions: Vec<(usize, usize, usize)> = Vec::default();
 
     for state_index in lts.iter_states() {
-        for &(label, to) in lts.outgoing_transitions(state_index) {
+        for (label, to) in lts.outgoing_transitions(state_index) {
             let block = partition.block_number(state_index);
             let to_block = partition.block_number(to);
 
@@ -95,13 +102,22 @@ pub fn quotient_lts(
     transitions.sort_unstable();
     transitions.dedup();
 
-    let result = LabelledTransitionSystem::new(
+    let mut result = LabelledTransitionSystem::new(
         partition.block_number(lts.initial_state_index()),
         Some(partition.num_of_blocks()),
         || transitions.iter().cloned(),
         lts.labels().into(),
         lts.hidden_labels().into()
     );
+
+    // Every block is labelled by the state vectors of all its states.
+    if let Some(state_labels) = lts.state_labels() {
+        result = result.with_state_labels(
+            state_labels.map_states(partition.num_of_blocks(), |state_index| {
+                Some(partition.block_number(state_index))
+            }),
+        );
+    }
     debug!("Time quotient: {:.3}s", start.elapsed().as_secs_f64());
     result
 }
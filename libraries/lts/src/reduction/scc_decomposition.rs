@@ -11,7 +11,7 @@ use crate::Partition;
 
 /// Computes the strongly connected tau component partitioning of the given LTS.
 pub fn tau_scc_decomposition(lts: &LabelledTransitionSystem) -> IndexedPartition {
-    let partition = scc_decomposition(lts, &|_, label_index, _| lts.is_hidden_label(label_index));
+    let partition = scc_decomposition_with(lts, &|_, label_index, _| lts.is_hidden_label(label_index));
     if cfg!(debug_assertions) {
         let quotient_lts = quotient_lts(lts, &partition, true);
         debug_assert!(!has_tau_loop(&quotient_lts), "The SCC decomposition contains tau-loops");
@@ -20,7 +20,14 @@ pub fn tau_scc_decomposition(lts: &LabelledTransitionSystem) -> IndexedPartition
 }
 
 /// Computes the strongly connected component partitioning of the given LTS.
-pub fn scc_decomposition<F>(lts: &LabelledTransitionSystem, filter: &F) -> IndexedPartition
+pub fn scc_decomposition(lts: &LabelledTransitionSystem) -> IndexedPartition {
+    scc_decomposition_with(lts, &|_, _, _| true)
+}
+
+/// Computes the strongly connected component partitioning of the given LTS,
+/// where only the (from, label, to) transitions that satisfy the filter are
+/// considered.
+pub fn scc_decomposition_with<F>(lts: &LabelledTransitionSystem, filter: &F) -> IndexedPartition
 where
     F: Fn(usize, usize, usize) -> bool,
 {
@@ -108,13 +115,13 @@ fn strongly_connect<F>(
 
     // Consider successors of the current state.
     for (label_index, to_index) in lts.outgoing_transitions(state_index) {
-        if filter(state_index, *label_index, *to_index) {
-            if let Some(meta) = &mut state_info[*to_index] {
+        if filter(state_index, label_index, to_index) {
+            if let Some(meta) = &mut state_info[to_index] {
                 if meta.on_stack {
                     // Successor w is in stack S and hence in the current SCC
                     // If w is not on stack, then (v, w) is an edge pointing to an SCC already found and must be ignored
                     // v.lowlink := min(v.lowlink, w.lowlink);
-                    let w_index = state_info[*to_index]
+                    let w_index = state_info[to_index]
                         .as_ref()
                         .expect("The state must be visited in the recursive call")
                         .index;
@@ -124,7 +131,7 @@ fn strongly_connect<F>(
             } else {
                 // Successor w has not yet been visited; recurse on it
                 strongly_connect(
-                    *to_index,
+                    to_index,
                     lts,
                     filter,
                     partition,
@@ -135,7 +142,7 @@ fn strongly_connect<F>(
                 );
 
                 // v.lowlink := min(v.lowlink, w.lowlink);
-                let w_lowlink = state_info[*to_index]
+                let w_lowlink = state_info[to_index]
                     .as_ref()
                     .expect("The state must be visited in the recursive call")
                     .lowlink;
@@ -163,6 +170,15 @@ fn strongly_connect<F>(
     }
 }
 
+/// Returns the LTS in which every strongly connected tau component is replaced
+/// by a single state, together with the partition that maps the states of the
+/// given LTS to the states of the resulting LTS. The hidden self loops that
+/// are introduced by this are removed, so the result has no tau-loops.
+pub fn eliminate_tau_loops(lts: &LabelledTransitionSystem) -> (LabelledTransitionSystem, IndexedPartition) {
+    let partition = tau_scc_decomposition(lts);
+    (quotient_lts(lts, &partition, true), partition)
+}
+
 /// Returns true iff the labelled transition system has tau-loops.
 pub fn has_tau_loop(lts: &LabelledTransitionSystem) -> bool {
     sort_topological(lts, |label_index, _| lts.is_hidden_label(label_index), false).is_err()
@@ -190,9 +206,9 @@ mod tests {
         // Depth first search to find all reachable states.
         while let Some(inner_state_index) = stack.pop() {
             for (_, to_index) in lts.outgoing_transitions(inner_state_index) {
-                if filter(inner_state_index, 0, *to_index) && !visited[*to_index] {
-                    visited[*to_index] = true;
-                    stack.push(*to_index);
+                if filter(inner_state_index, 0, to_index) && !visited[to_index] {
+                    visited[to_index] = true;
+                    stack.push(to_index);
                 }
             }
         }
@@ -244,9 +260,44 @@ mod tests {
             None,
             || transitions.iter().cloned(),
             vec!["tau".into(), "a".into()],
-            vec!["tau".into()]
+            vec!["tau".into()],
         );
 
         let _ = tau_scc_decomposition(&lts);
     }
+
+    #[test]
+    fn test_scc_decomposition() {
+        let transitions = [(0, 1, 1), (1, 2, 0), (1, 1, 2), (2, 1, 3), (3, 2, 2)];
+
+        let lts = LabelledTransitionSystem::new(
+            0,
+            None,
+            || transitions.iter().cloned(),
+            vec!["tau".into(), "a".into(), "b".into()],
+            vec!["tau".into()],
+        );
+
+        let partition = scc_decomposition(&lts);
+        assert_eq!(partition.num_of_blocks(), 2);
+        assert_eq!(partition.block_number(0), partition.block_number(1));
+        assert_eq!(partition.block_number(2), partition.block_number(3));
+        assert_ne!(partition.block_number(0), partition.block_number(2));
+
+        // None of the transitions are hidden.
+        assert_eq!(tau_scc_decomposition(&lts).num_of_blocks(), 4);
+    }
+
+    #[test]
+    fn test_random_eliminate_tau_loops() {
+        let lts = random_lts(10, 3, 3);
+        let (reduction, partition) = eliminate_tau_loops(&lts);
+
+        assert!(!has_tau_loop(&reduction), "The result should not contain tau-loops");
+        assert_eq!(reduction.num_of_states(), partition.num_of_blocks());
+        assert_eq!(
+            reduction.initial_state_index(),
+            partition.block_number(lts.initial_state_index())
+        );
+    }
 }
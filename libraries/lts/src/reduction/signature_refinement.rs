@@ -12,6 +12,7 @@ use crate::branching_bisim_signature_inductive;
 use crate::branching_bisim_signature_sorted;
 use crate::combine_partition;
 use crate::preprocess_branching;
+use crate::strong_bisim_paige_tarjan;
 use crate::strong_bisim_signature;
 use crate::BlockPartition;
 use crate::BlockPartitionBuilder;
@@ -35,7 +36,7 @@ pub fn strong_bisim_sigref(lts: &LabelledTransitionSystem, timing: &mut Timing)
 
     debug_assert_eq!(
         partition,
-        strong_bisim_sigref_naive(lts, timing),
+        strong_bisim_paige_tarjan(lts, timing),
         "The resulting partition is not a valid strong bisimulation partition."
     );
 
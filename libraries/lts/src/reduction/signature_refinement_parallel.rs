@@ -0,0 +1,166 @@
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+use dashmap::DashMap;
+use log::debug;
+use log::trace;
+use rayon::prelude::*;
+use rustc_hash::FxBuildHasher;
+use rustc_hash::FxHashSet;
+use utilities::Timing;
+
+use crate::branching_bisim_signature;
+use crate::combine_partition;
+use crate::preprocess_branching;
+use crate::strong_bisim_signature;
+use crate::IndexedPartition;
+use crate::LabelledTransitionSystem;
+use crate::SignatureBuilder;
+
+/// Computes a strong bisimulation partitioning using signature refinement,
+/// where the signatures are computed in parallel.
+pub fn strong_bisim_sigref_parallel(lts: &LabelledTransitionSystem, timing: &mut Timing) -> IndexedPartition {
+    let mut time = timing.start("reduction");
+    let partition = signature_refinement_parallel(
+        lts,
+        || (),
+        |state_index, partition, builder, _| {
+            strong_bisim_signature(state_index, lts, partition, builder);
+        },
+    );
+
+    time.finish();
+    partition
+}
+
+/// Computes a branching bisimulation partitioning using signature refinement,
+/// where the signatures are computed in parallel.
+pub fn branching_bisim_sigref_parallel(lts: &LabelledTransitionSystem, timing: &mut Timing) -> IndexedPartition {
+    let mut timepre = timing.start("preprocess");
+    let (preprocessed_lts, preprocess_partition) = preprocess_branching(lts);
+    timepre.finish();
+
+    let mut time = timing.start("reduction");
+    let partition = signature_refinement_parallel(
+        &preprocessed_lts,
+        || (FxHashSet::default(), Vec::new()),
+        |state_index, partition, builder, (visited, stack)| {
+            branching_bisim_signature(state_index, &preprocessed_lts, partition, builder, visited, stack);
+        },
+    );
+    time.finish();
+
+    // Combine the SCC partition with the branching bisimulation partition.
+    let combined_partition = combine_partition(preprocess_partition, &partition);
+
+    trace!("Final partition {combined_partition}");
+    combined_partition
+}
+
+/// Signature refinement where in every iteration the signatures of all states
+/// are computed in parallel.
+///
+/// The signature function is called for each state and should fill the
+/// signature builder with the signature of the state. The `init` function
+/// creates the thread local data that is passed to the signature function,
+/// for example to avoid reallocations of the structures used to compute the
+/// signature. The block numbers of the next partition are assigned through a
+/// concurrent map from signatures to block numbers, which means that the
+/// numbering of the blocks depends on the scheduling of the threads.
+fn signature_refinement_parallel<F, I, T>(lts: &LabelledTransitionSystem, init: I, signature: F) -> IndexedPartition
+where
+    F: Fn(usize, &IndexedPartition, &mut SignatureBuilder, &mut T) + Sync,
+    I: Fn() -> T + Sync,
+    T: Send,
+{
+    trace!("{:?}", lts);
+
+    // Maps every signature to the block number in the next partition.
+    let id: DashMap<SignatureBuilder, usize, FxBuildHasher> = DashMap::default();
+    let num_of_blocks = AtomicUsize::new(0);
+
+    // Put all the states in the initial partition { S }.
+    let mut partition = IndexedPartition::new(lts.num_of_states());
+
+    // Refine partitions until stable.
+    let mut old_count = 1;
+    let mut iteration = 0;
+
+    loop {
+        id.clear();
+        num_of_blocks.store(0, Ordering::Relaxed);
+
+        let blocks: Vec<usize> = (0..lts.num_of_states())
+            .into_par_iter()
+            .map_init(
+                || (SignatureBuilder::default(), init()),
+                |(builder, local), state_index| {
+                    // Compute the signature of a single state
+                    signature(state_index, &partition, builder, local);
+
+                    trace!("State {state_index} signature {:?}", builder);
+
+                    // Only clone the signature when it has not been seen before.
+                    if let Some(index) = id.get(builder.as_slice()) {
+                        *index
+                    } else {
+                        *id.entry(builder.clone())
+                            .or_insert_with(|| num_of_blocks.fetch_add(1, Ordering::Relaxed))
+                    }
+                },
+            )
+            .collect();
+
+        let new_count = id.len();
+        partition = IndexedPartition::with_partition(blocks, new_count);
+        iteration += 1;
+
+        debug!("Iteration {iteration}, found {new_count} blocks");
+        debug_assert!(
+            iteration <= lts.num_of_states().max(2),
+            "There can never be more splits than number of states, but at least two iterations for stability"
+        );
+
+        if new_count == old_count {
+            break;
+        }
+        old_count = new_count;
+    }
+
+    trace!("Refinement partition {partition}");
+    partition
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+    use utilities::Timing;
+
+    use crate::branching_bisim_sigref_naive;
+    use crate::random_lts;
+    use crate::strong_bisim_sigref_naive;
+
+    use super::*;
+
+    #[test]
+    fn test_random_strong_bisim_sigref_parallel() {
+        let lts = random_lts(100, 3, 3);
+        let mut timing = Timing::new();
+
+        assert_eq!(
+            strong_bisim_sigref_parallel(&lts, &mut timing),
+            strong_bisim_sigref_naive(&lts, &mut timing)
+        );
+    }
+
+    #[test]
+    fn test_random_branching_bisim_sigref_parallel() {
+        let lts = random_lts(100, 3, 3);
+        let mut timing = Timing::new();
+
+        assert_eq!(
+            branching_bisim_sigref_parallel(&lts, &mut timing),
+            branching_bisim_sigref_naive(&lts, &mut timing)
+        );
+    }
+}
@@ -7,11 +7,10 @@ use crate::LabelledTransitionSystem;
 use crate::Partition;
 use crate::StateIndex;
 
-use super::quotient_lts;
+use super::eliminate_tau_loops;
 use super::reorder_partition;
 use super::reorder_states;
 use super::sort_topological;
-use super::tau_scc_decomposition;
 use super::BlockPartition;
 use super::IndexedPartition;
 
@@ -98,7 +97,7 @@ pub fn strong_bisim_signature(
     builder.clear();
 
     for (label, to) in lts.outgoing_transitions(state_index) {
-        builder.push((*label, partition.block_number(*to)));
+        builder.push((label, partition.block_number(to)));
     }
 
     // Compute the flat signature, which has Hash and is more compact.
@@ -128,20 +127,20 @@ pub fn branching_bisim_signature(
         visited.insert(inner_state_index);
 
         for (label_index, to_index) in lts.outgoing_transitions(inner_state_index) {
-            if lts.is_hidden_label(*label_index) {
-                if partition.block_number(state_index) == partition.block_number(*to_index) {
+            if lts.is_hidden_label(label_index) {
+                if partition.block_number(state_index) == partition.block_number(to_index) {
                     // Explore the outgoing state as well, still tau path in same block
-                    if !visited.contains(to_index) {
-                        visited.insert(*to_index);
-                        stack.push(*to_index);
+                    if !visited.contains(&to_index) {
+                        visited.insert(to_index);
+                        stack.push(to_index);
                     }
                 } else {
                     //  pi(s) != pi(t)
-                    builder.push((*label_index, partition.block_number(*to_index)));
+                    builder.push((label_index, partition.block_number(to_index)));
                 }
             } else {
                 // (a != tau) This is a visible action only reachable from tau paths with equal signatures.
-                builder.push((*label_index, partition.block_number(*to_index)));
+                builder.push((label_index, partition.block_number(to_index)));
             }
         }
     }
@@ -161,7 +160,7 @@ pub fn branching_bisim_signature_sorted(
 ) {
     builder.clear();
 
-    for &(label_index, to) in lts.outgoing_transitions(state_index) {
+    for (label_index, to) in lts.outgoing_transitions(state_index) {
         let to_block = partition.block_number(to);
 
         if partition.block_number(state_index) == to_block {
@@ -193,7 +192,7 @@ pub fn branching_bisim_signature_inductive(
     builder.clear();
 
     let num_act: usize = lts.num_of_labels(); //this label index does not occur.
-    for &(label_index, to) in lts.outgoing_transitions(state_index) {
+    for (label_index, to) in lts.outgoing_transitions(state_index) {
         let to_block = partition.block_number(to);
 
         if partition.block_number(state_index) == to_block {
@@ -217,8 +216,7 @@ pub fn branching_bisim_signature_inductive(
 /// Perform the preprocessing necessary for branching bisimulation with the
 /// sorted signature see `branching_bisim_signature_sorted`.
 pub fn preprocess_branching(lts: &LabelledTransitionSystem) -> (LabelledTransitionSystem, IndexedPartition) {
-    let scc_partition = tau_scc_decomposition(lts);
-    let tau_loop_free_lts = quotient_lts(lts, &scc_partition, true);
+    let (tau_loop_free_lts, scc_partition) = eliminate_tau_loops(lts);
 
     // Sort the states according to the topological order of the tau transitions.
     let topological_permutation = sort_topological(
@@ -0,0 +1,294 @@
+use log::debug;
+use utilities::Timing;
+
+use crate::disjoint_union;
+use crate::IncomingTransitions;
+use crate::IndexedPartition;
+use crate::LabelIndex;
+use crate::LabelledTransitionSystem;
+use crate::StateIndex;
+
+/// A preorder on the states of a labelled transition system, stored as a
+/// dense relation.
+pub struct Preorder {
+    num_of_states: usize,
+    relation: Vec<bool>,
+}
+
+impl Preorder {
+    /// Creates a relation in which all states are related.
+    fn new(num_of_states: usize) -> Preorder {
+        Preorder {
+            num_of_states,
+            relation: vec![true; num_of_states * num_of_states],
+        }
+    }
+
+    /// Returns true iff the first state is smaller than the second state.
+    pub fn contains(&self, state_index: StateIndex, other_state_index: StateIndex) -> bool {
+        self.relation[state_index * self.num_of_states + other_state_index]
+    }
+
+    /// Returns the partition in which two states are in the same block iff
+    /// they are related in both directions.
+    pub fn equivalence(&self) -> IndexedPartition {
+        let mut partition = IndexedPartition::new(self.num_of_states);
+        let mut representatives: Vec<StateIndex> = Vec::new();
+
+        for state_index in 0..self.num_of_states {
+            let block = representatives
+                .iter()
+                .position(|&other| self.contains(state_index, other) && self.contains(other, state_index))
+                .unwrap_or_else(|| {
+                    representatives.push(state_index);
+                    representatives.len() - 1
+                });
+
+            partition.set_block(state_index, block);
+        }
+
+        partition
+    }
+
+    fn remove(&mut self, state_index: StateIndex, other_state_index: StateIndex) {
+        self.relation[state_index * self.num_of_states + other_state_index] = false;
+    }
+}
+
+/// Computes the largest (strong) simulation preorder, in which a state is
+/// smaller than another state iff the latter can simulate the former.
+///
+/// The relation is refined by rechecking the predecessors of every pair that
+/// is removed, starting from the relation in which all states are related.
+pub fn simulation_preorder(lts: &LabelledTransitionSystem) -> Preorder {
+    let mut preorder = Preorder::new(lts.num_of_states());
+    let incoming = IncomingTransitions::new(lts);
+
+    let is_simulated = |preorder: &Preorder, state_index: StateIndex, other_state_index: StateIndex| {
+        lts.outgoing_transitions(state_index).all(|(label_index, to)| {
+            lts.outgoing_transitions(other_state_index)
+                .any(|(other_label_index, other_to)| {
+                    other_label_index == label_index && preorder.contains(to, other_to)
+                })
+        })
+    };
+
+    let mut worklist = Vec::new();
+    for state_index in lts.iter_states() {
+        for other_state_index in lts.iter_states() {
+            if !is_simulated(&preorder, state_index, other_state_index) {
+                preorder.remove(state_index, other_state_index);
+                worklist.push((state_index, other_state_index));
+            }
+        }
+    }
+
+    // The pairs of predecessors with the same label might no longer be related.
+    while let Some((state_index, other_state_index)) = worklist.pop() {
+        for &(label_index, from) in incoming.incoming_transitions(state_index) {
+            for &(other_label_index, other_from) in incoming.incoming_transitions(other_state_index) {
+                if label_index == other_label_index
+                    && preorder.contains(from, other_from)
+                    && !is_simulated(&preorder, from, other_from)
+                {
+                    preorder.remove(from, other_from);
+                    worklist.push((from, other_from));
+                }
+            }
+        }
+    }
+
+    preorder
+}
+
+/// Computes the largest coupled simulation preorder, in which hidden actions are abstracted from.
+///
+/// A relation R is a coupled simulation iff for every (p, q) in R every step
+/// p -a-> p' can be mimicked by a weak step q =a=> q' such that (p', q') is in
+/// R, and additionally q => q' by hidden actions such that (q', p) is in R.
+/// The relation is computed as a greatest fixed point, and as such is only
+/// suitable for moderately sized labelled transition systems.
+pub fn coupled_simulation_preorder(lts: &LabelledTransitionSystem) -> Preorder {
+    let mut preorder = Preorder::new(lts.num_of_states());
+
+    // The states reachable by hidden transitions, including the state itself.
+    let closure: Vec<Vec<StateIndex>> = lts
+        .iter_states()
+        .map(|state_index| tau_closure(lts, state_index))
+        .collect();
+
+    // The weak transitions of every state, where q =tau=> q' iff q' is in the closure of q.
+    let weak_transitions: Vec<Vec<(LabelIndex, StateIndex)>> = lts
+        .iter_states()
+        .map(|state_index| {
+            let mut transitions: Vec<(LabelIndex, StateIndex)> = Vec::new();
+            for &state in &closure[state_index] {
+                transitions.push((0, state));
+                for (label_index, to) in lts.outgoing_transitions(state) {
+                    if !lts.is_hidden_label(label_index) {
+                        transitions.extend(closure[to].iter().map(|&to| (label_index, to)));
+                    }
+                }
+            }
+
+            transitions.sort_unstable();
+            transitions.dedup();
+            transitions
+        })
+        .collect();
+
+    let mut changed = true;
+    let mut iteration = 0;
+    while changed {
+        changed = false;
+
+        for state_index in lts.iter_states() {
+            for other_state_index in lts.iter_states() {
+                if !preorder.contains(state_index, other_state_index) {
+                    continue;
+                }
+
+                let simulates = lts.outgoing_transitions(state_index).all(|(label_index, to)| {
+                    let label_index = if lts.is_hidden_label(label_index) {
+                        0
+                    } else {
+                        label_index
+                    };
+
+                    weak_transitions[other_state_index]
+                        .iter()
+                        .any(|&(other_label_index, other_to)| {
+                            other_label_index == label_index && preorder.contains(to, other_to)
+                        })
+                });
+
+                let coupled = closure[other_state_index]
+                    .iter()
+                    .any(|&other_to| preorder.contains(other_to, state_index));
+
+                if !simulates || !coupled {
+                    preorder.remove(state_index, other_state_index);
+                    changed = true;
+                }
+            }
+        }
+
+        iteration += 1;
+        debug!("Iteration {iteration} of the coupled simulation preorder");
+    }
+
+    preorder
+}
+
+/// Computes the partition of states that simulate each other.
+pub fn simulation_equivalence(lts: &LabelledTransitionSystem, timing: &mut Timing) -> IndexedPartition {
+    let mut time = timing.start("reduction");
+    let partition = simulation_preorder(lts).equivalence();
+    time.finish();
+
+    partition
+}
+
+/// Computes the partition of states that are coupled similar in both directions.
+pub fn coupled_simulation_equivalence(lts: &LabelledTransitionSystem, timing: &mut Timing) -> IndexedPartition {
+    let mut time = timing.start("reduction");
+    let partition = coupled_simulation_preorder(lts).equivalence();
+    time.finish();
+
+    partition
+}
+
+/// Returns true iff the initial state of the left LTS is simulated by the initial state of the right LTS.
+pub fn is_simulated_by(left: &LabelledTransitionSystem, right: &LabelledTransitionSystem) -> bool {
+    let (lts, offset) = disjoint_union(left, right);
+    simulation_preorder(&lts).contains(left.initial_state_index(), right.initial_state_index() + offset)
+}
+
+/// Returns true iff the initial state of the left LTS is smaller than the
+/// initial state of the right LTS in the coupled simulation preorder.
+pub fn is_coupled_simulated_by(left: &LabelledTransitionSystem, right: &LabelledTransitionSystem) -> bool {
+    let (lts, offset) = disjoint_union(left, right);
+    coupled_simulation_preorder(&lts).contains(left.initial_state_index(), right.initial_state_index() + offset)
+}
+
+/// Returns the states reachable from the given state by hidden transitions, including the state itself.
+pub(crate) fn tau_closure(lts: &LabelledTransitionSystem, state_index: StateIndex) -> Vec<StateIndex> {
+    let mut closure = vec![state_index];
+    let mut index = 0;
+    while index < closure.len() {
+        let state = closure[index];
+        index += 1;
+
+        for (label_index, to) in lts.outgoing_transitions(state) {
+            if lts.is_hidden_label(label_index) && !closure.contains(&to) {
+                closure.push(to);
+            }
+        }
+    }
+
+    closure
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use crate::random_lts;
+    use crate::strong_bisim_sigref_naive;
+    use crate::LtsBuilder;
+    use crate::Partition;
+
+    use super::*;
+
+    #[test]
+    fn test_simulation_preorder() {
+        // a.b + a.c is simulated by a.(b + c), but not the other way around.
+        let left = LtsBuilder::from_transitions(&[(0, "a", 1), (1, "b", 2), (0, "a", 3), (3, "c", 4)]).finish(0);
+        let right = LtsBuilder::from_transitions(&[(0, "a", 1), (1, "b", 2), (1, "c", 3)]).finish(0);
+
+        assert!(is_simulated_by(&left, &right));
+        assert!(!is_simulated_by(&right, &left));
+
+        // However, a.b + a.(b + c) and a.(b + c) are simulation equivalent.
+        let left =
+            LtsBuilder::from_transitions(&[(0, "a", 1), (1, "b", 2), (0, "a", 3), (3, "b", 4), (3, "c", 5)]).finish(0);
+        assert!(is_simulated_by(&left, &right));
+        assert!(is_simulated_by(&right, &left));
+    }
+
+    #[test]
+    fn test_random_simulation_equivalence() {
+        let lts = random_lts(20, 3, 3);
+        let mut timing = Timing::new();
+
+        // Simulation equivalence is coarser than strong bisimilarity.
+        let strong_partition = strong_bisim_sigref_naive(&lts, &mut timing);
+        let simulation_partition = simulation_equivalence(&lts, &mut timing);
+
+        for state_index in lts.iter_states() {
+            for other_state_index in lts.iter_states() {
+                if strong_partition.block_number(state_index) == strong_partition.block_number(other_state_index) {
+                    assert_eq!(
+                        simulation_partition.block_number(state_index),
+                        simulation_partition.block_number(other_state_index)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_coupled_simulation_preorder() {
+        // Internal steps are abstracted from.
+        let left = LtsBuilder::from_transitions(&[(0, "tau", 1), (1, "a", 2)]).finish(0);
+        let right = LtsBuilder::from_transitions(&[(0, "a", 1)]).finish(0);
+        assert!(is_coupled_simulated_by(&left, &right));
+        assert!(is_coupled_simulated_by(&right, &left));
+
+        // But internal choices are not the same as external choices.
+        let left = LtsBuilder::from_transitions(&[(0, "a", 1), (0, "b", 2)]).finish(0);
+        let right = LtsBuilder::from_transitions(&[(0, "tau", 1), (1, "a", 2), (0, "tau", 3), (3, "b", 4)]).finish(0);
+        assert!(!is_coupled_simulated_by(&left, &right));
+        assert!(!is_coupled_simulated_by(&right, &left));
+    }
+}
@@ -78,19 +78,26 @@ where
         let new_state_index = permutation(state_index);
 
         for (label, to_index) in lts.outgoing_transitions(state_index) {
-            let new_to_index = permutation(*to_index);
-            transitions.push((new_state_index, *label, new_to_index));
+            let new_to_index = permutation(to_index);
+            transitions.push((new_state_index, label, new_to_index));
         }
     }
 
-    debug!("Time reorder_states: {:.3}s", start.elapsed().as_secs_f64());
-    LabelledTransitionSystem::new(
+    let result = LabelledTransitionSystem::new(
         permutation(lts.initial_state_index()),
         Some(lts.num_of_states()),
         || transitions.iter().cloned(),
         lts.labels().into(),
         lts.hidden_labels().into(),
-    )
+    );
+
+    debug!("Time reorder_states: {:.3}s", start.elapsed().as_secs_f64());
+    match lts.state_labels() {
+        Some(state_labels) => result.with_state_labels(
+            state_labels.map_states(lts.num_of_states(), |state_index| Some(permutation(state_index))),
+        ),
+        None => result,
+    }
 }
 
 // The mark of a state in the depth first search.
@@ -128,11 +135,11 @@ where
                     .filter(|(label, to)| filter(*label, *to))
                 {
                     // If it was marked temporary, then a cycle is detected.
-                    if marks[*next_state] == Some(Mark::Temporary) {
+                    if marks[next_state] == Some(Mark::Temporary) {
                         return false;
                     }
-                    if marks[*next_state].is_none() {
-                        depth_stack.push(*next_state);
+                    if marks[next_state].is_none() {
+                        depth_stack.push(next_state);
                     }
                 }
             }
@@ -164,10 +171,10 @@ where
             .filter(|(label, to)| filter(*label, *to))
         {
             if reverse {
-                if state_order <= permutation(*successor) {
+                if state_order <= permutation(successor) {
                     return false;
                 }
-            } else if state_order >= permutation(*successor) {
+            } else if state_order >= permutation(successor) {
                 return false;
             }
         }
@@ -241,12 +248,12 @@ mod tests {
 
         for from in lts.iter_states() {
             // Check that the states are in the correct order.
-            for &(label, to) in lts.outgoing_transitions(from) {
+            for (label, to) in lts.outgoing_transitions(from) {
                 let new_from = order[from];
                 let new_to = order[to];
                 assert!(new_lts
                     .outgoing_transitions(new_from)
-                    .any(|trans| *trans == (label, new_to)));
+                    .any(|trans| trans == (label, new_to)));
             }
         }
     }
@@ -0,0 +1,131 @@
+use log::debug;
+use utilities::Timing;
+
+use crate::combine_partition;
+use crate::preprocess_branching;
+use crate::strong_bisim_sigref;
+use crate::IndexedPartition;
+use crate::LabelIndex;
+use crate::LabelledTransitionSystem;
+use crate::LtsBuilder;
+use crate::StateIndex;
+
+use super::simulation::tau_closure;
+
+/// Computes a weak bisimulation partitioning by computing the strong
+/// bisimulation partitioning of the saturated LTS.
+///
+/// The saturated LTS contains a transition s -a-> t iff s =a=> t in the
+/// original LTS, where s =tau=> t includes the empty sequence of hidden
+/// transitions. Since the number of transitions of the saturated LTS can be
+/// quadratic in the number of states, the tau strongly connected components
+/// are first collapsed.
+pub fn weak_bisim_sigref(lts: &LabelledTransitionSystem, timing: &mut Timing) -> IndexedPartition {
+    let mut timepre = timing.start("preprocess");
+    let (preprocessed_lts, preprocess_partition) = preprocess_branching(lts);
+    let saturated_lts = saturate(&preprocessed_lts);
+    timepre.finish();
+
+    debug!(
+        "Saturated LTS has {} states and {} transitions",
+        saturated_lts.num_of_states(),
+        saturated_lts.num_of_transitions()
+    );
+
+    let partition = strong_bisim_sigref(&saturated_lts, timing);
+
+    // Combine the SCC partition with the weak bisimulation partition.
+    combine_partition(preprocess_partition, &partition)
+}
+
+/// Returns the LTS with a transition s -a-> t for every weak transition s =a=> t.
+fn saturate(lts: &LabelledTransitionSystem) -> LabelledTransitionSystem {
+    let mut builder = LtsBuilder::new(vec![]);
+    let labels: Vec<LabelIndex> = lts.labels().iter().map(|label| builder.add_label(label)).collect();
+
+    // The hidden label is always the first label.
+    let hidden_label = labels[0];
+
+    let closure: Vec<Vec<StateIndex>> = lts
+        .iter_states()
+        .map(|state_index| tau_closure(lts, state_index))
+        .collect();
+
+    for state_index in lts.iter_states() {
+        for &state in &closure[state_index] {
+            builder.add_transition_index(state_index, hidden_label, state);
+
+            for (label_index, to) in lts.outgoing_transitions(state) {
+                if !lts.is_hidden_label(label_index) {
+                    for &to in &closure[to] {
+                        builder.add_transition_index(state_index, labels[label_index], to);
+                    }
+                }
+            }
+        }
+    }
+
+    builder.require_num_of_states(lts.num_of_states());
+    builder.finish(lts.initial_state_index())
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use crate::branching_bisim_sigref;
+    use crate::disjoint_union;
+    use crate::random_lts;
+    use crate::Partition;
+
+    use super::*;
+
+    #[test]
+    fn test_weak_bisim() {
+        // a.(tau.b + c) and a.(tau.b + c) + a.b are weakly bisimilar, but not branching bisimilar.
+        let left = LtsBuilder::from_transitions(&[(0, "a", 1), (1, "tau", 2), (2, "b", 3), (1, "c", 4)]).finish(0);
+        let right = LtsBuilder::from_transitions(&[
+            (0, "a", 1),
+            (1, "tau", 2),
+            (2, "b", 3),
+            (1, "c", 4),
+            (0, "a", 5),
+            (5, "b", 6),
+        ])
+        .finish(0);
+
+        let (union, offset) = disjoint_union(&left, &right);
+        let mut timing = Timing::new();
+
+        let weak_partition = weak_bisim_sigref(&union, &mut timing);
+        assert_eq!(weak_partition.block_number(0), weak_partition.block_number(offset));
+
+        let branching_partition = branching_bisim_sigref(&union, &mut timing);
+        assert_ne!(
+            branching_partition.block_number(0),
+            branching_partition.block_number(offset)
+        );
+    }
+
+    #[test]
+    fn test_random_weak_bisim() {
+        let lts = random_lts(20, 3, 3);
+        let mut timing = Timing::new();
+
+        // Weak bisimilarity is coarser than branching bisimilarity.
+        let branching_partition = branching_bisim_sigref(&lts, &mut timing);
+        let weak_partition = weak_bisim_sigref(&lts, &mut timing);
+
+        for state_index in lts.iter_states() {
+            for other_state_index in lts.iter_states() {
+                if branching_partition.block_number(state_index) == branching_partition.block_number(other_state_index)
+                {
+                    assert_eq!(
+                        weak_partition.block_number(state_index),
+                        weak_partition.block_number(other_state_index)
+                    );
+                }
+            }
+        }
+    }
+}
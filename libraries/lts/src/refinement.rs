@@ -0,0 +1,354 @@
+use std::collections::VecDeque;
+use std::fmt;
+
+use log::debug;
+use rustc_hash::FxHashMap;
+use rustc_hash::FxHashSet;
+
+use crate::tau_scc_decomposition;
+use crate::IncomingTransitions;
+use crate::LabelIndex;
+use crate::LabelledTransitionSystem;
+use crate::Partition;
+use crate::StateIndex;
+
+/// The refinement relations that can be checked between two labelled
+/// transition systems, where hidden actions are abstracted from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RefinementType {
+    Trace,
+    Failures,
+    FailuresDivergences,
+}
+
+/// Returns true iff the implementation refines the specification for the
+/// given refinement relation, where labels are related by their name.
+pub fn refines(
+    specification: &LabelledTransitionSystem,
+    implementation: &LabelledTransitionSystem,
+    refinement: RefinementType,
+) -> bool {
+    refinement_counterexample(specification, implementation, refinement).is_none()
+}
+
+/// A trace of the implementation after which it violates the refinement.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Counterexample {
+    /// The visible actions of the trace.
+    pub trace: Vec<String>,
+    pub violation: Violation,
+}
+
+/// The behaviour of the implementation after the trace of a counterexample
+/// that the specification does not have.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Violation {
+    /// The implementation can perform the given action.
+    Action(String),
+
+    /// The implementation can reach a stable state in which only the given actions are enabled.
+    Refusal(Vec<String>),
+
+    /// The implementation can diverge.
+    Divergence,
+}
+
+/// Returns a counterexample iff the implementation does not refine the
+/// specification for the given refinement relation.
+///
+/// The specification is determinised on-the-fly by the subset construction,
+/// and the pairs of an implementation state and a set of specification states
+/// that are reachable by the same trace are explored in a breadth first
+/// manner. As such the counterexample has a shortest trace.
+pub fn refinement_counterexample(
+    specification: &LabelledTransitionSystem,
+    implementation: &LabelledTransitionSystem,
+    refinement: RefinementType,
+) -> Option<Counterexample> {
+    // Relate the labels of the implementation to the labels of the specification.
+    let label_map: Vec<Option<LabelIndex>> = implementation
+        .labels()
+        .iter()
+        .map(|name| specification.labels().iter().position(|other| other == name))
+        .collect();
+
+    let (specification_divergent, implementation_divergent) = if refinement == RefinementType::FailuresDivergences {
+        (divergent_states(specification), divergent_states(implementation))
+    } else {
+        (
+            vec![false; specification.num_of_states()],
+            vec![false; implementation.num_of_states()],
+        )
+    };
+
+    // Every set of specification states is stored once.
+    let mut sets: Vec<Vec<StateIndex>> = Vec::new();
+    let mut set_index: FxHashMap<Vec<StateIndex>, usize> = FxHashMap::default();
+    let mut intern = |set: Vec<StateIndex>, sets: &mut Vec<Vec<StateIndex>>| {
+        *set_index.entry(set.clone()).or_insert_with(|| {
+            sets.push(set);
+            sets.len() - 1
+        })
+    };
+
+    let initial_set = intern(
+        tau_closure(specification, vec![specification.initial_state_index()]),
+        &mut sets,
+    );
+
+    // For every explored pair the pair from which it was reached, and the visible action of that step.
+    let mut predecessors: Vec<Option<(usize, Option<LabelIndex>)>> = vec![None];
+    let counterexample = |predecessors: &Vec<Option<(usize, Option<LabelIndex>)>>, mut node: usize, violation| {
+        let mut trace = Vec::new();
+        while let Some((predecessor, label_index)) = predecessors[node] {
+            if let Some(label_index) = label_index {
+                trace.push(implementation.labels()[label_index].clone());
+            }
+            node = predecessor;
+        }
+
+        trace.reverse();
+        Some(Counterexample { trace, violation })
+    };
+
+    let mut visited: FxHashSet<(StateIndex, usize)> = FxHashSet::default();
+    let mut queue = VecDeque::new();
+    visited.insert((implementation.initial_state_index(), initial_set));
+    queue.push_back((implementation.initial_state_index(), initial_set, 0));
+
+    let mut enabled = Vec::new();
+    while let Some((state_index, set, node)) = queue.pop_front() {
+        if sets[set].iter().any(|&state| specification_divergent[state]) {
+            // After a divergent trace of the specification every behaviour is allowed.
+            continue;
+        }
+
+        if implementation_divergent[state_index] {
+            debug!("Implementation state {state_index} diverges, but the specification does not");
+            return counterexample(&predecessors, node, Violation::Divergence);
+        }
+
+        if refinement != RefinementType::Trace && is_stable(implementation, state_index) {
+            // The specification must be able to refuse at least the actions refused by the implementation.
+            enabled.clear();
+            enabled.extend(
+                implementation
+                    .outgoing_transitions(state_index)
+                    .filter_map(|(label_index, _)| label_map[label_index]),
+            );
+
+            if !sets[set].iter().any(|&state| {
+                is_stable(specification, state)
+                    && specification
+                        .outgoing_transitions(state)
+                        .all(|(label_index, _)| enabled.contains(&label_index))
+            }) {
+                debug!("Implementation state {state_index} has a refusal that the specification does not have");
+                let mut actions: Vec<String> = implementation
+                    .outgoing_transitions(state_index)
+                    .map(|(label_index, _)| implementation.labels()[label_index].clone())
+                    .collect();
+                actions.dedup();
+
+                return counterexample(&predecessors, node, Violation::Refusal(actions));
+            }
+        }
+
+        for (label_index, to) in implementation.outgoing_transitions(state_index) {
+            let next = if implementation.is_hidden_label(label_index) {
+                set
+            } else {
+                let successors: Vec<StateIndex> = match label_map[label_index] {
+                    Some(label_index) => sets[set]
+                        .iter()
+                        .flat_map(|&state| specification.outgoing_transitions(state))
+                        .filter(|(label, _)| *label == label_index)
+                        .map(|(_, to)| to)
+                        .collect(),
+                    None => Vec::new(),
+                };
+
+                if successors.is_empty() {
+                    debug!(
+                        "The specification cannot perform action {} after reaching implementation state {state_index}",
+                        implementation.labels()[label_index]
+                    );
+                    return counterexample(
+                        &predecessors,
+                        node,
+                        Violation::Action(implementation.labels()[label_index].clone()),
+                    );
+                }
+
+                intern(tau_closure(specification, successors), &mut sets)
+            };
+
+            if visited.insert((to, next)) {
+                let visible = (!implementation.is_hidden_label(label_index)).then_some(label_index);
+                predecessors.push(Some((node, visible)));
+                queue.push_back((to, next, predecessors.len() - 1));
+            }
+        }
+    }
+
+    None
+}
+
+/// Returns true iff both labelled transition systems refine each other.
+pub fn is_equivalent(
+    left: &LabelledTransitionSystem,
+    right: &LabelledTransitionSystem,
+    refinement: RefinementType,
+) -> bool {
+    refines(left, right, refinement) && refines(right, left, refinement)
+}
+
+impl fmt::Display for Counterexample {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.trace.is_empty() {
+            write!(f, "Initially")?;
+        } else {
+            write!(f, "After the trace {}", self.trace.join(" . "))?;
+        }
+
+        match &self.violation {
+            Violation::Action(action) => write!(f, " the implementation can perform {action}, but the specification cannot"),
+            Violation::Refusal(actions) => write!(
+                f,
+                " the implementation can reach a stable state with only the actions {{{}}} enabled, but the specification cannot",
+                actions.join(", ")
+            ),
+            Violation::Divergence => write!(f, " the implementation can diverge, but the specification cannot"),
+        }
+    }
+}
+
+/// Returns true iff the given state has no outgoing hidden transitions.
+fn is_stable(lts: &LabelledTransitionSystem, state_index: StateIndex) -> bool {
+    lts.outgoing_transitions(state_index)
+        .all(|(label_index, _)| !lts.is_hidden_label(label_index))
+}
+
+/// Returns the sorted set of states reachable from the given states by hidden transitions.
+fn tau_closure(lts: &LabelledTransitionSystem, mut stack: Vec<StateIndex>) -> Vec<StateIndex> {
+    let mut closure = FxHashSet::default();
+    while let Some(state_index) = stack.pop() {
+        if closure.insert(state_index) {
+            for (label_index, to) in lts.outgoing_transitions(state_index) {
+                if lts.is_hidden_label(label_index) {
+                    stack.push(to);
+                }
+            }
+        }
+    }
+
+    let mut result: Vec<StateIndex> = closure.into_iter().collect();
+    result.sort_unstable();
+    result
+}
+
+/// Returns for every state whether it can perform an infinite sequence of hidden transitions.
+fn divergent_states(lts: &LabelledTransitionSystem) -> Vec<bool> {
+    let partition = tau_scc_decomposition(lts);
+
+    // States on a cycle of hidden transitions diverge.
+    let mut block_size = vec![0usize; partition.num_of_blocks()];
+    for state_index in lts.iter_states() {
+        block_size[partition.block_number(state_index)] += 1;
+    }
+
+    let mut divergent = vec![false; lts.num_of_states()];
+    let mut stack = Vec::new();
+    for state_index in lts.iter_states() {
+        if block_size[partition.block_number(state_index)] > 1
+            || lts
+                .outgoing_transitions(state_index)
+                .any(|(label_index, to)| lts.is_hidden_label(label_index) && to == state_index)
+        {
+            divergent[state_index] = true;
+            stack.push(state_index);
+        }
+    }
+
+    // States that can reach a divergent state by hidden transitions also diverge.
+    let incoming = IncomingTransitions::new(lts);
+    while let Some(state_index) = stack.pop() {
+        for &(_, from) in incoming.incoming_silent_transitions(state_index) {
+            if !divergent[from] {
+                divergent[from] = true;
+                stack.push(from);
+            }
+        }
+    }
+
+    divergent
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::LtsBuilder;
+
+    use super::*;
+
+    #[test]
+    fn test_trace_refinement() {
+        let specification = LtsBuilder::from_transitions(&[(0, "a", 1), (1, "b", 2), (1, "c", 3)]).finish(0);
+        let implementation = LtsBuilder::from_transitions(&[(0, "a", 1), (1, "tau", 2), (2, "b", 3)]).finish(0);
+
+        assert!(refines(&specification, &implementation, RefinementType::Trace));
+        assert!(!refines(&implementation, &specification, RefinementType::Trace));
+        assert!(!is_equivalent(&specification, &implementation, RefinementType::Trace));
+
+        assert_eq!(
+            refinement_counterexample(&implementation, &specification, RefinementType::Trace),
+            Some(Counterexample {
+                trace: vec!["a".to_string()],
+                violation: Violation::Action("c".to_string())
+            })
+        );
+    }
+
+    #[test]
+    fn test_failures_refinement() {
+        // The external choice between b and c can not be refined by only b.
+        let specification = LtsBuilder::from_transitions(&[(0, "a", 1), (1, "b", 2), (1, "c", 3)]).finish(0);
+        let implementation = LtsBuilder::from_transitions(&[(0, "a", 1), (1, "b", 2)]).finish(0);
+        assert!(!refines(&specification, &implementation, RefinementType::Failures));
+        assert_eq!(
+            refinement_counterexample(&specification, &implementation, RefinementType::Failures),
+            Some(Counterexample {
+                trace: vec!["a".to_string()],
+                violation: Violation::Refusal(vec!["b".to_string()])
+            })
+        );
+
+        // But the internal choice between b and c can be refined by only b.
+        let specification =
+            LtsBuilder::from_transitions(&[(0, "a", 1), (1, "tau", 2), (1, "tau", 3), (2, "b", 4), (3, "c", 5)])
+                .finish(0);
+        assert!(refines(&specification, &implementation, RefinementType::Failures));
+        assert!(!refines(&implementation, &specification, RefinementType::Failures));
+    }
+
+    #[test]
+    fn test_failures_divergences_refinement() {
+        let specification = LtsBuilder::from_transitions(&[(0, "a", 1)]).finish(0);
+        let implementation = LtsBuilder::from_transitions(&[(0, "a", 1), (0, "tau", 2), (2, "tau", 0)]).finish(0);
+
+        assert!(refines(&specification, &implementation, RefinementType::Failures));
+        assert_eq!(
+            refinement_counterexample(&specification, &implementation, RefinementType::FailuresDivergences),
+            Some(Counterexample {
+                trace: vec![],
+                violation: Violation::Divergence
+            })
+        );
+
+        // A divergent specification allows any behaviour.
+        assert!(refines(
+            &implementation,
+            &LtsBuilder::from_transitions(&[(0, "b", 1)]).finish(0),
+            RefinementType::FailuresDivergences
+        ));
+    }
+}
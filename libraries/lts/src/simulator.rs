@@ -0,0 +1,324 @@
+use rand::rngs::StdRng;
+use rand::Rng;
+use rand::SeedableRng;
+
+use crate::LabelIndex;
+use crate::LabelTable;
+use crate::LabelledTransitionSystem;
+use crate::LtsBuilder;
+use crate::MultiAction;
+use crate::StateIndex;
+
+/// A finite path through a labelled transition system.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Trace {
+    initial_state: StateIndex,
+    steps: Vec<(LabelIndex, StateIndex)>,
+}
+
+impl Trace {
+    /// Creates the empty trace from the given state.
+    pub fn new(initial_state: StateIndex) -> Trace {
+        Trace {
+            initial_state,
+            steps: Vec::new(),
+        }
+    }
+
+    /// Returns the state in which the trace starts.
+    pub fn initial_state(&self) -> StateIndex {
+        self.initial_state
+    }
+
+    /// Returns the label and target state of every transition in the trace.
+    pub fn steps(&self) -> &[(LabelIndex, StateIndex)] {
+        &self.steps
+    }
+
+    /// Extends the trace with a transition from its last state.
+    pub fn push(&mut self, label_index: LabelIndex, to: StateIndex) {
+        self.steps.push((label_index, to));
+    }
+
+    /// Returns the state in which the trace ends.
+    pub fn last_state(&self) -> StateIndex {
+        self.steps.last().map_or(self.initial_state, |&(_, to)| to)
+    }
+
+    /// Returns the number of transitions in the trace.
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    /// Returns true iff the trace has no transitions.
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Returns the names of the labels of the trace in the given LTS.
+    pub fn actions<'a>(&self, lts: &'a LabelledTransitionSystem) -> Vec<&'a str> {
+        self.steps
+            .iter()
+            .map(|&(label_index, _)| lts.labels()[label_index].as_str())
+            .collect()
+    }
+
+    /// Replays the given sequence of actions from the initial state of the LTS,
+    /// where the actions are compared as multi-actions, see [MultiAction].
+    /// Since the LTS can be nondeterministic, all states reachable by a prefix
+    /// of the actions are tracked and a trace is only chosen at the end.
+    ///
+    /// Returns the number of actions that could be replayed when the full
+    /// sequence is not a trace of the LTS.
+    pub fn replay<S: AsRef<str>>(lts: &LabelledTransitionSystem, actions: &[S]) -> Result<Trace, usize> {
+        let table = LabelTable::new(lts);
+
+        // For every step the reached states together with the transition by which they were reached.
+        let mut layers: Vec<Vec<(StateIndex, LabelIndex, usize)>> = vec![vec![(lts.initial_state_index(), 0, 0)]];
+        let mut visited = vec![usize::MAX; lts.num_of_states()];
+
+        for (step, action) in actions.iter().enumerate() {
+            let action = MultiAction::parse(action.as_ref());
+            let mut next = Vec::new();
+
+            for (index, &(state_index, _, _)) in layers[step].iter().enumerate() {
+                for (label_index, to) in lts.outgoing_transitions(state_index) {
+                    if visited[to] != step && *table.multi_action(label_index) == action {
+                        visited[to] = step;
+                        next.push((to, label_index, index));
+                    }
+                }
+            }
+
+            if next.is_empty() {
+                return Err(step);
+            }
+            layers.push(next);
+        }
+
+        // Follow the predecessors back from any state reached at the end.
+        let mut steps = Vec::with_capacity(actions.len());
+        let mut index = 0;
+        for layer in layers[1..].iter().rev() {
+            let (to, label_index, predecessor) = layer[index];
+            steps.push((label_index, to));
+            index = predecessor;
+        }
+        steps.reverse();
+
+        Ok(Trace {
+            initial_state: lts.initial_state_index(),
+            steps,
+        })
+    }
+
+    /// Returns the trace as a linear LTS, for example to write it in the .aut format.
+    pub fn to_lts(&self, lts: &LabelledTransitionSystem) -> LabelledTransitionSystem {
+        let mut builder = LtsBuilder::new(vec![]);
+        for (index, action) in self.actions(lts).into_iter().enumerate() {
+            builder.add_transition(index, action, index + 1);
+        }
+
+        builder.require_num_of_states(self.len() + 1);
+        builder.finish(0)
+    }
+}
+
+/// Simulates a labelled transition system, where transitions are taken one at
+/// a time either by choosing them explicitly or at random. The random choices
+/// are reproducible when the simulator is created with a seed.
+pub struct Simulator<'a> {
+    lts: &'a LabelledTransitionSystem,
+    trace: Trace,
+    rng: StdRng,
+}
+
+impl<'a> Simulator<'a> {
+    /// Creates a simulator that starts in the initial state, where the random choices are seeded by the system.
+    pub fn new(lts: &'a LabelledTransitionSystem) -> Simulator<'a> {
+        Simulator {
+            lts,
+            trace: Trace::new(lts.initial_state_index()),
+            rng: StdRng::from_os_rng(),
+        }
+    }
+
+    /// Creates a simulator that starts in the initial state, where the random choices are determined by the given seed.
+    pub fn with_seed(lts: &'a LabelledTransitionSystem, seed: u64) -> Simulator<'a> {
+        Simulator {
+            lts,
+            trace: Trace::new(lts.initial_state_index()),
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Continues the simulation at the end of the given trace, which must be a trace of the LTS.
+    pub fn with_trace(lts: &'a LabelledTransitionSystem, trace: Trace) -> Simulator<'a> {
+        Simulator {
+            lts,
+            trace,
+            rng: StdRng::from_os_rng(),
+        }
+    }
+
+    /// Returns the current state of the simulation.
+    pub fn current_state(&self) -> StateIndex {
+        self.trace.last_state()
+    }
+
+    /// Returns the transitions that are enabled in the current state, sorted by label.
+    pub fn enabled(&self) -> impl ExactSizeIterator<Item = (LabelIndex, StateIndex)> + '_ {
+        self.lts.outgoing_transitions(self.current_state())
+    }
+
+    /// Returns true iff no transitions are enabled in the current state.
+    pub fn is_deadlock(&self) -> bool {
+        self.enabled().len() == 0
+    }
+
+    /// Takes the enabled transition with the given index, see [Simulator::enabled].
+    /// Returns None iff there is no such transition.
+    pub fn select(&mut self, index: usize) -> Option<(LabelIndex, StateIndex)> {
+        let transition = self.enabled().nth(index)?;
+        self.trace.steps.push(transition);
+        Some(transition)
+    }
+
+    /// Takes an enabled transition uniformly at random, or returns None in a deadlock state.
+    pub fn step(&mut self) -> Option<(LabelIndex, StateIndex)> {
+        let num_of_enabled = self.enabled().len();
+        if num_of_enabled == 0 {
+            return None;
+        }
+
+        let index = self.rng.random_range(0..num_of_enabled);
+        self.select(index)
+    }
+
+    /// Takes at most the given number of random transitions, and stops early
+    /// in a deadlock state. Returns the number of transitions taken.
+    pub fn random_walk(&mut self, max_steps: usize) -> usize {
+        let mut steps = 0;
+        while steps < max_steps && self.step().is_some() {
+            steps += 1;
+        }
+
+        steps
+    }
+
+    /// Undoes the last transition, and returns false iff the trace was already empty.
+    pub fn undo(&mut self) -> bool {
+        self.trace.steps.pop().is_some()
+    }
+
+    /// Restarts the simulation from the initial state.
+    pub fn reset(&mut self) {
+        self.trace.steps.clear();
+    }
+
+    /// Returns the trace that has been simulated so far.
+    pub fn trace(&self) -> &Trace {
+        &self.trace
+    }
+
+    /// Returns the trace that has been simulated, consuming the simulator.
+    pub fn into_trace(self) -> Trace {
+        self.trace
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::random_lts;
+
+    use super::*;
+
+    #[test]
+    fn test_simulator() {
+        let mut builder = LtsBuilder::new(vec![]);
+        builder.add_transition(0, "a", 1);
+        builder.add_transition(0, "b", 2);
+        builder.add_transition(1, "c", 0);
+        let lts = builder.finish(0);
+
+        let mut simulator = Simulator::new(&lts);
+        assert_eq!(simulator.enabled().len(), 2);
+
+        let (label_index, to) = simulator.select(0).unwrap();
+        assert_eq!(lts.labels()[label_index], "a");
+        assert_eq!(simulator.current_state(), to);
+        assert_eq!(simulator.select(1), None);
+
+        simulator.select(0);
+        simulator.select(1);
+        assert!(simulator.is_deadlock());
+        assert_eq!(simulator.step(), None);
+        assert_eq!(simulator.trace().actions(&lts), ["a", "c", "b"]);
+
+        // The simulation can be continued from the resulting trace.
+        let trace = simulator.into_trace();
+        let mut simulator = Simulator::with_trace(&lts, trace.clone());
+        assert_eq!(simulator.trace(), &trace);
+
+        assert!(simulator.undo());
+        assert_eq!(simulator.current_state(), 0);
+
+        simulator.reset();
+        assert!(simulator.trace().is_empty());
+        assert!(!simulator.undo());
+    }
+
+    #[test]
+    fn test_random_walk() {
+        let lts = random_lts(20, 3, 3);
+
+        // Runs with the same seed are reproducible.
+        let mut simulator = Simulator::with_seed(&lts, 42);
+        let steps = simulator.random_walk(100);
+        let trace = simulator.trace().clone();
+        assert_eq!(trace.len(), steps);
+
+        let mut other = Simulator::with_seed(&lts, 42);
+        other.random_walk(100);
+        assert_eq!(*other.trace(), trace);
+
+        // Every step of the walk is a transition of the LTS.
+        let mut state_index = trace.initial_state();
+        for &(label_index, to) in trace.steps() {
+            assert!(lts
+                .outgoing_transitions(state_index)
+                .any(|transition| transition == (label_index, to)));
+            state_index = to;
+        }
+
+        let linear = trace.to_lts(&lts);
+        assert_eq!(linear.num_of_states(), trace.len() + 1);
+
+        // Replaying the actions of the walk results in a trace with the same actions.
+        let replayed = Trace::replay(&lts, &trace.actions(&lts)).unwrap();
+        assert_eq!(replayed.actions(&lts), trace.actions(&lts));
+    }
+
+    #[test]
+    fn test_replay() {
+        let mut builder = LtsBuilder::new(vec![]);
+        builder.add_transition(0, "a", 1);
+        builder.add_transition(0, "a", 2);
+        builder.add_transition(2, "b|c(1)", 3);
+        builder.add_transition(3, "a", 0);
+        let lts = builder.finish(0);
+
+        // Only the second a-transition can be followed by b|c(1).
+        let trace = Trace::replay(&lts, &["a", "c( 1 )|b", "a"]).unwrap();
+        assert_eq!(trace.steps()[0].1, 2);
+        assert_eq!(trace.last_state(), 0);
+
+        assert_eq!(Trace::replay(&lts, &["a", "b"]), Err(1));
+        assert_eq!(Trace::replay::<&str>(&lts, &[]), Ok(Trace::new(0)));
+
+        let mut simulator = Simulator::with_trace(&lts, trace);
+        assert_eq!(simulator.enabled().len(), 2);
+        assert!(simulator.undo());
+        assert_eq!(simulator.current_state(), 3);
+    }
+}
@@ -0,0 +1,112 @@
+use crate::StateIndex;
+
+/// The state labels of a labelled transition system, which assign a set of
+/// state vectors to every state. Every state vector contains the values of
+/// the process parameters. Initially every state has exactly one state
+/// vector, but after quotienting a state has the vectors of all the states in
+/// its block.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StateLabels {
+    parameters: Vec<String>,
+    labels: Vec<Vec<Vec<String>>>,
+}
+
+impl StateLabels {
+    /// Creates the state labels with a single state vector for every state.
+    pub fn new(parameters: Vec<String>, vectors: Vec<Vec<String>>) -> StateLabels {
+        debug_assert!(
+            vectors.iter().all(|vector| vector.len() == parameters.len()),
+            "Every state vector should have a value for every parameter"
+        );
+
+        StateLabels {
+            parameters,
+            labels: vectors.into_iter().map(|vector| vec![vector]).collect(),
+        }
+    }
+
+    /// Returns the names of the process parameters.
+    pub fn parameters(&self) -> &[String] {
+        &self.parameters
+    }
+
+    /// Returns the state vectors of the given state.
+    pub fn state_label(&self, state_index: StateIndex) -> &[Vec<String>] {
+        &self.labels[state_index]
+    }
+
+    /// Returns the number of states that are labelled.
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    /// Returns true iff there are no labelled states.
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    /// Returns the state labels for the given number of states, where every
+    /// state is mapped to a new state or removed when the mapping returns
+    /// None. The state vectors of states that are mapped to the same state are
+    /// merged.
+    pub fn map_states<F>(&self, num_of_states: usize, mapping: F) -> StateLabels
+    where
+        F: Fn(StateIndex) -> Option<StateIndex>,
+    {
+        let mut labels: Vec<Vec<Vec<String>>> = vec![Vec::new(); num_of_states];
+        for (state_index, vectors) in self.labels.iter().enumerate() {
+            if let Some(new_state_index) = mapping(state_index) {
+                labels[new_state_index].extend(vectors.iter().cloned());
+            }
+        }
+
+        for vectors in &mut labels {
+            vectors.sort_unstable();
+            vectors.dedup();
+        }
+
+        StateLabels {
+            parameters: self.parameters.clone(),
+            labels,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use utilities::Timing;
+
+    use crate::quotient_lts;
+    use crate::strong_bisim_sigref;
+    use crate::LtsBuilder;
+    use crate::Partition;
+
+    use super::*;
+
+    #[test]
+    fn test_quotient_state_labels() {
+        // The states 1 and 2 are bisimilar, and as such their state vectors are merged.
+        let mut builder = LtsBuilder::new(vec![]);
+        builder.add_transition(0, "a", 1);
+        builder.add_transition(0, "a", 2);
+        builder.add_transition(1, "b", 3);
+        builder.add_transition(2, "b", 3);
+
+        let vector = |value: &str| vec![value.to_string()];
+        let lts = builder.finish(0).with_state_labels(StateLabels::new(
+            vec!["x".to_string()],
+            vec![vector("0"), vector("2"), vector("1"), vector("3")],
+        ));
+
+        let partition = strong_bisim_sigref(&lts, &mut Timing::new());
+        let quotient = quotient_lts(&lts, &partition, false);
+
+        let state_labels = quotient.state_labels().unwrap();
+        assert_eq!(state_labels.len(), 3);
+        assert_eq!(
+            state_labels.state_label(partition.block_number(1)),
+            [vector("1"), vector("2")]
+        );
+        assert_eq!(state_labels.state_label(partition.block_number(3)), [vector("3")]);
+    }
+}
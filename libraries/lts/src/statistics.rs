@@ -0,0 +1,238 @@
+use std::collections::VecDeque;
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::scc_decomposition;
+use crate::tau_scc_decomposition;
+use crate::LabelledTransitionSystem;
+use crate::Partition;
+use crate::StateIndex;
+
+/// Various statistics of a labelled transition system.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct LtsStatistics {
+    pub num_of_states: usize,
+    pub num_of_transitions: usize,
+    pub num_of_labels: usize,
+
+    /// The number of transitions labelled by the hidden action tau.
+    pub num_of_tau_transitions: usize,
+
+    /// The number of states without outgoing transitions.
+    pub num_of_deadlocks: usize,
+
+    /// The number of states that are reachable from the initial state.
+    pub num_of_reachable_states: usize,
+
+    pub min_out_degree: usize,
+    pub max_out_degree: usize,
+
+    /// The number of states with out-degree i is stored at index i.
+    pub out_degree_distribution: Vec<usize>,
+
+    pub num_of_sccs: usize,
+    pub largest_scc: usize,
+
+    /// The number of strongly connected components w.r.t. tau transitions.
+    pub num_of_tau_sccs: usize,
+
+    /// The largest distance from the initial state to a reachable state.
+    pub depth: usize,
+
+    /// The largest distance between any two states such that the second one
+    /// is reachable from the first one, which is only computed on request
+    /// since it requires a breadth first search from every state.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diameter: Option<usize>,
+}
+
+impl LtsStatistics {
+    /// Computes the statistics of the given LTS, except for the diameter.
+    pub fn new(lts: &LabelledTransitionSystem) -> LtsStatistics {
+        let mut out_degree_distribution = Vec::new();
+        let mut num_of_tau_transitions = 0;
+
+        for state_index in lts.iter_states() {
+            let out_degree = lts.outgoing_transitions(state_index).len();
+            if out_degree_distribution.len() <= out_degree {
+                out_degree_distribution.resize(out_degree + 1, 0);
+            }
+            out_degree_distribution[out_degree] += 1;
+
+            num_of_tau_transitions += lts
+                .outgoing_transitions(state_index)
+                .filter(|&(label_index, _)| lts.is_hidden_label(label_index))
+                .count();
+        }
+
+        let distances = distances_from(lts, lts.initial_state_index());
+
+        let scc_partition = scc_decomposition(lts);
+        let mut scc_sizes = vec![0; scc_partition.num_of_blocks()];
+        for state_index in lts.iter_states() {
+            scc_sizes[scc_partition.block_number(state_index)] += 1;
+        }
+
+        LtsStatistics {
+            num_of_states: lts.num_of_states(),
+            num_of_transitions: lts.num_of_transitions(),
+            num_of_labels: lts.num_of_labels(),
+            num_of_tau_transitions,
+            num_of_deadlocks: out_degree_distribution.first().copied().unwrap_or(0),
+            num_of_reachable_states: distances.iter().filter(|distance| distance.is_some()).count(),
+            min_out_degree: out_degree_distribution
+                .iter()
+                .position(|&count| count > 0)
+                .unwrap_or(0),
+            max_out_degree: out_degree_distribution.len().saturating_sub(1),
+            out_degree_distribution,
+            num_of_sccs: scc_sizes.iter().filter(|&&size| size > 0).count(),
+            largest_scc: scc_sizes.iter().copied().max().unwrap_or(0),
+            num_of_tau_sccs: tau_scc_decomposition(lts).num_of_blocks(),
+            depth: distances.iter().flatten().copied().max().unwrap_or(0),
+            diameter: None,
+        }
+    }
+
+    /// Also computes the diameter of the given LTS, see [diameter].
+    pub fn with_diameter(mut self, lts: &LabelledTransitionSystem) -> LtsStatistics {
+        self.diameter = Some(diameter(lts));
+        self
+    }
+
+    /// Returns the average number of outgoing transitions per state.
+    pub fn average_out_degree(&self) -> f64 {
+        if self.num_of_states == 0 {
+            0.0
+        } else {
+            self.num_of_transitions as f64 / self.num_of_states as f64
+        }
+    }
+
+    /// Returns the percentage of transitions that are labelled by tau.
+    pub fn tau_percentage(&self) -> f64 {
+        if self.num_of_transitions == 0 {
+            0.0
+        } else {
+            100.0 * self.num_of_tau_transitions as f64 / self.num_of_transitions as f64
+        }
+    }
+}
+
+impl fmt::Display for LtsStatistics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Number of states: {}", self.num_of_states)?;
+        writeln!(f, "Number of reachable states: {}", self.num_of_reachable_states)?;
+        writeln!(f, "Number of transitions: {}", self.num_of_transitions)?;
+        writeln!(f, "Number of action labels: {}", self.num_of_labels)?;
+        writeln!(
+            f,
+            "Number of tau transitions: {} ({:.2}%)",
+            self.num_of_tau_transitions,
+            self.tau_percentage()
+        )?;
+        writeln!(f, "Number of deadlocks: {}", self.num_of_deadlocks)?;
+        writeln!(
+            f,
+            "Out-degree: min {}, max {}, average {:.2}",
+            self.min_out_degree,
+            self.max_out_degree,
+            self.average_out_degree()
+        )?;
+        for (out_degree, count) in self.out_degree_distribution.iter().enumerate() {
+            if *count > 0 {
+                writeln!(f, "  {out_degree}: {count}")?;
+            }
+        }
+        writeln!(
+            f,
+            "Number of strongly connected components: {} (largest {})",
+            self.num_of_sccs, self.largest_scc
+        )?;
+        writeln!(f, "Number of tau strongly connected components: {}", self.num_of_tau_sccs)?;
+        write!(f, "Depth: {}", self.depth)?;
+        if let Some(diameter) = self.diameter {
+            write!(f, "\nDiameter: {diameter}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Computes the diameter of the LTS, i.e., the length of the longest shortest
+/// path between any two states where the second state is reachable from the
+/// first one. This performs a breadth first search from every state and as
+/// such takes O(n * m) time.
+pub fn diameter(lts: &LabelledTransitionSystem) -> usize {
+    lts.iter_states()
+        .map(|state_index| {
+            distances_from(lts, state_index)
+                .into_iter()
+                .flatten()
+                .max()
+                .unwrap_or(0)
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// Returns the length of the shortest path from the given state to every
+/// state, or None when a state is not reachable.
+fn distances_from(lts: &LabelledTransitionSystem, state_index: StateIndex) -> Vec<Option<usize>> {
+    let mut distances = vec![None; lts.num_of_states()];
+    let mut queue = VecDeque::from([state_index]);
+    distances[state_index] = Some(0);
+
+    while let Some(state_index) = queue.pop_front() {
+        let distance = distances[state_index].unwrap_or_default();
+        for (_, to) in lts.outgoing_transitions(state_index) {
+            if distances[to].is_none() {
+                distances[to] = Some(distance + 1);
+                queue.push_back(to);
+            }
+        }
+    }
+
+    distances
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::random_lts;
+    use crate::LtsBuilder;
+
+    use super::*;
+
+    #[test]
+    fn test_statistics() {
+        let mut builder = LtsBuilder::new(vec!["tau".to_string()]);
+        builder.add_transition(0, "a", 1);
+        builder.add_transition(0, "tau", 2);
+        builder.add_transition(1, "b", 0);
+        builder.add_transition(2, "c", 3);
+        let lts = builder.finish(0);
+
+        let statistics = LtsStatistics::new(&lts).with_diameter(&lts);
+        assert_eq!(statistics.num_of_states, 4);
+        assert_eq!(statistics.num_of_transitions, 4);
+        assert_eq!(statistics.num_of_tau_transitions, 1);
+        assert_eq!(statistics.num_of_deadlocks, 1);
+        assert_eq!(statistics.num_of_reachable_states, 4);
+        assert_eq!(statistics.out_degree_distribution, [1, 2, 1]);
+        assert_eq!(statistics.num_of_sccs, 3);
+        assert_eq!(statistics.largest_scc, 2);
+        assert_eq!(statistics.depth, 2);
+        assert_eq!(statistics.diameter, Some(3));
+    }
+
+    #[test]
+    fn test_random_statistics() {
+        let lts = random_lts(100, 3, 3);
+        let statistics = LtsStatistics::new(&lts);
+
+        assert_eq!(statistics.out_degree_distribution.iter().sum::<usize>(), lts.num_of_states());
+        assert!(statistics.num_of_reachable_states <= lts.num_of_states());
+        assert!(statistics.depth < statistics.num_of_reachable_states);
+    }
+}
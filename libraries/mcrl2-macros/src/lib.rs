@@ -5,8 +5,10 @@
 
 #![forbid(unsafe_code)]
 
+mod match_term;
 mod mcrl2_derive_terms;
 
+use match_term::match_term_impl;
 use mcrl2_derive_terms::mcrl2_derive_terms_impl;
 
 /// This proc macro can be used to generate implementations for the types stored
@@ -43,3 +45,28 @@ pub fn mcrl2_term(_attributes: proc_macro::TokenStream, input: proc_macro::Token
 pub fn mcrl2_ignore(_attributes: proc_macro::TokenStream, input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     input
 }
+
+/// Matches a term against a number of patterns, given as strings in the
+/// textual aterm format, and evaluates the expression of the first pattern
+/// that matches.
+///
+/// A name without arguments that consists of a single lowercase letter
+/// followed by digits, e.g. `x` or `t1`, is a variable that is bound to the
+/// matching subterm. A variable that occurs multiple times must match the same
+/// term. The wildcard `_` matches any term, numbers match integer terms and all
+/// other names are function symbols. When no pattern matches the expression of
+/// the `_` arm is evaluated, or it panics when there is no such arm.
+///
+/// # Example
+///
+/// ```ignore
+/// let result = match_term!(t,
+///     "and(true, x)" => x.protect(),
+///     "not(not(x))" => x.protect(),
+///     _ => t.clone(),
+/// );
+/// ```
+#[proc_macro]
+pub fn match_term(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    match_term_impl(proc_macro2::TokenStream::from(input)).into()
+}
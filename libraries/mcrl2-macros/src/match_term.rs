@@ -0,0 +1,303 @@
+use proc_macro2::Span;
+use proc_macro2::TokenStream;
+
+use quote::format_ident;
+use quote::quote;
+use syn::parse::Parse;
+use syn::parse::ParseStream;
+use syn::Expr;
+use syn::Ident;
+use syn::Lifetime;
+use syn::LitStr;
+use syn::Token;
+
+/// The input of the match_term! macro: `term, pattern => expression, ...`.
+struct MatchTermInput {
+    term: Expr,
+    arms: Vec<(Option<LitStr>, Expr)>,
+}
+
+impl Parse for MatchTermInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let term: Expr = input.parse()?;
+
+        let mut arms = Vec::new();
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+
+            let pattern = if input.peek(Token![_]) {
+                input.parse::<Token![_]>()?;
+                None
+            } else {
+                Some(input.parse::<LitStr>()?)
+            };
+
+            input.parse::<Token![=>]>()?;
+            arms.push((pattern, input.parse::<Expr>()?));
+        }
+
+        Ok(MatchTermInput { term, arms })
+    }
+}
+
+/// A parsed pattern string.
+#[derive(Debug, PartialEq)]
+enum Pattern {
+    /// Matches any term and binds it to the variable.
+    Variable(String),
+
+    /// Matches any term.
+    Wildcard,
+
+    /// Matches an integer term with the given value.
+    Int(usize),
+
+    /// Matches a term with the given head symbol and arguments.
+    Application(String, Vec<Pattern>),
+}
+
+/// Returns true iff the name of a constant should be treated as a variable,
+/// which is the case for a single lowercase letter followed by digits.
+fn is_variable(name: &str) -> bool {
+    let mut characters = name.chars();
+    characters.next().is_some_and(|c| c.is_ascii_lowercase()) && characters.all(|c| c.is_ascii_digit())
+}
+
+/// Parses a pattern of the form `f(p_1, ..., p_n)`, where the p_i are patterns.
+fn parse_pattern(text: &str) -> Result<Pattern, String> {
+    let mut input = text.char_indices().peekable();
+    let pattern = parse_pattern_rec(text, &mut input)?;
+
+    match input.next() {
+        None => Ok(pattern),
+        Some((position, character)) => Err(format!("unexpected character '{character}' at position {position}")),
+    }
+}
+
+fn parse_pattern_rec(
+    text: &str,
+    input: &mut std::iter::Peekable<std::str::CharIndices<'_>>,
+) -> Result<Pattern, String> {
+    skip_whitespace(input);
+
+    let start = input.peek().map_or(text.len(), |(position, _)| *position);
+    while input
+        .next_if(|(_, c)| !c.is_whitespace() && !"(),".contains(*c))
+        .is_some()
+    {}
+    let end = input.peek().map_or(text.len(), |(position, _)| *position);
+
+    let name = &text[start..end];
+    if name.is_empty() {
+        return Err(format!("expected a name at position {start}"));
+    }
+
+    skip_whitespace(input);
+    if input.next_if(|(_, c)| *c == '(').is_some() {
+        let mut arguments = vec![parse_pattern_rec(text, input)?];
+        loop {
+            skip_whitespace(input);
+            match input.next() {
+                Some((_, ',')) => arguments.push(parse_pattern_rec(text, input)?),
+                Some((_, ')')) => break,
+                Some((position, character)) => {
+                    return Err(format!("unexpected character '{character}' at position {position}"))
+                }
+                None => return Err("unexpected end of the pattern".to_string()),
+            }
+        }
+
+        skip_whitespace(input);
+        Ok(Pattern::Application(name.to_string(), arguments))
+    } else if name == "_" {
+        Ok(Pattern::Wildcard)
+    } else if name.chars().all(|c| c.is_ascii_digit()) {
+        name.parse()
+            .map(Pattern::Int)
+            .map_err(|_| format!("the integer {name} is too large"))
+    } else if is_variable(name) {
+        Ok(Pattern::Variable(name.to_string()))
+    } else {
+        Ok(Pattern::Application(name.to_string(), Vec::new()))
+    }
+}
+
+fn skip_whitespace(input: &mut std::iter::Peekable<std::str::CharIndices<'_>>) {
+    while input.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+}
+
+/// Generates the checks for the given pattern on the term stored in `term`,
+/// which break out of `arm` when the term does not match. Variables that
+/// occur multiple times must be bound to the same term.
+fn generate_checks(
+    pattern: &Pattern,
+    term: &Ident,
+    arm: &Lifetime,
+    bound: &mut Vec<String>,
+    counter: &mut usize,
+) -> TokenStream {
+    match pattern {
+        Pattern::Wildcard => quote!(),
+        Pattern::Variable(name) => {
+            let variable = Ident::new(name, Span::call_site());
+            if bound.contains(name) {
+                quote!(
+                    if #variable != #term {
+                        break #arm;
+                    }
+                )
+            } else {
+                bound.push(name.clone());
+                quote!(
+                    let #variable = #term.copy();
+                )
+            }
+        }
+        Pattern::Int(value) => quote!(
+            if !#term.is_int() || #term.int_value() != #value {
+                break #arm;
+            }
+        ),
+        Pattern::Application(name, arguments) => {
+            let arity = arguments.len();
+            let mut result = quote!(
+                if #term.is_int() || #term.get_head_symbol().arity() != #arity || #term.get_head_symbol().name() != #name {
+                    break #arm;
+                }
+            );
+
+            for (index, argument) in arguments.iter().enumerate() {
+                if *argument == Pattern::Wildcard {
+                    continue;
+                }
+
+                *counter += 1;
+                let subterm = format_ident!("__match_term_{}", *counter);
+                let checks = generate_checks(argument, &subterm, arm, bound, counter);
+                result.extend(quote!(
+                    let #subterm = #term.arg(#index);
+                    #checks
+                ));
+            }
+
+            result
+        }
+    }
+}
+
+pub(crate) fn match_term_impl(input: TokenStream) -> TokenStream {
+    let input: MatchTermInput = match syn::parse2(input) {
+        Ok(input) => input,
+        Err(error) => return error.to_compile_error(),
+    };
+
+    let term = &input.term;
+    let term_ident = format_ident!("__match_term");
+    let result_label = Lifetime::new("'__match_term", Span::call_site());
+
+    let mut arms = TokenStream::new();
+    let mut fallback = None;
+    let mut counter = 0;
+    for (index, (pattern, body)) in input.arms.iter().enumerate() {
+        match pattern {
+            None => {
+                fallback = Some(quote!(#body));
+                break;
+            }
+            Some(literal) => {
+                let pattern = match parse_pattern(&literal.value()) {
+                    Ok(pattern) => pattern,
+                    Err(message) => {
+                        return syn::Error::new(literal.span(), format!("invalid pattern: {message}"))
+                            .to_compile_error()
+                    }
+                };
+
+                let arm_label = Lifetime::new(&format!("'__match_term_arm_{index}"), Span::call_site());
+                let checks = generate_checks(&pattern, &term_ident, &arm_label, &mut Vec::new(), &mut counter);
+
+                if matches!(pattern, Pattern::Variable(_) | Pattern::Wildcard) {
+                    // This arm always matches, so the remaining arms are unreachable.
+                    fallback = Some(quote!(
+                        #checks
+                        #body
+                    ));
+                    break;
+                }
+
+                arms.extend(quote!(
+                    #arm_label: {
+                        #checks
+                        break #result_label { #body };
+                    }
+                ));
+            }
+        }
+    }
+
+    let fallback = match fallback {
+        Some(fallback) => fallback,
+        None => quote!(panic!("The term {:?} does not match any of the patterns", #term_ident)),
+    };
+
+    quote!(
+        #result_label: {
+            let #term_ident = &(#term);
+            #arms
+            #fallback
+        }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_pattern() {
+        assert_eq!(
+            parse_pattern("and(true, x)"),
+            Ok(Pattern::Application(
+                "and".to_string(),
+                vec![
+                    Pattern::Application("true".to_string(), vec![]),
+                    Pattern::Variable("x".to_string())
+                ]
+            ))
+        );
+        assert_eq!(
+            parse_pattern(" f(_ ,12) "),
+            Ok(Pattern::Application(
+                "f".to_string(),
+                vec![Pattern::Wildcard, Pattern::Int(12)]
+            ))
+        );
+        assert!(parse_pattern("f(x").is_err());
+        assert!(parse_pattern("f(x) y").is_err());
+        assert!(parse_pattern("f(,x)").is_err());
+    }
+
+    #[test]
+    fn test_match_term_macro() {
+        let input = r#"t, "and(true, x)" => x.protect(), "not(x1)" => x1.protect(), _ => t.clone()"#;
+
+        let tokens = TokenStream::from_str(input).unwrap();
+        let result = match_term_impl(tokens);
+
+        // The generated code is a labelled block with one labelled block per pattern and the wildcard as fallback.
+        let code = result.to_string();
+        assert!(syn::parse2::<Expr>(result).is_ok());
+        assert!(!code.contains("compile_error"));
+        assert!(code.contains("'__match_term_arm_0") && code.contains("'__match_term_arm_1"));
+        assert!(code.contains("\"and\"") && code.contains("\"true\"") && code.contains("\"not\""));
+        assert!(!code.contains("panic"));
+
+        let tokens = TokenStream::from_str(r#"t, "f(x" => x.protect()"#).unwrap();
+        assert!(match_term_impl(tokens).to_string().contains("compile_error"));
+    }
+}
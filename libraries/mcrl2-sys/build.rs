@@ -62,6 +62,8 @@ fn main() {
         //"symbolic_lts_io.cpp",
     ];
 
+    let pbes_source_files = ["pbes.cpp", "pbes_io.cpp"];
+
     let data_source_files = [
         "data.cpp",
         "data_io.cpp",
@@ -114,7 +116,7 @@ fn main() {
     build_dparser.compile("dparser");
 
     // These are the files for which we need to call cxxbuild to produce the bridge code.
-    let mut build = cxx_build::bridges(["src/atermpp.rs", "src/data.rs", "src/lps.rs"]);
+    let mut build = cxx_build::bridges(["src/atermpp.rs", "src/data.rs", "src/lps.rs", "src/pbes.rs"]);
 
     // Additional files needed to compile the bridge, basically to build mCRL2 itself.
     build
@@ -129,6 +131,7 @@ fn main() {
                 "libraries/core/include",
                 "libraries/data/include",
                 "libraries/lps/include",
+                "libraries/pbes/include",
                 "libraries/process/include",
                 "libraries/utilities/include",
             ],
@@ -144,6 +147,10 @@ fn main() {
             mcrl2_path.clone() + "libraries/lps/source/",
             &lps_source_files,
         ))
+        .files(add_prefix(
+            mcrl2_path.clone() + "libraries/pbes/source/",
+            &pbes_source_files,
+        ))
         .files(add_prefix(
             mcrl2_path.clone() + "libraries/data/source/",
             &data_source_files,
@@ -200,4 +207,5 @@ fn main() {
     rerun_if_changed!("cpp/atermpp/atermpp.h");
     rerun_if_changed!("cpp/data/data.h");
     rerun_if_changed!("cpp/lps/lps.h");
+    rerun_if_changed!("cpp/pbes/pbes.h");
 }
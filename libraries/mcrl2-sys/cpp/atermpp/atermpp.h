@@ -5,6 +5,7 @@
 #include "mcrl2/core/identifier_string.h"
 
 #include "mcrl2/atermpp/aterm_core.h"
+#include "mcrl2/atermpp/aterm_int.h"
 #include "mcrl2/atermpp/aterm_io_text.h"
 #include "mcrl2/atermpp/detail/aterm_hash.h"
 #include "mcrl2/atermpp/detail/aterm_pool_storage_implementation.h"
@@ -172,6 +173,18 @@ bool aterm_is_empty_list(const detail::_aterm* term)
   return t.function() == detail::g_as_empty_list;
 }
 
+std::size_t aterm_int_value(const detail::_aterm* term)
+{
+  return reinterpret_cast<const detail::_aterm_int*>(term)->value();
+}
+
+const detail::_aterm* create_aterm_int(std::size_t value)
+{
+  unprotected_aterm_core result(nullptr);
+  make_aterm_int(reinterpret_cast<aterm_int&>(result), value);
+  return detail::address(result);
+}
+
 rust::String print_aterm(const detail::_aterm* term)
 {
   atermpp::unprotected_aterm_core t(term);
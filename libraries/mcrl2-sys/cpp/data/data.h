@@ -6,6 +6,7 @@
 #include "mcrl2/atermpp/aterm.h"
 #include "mcrl2/core/identifier_string.h"
 #include "mcrl2/data/detail/rewrite/jitty.h"
+#include "mcrl2/data/machine_number.h"
 #include "mcrl2/data/sort_expression.h"
 #include "mcrl2/data/parse.h"
 
@@ -104,6 +105,12 @@ std::unique_ptr<std::vector<atermpp::aterm>> get_data_specification_constructors
   return std::make_unique<std::vector<atermpp::aterm>>(constructors.begin(), constructors.end());
 }
 
+std::unique_ptr<std::vector<atermpp::aterm>> get_data_specification_sorts(const data_specification& data_spec)
+{
+  const auto& sorts = data_spec.sorts();
+  return std::make_unique<std::vector<atermpp::aterm>>(sorts.begin(), sorts.end());
+}
+
 bool is_data_where_clause(const atermpp::detail::_aterm* term)
 {
   atermpp::unprotected_aterm_core t(term);
@@ -183,6 +190,19 @@ bool is_data_machine_number(const atermpp::detail::_aterm* term)
   return mcrl2::data::is_machine_number(static_cast<const atermpp::aterm&>(t));
 }
 
+std::uint64_t get_data_machine_number_value(const atermpp::detail::_aterm* term)
+{
+  atermpp::unprotected_aterm_core t(term);
+  return atermpp::down_cast<machine_number>(static_cast<const atermpp::aterm&>(t)).value();
+}
+
+const atermpp::detail::_aterm* create_data_machine_number(std::uint64_t value)
+{
+  atermpp::unprotected_aterm_core result(nullptr);
+  make_machine_number(reinterpret_cast<machine_number&>(result), value);
+  return atermpp::detail::address(result);
+}
+
 std::unique_ptr<atermpp::aterm> true_term() 
 {
   return std::make_unique<atermpp::aterm>(data::sort_bool::true_());
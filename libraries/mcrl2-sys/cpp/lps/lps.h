@@ -4,7 +4,13 @@
 
 #include "rust/cxx.h"
 
+#include "mcrl2/data/detail/io.h"
+#include "mcrl2/data/rewriter.h"
+#include "mcrl2/lps/constelm.h"
 #include "mcrl2/lps/io.h"
+#include "mcrl2/lps/parelm.h"
+#include "mcrl2/lps/sumelm.h"
+#include "mcrl2/lps/suminst.h"
 #include "mcrl2/utilities/exception.h"
 
 namespace mcrl2::lps
@@ -17,11 +23,77 @@ std::unique_ptr<specification> read_linear_process_specification(rust::Str filen
   return result;
 }
 
+void write_linear_process_specification(const specification& spec, rust::Str filename)
+{
+  save_lps(spec, std::string(filename));
+}
+
+void remove_constant_parameters(specification& spec, bool instantiate_global_variables)
+{
+  data::rewriter rewriter(spec.data());
+  constelm(spec, rewriter, instantiate_global_variables);
+}
+
+void remove_unused_parameters(specification& spec)
+{
+  parelm(spec);
+}
+
+void eliminate_sum_variables(specification& spec)
+{
+  sumelm_algorithm<specification>(spec).run();
+}
+
+void instantiate_sum_variables(specification& spec, bool tau_summands_only)
+{
+  data::rewriter rewriter(spec.data());
+  suminst_algorithm<data::rewriter, specification>(spec, rewriter, finite_sorts(spec.data()), tau_summands_only).run();
+}
+
 std::unique_ptr<mcrl2::data::data_specification> get_data_specification(const specification& spec)
 {
   return std::make_unique<mcrl2::data::data_specification>(spec.data());
 }
 
+std::unique_ptr<atermpp::aterm> get_data_specification_term(const specification& spec)
+{
+  return std::make_unique<atermpp::aterm>(data::detail::data_specification_to_aterm(spec.data()));
+}
+
+std::unique_ptr<atermpp::aterm> get_action_labels(const specification& spec)
+{
+  return std::make_unique<atermpp::aterm>(spec.action_labels());
+}
+
+std::unique_ptr<atermpp::aterm> get_process_parameters(const specification& spec)
+{
+  return std::make_unique<atermpp::aterm>(spec.process().process_parameters());
+}
+
+std::unique_ptr<atermpp::aterm> get_initial_state(const specification& spec)
+{
+  return std::make_unique<atermpp::aterm>(spec.initial_process().expressions());
+}
+
+std::unique_ptr<std::vector<atermpp::aterm>> get_action_summands(const specification& spec)
+{
+  const data::variable_list& parameters = spec.process().process_parameters();
+
+  // Every summand is stored as the list [variables, condition, actions, time, next state].
+  auto result = std::make_unique<std::vector<atermpp::aterm>>();
+  for (const action_summand& summand : spec.process().action_summands())
+  {
+    result->push_back(atermpp::aterm_list({
+      atermpp::aterm(summand.summation_variables()),
+      atermpp::aterm(summand.condition()),
+      atermpp::aterm(summand.multi_action().actions()),
+      atermpp::aterm(summand.multi_action().time()),
+      atermpp::aterm(summand.next_state(parameters)),
+    }));
+  }
+
+  return result;
+}
 
 rust::String print_linear_process_specification(const specification& spec)
 {
@@ -30,4 +102,18 @@ rust::String print_linear_process_specification(const specification& spec)
   return str.str();
 }
 
+rust::String print_linear_process(const specification& spec)
+{
+  // Printing a specification with an empty data specification omits the sort, map and equation sections.
+  specification process(data::data_specification(),
+      spec.action_labels(),
+      spec.global_variables(),
+      spec.process(),
+      spec.initial_process());
+
+  std::stringstream str;
+  str << process;
+  return str.str();
+}
+
 } // namespace mcrl2::lps
\ No newline at end of file
@@ -0,0 +1,118 @@
+#pragma once
+#include <memory>
+#include <string>
+
+#include "rust/cxx.h"
+
+#include "mcrl2/atermpp/aterm.h"
+#include "mcrl2/data/detail/io.h"
+#include "mcrl2/pbes/io.h"
+#include "mcrl2/pbes/pbes.h"
+#include "mcrl2/pbes/replace.h"
+#include "mcrl2/pbes/txt2pbes.h"
+
+namespace mcrl2::pbes_system
+{
+
+namespace detail
+{
+
+/// Recreates the propositional variable instantiations in the given expression, since the index that is stored in
+/// them is not known to the Rust side.
+inline pbes_expression reindex(const pbes_expression& expression)
+{
+  return replace_propositional_variables(expression,
+      [](const propositional_variable_instantiation& x)
+      { return propositional_variable_instantiation(x.name(), x.parameters()); });
+}
+
+} // namespace detail
+
+std::unique_ptr<pbes> read_pbes(rust::Str filename)
+{
+  auto result = std::make_unique<pbes>();
+  load_pbes(*result, std::string(filename));
+  return result;
+}
+
+void write_pbes(const pbes& p, rust::Str filename)
+{
+  save_pbes(p, std::string(filename));
+}
+
+std::unique_ptr<pbes> parse_pbes(rust::Str text)
+{
+  return std::make_unique<pbes>(txt2pbes(std::string(text), false));
+}
+
+std::unique_ptr<pbes> create_pbes(const data::data_specification& data_spec,
+    const atermpp::detail::_aterm* global_variables,
+    const atermpp::detail::_aterm* equations,
+    const atermpp::detail::_aterm* initial_state)
+{
+  atermpp::unprotected_aterm_core globals_term(global_variables);
+  atermpp::unprotected_aterm_core equations_term(equations);
+  atermpp::unprotected_aterm_core initial_term(initial_state);
+
+  const auto& globals = static_cast<const data::variable_list&>(static_cast<const atermpp::aterm&>(globals_term));
+  std::set<data::variable> global_set(globals.begin(), globals.end());
+
+  // Every equation is the term PBEqn(FixPoint, PropVarDecl, PBExpr).
+  std::vector<pbes_equation> result_equations;
+  for (const atermpp::aterm& equation :
+      static_cast<const atermpp::aterm_list&>(static_cast<const atermpp::aterm&>(equations_term)))
+  {
+    const auto& declaration = static_cast<const propositional_variable&>(equation[1]);
+    result_equations.emplace_back(fixpoint_symbol(equation[0]),
+        propositional_variable(declaration.name(), declaration.parameters()),
+        detail::reindex(pbes_expression(equation[2])));
+  }
+
+  const auto& initial = static_cast<const propositional_variable_instantiation&>(
+      static_cast<const atermpp::aterm&>(initial_term));
+  return std::make_unique<pbes>(data_spec,
+      global_set,
+      result_equations,
+      propositional_variable_instantiation(initial.name(), initial.parameters()));
+}
+
+rust::String print_pbes(const pbes& p)
+{
+  std::stringstream str;
+  str << p;
+  return str.str();
+}
+
+std::unique_ptr<data::data_specification> get_pbes_data_specification(const pbes& p)
+{
+  return std::make_unique<data::data_specification>(p.data());
+}
+
+std::unique_ptr<atermpp::aterm> get_pbes_data_specification_term(const pbes& p)
+{
+  return std::make_unique<atermpp::aterm>(data::detail::data_specification_to_aterm(p.data()));
+}
+
+std::unique_ptr<atermpp::aterm> get_pbes_global_variables(const pbes& p)
+{
+  return std::make_unique<atermpp::aterm>(
+      data::variable_list(p.global_variables().begin(), p.global_variables().end()));
+}
+
+std::unique_ptr<std::vector<atermpp::aterm>> get_pbes_equations(const pbes& p)
+{
+  auto result = std::make_unique<std::vector<atermpp::aterm>>();
+  for (const pbes_equation& equation : p.equations())
+  {
+    result->push_back(pbes_equation_to_aterm(equation));
+  }
+
+  return result;
+}
+
+std::unique_ptr<atermpp::aterm> get_pbes_initial_state(const pbes& p)
+{
+  return std::make_unique<atermpp::aterm>(p.initial_state());
+}
+
+} // namespace mcrl2::pbes_system
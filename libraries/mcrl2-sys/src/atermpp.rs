@@ -84,6 +84,13 @@ pub mod ffi {
         /// Returns true iff the term is an aterm_int.
         unsafe fn aterm_is_int(term: *const _aterm) -> bool;
 
+        /// Returns the value of an aterm_int.
+        unsafe fn aterm_int_value(term: *const _aterm) -> usize;
+
+        /// Creates an aterm_int with the given value, must be protected before
+        /// the busy flags are set to false.
+        fn create_aterm_int(value: usize) -> *const _aterm;
+
         /// Converts an aterm to a string.
         unsafe fn print_aterm(term: *const _aterm) -> String;
 
@@ -37,6 +37,9 @@ pub mod ffi {
             sort: *const _aterm,
         ) -> UniquePtr<CxxVector<aterm>>;
 
+        /// Returns the sorts defined in the data specification.
+        fn get_data_specification_sorts(data_spec: &data_specification) -> UniquePtr<CxxVector<aterm>>;
+
         /// Creates an instance of the jitty rewriter.
         fn create_jitty_rewriter(data_spec: &data_specification) -> UniquePtr<RewriterJitty>;
 
@@ -83,5 +86,12 @@ pub mod ffi {
         unsafe fn is_data_abstraction(term: *const _aterm) -> bool;
         unsafe fn is_data_untyped_identifier(term: *const _aterm) -> bool;
         unsafe fn is_data_machine_number(term: *const _aterm) -> bool;
+
+        // For data::machine_number
+        /// Returns the value of the given machine number.
+        unsafe fn get_data_machine_number_value(term: *const _aterm) -> u64;
+
+        /// Creates an unprotected machine number, must be within in a critical section.
+        fn create_data_machine_number(value: u64) -> *const _aterm;
     }
 }
@@ -9,6 +9,7 @@
 pub mod atermpp;
 pub mod data;
 pub mod lps;
+pub mod pbes;
 
 // Reexport the cxx types that we use
 pub mod cxx {
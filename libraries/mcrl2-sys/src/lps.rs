@@ -7,15 +7,52 @@ pub mod ffi {
         #[namespace = "mcrl2::data"]
         type data_specification = crate::data::ffi::data_specification;
 
+        #[namespace = "atermpp"]
+        type aterm = crate::atermpp::ffi::aterm;
+
         type specification;
 
         /// Reads a .lps file and returns the resulting linear process specification.
         fn read_linear_process_specification(filename: &str) -> Result<UniquePtr<specification>>;
 
+        /// Writes the linear process specification to the given .lps file.
+        fn write_linear_process_specification(spec: &specification, filename: &str) -> Result<()>;
+
+        /// Removes the process parameters that have a constant value in every reachable state (constelm).
+        fn remove_constant_parameters(spec: Pin<&mut specification>, instantiate_global_variables: bool);
+
+        /// Removes the process parameters that do not influence the behaviour (parelm).
+        fn remove_unused_parameters(spec: Pin<&mut specification>);
+
+        /// Removes the sum variables that are determined by an equality in the condition (sumelm).
+        fn eliminate_sum_variables(spec: Pin<&mut specification>);
+
+        /// Replaces the sum variables of a finite sort by a summand for every value (suminst).
+        fn instantiate_sum_variables(spec: Pin<&mut specification>, tau_summands_only: bool);
+
         /// Converts a linear process specification to a string.
         fn print_linear_process_specification(spec: &specification) -> String;
 
+        /// Converts the action declarations, process equation and initial process to a string.
+        fn print_linear_process(spec: &specification) -> String;
+
         /// Obtains the related data specification
         fn get_data_specification(spec: &specification) -> UniquePtr<data_specification>;
+
+        /// Returns the data specification as a term, as it is stored in .lps and .lts files.
+        fn get_data_specification_term(spec: &specification) -> UniquePtr<aterm>;
+
+        /// Returns the list of action label declarations.
+        fn get_action_labels(spec: &specification) -> UniquePtr<aterm>;
+
+        /// Returns the list of process parameters.
+        fn get_process_parameters(spec: &specification) -> UniquePtr<aterm>;
+
+        /// Returns the list of data expressions of the initial state.
+        fn get_initial_state(spec: &specification) -> UniquePtr<aterm>;
+
+        /// Returns the action summands, where every summand is the list
+        /// [variables, condition, actions, time, next state].
+        fn get_action_summands(spec: &specification) -> UniquePtr<CxxVector<aterm>>;
     }
 }
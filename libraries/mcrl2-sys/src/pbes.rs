@@ -0,0 +1,55 @@
+#[cxx::bridge(namespace = "mcrl2::pbes_system")]
+#[allow(clippy::missing_safety_doc)]
+pub mod ffi {
+
+    unsafe extern "C++" {
+        include!("mcrl2-sys/cpp/pbes/pbes.h");
+
+        #[namespace = "mcrl2::data"]
+        type data_specification = crate::data::ffi::data_specification;
+
+        #[namespace = "atermpp"]
+        type aterm = crate::atermpp::ffi::aterm;
+
+        #[namespace = "atermpp::detail"]
+        type _aterm = crate::atermpp::ffi::_aterm;
+
+        type pbes;
+
+        /// Reads a .pbes file and returns the resulting parameterised boolean equation system.
+        fn read_pbes(filename: &str) -> Result<UniquePtr<pbes>>;
+
+        /// Writes the parameterised boolean equation system to the given .pbes file.
+        fn write_pbes(p: &pbes, filename: &str) -> Result<()>;
+
+        /// Parses and typechecks the given text in the textual PBES format.
+        fn parse_pbes(text: &str) -> Result<UniquePtr<pbes>>;
+
+        /// Creates a PBES from the list of global variables, the list of equations
+        /// PBEqn(FixPoint, PropVarDecl, PBExpr) and the initial PropVarInst.
+        unsafe fn create_pbes(
+            data_spec: &data_specification,
+            global_variables: *const _aterm,
+            equations: *const _aterm,
+            initial_state: *const _aterm,
+        ) -> Result<UniquePtr<pbes>>;
+
+        /// Converts a PBES to a string in the textual format.
+        fn print_pbes(p: &pbes) -> String;
+
+        /// Obtains the related data specification.
+        fn get_pbes_data_specification(p: &pbes) -> UniquePtr<data_specification>;
+
+        /// Returns the data specification as a term, as it is stored in .pbes files.
+        fn get_pbes_data_specification_term(p: &pbes) -> UniquePtr<aterm>;
+
+        /// Returns the list of global variables.
+        fn get_pbes_global_variables(p: &pbes) -> UniquePtr<aterm>;
+
+        /// Returns the equations, where every equation is the term PBEqn(FixPoint, PropVarDecl, PBExpr).
+        fn get_pbes_equations(p: &pbes) -> UniquePtr<CxxVector<aterm>>;
+
+        /// Returns the initial state PropVarInst(name, arguments, index).
+        fn get_pbes_initial_state(p: &pbes) -> UniquePtr<aterm>;
+    }
+}
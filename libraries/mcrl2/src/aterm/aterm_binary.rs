@@ -0,0 +1,77 @@
+//! Implements the traits of [io::io_baf] such that terms can be read from and
+//! written to the binary aterm format.
+
+use io::io_baf::BinaryTerm;
+use io::io_baf::BinaryTermFactory;
+
+use crate::aterm::ATerm;
+use crate::aterm::Symbol;
+use crate::aterm::TermPool;
+
+impl BinaryTerm for ATerm {
+    fn value(&self) -> Option<u64> {
+        if self.is_int() {
+            Some(self.int_value() as u64)
+        } else {
+            None
+        }
+    }
+
+    fn symbol(&self) -> (String, usize) {
+        let symbol = self.get_head_symbol();
+        (symbol.name().to_string(), symbol.arity())
+    }
+
+    fn arguments(&self) -> Vec<Self> {
+        self.copy().arguments().map(|argument| argument.protect()).collect()
+    }
+}
+
+impl BinaryTermFactory for TermPool {
+    type Term = ATerm;
+    type Symbol = Symbol;
+
+    fn create_symbol(&mut self, name: &str, arity: usize) -> Symbol {
+        TermPool::create_symbol(self, name, arity)
+    }
+
+    fn create_term(&mut self, symbol: &Symbol, arguments: &[ATerm]) -> ATerm {
+        self.create(symbol, arguments)
+    }
+
+    fn create_int(&mut self, value: u64) -> ATerm {
+        TermPool::create_int(self, value as usize).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use io::io_baf::BinaryATermReader;
+    use io::io_baf::BinaryATermWriter;
+
+    use super::*;
+
+    #[test]
+    fn test_binary_aterm_round_trip() {
+        let mut tp = TermPool::new();
+
+        let terms = [
+            tp.from_string("f(g(a), g(a), 42)").unwrap(),
+            tp.from_string("[a, b, g(a)]").unwrap(),
+            tp.from_string("7").unwrap(),
+        ];
+
+        let mut buffer = Vec::new();
+        let mut writer = BinaryATermWriter::new(&mut buffer).unwrap();
+        for term in &terms {
+            writer.write(term).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let mut reader = BinaryATermReader::new(&buffer[..]).unwrap();
+        for term in &terms {
+            assert_eq!(reader.read(&mut tp).unwrap().as_ref(), Some(term));
+        }
+        assert_eq!(reader.read(&mut tp).unwrap(), None);
+    }
+}
@@ -1,3 +1,4 @@
+use std::borrow::Borrow;
 use std::error::Error;
 use std::fmt;
 
@@ -5,7 +6,10 @@ use ahash::AHashSet;
 use log::trace;
 
 use crate::aterm::ATerm;
+use crate::aterm::ATermRef;
+use crate::aterm::Protected;
 use crate::aterm::Symbol;
+use crate::aterm::SymbolRef;
 use crate::aterm::TermPool;
 
 /// This can be used to construct a term from a given input of (inductive) type I,
@@ -202,6 +206,70 @@ impl<I: fmt::Debug, C: fmt::Debug> fmt::Debug for Config<I, C> {
     }
 }
 
+/// Constructs terms bottom-up on an explicit stack, which avoids recursion when
+/// building deep terms. The arguments of a term are pushed in order after which
+/// [BottomUpBuilder::push_symbol] replaces them by the application of the
+/// symbol, i.e., the terms are given in post order.
+///
+/// The terms on the stack are stored in a protected container, such that they
+/// do not need to be protected individually.
+#[derive(Debug, Default)]
+pub struct BottomUpBuilder {
+    terms: Protected<Vec<ATermRef<'static>>>,
+}
+
+impl BottomUpBuilder {
+    pub fn new() -> BottomUpBuilder {
+        BottomUpBuilder::default()
+    }
+
+    /// Pushes the given term onto the stack.
+    pub fn push(&mut self, term: &ATermRef<'_>) {
+        let mut write = self.terms.write();
+        let term = write.protect(term);
+        write.push(term);
+    }
+
+    /// Replaces the top `arity` terms on the stack by the term symbol(t_0, ..., t_arity), where t_arity is the top of the stack.
+    pub fn push_symbol<'a>(&mut self, tp: &mut TermPool, symbol: &impl Borrow<SymbolRef<'a>>, arity: usize) {
+        debug_assert_eq!(symbol.borrow().arity(), arity, "The arity does not match the symbol");
+
+        let mut write = self.terms.write();
+        let start = write
+            .len()
+            .checked_sub(arity)
+            .expect("The stack should contain at least arity terms");
+
+        let result = tp.create(symbol, &write[start..]);
+        write.truncate(start);
+
+        let term = write.protect(&result);
+        write.push(term);
+    }
+
+    /// Removes the top of the stack and returns it.
+    pub fn pop(&mut self) -> Option<ATerm> {
+        let result = self.terms.read().last().map(|term| term.protect());
+        self.terms.write().pop();
+        result
+    }
+
+    /// Returns the number of terms on the stack.
+    pub fn len(&self) -> usize {
+        self.terms.read().len()
+    }
+
+    /// Returns true iff the stack is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all terms from the stack.
+    pub fn clear(&mut self) {
+        self.terms.write().clear();
+    }
+}
+
 /// Create a random term consisting of the given symbol and constants. Performs
 /// iterations number of constructions, and uses chance_duplicates to choose the
 /// amount of subterms that are duplicated.
@@ -240,3 +308,36 @@ pub fn random_term(
 
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bottom_up_builder() {
+        let mut tp = TermPool::new();
+        let f = tp.create_symbol("f", 2);
+        let a = tp.from_string("a").unwrap();
+
+        // Construct a list-like term that is too deep to construct recursively.
+        let depth = 100000;
+        let mut builder = BottomUpBuilder::new();
+        builder.push(&a);
+        for _ in 0..depth {
+            builder.push(&a);
+            builder.push_symbol(&mut tp, &f, 2);
+        }
+        tp.collect();
+
+        assert_eq!(builder.len(), 1);
+        let result = builder.pop().unwrap();
+        assert!(builder.is_empty());
+
+        let mut current = result;
+        for _ in 0..depth {
+            assert_eq!(current.arg(1), a.copy());
+            current = current.arg(0).protect();
+        }
+        assert_eq!(current, a);
+    }
+}
@@ -7,7 +7,6 @@ use std::pin::Pin;
 use std::sync::Arc;
 
 use mcrl2_sys::atermpp::ffi;
-use utilities::PhantomUnsend;
 
 use crate::aterm::ATermRef;
 use crate::aterm::BfTermPool;
@@ -25,9 +24,8 @@ pub struct Protected<C> {
     container: Arc<BfTermPool<C>>,
     root: usize,
 
-    // Protected is not Send because it uses thread-local state for its protection
-    // mechanism.
-    _unsend: PhantomUnsend,
+    /// The index of the thread term pool whose container protection set contains the root.
+    pool: usize,
 }
 
 impl<C: Markable + Send + 'static> Protected<C> {
@@ -35,12 +33,12 @@ impl<C: Markable + Send + 'static> Protected<C> {
     pub fn new(container: C) -> Protected<C> {
         let shared = Arc::new(BfTermPool::new(container));
 
-        let root = THREAD_TERM_POOL.with_borrow_mut(|tp| tp.protect_container(shared.clone()));
+        let (root, pool) = THREAD_TERM_POOL.with_borrow_mut(|tp| (tp.protect_container(shared.clone()), tp.index()));
 
         Protected {
             container: shared,
             root,
-            _unsend: Default::default(),
+            pool,
         }
     }
 
@@ -109,7 +107,7 @@ impl<C: Ord + PartialEq + PartialOrd + Markable> Ord for Protected<C> {
 impl<C> Drop for Protected<C> {
     fn drop(&mut self) {
         THREAD_TERM_POOL.with_borrow_mut(|tp| {
-            tp.drop_container(self.root);
+            tp.drop_container(self.root, self.pool);
         });
     }
 }
@@ -0,0 +1,196 @@
+use std::fmt;
+
+use ahash::AHashMap;
+use ahash::AHashSet;
+
+use crate::aterm::ATerm;
+use crate::aterm::ATermRef;
+
+/// A map that associates values with terms, for example to store the depth or
+/// the normal form of shared subterms.
+///
+/// Due to maximal sharing, terms are compared and hashed by their address
+/// instead of their structure, which takes constant time. The keys are
+/// protected by the map, so they are not garbage collected while the entry
+/// exists.
+pub struct TermMap<T> {
+    map: AHashMap<ATerm, T>,
+}
+
+impl<T> TermMap<T> {
+    pub fn new() -> TermMap<T> {
+        TermMap { map: AHashMap::new() }
+    }
+
+    /// Associates the value with the given term, and returns the previous value if it existed.
+    pub fn insert(&mut self, term: &ATermRef<'_>, value: T) -> Option<T> {
+        match self.map.get_mut(term) {
+            Some(existing) => Some(std::mem::replace(existing, value)),
+            None => {
+                self.map.insert(term.protect(), value);
+                None
+            }
+        }
+    }
+
+    /// Returns the value associated with the given term.
+    pub fn get(&self, term: &ATermRef<'_>) -> Option<&T> {
+        self.map.get(term)
+    }
+
+    /// Returns a mutable reference to the value associated with the given term.
+    pub fn get_mut(&mut self, term: &ATermRef<'_>) -> Option<&mut T> {
+        self.map.get_mut(term)
+    }
+
+    /// Returns the value associated with the given term, inserting the result of `default` when there is none.
+    pub fn get_or_insert_with(&mut self, term: &ATermRef<'_>, default: impl FnOnce() -> T) -> &mut T {
+        if !self.map.contains_key(term) {
+            self.map.insert(term.protect(), default());
+        }
+
+        self.map.get_mut(term).expect("The term was inserted above")
+    }
+
+    /// Removes the given term from the map and returns its value.
+    pub fn remove(&mut self, term: &ATermRef<'_>) -> Option<T> {
+        self.map.remove(term)
+    }
+
+    /// Returns true iff a value is associated with the given term.
+    pub fn contains_key(&self, term: &ATermRef<'_>) -> bool {
+        self.map.contains_key(term)
+    }
+
+    /// Returns an iterator over all terms and their values, in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (ATermRef<'_>, &T)> {
+        self.map.iter().map(|(term, value)| (term.copy(), value))
+    }
+
+    /// Returns the number of terms in the map.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns true iff the map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Removes all entries from the map.
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+}
+
+impl<T> Default for TermMap<T> {
+    fn default() -> Self {
+        TermMap::new()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for TermMap<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.map.iter()).finish()
+    }
+}
+
+/// A set of terms, which are compared by their address similar to [TermMap].
+#[derive(Default)]
+pub struct TermSet {
+    set: AHashSet<ATerm>,
+}
+
+impl TermSet {
+    pub fn new() -> TermSet {
+        TermSet::default()
+    }
+
+    /// Adds the term to the set, returns true iff it was not yet present.
+    pub fn insert(&mut self, term: &ATermRef<'_>) -> bool {
+        if self.set.contains(term) {
+            false
+        } else {
+            self.set.insert(term.protect())
+        }
+    }
+
+    /// Returns true iff the set contains the given term.
+    pub fn contains(&self, term: &ATermRef<'_>) -> bool {
+        self.set.contains(term)
+    }
+
+    /// Removes the term from the set, returns true iff it was present.
+    pub fn remove(&mut self, term: &ATermRef<'_>) -> bool {
+        self.set.remove(term)
+    }
+
+    /// Returns an iterator over all terms in the set, in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = ATermRef<'_>> {
+        self.set.iter().map(|term| term.copy())
+    }
+
+    /// Returns the number of terms in the set.
+    pub fn len(&self) -> usize {
+        self.set.len()
+    }
+
+    /// Returns true iff the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.set.is_empty()
+    }
+
+    /// Removes all terms from the set.
+    pub fn clear(&mut self) {
+        self.set.clear();
+    }
+}
+
+impl fmt::Debug for TermSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.set.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::aterm::TermPool;
+
+    use super::*;
+
+    #[test]
+    fn test_term_map() {
+        let mut tp = TermPool::new();
+        let t = tp.from_string("f(g(a), g(a), b)").unwrap();
+
+        // Count the number of occurrences of every subterm.
+        let mut counts: TermMap<usize> = TermMap::new();
+        for subterm in t.iter() {
+            *counts.get_or_insert_with(&subterm, || 0) += 1;
+        }
+
+        let mut visited = TermSet::new();
+        for subterm in t.iter() {
+            visited.insert(&subterm);
+        }
+
+        // The keys are protected by the map, so the original term can be collected.
+        let g = tp.from_string("g(a)").unwrap();
+        let a = tp.from_string("a").unwrap();
+        drop(t);
+        tp.collect();
+
+        assert_eq!(counts.len(), 4);
+        assert_eq!(counts.get(&g), Some(&2));
+        assert_eq!(counts.get(&a), Some(&2));
+        assert_eq!(counts.insert(&a, 5), Some(2));
+        assert_eq!(counts.remove(&a), Some(5));
+        assert!(!counts.contains_key(&a));
+
+        assert_eq!(visited.len(), 4);
+        assert!(visited.contains(&g));
+        assert!(!visited.insert(&g));
+        assert!(visited.remove(&g));
+        assert!(!visited.contains(&g));
+    }
+}
@@ -5,20 +5,25 @@ use std::mem::ManuallyDrop;
 use std::sync::Arc;
 
 use log::trace;
+use serde::Serialize;
 
 use mcrl2_sys::atermpp::ffi;
-use mcrl2_sys::cxx::Exception;
 use mcrl2_sys::cxx::UniquePtr;
 use utilities::protection_set::ProtectionSet;
 
+use crate::aterm::aterm_text::parse_aterm;
 use crate::aterm::ATerm;
+use crate::aterm::ATermInt;
+use crate::aterm::ATermParseError;
 use crate::aterm::BfTermPoolThreadWrite;
 use crate::aterm::Symbol;
 use crate::data::BoolSort;
 use crate::data::DataExpression;
 
+use super::global_aterm_pool::collect_garbage;
 use super::global_aterm_pool::mark_protection_sets;
 use super::global_aterm_pool::protection_set_size;
+use super::global_aterm_pool::test_garbage_collection;
 use super::global_aterm_pool::ATermPtr;
 use super::global_aterm_pool::SharedContainerProtectionSet;
 use super::global_aterm_pool::SharedProtectionSet;
@@ -69,13 +74,13 @@ fn protect_with(
     let term = ATermRef::new(term);
     trace!("Protected term {:?}, index {}, protection set {}", term, root, index,);
 
-    let result = ATerm::new(term, root);
+    let result = ATerm::new(term, root, index);
 
     // Test for garbage collection intermediately.
     *gc_counter = gc_counter.saturating_sub(1);
 
     if guard.unlock() && *gc_counter == 0 {
-        ffi::test_garbage_collection();
+        test_garbage_collection();
         *gc_counter = TEST_GC_INTERVAL;
     }
 
@@ -109,6 +114,35 @@ impl ThreadTermPool {
         }
     }
 
+    /// Protects all the given terms, acquiring the protection set only once.
+    pub fn protect_many(&mut self, terms: &[ATermRef<'_>]) -> Vec<ATerm> {
+        let mut result = Vec::with_capacity(terms.len());
+
+        unsafe {
+            let mut guard = self.protection_set.write_exclusive();
+            for term in terms {
+                term.require_valid();
+                let root = guard.protect(ATermPtr::new(term.get()));
+                trace!(
+                    "Protected term {:?}, index {}, protection set {}",
+                    term,
+                    root,
+                    self.index,
+                );
+
+                result.push(ATerm::new(ATermRef::new(term.get()), root, self.index));
+            }
+
+            self.gc_counter = self.gc_counter.saturating_sub(terms.len());
+            if guard.unlock() && self.gc_counter == 0 {
+                test_garbage_collection();
+                self.gc_counter = TEST_GC_INTERVAL;
+            }
+        }
+
+        result
+    }
+
     /// Protects the given aterm address and returns the term.
     pub fn protect_container(&mut self, container: Arc<dyn Markable + Send + Sync>) -> usize {
         let root = unsafe { self.container_protection_set.write_exclusive().protect(container) };
@@ -118,10 +152,23 @@ impl ThreadTermPool {
         root
     }
 
-    /// Removes the [ATerm] from the protection set.
+    /// Removes the [ATerm] from the protection set of the thread term pool that protected it.
     pub fn drop(&mut self, term: &ATerm) {
         term.require_valid();
 
+        if term.pool != self.index {
+            // The term was sent from another thread, so its protection set must be locked exclusively.
+            let (protection_set, _) = GLOBAL_TERM_POOL.lock().thread_protection_sets(term.pool);
+            trace!(
+                "Dropped term {:?}, index {}, protection set {}",
+                term.term,
+                term.root,
+                term.pool
+            );
+            protection_set.write().unprotect(term.root);
+            return;
+        }
+
         unsafe {
             let mut protection_set = self.protection_set.write_exclusive();
             trace!(
@@ -134,19 +181,40 @@ impl ThreadTermPool {
         }
     }
 
-    /// Removes the container from the protection set.
-    pub fn drop_container(&mut self, container_root: usize) {
+    /// Removes all the given roots from the protection set, acquiring it only once.
+    pub(crate) fn drop_many(&mut self, roots: &[usize]) {
         unsafe {
-            let mut container_protection_set = self.container_protection_set.write_exclusive();
-            trace!(
-                "Dropped container index {}, protection set {}",
-                container_root,
-                self.index
-            );
-            container_protection_set.unprotect(container_root);
+            let mut protection_set = self.protection_set.write_exclusive();
+            for root in roots {
+                trace!("Dropped index {}, protection set {}", root, self.index);
+                protection_set.unprotect(*root);
+            }
         }
     }
 
+    /// Removes the container from the container protection set of the thread term pool with the given index.
+    pub fn drop_container(&mut self, container_root: usize, pool: usize) {
+        trace!("Dropped container index {}, protection set {}", container_root, pool);
+
+        if pool != self.index {
+            // The container was sent from another thread, so its protection set must be locked exclusively.
+            let (_, container_protection_set) = GLOBAL_TERM_POOL.lock().thread_protection_sets(pool);
+            container_protection_set.write().unprotect(container_root);
+            return;
+        }
+
+        unsafe {
+            self.container_protection_set
+                .write_exclusive()
+                .unprotect(container_root);
+        }
+    }
+
+    /// Returns the index of this thread term pool, which identifies its protection sets.
+    pub(crate) fn index(&self) -> usize {
+        self.index
+    }
+
     /// Returns true iff the given term is a data application.
     pub fn is_data_application(&mut self, term: &ATermRef<'_>) -> bool {
         let symbol = term.get_head_symbol();
@@ -171,12 +239,12 @@ impl Default for ThreadTermPool {
 
 impl Drop for ThreadTermPool {
     fn drop(&mut self) {
-        debug_assert!(
-            self.protection_set.read().len() == 0,
-            "The protection set should be empty"
-        );
+        // Terms and containers that were sent to other threads can outlive this thread, in which case the protection
+        // sets must remain registered. These are read before locking the global pool, since garbage collection
+        // acquires them in the opposite order.
+        let in_use = !self.protection_set.read().is_empty() || !self.container_protection_set.read().is_empty();
 
-        GLOBAL_TERM_POOL.lock().drop_thread_term_pool(self.index);
+        GLOBAL_TERM_POOL.lock().drop_thread_term_pool(self.index, in_use);
 
         #[cfg(not(target_os = "macos"))]
         unsafe {
@@ -185,12 +253,71 @@ impl Drop for ThreadTermPool {
     }
 }
 
+/// The metrics of the term pool and the protection sets of all threads.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct Metrics {
+    /// The number of terms in the term pool.
+    pub terms: usize,
+
+    /// The capacity of the term pool, for terms of all arities.
+    pub capacity: usize,
+
+    /// The number of terms protected by the thread local protection sets.
+    pub protected_terms: usize,
+
+    /// The total number of insertions into the thread local protection sets.
+    pub protection_insertions: u64,
+
+    /// The largest number of terms that were protected at the same time, summed over the threads.
+    pub peak_protected_terms: usize,
+
+    /// The number of terms protected by the global protection set.
+    pub global_terms: usize,
+
+    /// The number of protected containers.
+    pub containers: usize,
+
+    /// The number of terms in the protected containers.
+    pub terms_in_containers: usize,
+
+    /// The total number of insertions into the container protection sets.
+    pub container_insertions: u64,
+
+    /// The largest number of containers that were protected at the same time, summed over the threads.
+    pub peak_containers: usize,
+
+    /// The number of garbage collections.
+    pub garbage_collections: usize,
+}
+
+impl fmt::Display for Metrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f,
+            "{} terms, max capacity {}, {} variables in thread root sets, {} global terms and {} in {} containers (term set {} insertions, max {}; container set {} insertions, max {}), {} garbage collections",
+            self.terms,
+            self.capacity,
+            self.protected_terms,
+            self.global_terms,
+            self.terms_in_containers,
+            self.containers,
+            self.protection_insertions,
+            self.peak_protected_terms,
+            self.container_insertions,
+            self.peak_containers,
+            self.garbage_collections,
+        )
+    }
+}
+
 /// This is the thread local term pool.
 pub struct TermPool {
     arguments: Vec<*const ffi::_aterm>,
     true_term: DataExpression,
 }
 
+// The arguments only hold pointers during the creation of a single term.
+unsafe impl Send for TermPool {}
+
 impl TermPool {
     pub fn new() -> TermPool {
         TermPool {
@@ -206,15 +333,22 @@ impl TermPool {
 
     /// Trigger a garbage collection explicitly.
     pub fn collect(&mut self) {
-        ffi::collect_garbage();
+        collect_garbage();
     }
 
-    /// Creates an ATerm from a string.
-    pub fn from_string(&mut self, text: &str) -> Result<ATerm, Exception> {
-        match ffi::aterm_from_string(String::from(text)) {
-            Ok(term) => Ok(term.into()),
-            Err(exception) => Err(exception),
-        }
+    /// Returns the metrics of the term pool, which includes all threads.
+    pub fn metrics(&self) -> Metrics {
+        GLOBAL_TERM_POOL.lock().metrics()
+    }
+
+    /// Creates an ATerm from a string in the textual aterm format.
+    pub fn from_string(&mut self, text: &str) -> Result<ATerm, ATermParseError> {
+        parse_aterm(self, text)
+    }
+
+    /// Protects all the given terms at once, which is cheaper than protecting them one by one.
+    pub fn protect_many(&mut self, terms: &[ATermRef<'_>]) -> Vec<ATerm> {
+        THREAD_TERM_POOL.with_borrow_mut(|tp| tp.protect_many(terms))
     }
 
     /// Creates an [ATerm] with the given symbol and arguments.
@@ -293,6 +427,25 @@ impl TermPool {
         })
     }
 
+    /// Creates the aterm list with the given elements.
+    pub fn create_list(&mut self, elements: &[ATerm]) -> ATerm {
+        let cons = self.create_symbol("<list_constructor>", 2);
+        let empty = self.create_symbol("<empty_list>", 0);
+
+        let mut result = self.create(&empty, &[] as &[ATerm]);
+        for element in elements.iter().rev() {
+            let tail = result;
+            result = self.create(&cons, &[element.copy(), tail.copy()]);
+        }
+
+        result
+    }
+
+    /// Creates an integer term with the given value.
+    pub fn create_int(&mut self, value: usize) -> ATermInt {
+        self.create_with(|| ffi::create_aterm_int(value)).into()
+    }
+
     /// Creates a function symbol with the given name and arity.
     pub fn create_symbol(&mut self, name: &str, arity: usize) -> Symbol {
         Symbol::take(ffi::create_function_symbol(String::from(name), arity))
@@ -386,4 +539,37 @@ mod tests {
             }
         });
     }
+
+    #[test]
+    fn test_metrics() {
+        let mut tp = TermPool::new();
+        let t = tp.from_string("f(g(a), b)").unwrap();
+        let global = t.protect_global();
+
+        let before = tp.metrics();
+        tp.collect();
+        let after = tp.metrics();
+
+        assert!(after.terms >= 4);
+        assert!(after.protected_terms >= 1);
+        assert!(after.global_terms >= 1);
+        assert!(after.garbage_collections > before.garbage_collections);
+        drop(global);
+    }
+
+    #[test]
+    fn test_protect_many() {
+        let mut tp = TermPool::new();
+        let t = tp.from_string("f(g(a), b, g(a))").unwrap();
+
+        let arguments: Vec<ATermRef<'_>> = t.arguments().collect();
+        let protected = tp.protect_many(&arguments);
+        drop(t);
+        tp.collect();
+
+        assert_eq!(protected.len(), 3);
+        assert_eq!(protected[0], tp.from_string("g(a)").unwrap());
+        assert_eq!(protected[1], tp.from_string("b").unwrap());
+        assert_eq!(protected[0], protected[2]);
+    }
 }
@@ -0,0 +1,114 @@
+use std::borrow::Borrow;
+use std::cell::RefCell;
+use std::mem::ManuallyDrop;
+
+use utilities::PhantomUnsend;
+
+use crate::aterm::ATerm;
+use crate::aterm::ATermRef;
+use crate::aterm::SymbolRef;
+use crate::aterm::TermPool;
+use crate::aterm::THREAD_TERM_POOL;
+
+/// A guard that keeps all the terms created or protected in it alive until the
+/// scope is dropped, at which point they are all unprotected at once. This
+/// avoids the bookkeeping of protecting and dropping every intermediate term
+/// individually, for example during a single step of state space exploration.
+///
+/// The terms are returned as [ATermRef] whose lifetime is bound to the scope.
+#[derive(Default)]
+pub struct ProtectionScope {
+    roots: RefCell<Vec<usize>>,
+
+    // The roots are stored in the thread-local protection set.
+    _marker: PhantomUnsend,
+}
+
+impl ProtectionScope {
+    pub fn new() -> ProtectionScope {
+        ProtectionScope::default()
+    }
+
+    /// Protects the given term until the scope is dropped.
+    pub fn protect<'a>(&'a self, term: &ATermRef<'_>) -> ATermRef<'a> {
+        self.adopt(term.protect())
+    }
+
+    /// Moves the protection of the given term into the scope.
+    pub fn adopt<'a>(&'a self, term: ATerm) -> ATermRef<'a> {
+        if term.is_default() {
+            return ATermRef::default();
+        }
+
+        if term.pool != THREAD_TERM_POOL.with_borrow(|tp| tp.index()) {
+            // The roots of the scope are removed from the protection set of this thread.
+            return self.protect(&term.copy());
+        }
+
+        // The root is now owned by the scope, so the term should not be dropped.
+        let term = ManuallyDrop::new(term);
+        self.roots.borrow_mut().push(term.root);
+
+        unsafe { ATermRef::new(term.get()) }
+    }
+
+    /// Creates a term with the given symbol and arguments that is protected until the scope is dropped.
+    pub fn create<'a, 'b, 'c>(
+        &'a self,
+        tp: &mut TermPool,
+        symbol: &impl Borrow<SymbolRef<'b>>,
+        arguments: &[impl Borrow<ATermRef<'c>>],
+    ) -> ATermRef<'a> {
+        self.adopt(tp.create(symbol, arguments))
+    }
+
+    /// Returns the number of terms protected by this scope.
+    pub fn len(&self) -> usize {
+        self.roots.borrow().len()
+    }
+
+    /// Returns true iff no terms are protected by this scope.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Drop for ProtectionScope {
+    fn drop(&mut self) {
+        let roots = self.roots.get_mut();
+        if !roots.is_empty() {
+            THREAD_TERM_POOL.with_borrow_mut(|tp| tp.drop_many(roots));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_protection_scope() {
+        let mut tp = TermPool::new();
+        let f = tp.create_symbol("f", 2);
+        let a = tp.from_string("a").unwrap();
+
+        {
+            let scope = ProtectionScope::new();
+
+            let mut term = scope.protect(&a.copy());
+            for _ in 0..100 {
+                term = scope.create(&mut tp, &f, &[term, a.copy()]);
+            }
+            tp.collect();
+
+            assert_eq!(scope.len(), 101);
+            assert_eq!(term.get_head_symbol(), f.copy());
+            assert_eq!(term.arg(1), a.copy());
+        }
+
+        // All terms of the scope have been unprotected.
+        let term = tp.from_string("f(a, a)").unwrap();
+        tp.collect();
+        assert_eq!(term.arg(0), a.copy());
+    }
+}
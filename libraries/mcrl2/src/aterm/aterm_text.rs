@@ -0,0 +1,210 @@
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+use thiserror::Error;
+
+use crate::aterm::ATerm;
+use crate::aterm::TermPool;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ATermParseError {
+    #[error("Unexpected character '{0}' at position {1}")]
+    UnexpectedCharacter(char, usize),
+
+    #[error("Unexpected end of the input")]
+    UnexpectedEnd(),
+
+    #[error("The quoted name starting at position {0} is not terminated")]
+    UnterminatedName(usize),
+
+    #[error("The integer at position {0} is too large")]
+    InvalidInteger(usize),
+}
+
+/// A term of which the arguments are being parsed.
+enum Frame {
+    Application(String),
+    List,
+}
+
+/// Parses a term in the textual aterm format, which consists of function
+/// applications `f(t1, ..., tn)`, where the name can be quoted to contain
+/// special characters, integers and lists `[t1, ..., tn]`. Uses an explicit
+/// stack, such that deeply nested terms can be parsed.
+pub(crate) fn parse_aterm(tp: &mut TermPool, text: &str) -> Result<ATerm, ATermParseError> {
+    let mut input = text.char_indices().peekable();
+
+    // The terms of which the arguments are being parsed, and the arguments parsed so far.
+    let mut stack: Vec<(Frame, Vec<ATerm>)> = Vec::new();
+
+    loop {
+        skip_whitespace(&mut input);
+        let (position, character) = *input.peek().ok_or(ATermParseError::UnexpectedEnd())?;
+
+        let mut term = if character == '[' {
+            input.next();
+            skip_whitespace(&mut input);
+            if next_if(&mut input, ']') {
+                tp.create_list(&[])
+            } else {
+                stack.push((Frame::List, Vec::new()));
+                continue;
+            }
+        } else if character.is_ascii_digit() {
+            let mut value: usize = 0;
+            while let Some(&(_, digit)) = input.peek() {
+                let Some(digit) = digit.to_digit(10) else {
+                    break;
+                };
+
+                value = value
+                    .checked_mul(10)
+                    .and_then(|value| value.checked_add(digit as usize))
+                    .ok_or(ATermParseError::InvalidInteger(position))?;
+                input.next();
+            }
+
+            tp.create_int(value).into()
+        } else {
+            let name = parse_name(&mut input)?;
+
+            skip_whitespace(&mut input);
+            if next_if(&mut input, '(') {
+                skip_whitespace(&mut input);
+                if next_if(&mut input, ')') {
+                    let symbol = tp.create_symbol(&name, 0);
+                    tp.create(&symbol, &[] as &[ATerm])
+                } else {
+                    stack.push((Frame::Application(name), Vec::new()));
+                    continue;
+                }
+            } else {
+                let symbol = tp.create_symbol(&name, 0);
+                tp.create(&symbol, &[] as &[ATerm])
+            }
+        };
+
+        // Add the term to the enclosing terms, and construct the ones for which all arguments have been parsed.
+        loop {
+            skip_whitespace(&mut input);
+            let Some((_, arguments)) = stack.last_mut() else {
+                return match input.next() {
+                    None => Ok(term),
+                    Some((position, character)) => Err(ATermParseError::UnexpectedCharacter(character, position)),
+                };
+            };
+            arguments.push(term);
+
+            match input.next() {
+                Some((_, ',')) => break,
+                Some((position, character)) => {
+                    let (frame, arguments) = stack.pop().expect("The stack is not empty");
+                    term = match (frame, character) {
+                        (Frame::Application(name), ')') => {
+                            let symbol = tp.create_symbol(&name, arguments.len());
+                            tp.create(&symbol, &arguments)
+                        }
+                        (Frame::List, ']') => tp.create_list(&arguments),
+                        _ => return Err(ATermParseError::UnexpectedCharacter(character, position)),
+                    };
+                }
+                None => return Err(ATermParseError::UnexpectedEnd()),
+            }
+        }
+    }
+}
+
+/// Parses a function symbol name, which is either quoted or ends at a special character.
+fn parse_name(input: &mut Peekable<CharIndices<'_>>) -> Result<String, ATermParseError> {
+    let mut name = String::new();
+    let (start, _) = *input.peek().ok_or(ATermParseError::UnexpectedEnd())?;
+
+    if next_if(input, '"') {
+        loop {
+            match input.next() {
+                Some((_, '"')) => return Ok(name),
+                Some((_, '\\')) => match input.next() {
+                    Some((_, 'n')) => name.push('\n'),
+                    Some((_, character)) => name.push(character),
+                    None => return Err(ATermParseError::UnterminatedName(start)),
+                },
+                Some((_, character)) => name.push(character),
+                None => return Err(ATermParseError::UnterminatedName(start)),
+            }
+        }
+    }
+
+    while let Some(&(_, character)) = input.peek() {
+        if character.is_whitespace() || "()[],\"".contains(character) {
+            break;
+        }
+
+        name.push(character);
+        input.next();
+    }
+
+    if name.is_empty() {
+        let (position, character) = *input.peek().ok_or(ATermParseError::UnexpectedEnd())?;
+        return Err(ATermParseError::UnexpectedCharacter(character, position));
+    }
+
+    Ok(name)
+}
+
+fn skip_whitespace(input: &mut Peekable<CharIndices<'_>>) {
+    while input.next_if(|(_, character)| character.is_whitespace()).is_some() {}
+}
+
+/// Consumes the next character if it is the expected one.
+fn next_if(input: &mut Peekable<CharIndices<'_>>, expected: char) -> bool {
+    input.next_if(|&(_, character)| character == expected).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_aterm() {
+        let mut tp = TermPool::new();
+
+        let a = tp.from_string("a").unwrap();
+        let list = tp.from_string("[a, 12, []]").unwrap();
+        let term = tp.from_string(" f( a ,[a,12,[ ]], \"g(\\\"\"() ) ").unwrap();
+
+        let g = tp.create_symbol("g(\"", 0);
+        let g = tp.create(&g, &[] as &[ATerm]);
+        let f = tp.create_symbol("f", 3);
+        assert_eq!(term, tp.create(&f, &[a.copy(), list.copy(), g.copy()]));
+
+        assert!(list.is_list());
+        assert!(list.arg(1).arg(0).is_int());
+        assert!(list.arg(1).arg(1).arg(0).is_empty_list());
+    }
+
+    #[test]
+    fn test_parse_aterm_errors() {
+        let mut tp = TermPool::new();
+
+        assert_eq!(tp.from_string("f(a,").unwrap_err(), ATermParseError::UnexpectedEnd());
+        assert_eq!(
+            tp.from_string("f(a]").unwrap_err(),
+            ATermParseError::UnexpectedCharacter(']', 3)
+        );
+        assert_eq!(
+            tp.from_string("f(a) b").unwrap_err(),
+            ATermParseError::UnexpectedCharacter('b', 5)
+        );
+        assert_eq!(tp.from_string("\"f").unwrap_err(), ATermParseError::UnterminatedName(0));
+    }
+
+    #[test]
+    fn test_parse_deep_aterm() {
+        let mut tp = TermPool::new();
+
+        let depth = 100000;
+        let text = format!("{}a{}", "s(".repeat(depth), ")".repeat(depth));
+        let term = tp.from_string(&text).unwrap();
+        assert_eq!(term.get_head_symbol().name(), "s");
+    }
+}
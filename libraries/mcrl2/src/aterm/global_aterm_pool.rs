@@ -1,5 +1,10 @@
+use std::cell::Cell;
+use std::cell::UnsafeCell;
 use std::fmt::Debug;
+use std::hint;
 use std::pin::Pin;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::sync::LazyLock;
 
@@ -9,9 +14,11 @@ use parking_lot::Mutex;
 
 use mcrl2_sys::atermpp::ffi;
 use utilities::protection_set::ProtectionSet;
+use utilities::thread_id;
 
 use crate::aterm::ATermRef;
 use crate::aterm::BfTermPool;
+use crate::aterm::Metrics;
 
 use super::ATermGlobal;
 use super::Markable;
@@ -38,10 +45,175 @@ pub(crate) type SharedProtectionSet = Arc<BfTermPool<ProtectionSet<ATermPtr>>>;
 /// The protection set for containers.
 pub(crate) type SharedContainerProtectionSet = Arc<BfTermPool<ProtectionSet<Arc<dyn Markable + Sync + Send>>>>;
 
+/// The number of shards of the global protection set, must be a power of two.
+const GLOBAL_PROTECTION_SHARDS: usize = 64;
+
+/// A shard of the global protection set. The thread that owns the shard
+/// accesses it without locking by setting its busy flag, unless another
+/// thread has set the forbidden flag. The other threads, and the owner when
+/// access is forbidden, lock the shard and set the forbidden flag to wait
+/// until the owner is no longer busy.
+struct GlobalProtectionShard {
+    /// Set when a thread owns this shard.
+    owned: AtomicBool,
+
+    /// Set by the owner while it accesses the protection set without locking.
+    busy: AtomicBool,
+
+    /// Set while a thread that holds the lock accesses the protection set.
+    forbidden: AtomicBool,
+
+    lock: Mutex<()>,
+    set: UnsafeCell<ProtectionSet<ATermPtr>>,
+}
+
+/// The protection set is only accessed by the owner while it is busy, or by
+/// the thread holding the lock while access is forbidden.
+unsafe impl Sync for GlobalProtectionShard {}
+
+impl GlobalProtectionShard {
+    fn new() -> GlobalProtectionShard {
+        GlobalProtectionShard {
+            owned: AtomicBool::new(false),
+            busy: AtomicBool::new(false),
+            forbidden: AtomicBool::new(false),
+            lock: Mutex::new(()),
+            set: UnsafeCell::new(ProtectionSet::new()),
+        }
+    }
+
+    /// Calls the function with exclusive access to the protection set, where
+    /// `owner` indicates that the current thread owns this shard.
+    fn access<R>(&self, owner: bool, function: impl FnOnce(&mut ProtectionSet<ATermPtr>) -> R) -> R {
+        if owner {
+            // The fast path, which only fails when another thread has forbidden access.
+            self.busy.store(true, Ordering::SeqCst);
+            if !self.forbidden.load(Ordering::SeqCst) {
+                let result = function(unsafe { &mut *self.set.get() });
+                self.busy.store(false, Ordering::SeqCst);
+                return result;
+            }
+            self.busy.store(false, Ordering::SeqCst);
+        }
+
+        let _guard = self.lock.lock();
+        self.forbidden.store(true, Ordering::SeqCst);
+        while self.busy.load(Ordering::SeqCst) {
+            hint::spin_loop();
+        }
+
+        let result = function(unsafe { &mut *self.set.get() });
+        self.forbidden.store(false, Ordering::SeqCst);
+        result
+    }
+}
+
+/// The protection set for global terms, which is split into shards such that
+/// threads rarely contend for the same shard. Every thread claims a shard that
+/// it owns until it terminates, see [ShardOwner]. The shard is stored in the
+/// [ATermGlobal] such that it can be dropped on any thread, which is only lock
+/// free on the owning thread.
+static GLOBAL_PROTECTION_SETS: LazyLock<[GlobalProtectionShard; GLOBAL_PROTECTION_SHARDS]> =
+    LazyLock::new(|| std::array::from_fn(|_| GlobalProtectionShard::new()));
+
+/// The shard that is used by the current thread, and whether the thread owns it.
+struct ShardOwner {
+    shard: usize,
+    owner: bool,
+}
+
+impl ShardOwner {
+    /// Claims the first shard that is not owned, starting from the shard
+    /// determined by the [thread_id]. When there are more threads than shards
+    /// this thread shares that shard without owning it.
+    fn claim() -> ShardOwner {
+        let start = thread_id() & (GLOBAL_PROTECTION_SHARDS - 1);
+        for offset in 0..GLOBAL_PROTECTION_SHARDS {
+            let shard = (start + offset) & (GLOBAL_PROTECTION_SHARDS - 1);
+            if GLOBAL_PROTECTION_SETS[shard]
+                .owned
+                .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+                .is_ok()
+            {
+                return ShardOwner { shard, owner: true };
+            }
+        }
+
+        ShardOwner {
+            shard: start,
+            owner: false,
+        }
+    }
+}
+
+impl Drop for ShardOwner {
+    fn drop(&mut self) {
+        if self.owner {
+            GLOBAL_PROTECTION_SETS[self.shard].owned.store(false, Ordering::Release);
+        }
+    }
+}
+
+thread_local! {
+    static SHARD_OWNER: ShardOwner = ShardOwner::claim();
+}
+
+/// Returns the shard of the current thread and whether it owns the shard, or
+/// None when the thread is terminating.
+fn current_shard() -> Option<(usize, bool)> {
+    SHARD_OWNER.try_with(|owner| (owner.shard, owner.owner)).ok()
+}
+
+/// Protects the given aterm address on the global protection set and returns the term.
+pub(crate) fn protect_global(term: *const ffi::_aterm) -> ATermGlobal {
+    debug_assert!(!term.is_null(), "Can only protect valid terms");
+
+    let (shard, owner) = current_shard().unwrap_or((thread_id() & (GLOBAL_PROTECTION_SHARDS - 1), false));
+    let root = GLOBAL_PROTECTION_SETS[shard].access(owner, |set| set.protect(ATermPtr::new(term)));
+
+    let term = ATermRef::new(term);
+    trace!("Protected term {:?} global, shard {shard}, index {root}", term);
+
+    ATermGlobal { term, root, shard }
+}
+
+/// Removes the [ATermGlobal] from the global protection set.
+pub(crate) fn drop_global(term: &ATermGlobal) {
+    term.require_valid();
+
+    trace!(
+        "Dropped term {:?} global, shard {}, index {}",
+        term.term,
+        term.shard,
+        term.root
+    );
+    let owner = current_shard() == Some((term.shard, true));
+    GLOBAL_PROTECTION_SETS[term.shard].access(owner, |set| set.unprotect(term.root));
+}
+
+thread_local! {
+    /// Set when this thread asks the C++ library to collect garbage, such that the collection is counted when it marks the terms.
+    static COLLECTION_REQUESTED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Triggers a garbage collection explicitly.
+pub(crate) fn collect_garbage() {
+    COLLECTION_REQUESTED.set(true);
+    ffi::collect_garbage();
+    COLLECTION_REQUESTED.set(false);
+}
+
+/// Performs a garbage collection when the C++ library deems it necessary.
+pub(crate) fn test_garbage_collection() {
+    COLLECTION_REQUESTED.set(true);
+    ffi::test_garbage_collection();
+    COLLECTION_REQUESTED.set(false);
+}
+
 /// The single global (singleton) term pool.
 pub(crate) struct GlobalTermPool {
-    /// The protection set for global terms.
-    protection_set: ProtectionSet<ATermPtr>,
+    /// The number of garbage collections that have been performed.
+    garbage_collections: usize,
 
     /// The protection sets for thread local terms.
     thread_protection_sets: Vec<Option<SharedProtectionSet>>,
@@ -57,32 +229,12 @@ impl GlobalTermPool {
         ffi::enable_automatic_garbage_collection(false);
 
         GlobalTermPool {
-            protection_set: ProtectionSet::new(),
+            garbage_collections: 0,
             thread_protection_sets: vec![],
             thread_container_sets: vec![],
         }
     }
 
-    /// Protects the given aterm address and returns the term.
-    pub fn protect(&mut self, term: *const ffi::_aterm) -> ATermGlobal {
-        debug_assert!(!term.is_null(), "Can only protect valid terms");
-        let aterm = ATermPtr::new(term);
-        let root = self.protection_set.protect(aterm.clone());
-
-        let term = ATermRef::new(term);
-        trace!("Protected term {:?} global, index {}", term, root,);
-
-        ATermGlobal { term, root }
-    }
-
-    /// Removes the [ATermGlobal] from the protection set.
-    pub fn drop_term(&mut self, term: &ATermGlobal) {
-        term.require_valid();
-
-        trace!("Dropped term {:?} global, index {}", term.term, term.root,);
-        self.protection_set.unprotect(term.root);
-    }
-
     /// Register a new thread term pool to manage thread specific aspects.l
     pub(crate) fn register_thread_term_pool(&mut self) -> (SharedProtectionSet, SharedContainerProtectionSet, usize) {
         trace!("Registered ThreadTermPool {}", self.thread_protection_sets.len());
@@ -102,15 +254,35 @@ impl GlobalTermPool {
         )
     }
 
-    /// Drops the thread term pool with the given index.
-    pub(crate) fn drop_thread_term_pool(&mut self, index: usize) {
-        self.thread_protection_sets[index] = None;
-        self.thread_container_sets[index] = None;
-        trace!("Removed ThreadTermPool {}", index);
+    /// Drops the thread term pool with the given index. The protection sets are kept when they are still in use by
+    /// terms or containers that were sent to other threads.
+    pub(crate) fn drop_thread_term_pool(&mut self, index: usize, in_use: bool) {
+        if !in_use {
+            self.thread_protection_sets[index] = None;
+            self.thread_container_sets[index] = None;
+            trace!("Removed ThreadTermPool {}", index);
+        }
+    }
+
+    /// Returns the protection sets of the thread term pool with the given index.
+    pub(crate) fn thread_protection_sets(&self, index: usize) -> (SharedProtectionSet, SharedContainerProtectionSet) {
+        (
+            self.thread_protection_sets[index]
+                .clone()
+                .expect("The protection set of a term should be registered"),
+            self.thread_container_sets[index]
+                .clone()
+                .expect("The container protection set should be registered"),
+        )
     }
 
     /// Marks the terms in all protection sets.
     fn mark_protection_sets(&mut self, mut todo: Pin<&mut ffi::term_mark_stack>) {
+        // This is called for every registered thread pool, but only counted once per collection.
+        if COLLECTION_REQUESTED.replace(false) {
+            self.garbage_collections += 1;
+        }
+
         trace!("Marking terms:");
         for set in self.thread_protection_sets.iter().flatten() {
             // Do not lock since we acquired a global lock.
@@ -125,12 +297,17 @@ impl GlobalTermPool {
             }
         }
 
-        for (term, root) in &self.protection_set {
-            unsafe {
-                ffi::aterm_mark_address(term.ptr, todo.as_mut());
+        // Protecting and dropping global terms does not use the global lock, so access to the shards must be forbidden here.
+        for (shard, set) in GLOBAL_PROTECTION_SETS.iter().enumerate() {
+            set.access(false, |set| {
+                for (term, root) in set.iter() {
+                    unsafe {
+                        ffi::aterm_mark_address(term.ptr, todo.as_mut());
 
-                trace!("Marked global {:?}, index {root}", term.ptr);
-            }
+                        trace!("Marked global {:?}, shard {shard}, index {root}", term.ptr);
+                    }
+                }
+            });
         }
 
         for set in self.thread_container_sets.iter().flatten() {
@@ -158,6 +335,10 @@ impl GlobalTermPool {
             result += set.read().len();
         }
 
+        for set in GLOBAL_PROTECTION_SETS.iter() {
+            result += set.access(false, |set| set.len());
+        }
+
         // Gather the sizes of all containers
         for set in self.thread_container_sets.iter().flatten() {
             for (container, _index) in set.read().iter() {
@@ -167,6 +348,40 @@ impl GlobalTermPool {
         result
     }
 
+    /// Returns the metrics of the term pool and all protection sets.
+    pub(crate) fn metrics(&self) -> Metrics {
+        let mut metrics = Metrics {
+            terms: self.len(),
+            capacity: self.capacity(),
+            garbage_collections: self.garbage_collections,
+            ..Default::default()
+        };
+
+        for set in self.thread_protection_sets.iter().flatten() {
+            let protection_set = set.read();
+            metrics.protected_terms += protection_set.len();
+            metrics.protection_insertions += protection_set.number_of_insertions();
+            metrics.peak_protected_terms += protection_set.maximum_size();
+        }
+
+        for set in GLOBAL_PROTECTION_SETS.iter() {
+            metrics.global_terms += set.access(false, |set| set.len());
+        }
+
+        for set in self.thread_container_sets.iter().flatten() {
+            let protection_set = set.read();
+            metrics.containers += protection_set.len();
+            metrics.container_insertions += protection_set.number_of_insertions();
+            metrics.peak_containers += protection_set.maximum_size();
+
+            for (container, _) in protection_set.iter() {
+                metrics.terms_in_containers += container.len();
+            }
+        }
+
+        metrics
+    }
+
     /// Returns the number of terms in the pool.
     pub fn len(&self) -> usize {
         ffi::aterm_pool_size()
@@ -180,49 +395,13 @@ impl GlobalTermPool {
 
 impl Debug for GlobalTermPool {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut protected = 0;
-        let mut total = 0;
-        let mut max = 0;
-
-        for set in self.thread_protection_sets.iter().flatten() {
-            let protection_set = set.read();
-            protected += protection_set.len();
-            total += protection_set.number_of_insertions();
-            max += protection_set.maximum_size();
-        }
-
-        let mut num_containers = 0;
-        let mut max_containers = 0;
-        let mut total_containers = 0;
-        let mut inside_containers = 0;
-        for set in self.thread_container_sets.iter().flatten() {
-            let protection_set = set.read();
-            num_containers += protection_set.len();
-            total_containers += protection_set.number_of_insertions();
-            max_containers += protection_set.maximum_size();
-
-            for (container, _) in protection_set.iter() {
-                inside_containers += container.len();
-            }
-        }
-
-        write!(f,
-            "{} terms, max capacity {}, {} variables in thread root sets and {} in {} containers (term set {} insertions, max {}; container set {} insertions, max {})",
-            self.len(),
-            self.capacity(),
-            protected,
-            inside_containers,
-            num_containers,
-            total,
-            max,
-            total_containers,
-            max_containers,
-        )
+        write!(f, "{}", self.metrics())
     }
 }
 
 /// This is the global set of protection sets that are managed by the ThreadTermPool
-pub(crate) static GLOBAL_TERM_POOL: LazyLock<Mutex<GlobalTermPool>> = LazyLock::new(|| Mutex::new(GlobalTermPool::new()));
+pub(crate) static GLOBAL_TERM_POOL: LazyLock<Mutex<GlobalTermPool>> =
+    LazyLock::new(|| Mutex::new(GlobalTermPool::new()));
 
 /// Marks the terms in all protection sets using the global aterm pool.
 pub(crate) fn mark_protection_sets(todo: Pin<&mut ffi::term_mark_stack>) {
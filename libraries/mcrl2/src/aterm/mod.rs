@@ -11,9 +11,13 @@
 //! protected term. They can be upgraded to a protected term using "protect" and
 //! borrowed using "borrow".
 
+pub mod aterm_binary;
 pub mod aterm_builder;
 pub mod aterm_container;
+pub mod aterm_map;
 pub mod aterm_pool;
+pub mod aterm_scope;
+pub mod aterm_text;
 pub mod busy_forbidden;
 pub mod global_aterm_pool;
 pub mod symbol;
@@ -21,7 +25,10 @@ pub mod term;
 
 pub use aterm_builder::*;
 pub use aterm_container::*;
+pub use aterm_map::*;
 pub use aterm_pool::*;
+pub use aterm_scope::*;
+pub use aterm_text::*;
 pub use busy_forbidden::*;
 pub use symbol::*;
 pub use term::*;
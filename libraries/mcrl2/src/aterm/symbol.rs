@@ -15,6 +15,10 @@ pub struct SymbolRef<'a> {
     marker: PhantomData<&'a ()>,
 }
 
+// Function symbols are immutable and reference counted atomically by the global function symbol pool.
+unsafe impl Send for SymbolRef<'_> {}
+unsafe impl Sync for SymbolRef<'_> {}
+
 /// A Symbol references to an aterm function symbol, which has a name and an arity.
 impl<'a> SymbolRef<'a> {
     fn new(symbol: *const ffi::_function_symbol) -> SymbolRef<'a> {
@@ -9,12 +9,12 @@ use std::ops::Deref;
 
 use mcrl2_sys::atermpp::ffi;
 use mcrl2_sys::cxx::UniquePtr;
-use utilities::PhantomUnsend;
 
 use crate::aterm::SymbolRef;
 use crate::aterm::THREAD_TERM_POOL;
 
-use super::global_aterm_pool::GLOBAL_TERM_POOL;
+use super::global_aterm_pool::drop_global;
+use super::global_aterm_pool::protect_global;
 
 /// This represents a lifetime bound reference to an existing ATerm that is
 /// protected somewhere statically.
@@ -62,7 +62,7 @@ impl<'a> ATermRef<'a> {
         if self.is_default() {
             ATermGlobal::default()
         } else {
-            GLOBAL_TERM_POOL.lock().protect(self.term)
+            protect_global(self.term)
         }
     }
 
@@ -158,6 +158,12 @@ impl ATermRef<'_> {
         unsafe { ffi::aterm_is_int(self.term) }
     }
 
+    /// Returns the value of an aterm_int.
+    pub fn int_value(&self) -> usize {
+        debug_assert!(self.is_int(), "int_value() is only defined for a aterm_int");
+        unsafe { ffi::aterm_int_value(self.term) }
+    }
+
     /// Returns the head function symbol of the term.
     pub fn get_head_symbol(&self) -> SymbolRef<'_> {
         self.require_valid();
@@ -169,6 +175,50 @@ impl ATermRef<'_> {
         TermIterator::new(self.copy())
     }
 
+    /// Compares the terms by their structure, i.e., lexicographically by the
+    /// name and arity of their head symbols followed by their arguments, where
+    /// integers are compared by value.
+    ///
+    /// In contrast to [Ord], which compares the addresses of the terms, this
+    /// ordering does not depend on the order in which terms have been created.
+    /// It should therefore be used whenever terms are sorted for output.
+    pub fn structural_cmp(&self, other: &ATermRef<'_>) -> Ordering {
+        // The arguments are valid as long as the given terms are, so the lifetimes can be shortened.
+        let mut stack = vec![(ATermRef::<'_>::new(self.term), ATermRef::<'_>::new(other.term))];
+
+        while let Some((left, right)) = stack.pop() {
+            if left == right {
+                // Due to maximal sharing equal terms have the same address.
+                continue;
+            }
+
+            let ordering = match (left.is_int(), right.is_int()) {
+                (true, true) => left.int_value().cmp(&right.int_value()),
+                (true, false) => Ordering::Less,
+                (false, true) => Ordering::Greater,
+                (false, false) => {
+                    let left_symbol = left.get_head_symbol();
+                    let right_symbol = right.get_head_symbol();
+                    left_symbol
+                        .name()
+                        .cmp(right_symbol.name())
+                        .then(left_symbol.arity().cmp(&right_symbol.arity()))
+                }
+            };
+
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+
+            // The arguments are compared from left to right.
+            for (left_argument, right_argument) in left.arguments().zip(right.arguments()).rev() {
+                stack.push((ATermRef::new(left_argument.term), ATermRef::new(right_argument.term)));
+            }
+        }
+
+        Ordering::Equal
+    }
+
     /// Panics if the term is default
     pub fn require_valid(&self) {
         debug_assert!(
@@ -205,9 +255,9 @@ pub struct ATerm {
     pub(crate) term: ATermRef<'static>,
     pub(crate) root: usize,
 
-    // ATerm is not Send because it uses thread-local state for its protection
-    // mechanism.
-    _marker: PhantomUnsend,
+    /// The index of the thread term pool whose protection set contains the
+    /// root, such that the term can be dropped on any thread.
+    pub(crate) pool: usize,
 }
 
 impl ATerm {
@@ -219,14 +269,10 @@ impl ATerm {
         self.term.get()
     }
 
-    /// Creates a new term from the given reference and protection set root
-    /// entry.
-    pub(crate) fn new(term: ATermRef<'static>, root: usize) -> ATerm {
-        ATerm {
-            term,
-            root,
-            _marker: PhantomData,
-        }
+    /// Creates a new term from the given reference and the root entry in the
+    /// protection set of the thread term pool with the given index.
+    pub(crate) fn new(term: ATermRef<'static>, root: usize, pool: usize) -> ATerm {
+        ATerm { term, root, pool }
     }
 }
 
@@ -317,12 +363,15 @@ impl From<&ffi::aterm> for ATerm {
 pub struct ATermGlobal {
     pub(crate) term: ATermRef<'static>,
     pub(crate) root: usize,
+
+    /// The shard of the global protection set in which the term is protected.
+    pub(crate) shard: usize,
 }
 
 impl Drop for ATermGlobal {
     fn drop(&mut self) {
         if !self.is_default() {
-            GLOBAL_TERM_POOL.lock().drop_term(self);
+            drop_global(self);
         }
     }
 }
@@ -341,6 +390,12 @@ impl Deref for ATermGlobal {
     }
 }
 
+impl<'a> Borrow<ATermRef<'a>> for ATermGlobal {
+    fn borrow(&self) -> &ATermRef<'a> {
+        &self.term
+    }
+}
+
 impl Hash for ATermGlobal {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.term.hash(state)
@@ -481,6 +536,45 @@ impl<T: From<ATerm>> IntoIterator for &ATermList<T> {
     }
 }
 
+/// A term that represents a machine integer.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ATermInt {
+    term: ATerm,
+}
+
+impl ATermInt {
+    /// Returns the value of the integer.
+    pub fn value(&self) -> usize {
+        self.term.int_value()
+    }
+}
+
+impl From<ATermInt> for ATerm {
+    fn from(value: ATermInt) -> Self {
+        value.term
+    }
+}
+
+impl From<ATerm> for ATermInt {
+    fn from(value: ATerm) -> Self {
+        debug_assert!(value.is_int(), "Can only convert a aterm_int");
+        ATermInt { term: value }
+    }
+}
+
+impl<'a> From<ATermRef<'a>> for ATermInt {
+    fn from(value: ATermRef<'a>) -> Self {
+        debug_assert!(value.is_int(), "Can only convert a aterm_int");
+        ATermInt { term: value.protect() }
+    }
+}
+
+impl fmt::Display for ATermInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.value())
+    }
+}
+
 /// An iterator over the arguments of a term.
 #[derive(Default)]
 pub struct ATermArgs<'a> {
@@ -577,6 +671,7 @@ mod tests {
     use std::sync::Mutex;
     use std::thread;
 
+    use mcrl2_macros::match_term;
     use test_log::test;
 
     use crate::aterm::random_term;
@@ -626,6 +721,42 @@ mod tests {
         assert_eq!(values[3], tp.from_string("i").unwrap());
     }
 
+    #[test]
+    fn test_aterm_int() {
+        let mut tp = TermPool::new();
+        let value = tp.create_int(usize::MAX);
+
+        assert_eq!(value.value(), usize::MAX);
+        assert!(ATerm::from(value).is_int());
+        assert_eq!(ATermInt::from(tp.from_string("42").unwrap()).value(), 42);
+
+        // Integers are maximally shared as well.
+        let t = tp.from_string("f(3)").unwrap();
+        assert_eq!(ATerm::from(tp.create_int(3)), t.arg(0).protect());
+        assert_eq!(t.arg(0).int_value(), 3);
+    }
+
+    #[test]
+    fn test_structural_cmp() {
+        let mut tp = TermPool::new();
+
+        // The terms are created in reverse order, so their addresses are not sorted.
+        let mut terms: Vec<ATerm> = ["g(b)", "g(a)", "f(b, a)", "f(a, b)", "f(a)", "f(2)", "f(10)", "b", "a"]
+            .iter()
+            .map(|text| tp.from_string(text).unwrap())
+            .collect();
+        terms.sort_by(|left, right| left.structural_cmp(right));
+
+        let sorted: Vec<String> = terms.iter().map(|t| t.to_string()).collect();
+        assert_eq!(
+            sorted,
+            vec!["a", "b", "f(2)", "f(10)", "f(a)", "f(a,b)", "f(b,a)", "g(a)", "g(b)"]
+        );
+
+        let t = tp.from_string("f(g(a), b)").unwrap();
+        assert_eq!(t.structural_cmp(&t), Ordering::Equal);
+    }
+
     #[test]
     fn test_global_aterm_pool_parallel() {
         let seed: u64 = rand::rng().random();
@@ -662,4 +793,66 @@ mod tests {
             verify_term(&term);
         }
     }
+
+    #[test]
+    fn test_global_protection_shards() {
+        let terms: Mutex<Vec<ATermGlobal>> = Mutex::new(vec![]);
+
+        thread::scope(|s| {
+            for i in 0..8 {
+                let terms = &terms;
+                s.spawn(move || {
+                    let mut tp = TermPool::new();
+                    let t = tp.from_string(&format!("f(g({i}), a)")).unwrap();
+
+                    // Protect and drop many global terms concurrently, some of them are dropped by other threads.
+                    for _ in 0..1000 {
+                        let global = t.protect_global();
+                        let _clone = global.clone();
+                        terms.lock().unwrap().push(global);
+                    }
+
+                    tp.collect();
+                    terms.lock().unwrap().truncate(100);
+                });
+            }
+        });
+
+        let mut tp = TermPool::new();
+        tp.collect();
+
+        for term in &*terms.lock().unwrap() {
+            verify_term(term);
+        }
+    }
+
+    #[test]
+    fn test_match_term() {
+        fn simplify(tp: &mut TermPool, t: &ATerm) -> ATerm {
+            match_term!(t,
+                "and(true, x)" => x.protect(),
+                "and(x, x)" => x.protect(),
+                "not(not(x))" => x.protect(),
+                "succ(0)" => tp.create_int(1).into(),
+                _ => t.clone(),
+            )
+        }
+
+        let mut tp = TermPool::new();
+        let a = tp.from_string("a").unwrap();
+
+        for (input, expected) in [
+            ("and(true, a)", "a"),
+            ("and(a, a)", "a"),
+            ("not(not(a))", "a"),
+            ("succ(0)", "1"),
+            ("and(a, b)", "and(a, b)"),
+            ("not(a)", "not(a)"),
+        ] {
+            let t = tp.from_string(input).unwrap();
+            assert_eq!(simplify(&mut tp, &t), tp.from_string(expected).unwrap());
+        }
+
+        assert_eq!(simplify(&mut tp, &a), a);
+    }
 }
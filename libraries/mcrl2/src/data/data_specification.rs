@@ -12,6 +12,7 @@ use crate::aterm::ATermRef;
 use super::DataExpression;
 use super::DataFunctionSymbol;
 use super::DataVariable;
+use super::SortExpression;
 use super::SortExpressionRef;
 
 /// A safe abstraction for the mCRL2 data specification.
@@ -51,6 +52,14 @@ impl DataSpecification {
             .collect()
     }
 
+    /// Returns the sorts defined in the data specification.
+    pub fn sorts(&self) -> Vec<SortExpression> {
+        ffi::get_data_specification_sorts(&self.data_spec)
+            .iter()
+            .map(|x| ATerm::from(x).into())
+            .collect()
+    }
+
     /// Returns the constructors for the given sort expression.
     pub fn constructors(&self, sort: &SortExpressionRef<'_>) -> Vec<DataFunctionSymbol> {
         let t: ATermRef<'_> = sort.copy().into();
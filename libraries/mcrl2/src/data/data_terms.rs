@@ -61,6 +61,7 @@ mod inner {
     use std::borrow::Borrow;
     use std::ops::Deref;
 
+    use crate::aterm::ATermList;
     use crate::aterm::Markable;
     use crate::aterm::TermPool;
     use crate::aterm::Todo;
@@ -139,6 +140,8 @@ mod inner {
                 write!(f, "{}", DataVariableRef::from(self.term.copy()))
             } else if is_data_machine_number(&self.term) {
                 write!(f, "{}", MachineNumberRef::from(self.term.copy()))
+            } else if is_data_abstraction(&self.term) {
+                write!(f, "{}", DataAbstractionRef::from(self.term.copy()))
             } else {
                 write!(f, "{}", self.term)
             }
@@ -291,15 +294,78 @@ mod inner {
         }
     }
 
+    /// A binder applied to a number of variables and a body, for example
+    /// `forall x: Sort . e` or `exists x: Sort . e`.
+    #[mcrl2_term(is_data_abstraction)]
+    pub struct DataAbstraction {
+        term: ATerm,
+    }
+
+    impl DataAbstraction {
+        /// Returns true iff this is a universal quantifier.
+        pub fn is_forall(&self) -> bool {
+            self.term.arg(0).get_head_symbol().name() == "Forall"
+        }
+
+        /// Returns true iff this is an existential quantifier.
+        pub fn is_exists(&self) -> bool {
+            self.term.arg(0).get_head_symbol().name() == "Exists"
+        }
+
+        /// Returns the variables bound by the abstraction.
+        pub fn variables(&self) -> ATermList<DataVariable> {
+            self.term.arg(1).into()
+        }
+
+        /// Returns the body of the abstraction.
+        pub fn body(&self) -> DataExpressionRef<'_> {
+            self.term.arg(2).into()
+        }
+    }
+
+    impl fmt::Display for DataAbstraction {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let binder = if self.is_forall() {
+                "forall"
+            } else if self.is_exists() {
+                "exists"
+            } else {
+                "lambda"
+            };
+
+            write!(f, "{} ", binder)?;
+
+            let mut first = true;
+            for variable in self.variables() {
+                if !first {
+                    write!(f, ", ")?;
+                }
+
+                write!(f, "{}: {}", variable, variable.sort())?;
+                first = false;
+            }
+
+            write!(f, ". {}", self.body())
+        }
+    }
+
     #[mcrl2_term(is_data_machine_number)]
-    struct MachineNumber {
+    pub struct MachineNumber {
         pub term: ATerm,
     }
 
     impl MachineNumber {
+        /// Creates a new machine number with the given value.
+        #[mcrl2_ignore]
+        pub fn new(tp: &mut TermPool, value: u64) -> MachineNumber {
+            MachineNumber {
+                term: tp.create_with(|| mcrl2_sys::data::ffi::create_data_machine_number(value)),
+            }
+        }
+
         /// Obtain the underlying value of a machine number.
         pub fn value(&self) -> u64 {
-            0
+            unsafe { ffi::get_data_machine_number_value(self.term.get()) }
         }
     }
 
@@ -323,6 +389,20 @@ mod inner {
         }
     }
 
+    #[mcrl2_ignore]
+    impl From<DataAbstraction> for DataExpression {
+        fn from(value: DataAbstraction) -> Self {
+            value.term.into()
+        }
+    }
+
+    #[mcrl2_ignore]
+    impl From<MachineNumber> for DataExpression {
+        fn from(value: MachineNumber) -> Self {
+            value.term.into()
+        }
+    }
+
     #[mcrl2_ignore]
     impl From<DataVariable> for DataExpression {
         fn from(value: DataVariable) -> Self {
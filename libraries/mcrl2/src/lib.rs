@@ -7,3 +7,4 @@
 pub mod aterm;
 pub mod data;
 pub mod lps;
+pub mod pbes;
@@ -8,7 +8,11 @@ use std::fmt;
 use mcrl2_sys::cxx::UniquePtr;
 use mcrl2_sys::lps::ffi;
 
+use crate::aterm::ATerm;
+use crate::aterm::ATermList;
+use crate::data::DataExpression;
 use crate::data::DataSpecification;
+use crate::data::DataVariable;
 
 /// Rust representation of a lps::linear_process_specification.
 pub struct LinearProcessSpecification {
@@ -23,12 +27,136 @@ impl LinearProcessSpecification {
         })
     }
 
+    /// Writes the linear process specification to the given path.
+    pub fn write(&self, filename: &str) -> Result<(), Box<dyn Error>> {
+        Ok(ffi::write_linear_process_specification(&self.lps, filename)?)
+    }
+
+    /// Removes the process parameters that have the same value in every
+    /// reachable state, and substitutes that value for their occurrences
+    /// (constelm). When `instantiate_global_variables` is set the global
+    /// variables may be replaced by arbitrary values to find more constants.
+    pub fn remove_constant_parameters(&mut self, instantiate_global_variables: bool) {
+        ffi::remove_constant_parameters(self.lps.pin_mut(), instantiate_global_variables);
+    }
+
+    /// Removes the process parameters that influence neither the conditions
+    /// nor the actions, directly or through other parameters (parelm).
+    pub fn remove_unused_parameters(&mut self) {
+        ffi::remove_unused_parameters(self.lps.pin_mut());
+    }
+
+    /// Removes the sum variables `d` for which the condition contains an
+    /// equality `d == e`, by substituting `e` for `d` in the summand, and
+    /// the sum variables that do not occur in the summand (sumelm).
+    pub fn eliminate_sum_variables(&mut self) {
+        ffi::eliminate_sum_variables(self.lps.pin_mut());
+    }
+
+    /// Replaces every summand with sum variables of a finite sort by a
+    /// summand for every combination of their values (suminst). When
+    /// `tau_summands_only` is set only the summands without actions are
+    /// instantiated.
+    pub fn instantiate_sum_variables(&mut self, tau_summands_only: bool) {
+        ffi::instantiate_sum_variables(self.lps.pin_mut(), tau_summands_only);
+    }
+
     /// Returns the underlying data specification.
     pub fn data_specification(&self) -> DataSpecification {
         DataSpecification {
             data_spec: ffi::get_data_specification(&self.lps),
         }
     }
+
+    /// Returns the data specification as a term, as it is stored in .lts files.
+    pub fn data_specification_term(&self) -> ATerm {
+        ffi::get_data_specification_term(&self.lps).into()
+    }
+
+    /// Returns the list of action label declarations as a term.
+    pub fn action_labels(&self) -> ATerm {
+        ffi::get_action_labels(&self.lps).into()
+    }
+
+    /// Returns the process parameters of the linear process.
+    pub fn process_parameters(&self) -> Vec<DataVariable> {
+        let parameters: ATermList<DataVariable> = ATerm::from(ffi::get_process_parameters(&self.lps)).into();
+        parameters.iter().collect()
+    }
+
+    /// Returns the value of every process parameter in the initial state.
+    pub fn initial_state(&self) -> Vec<DataExpression> {
+        let values: ATermList<DataExpression> = ATerm::from(ffi::get_initial_state(&self.lps)).into();
+        values.iter().collect()
+    }
+
+    /// Returns the action declarations, the process equation and the initial
+    /// process in the mCRL2 syntax, but without the data specification that
+    /// is included by [fmt::Display].
+    pub fn pretty_print_process(&self) -> String {
+        ffi::print_linear_process(&self.lps)
+    }
+
+    /// Returns the action summands of the linear process.
+    pub fn action_summands(&self) -> Vec<ActionSummand> {
+        ffi::get_action_summands(&self.lps)
+            .iter()
+            .map(|summand| ActionSummand::from(ATerm::from(summand)))
+            .collect()
+    }
+}
+
+/// An action `a(d_0, ..., d_n)` of the multi-action of a summand.
+#[derive(Clone, Debug)]
+pub struct Action {
+    /// The name of the action label.
+    pub name: String,
+
+    /// The action label `ActId(a, [sorts])`, which is needed to construct the action term.
+    pub label: ATerm,
+
+    pub arguments: Vec<DataExpression>,
+}
+
+/// A summand `sum variables . condition -> actions @ time . P(next_state)` of
+/// a linear process, where the next state contains the new value of every
+/// process parameter.
+#[derive(Clone, Debug)]
+pub struct ActionSummand {
+    pub variables: Vec<DataVariable>,
+    pub condition: DataExpression,
+    pub actions: Vec<Action>,
+    /// The time of the multi-action, which is `@undefined_real` for untimed summands.
+    pub time: DataExpression,
+    pub next_state: Vec<DataExpression>,
+}
+
+impl From<ATerm> for ActionSummand {
+    fn from(value: ATerm) -> Self {
+        let fields: Vec<ATerm> = ATermList::<ATerm>::from(value).iter().collect();
+        let variables: ATermList<DataVariable> = fields[0].clone().into();
+        let actions: ATermList<ATerm> = fields[2].clone().into();
+        let next_state: ATermList<DataExpression> = fields[4].clone().into();
+
+        ActionSummand {
+            variables: variables.iter().collect(),
+            condition: fields[1].clone().into(),
+            actions: actions
+                .iter()
+                .map(|action| {
+                    // An action is the term Action(ActId(name, sorts), arguments).
+                    let arguments: ATermList<DataExpression> = action.arg(1).into();
+                    Action {
+                        name: action.arg(0).arg(0).get_head_symbol().name().to_string(),
+                        label: action.arg(0).protect(),
+                        arguments: arguments.iter().collect(),
+                    }
+                })
+                .collect(),
+            time: fields[3].clone().into(),
+            next_state: next_state.iter().collect(),
+        }
+    }
 }
 
 impl fmt::Display for LinearProcessSpecification {
@@ -47,6 +175,51 @@ mod tests {
 
         let _data_spec = lps.data_specification();
 
+        let parameters = lps.process_parameters();
+        assert_eq!(parameters.len(), lps.initial_state().len());
+        for summand in lps.action_summands() {
+            assert_eq!(summand.next_state.len(), parameters.len());
+        }
+
         println!("{}", lps);
+        assert!(lps.pretty_print_process().contains("init"));
+    }
+
+    #[test]
+    fn test_remove_parameters() {
+        let mut lps = LinearProcessSpecification::read("../../examples/lps/abp.lps").unwrap();
+        let num_of_parameters = lps.process_parameters().len();
+
+        lps.remove_unused_parameters();
+        lps.remove_constant_parameters(false);
+
+        // The summands must remain consistent with the remaining parameters.
+        let parameters = lps.process_parameters();
+        assert!(parameters.len() <= num_of_parameters);
+        assert_eq!(parameters.len(), lps.initial_state().len());
+        for summand in lps.action_summands() {
+            assert_eq!(summand.next_state.len(), parameters.len());
+        }
+    }
+
+    #[test]
+    fn test_sum_variables() {
+        let mut lps = LinearProcessSpecification::read("../../examples/lps/abp.lps").unwrap();
+        let num_of_summands = lps.action_summands().len();
+        let num_of_variables = |lps: &LinearProcessSpecification| -> usize {
+            lps.action_summands()
+                .iter()
+                .map(|summand| summand.variables.len())
+                .sum()
+        };
+        let before = num_of_variables(&lps);
+
+        lps.eliminate_sum_variables();
+        assert!(num_of_variables(&lps) <= before);
+
+        // Instantiating splits the summands, but never removes one.
+        lps.instantiate_sum_variables(false);
+        assert!(lps.action_summands().len() >= num_of_summands);
+        assert!(num_of_variables(&lps) <= before);
     }
 }
@@ -0,0 +1,115 @@
+//!
+//! Safe abstraction for the PBES library,
+//!
+
+use std::error::Error;
+use std::fmt;
+
+use mcrl2_sys::cxx::UniquePtr;
+use mcrl2_sys::pbes::ffi;
+use utilities::lock_global;
+
+use crate::aterm::ATerm;
+use crate::aterm::ATermList;
+use crate::aterm::ATermRef;
+use crate::data::DataSpecification;
+use crate::data::DataVariable;
+
+/// Rust representation of a pbes_system::pbes.
+pub struct PbesSpecification {
+    pbes: UniquePtr<ffi::pbes>,
+}
+
+impl PbesSpecification {
+    /// Reads the parameterised boolean equation system from the given path.
+    pub fn read(filename: &str) -> Result<PbesSpecification, Box<dyn Error>> {
+        Ok(PbesSpecification {
+            pbes: ffi::read_pbes(filename)?,
+        })
+    }
+
+    /// Parses and typechecks the given text in the textual PBES format.
+    pub fn parse(text: &str) -> Result<PbesSpecification, Box<dyn Error>> {
+        let _guard = lock_global();
+        Ok(PbesSpecification {
+            pbes: ffi::parse_pbes(text)?,
+        })
+    }
+
+    /// Creates a PBES from the given global variables, the list of equations
+    /// `PBEqn(FixPoint, PropVarDecl, PBExpr)` and the initial state `PropVarInst`.
+    pub fn new(
+        data_spec: &DataSpecification,
+        global_variables: &ATerm,
+        equations: &ATerm,
+        initial_state: &ATerm,
+    ) -> Result<PbesSpecification, Box<dyn Error>> {
+        let global_variables: ATermRef<'_> = global_variables.copy();
+        let equations: ATermRef<'_> = equations.copy();
+        let initial_state: ATermRef<'_> = initial_state.copy();
+
+        unsafe {
+            Ok(PbesSpecification {
+                pbes: ffi::create_pbes(
+                    &data_spec.data_spec,
+                    global_variables.get(),
+                    equations.get(),
+                    initial_state.get(),
+                )?,
+            })
+        }
+    }
+
+    /// Writes the parameterised boolean equation system to the given path.
+    pub fn write(&self, filename: &str) -> Result<(), Box<dyn Error>> {
+        Ok(ffi::write_pbes(&self.pbes, filename)?)
+    }
+
+    /// Returns the underlying data specification.
+    pub fn data_specification(&self) -> DataSpecification {
+        DataSpecification {
+            data_spec: ffi::get_pbes_data_specification(&self.pbes),
+        }
+    }
+
+    /// Returns the data specification as a term, as it is stored in .pbes files.
+    pub fn data_specification_term(&self) -> ATerm {
+        ffi::get_pbes_data_specification_term(&self.pbes).into()
+    }
+
+    /// Returns the global variables of the equation system.
+    pub fn global_variables(&self) -> Vec<DataVariable> {
+        let variables: ATermList<DataVariable> = ATerm::from(ffi::get_pbes_global_variables(&self.pbes)).into();
+        variables.iter().collect()
+    }
+
+    /// Returns the equations, where every equation is the term `PBEqn(FixPoint, PropVarDecl, PBExpr)`.
+    pub fn equations(&self) -> Vec<ATerm> {
+        ffi::get_pbes_equations(&self.pbes).iter().map(ATerm::from).collect()
+    }
+
+    /// Returns the initial state as the term `PropVarInst(name, arguments, index)`.
+    pub fn initial_state(&self) -> ATerm {
+        ffi::get_pbes_initial_state(&self.pbes).into()
+    }
+}
+
+impl fmt::Display for PbesSpecification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", ffi::print_pbes(&self.pbes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pbes() {
+        let pbes = PbesSpecification::parse("pbes nu X(b: Bool) = X(!b) && val(b); init X(true);").unwrap();
+
+        assert_eq!(pbes.equations().len(), 1);
+        assert!(pbes.global_variables().is_empty());
+        assert!(pbes.to_string().contains("init"));
+    }
+}
@@ -0,0 +1,477 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use log::debug;
+use log::info;
+use mcrl2::aterm::TermPool;
+use mcrl2::data::BoolSort;
+use mcrl2::data::DataExpression;
+use sabre::for_each_instance;
+use sabre::Enumerator;
+use sabre::RewriteEngine;
+use sabre::RewriteSpecification;
+use sabre::Strategy;
+use sabre::Substitution;
+
+use crate::FixpointSymbol;
+use crate::ParityGame;
+use crate::Pbes;
+use crate::PbesError;
+use crate::PbesExpression;
+use crate::Player;
+use crate::VertexIndex;
+
+/// The right-hand side of an equation of a boolean equation system, which is
+/// the result of instantiating the data parameters of a PBES equation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum BesExpression {
+    True,
+    False,
+
+    /// The equation with the given index for the given values of its parameters.
+    Variable(usize, Vec<DataExpression>),
+    And(Vec<BesExpression>),
+    Or(Vec<BesExpression>),
+}
+
+/// Instantiates the given PBES into a parity game, similar to pbessolve of
+/// mCRL2, where the vertices are the instances `X(v)` of the predicate
+/// variables that are reachable from the initial state. Player even wins the
+/// vertex of an instance iff it is true in the solution of the PBES.
+///
+/// The data expressions are evaluated by a rewriter with the given strategy,
+/// and the quantifiers are eliminated by enumerating the values of their
+/// variables, which must be of finite sorts, see [Enumerator]. Negations and
+/// the left-hand side of implications may not contain predicate variables.
+///
+/// The vertex of an instance is owned by even when its right-hand side is a
+/// disjunction, and by odd when it is a conjunction, and its priority is even
+/// for greatest and odd for least fixpoints, where earlier equations have
+/// higher priorities. Nested subformulas become vertices with priority zero.
+pub fn instantiate_pbes(pbes: &Pbes, tp: Rc<RefCell<TermPool>>, strategy: Strategy) -> Result<ParityGame, PbesError> {
+    let mut instantiator = Instantiator::new(pbes, tp, strategy)?;
+    instantiator.explore(usize::MAX)?;
+
+    info!(
+        "Instantiated the PBES into a parity game with {} vertices",
+        instantiator.num_of_vertices()
+    );
+    Ok(instantiator.into_game())
+}
+
+/// Returns the priority of every equation, which is even for greatest and odd
+/// for least fixpoints, and only increases at the start of an earlier block of
+/// equations with the same fixpoint symbol.
+fn equation_priorities(pbes: &Pbes) -> Vec<usize> {
+    let mut result = vec![0; pbes.equations.len()];
+    let mut priority = 0;
+    let mut previous: Option<FixpointSymbol> = None;
+
+    for (index, equation) in pbes.equations.iter().enumerate().rev() {
+        let parity = match equation.symbol {
+            FixpointSymbol::Nu => 0,
+            FixpointSymbol::Mu => 1,
+        };
+
+        if previous != Some(equation.symbol) {
+            if previous.is_some() || parity == 1 {
+                priority += 1;
+            }
+            previous = Some(equation.symbol);
+        }
+
+        debug_assert_eq!(priority % 2, parity);
+        result[index] = priority;
+    }
+
+    result
+}
+
+/// Instantiates the equations of a PBES on demand, such that a partially
+/// instantiated parity game can already be solved, see [crate::solve_pbes_local].
+pub(crate) struct Instantiator<'a> {
+    pbes: &'a Pbes,
+    rewriter: Box<dyn RewriteEngine>,
+    enumerator: Enumerator,
+
+    /// The index of the equation of every predicate variable.
+    equations: HashMap<String, usize>,
+
+    /// The priority of every equation.
+    priorities: Vec<usize>,
+
+    /// The vertex of every instance of a predicate variable.
+    vertices: HashMap<(usize, Vec<DataExpression>), VertexIndex>,
+
+    /// The instances of which the right-hand side has not been instantiated yet.
+    queue: VecDeque<(usize, Vec<DataExpression>, VertexIndex)>,
+
+    owners: Vec<Player>,
+    game_priorities: Vec<usize>,
+    successors: Vec<Vec<VertexIndex>>,
+    initial_vertex: VertexIndex,
+
+    true_term: DataExpression,
+    false_term: DataExpression,
+}
+
+impl<'a> Instantiator<'a> {
+    /// Creates an instantiator of which only the vertex of the initial state is known.
+    pub(crate) fn new(
+        pbes: &'a Pbes,
+        tp: Rc<RefCell<TermPool>>,
+        strategy: Strategy,
+    ) -> Result<Instantiator<'a>, PbesError> {
+        let spec = RewriteSpecification::from(pbes.data_specification.clone());
+        let enumerator = Enumerator::new(&mut tp.borrow_mut(), &spec);
+
+        let mut instantiator = Instantiator {
+            pbes,
+            rewriter: strategy.rewriter(tp, &spec),
+            enumerator,
+            equations: pbes
+                .equations
+                .iter()
+                .enumerate()
+                .map(|(index, equation)| (equation.variable.name.clone(), index))
+                .collect(),
+            priorities: equation_priorities(pbes),
+            vertices: HashMap::new(),
+            queue: VecDeque::new(),
+            owners: Vec::new(),
+            game_priorities: Vec::new(),
+            successors: Vec::new(),
+            initial_vertex: 0,
+            true_term: BoolSort::true_term(),
+            false_term: BoolSort::false_term(),
+        };
+
+        // The vertices 0 and 1 are won by even and odd respectively.
+        instantiator.add_vertex(Player::Even, 0, vec![0]);
+        instantiator.add_vertex(Player::Odd, 1, vec![1]);
+
+        let initial_state = instantiator.evaluate(
+            &PbesExpression::Variable(pbes.initial_state.clone()),
+            &Substitution::new(),
+        )?;
+        instantiator.initial_vertex = instantiator.vertex(&initial_state);
+
+        Ok(instantiator)
+    }
+
+    /// Instantiates the right-hand sides of at most the given number of
+    /// instances, and returns true iff all reachable instances have been
+    /// instantiated.
+    pub(crate) fn explore(&mut self, limit: usize) -> Result<bool, PbesError> {
+        let pbes = self.pbes;
+        for _ in 0..limit {
+            let Some((equation_index, values, vertex)) = self.queue.pop_front() else {
+                return Ok(true);
+            };
+
+            let equation = &pbes.equations[equation_index];
+            let substitution: Substitution = equation
+                .variable
+                .parameters
+                .iter()
+                .cloned()
+                .zip(values.iter().cloned())
+                .collect();
+
+            let expression = self.evaluate(&equation.formula, &substitution)?;
+            debug!("{}({:?}) = {:?}", equation.variable.name, values, expression);
+
+            let (owner, successors) = match &expression {
+                BesExpression::And(expressions) => (Player::Odd, expressions.iter().collect()),
+                BesExpression::Or(expressions) => (Player::Even, expressions.iter().collect()),
+                expression => (Player::Even, vec![expression]),
+            };
+
+            let successors = successors
+                .into_iter()
+                .map(|expression| self.vertex(expression))
+                .collect();
+            self.owners[vertex] = owner;
+            self.successors[vertex] = successors;
+        }
+
+        Ok(self.queue.is_empty())
+    }
+
+    pub(crate) fn num_of_vertices(&self) -> usize {
+        self.owners.len()
+    }
+
+    /// Returns the parity game of the vertices found so far, where the
+    /// instances that have not been instantiated yet are won by the given
+    /// player.
+    pub(crate) fn partial_game(&self, unexplored: Player) -> ParityGame {
+        let winning_vertex = match unexplored {
+            Player::Even => 0,
+            Player::Odd => 1,
+        };
+
+        ParityGame::new(
+            self.owners.clone(),
+            self.game_priorities.clone(),
+            self.successors
+                .iter()
+                .map(|successors| {
+                    if successors.is_empty() {
+                        vec![winning_vertex]
+                    } else {
+                        successors.clone()
+                    }
+                })
+                .collect(),
+            self.initial_vertex,
+        )
+    }
+
+    /// Returns the parity game after all reachable instances have been instantiated.
+    pub(crate) fn into_game(self) -> ParityGame {
+        debug_assert!(self.queue.is_empty(), "All instances must be instantiated");
+        ParityGame::new(self.owners, self.game_priorities, self.successors, self.initial_vertex)
+    }
+
+    fn add_vertex(&mut self, owner: Player, priority: usize, successors: Vec<VertexIndex>) -> VertexIndex {
+        self.owners.push(owner);
+        self.game_priorities.push(priority);
+        self.successors.push(successors);
+        self.owners.len() - 1
+    }
+
+    /// Returns the vertex for the given expression, where the vertices of the
+    /// instances of predicate variables are added to the queue when they are new.
+    fn vertex(&mut self, expression: &BesExpression) -> VertexIndex {
+        match expression {
+            BesExpression::True => 0,
+            BesExpression::False => 1,
+            BesExpression::Variable(equation_index, values) => {
+                let key = (*equation_index, values.clone());
+                if let Some(&vertex) = self.vertices.get(&key) {
+                    return vertex;
+                }
+
+                // The owner and successors are determined when the instance is taken from the queue.
+                let vertex = self.add_vertex(Player::Even, self.priorities[*equation_index], Vec::new());
+                self.vertices.insert(key, vertex);
+                self.queue.push_back((*equation_index, values.clone(), vertex));
+                vertex
+            }
+            BesExpression::And(expressions) | BesExpression::Or(expressions) => {
+                let owner = if matches!(expression, BesExpression::And(_)) {
+                    Player::Odd
+                } else {
+                    Player::Even
+                };
+
+                let successors = expressions.iter().map(|expression| self.vertex(expression)).collect();
+                self.add_vertex(owner, 0, successors)
+            }
+        }
+    }
+
+    /// Evaluates the expression where the data variables are replaced by their
+    /// values in the substitution.
+    fn evaluate(
+        &mut self,
+        expression: &PbesExpression,
+        substitution: &Substitution,
+    ) -> Result<BesExpression, PbesError> {
+        match expression {
+            PbesExpression::Data(expression) => {
+                let result = self.rewriter.rewrite_with_substitution(expression, substitution);
+                if result == self.true_term {
+                    Ok(BesExpression::True)
+                } else if result == self.false_term {
+                    Ok(BesExpression::False)
+                } else {
+                    Err(PbesError::UndecidedCondition(
+                        expression.to_string(),
+                        result.to_string(),
+                    ))
+                }
+            }
+            PbesExpression::Variable(variable) => {
+                let equation_index = *self
+                    .equations
+                    .get(&variable.name)
+                    .ok_or_else(|| PbesError::UndefinedVariable(variable.name.clone()))?;
+
+                let values = variable
+                    .arguments
+                    .iter()
+                    .map(|argument| self.rewriter.rewrite_with_substitution(argument, substitution))
+                    .collect();
+                Ok(BesExpression::Variable(equation_index, values))
+            }
+            PbesExpression::Not(inner) => negate(self.evaluate(inner, substitution)?, expression),
+            PbesExpression::And(left, right) => {
+                let left = self.evaluate(left, substitution)?;
+                if left == BesExpression::False {
+                    return Ok(left);
+                }
+
+                Ok(conjunction(vec![left, self.evaluate(right, substitution)?]))
+            }
+            PbesExpression::Or(left, right) => {
+                let left = self.evaluate(left, substitution)?;
+                if left == BesExpression::True {
+                    return Ok(left);
+                }
+
+                Ok(disjunction(vec![left, self.evaluate(right, substitution)?]))
+            }
+            PbesExpression::Implies(left, right) => {
+                let left = negate(self.evaluate(left, substitution)?, expression)?;
+                if left == BesExpression::True {
+                    return Ok(left);
+                }
+
+                Ok(disjunction(vec![left, self.evaluate(right, substitution)?]))
+            }
+            PbesExpression::Forall(variables, body) | PbesExpression::Exists(variables, body) => {
+                let is_forall = matches!(expression, PbesExpression::Forall(_, _));
+                let domains = variables
+                    .iter()
+                    .map(|variable| {
+                        let sort = variable.sort().protect();
+                        self.enumerator
+                            .values(&sort)
+                            .map(|values| values.to_vec())
+                            .ok_or_else(|| PbesError::InfiniteSort(variable.to_string(), sort.to_string()))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                let domains: Vec<&[DataExpression]> = domains.iter().map(|domain| &domain[..]).collect();
+
+                // The bound variables shadow the variables with the same name in the substitution.
+                let outer: Substitution = substitution
+                    .iter()
+                    .filter(|(variable, _)| !variables.contains(variable))
+                    .cloned()
+                    .collect();
+
+                let mut expressions = Vec::new();
+                let mut error = None;
+                for_each_instance(&domains, |instance| {
+                    let mut substitution = outer.clone();
+                    substitution.extend(variables.iter().cloned().zip(instance.iter().cloned()));
+
+                    match self.evaluate(body, &substitution) {
+                        Ok(expression) => {
+                            // The quantifier is decided as soon as an instance is false, or true respectively.
+                            let decided = if is_forall {
+                                expression == BesExpression::False
+                            } else {
+                                expression == BesExpression::True
+                            };
+
+                            expressions.push(expression);
+                            !decided
+                        }
+                        Err(result) => {
+                            error = Some(result);
+                            false
+                        }
+                    }
+                });
+
+                if let Some(error) = error {
+                    return Err(error);
+                }
+
+                Ok(if is_forall {
+                    conjunction(expressions)
+                } else {
+                    disjunction(expressions)
+                })
+            }
+        }
+    }
+}
+
+/// Returns the negation of the given expression, which may not contain predicate variables.
+fn negate(expression: BesExpression, original: &PbesExpression) -> Result<BesExpression, PbesError> {
+    match expression {
+        BesExpression::True => Ok(BesExpression::False),
+        BesExpression::False => Ok(BesExpression::True),
+        BesExpression::Variable(_, _) => Err(PbesError::NegatedVariable(original.to_string())),
+        BesExpression::And(expressions) => Ok(disjunction(
+            expressions
+                .into_iter()
+                .map(|expression| negate(expression, original))
+                .collect::<Result<Vec<_>, _>>()?,
+        )),
+        BesExpression::Or(expressions) => Ok(conjunction(
+            expressions
+                .into_iter()
+                .map(|expression| negate(expression, original))
+                .collect::<Result<Vec<_>, _>>()?,
+        )),
+    }
+}
+
+/// Returns the conjunction of the given expressions, where the nested
+/// conjunctions are flattened and the constants are simplified.
+fn conjunction(expressions: Vec<BesExpression>) -> BesExpression {
+    let mut result = Vec::new();
+    for expression in expressions {
+        match expression {
+            BesExpression::True => {}
+            BesExpression::False => return BesExpression::False,
+            BesExpression::And(expressions) => result.extend(expressions),
+            expression => result.push(expression),
+        }
+    }
+
+    match result.len() {
+        0 => BesExpression::True,
+        1 => result.pop().expect("The conjunction has a single element"),
+        _ => BesExpression::And(result),
+    }
+}
+
+/// Returns the disjunction of the given expressions, see [conjunction].
+fn disjunction(expressions: Vec<BesExpression>) -> BesExpression {
+    let mut result = Vec::new();
+    for expression in expressions {
+        match expression {
+            BesExpression::False => {}
+            BesExpression::True => return BesExpression::True,
+            BesExpression::Or(expressions) => result.extend(expressions),
+            expression => result.push(expression),
+        }
+    }
+
+    match result.len() {
+        0 => BesExpression::False,
+        1 => result.pop().expect("The disjunction has a single element"),
+        _ => BesExpression::Or(result),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use test_log::test;
+
+    #[test]
+    fn test_equation_priorities() {
+        let pbes = Pbes::parse(
+            "pbes
+                mu X = Y;
+                nu Y = Z;
+                nu Z = X;
+                mu W = W;
+
+                init X;",
+        )
+        .unwrap();
+
+        assert_eq!(equation_priorities(&pbes), vec![3, 2, 2, 1]);
+    }
+}
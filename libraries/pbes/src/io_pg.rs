@@ -0,0 +1,210 @@
+//! Reading and writing parity games in the textual format of PGSolver, which
+//! is also used by other parity game solvers such as Oink. The file starts
+//! with an optional header `parity <max_identifier>;` and an optional
+//! `start <identifier>;`, followed by a statement for every vertex:
+//!
+//!     `<identifier> <priority> <owner> <successor>,...,<successor> "<name>";`
+//!
+//! where the owner is 0 for player even and 1 for player odd, and the name is
+//! optional. The identifiers of the vertices must be 0 up to the number of
+//! vertices.
+
+use std::error::Error;
+use std::io::Read;
+use std::io::Write;
+
+use log::debug;
+use thiserror::Error;
+
+use crate::ParityGame;
+use crate::Player;
+use crate::VertexIndex;
+
+#[derive(Error, Debug)]
+pub enum PgError {
+    #[error("Invalid statement {0}")]
+    InvalidStatement(String),
+
+    #[error("Invalid owner {0}, expected 0 or 1")]
+    InvalidOwner(String),
+
+    #[error("Vertex {0} is defined more than once")]
+    DuplicateVertex(VertexIndex),
+
+    #[error("Vertex {0} is not defined")]
+    UndefinedVertex(VertexIndex),
+
+    #[error("Vertex {0} has no successors")]
+    NoSuccessors(VertexIndex),
+}
+
+/// Loads a parity game in the PGSolver format from the given reader. The
+/// initial vertex is the one given by `start`, or zero otherwise.
+pub fn read_pg(mut reader: impl Read) -> Result<ParityGame, Box<dyn Error>> {
+    let mut input = String::new();
+    reader.read_to_string(&mut input)?;
+
+    let mut initial_vertex = 0;
+    let mut vertices: Vec<Option<(usize, Player, Vec<VertexIndex>)>> = Vec::new();
+
+    for statement in statements(&input) {
+        let mut words = statement.split_whitespace();
+        let Some(first) = words.next() else {
+            continue;
+        };
+
+        match first {
+            "parity" => {
+                let max_identifier: usize = parse_word(words.next(), statement)?;
+                vertices.reserve(max_identifier + 1);
+            }
+            "start" => {
+                initial_vertex = parse_word(words.next(), statement)?;
+            }
+            _ => {
+                let vertex: VertexIndex = parse_word(Some(first), statement)?;
+                let priority: usize = parse_word(words.next(), statement)?;
+                let owner = match words.next() {
+                    Some("0") => Player::Even,
+                    Some("1") => Player::Odd,
+                    owner => return Err(PgError::InvalidOwner(owner.unwrap_or_default().to_string()).into()),
+                };
+
+                // The successors are separated by commas, but some tools also put spaces after them.
+                let mut successors = Vec::new();
+                for word in words.take_while(|word| !word.starts_with('"')) {
+                    for successor in word.split(',').filter(|successor| !successor.is_empty()) {
+                        successors.push(parse_word(Some(successor), statement)?);
+                    }
+                }
+
+                if successors.is_empty() {
+                    return Err(PgError::NoSuccessors(vertex).into());
+                }
+
+                if vertex >= vertices.len() {
+                    vertices.resize(vertex + 1, None);
+                }
+
+                if vertices[vertex].replace((priority, owner, successors)).is_some() {
+                    return Err(PgError::DuplicateVertex(vertex).into());
+                }
+            }
+        }
+    }
+
+    let mut owners = Vec::with_capacity(vertices.len());
+    let mut priorities = Vec::with_capacity(vertices.len());
+    let mut successors = Vec::with_capacity(vertices.len());
+    for (vertex, definition) in vertices.into_iter().enumerate() {
+        let (priority, owner, vertex_successors) = definition.ok_or(PgError::UndefinedVertex(vertex))?;
+
+        owners.push(owner);
+        priorities.push(priority);
+        successors.push(vertex_successors);
+    }
+
+    // The successors and the initial vertex must refer to defined vertices.
+    let num_of_vertices = owners.len();
+    if let Some(&undefined) = successors
+        .iter()
+        .flatten()
+        .chain(std::iter::once(&initial_vertex))
+        .find(|&&vertex| vertex >= num_of_vertices)
+    {
+        return Err(PgError::UndefinedVertex(undefined).into());
+    }
+
+    debug!("Read parity game with {num_of_vertices} vertices");
+    Ok(ParityGame::new(owners, priorities, successors, initial_vertex))
+}
+
+/// Writes the given parity game in the PGSolver format to the given writer.
+pub fn write_pg(writer: &mut impl Write, game: &ParityGame) -> Result<(), Box<dyn Error>> {
+    writeln!(writer, "parity {};", game.num_of_vertices().saturating_sub(1))?;
+    writeln!(writer, "start {};", game.initial_vertex())?;
+
+    for vertex in 0..game.num_of_vertices() {
+        let successors: Vec<String> = game
+            .successors(vertex)
+            .iter()
+            .map(|successor| successor.to_string())
+            .collect();
+
+        writeln!(
+            writer,
+            "{} {} {} {};",
+            vertex,
+            game.priority(vertex),
+            match game.owner(vertex) {
+                Player::Even => 0,
+                Player::Odd => 1,
+            },
+            successors.join(",")
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Returns the statements of the input, which are terminated by semicolons
+/// that do not occur in the quoted names of the vertices.
+fn statements(input: &str) -> impl Iterator<Item = &str> {
+    let mut quoted = false;
+    input
+        .split(move |c| {
+            if c == '"' {
+                quoted = !quoted;
+            }
+
+            c == ';' && !quoted
+        })
+        .map(str::trim)
+        .filter(|statement| !statement.is_empty())
+}
+
+/// Parses the given word of the statement as a number.
+fn parse_word<T: std::str::FromStr>(word: Option<&str>, statement: &str) -> Result<T, PgError> {
+    word.and_then(|word| word.parse().ok())
+        .ok_or_else(|| PgError::InvalidStatement(statement.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use test_log::test;
+
+    use crate::random_parity_game;
+
+    #[test]
+    fn test_reading_pg() {
+        let input = "parity 3;\nstart 3;\n0 0 0 1,2 \"X(true)\";\n1 2 1 1;\n2 1 1 2 \"a;b\";\n3 0 1 0, 2;\n";
+        let game = read_pg(input.as_bytes()).unwrap();
+
+        assert_eq!(
+            game,
+            ParityGame::new(
+                vec![Player::Even, Player::Odd, Player::Odd, Player::Odd],
+                vec![0, 2, 1, 0],
+                vec![vec![1, 2], vec![1], vec![2], vec![0, 2]],
+                3,
+            )
+        );
+
+        assert!(read_pg("0 0 0 1;".as_bytes()).is_err());
+        assert!(read_pg("0 0 2 0;".as_bytes()).is_err());
+        assert!(read_pg("0 0 0 0;\n0 1 1 0;".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_writing_pg() {
+        let game = random_parity_game(100, 8, 4);
+
+        // Check that it can be read after writing, and results in the same game.
+        let mut buffer: Vec<u8> = Vec::new();
+        write_pg(&mut buffer, &game).unwrap();
+
+        assert_eq!(read_pg(&buffer[..]).unwrap(), game);
+    }
+}
@@ -0,0 +1,37 @@
+//!
+//! A crate containing the types for parameterised boolean equation systems
+//! (PBESs), which are the foundation for the verification of modal formulas,
+//! together with reading, parsing and the conversion from and to the ATerm
+//! encoding that is used by the mCRL2 toolset, the translation of a linear
+//! process and a modal formula into a PBES, simplifying a PBES, and solving a
+//! PBES by instantiating it into a parity game that is solved by one of
+//! several parity game solvers. Parity games can be exchanged with other
+//! solvers in the PGSolver format.
+//!
+//! This crate does not use unsafe code.
+
+#![forbid(unsafe_code)]
+
+mod instantiate;
+mod io_pg;
+mod lps2pbes;
+mod parity_game;
+mod pbes;
+mod pbes_expression;
+mod priority_promotion;
+mod simplify;
+mod small_progress_measures;
+mod solve;
+mod zielonka;
+
+pub use instantiate::*;
+pub use io_pg::*;
+pub use lps2pbes::*;
+pub use parity_game::*;
+pub use pbes::*;
+pub use pbes_expression::*;
+pub use priority_promotion::*;
+pub use simplify::*;
+pub use small_progress_measures::*;
+pub use solve::*;
+pub use zielonka::*;
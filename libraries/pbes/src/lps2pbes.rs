@@ -0,0 +1,457 @@
+use std::collections::HashSet;
+
+use log::info;
+use lts::ActionFormula;
+use lts::MultiAction;
+use lts::RegularFormula;
+use lts::StateFormula;
+use mcrl2::aterm::ATerm;
+use mcrl2::aterm::TermPool;
+use mcrl2::data::BoolSort;
+use mcrl2::data::DataExpression;
+use mcrl2::data::DataVariable;
+use mcrl2::lps::ActionSummand;
+use mcrl2::lps::LinearProcessSpecification;
+use sabre::utilities::instantiate;
+
+use crate::FixpointSymbol;
+use crate::Pbes;
+use crate::PbesEquation;
+use crate::PbesError;
+use crate::PbesExpression;
+use crate::PropositionalVariable;
+use crate::PropositionalVariableInstantiation;
+
+/// Translates the given linear process and closed state formula into a PBES
+/// of which the solution for the initial state is true iff the formula holds
+/// in the initial state of the process, following the standard translation
+/// of lps2pbes in mCRL2.
+///
+/// Every fixpoint of the formula becomes an equation with the process
+/// parameters as parameters, in the order in which the fixpoints are nested.
+/// A modality `<a>f` becomes the disjunction over the summands `c -> a . P(g)`
+/// of `exists e . c && f(g)`, and `[a]f` the conjunction of `forall e . c => f(g)`.
+/// The regular operators are replaced by nested modalities and fresh
+/// fixpoints, and when the formula is not a fixpoint it is wrapped in a
+/// greatest fixpoint.
+///
+/// The formulas are without data, so an action formula `a` matches the
+/// summands of which the multi-action consists of actions with those names,
+/// regardless of their arguments. The global variables of the process are
+/// not translated.
+pub fn lps2pbes(
+    tp: &mut TermPool,
+    lps: &LinearProcessSpecification,
+    formula: &StateFormula,
+) -> Result<Pbes, PbesError> {
+    let parameters = lps.process_parameters();
+    let summands = lps
+        .action_summands()
+        .into_iter()
+        .map(|summand| {
+            let multi_action = MultiAction::new(
+                summand
+                    .actions
+                    .iter()
+                    .map(|action| lts::Action::new(action.name.clone(), Vec::new()))
+                    .collect(),
+            );
+            (multi_action, summand)
+        })
+        .collect();
+
+    let mut translator = Translator {
+        tp,
+        arguments: parameters.iter().cloned().map(DataExpression::from).collect(),
+        used_names: parameters
+            .iter()
+            .map(|parameter| parameter.name().to_string())
+            .collect(),
+        parameters,
+        summands,
+        equations: Vec::new(),
+        environment: Vec::new(),
+    };
+
+    let formula = positive_normal_form(formula, false);
+    let initial_state = lps.initial_state();
+    let initial_state = match formula {
+        StateFormula::Mu(_, _) | StateFormula::Nu(_, _) => translator.translate(&formula, &initial_state)?,
+        _ => translator.fixpoint(FixpointSymbol::Nu, "", "X", &formula, &initial_state)?,
+    };
+
+    let PbesExpression::Variable(initial_state) = initial_state else {
+        unreachable!("The translation of a fixpoint is a propositional variable instantiation");
+    };
+
+    let equations: Vec<PbesEquation> = translator
+        .equations
+        .into_iter()
+        .map(|equation| equation.expect("Every equation is defined after its body has been translated"))
+        .collect();
+    info!("Translated the formula into {} equations", equations.len());
+
+    Ok(Pbes {
+        data_specification: lps.data_specification(),
+        global_variables: Vec::new(),
+        equations,
+        initial_state,
+    })
+}
+
+/// Returns the formula in which the negations and implications have been
+/// removed, by pushing the negations inwards. A fixpoint variable occurs
+/// under an even number of negations, so it is unchanged when the negation
+/// of its fixpoint is replaced by the dual fixpoint.
+fn positive_normal_form(formula: &StateFormula, negated: bool) -> StateFormula {
+    let positive = |formula: &StateFormula, negated: bool| Box::new(positive_normal_form(formula, negated));
+
+    match formula {
+        StateFormula::True if negated => StateFormula::False,
+        StateFormula::True => StateFormula::True,
+        StateFormula::False if negated => StateFormula::True,
+        StateFormula::False => StateFormula::False,
+        StateFormula::Not(formula) => positive_normal_form(formula, !negated),
+        StateFormula::And(left, right) if negated => StateFormula::Or(positive(left, true), positive(right, true)),
+        StateFormula::And(left, right) => StateFormula::And(positive(left, false), positive(right, false)),
+        StateFormula::Or(left, right) if negated => StateFormula::And(positive(left, true), positive(right, true)),
+        StateFormula::Or(left, right) => StateFormula::Or(positive(left, false), positive(right, false)),
+        StateFormula::Implies(left, right) if negated => {
+            StateFormula::And(positive(left, false), positive(right, true))
+        }
+        StateFormula::Implies(left, right) => StateFormula::Or(positive(left, true), positive(right, false)),
+        StateFormula::Diamond(regular, formula) if negated => {
+            StateFormula::Box(regular.clone(), positive(formula, true))
+        }
+        StateFormula::Diamond(regular, formula) => StateFormula::Diamond(regular.clone(), positive(formula, false)),
+        StateFormula::Box(regular, formula) if negated => {
+            StateFormula::Diamond(regular.clone(), positive(formula, true))
+        }
+        StateFormula::Box(regular, formula) => StateFormula::Box(regular.clone(), positive(formula, false)),
+        StateFormula::Mu(variable, formula) if negated => StateFormula::Nu(variable.clone(), positive(formula, true)),
+        StateFormula::Mu(variable, formula) => StateFormula::Mu(variable.clone(), positive(formula, false)),
+        StateFormula::Nu(variable, formula) if negated => StateFormula::Mu(variable.clone(), positive(formula, true)),
+        StateFormula::Nu(variable, formula) => StateFormula::Nu(variable.clone(), positive(formula, false)),
+        StateFormula::Variable(variable) => StateFormula::Variable(variable.clone()),
+    }
+}
+
+/// Returns true iff the given action formula holds for the names of the actions of the multi-action.
+fn matches(formula: &ActionFormula, multi_action: &MultiAction) -> Result<bool, PbesError> {
+    Ok(match formula {
+        ActionFormula::True => true,
+        ActionFormula::False => false,
+        ActionFormula::Action(action) => {
+            let action_formula = MultiAction::parse(action);
+            if action_formula
+                .actions()
+                .iter()
+                .any(|action| !action.arguments().is_empty())
+            {
+                return Err(PbesError::UnsupportedActionFormula(action.clone()));
+            }
+
+            action_formula == *multi_action
+        }
+        ActionFormula::Not(formula) => !matches(formula, multi_action)?,
+        ActionFormula::And(left, right) => matches(left, multi_action)? && matches(right, multi_action)?,
+        ActionFormula::Or(left, right) => matches(left, multi_action)? || matches(right, multi_action)?,
+        ActionFormula::Implies(left, right) => !matches(left, multi_action)? || matches(right, multi_action)?,
+    })
+}
+
+/// Translates formulas in positive normal form into the right-hand sides of equations.
+struct Translator<'a> {
+    tp: &'a mut TermPool,
+    parameters: Vec<DataVariable>,
+
+    /// The process parameters as data expressions.
+    arguments: Vec<DataExpression>,
+    summands: Vec<(MultiAction, ActionSummand)>,
+
+    /// The equations in the order of their fixpoints, which are None while their body is translated.
+    equations: Vec<Option<PbesEquation>>,
+
+    /// Maps the bound fixpoint variables of the formula to the names of their equations.
+    environment: Vec<(String, String)>,
+
+    /// The names of the predicate variables, process parameters and quantified variables.
+    used_names: HashSet<String>,
+}
+
+impl Translator<'_> {
+    /// Translates the formula where the process parameters have the given values.
+    fn translate(&mut self, formula: &StateFormula, arguments: &[DataExpression]) -> Result<PbesExpression, PbesError> {
+        Ok(match formula {
+            StateFormula::True => PbesExpression::Data(BoolSort::true_term()),
+            StateFormula::False => PbesExpression::Data(BoolSort::false_term()),
+            StateFormula::Not(_) | StateFormula::Implies(_, _) => {
+                unreachable!("The formula {formula} is not in positive normal form")
+            }
+            StateFormula::And(left, right) => PbesExpression::And(
+                Box::new(self.translate(left, arguments)?),
+                Box::new(self.translate(right, arguments)?),
+            ),
+            StateFormula::Or(left, right) => PbesExpression::Or(
+                Box::new(self.translate(left, arguments)?),
+                Box::new(self.translate(right, arguments)?),
+            ),
+            StateFormula::Diamond(regular, formula) => self.modality(regular, formula, false, arguments)?,
+            StateFormula::Box(regular, formula) => self.modality(regular, formula, true, arguments)?,
+            StateFormula::Mu(variable, formula) => {
+                self.fixpoint(FixpointSymbol::Mu, variable, variable, formula, arguments)?
+            }
+            StateFormula::Nu(variable, formula) => {
+                self.fixpoint(FixpointSymbol::Nu, variable, variable, formula, arguments)?
+            }
+            StateFormula::Variable(variable) => {
+                let (_, name) = self
+                    .environment
+                    .iter()
+                    .rev()
+                    .find(|(bound, _)| bound == variable)
+                    .unwrap_or_else(|| panic!("Variable {variable} is not bound by a fixpoint"));
+
+                PbesExpression::Variable(PropositionalVariableInstantiation {
+                    name: name.clone(),
+                    arguments: arguments.to_vec(),
+                })
+            }
+        })
+    }
+
+    /// Adds the equation for the fixpoint of the given variable, and returns
+    /// its instantiation with the given arguments. The name of the equation
+    /// is derived from the given name, such that it is unique.
+    fn fixpoint(
+        &mut self,
+        symbol: FixpointSymbol,
+        variable: &str,
+        name: &str,
+        formula: &StateFormula,
+        arguments: &[DataExpression],
+    ) -> Result<PbesExpression, PbesError> {
+        let name = self.fresh_name(name);
+        let index = self.equations.len();
+        self.equations.push(None);
+
+        self.environment.push((variable.to_string(), name.clone()));
+        let parameters = self.arguments.clone();
+        let formula = self.translate(formula, &parameters)?;
+        self.environment.pop();
+
+        self.equations[index] = Some(PbesEquation {
+            symbol,
+            variable: PropositionalVariable {
+                name: name.clone(),
+                parameters: self.parameters.clone(),
+            },
+            formula,
+        });
+
+        Ok(PbesExpression::Variable(PropositionalVariableInstantiation {
+            name,
+            arguments: arguments.to_vec(),
+        }))
+    }
+
+    /// Translates the modality `<regular>formula`, or `[regular]formula` when is_box is true.
+    fn modality(
+        &mut self,
+        regular: &RegularFormula,
+        formula: &StateFormula,
+        is_box: bool,
+        arguments: &[DataExpression],
+    ) -> Result<PbesExpression, PbesError> {
+        let modality = |regular: &RegularFormula, formula: StateFormula| {
+            if is_box {
+                StateFormula::Box(regular.clone(), Box::new(formula))
+            } else {
+                StateFormula::Diamond(regular.clone(), Box::new(formula))
+            }
+        };
+
+        match regular {
+            RegularFormula::Action(action) => self.step(action, formula, is_box, arguments),
+            RegularFormula::Sequence(left, right) => {
+                self.modality(left, &modality(right, formula.clone()), is_box, arguments)
+            }
+            RegularFormula::Choice(left, right) => {
+                let left = Box::new(self.modality(left, formula, is_box, arguments)?);
+                let right = Box::new(self.modality(right, formula, is_box, arguments)?);
+                Ok(if is_box {
+                    PbesExpression::And(left, right)
+                } else {
+                    PbesExpression::Or(left, right)
+                })
+            }
+            RegularFormula::Star(regular) => {
+                // The variable cannot occur in the formula, since it is not an identifier.
+                let variable = format!("#{}", self.equations.len());
+                let repeat = Box::new(modality(regular, StateFormula::Variable(variable.clone())));
+                if is_box {
+                    let body = StateFormula::And(Box::new(formula.clone()), repeat);
+                    self.fixpoint(FixpointSymbol::Nu, &variable, "X", &body, arguments)
+                } else {
+                    let body = StateFormula::Or(Box::new(formula.clone()), repeat);
+                    self.fixpoint(FixpointSymbol::Mu, &variable, "X", &body, arguments)
+                }
+            }
+            RegularFormula::Plus(inner) => self.modality(
+                inner,
+                &modality(&RegularFormula::Star(inner.clone()), formula.clone()),
+                is_box,
+                arguments,
+            ),
+        }
+    }
+
+    /// Translates the modality with a single step of the summands that match the action formula.
+    fn step(
+        &mut self,
+        action: &ActionFormula,
+        formula: &StateFormula,
+        is_box: bool,
+        arguments: &[DataExpression],
+    ) -> Result<PbesExpression, PbesError> {
+        let mut result: Option<PbesExpression> = None;
+
+        for summand_index in 0..self.summands.len() {
+            if !matches(action, &self.summands[summand_index].0)? {
+                continue;
+            }
+            let summand = self.summands[summand_index].1.clone();
+
+            // The sum variables are renamed, since the arguments may contain sum variables of the enclosing modalities.
+            let variables: Vec<DataVariable> = summand
+                .variables
+                .iter()
+                .map(|variable| {
+                    let name = self.fresh_name(variable.name());
+                    DataVariable::with_sort(self.tp, &name, &variable.sort())
+                })
+                .collect();
+
+            let substitution: Vec<(ATerm, ATerm)> = self
+                .parameters
+                .iter()
+                .cloned()
+                .map(Into::into)
+                .zip(arguments.iter().cloned().map(Into::into))
+                .chain(
+                    summand
+                        .variables
+                        .iter()
+                        .cloned()
+                        .map(Into::into)
+                        .zip(variables.iter().cloned().map(Into::into)),
+                )
+                .collect();
+
+            let condition = PbesExpression::Data(instantiate(self.tp, &summand.condition, &substitution));
+            let next_state: Vec<DataExpression> = summand
+                .next_state
+                .iter()
+                .map(|expression| instantiate(self.tp, expression, &substitution))
+                .collect();
+            let formula = Box::new(self.translate(formula, &next_state)?);
+
+            let expression = if is_box {
+                PbesExpression::Implies(Box::new(condition), formula)
+            } else {
+                PbesExpression::And(Box::new(condition), formula)
+            };
+
+            let expression = match (variables.is_empty(), is_box) {
+                (true, _) => expression,
+                (false, true) => PbesExpression::Forall(variables, Box::new(expression)),
+                (false, false) => PbesExpression::Exists(variables, Box::new(expression)),
+            };
+
+            result = Some(match result {
+                Some(result) if is_box => PbesExpression::And(Box::new(result), Box::new(expression)),
+                Some(result) => PbesExpression::Or(Box::new(result), Box::new(expression)),
+                None => expression,
+            });
+        }
+
+        // The empty disjunction is false, and the empty conjunction is true.
+        Ok(result.unwrap_or_else(|| {
+            PbesExpression::Data(if is_box {
+                BoolSort::true_term()
+            } else {
+                BoolSort::false_term()
+            })
+        }))
+    }
+
+    /// Returns the given name, or the name followed by the smallest number for which it is not used yet.
+    fn fresh_name(&mut self, name: &str) -> String {
+        let mut result = name.to_string();
+        let mut index = 0;
+        while self.used_names.contains(&result) {
+            index += 1;
+            result = format!("{name}{index}");
+        }
+
+        self.used_names.insert(result.clone());
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use test_log::test;
+
+    fn translate(formula: &str) -> Result<Pbes, PbesError> {
+        let lps = LinearProcessSpecification::read("../../examples/lps/abp.lps").unwrap();
+        let mut tp = TermPool::new();
+        lps2pbes(&mut tp, &lps, &StateFormula::parse(formula).unwrap())
+    }
+
+    #[test]
+    fn test_lps2pbes_deadlock_freedom() {
+        let lps = LinearProcessSpecification::read("../../examples/lps/abp.lps").unwrap();
+        let mut tp = TermPool::new();
+        let pbes = lps2pbes(
+            &mut tp,
+            &lps,
+            &StateFormula::parse("nu X. [true]X && <true>true").unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(pbes.equations.len(), 1);
+        assert_eq!(pbes.equations[0].symbol, FixpointSymbol::Nu);
+        assert_eq!(pbes.equations[0].variable.name, "X");
+        assert_eq!(pbes.equations[0].variable.parameters, lps.process_parameters());
+        assert_eq!(pbes.initial_state.arguments, lps.initial_state());
+
+        // The result must be accepted by mCRL2.
+        pbes.to_specification(&mut tp).unwrap();
+    }
+
+    #[test]
+    fn test_lps2pbes_regular_formulas() {
+        // The formula is wrapped in a fixpoint, and the star introduces another one.
+        let pbes = translate("[true*]<true>true").unwrap();
+        assert_eq!(pbes.equations.len(), 2);
+        assert_eq!(pbes.equations[1].symbol, FixpointSymbol::Nu);
+
+        // The negation of the least fixpoint is a greatest fixpoint.
+        let pbes = translate("!mu X. <true>X").unwrap();
+        assert_eq!(pbes.equations.len(), 1);
+        assert_eq!(pbes.equations[0].symbol, FixpointSymbol::Nu);
+
+        // The names of the equations are unique.
+        let pbes = translate("mu X. <true>X || nu X. [true]X").unwrap();
+        assert_eq!(pbes.equations.len(), 2);
+        assert_ne!(pbes.equations[0].variable.name, pbes.equations[1].variable.name);
+
+        assert!(matches!(
+            translate("<r1(d1)>true"),
+            Err(PbesError::UnsupportedActionFormula(_))
+        ));
+    }
+}
@@ -0,0 +1,184 @@
+use std::fmt;
+
+use rand::Rng;
+
+/// The index of a vertex in a [ParityGame].
+pub type VertexIndex = usize;
+
+/// The players of a parity game, where player even wins the vertices of the
+/// variables that are true in the solution of the corresponding PBES.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Player {
+    Even,
+    Odd,
+}
+
+impl Player {
+    /// Returns the player that wins the plays in which the given priority is the highest that occurs infinitely often.
+    pub fn from_priority(priority: usize) -> Player {
+        if priority % 2 == 0 {
+            Player::Even
+        } else {
+            Player::Odd
+        }
+    }
+
+    pub fn opponent(&self) -> Player {
+        match self {
+            Player::Even => Player::Odd,
+            Player::Odd => Player::Even,
+        }
+    }
+}
+
+impl fmt::Display for Player {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Player::Even => write!(f, "even"),
+            Player::Odd => write!(f, "odd"),
+        }
+    }
+}
+
+/// A parity game in which the owner of a vertex chooses the next vertex of the
+/// play, and a player wins an infinite play when the highest priority that
+/// occurs infinitely often has its parity (max-parity convention).
+///
+/// Every vertex must have at least one successor, such that all plays are infinite.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ParityGame {
+    owners: Vec<Player>,
+    priorities: Vec<usize>,
+    successors: Vec<Vec<VertexIndex>>,
+    initial_vertex: VertexIndex,
+}
+
+impl ParityGame {
+    /// Creates a parity game from the owner, priority and successors of every vertex.
+    pub fn new(
+        owners: Vec<Player>,
+        priorities: Vec<usize>,
+        successors: Vec<Vec<VertexIndex>>,
+        initial_vertex: VertexIndex,
+    ) -> ParityGame {
+        debug_assert_eq!(owners.len(), priorities.len());
+        debug_assert_eq!(owners.len(), successors.len());
+        debug_assert!(
+            successors.iter().all(|successors| !successors.is_empty()),
+            "Every vertex must have a successor"
+        );
+
+        ParityGame {
+            owners,
+            priorities,
+            successors,
+            initial_vertex,
+        }
+    }
+
+    pub fn num_of_vertices(&self) -> usize {
+        self.owners.len()
+    }
+
+    pub fn num_of_edges(&self) -> usize {
+        self.successors.iter().map(|successors| successors.len()).sum()
+    }
+
+    pub fn initial_vertex(&self) -> VertexIndex {
+        self.initial_vertex
+    }
+
+    pub fn owner(&self, vertex: VertexIndex) -> Player {
+        self.owners[vertex]
+    }
+
+    pub fn priority(&self, vertex: VertexIndex) -> usize {
+        self.priorities[vertex]
+    }
+
+    pub fn successors(&self, vertex: VertexIndex) -> &[VertexIndex] {
+        &self.successors[vertex]
+    }
+
+    /// Returns the predecessors of every vertex.
+    pub fn predecessors(&self) -> Vec<Vec<VertexIndex>> {
+        let mut result = vec![Vec::new(); self.num_of_vertices()];
+        for (vertex, successors) in self.successors.iter().enumerate() {
+            for &successor in successors {
+                result[successor].push(vertex);
+            }
+        }
+
+        result
+    }
+
+    /// Returns the vertices of the subgame induced by the given vertices from
+    /// which the player can force the play into the given target vertices,
+    /// where the predecessors are computed by [Self::predecessors].
+    pub fn attractor(
+        &self,
+        predecessors: &[Vec<VertexIndex>],
+        vertices: &[bool],
+        mut target: Vec<bool>,
+        player: Player,
+    ) -> Vec<bool> {
+        // The number of successors of every vertex of the opponent that are not attracted yet.
+        let mut remaining: Vec<usize> = (0..self.num_of_vertices())
+            .map(|vertex| {
+                self.successors(vertex)
+                    .iter()
+                    .filter(|&&successor| vertices[successor])
+                    .count()
+            })
+            .collect();
+
+        let mut queue: Vec<VertexIndex> = (0..target.len()).filter(|&vertex| target[vertex]).collect();
+        while let Some(vertex) = queue.pop() {
+            for &predecessor in &predecessors[vertex] {
+                if !vertices[predecessor] || target[predecessor] {
+                    continue;
+                }
+
+                remaining[predecessor] -= 1;
+                if self.owner(predecessor) == player || remaining[predecessor] == 0 {
+                    target[predecessor] = true;
+                    queue.push(predecessor);
+                }
+            }
+        }
+
+        target
+    }
+}
+
+/// Generates a random parity game with the given number of vertices and
+/// priorities, where every vertex has at least one and at most the given
+/// number of successors.
+pub fn random_parity_game(num_of_vertices: usize, num_of_priorities: usize, outdegree: usize) -> ParityGame {
+    let mut rng = rand::rng();
+
+    let owners = (0..num_of_vertices)
+        .map(|_| {
+            if rng.random_bool(0.5) {
+                Player::Even
+            } else {
+                Player::Odd
+            }
+        })
+        .collect();
+    let priorities = (0..num_of_vertices)
+        .map(|_| rng.random_range(0..num_of_priorities))
+        .collect();
+    let successors = (0..num_of_vertices)
+        .map(|_| {
+            let mut successors: Vec<VertexIndex> = (0..rng.random_range(1..=outdegree))
+                .map(|_| rng.random_range(0..num_of_vertices))
+                .collect();
+            successors.sort_unstable();
+            successors.dedup();
+            successors
+        })
+        .collect();
+
+    ParityGame::new(owners, priorities, successors, 0)
+}
@@ -0,0 +1,191 @@
+use std::error::Error;
+use std::fmt;
+
+use mcrl2::aterm::ATerm;
+use mcrl2::aterm::ATermRef;
+use mcrl2::aterm::TermPool;
+use mcrl2::data::DataSpecification;
+use mcrl2::data::DataVariable;
+use mcrl2::pbes::PbesSpecification;
+
+use crate::FixpointSymbol;
+use crate::PbesError;
+use crate::PbesExpression;
+use crate::PropositionalVariable;
+use crate::PropositionalVariableInstantiation;
+
+/// An equation `sigma X(d: D) = phi` of a parameterised boolean equation system.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct PbesEquation {
+    pub symbol: FixpointSymbol,
+    pub variable: PropositionalVariable,
+    pub formula: PbesExpression,
+}
+
+impl PbesEquation {
+    /// Converts the term `PBEqn(FixPoint, PropVarDecl, PBExpr)` into an equation.
+    pub fn from_term(term: &ATermRef<'_>) -> Result<PbesEquation, PbesError> {
+        if term.get_head_symbol().name() != "PBEqn" || term.get_head_symbol().arity() != 3 {
+            return Err(PbesError::InvalidTerm(term.to_string(), "equation"));
+        }
+
+        Ok(PbesEquation {
+            symbol: FixpointSymbol::from_term(&term.arg(0))?,
+            variable: PropositionalVariable::from_term(&term.arg(1))?,
+            formula: PbesExpression::from_term(&term.arg(2))?,
+        })
+    }
+
+    pub fn to_term(&self, tp: &mut TermPool) -> ATerm {
+        let symbol = self.symbol.to_term(tp);
+        let variable = self.variable.to_term(tp);
+        let formula = self.formula.to_term(tp);
+
+        let equation = tp.create_symbol("PBEqn", 3);
+        tp.create(&equation, &[symbol, variable, formula])
+    }
+}
+
+impl fmt::Display for PbesEquation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} = {};", self.symbol, self.variable, self.formula)
+    }
+}
+
+/// A parameterised boolean equation system, which consists of a sequence of
+/// fixpoint equations and an initial state, where the order of the equations
+/// determines the solution.
+#[derive(Clone)]
+pub struct Pbes {
+    pub data_specification: DataSpecification,
+    pub global_variables: Vec<DataVariable>,
+    pub equations: Vec<PbesEquation>,
+    pub initial_state: PropositionalVariableInstantiation,
+}
+
+impl Pbes {
+    /// Reads the equation system from the given .pbes file.
+    pub fn read(filename: &str) -> Result<Pbes, Box<dyn Error>> {
+        Ok(Pbes::from_specification(&PbesSpecification::read(filename)?)?)
+    }
+
+    /// Parses and typechecks the given text in the textual PBES format of mCRL2.
+    pub fn parse(text: &str) -> Result<Pbes, Box<dyn Error>> {
+        Ok(Pbes::from_specification(&PbesSpecification::parse(text)?)?)
+    }
+
+    /// Converts the equation system of mCRL2 into its Rust representation.
+    pub fn from_specification(specification: &PbesSpecification) -> Result<Pbes, PbesError> {
+        let equations = specification
+            .equations()
+            .iter()
+            .map(|equation| PbesEquation::from_term(equation))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Pbes {
+            data_specification: specification.data_specification(),
+            global_variables: specification.global_variables(),
+            equations,
+            initial_state: PropositionalVariableInstantiation::from_term(&specification.initial_state())?,
+        })
+    }
+
+    /// Converts the equation system into the mCRL2 representation, which
+    /// fails when the initial state has no equation.
+    pub fn to_specification(&self, tp: &mut TermPool) -> Result<PbesSpecification, Box<dyn Error>> {
+        if self.equation(&self.initial_state.name).is_none() {
+            return Err(PbesError::UndefinedInitialState(self.initial_state.name.clone()).into());
+        }
+
+        let global_variables: Vec<ATerm> = self
+            .global_variables
+            .iter()
+            .map(|variable| variable.clone().into())
+            .collect();
+        let global_variables = tp.create_list(&global_variables);
+
+        let equations: Vec<ATerm> = self.equations.iter().map(|equation| equation.to_term(tp)).collect();
+        let equations = tp.create_list(&equations);
+
+        let initial_state = self.initial_state.to_term(tp);
+        PbesSpecification::new(&self.data_specification, &global_variables, &equations, &initial_state)
+    }
+
+    /// Writes the equation system to the given .pbes file.
+    pub fn write(&self, tp: &mut TermPool, filename: &str) -> Result<(), Box<dyn Error>> {
+        self.to_specification(tp)?.write(filename)
+    }
+
+    /// Returns the equation for the predicate variable with the given name.
+    pub fn equation(&self, name: &str) -> Option<&PbesEquation> {
+        self.equations.iter().find(|equation| equation.variable.name == name)
+    }
+}
+
+impl fmt::Display for Pbes {
+    /// Prints the global variables, equations and initial state in the textual
+    /// PBES format, but without the data specification.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.global_variables.is_empty() {
+            write!(f, "glob ")?;
+            for (index, variable) in self.global_variables.iter().enumerate() {
+                if index > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{}: {}", variable, variable.sort().protect())?;
+            }
+            writeln!(f, ";")?;
+            writeln!(f)?;
+        }
+
+        writeln!(f, "pbes")?;
+        for equation in &self.equations {
+            writeln!(f, "  {}", equation)?;
+        }
+        writeln!(f)?;
+        write!(f, "init {};", self.initial_state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use test_log::test;
+
+    const EXAMPLE: &str = "pbes
+        nu X(b: Bool) = (val(b) => Y(!b)) && forall n: Nat . val(n < 2) || X(b);
+        mu Y(c: Bool) = exists m: Nat . val(m == 1) && !X(c);
+
+        init X(true);";
+
+    #[test]
+    fn test_parse_pbes() {
+        let pbes = Pbes::parse(EXAMPLE).unwrap();
+
+        assert_eq!(pbes.equations.len(), 2);
+        assert_eq!(pbes.equations[0].symbol, FixpointSymbol::Nu);
+        assert_eq!(pbes.equations[1].symbol, FixpointSymbol::Mu);
+        assert_eq!(pbes.equations[0].variable.name, "X");
+        assert_eq!(pbes.equations[0].variable.parameters.len(), 1);
+        assert_eq!(pbes.initial_state.name, "X");
+        assert_eq!(pbes.initial_state.arguments.len(), 1);
+        assert!(matches!(pbes.equations[0].formula, PbesExpression::And(_, _)));
+    }
+
+    #[test]
+    fn test_pbes_term_round_trip() {
+        let pbes = Pbes::parse(EXAMPLE).unwrap();
+        let mut tp = TermPool::new();
+
+        for equation in &pbes.equations {
+            let term = equation.to_term(&mut tp);
+            assert_eq!(&PbesEquation::from_term(&term).unwrap(), equation);
+        }
+
+        let result = Pbes::from_specification(&pbes.to_specification(&mut tp).unwrap()).unwrap();
+        assert_eq!(result.equations, pbes.equations);
+        assert_eq!(result.initial_state, pbes.initial_state);
+        assert_eq!(result.to_string(), pbes.to_string());
+    }
+}
@@ -0,0 +1,328 @@
+use std::fmt;
+
+use mcrl2::aterm::ATerm;
+use mcrl2::aterm::ATermList;
+use mcrl2::aterm::ATermRef;
+use mcrl2::aterm::TermPool;
+use mcrl2::data::DataExpression;
+use mcrl2::data::DataVariable;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum PbesError {
+    #[error("The term {0} is not a valid {1}")]
+    InvalidTerm(String, &'static str),
+
+    #[error("The initial state refers to {0}, which has no equation")]
+    UndefinedInitialState(String),
+
+    #[error("The action formula {0} has data arguments, which are not supported")]
+    UnsupportedActionFormula(String),
+
+    #[error("The predicate variable {0} has no equation")]
+    UndefinedVariable(String),
+
+    #[error("The predicate variables in {0} occur under a negation")]
+    NegatedVariable(String),
+
+    #[error("Cannot enumerate the values of quantified variable {0} of sort {1}")]
+    InfiniteSort(String, String),
+
+    #[error("The data expression {0} rewrites to {1} instead of true or false")]
+    UndecidedCondition(String, String),
+}
+
+/// The fixpoint symbol of an equation, which is either the least (mu) or the
+/// greatest (nu) fixpoint.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FixpointSymbol {
+    Mu,
+    Nu,
+}
+
+impl FixpointSymbol {
+    /// Converts the term `Mu()` or `Nu()` into a fixpoint symbol.
+    pub fn from_term(term: &ATermRef<'_>) -> Result<FixpointSymbol, PbesError> {
+        match (term.get_head_symbol().name(), term.get_head_symbol().arity()) {
+            ("Mu", 0) => Ok(FixpointSymbol::Mu),
+            ("Nu", 0) => Ok(FixpointSymbol::Nu),
+            _ => Err(PbesError::InvalidTerm(term.to_string(), "fixpoint symbol")),
+        }
+    }
+
+    pub fn to_term(&self, tp: &mut TermPool) -> ATerm {
+        let symbol = tp.create_symbol(
+            match self {
+                FixpointSymbol::Mu => "Mu",
+                FixpointSymbol::Nu => "Nu",
+            },
+            0,
+        );
+        tp.create(&symbol, &[] as &[ATerm])
+    }
+}
+
+impl fmt::Display for FixpointSymbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FixpointSymbol::Mu => write!(f, "mu"),
+            FixpointSymbol::Nu => write!(f, "nu"),
+        }
+    }
+}
+
+/// The left-hand side `X(d_0: D_0, ..., d_n: D_n)` of an equation, which
+/// declares a predicate variable with its data parameters.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct PropositionalVariable {
+    pub name: String,
+    pub parameters: Vec<DataVariable>,
+}
+
+impl PropositionalVariable {
+    /// Converts the term `PropVarDecl(name, parameters)` into a propositional variable.
+    pub fn from_term(term: &ATermRef<'_>) -> Result<PropositionalVariable, PbesError> {
+        if term.get_head_symbol().name() != "PropVarDecl" || term.get_head_symbol().arity() < 2 {
+            return Err(PbesError::InvalidTerm(term.to_string(), "propositional variable"));
+        }
+
+        let parameters: ATermList<DataVariable> = term.arg(1).protect().into();
+        Ok(PropositionalVariable {
+            name: term.arg(0).get_head_symbol().name().to_string(),
+            parameters: parameters.iter().collect(),
+        })
+    }
+
+    pub fn to_term(&self, tp: &mut TermPool) -> ATerm {
+        let name = create_name(tp, &self.name);
+        let parameters: Vec<ATerm> = self
+            .parameters
+            .iter()
+            .map(|parameter| parameter.clone().into())
+            .collect();
+        let parameters = tp.create_list(&parameters);
+
+        let symbol = tp.create_symbol("PropVarDecl", 2);
+        tp.create(&symbol, &[name, parameters])
+    }
+}
+
+impl fmt::Display for PropositionalVariable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        if !self.parameters.is_empty() {
+            write!(f, "(")?;
+            for (index, parameter) in self.parameters.iter().enumerate() {
+                if index > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{}: {}", parameter, parameter.sort().protect())?;
+            }
+            write!(f, ")")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// An occurrence `X(e_0, ..., e_n)` of a predicate variable in the right-hand
+/// side of an equation, or the initial state.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct PropositionalVariableInstantiation {
+    pub name: String,
+    pub arguments: Vec<DataExpression>,
+}
+
+impl PropositionalVariableInstantiation {
+    /// Converts the term `PropVarInst(name, arguments, index)` into an
+    /// instantiation, where the index is ignored since it is only used
+    /// internally by mCRL2.
+    pub fn from_term(term: &ATermRef<'_>) -> Result<PropositionalVariableInstantiation, PbesError> {
+        if !is_propositional_variable_instantiation(term) {
+            return Err(PbesError::InvalidTerm(
+                term.to_string(),
+                "propositional variable instantiation",
+            ));
+        }
+
+        let arguments: ATermList<DataExpression> = term.arg(1).protect().into();
+        Ok(PropositionalVariableInstantiation {
+            name: term.arg(0).get_head_symbol().name().to_string(),
+            arguments: arguments.iter().collect(),
+        })
+    }
+
+    /// Creates the term `PropVarInst(name, arguments, 0)`, for which the index
+    /// is recomputed when it is passed to mCRL2.
+    pub fn to_term(&self, tp: &mut TermPool) -> ATerm {
+        let name = create_name(tp, &self.name);
+        let arguments: Vec<ATerm> = self.arguments.iter().map(|argument| argument.clone().into()).collect();
+        let arguments = tp.create_list(&arguments);
+        let index: ATerm = tp.create_int(0).into();
+
+        let symbol = tp.create_symbol("PropVarInst", 3);
+        tp.create(&symbol, &[name, arguments, index])
+    }
+}
+
+impl fmt::Display for PropositionalVariableInstantiation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        if !self.arguments.is_empty() {
+            write!(f, "(")?;
+            for (index, argument) in self.arguments.iter().enumerate() {
+                if index > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{}", argument)?;
+            }
+            write!(f, ")")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The right-hand side of an equation, which is a first-order formula over
+/// data expressions and predicate variables.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub enum PbesExpression {
+    Data(DataExpression),
+    Variable(PropositionalVariableInstantiation),
+    Not(Box<PbesExpression>),
+    And(Box<PbesExpression>, Box<PbesExpression>),
+    Or(Box<PbesExpression>, Box<PbesExpression>),
+    Implies(Box<PbesExpression>, Box<PbesExpression>),
+    Forall(Vec<DataVariable>, Box<PbesExpression>),
+    Exists(Vec<DataVariable>, Box<PbesExpression>),
+}
+
+impl PbesExpression {
+    /// Converts the given term into a PBES expression, where every term that is
+    /// not one of the PBES operators is a data expression.
+    pub fn from_term(term: &ATermRef<'_>) -> Result<PbesExpression, PbesError> {
+        if is_propositional_variable_instantiation(term) {
+            return Ok(PbesExpression::Variable(PropositionalVariableInstantiation::from_term(
+                term,
+            )?));
+        }
+
+        let binary = |term: &ATermRef<'_>| -> Result<(Box<PbesExpression>, Box<PbesExpression>), PbesError> {
+            Ok((
+                Box::new(PbesExpression::from_term(&term.arg(0))?),
+                Box::new(PbesExpression::from_term(&term.arg(1))?),
+            ))
+        };
+
+        let quantifier = |term: &ATermRef<'_>| -> Result<(Vec<DataVariable>, Box<PbesExpression>), PbesError> {
+            let variables: ATermList<DataVariable> = term.arg(0).protect().into();
+            Ok((
+                variables.iter().collect(),
+                Box::new(PbesExpression::from_term(&term.arg(1))?),
+            ))
+        };
+
+        match (term.get_head_symbol().name(), term.get_head_symbol().arity()) {
+            ("PBESNot", 1) => Ok(PbesExpression::Not(Box::new(PbesExpression::from_term(&term.arg(0))?))),
+            ("PBESAnd", 2) => {
+                let (left, right) = binary(term)?;
+                Ok(PbesExpression::And(left, right))
+            }
+            ("PBESOr", 2) => {
+                let (left, right) = binary(term)?;
+                Ok(PbesExpression::Or(left, right))
+            }
+            ("PBESImp", 2) => {
+                let (left, right) = binary(term)?;
+                Ok(PbesExpression::Implies(left, right))
+            }
+            ("PBESForall", 2) => {
+                let (variables, body) = quantifier(term)?;
+                Ok(PbesExpression::Forall(variables, body))
+            }
+            ("PBESExists", 2) => {
+                let (variables, body) = quantifier(term)?;
+                Ok(PbesExpression::Exists(variables, body))
+            }
+            _ => Ok(PbesExpression::Data(term.protect().into())),
+        }
+    }
+
+    pub fn to_term(&self, tp: &mut TermPool) -> ATerm {
+        match self {
+            PbesExpression::Data(expression) => expression.clone().into(),
+            PbesExpression::Variable(variable) => variable.to_term(tp),
+            PbesExpression::Not(expression) => {
+                let argument = expression.to_term(tp);
+                let symbol = tp.create_symbol("PBESNot", 1);
+                tp.create(&symbol, &[argument])
+            }
+            PbesExpression::And(left, right) => create_binary(tp, "PBESAnd", left, right),
+            PbesExpression::Or(left, right) => create_binary(tp, "PBESOr", left, right),
+            PbesExpression::Implies(left, right) => create_binary(tp, "PBESImp", left, right),
+            PbesExpression::Forall(variables, body) => create_quantifier(tp, "PBESForall", variables, body),
+            PbesExpression::Exists(variables, body) => create_quantifier(tp, "PBESExists", variables, body),
+        }
+    }
+}
+
+impl fmt::Display for PbesExpression {
+    /// Prints the expression in the textual PBES format, where the binary
+    /// operators are always parenthesised.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let write_variables = |f: &mut fmt::Formatter<'_>, variables: &[DataVariable]| -> fmt::Result {
+            for (index, variable) in variables.iter().enumerate() {
+                if index > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{}: {}", variable, variable.sort().protect())?;
+            }
+            Ok(())
+        };
+
+        match self {
+            PbesExpression::Data(expression) => write!(f, "val({})", expression),
+            PbesExpression::Variable(variable) => write!(f, "{}", variable),
+            PbesExpression::Not(expression) => write!(f, "!{}", expression),
+            PbesExpression::And(left, right) => write!(f, "({} && {})", left, right),
+            PbesExpression::Or(left, right) => write!(f, "({} || {})", left, right),
+            PbesExpression::Implies(left, right) => write!(f, "({} => {})", left, right),
+            PbesExpression::Forall(variables, body) => {
+                write!(f, "(forall ")?;
+                write_variables(f, variables)?;
+                write!(f, ". {})", body)
+            }
+            PbesExpression::Exists(variables, body) => {
+                write!(f, "(exists ")?;
+                write_variables(f, variables)?;
+                write!(f, ". {})", body)
+            }
+        }
+    }
+}
+
+/// Returns true iff the given term is `PropVarInst(name, arguments, index)`.
+fn is_propositional_variable_instantiation(term: &ATermRef<'_>) -> bool {
+    term.get_head_symbol().name() == "PropVarInst" && term.get_head_symbol().arity() >= 2
+}
+
+/// Creates the constant that mCRL2 uses to represent an identifier.
+fn create_name(tp: &mut TermPool, name: &str) -> ATerm {
+    let symbol = tp.create_symbol(name, 0);
+    tp.create(&symbol, &[] as &[ATerm])
+}
+
+fn create_binary(tp: &mut TermPool, name: &str, left: &PbesExpression, right: &PbesExpression) -> ATerm {
+    let left = left.to_term(tp);
+    let right = right.to_term(tp);
+    let symbol = tp.create_symbol(name, 2);
+    tp.create(&symbol, &[left, right])
+}
+
+fn create_quantifier(tp: &mut TermPool, name: &str, variables: &[DataVariable], body: &PbesExpression) -> ATerm {
+    let variables: Vec<ATerm> = variables.iter().map(|variable| variable.clone().into()).collect();
+    let variables = tp.create_list(&variables);
+    let body = body.to_term(tp);
+    let symbol = tp.create_symbol(name, 2);
+    tp.create(&symbol, &[variables, body])
+}
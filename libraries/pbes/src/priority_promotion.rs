@@ -0,0 +1,135 @@
+use log::debug;
+use log::trace;
+
+use crate::ParityGame;
+use crate::ParityGameSolver;
+use crate::Player;
+use crate::VertexIndex;
+
+/// Solves parity games with the priority promotion algorithm of Benerecetti,
+/// Dell'Erba and Mogavero, see [solve_priority_promotion].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PriorityPromotionSolver;
+
+impl ParityGameSolver for PriorityPromotionSolver {
+    fn solve(&self, game: &ParityGame) -> Vec<Player> {
+        solve_priority_promotion(game)
+    }
+}
+
+/// Returns the winner of every vertex of the given parity game, computed by
+/// repeatedly searching for a dominion of one of the players, which is then
+/// removed together with its attractor.
+///
+/// The search assigns every vertex to the region of a priority, where the
+/// region of a priority is the attractor of the vertices with that priority
+/// in the subgame of the lower regions. A region from which the opponent can
+/// only escape to higher regions is promoted to the lowest of those regions,
+/// instead of recursively solving the subgames as done by the algorithm of
+/// Zielonka. This avoids the recomputations that make the latter slow on
+/// games with many nested priorities.
+pub fn solve_priority_promotion(game: &ParityGame) -> Vec<Player> {
+    let num_of_vertices = game.num_of_vertices();
+    let predecessors = game.predecessors();
+
+    let mut remaining = vec![true; num_of_vertices];
+    let mut num_of_remaining = num_of_vertices;
+    let mut winners = vec![Player::Even; num_of_vertices];
+
+    while num_of_remaining > 0 {
+        let (dominion, player) = search_dominion(game, &predecessors, &remaining);
+        let dominion = game.attractor(&predecessors, &remaining, dominion, player);
+
+        for vertex in (0..num_of_vertices).filter(|&vertex| dominion[vertex]) {
+            remaining[vertex] = false;
+            winners[vertex] = player;
+            num_of_remaining -= 1;
+        }
+        debug!("Found a dominion of player {player}, {num_of_remaining} vertices remain");
+    }
+
+    winners
+}
+
+/// Returns a non-empty dominion of the returned player in the subgame induced
+/// by the remaining vertices.
+fn search_dominion(game: &ParityGame, predecessors: &[Vec<VertexIndex>], remaining: &[bool]) -> (Vec<bool>, Player) {
+    let num_of_vertices = game.num_of_vertices();
+
+    // The priority of the region of every vertex, which is at least its own priority.
+    let mut region: Vec<usize> = (0..num_of_vertices).map(|vertex| game.priority(vertex)).collect();
+    let mut priority = (0..num_of_vertices)
+        .filter(|&vertex| remaining[vertex])
+        .map(|vertex| game.priority(vertex))
+        .max()
+        .expect("The remaining game is not empty");
+
+    loop {
+        let player = Player::from_priority(priority);
+
+        // The subgame of the vertices in the regions up to the current priority
+        // is total, since it is the complement of attractors in a total game.
+        let subgame: Vec<bool> = (0..num_of_vertices)
+            .map(|vertex| remaining[vertex] && region[vertex] <= priority)
+            .collect();
+        let target: Vec<bool> = (0..num_of_vertices)
+            .map(|vertex| subgame[vertex] && region[vertex] == priority)
+            .collect();
+        let attracted = game.attractor(predecessors, &subgame, target, player);
+        for vertex in (0..num_of_vertices).filter(|&vertex| attracted[vertex]) {
+            region[vertex] = priority;
+        }
+
+        if !is_closed(game, &subgame, &attracted, player) {
+            // The opponent can escape to a lower region, or the player cannot stay in the region.
+            priority = (0..num_of_vertices)
+                .filter(|&vertex| subgame[vertex] && !attracted[vertex])
+                .map(|vertex| region[vertex])
+                .max()
+                .expect("An open region in a total subgame has a lower region");
+            continue;
+        }
+
+        // The region is closed in the subgame, so the opponent can only escape to higher regions.
+        let escape = (0..num_of_vertices)
+            .filter(|&vertex| attracted[vertex] && game.owner(vertex) != player)
+            .flat_map(|vertex| game.successors(vertex).iter())
+            .filter(|&&successor| remaining[successor] && !attracted[successor])
+            .map(|&successor| region[successor])
+            .min();
+
+        match escape {
+            None => return (attracted, player),
+            Some(promotion) => {
+                trace!("Promoting the region of priority {priority} to {promotion}");
+
+                // The regions below the promoted region are reset, since they were computed in a different subgame.
+                for vertex in (0..num_of_vertices).filter(|&vertex| remaining[vertex]) {
+                    if attracted[vertex] {
+                        region[vertex] = promotion;
+                    } else if region[vertex] < promotion {
+                        region[vertex] = game.priority(vertex);
+                    }
+                }
+
+                priority = promotion;
+            }
+        }
+    }
+}
+
+/// Returns true iff the player can keep the play in the region, and the
+/// opponent cannot leave it to another vertex of the subgame.
+fn is_closed(game: &ParityGame, subgame: &[bool], region: &[bool], player: Player) -> bool {
+    (0..game.num_of_vertices())
+        .filter(|&vertex| region[vertex])
+        .all(|vertex| {
+            let mut successors = game.successors(vertex).iter().filter(|&&successor| subgame[successor]);
+
+            if game.owner(vertex) == player {
+                successors.any(|&successor| region[successor])
+            } else {
+                successors.all(|&successor| region[successor])
+            }
+        })
+}
@@ -0,0 +1,304 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use log::debug;
+use log::info;
+use mcrl2::aterm::TermPool;
+use mcrl2::data::BoolSort;
+use mcrl2::data::DataExpression;
+use sabre::for_each_instance;
+use sabre::Enumerator;
+use sabre::RewriteEngine;
+use sabre::RewriteSpecification;
+use sabre::Strategy;
+use sabre::Substitution;
+
+use crate::Pbes;
+use crate::PbesExpression;
+use crate::PropositionalVariableInstantiation;
+
+/// Simplifies the right-hand sides of the equations of the given PBES, similar
+/// to pbesrewr of mCRL2, which reduces the work of instantiating it.
+///
+/// The data expressions are rewritten by a rewriter with the given strategy,
+/// and the boolean operators are simplified when one of their operands is
+/// true or false. The quantifiers over finite sorts are eliminated by
+/// enumerating the values of their variables, see [Enumerator], and the
+/// quantifiers over infinite sorts are kept. Finally, the occurrences of the
+/// predicate variables of which the right-hand side simplifies to true or
+/// false are replaced by that constant, until no further equations become
+/// constant.
+pub fn simplify_pbes(pbes: &Pbes, tp: Rc<RefCell<TermPool>>, strategy: Strategy) -> Pbes {
+    let spec = RewriteSpecification::from(pbes.data_specification.clone());
+    let enumerator = Enumerator::new(&mut tp.borrow_mut(), &spec);
+
+    let mut simplifier = Simplifier {
+        rewriter: strategy.rewriter(tp, &spec),
+        enumerator,
+        constants: HashMap::new(),
+        true_term: BoolSort::true_term(),
+        false_term: BoolSort::false_term(),
+    };
+
+    let mut equations = pbes.equations.clone();
+    loop {
+        let num_of_constants = simplifier.constants.len();
+        for (equation, original) in equations.iter_mut().zip(&pbes.equations) {
+            equation.formula = simplifier.simplify(&original.formula, &Substitution::new());
+
+            if let Some(value) = simplifier.constant(&equation.formula) {
+                debug!("The equation of {} is constant {}", equation.variable.name, value);
+                simplifier.constants.insert(equation.variable.name.clone(), value);
+            }
+        }
+
+        // The constants can only make other equations constant after they have been substituted.
+        if simplifier.constants.len() == num_of_constants {
+            break;
+        }
+    }
+
+    info!(
+        "Simplified the PBES, {} of the {} equations are constant",
+        simplifier.constants.len(),
+        equations.len()
+    );
+
+    let initial_state = PropositionalVariableInstantiation {
+        name: pbes.initial_state.name.clone(),
+        arguments: pbes
+            .initial_state
+            .arguments
+            .iter()
+            .map(|argument| simplifier.rewriter.rewrite(argument.clone()))
+            .collect(),
+    };
+
+    Pbes {
+        data_specification: pbes.data_specification.clone(),
+        global_variables: pbes.global_variables.clone(),
+        equations,
+        initial_state,
+    }
+}
+
+struct Simplifier {
+    rewriter: Box<dyn RewriteEngine>,
+    enumerator: Enumerator,
+
+    /// The predicate variables of which the right-hand side is true or false.
+    constants: HashMap<String, bool>,
+
+    true_term: DataExpression,
+    false_term: DataExpression,
+}
+
+impl Simplifier {
+    /// Returns the simplified expression, where the data variables are
+    /// replaced by their values in the substitution.
+    fn simplify(&mut self, expression: &PbesExpression, substitution: &Substitution) -> PbesExpression {
+        match expression {
+            PbesExpression::Data(expression) => {
+                PbesExpression::Data(self.rewriter.rewrite_with_substitution(expression, substitution))
+            }
+            PbesExpression::Variable(variable) => {
+                if let Some(&value) = self.constants.get(&variable.name) {
+                    return self.value(value);
+                }
+
+                PbesExpression::Variable(PropositionalVariableInstantiation {
+                    name: variable.name.clone(),
+                    arguments: variable
+                        .arguments
+                        .iter()
+                        .map(|argument| self.rewriter.rewrite_with_substitution(argument, substitution))
+                        .collect(),
+                })
+            }
+            PbesExpression::Not(inner) => {
+                let inner = self.simplify(inner, substitution);
+                self.not(inner)
+            }
+            PbesExpression::And(left, right) => {
+                let left = self.simplify(left, substitution);
+                if self.constant(&left) == Some(false) {
+                    return left;
+                }
+
+                let right = self.simplify(right, substitution);
+                self.and(left, right)
+            }
+            PbesExpression::Or(left, right) => {
+                let left = self.simplify(left, substitution);
+                if self.constant(&left) == Some(true) {
+                    return left;
+                }
+
+                let right = self.simplify(right, substitution);
+                self.or(left, right)
+            }
+            PbesExpression::Implies(left, right) => {
+                let left = self.simplify(left, substitution);
+                let left = self.not(left);
+                if self.constant(&left) == Some(true) {
+                    return left;
+                }
+
+                let right = self.simplify(right, substitution);
+                match (self.constant(&left), self.constant(&right)) {
+                    (Some(false), _) => right,
+                    (_, Some(_)) => self.or(left, right),
+                    _ => PbesExpression::Implies(Box::new(self.not(left)), Box::new(right)),
+                }
+            }
+            PbesExpression::Forall(variables, body) | PbesExpression::Exists(variables, body) => {
+                let is_forall = matches!(expression, PbesExpression::Forall(_, _));
+
+                // The bound variables shadow the variables with the same name in the substitution.
+                let outer: Substitution = substitution
+                    .iter()
+                    .filter(|(variable, _)| !variables.contains(variable))
+                    .cloned()
+                    .collect();
+
+                let domains: Option<Vec<Vec<DataExpression>>> = variables
+                    .iter()
+                    .map(|variable| {
+                        self.enumerator
+                            .values(&variable.sort().protect())
+                            .map(|values| values.to_vec())
+                    })
+                    .collect();
+
+                let Some(domains) = domains else {
+                    // The quantifier cannot be eliminated, but its body can still be simplified.
+                    let body = self.simplify(body, &outer);
+                    return if self.constant(&body).is_some() {
+                        body
+                    } else if is_forall {
+                        PbesExpression::Forall(variables.clone(), Box::new(body))
+                    } else {
+                        PbesExpression::Exists(variables.clone(), Box::new(body))
+                    };
+                };
+                let domains: Vec<&[DataExpression]> = domains.iter().map(|domain| &domain[..]).collect();
+
+                let mut result = self.value(is_forall);
+                for_each_instance(&domains, |instance| {
+                    let mut substitution = outer.clone();
+                    substitution.extend(variables.iter().cloned().zip(instance.iter().cloned()));
+
+                    let expression = self.simplify(body, &substitution);
+                    result = if is_forall {
+                        self.and(result.clone(), expression)
+                    } else {
+                        self.or(result.clone(), expression)
+                    };
+
+                    // The quantifier is decided as soon as an instance is false, or true respectively.
+                    self.constant(&result) != Some(!is_forall)
+                });
+
+                result
+            }
+        }
+    }
+
+    /// Returns the value of the given expression when it is true or false.
+    fn constant(&self, expression: &PbesExpression) -> Option<bool> {
+        match expression {
+            PbesExpression::Data(expression) if *expression == self.true_term => Some(true),
+            PbesExpression::Data(expression) if *expression == self.false_term => Some(false),
+            _ => None,
+        }
+    }
+
+    fn value(&self, value: bool) -> PbesExpression {
+        PbesExpression::Data(if value {
+            self.true_term.clone()
+        } else {
+            self.false_term.clone()
+        })
+    }
+
+    /// Returns the negation of the given simplified expression.
+    fn not(&self, expression: PbesExpression) -> PbesExpression {
+        match (self.constant(&expression), expression) {
+            (Some(value), _) => self.value(!value),
+            (None, PbesExpression::Not(inner)) => *inner,
+            (None, expression) => PbesExpression::Not(Box::new(expression)),
+        }
+    }
+
+    /// Returns the conjunction of the given simplified expressions.
+    fn and(&self, left: PbesExpression, right: PbesExpression) -> PbesExpression {
+        match (self.constant(&left), self.constant(&right)) {
+            (Some(false), _) | (_, Some(true)) => left,
+            (Some(true), _) | (_, Some(false)) => right,
+            _ => PbesExpression::And(Box::new(left), Box::new(right)),
+        }
+    }
+
+    /// Returns the disjunction of the given simplified expressions.
+    fn or(&self, left: PbesExpression, right: PbesExpression) -> PbesExpression {
+        match (self.constant(&left), self.constant(&right)) {
+            (Some(true), _) | (_, Some(false)) => left,
+            (Some(false), _) | (_, Some(true)) => right,
+            _ => PbesExpression::Or(Box::new(left), Box::new(right)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use test_log::test;
+
+    use crate::solve_pbes;
+    use crate::SolverKind;
+
+    fn simplify(text: &str) -> Pbes {
+        let pbes = Pbes::parse(text).unwrap();
+        simplify_pbes(&pbes, Rc::new(RefCell::new(TermPool::new())), Strategy::Outermost)
+    }
+
+    #[test]
+    fn test_simplify_pbes() {
+        // The quantifier over Bool is eliminated, and the constant Y is propagated into X.
+        let pbes = simplify(
+            "pbes
+                nu X(b: Bool) = (exists c: Bool . val(c && b)) || Y(b);
+                mu Y(b: Bool) = val(1 > 2) && Y(b);
+                nu Z(n: Nat) = forall m: Nat . val(true) || Z(m);
+
+                init X(true);",
+        );
+
+        assert_eq!(pbes.equations[0].formula.to_string(), "val(b)");
+        assert_eq!(pbes.equations[1].formula.to_string(), "val(false)");
+        assert_eq!(pbes.equations[2].formula.to_string(), "val(true)");
+    }
+
+    #[test]
+    fn test_simplify_preserves_solution() {
+        let tp = Rc::new(RefCell::new(TermPool::new()));
+        for text in [
+            "pbes nu X(b: Bool) = val(b) || X(!b); init X(false);",
+            "pbes mu X(b: Bool) = exists c: Bool . val(c) && Y(c); nu Y(c: Bool) = val(c); init X(false);",
+            "pbes mu X(b: Bool) = forall c: Bool . Y(c); nu Y(c: Bool) = val(c); init X(false);",
+            "pbes nu X = Y; mu Y = X; init X;",
+            "pbes mu X(n: Nat) = val(n > 3) || (val(n < 10) => X(n + 1)); init X(0);",
+        ] {
+            let pbes = Pbes::parse(text).unwrap();
+            let simplified = simplify_pbes(&pbes, tp.clone(), Strategy::Outermost);
+
+            assert_eq!(
+                solve_pbes(&pbes, tp.clone(), Strategy::Outermost, SolverKind::default()).unwrap(),
+                solve_pbes(&simplified, tp.clone(), Strategy::Outermost, SolverKind::default()).unwrap(),
+                "The solution of {text} changed by simplifying it to {simplified}"
+            );
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+
+use log::debug;
+
+use crate::ParityGame;
+use crate::ParityGameSolver;
+use crate::Player;
+
+/// Solves parity games with the small progress measures algorithm of
+/// Jurdziński, see [solve_small_progress_measures].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SmallProgressMeasuresSolver;
+
+impl ParityGameSolver for SmallProgressMeasuresSolver {
+    fn solve(&self, game: &ParityGame) -> Vec<Player> {
+        solve_small_progress_measures(game)
+    }
+}
+
+/// A progress measure counts for every odd priority how often it can be
+/// visited before a higher even priority, and None is the top element which
+/// means that player odd wins.
+type Measure = Option<Vec<usize>>;
+
+/// Returns the winner of every vertex of the given parity game, computed by
+/// lifting the progress measures of the vertices until they are stable.
+///
+/// The running time is polynomial in the number of vertices, but exponential
+/// in the number of odd priorities. Unlike the recursive algorithm its
+/// running time does not depend on the nesting of the priorities.
+pub fn solve_small_progress_measures(game: &ParityGame) -> Vec<Player> {
+    let num_of_vertices = game.num_of_vertices();
+    let num_of_priorities = (0..num_of_vertices)
+        .map(|vertex| game.priority(vertex) + 1)
+        .max()
+        .unwrap_or(0);
+
+    // The value of every odd priority is at most the number of vertices with that priority.
+    let mut bounds = vec![0; num_of_priorities];
+    for vertex in 0..num_of_vertices {
+        if game.priority(vertex) % 2 == 1 {
+            bounds[game.priority(vertex)] += 1;
+        }
+    }
+
+    let predecessors = game.predecessors();
+    let mut measures: Vec<Measure> = vec![Some(vec![0; num_of_priorities]); num_of_vertices];
+    let mut queue: VecDeque<usize> = (0..num_of_vertices).collect();
+    let mut queued = vec![true; num_of_vertices];
+    let mut num_of_lifts = 0;
+
+    while let Some(vertex) = queue.pop_front() {
+        queued[vertex] = false;
+
+        // Player even chooses the successor with the smallest measure, and odd the largest.
+        let priority = game.priority(vertex);
+        let candidates = game
+            .successors(vertex)
+            .iter()
+            .map(|&successor| progress(&measures[successor], priority, &bounds));
+        let lifted = match game.owner(vertex) {
+            Player::Even => candidates.min_by(compare),
+            Player::Odd => candidates.max_by(compare),
+        }
+        .expect("Every vertex has a successor");
+
+        if compare(&lifted, &measures[vertex]) == Ordering::Greater {
+            measures[vertex] = lifted;
+            num_of_lifts += 1;
+
+            for &predecessor in &predecessors[vertex] {
+                if !queued[predecessor] {
+                    queued[predecessor] = true;
+                    queue.push_back(predecessor);
+                }
+            }
+        }
+    }
+
+    debug!("Stabilised the progress measures after {num_of_lifts} lifts");
+    measures
+        .iter()
+        .map(|measure| if measure.is_some() { Player::Even } else { Player::Odd })
+        .collect()
+}
+
+/// Returns the least measure that is at least the given measure on the
+/// priorities from the given priority upwards, and greater when the priority
+/// is odd.
+fn progress(measure: &Measure, priority: usize, bounds: &[usize]) -> Measure {
+    let mut result = measure.clone()?;
+    for value in &mut result[..priority] {
+        *value = 0;
+    }
+
+    if priority % 2 == 1 {
+        // Increment the measure as a number with a digit for every odd priority, of which the lowest is the least significant.
+        let mut digit = priority;
+        loop {
+            if digit >= result.len() {
+                return None;
+            }
+
+            if result[digit] < bounds[digit] {
+                result[digit] += 1;
+                break;
+            }
+
+            result[digit] = 0;
+            digit += 2;
+        }
+    }
+
+    Some(result)
+}
+
+/// Compares the measures where the highest priority is the most significant, and the top is the largest.
+fn compare(left: &Measure, right: &Measure) -> Ordering {
+    match (left, right) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(left), Some(right)) => left.iter().rev().cmp(right.iter().rev()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use test_log::test;
+
+    #[test]
+    fn test_progress() {
+        let bounds = vec![0, 1, 0, 2];
+
+        // The priorities below the given priority are reset.
+        assert_eq!(progress(&Some(vec![0, 1, 0, 1]), 2, &bounds), Some(vec![0, 0, 0, 1]));
+        assert_eq!(progress(&Some(vec![0, 1, 0, 1]), 1, &bounds), Some(vec![0, 0, 0, 2]));
+        assert_eq!(progress(&Some(vec![0, 1, 0, 2]), 1, &bounds), None);
+        assert_eq!(progress(&None, 0, &bounds), None);
+    }
+}
@@ -0,0 +1,256 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+use std::str::FromStr;
+
+use log::debug;
+use mcrl2::aterm::TermPool;
+use sabre::Strategy;
+
+use crate::instantiate_pbes;
+use crate::Instantiator;
+use crate::ParityGame;
+use crate::Pbes;
+use crate::PbesError;
+use crate::Player;
+use crate::PriorityPromotionSolver;
+use crate::SmallProgressMeasuresSolver;
+use crate::ZielonkaSolver;
+
+/// A common interface for the algorithms that compute the winners of a parity game.
+pub trait ParityGameSolver {
+    /// Returns the winner of every vertex of the given parity game.
+    fn solve(&self, game: &ParityGame) -> Vec<Player>;
+}
+
+/// The algorithm that is used to solve parity games.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum SolverKind {
+    /// The recursive algorithm of Zielonka, see [ZielonkaSolver].
+    #[default]
+    Zielonka,
+
+    /// The small progress measures algorithm of Jurdziński, see [SmallProgressMeasuresSolver].
+    SmallProgressMeasures,
+
+    /// The priority promotion algorithm, see [PriorityPromotionSolver].
+    PriorityPromotion,
+}
+
+impl SolverKind {
+    /// Creates the solver that implements this algorithm.
+    pub fn solver(self) -> Box<dyn ParityGameSolver> {
+        match self {
+            SolverKind::Zielonka => Box::new(ZielonkaSolver),
+            SolverKind::SmallProgressMeasures => Box::new(SmallProgressMeasuresSolver),
+            SolverKind::PriorityPromotion => Box::new(PriorityPromotionSolver),
+        }
+    }
+}
+
+impl FromStr for SolverKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "zielonka" => Ok(SolverKind::Zielonka),
+            "spm" => Ok(SolverKind::SmallProgressMeasures),
+            "priority-promotion" => Ok(SolverKind::PriorityPromotion),
+            _ => Err(format!(
+                "Unknown solver {s}, expected one of zielonka, spm or priority-promotion"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for SolverKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolverKind::Zielonka => write!(f, "zielonka"),
+            SolverKind::SmallProgressMeasures => write!(f, "spm"),
+            SolverKind::PriorityPromotion => write!(f, "priority-promotion"),
+        }
+    }
+}
+
+/// Returns the solution of the initial state of the given PBES, which is
+/// computed by instantiating it into a parity game that is solved by the
+/// given solver.
+pub fn solve_pbes(
+    pbes: &Pbes,
+    tp: Rc<RefCell<TermPool>>,
+    strategy: Strategy,
+    solver: SolverKind,
+) -> Result<bool, PbesError> {
+    let game = instantiate_pbes(pbes, tp, strategy)?;
+    let winners = solver.solver().solve(&game);
+
+    Ok(winners[game.initial_vertex()] == Player::Even)
+}
+
+/// The number of instances that are instantiated before the partial parity
+/// game is solved for the first time by [solve_pbes_local].
+const INITIAL_BATCH_SIZE: usize = 1000;
+
+/// Returns the solution of the initial state of the given PBES, similar to
+/// [solve_pbes], but the parity game is instantiated on-the-fly and solved
+/// after every batch of instances.
+///
+/// The instances that have not been instantiated yet are assumed to be won by
+/// player odd and by player even respectively. When even wins the initial
+/// vertex in the former game, or odd wins it in the latter, then the winning
+/// strategy never reaches these instances and the solution is known. This
+/// terminates for falsifiable properties as soon as a counterexample has been
+/// instantiated, even when the reachable part of the PBES is infinite. The
+/// size of the batches doubles every time, such that the partial games are
+/// solved a logarithmic number of times.
+pub fn solve_pbes_local(
+    pbes: &Pbes,
+    tp: Rc<RefCell<TermPool>>,
+    strategy: Strategy,
+    solver: SolverKind,
+) -> Result<bool, PbesError> {
+    let mut instantiator = Instantiator::new(pbes, tp, strategy)?;
+    let solver = solver.solver();
+
+    let mut batch_size = INITIAL_BATCH_SIZE;
+    loop {
+        if instantiator.explore(batch_size)? {
+            let game = instantiator.into_game();
+            return Ok(solver.solve(&game)[game.initial_vertex()] == Player::Even);
+        }
+
+        debug!(
+            "Solving the partial parity game with {} vertices",
+            instantiator.num_of_vertices()
+        );
+        for unexplored in [Player::Odd, Player::Even] {
+            let game = instantiator.partial_game(unexplored);
+            let winner = solver.solve(&game)[game.initial_vertex()];
+            if winner != unexplored {
+                return Ok(winner == Player::Even);
+            }
+        }
+
+        batch_size *= 2;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mcrl2::lps::LinearProcessSpecification;
+
+    use lts::StateFormula;
+
+    use crate::lps2pbes;
+    use crate::random_parity_game;
+
+    use super::*;
+
+    use test_log::test;
+
+    const SOLVERS: [SolverKind; 3] = [
+        SolverKind::Zielonka,
+        SolverKind::SmallProgressMeasures,
+        SolverKind::PriorityPromotion,
+    ];
+
+    fn solve(text: &str) -> bool {
+        let pbes = Pbes::parse(text).unwrap();
+        let solutions: Vec<bool> = SOLVERS
+            .iter()
+            .map(|&solver| {
+                solve_pbes(
+                    &pbes,
+                    Rc::new(RefCell::new(TermPool::new())),
+                    Strategy::Outermost,
+                    solver,
+                )
+                .unwrap()
+            })
+            .collect();
+
+        assert!(
+            solutions.iter().all(|&solution| solution == solutions[0]),
+            "The solvers disagree on {text}"
+        );
+        solutions[0]
+    }
+
+    #[test]
+    fn test_solve_pbes() {
+        assert!(solve("pbes nu X = X; init X;"));
+        assert!(!solve("pbes mu X = X; init X;"));
+
+        // X(false) depends on X(true), which holds.
+        assert!(solve("pbes nu X(b: Bool) = val(b) || X(!b); init X(false);"));
+        assert!(!solve("pbes mu X(b: Bool) = val(b && !b) || X(!b); init X(true);"));
+
+        // The quantifiers are eliminated by enumerating the values of Bool.
+        assert!(solve(
+            "pbes mu X(b: Bool) = exists c: Bool . val(c) && Y(c); nu Y(c: Bool) = val(c); init X(false);"
+        ));
+        assert!(!solve(
+            "pbes mu X(b: Bool) = forall c: Bool . Y(c); nu Y(c: Bool) = val(c); init X(false);"
+        ));
+
+        // The alternation between the fixpoints determines the solution.
+        assert!(!solve("pbes mu X = Y; nu Y = X; init X;"));
+        assert!(solve("pbes nu X = Y; mu Y = X; init X;"));
+    }
+
+    #[test]
+    fn test_solve_lps2pbes() {
+        let lps = LinearProcessSpecification::read("../../examples/lps/abp.lps").unwrap();
+        let tp = Rc::new(RefCell::new(TermPool::new()));
+
+        // The alternating bit protocol is deadlock free, but it can always do an action.
+        let solve = |formula: &str| {
+            let pbes = lps2pbes(&mut tp.borrow_mut(), &lps, &StateFormula::parse(formula).unwrap()).unwrap();
+            solve_pbes(&pbes, tp.clone(), Strategy::Outermost, SolverKind::default()).unwrap()
+        };
+
+        assert!(solve("[true*]<true>true"));
+        assert!(!solve("<true*>[true]false"));
+    }
+
+    #[test]
+    fn test_solve_pbes_local() {
+        // The reachable instances are infinite, but the solution is decided by the first instances.
+        let solve_local = |text: &str| {
+            let pbes = Pbes::parse(text).unwrap();
+            solve_pbes_local(
+                &pbes,
+                Rc::new(RefCell::new(TermPool::new())),
+                Strategy::Outermost,
+                SolverKind::default(),
+            )
+            .unwrap()
+        };
+
+        assert!(solve_local("pbes mu X(n: Nat) = val(n == 3) || X(n + 1); init X(0);"));
+        assert!(!solve_local("pbes nu X(n: Nat) = val(n != 3) && X(n + 1); init X(0);"));
+
+        // The solution of finite equation systems is the same as the global solution.
+        assert!(solve_local("pbes nu X(b: Bool) = val(b) || X(!b); init X(false);"));
+        assert!(!solve_local("pbes mu X = Y; nu Y = X; init X;"));
+    }
+
+    #[test]
+    fn test_random_parity_games() {
+        for _ in 0..100 {
+            let game = random_parity_game(50, 6, 3);
+            let expected = ZielonkaSolver.solve(&game);
+
+            assert_eq!(SmallProgressMeasuresSolver.solve(&game), expected, "{game:?}");
+            assert_eq!(PriorityPromotionSolver.solve(&game), expected, "{game:?}");
+        }
+    }
+
+    #[test]
+    fn test_solver_kind() {
+        for solver in SOLVERS {
+            assert_eq!(solver.to_string().parse::<SolverKind>(), Ok(solver));
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use log::trace;
+
+use crate::ParityGame;
+use crate::ParityGameSolver;
+use crate::Player;
+use crate::VertexIndex;
+
+/// Solves parity games with the recursive algorithm of Zielonka, see [solve_zielonka].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ZielonkaSolver;
+
+impl ParityGameSolver for ZielonkaSolver {
+    fn solve(&self, game: &ParityGame) -> Vec<Player> {
+        solve_zielonka(game)
+    }
+}
+
+/// Returns the winner of every vertex of the given parity game, computed by
+/// the recursive algorithm of Zielonka. Its running time is exponential in the
+/// number of priorities in the worst case, but it performs well in practice.
+pub fn solve_zielonka(game: &ParityGame) -> Vec<Player> {
+    let solver = Zielonka {
+        game,
+        predecessors: game.predecessors(),
+    };
+
+    let [even, _] = solver.solve(&vec![true; game.num_of_vertices()], 0);
+    even.iter()
+        .map(|&won| if won { Player::Even } else { Player::Odd })
+        .collect()
+}
+
+/// The sets of vertices are represented by a boolean for every vertex of the game.
+struct Zielonka<'a> {
+    game: &'a ParityGame,
+    predecessors: Vec<Vec<VertexIndex>>,
+}
+
+impl Zielonka<'_> {
+    /// Returns the winning regions of player even and odd in the subgame
+    /// induced by the given vertices, which must be a trap for both players.
+    fn solve(&self, vertices: &[bool], depth: usize) -> [Vec<bool>; 2] {
+        let num_of_vertices = self.game.num_of_vertices();
+        let Some(max_priority) = (0..num_of_vertices)
+            .filter(|&vertex| vertices[vertex])
+            .map(|vertex| self.game.priority(vertex))
+            .max()
+        else {
+            return [vec![false; num_of_vertices], vec![false; num_of_vertices]];
+        };
+
+        trace!("Solving subgame with priority {max_priority} at depth {depth}");
+        let player = Player::from_priority(max_priority);
+        let opponent = player.opponent();
+
+        // The player can force the play to the vertices with the highest priority.
+        let target: Vec<bool> = (0..num_of_vertices)
+            .map(|vertex| vertices[vertex] && self.game.priority(vertex) == max_priority)
+            .collect();
+        let attractor = self.game.attractor(&self.predecessors, vertices, target, player);
+
+        let mut winning = self.solve(&difference(vertices, &attractor), depth + 1);
+        if !winning[opponent as usize].contains(&true) {
+            winning[player as usize] = vertices.to_vec();
+            return winning;
+        }
+
+        // The opponent wins the vertices from which it can force the play into its winning region.
+        let attractor = self.game.attractor(
+            &self.predecessors,
+            vertices,
+            winning[opponent as usize].clone(),
+            opponent,
+        );
+        let mut winning = self.solve(&difference(vertices, &attractor), depth + 1);
+        for (won, attracted) in winning[opponent as usize].iter_mut().zip(attractor) {
+            *won |= attracted;
+        }
+
+        winning
+    }
+}
+
+fn difference(left: &[bool], right: &[bool]) -> Vec<bool> {
+    left.iter().zip(right).map(|(&left, &right)| left && !right).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use test_log::test;
+
+    #[test]
+    fn test_zielonka() {
+        // Vertex 0 is won by even by moving to the self-loop with priority 2 in
+        // vertex 1, instead of the self-loop with priority 1 in vertex 2. Odd
+        // moves from vertex 3 to vertex 2, so it wins vertices 2 and 3.
+        let game = ParityGame::new(
+            vec![Player::Even, Player::Odd, Player::Odd, Player::Odd],
+            vec![0, 2, 1, 0],
+            vec![vec![1, 2], vec![1], vec![2], vec![0, 2]],
+            0,
+        );
+
+        assert_eq!(
+            solve_zielonka(&game),
+            vec![Player::Even, Player::Even, Player::Odd, Player::Odd]
+        );
+    }
+}
@@ -4,10 +4,9 @@ use std::path::PathBuf;
 use std::str::FromStr;
 
 use mcrl2::aterm::ATerm;
+use mcrl2::aterm::BottomUpBuilder;
 use mcrl2::aterm::Symbol;
-use mcrl2::aterm::TermBuilder;
 use mcrl2::aterm::TermPool;
-use mcrl2::aterm::Yield;
 use pest::iterators::Pair;
 use pest::Parser;
 use pest_derive::Parser;
@@ -206,41 +205,42 @@ pub fn from_string(tp: &mut TermPool, str: &str) -> Result<ATerm, Box<dyn Error>
     parse_term(tp, pairs.next().unwrap())
 }
 
+/// The work that remains to be done to construct a term from a parsed term.
+enum Work<'a> {
+    Term(Pair<'a, Rule>),
+    Symbol(Symbol),
+}
+
 /// Extracts data from parsed term.
 fn parse_term(tp: &mut TermPool, pair: Pair<Rule>) -> Result<ATerm, Box<dyn Error>> {
     debug_assert_eq!(pair.as_rule(), Rule::term);
 
-    let mut builder = TermBuilder::<Pair<'_, Rule>, Symbol>::new();
-
-    Ok(builder
-        .evaluate(
-            tp,
-            pair,
-            |tp, stack, pair| {
-                match pair.as_rule() {
-                    Rule::term => {
-                        let mut inner = pair.into_inner();
-                        let head_symbol = inner.next().unwrap().as_str();
-
-                        // Queue applications for all the arguments.
-                        let mut arity = 0;
-                        if let Some(args) = inner.next() {
-                            for arg in args.into_inner() {
-                                stack.push(arg);
-                                arity += 1;
-                            }
-                        }
-
-                        Ok(Yield::Construct(tp.create_symbol(head_symbol, arity)))
-                    }
-                    _ => {
-                        panic!("Should be unreachable!")
-                    }
-                }
-            },
-            |tp, symbol, args| Ok(tp.create(&symbol, args)),
-        )
-        .unwrap())
+    // Constructs the term bottom up, by visiting the arguments before their head symbol.
+    let mut builder = BottomUpBuilder::new();
+    let mut stack = vec![Work::Term(pair)];
+
+    while let Some(work) = stack.pop() {
+        match work {
+            Work::Term(pair) => {
+                let mut inner = pair.into_inner();
+                let head_symbol = inner.next().unwrap().as_str();
+
+                let arguments: Vec<Pair<'_, Rule>> = match inner.next() {
+                    Some(args) => args.into_inner().collect(),
+                    None => vec![],
+                };
+
+                // The arguments are popped from the stack in order.
+                stack.push(Work::Symbol(tp.create_symbol(head_symbol, arguments.len())));
+                stack.extend(arguments.into_iter().rev().map(Work::Term));
+            }
+            Work::Symbol(symbol) => {
+                builder.push_symbol(tp, &symbol, symbol.arity());
+            }
+        }
+    }
+
+    Ok(builder.pop().expect("The builder should contain the term"))
 }
 
 // /Extracts data from parsed rewrite rule
@@ -43,7 +43,11 @@ impl RewriteSpecificationSyntax {
             });
         }
 
-        RewriteSpecification { rewrite_rules }
+        // REC specifications are untyped, so there are no sorts to enumerate.
+        RewriteSpecification {
+            rewrite_rules,
+            constructors: vec![],
+        }
     }
 
     pub fn merge(&mut self, include_spec: &RewriteSpecificationSyntax) {
@@ -45,7 +45,7 @@ pub fn criterion_benchmark_jitty(c: &mut Criterion) {
         let (data_spec, expressions) = load_case(&mut tp.borrow_mut(), data_spec, expressions, 1);
 
         let mut jitty = JittyRewriter::new(&data_spec);
-        let mut inner = InnermostRewriter::new(tp.clone(), &data_spec.into());
+        let mut inner = InnermostRewriter::new(&data_spec.into());
 
         c.bench_function(&format!("innermost {}", name), |bencher| {
             bencher.iter(|| {
@@ -0,0 +1,445 @@
+use std::collections::HashMap;
+
+use log::debug;
+use mcrl2::aterm::ATermRef;
+use mcrl2::aterm::TermPool;
+use mcrl2::data::is_data_application;
+use mcrl2::data::is_data_function_symbol;
+use mcrl2::data::is_data_machine_number;
+use mcrl2::data::BoolSort;
+use mcrl2::data::DataApplication;
+use mcrl2::data::DataExpression;
+use mcrl2::data::DataExpressionRef;
+use mcrl2::data::DataFunctionSymbol;
+use mcrl2::data::DataFunctionSymbolRef;
+use mcrl2::data::FunctionSortRef;
+use mcrl2::data::MachineNumber;
+use mcrl2::data::MachineNumberRef;
+use mcrl2::data::SortExpression;
+
+use crate::RewriteSpecification;
+
+/// Evaluates the arithmetic operations on the standard mCRL2 number sorts Pos,
+/// Nat and Int directly on machine integers, instead of applying the rewrite
+/// rules on their binary representation.
+///
+/// Both the encoding using `@c1` and `@cDub(b, p)` and the encoding using
+/// machine words, `@most_significant_digit(w)` and `@concat_digit(p, w)`, are
+/// recognised. Whenever the result cannot be represented, for example due to
+/// an overflow, the operation is not evaluated and the rewrite rules are used
+/// instead.
+pub struct Arithmetic {
+    /// The constructors of the number sorts, indexed by their operation id.
+    constructors: HashMap<usize, Constructor>,
+
+    /// The constructors that are used to represent the values of every sort.
+    symbols: HashMap<(NumberSort, Constructor), DataFunctionSymbol>,
+
+    /// The operations that can be evaluated, indexed by their operation id.
+    operations: HashMap<usize, (Operation, Codomain)>,
+
+    true_term: DataExpression,
+    false_term: DataExpression,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+enum NumberSort {
+    Pos,
+    Nat,
+    Int,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+enum Codomain {
+    Number(NumberSort),
+    Bool,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+enum Constructor {
+    /// @c1: Pos
+    One,
+    /// @cDub: Bool # Pos -> Pos
+    Double,
+    /// @c0: Nat
+    Zero,
+    /// @cNat: Pos -> Nat
+    Nat,
+    /// @cInt: Nat -> Int
+    Int,
+    /// @cNeg: Pos -> Int
+    Negative,
+    /// @most_significant_digit: @word -> Pos, or @most_significant_digitNat: @word -> Nat
+    Digit,
+    /// @concat_digit: Pos # @word -> Pos, or Nat # @word -> Nat
+    Concat,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+enum Operation {
+    Add,
+    Subtract,
+    Negate,
+    Multiply,
+    Div,
+    Mod,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Equal,
+    NotEqual,
+    Max,
+    Min,
+    Succ,
+    Pred,
+    Abs,
+    Convert,
+}
+
+/// The base of the machine word encoding.
+const WORD_BASE: i128 = 1 << 64;
+
+impl Arithmetic {
+    /// Recognises the number sorts and the arithmetic operations on them in the given specification.
+    pub fn new(spec: &RewriteSpecification) -> Arithmetic {
+        let mut constructors = HashMap::new();
+        let mut symbols = HashMap::new();
+
+        for (sort, sort_constructors) in &spec.constructors {
+            let Some(number_sort) = number_sort(sort) else {
+                continue;
+            };
+
+            for symbol in sort_constructors {
+                let constructor = match (number_sort, symbol.name()) {
+                    (NumberSort::Pos, "@c1") => Constructor::One,
+                    (NumberSort::Pos, "@cDub") => Constructor::Double,
+                    (NumberSort::Nat, "@c0") => Constructor::Zero,
+                    (NumberSort::Nat, "@cNat") => Constructor::Nat,
+                    (NumberSort::Int, "@cInt") => Constructor::Int,
+                    (NumberSort::Int, "@cNeg") => Constructor::Negative,
+                    (NumberSort::Pos, "@most_significant_digit") => Constructor::Digit,
+                    (NumberSort::Nat, "@most_significant_digitNat") => Constructor::Digit,
+                    (NumberSort::Pos | NumberSort::Nat, "@concat_digit") => Constructor::Concat,
+                    _ => {
+                        debug!(
+                            "Unknown constructor {} of sort {}, arithmetic is disabled",
+                            symbol, sort
+                        );
+                        return Arithmetic::disabled();
+                    }
+                };
+
+                constructors.insert(symbol.operation_id(), constructor);
+                symbols.insert((number_sort, constructor), symbol.clone());
+            }
+        }
+
+        let mut operations = HashMap::new();
+        for rule in &spec.rewrite_rules {
+            let symbol = rule.lhs.data_function_symbol();
+            if let Some(operation) = operation(&symbol) {
+                operations.insert(symbol.operation_id(), operation);
+            }
+        }
+
+        debug!("Evaluating {} arithmetic operations natively", operations.len());
+        Arithmetic {
+            constructors,
+            symbols,
+            operations,
+            true_term: BoolSort::true_term(),
+            false_term: BoolSort::false_term(),
+        }
+    }
+
+    /// Returns an instance that never evaluates any operation.
+    fn disabled() -> Arithmetic {
+        Arithmetic {
+            constructors: HashMap::new(),
+            symbols: HashMap::new(),
+            operations: HashMap::new(),
+            true_term: BoolSort::true_term(),
+            false_term: BoolSort::false_term(),
+        }
+    }
+
+    /// Evaluates the application of the given symbol to the arguments, which
+    /// must be in normal form. Returns None when the symbol is not an
+    /// arithmetic operation, the arguments are not numbers or the result cannot
+    /// be represented.
+    pub fn evaluate(
+        &self,
+        tp: &mut TermPool,
+        symbol: &DataFunctionSymbolRef<'_>,
+        arguments: &[DataExpressionRef<'_>],
+    ) -> Option<DataExpression> {
+        if self.operations.is_empty() {
+            return None;
+        }
+
+        let (operation, codomain) = *self.operations.get(&symbol.operation_id())?;
+
+        let mut values = [0i128; 2];
+        if arguments.len() > values.len() {
+            return None;
+        }
+        for (value, argument) in values.iter_mut().zip(arguments) {
+            *value = self.decode(argument)?;
+        }
+        let [x, y] = values;
+
+        let result = match operation {
+            Operation::Add => x.checked_add(y)?,
+            Operation::Subtract => x.checked_sub(y)?,
+            Operation::Negate => x.checked_neg()?,
+            Operation::Multiply => x.checked_mul(y)?,
+            Operation::Div => {
+                if y == 0 {
+                    return None;
+                }
+                x.checked_div_euclid(y)?
+            }
+            Operation::Mod => {
+                if y == 0 {
+                    return None;
+                }
+                x.checked_rem_euclid(y)?
+            }
+            Operation::Less => return Some(self.boolean(x < y)),
+            Operation::LessEqual => return Some(self.boolean(x <= y)),
+            Operation::Greater => return Some(self.boolean(x > y)),
+            Operation::GreaterEqual => return Some(self.boolean(x >= y)),
+            Operation::Equal => return Some(self.boolean(x == y)),
+            Operation::NotEqual => return Some(self.boolean(x != y)),
+            Operation::Max => x.max(y),
+            Operation::Min => x.min(y),
+            Operation::Succ => x.checked_add(1)?,
+            Operation::Pred => x.checked_sub(1)?,
+            Operation::Abs => x.checked_abs()?,
+            Operation::Convert => x,
+        };
+
+        match codomain {
+            Codomain::Number(sort) => self.encode(tp, sort, result),
+            Codomain::Bool => None,
+        }
+    }
+
+    /// Returns the value of the given number in normal form.
+    pub(crate) fn decode(&self, term: &ATermRef<'_>) -> Option<i128> {
+        if is_data_function_symbol(term) {
+            let symbol: DataFunctionSymbolRef<'_> = term.copy().into();
+            match self.constructors.get(&symbol.operation_id())? {
+                Constructor::One => Some(1),
+                Constructor::Zero => Some(0),
+                _ => None,
+            }
+        } else if is_data_application(term) {
+            let head: DataFunctionSymbolRef<'_> = term.arg(0).into();
+            let constructor = self.constructors.get(&head.operation_id())?;
+
+            match constructor {
+                Constructor::Double => {
+                    let bit = term.arg(1);
+                    let bit = if bit == *self.true_term.copy() {
+                        1
+                    } else if bit == *self.false_term.copy() {
+                        0
+                    } else {
+                        return None;
+                    };
+
+                    self.decode(&term.arg(2))?.checked_mul(2)?.checked_add(bit)
+                }
+                Constructor::Nat | Constructor::Int => self.decode(&term.arg(1)),
+                Constructor::Negative => self.decode(&term.arg(1))?.checked_neg(),
+                Constructor::Digit => Some(word(&term.arg(1))?.into()),
+                Constructor::Concat => {
+                    let word = word(&term.arg(2))?;
+                    self.decode(&term.arg(1))?
+                        .checked_mul(WORD_BASE)?
+                        .checked_add(word.into())
+                }
+                Constructor::One | Constructor::Zero => None,
+            }
+        } else {
+            None
+        }
+    }
+
+    /// Returns the normal form of the given positive number.
+    pub(crate) fn encode_pos(&self, tp: &mut TermPool, value: i128) -> Option<DataExpression> {
+        self.encode(tp, NumberSort::Pos, value)
+    }
+
+    /// Returns the normal form of the given natural number.
+    pub(crate) fn encode_nat(&self, tp: &mut TermPool, value: i128) -> Option<DataExpression> {
+        self.encode(tp, NumberSort::Nat, value)
+    }
+
+    /// Returns the normal form of the given value in the given sort, or None
+    /// when the value does not belong to the sort.
+    fn encode(&self, tp: &mut TermPool, sort: NumberSort, value: i128) -> Option<DataExpression> {
+        match sort {
+            NumberSort::Pos => {
+                if value < 1 {
+                    return None;
+                }
+
+                if let Some(digit) = self.symbols.get(&(NumberSort::Pos, Constructor::Digit)) {
+                    self.encode_words(tp, NumberSort::Pos, digit, value)
+                } else if value == 1 {
+                    Some(self.symbols.get(&(NumberSort::Pos, Constructor::One))?.clone().into())
+                } else {
+                    let double = self.symbols.get(&(NumberSort::Pos, Constructor::Double))?;
+                    let bit = self.boolean(value % 2 == 1);
+                    let rest = self.encode(tp, NumberSort::Pos, value / 2)?;
+
+                    Some(DataApplication::new(tp, double, &[bit, rest]).into())
+                }
+            }
+            NumberSort::Nat => {
+                if value < 0 {
+                    return None;
+                }
+
+                if let Some(digit) = self.symbols.get(&(NumberSort::Nat, Constructor::Digit)) {
+                    self.encode_words(tp, NumberSort::Nat, digit, value)
+                } else if value == 0 {
+                    Some(self.symbols.get(&(NumberSort::Nat, Constructor::Zero))?.clone().into())
+                } else {
+                    let nat = self.symbols.get(&(NumberSort::Nat, Constructor::Nat))?;
+                    let pos = self.encode(tp, NumberSort::Pos, value)?;
+
+                    Some(DataApplication::new(tp, nat, &[pos]).into())
+                }
+            }
+            NumberSort::Int => {
+                if value < 0 {
+                    let negative = self.symbols.get(&(NumberSort::Int, Constructor::Negative))?;
+                    let pos = self.encode(tp, NumberSort::Pos, value.checked_neg()?)?;
+
+                    Some(DataApplication::new(tp, negative, &[pos]).into())
+                } else {
+                    let int = self.symbols.get(&(NumberSort::Int, Constructor::Int))?;
+                    let nat = self.encode(tp, NumberSort::Nat, value)?;
+
+                    Some(DataApplication::new(tp, int, &[nat]).into())
+                }
+            }
+        }
+    }
+
+    /// Encodes the given non-negative value as a sequence of machine words.
+    fn encode_words(
+        &self,
+        tp: &mut TermPool,
+        sort: NumberSort,
+        digit: &DataFunctionSymbol,
+        value: i128,
+    ) -> Option<DataExpression> {
+        let least_significant = MachineNumber::new(tp, (value % WORD_BASE) as u64);
+        if value < WORD_BASE {
+            Some(DataApplication::new(tp, digit, &[least_significant]).into())
+        } else {
+            let concat = self.symbols.get(&(sort, Constructor::Concat))?;
+            let rest = self.encode_words(tp, sort, digit, value / WORD_BASE)?;
+
+            Some(DataApplication::new(tp, concat, &[rest, least_significant.into()]).into())
+        }
+    }
+
+    fn boolean(&self, value: bool) -> DataExpression {
+        if value {
+            self.true_term.clone()
+        } else {
+            self.false_term.clone()
+        }
+    }
+}
+
+/// Returns the value of a machine word.
+fn word(term: &ATermRef<'_>) -> Option<u64> {
+    if is_data_machine_number(term) {
+        Some(MachineNumberRef::from(term.copy()).value())
+    } else {
+        None
+    }
+}
+
+/// Returns the number sort corresponding to the given sort expression.
+fn number_sort(sort: &SortExpression) -> Option<NumberSort> {
+    if !sort.is_basic_sort() {
+        return None;
+    }
+
+    match sort.name() {
+        "Pos" => Some(NumberSort::Pos),
+        "Nat" => Some(NumberSort::Nat),
+        "Int" => Some(NumberSort::Int),
+        _ => None,
+    }
+}
+
+/// Returns the operation and its codomain when the given symbol is an arithmetic operation on numbers.
+fn operation(symbol: &DataFunctionSymbolRef<'_>) -> Option<(Operation, Codomain)> {
+    let sort = symbol.sort();
+    if !sort.is_function_sort() {
+        return None;
+    }
+
+    let function_sort = FunctionSortRef::from(sort);
+    let domain: Vec<SortExpression> = function_sort.domain().iter().collect();
+    if !domain.iter().all(|sort| number_sort(sort).is_some()) {
+        return None;
+    }
+
+    let codomain = function_sort.codomain();
+    let codomain = if let Some(sort) = number_sort(&codomain) {
+        Codomain::Number(sort)
+    } else if codomain.is_basic_sort() && codomain.name() == "Bool" {
+        Codomain::Bool
+    } else {
+        return None;
+    };
+
+    let operation = match (symbol.name(), domain.len()) {
+        ("+", 2) => Operation::Add,
+        ("-", 2) => Operation::Subtract,
+        ("-", 1) => Operation::Negate,
+        ("*", 2) => Operation::Multiply,
+        ("div", 2) => Operation::Div,
+        ("mod", 2) => Operation::Mod,
+        ("<", 2) => Operation::Less,
+        ("<=", 2) => Operation::LessEqual,
+        (">", 2) => Operation::Greater,
+        (">=", 2) => Operation::GreaterEqual,
+        ("==", 2) => Operation::Equal,
+        ("!=", 2) => Operation::NotEqual,
+        ("max", 2) => Operation::Max,
+        ("min", 2) => Operation::Min,
+        ("succ", 1) => Operation::Succ,
+        ("pred", 1) => Operation::Pred,
+        ("abs", 1) => Operation::Abs,
+        ("Pos2Nat" | "Pos2Int" | "Nat2Pos" | "Nat2Int" | "Int2Pos" | "Int2Nat", 1) => Operation::Convert,
+        _ => return None,
+    };
+
+    // Comparisons yield a boolean, and all other operations a number.
+    let is_comparison = matches!(
+        operation,
+        Operation::Less
+            | Operation::LessEqual
+            | Operation::Greater
+            | Operation::GreaterEqual
+            | Operation::Equal
+            | Operation::NotEqual
+    );
+    if is_comparison != (codomain == Codomain::Bool) {
+        return None;
+    }
+
+    Some((operation, codomain))
+}
@@ -0,0 +1,38 @@
+use mcrl2::aterm::TermPool;
+use mcrl2::data::DataExpression;
+use mcrl2::data::DataExpressionRef;
+use mcrl2::data::DataFunctionSymbolRef;
+
+use crate::Arithmetic;
+use crate::Containers;
+use crate::RewriteSpecification;
+
+/// Evaluates the operations on the builtin sorts of mCRL2 natively, see
+/// [Arithmetic] and [Containers].
+pub struct Builtins {
+    arithmetic: Arithmetic,
+    containers: Containers,
+}
+
+impl Builtins {
+    pub fn new(spec: &RewriteSpecification) -> Builtins {
+        Builtins {
+            arithmetic: Arithmetic::new(spec),
+            containers: Containers::new(spec),
+        }
+    }
+
+    /// Evaluates the application of the given symbol to the arguments, which
+    /// must be in normal form. Returns None when the result must be determined
+    /// by the rewrite rules instead.
+    pub fn evaluate(
+        &self,
+        tp: &mut TermPool,
+        symbol: &DataFunctionSymbolRef<'_>,
+        arguments: &[DataExpressionRef<'_>],
+    ) -> Option<DataExpression> {
+        self.arithmetic
+            .evaluate(tp, symbol, arguments)
+            .or_else(|| self.containers.evaluate(tp, &self.arithmetic, symbol, arguments))
+    }
+}
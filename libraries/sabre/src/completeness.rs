@@ -0,0 +1,257 @@
+use std::fmt;
+use std::iter;
+
+use ahash::AHashMap;
+use itertools::Itertools;
+use mcrl2::aterm::ATermRef;
+use mcrl2::data::is_data_application;
+use mcrl2::data::is_data_function_symbol;
+use mcrl2::data::is_data_variable;
+use mcrl2::data::DataExpressionRef;
+use mcrl2::data::DataFunctionSymbol;
+use mcrl2::data::FunctionSort;
+use mcrl2::data::SortExpression;
+
+use crate::RewriteSpecification;
+
+/// A pattern of constructors, where [CasePattern::Any] matches every term.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CasePattern {
+    Any,
+    Constructor(DataFunctionSymbol, Vec<CasePattern>),
+}
+
+/// The arguments for which no rule of the function symbol applies.
+#[derive(Clone, Debug)]
+pub struct MissingCase {
+    pub symbol: DataFunctionSymbol,
+    pub arguments: Vec<CasePattern>,
+}
+
+/// A row of the pattern matrix, where None is a pattern that is not built from constructors.
+type Row = Vec<Option<CasePattern>>;
+
+impl RewriteSpecification {
+    /// Checks whether the rules of every function symbol that is not a
+    /// constructor cover all combinations of constructors of its arguments,
+    /// and returns the cases that are not covered. The conditions of the rules
+    /// are not checked, so conditional rules are assumed to cover their left
+    /// hand side.
+    ///
+    /// Arguments of sorts without constructors are only covered by variables.
+    /// Function symbols without a function sort, which is the case for the
+    /// untyped REC specifications, are not checked.
+    pub fn check_completeness(&self) -> Vec<MissingCase> {
+        let constructors = Constructors::new(self);
+
+        // The patterns of all rules for every function symbol.
+        let mut functions: Vec<(DataFunctionSymbol, Vec<Row>)> = Vec::new();
+        for rule in &self.rewrite_rules {
+            let lhs = &rule.lhs;
+            if !(is_data_function_symbol(lhs) || is_data_application(lhs) && is_data_function_symbol(&lhs.arg(0))) {
+                continue;
+            }
+
+            let symbol = lhs.data_function_symbol().protect();
+            if constructors.is_constructor(&symbol) {
+                continue;
+            }
+
+            let row: Row = lhs
+                .data_arguments()
+                .map(|argument| constructors.pattern(&argument))
+                .collect();
+            match functions
+                .iter_mut()
+                .find(|(other, rows)| *other == symbol && rows[0].len() == row.len())
+            {
+                Some((_, rows)) => rows.push(row),
+                None => functions.push((symbol, vec![row])),
+            }
+        }
+
+        let mut result = Vec::new();
+        for (symbol, rows) in functions {
+            let sort: SortExpression = symbol.sort().protect();
+            let domain: Vec<SortExpression> = if sort.is_function_sort() {
+                FunctionSort::from(sort).domain().iter().collect()
+            } else {
+                Vec::new()
+            };
+
+            if domain.len() != rows[0].len() || domain.is_empty() {
+                continue;
+            }
+
+            for arguments in constructors.missing(rows, &domain) {
+                result.push(MissingCase {
+                    symbol: symbol.clone(),
+                    arguments,
+                });
+            }
+        }
+
+        result
+    }
+}
+
+/// The constructors of every sort, with the sorts of their arguments.
+struct Constructors {
+    sorts: AHashMap<SortExpression, Vec<(DataFunctionSymbol, Vec<SortExpression>)>>,
+}
+
+impl Constructors {
+    fn new(spec: &RewriteSpecification) -> Constructors {
+        let sorts = spec
+            .constructors
+            .iter()
+            .map(|(sort, constructors)| {
+                let constructors = constructors
+                    .iter()
+                    .map(|constructor| {
+                        let sort: SortExpression = constructor.sort().protect();
+                        let domain = if sort.is_function_sort() {
+                            FunctionSort::from(sort).domain().iter().collect()
+                        } else {
+                            Vec::new()
+                        };
+
+                        (constructor.clone(), domain)
+                    })
+                    .collect();
+
+                (sort.clone(), constructors)
+            })
+            .collect();
+
+        Constructors { sorts }
+    }
+
+    fn is_constructor(&self, symbol: &DataFunctionSymbol) -> bool {
+        self.sorts
+            .values()
+            .any(|constructors| constructors.iter().any(|(constructor, _)| constructor == symbol))
+    }
+
+    /// Converts the term into a pattern, returns None when it contains symbols that are not constructors.
+    fn pattern(&self, t: &ATermRef<'_>) -> Option<CasePattern> {
+        if is_data_variable(t) {
+            return Some(CasePattern::Any);
+        }
+
+        if !(is_data_function_symbol(t) || is_data_application(t) && is_data_function_symbol(&t.arg(0))) {
+            return None;
+        }
+
+        let t: DataExpressionRef<'_> = t.copy().into();
+        let symbol = t.data_function_symbol().protect();
+        if !self.is_constructor(&symbol) {
+            return None;
+        }
+
+        let arguments = t
+            .data_arguments()
+            .map(|argument| self.pattern(&argument))
+            .collect::<Option<Vec<CasePattern>>>()?;
+        Some(CasePattern::Constructor(symbol, arguments))
+    }
+
+    /// Returns the vectors of patterns that are not covered by any of the
+    /// rows, where the columns have the given sorts. This is the algorithm
+    /// that is used by compilers of functional languages to find missing cases
+    /// in pattern matching.
+    fn missing(&self, rows: Vec<Row>, sorts: &[SortExpression]) -> Vec<Vec<CasePattern>> {
+        let Some((sort, remaining)) = sorts.split_first() else {
+            // An empty vector is covered by any row.
+            return if rows.is_empty() { vec![vec![]] } else { vec![] };
+        };
+
+        if rows.is_empty() {
+            return vec![vec![CasePattern::Any; sorts.len()]];
+        }
+
+        let constructors = self.sorts.get(sort).map_or(&[][..], |constructors| &constructors[..]);
+        let uses_constructors = rows
+            .iter()
+            .any(|row| matches!(row[0], Some(CasePattern::Constructor(_, _))));
+
+        if constructors.is_empty() || !uses_constructors {
+            // Only the rows with a variable in the first column cover every term.
+            let default: Vec<Row> = rows
+                .into_iter()
+                .filter(|row| row[0] == Some(CasePattern::Any))
+                .map(|row| row[1..].to_vec())
+                .collect();
+
+            return self
+                .missing(default, remaining)
+                .into_iter()
+                .map(|case| iter::once(CasePattern::Any).chain(case).collect())
+                .collect();
+        }
+
+        let mut result = Vec::new();
+        for (constructor, domain) in constructors {
+            let arity = domain.len();
+
+            // The rows that match the constructor, where its arguments become new columns.
+            let specialised: Vec<Row> = rows
+                .iter()
+                .filter_map(|row| match &row[0] {
+                    Some(CasePattern::Any) => Some(
+                        iter::repeat(Some(CasePattern::Any))
+                            .take(arity)
+                            .chain(row[1..].iter().cloned())
+                            .collect(),
+                    ),
+                    Some(CasePattern::Constructor(symbol, arguments))
+                        if symbol == constructor && arguments.len() == arity =>
+                    {
+                        Some(
+                            arguments
+                                .iter()
+                                .cloned()
+                                .map(Some)
+                                .chain(row[1..].iter().cloned())
+                                .collect(),
+                        )
+                    }
+                    _ => None,
+                })
+                .collect();
+
+            let sorts: Vec<SortExpression> = domain.iter().chain(remaining).cloned().collect();
+            for mut case in self.missing(specialised, &sorts) {
+                let rest = case.split_off(arity);
+                result.push(
+                    iter::once(CasePattern::Constructor(constructor.clone(), case))
+                        .chain(rest)
+                        .collect(),
+                );
+            }
+        }
+
+        result
+    }
+}
+
+impl fmt::Display for CasePattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CasePattern::Any => write!(f, "_"),
+            CasePattern::Constructor(symbol, arguments) => {
+                if arguments.is_empty() {
+                    write!(f, "{}", symbol)
+                } else {
+                    write!(f, "{}({})", symbol, arguments.iter().format(", "))
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for MissingCase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}({})", self.symbol, self.arguments.iter().format(", "))
+    }
+}
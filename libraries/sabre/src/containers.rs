@@ -0,0 +1,526 @@
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use log::debug;
+use mcrl2::aterm::ATermRef;
+use mcrl2::aterm::TermPool;
+use mcrl2::data::is_data_application;
+use mcrl2::data::is_data_function_symbol;
+use mcrl2::data::is_data_machine_number;
+use mcrl2::data::BoolSort;
+use mcrl2::data::DataApplication;
+use mcrl2::data::DataExpression;
+use mcrl2::data::DataExpressionRef;
+use mcrl2::data::DataFunctionSymbol;
+use mcrl2::data::DataFunctionSymbolRef;
+use mcrl2::data::FunctionSortRef;
+use mcrl2::data::SortExpression;
+
+use crate::Arithmetic;
+use crate::RewriteSpecification;
+
+/// Evaluates the operations on the standard mCRL2 container sorts List, FSet
+/// and FBag directly on the elements, instead of applying the rewrite rules
+/// one element at a time.
+///
+/// The sorts Set and Bag are represented by a characteristic function together
+/// with a finite set or bag of exceptions. Their rewrite rules reduce the
+/// operations to operations on these finite sets and bags, which are then
+/// evaluated here, so Set and Bag themselves are not handled.
+///
+/// The elements of finite sets and bags are sorted by the `<` of their sort.
+/// Operations that depend on this order, such as insertion and union, are
+/// only evaluated when all elements are numbers or booleans, since the order
+/// on other sorts is defined by the rewrite rules of the specification.
+///
+/// The evaluation can be disabled by the `builtin-containers` feature, for
+/// example to test conformance with the rewrite rules.
+pub struct Containers {
+    /// The empty and cons constructors of every list sort.
+    lists: HashMap<SortExpression, (DataFunctionSymbol, DataFunctionSymbol)>,
+
+    /// The empty and cons constructors of every finite set sort.
+    sets: HashMap<SortExpression, (DataFunctionSymbol, DataFunctionSymbol)>,
+
+    /// The empty and cons constructors of every finite bag sort, where cons also has the count of the element.
+    bags: HashMap<SortExpression, (DataFunctionSymbol, DataFunctionSymbol)>,
+
+    /// The operations that can be evaluated, and their codomain, indexed by their operation id.
+    operations: HashMap<usize, (Operation, SortExpression)>,
+
+    /// The operation ids of all constructors in the specification.
+    constructors: HashSet<usize>,
+
+    true_term: DataExpression,
+    false_term: DataExpression,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Operation {
+    Concat,
+    Snoc,
+    Length,
+    Head,
+    Tail,
+    RHead,
+    RTail,
+    Element,
+    Member,
+    Insert,
+    Union,
+    Intersection,
+    Difference,
+    BagInsert,
+    Count,
+    Size,
+}
+
+impl Containers {
+    /// Recognises the container sorts and the operations on them in the given specification.
+    pub fn new(spec: &RewriteSpecification) -> Containers {
+        let mut result = Containers {
+            lists: HashMap::new(),
+            sets: HashMap::new(),
+            bags: HashMap::new(),
+            operations: HashMap::new(),
+            constructors: HashSet::new(),
+            true_term: BoolSort::true_term(),
+            false_term: BoolSort::false_term(),
+        };
+
+        if !cfg!(feature = "builtin-containers") {
+            return result;
+        }
+
+        for (sort, constructors) in &spec.constructors {
+            result
+                .constructors
+                .extend(constructors.iter().map(|symbol| symbol.operation_id()));
+
+            let find = |name: &str| constructors.iter().find(|symbol| symbol.name() == name).cloned();
+            if let (Some(empty), Some(cons)) = (find("[]"), find("|>")) {
+                result.lists.insert(sort.clone(), (empty, cons));
+            } else if let (Some(empty), Some(cons)) = (find("{}"), find("@fset_cons")) {
+                result.sets.insert(sort.clone(), (empty, cons));
+            } else if let (Some(empty), Some(cons)) = (find("{:}"), find("@fbag_cons")) {
+                result.bags.insert(sort.clone(), (empty, cons));
+            }
+        }
+
+        for rule in &spec.rewrite_rules {
+            let symbol = rule.lhs.data_function_symbol();
+            if let Some(operation) = result.operation(&symbol) {
+                result.operations.insert(symbol.operation_id(), operation);
+            }
+        }
+
+        debug!("Evaluating {} container operations natively", result.operations.len());
+        result
+    }
+
+    /// Evaluates the application of the given symbol to the arguments, which
+    /// must be in normal form. Returns None when the symbol is not a container
+    /// operation or the result cannot be determined from the arguments.
+    pub fn evaluate(
+        &self,
+        tp: &mut TermPool,
+        arithmetic: &Arithmetic,
+        symbol: &DataFunctionSymbolRef<'_>,
+        arguments: &[DataExpressionRef<'_>],
+    ) -> Option<DataExpression> {
+        if self.operations.is_empty() {
+            return None;
+        }
+
+        let (operation, codomain) = self.operations.get(&symbol.operation_id())?;
+        match operation {
+            Operation::Concat => {
+                let (_, cons) = self.lists.get(codomain)?;
+                let elements = self.elements(&self.lists, &arguments[0])?;
+                Some(build(tp, cons, &elements, arguments[1].protect()))
+            }
+            Operation::Snoc => {
+                let (empty, cons) = self.lists.get(codomain)?;
+                let mut elements = self.elements(&self.lists, &arguments[0])?;
+                elements.push(arguments[1].copy());
+                Some(build(tp, cons, &elements, empty.clone().into()))
+            }
+            Operation::Length => {
+                let elements = self.elements(&self.lists, &arguments[0])?;
+                arithmetic.encode_nat(tp, elements.len() as i128)
+            }
+            Operation::Head => {
+                let elements = self.elements(&self.lists, &arguments[0])?;
+                Some(elements.first()?.protect())
+            }
+            Operation::Tail => {
+                let list = &arguments[0];
+                if self.elements(&self.lists, list)?.is_empty() {
+                    return None;
+                }
+                Some(list.arg(2).protect().into())
+            }
+            Operation::RHead => {
+                let elements = self.elements(&self.lists, &arguments[0])?;
+                Some(elements.last()?.protect())
+            }
+            Operation::RTail => {
+                let (empty, cons) = self.lists.get(codomain)?;
+                let mut elements = self.elements(&self.lists, &arguments[0])?;
+                elements.pop()?;
+                Some(build(tp, cons, &elements, empty.clone().into()))
+            }
+            Operation::Element => {
+                let elements = self.elements(&self.lists, &arguments[0])?;
+                let index = usize::try_from(arithmetic.decode(&arguments[1])?).ok()?;
+                Some(elements.get(index)?.protect())
+            }
+            Operation::Member => {
+                let container = &arguments[1];
+                let elements = self
+                    .elements(&self.lists, container)
+                    .or_else(|| self.elements(&self.sets, container))
+                    .or_else(|| {
+                        let bag = self.bag_elements(arithmetic, container)?;
+                        Some(bag.into_iter().map(|(element, _)| element).collect())
+                    })?;
+
+                let mut decided = true;
+                for element in &elements {
+                    match self.equal(&arguments[0], element) {
+                        Some(true) => return Some(self.true_term.clone()),
+                        Some(false) => {}
+                        None => decided = false,
+                    }
+                }
+
+                if decided {
+                    Some(self.false_term.clone())
+                } else {
+                    None
+                }
+            }
+            Operation::Insert => {
+                let mut set = self.ordered(arithmetic, &arguments[1])?;
+                set.insert(self.key(arithmetic, &arguments[0])?, arguments[0].copy());
+                self.build_set(tp, codomain, set)
+            }
+            Operation::Union if self.bags.contains_key(codomain) => {
+                let mut bag = self.ordered_bag(arithmetic, &arguments[0])?;
+                for (key, (element, count)) in self.ordered_bag(arithmetic, &arguments[1])? {
+                    bag.entry(key).or_insert((element, 0)).1 += count;
+                }
+                self.build_bag(tp, arithmetic, codomain, bag)
+            }
+            Operation::Union => {
+                let mut set = self.ordered(arithmetic, &arguments[0])?;
+                set.extend(self.ordered(arithmetic, &arguments[1])?);
+                self.build_set(tp, codomain, set)
+            }
+            Operation::Intersection if self.bags.contains_key(codomain) => {
+                let mut bag = self.ordered_bag(arithmetic, &arguments[0])?;
+                let other = self.ordered_bag(arithmetic, &arguments[1])?;
+                for (key, (_, count)) in &mut bag {
+                    *count = (*count).min(other.get(key).map_or(0, |(_, other_count)| *other_count));
+                }
+                self.build_bag(tp, arithmetic, codomain, bag)
+            }
+            Operation::Intersection => {
+                let mut set = self.ordered(arithmetic, &arguments[0])?;
+                let other = self.ordered(arithmetic, &arguments[1])?;
+                set.retain(|value, _| other.contains_key(value));
+                self.build_set(tp, codomain, set)
+            }
+            Operation::Difference if self.bags.contains_key(codomain) => {
+                let mut bag = self.ordered_bag(arithmetic, &arguments[0])?;
+                let other = self.ordered_bag(arithmetic, &arguments[1])?;
+                for (key, (_, count)) in &mut bag {
+                    *count -= other.get(key).map_or(0, |(_, other_count)| *other_count);
+                }
+                self.build_bag(tp, arithmetic, codomain, bag)
+            }
+            Operation::Difference => {
+                let mut set = self.ordered(arithmetic, &arguments[0])?;
+                let other = self.ordered(arithmetic, &arguments[1])?;
+                set.retain(|value, _| !other.contains_key(value));
+                self.build_set(tp, codomain, set)
+            }
+            Operation::BagInsert => {
+                // The count is a Pos for @fbag_insert and a Nat for @fbag_cinsert.
+                let mut bag = self.ordered_bag(arithmetic, &arguments[2])?;
+                let count = arithmetic.decode(&arguments[1])?;
+                bag.entry(self.key(arithmetic, &arguments[0])?)
+                    .or_insert((arguments[0].copy(), 0))
+                    .1 += count;
+                self.build_bag(tp, arithmetic, codomain, bag)
+            }
+            Operation::Count => {
+                // The elements of a bag in normal form are unique, so at most one of them is equal.
+                let mut decided = true;
+                for (element, count) in self.bag_elements(arithmetic, &arguments[1])? {
+                    match self.equal(&arguments[0], &element) {
+                        Some(true) => return arithmetic.encode_nat(tp, count),
+                        Some(false) => {}
+                        None => decided = false,
+                    }
+                }
+
+                if decided {
+                    arithmetic.encode_nat(tp, 0)
+                } else {
+                    None
+                }
+            }
+            Operation::Size => {
+                let bag = self.bag_elements(arithmetic, &arguments[0])?;
+                arithmetic.encode_nat(tp, bag.iter().map(|(_, count)| count).sum())
+            }
+        }
+    }
+
+    /// Returns the elements of the given list or finite set, or None when the
+    /// term is not a container built from the constructors in the given sorts.
+    fn elements<'a>(
+        &self,
+        sorts: &HashMap<SortExpression, (DataFunctionSymbol, DataFunctionSymbol)>,
+        term: &DataExpressionRef<'a>,
+    ) -> Option<Vec<DataExpressionRef<'a>>> {
+        let root: &ATermRef<'a> = term;
+        let mut result = Vec::new();
+        let mut current = root.copy();
+
+        loop {
+            if is_data_application(&current) {
+                let head: DataFunctionSymbolRef<'_> = current.arg(0).into();
+                if !sorts
+                    .values()
+                    .any(|(_, cons)| cons.operation_id() == head.operation_id())
+                {
+                    return None;
+                }
+
+                result.push(current.arg(1).upgrade(root).into());
+                let next = current.arg(2).upgrade(root);
+                current = next;
+            } else if is_data_function_symbol(&current) {
+                let symbol: DataFunctionSymbolRef<'_> = current.copy().into();
+                if !sorts
+                    .values()
+                    .any(|(empty, _)| empty.operation_id() == symbol.operation_id())
+                {
+                    return None;
+                }
+
+                return Some(result);
+            } else {
+                return None;
+            }
+        }
+    }
+
+    /// Returns the elements of the given finite bag together with their count,
+    /// or None when the term is not a finite bag built from the constructors.
+    fn bag_elements<'a>(
+        &self,
+        arithmetic: &Arithmetic,
+        term: &DataExpressionRef<'a>,
+    ) -> Option<Vec<(DataExpressionRef<'a>, i128)>> {
+        let root: &ATermRef<'a> = term;
+        let mut result = Vec::new();
+        let mut current = root.copy();
+
+        loop {
+            if is_data_application(&current) {
+                let head: DataFunctionSymbolRef<'_> = current.arg(0).into();
+                if !self
+                    .bags
+                    .values()
+                    .any(|(_, cons)| cons.operation_id() == head.operation_id())
+                {
+                    return None;
+                }
+
+                let count = arithmetic.decode(&current.arg(2))?;
+                result.push((current.arg(1).upgrade(root).into(), count));
+                let next = current.arg(3).upgrade(root);
+                current = next;
+            } else if is_data_function_symbol(&current) {
+                let symbol: DataFunctionSymbolRef<'_> = current.copy().into();
+                if !self
+                    .bags
+                    .values()
+                    .any(|(empty, _)| empty.operation_id() == symbol.operation_id())
+                {
+                    return None;
+                }
+
+                return Some(result);
+            } else {
+                return None;
+            }
+        }
+    }
+
+    /// Returns the position of the given element in the order of its sort, or
+    /// None when the element is not a number or boolean. The sort of the
+    /// element determines the order, so the keys of different sorts are never
+    /// compared.
+    fn key(&self, arithmetic: &Arithmetic, element: &ATermRef<'_>) -> Option<i128> {
+        // The boolean false is smaller than true.
+        if *element == *self.false_term.copy() {
+            Some(0)
+        } else if *element == *self.true_term.copy() {
+            Some(1)
+        } else {
+            arithmetic.decode(element)
+        }
+    }
+
+    /// Returns the elements of a finite set ordered by their value, or None
+    /// when one of the elements cannot be ordered, see [Containers::key].
+    fn ordered<'a>(
+        &self,
+        arithmetic: &Arithmetic,
+        term: &DataExpressionRef<'a>,
+    ) -> Option<BTreeMap<i128, DataExpressionRef<'a>>> {
+        self.elements(&self.sets, term)?
+            .into_iter()
+            .map(|element| Some((self.key(arithmetic, &element)?, element)))
+            .collect()
+    }
+
+    /// Returns the elements of a finite bag and their counts ordered by their
+    /// value, or None when one of the elements cannot be ordered.
+    fn ordered_bag<'a>(
+        &self,
+        arithmetic: &Arithmetic,
+        term: &DataExpressionRef<'a>,
+    ) -> Option<BTreeMap<i128, (DataExpressionRef<'a>, i128)>> {
+        self.bag_elements(arithmetic, term)?
+            .into_iter()
+            .map(|(element, count)| Some((self.key(arithmetic, &element)?, (element, count))))
+            .collect()
+    }
+
+    /// Constructs the finite set of the given sort from the ordered elements.
+    fn build_set(
+        &self,
+        tp: &mut TermPool,
+        sort: &SortExpression,
+        set: BTreeMap<i128, DataExpressionRef<'_>>,
+    ) -> Option<DataExpression> {
+        let (empty, cons) = self.sets.get(sort)?;
+        let elements: Vec<DataExpressionRef<'_>> = set.into_values().collect();
+        Some(build(tp, cons, &elements, empty.clone().into()))
+    }
+
+    /// Constructs the finite bag of the given sort from the ordered elements,
+    /// where the elements with a count below one are omitted.
+    fn build_bag(
+        &self,
+        tp: &mut TermPool,
+        arithmetic: &Arithmetic,
+        sort: &SortExpression,
+        bag: BTreeMap<i128, (DataExpressionRef<'_>, i128)>,
+    ) -> Option<DataExpression> {
+        let (empty, cons) = self.bags.get(sort)?;
+
+        let mut result: DataExpression = empty.clone().into();
+        for (element, count) in bag.into_values().rev().filter(|(_, count)| *count > 0) {
+            let count = arithmetic.encode_pos(tp, count)?;
+
+            let element: &ATermRef<'_> = &element;
+            let count: &ATermRef<'_> = &count;
+            let rest: &ATermRef<'_> = &result;
+            result = DataApplication::new(tp, cons, &[element, count, rest]).into();
+        }
+
+        Some(result)
+    }
+
+    /// Decides whether two terms in normal form are equal, which is only
+    /// possible when they are syntactically equal or both consist of
+    /// constructors only.
+    fn equal(&self, left: &ATermRef<'_>, right: &ATermRef<'_>) -> Option<bool> {
+        if left == right {
+            Some(true)
+        } else if self.is_value(left) && self.is_value(right) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    /// Returns true iff the given term only consists of constructors and machine numbers.
+    fn is_value(&self, term: &ATermRef<'_>) -> bool {
+        if is_data_machine_number(term) {
+            true
+        } else if is_data_function_symbol(term) {
+            let symbol: DataFunctionSymbolRef<'_> = term.copy().into();
+            self.constructors.contains(&symbol.operation_id())
+        } else if is_data_application(term) {
+            let head: DataFunctionSymbolRef<'_> = term.arg(0).into();
+            self.constructors.contains(&head.operation_id())
+                && term.arguments().skip(1).all(|argument| self.is_value(&argument))
+        } else {
+            false
+        }
+    }
+
+    /// Returns the operation and its codomain when the given symbol is an operation on containers.
+    fn operation(&self, symbol: &DataFunctionSymbolRef<'_>) -> Option<(Operation, SortExpression)> {
+        let sort = symbol.sort();
+        if !sort.is_function_sort() {
+            return None;
+        }
+
+        let function_sort = FunctionSortRef::from(sort);
+        let domain: Vec<SortExpression> = function_sort.domain().iter().collect();
+        let codomain = function_sort.codomain();
+
+        let is_list = |sort: &SortExpression| self.lists.contains_key(sort);
+        let is_set = |sort: &SortExpression| self.sets.contains_key(sort);
+        let is_bag = |sort: &SortExpression| self.bags.contains_key(sort);
+        let is_finite = |sort: &SortExpression| is_set(sort) || is_bag(sort);
+
+        let operation = match (symbol.name(), &domain[..]) {
+            ("++", [left, right]) if is_list(left) && is_list(right) => Operation::Concat,
+            ("<|", [list, _]) if is_list(list) => Operation::Snoc,
+            ("#", [list]) if is_list(list) => Operation::Length,
+            ("head", [list]) if is_list(list) => Operation::Head,
+            ("tail", [list]) if is_list(list) => Operation::Tail,
+            ("rhead", [list]) if is_list(list) => Operation::RHead,
+            ("rtail", [list]) if is_list(list) => Operation::RTail,
+            (".", [list, _]) if is_list(list) => Operation::Element,
+            ("in", [_, container]) if is_list(container) || is_finite(container) => Operation::Member,
+            ("@fset_insert", [_, set]) if is_set(set) => Operation::Insert,
+            ("+", [left, right]) if is_finite(left) && left == right => Operation::Union,
+            ("*", [left, right]) if is_finite(left) && left == right => Operation::Intersection,
+            ("-", [left, right]) if is_finite(left) && left == right => Operation::Difference,
+            ("@fbag_insert" | "@fbag_cinsert", [_, _, bag]) if is_bag(bag) => Operation::BagInsert,
+            ("count", [_, bag]) if is_bag(bag) => Operation::Count,
+            ("#", [bag]) if is_bag(bag) => Operation::Size,
+            _ => return None,
+        };
+
+        Some((operation, codomain))
+    }
+}
+
+/// Constructs the container with the given elements in front of the tail.
+fn build(
+    tp: &mut TermPool,
+    cons: &DataFunctionSymbol,
+    elements: &[DataExpressionRef<'_>],
+    tail: DataExpression,
+) -> DataExpression {
+    let mut result = tail;
+    for element in elements.iter().rev() {
+        let element: &ATermRef<'_> = element;
+        let rest: &ATermRef<'_> = &result;
+        result = DataApplication::new(tp, cons, &[element, rest]).into();
+    }
+
+    result
+}
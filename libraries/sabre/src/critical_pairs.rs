@@ -0,0 +1,301 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+use itertools::Itertools;
+use mcrl2::aterm::ATerm;
+use mcrl2::aterm::TermPool;
+use mcrl2::data::is_data_application;
+use mcrl2::data::is_data_variable;
+use mcrl2::data::DataExpression;
+use mcrl2::data::DataVariable;
+use mcrl2::data::DataVariableRef;
+
+use crate::utilities::instantiate;
+use crate::utilities::replace_at;
+use crate::utilities::ExplicitPosition;
+use crate::utilities::PositionIndexed;
+use crate::Condition;
+use crate::RewriteEngine;
+use crate::RewriteSpecification;
+use crate::Rule;
+
+/// A term that can be rewritten by two rules, where the left hand side of the
+/// inner rule overlaps with a non-variable subterm of the left hand side of the
+/// outer rule.
+#[derive(Clone, Debug)]
+pub struct CriticalPair {
+    pub outer: Rule,
+    pub inner: Rule,
+
+    /// The position of the overlap in the left hand side of the outer rule.
+    pub position: ExplicitPosition,
+
+    /// The most general term that can be rewritten by both rules.
+    pub overlap: DataExpression,
+
+    /// The result of rewriting the overlap with the outer rule.
+    pub left: DataExpression,
+
+    /// The result of rewriting the overlap with the inner rule.
+    pub right: DataExpression,
+
+    /// The instantiated conditions of both rules, which are not taken into account when checking joinability.
+    pub conditions: Vec<Condition>,
+}
+
+/// A critical pair for which the rewriter computes different normal forms.
+#[derive(Clone, Debug)]
+pub struct NonJoinablePair {
+    pub pair: CriticalPair,
+    pub left_normal_form: DataExpression,
+    pub right_normal_form: DataExpression,
+}
+
+impl RewriteSpecification {
+    /// Returns all critical pairs between the rewrite rules of this specification.
+    pub fn critical_pairs(&self, tp: &mut TermPool) -> Vec<CriticalPair> {
+        let mut result = Vec::new();
+
+        for (outer_index, outer) in self.rewrite_rules.iter().enumerate() {
+            for (inner_index, inner) in self.rewrite_rules.iter().enumerate() {
+                // The variables of the inner rule are renamed to avoid clashes with the outer rule.
+                let inner = rename_variables(tp, inner);
+
+                for position in overlap_positions(&outer.lhs) {
+                    // Overlaps at the root are symmetric, and every rule trivially overlaps with itself.
+                    if position.is_empty() && inner_index <= outer_index {
+                        continue;
+                    }
+
+                    let subterm: DataExpression = outer.lhs.get_position(&position).protect().into();
+                    if let Some(sigma) = unify(tp, &subterm, &inner.lhs) {
+                        let overlap = instantiate(tp, &outer.lhs, &sigma);
+                        let left = instantiate(tp, &outer.rhs, &sigma);
+                        let contractum: ATerm = instantiate(tp, &inner.rhs, &sigma).into();
+                        let right = replace_at(tp, &overlap.copy(), &position, contractum).into();
+
+                        let conditions = outer
+                            .conditions
+                            .iter()
+                            .chain(&inner.conditions)
+                            .map(|condition| Condition {
+                                lhs: instantiate(tp, &condition.lhs, &sigma),
+                                rhs: instantiate(tp, &condition.rhs, &sigma),
+                                equality: condition.equality,
+                            })
+                            .collect();
+
+                        result.push(CriticalPair {
+                            outer: outer.clone(),
+                            inner: self.rewrite_rules[inner_index].clone(),
+                            position,
+                            overlap,
+                            left,
+                            right,
+                            conditions,
+                        });
+                    }
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Checks whether all critical pairs of this specification are joinable,
+    /// by comparing the normal forms computed by the given rewriter, and
+    /// returns the pairs that are not. The specification is locally confluent
+    /// when the result is empty, which implies confluence when the rewrite
+    /// rules are terminating.
+    ///
+    /// The rewriter should be constructed from this specification.
+    pub fn check_confluence(
+        &self,
+        tp: &Rc<RefCell<TermPool>>,
+        rewriter: &mut dyn RewriteEngine,
+    ) -> Vec<NonJoinablePair> {
+        // The rewriter borrows the term pool itself, so the critical pairs are computed first.
+        let pairs = self.critical_pairs(&mut tp.borrow_mut());
+
+        pairs
+            .into_iter()
+            .filter_map(|pair| {
+                let left_normal_form = rewriter.rewrite(pair.left.clone());
+                let right_normal_form = rewriter.rewrite(pair.right.clone());
+
+                if left_normal_form != right_normal_form {
+                    Some(NonJoinablePair {
+                        pair,
+                        left_normal_form,
+                        right_normal_form,
+                    })
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+/// Returns the positions of the subterms of the left hand side that are not variables, in breadth first order.
+fn overlap_positions(lhs: &DataExpression) -> Vec<ExplicitPosition> {
+    let mut result = vec![ExplicitPosition::empty_pos()];
+
+    let mut index = 0;
+    while let Some(position) = result.get(index) {
+        let term = lhs.get_position(position);
+        if is_data_application(&term) {
+            let position = position.clone();
+            for (argument_index, argument) in term.arguments().enumerate().skip(1) {
+                if !is_data_variable(&argument) {
+                    let mut argument_position = position.clone();
+                    argument_position.indices.push(argument_index + 1);
+                    result.push(argument_position);
+                }
+            }
+        }
+
+        index += 1;
+    }
+
+    result
+}
+
+/// Returns a copy of the rule where every variable x is replaced by x'.
+pub(crate) fn rename_variables(tp: &mut TermPool, rule: &Rule) -> Rule {
+    let mut renaming: Vec<(ATerm, ATerm)> = Vec::new();
+    for term in [&rule.lhs, &rule.rhs].into_iter().chain(
+        rule.conditions
+            .iter()
+            .flat_map(|condition| [&condition.lhs, &condition.rhs]),
+    ) {
+        for subterm in term.iter() {
+            if is_data_variable(&subterm) && !renaming.iter().any(|(variable, _)| variable.copy() == subterm) {
+                let variable: DataVariableRef<'_> = subterm.copy().into();
+                let renamed = DataVariable::with_sort(tp, &format!("{}'", variable.name()), &variable.sort());
+                renaming.push((subterm.protect(), renamed.into()));
+            }
+        }
+    }
+
+    Rule {
+        conditions: rule
+            .conditions
+            .iter()
+            .map(|condition| Condition {
+                lhs: instantiate(tp, &condition.lhs, &renaming),
+                rhs: instantiate(tp, &condition.rhs, &renaming),
+                equality: condition.equality,
+            })
+            .collect(),
+        lhs: instantiate(tp, &rule.lhs, &renaming),
+        rhs: instantiate(tp, &rule.rhs, &renaming),
+    }
+}
+
+/// Returns the most general unifier of the given terms, or None when they cannot be unified.
+pub(crate) fn unify(tp: &mut TermPool, left: &DataExpression, right: &DataExpression) -> Option<Vec<(ATerm, ATerm)>> {
+    let mut sigma: Vec<(ATerm, ATerm)> = Vec::new();
+    let mut equations = vec![(left.clone(), right.clone())];
+
+    while let Some((left, right)) = equations.pop() {
+        let left = instantiate(tp, &left, &sigma);
+        let right = instantiate(tp, &right, &sigma);
+
+        if left == right {
+            continue;
+        }
+
+        let (variable, value) = if is_data_variable(&left) {
+            (left, right)
+        } else if is_data_variable(&right) {
+            (right, left)
+        } else if is_data_application(&left)
+            && is_data_application(&right)
+            && left.get_head_symbol() == right.get_head_symbol()
+        {
+            // The head symbols are also arguments, and have the same arity.
+            for (left, right) in left.arguments().zip(right.arguments()) {
+                equations.push((left.protect().into(), right.protect().into()));
+            }
+            continue;
+        } else {
+            return None;
+        };
+
+        // The occurs check.
+        if value.iter().any(|subterm| subterm == *variable.copy()) {
+            return None;
+        }
+
+        // Keep the substitution idempotent.
+        let binding = [(variable.clone().into(), value.clone().into())];
+        for (_, assigned) in &mut sigma {
+            *assigned = instantiate(tp, &assigned.clone().into(), &binding).into();
+        }
+        sigma.push((variable.into(), value.into()));
+    }
+
+    Some(sigma)
+}
+
+impl fmt::Display for CriticalPair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} <- {} -> {}", self.left, self.overlap, self.right)?;
+        if !self.conditions.is_empty() {
+            write!(f, " if {}", self.conditions.iter().format(", "))?;
+        }
+
+        write!(
+            f,
+            "\n  using rule {}\n  and rule {} at position {}",
+            self.outer, self.inner, self.position
+        )
+    }
+}
+
+impl fmt::Display for NonJoinablePair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.pair)?;
+        write!(
+            f,
+            "  has normal forms {} and {}",
+            self.left_normal_form, self.right_normal_form
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_utility::create_rewrite_rule;
+
+    #[test]
+    fn test_critical_pairs() {
+        let mut tp = TermPool::new();
+
+        let spec = RewriteSpecification {
+            rewrite_rules: vec![
+                create_rewrite_rule(&mut tp, "f(g(x), b)", "x", &["x"]).unwrap(),
+                create_rewrite_rule(&mut tp, "g(a)", "b", &[]).unwrap(),
+                create_rewrite_rule(&mut tp, "h(x)", "x", &["x"]).unwrap(),
+            ],
+            constructors: vec![],
+        };
+
+        // Only g(a) overlaps with the subterm g(x) of f(g(x), b).
+        let pairs = spec.critical_pairs(&mut tp);
+        assert_eq!(pairs.len(), 1);
+        assert_eq!(pairs[0].position, ExplicitPosition::new(&[2]));
+
+        let expected = create_rewrite_rule(&mut tp, "f(g(a), b)", "a", &[]).unwrap();
+        assert_eq!(pairs[0].overlap, expected.lhs);
+        assert_eq!(pairs[0].left, expected.rhs);
+        assert_eq!(
+            pairs[0].right,
+            create_rewrite_rule(&mut tp, "f(b, b)", "a", &[]).unwrap().lhs
+        );
+    }
+}
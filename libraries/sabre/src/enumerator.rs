@@ -0,0 +1,429 @@
+use std::collections::HashMap;
+use std::collections::VecDeque;
+
+use log::debug;
+use log::trace;
+use mcrl2::aterm::apply;
+use mcrl2::aterm::ATerm;
+use mcrl2::aterm::TermPool;
+use mcrl2::data::is_data_abstraction;
+use mcrl2::data::BoolSort;
+use mcrl2::data::DataAbstraction;
+use mcrl2::data::DataAbstractionRef;
+use mcrl2::data::DataApplication;
+use mcrl2::data::DataExpression;
+use mcrl2::data::DataFunctionSymbol;
+use mcrl2::data::DataVariable;
+use mcrl2::data::FunctionSortRef;
+use mcrl2::data::SortExpression;
+
+use crate::utilities::replace_all_at;
+use crate::utilities::ExplicitPosition;
+use crate::utilities::PositionIndexed;
+use crate::utilities::PositionIterator;
+use crate::RewriteSpecification;
+
+/// The maximum number of values for a single sort, larger sorts are not enumerated.
+const MAX_SORT_SIZE: usize = 10_000;
+
+/// The maximum number of instances of the body that are rewritten to eliminate a single quantifier.
+const MAX_INSTANCES: usize = 100_000;
+
+/// The maximum number of partial solutions that are rewritten to enumerate the solutions of a single condition.
+const MAX_PARTIAL_SOLUTIONS: usize = 100_000;
+
+/// Eliminates `forall` and `exists` quantifiers over finite sorts by
+/// enumerating all values of the bound variables, similar to the enumerator of
+/// mCRL2.
+///
+/// A sort is finite whenever it has constructors that only take arguments of
+/// finite sorts, for example Bool or a non-recursive structured sort.
+#[derive(Clone)]
+pub struct Enumerator {
+    /// All values for the sorts that can be enumerated.
+    values: HashMap<SortExpression, Vec<DataExpression>>,
+
+    /// The constructors of every sort, used to enumerate the solutions of conditions.
+    constructors: HashMap<SortExpression, Vec<DataFunctionSymbol>>,
+}
+
+impl Enumerator {
+    /// Computes the values of all finite sorts in the given specification.
+    pub fn new(tp: &mut TermPool, spec: &RewriteSpecification) -> Enumerator {
+        let mut values: HashMap<SortExpression, Vec<DataExpression>> = HashMap::new();
+
+        // Sorts that are finite, but have too many values to be enumerated.
+        let mut too_large: Vec<&SortExpression> = Vec::new();
+
+        // Compute the values in a fixpoint since the constructors of a sort can depend on other sorts.
+        let mut changed = true;
+        while changed {
+            changed = false;
+
+            for (sort, constructors) in &spec.constructors {
+                if constructors.is_empty() || values.contains_key(sort) || too_large.contains(&sort) {
+                    continue;
+                }
+
+                // The domains of every constructor, which must all be enumerated already.
+                let mut domains = Vec::new();
+                for constructor in constructors {
+                    let constructor_sort = constructor.sort();
+                    if constructor_sort.is_function_sort() {
+                        let domain: Vec<SortExpression> =
+                            FunctionSortRef::from(constructor_sort).domain().iter().collect();
+                        domains.push(domain);
+                    } else {
+                        domains.push(vec![]);
+                    }
+                }
+
+                if domains.iter().flatten().any(|sort| too_large.contains(&sort)) {
+                    too_large.push(sort);
+                    changed = true;
+                    continue;
+                }
+
+                if !domains.iter().flatten().all(|sort| values.contains_key(sort)) {
+                    // Either recursive, or not all values of the arguments are known yet.
+                    continue;
+                }
+
+                let size = domains.iter().try_fold(0usize, |size, domain| {
+                    domain
+                        .iter()
+                        .try_fold(1usize, |product, sort| product.checked_mul(values[sort].len()))
+                        .and_then(|product| size.checked_add(product))
+                });
+
+                changed = true;
+                match size {
+                    Some(size) if size <= MAX_SORT_SIZE => {
+                        let mut result = Vec::with_capacity(size);
+                        for (constructor, domain) in constructors.iter().zip(&domains) {
+                            let arguments: Vec<&[DataExpression]> =
+                                domain.iter().map(|sort| &values[sort][..]).collect();
+
+                            for_each_instance(&arguments, |instance| {
+                                if instance.is_empty() {
+                                    result.push(constructor.clone().into());
+                                } else {
+                                    result.push(DataApplication::new(tp, constructor, instance).into());
+                                }
+                                true
+                            });
+                        }
+
+                        trace!("Sort {} has values {:?}", sort, result);
+                        values.insert(sort.clone(), result);
+                    }
+                    _ => {
+                        debug!(
+                            "Sort {} has more than {} values, it will not be enumerated",
+                            sort, MAX_SORT_SIZE
+                        );
+                        too_large.push(sort);
+                    }
+                }
+            }
+        }
+
+        let constructors = spec
+            .constructors
+            .iter()
+            .filter(|(_, constructors)| !constructors.is_empty())
+            .cloned()
+            .collect();
+
+        Enumerator { values, constructors }
+    }
+
+    /// Returns all values of the given sort, or None when the sort cannot be enumerated.
+    pub fn values(&self, sort: &SortExpression) -> Option<&[DataExpression]> {
+        self.values.get(sort).map(|values| &values[..])
+    }
+
+    /// Eliminates the given quantifier when all its variables can be
+    /// enumerated. The `rewrite` function is used to compute the normal form
+    /// of every instance of the body.
+    ///
+    /// Returns None when the abstraction is not a quantifier, one of the
+    /// variables cannot be enumerated or when the instances do not all rewrite
+    /// to true or false.
+    pub fn eliminate<F>(
+        &self,
+        tp: &mut TermPool,
+        abstraction: &DataAbstractionRef<'_>,
+        mut rewrite: F,
+    ) -> Option<DataExpression>
+    where
+        F: FnMut(&mut TermPool, DataExpression) -> DataExpression,
+    {
+        let (absorbing, neutral) = if abstraction.is_forall() {
+            (BoolSort::false_term(), BoolSort::true_term())
+        } else if abstraction.is_exists() {
+            (BoolSort::true_term(), BoolSort::false_term())
+        } else {
+            return None;
+        };
+
+        let variables: Vec<DataVariable> = abstraction.variables().iter().collect();
+        let domains: Vec<&[DataExpression]> = variables
+            .iter()
+            .map(|variable| self.values(&variable.sort().protect()))
+            .collect::<Option<_>>()?;
+
+        let instances = domains
+            .iter()
+            .try_fold(1usize, |product, domain| product.checked_mul(domain.len()));
+        if instances.map_or(true, |instances| instances > MAX_INSTANCES) {
+            debug!("Too many instances to eliminate {}", abstraction);
+            return None;
+        }
+
+        let body: ATerm = abstraction.body().protect().into();
+        let mut decided = true;
+        let mut result = neutral.clone();
+
+        for_each_instance(&domains, |instance| {
+            let instance_body: DataExpression = apply(tp, &body, &|_, t| {
+                variables
+                    .iter()
+                    .position(|variable| **variable == *t)
+                    .map(|index| instance[index].clone().into())
+            })
+            .into();
+
+            let normal_form = rewrite(tp, instance_body);
+            trace!("Instance {:?} of {} rewrites to {}", instance, abstraction, normal_form);
+
+            if normal_form == absorbing {
+                result = absorbing.clone();
+                decided = true;
+                return false;
+            } else if normal_form != neutral {
+                decided = false;
+            }
+
+            true
+        });
+
+        if decided {
+            Some(result)
+        } else {
+            None
+        }
+    }
+
+    /// Eliminates all outermost quantifiers in the given term that can be
+    /// decided, see [Enumerator::eliminate]. Returns None when no quantifier
+    /// was eliminated.
+    pub fn eliminate_subterms<F>(
+        &self,
+        tp: &mut TermPool,
+        term: &DataExpression,
+        mut rewrite: F,
+    ) -> Option<DataExpression>
+    where
+        F: FnMut(&mut TermPool, DataExpression) -> DataExpression,
+    {
+        let mut positions: Vec<ExplicitPosition> = Vec::new();
+        for (subterm, position) in PositionIterator::new(term.copy().into()) {
+            if is_data_abstraction(&subterm) && !positions.iter().any(|p| position.indices.starts_with(&p.indices)) {
+                positions.push(position);
+            }
+        }
+
+        // The positions are independent, so all eliminated quantifiers can be replaced at once.
+        let mut replacements: Vec<(ExplicitPosition, ATerm)> = Vec::new();
+        for position in positions {
+            let abstraction: DataAbstraction = term.get_position(&position).protect().into();
+
+            if let Some(value) = self.eliminate(tp, &abstraction.copy(), &mut rewrite) {
+                replacements.push((position, value.into()));
+            }
+        }
+
+        if replacements.is_empty() {
+            None
+        } else {
+            Some(replace_all_at(tp, term, &replacements).into())
+        }
+    }
+
+    /// Returns the values of the given variables for which the condition
+    /// rewrites to true, similar to the enumerator of mCRL2. This also works
+    /// for sorts that cannot be enumerated, since the variables are
+    /// instantiated one at a time with every constructor of their sort,
+    /// applied to fresh variables, and the partial solutions for which the
+    /// condition rewrites to false are discarded. For example, the solutions
+    /// of `n < 3` for `n: Nat` are 0, 1 and 2.
+    ///
+    /// Returns None when the solutions cannot be enumerated, for example when
+    /// there are infinitely many, when the condition cannot be decided or
+    /// when a variable has a sort without constructors.
+    pub fn solutions<F>(
+        &self,
+        tp: &mut TermPool,
+        variables: &[DataVariable],
+        condition: &DataExpression,
+        mut rewrite: F,
+    ) -> Option<Vec<Vec<DataExpression>>>
+    where
+        F: FnMut(DataExpression) -> DataExpression,
+    {
+        let true_term = BoolSort::true_term();
+        let false_term = BoolSort::false_term();
+
+        // A partial solution consists of the values of the variables, the variables occurring in these values and the
+        // condition where the variables have been replaced by their values.
+        let mut partial_solutions: VecDeque<(Vec<ATerm>, Vec<DataVariable>, DataExpression)> = VecDeque::new();
+        partial_solutions.push_back((
+            variables.iter().map(|variable| variable.clone().into()).collect(),
+            variables.to_vec(),
+            rewrite(condition.clone()),
+        ));
+
+        let mut result = Vec::new();
+        let mut fresh_variables = 0;
+        let mut steps = 0;
+        while let Some((values, mut free, condition)) = partial_solutions.pop_front() {
+            steps += 1;
+            if steps > MAX_PARTIAL_SOLUTIONS {
+                debug!("Too many partial solutions to enumerate the solutions of {}", condition);
+                return None;
+            }
+
+            if condition == false_term {
+                continue;
+            }
+
+            if free.is_empty() {
+                if condition != true_term {
+                    debug!("Cannot decide whether {} holds", condition);
+                    return None;
+                }
+
+                result.push(values.into_iter().map(|value| rewrite(value.into())).collect());
+                continue;
+            }
+
+            // The terms with which the first variable is instantiated, with the fresh variables that occur in them.
+            let variable = free.remove(0);
+            let sort = variable.sort().protect();
+            let mut instances: Vec<(ATerm, Vec<DataVariable>)> = Vec::new();
+            if let Some(values) = self.values.get(&sort) {
+                instances.extend(values.iter().map(|value| (value.clone().into(), Vec::new())));
+            } else {
+                for constructor in self.constructors.get(&sort)? {
+                    let constructor_sort = constructor.sort();
+                    if constructor_sort.is_function_sort() {
+                        let mut arguments = Vec::new();
+                        for sort in FunctionSortRef::from(constructor_sort).domain().iter() {
+                            fresh_variables += 1;
+                            arguments.push(DataVariable::with_sort(
+                                tp,
+                                &format!("@x{fresh_variables}"),
+                                &sort.copy(),
+                            ));
+                        }
+
+                        let terms: Vec<DataExpression> =
+                            arguments.iter().map(|argument| argument.clone().into()).collect();
+                        instances.push((DataApplication::new(tp, constructor, &terms).into(), arguments));
+                    } else {
+                        instances.push((constructor.clone().into(), Vec::new()));
+                    }
+                }
+            }
+
+            let condition: ATerm = condition.into();
+            for (instance, arguments) in instances {
+                let substitute = |tp: &mut TermPool, term: &ATerm| {
+                    apply(tp, term, &|_, t| (**variable == **t).then(|| instance.clone()))
+                };
+
+                let values = values.iter().map(|value| substitute(tp, value)).collect();
+                let condition = rewrite(substitute(tp, &condition).into());
+
+                let mut free = free.clone();
+                free.extend(arguments);
+                partial_solutions.push_back((values, free, condition));
+            }
+        }
+
+        trace!("Enumerated {} solutions in {} steps", result.len(), steps);
+        Some(result)
+    }
+}
+
+/// Calls the function for every combination of values in the given domains,
+/// until it returns false.
+pub fn for_each_instance<F>(domains: &[&[DataExpression]], mut function: F)
+where
+    F: FnMut(&[DataExpression]) -> bool,
+{
+    if domains.iter().any(|domain| domain.is_empty()) {
+        return;
+    }
+
+    let mut indices = vec![0; domains.len()];
+    let mut instance: Vec<DataExpression> = domains.iter().map(|domain| domain[0].clone()).collect();
+
+    loop {
+        if !function(&instance) {
+            return;
+        }
+
+        // Increment the indices as a number where every domain is a single digit.
+        let mut digit = 0;
+        loop {
+            if digit == domains.len() {
+                return;
+            }
+
+            indices[digit] += 1;
+            if indices[digit] < domains[digit].len() {
+                instance[digit] = domains[digit][indices[digit]].clone();
+                break;
+            }
+
+            indices[digit] = 0;
+            instance[digit] = domains[digit][0].clone();
+            digit += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use mcrl2::aterm::TermPool;
+    use mcrl2::data::DataFunctionSymbol;
+
+    use test_log::test;
+
+    #[test]
+    fn test_for_each_instance() {
+        let mut tp = TermPool::new();
+
+        let a: DataExpression = DataFunctionSymbol::new(&mut tp, "a").into();
+        let b: DataExpression = DataFunctionSymbol::new(&mut tp, "b").into();
+        let c: DataExpression = DataFunctionSymbol::new(&mut tp, "c").into();
+
+        let first = [a.clone(), b.clone()];
+        let second = [a, b, c];
+
+        let mut instances = Vec::new();
+        for_each_instance(&[&first[..], &second[..]], |instance| {
+            instances.push(instance.to_vec());
+            true
+        });
+
+        assert_eq!(instances.len(), 6, "Every combination should be enumerated once");
+        instances.sort();
+        instances.dedup();
+        assert_eq!(instances.len(), 6, "All combinations should be different");
+    }
+}
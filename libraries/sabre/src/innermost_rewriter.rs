@@ -1,14 +1,20 @@
 use std::cell::RefCell;
-use std::rc::Rc;
+use std::sync::Arc;
 
 use log::info;
 use log::trace;
+use mcrl2::aterm::ATerm;
 use mcrl2::aterm::ATermRef;
 use mcrl2::aterm::TermPool;
+use mcrl2::data::is_data_abstraction;
+use mcrl2::data::is_data_machine_number;
+use mcrl2::data::is_data_variable;
+use mcrl2::data::DataAbstraction;
 use mcrl2::data::DataApplication;
 use mcrl2::data::DataExpression;
 use mcrl2::data::DataExpressionRef;
 
+use crate::limits::Guard;
 use crate::matching::conditions::extend_conditions;
 use crate::matching::conditions::EMACondition;
 use crate::matching::nonlinear::check_equivalence_classes;
@@ -16,49 +22,149 @@ use crate::matching::nonlinear::derive_equivalence_classes;
 use crate::matching::nonlinear::EquivalenceClass;
 use crate::set_automaton::MatchAnnouncement;
 use crate::set_automaton::SetAutomaton;
+use crate::single_step::apply_first;
+use crate::single_step::SingleStep;
+use crate::utilities::instantiate;
+use crate::utilities::lookup;
+use crate::utilities::to_aterm_substitution;
 use crate::utilities::Config;
 use crate::utilities::InnermostStack;
 use crate::utilities::PositionIndexed;
 use crate::utilities::RHSStack;
 use crate::utilities::SCCTBuilder;
+use crate::Builtins;
+use crate::CacheStatistics;
+use crate::Enumerator;
+use crate::NormalFormCache;
 use crate::RewriteEngine;
+use crate::RewriteError;
+use crate::RewriteLimits;
 use crate::RewriteSpecification;
 use crate::RewritingStatistics;
 use crate::Rule;
+use crate::SharedRewriteSpecification;
+use crate::Substitution;
+use crate::TraceCallback;
+use crate::Tracer;
 
 impl RewriteEngine for InnermostRewriter {
-    fn rewrite(&mut self, t: DataExpression) -> DataExpression {
+    fn try_rewrite(&mut self, t: DataExpression) -> Result<DataExpression, RewriteError> {
+        self.normalise(t, &[])
+    }
+
+    fn try_rewrite_with_substitution(
+        &mut self,
+        term: &DataExpression,
+        substitution: &Substitution,
+    ) -> Result<DataExpression, RewriteError> {
+        self.normalise(term.clone(), &to_aterm_substitution(substitution))
+    }
+
+    fn set_trace(&mut self, callback: Option<TraceCallback>) {
+        self.trace = callback;
+    }
+
+    fn set_limits(&mut self, limits: RewriteLimits) {
+        self.limits = limits;
+    }
+
+    fn statistics(&self) -> &RewritingStatistics {
+        &self.statistics
+    }
+
+    fn rewrite_once(&mut self, term: &DataExpression) -> Option<DataExpression> {
+        let redexes = self.single_step.redexes(term);
+        apply_first(self, |rewriter| &rewriter.tp, term, redexes)
+    }
+}
+
+impl InnermostRewriter {
+    pub fn new(spec: &RewriteSpecification) -> InnermostRewriter {
+        let apma = SetAutomaton::new(spec, AnnouncementInnermost::new, true);
+        InnermostRewriter::with_automaton(spec, Arc::new(apma))
+    }
+
+    /// Creates a rewriter for the given shared specification, where the automaton is constructed only once and then
+    /// shared by all rewriters created from the specification.
+    pub fn from_shared(spec: &SharedRewriteSpecification) -> InnermostRewriter {
+        InnermostRewriter::with_automaton(&spec.local(), spec.innermost_automaton())
+    }
+
+    fn with_automaton(
+        spec: &RewriteSpecification,
+        apma: Arc<SetAutomaton<AnnouncementInnermost>>,
+    ) -> InnermostRewriter {
+        let mut tp = TermPool::new();
+        let enumerator = Enumerator::new(&mut tp, spec);
+        let builtins = Builtins::new(spec);
+
+        info!("ATerm pool: {}", tp);
+        InnermostRewriter {
+            apma,
+            enumerator,
+            builtins,
+            tp: RefCell::new(tp),
+            stack: InnermostStack::default(),
+            builder: SCCTBuilder::new(),
+            trace: None,
+            cache: NormalFormCache::default(),
+            limits: RewriteLimits::default(),
+            statistics: RewritingStatistics::default(),
+            single_step: SingleStep::new(spec),
+        }
+    }
+
+    /// Rewrites the given term to normal form, where the variables of the substitution are replaced by their values.
+    fn normalise(&mut self, t: DataExpression, sigma: &[(ATerm, ATerm)]) -> Result<DataExpression, RewriteError> {
         let mut stats = RewritingStatistics::default();
 
         trace!("input: {}", t);
 
+        let mut tracer = Tracer::new(self.trace.take());
+        let mut guard = Guard::new(self.limits);
         let result = InnermostRewriter::rewrite_aux(
             &mut self.tp.borrow_mut(),
             &mut self.stack,
             &mut self.builder,
             &mut stats,
+            &mut tracer,
+            &mut self.cache,
+            &mut guard,
             &self.apma,
+            &self.enumerator,
+            &self.builtins,
+            sigma,
             t,
         );
+        self.trace = tracer.into_callback();
         info!(
             "{} rewrites, {} single steps and {} symbol comparisons",
             stats.recursions, stats.rewrite_steps, stats.symbol_comparisons
         );
-        result
+        self.statistics.merge(stats);
+        if self.cache.is_enabled() {
+            let cache_stats = self.cache.statistics();
+            info!(
+                "{} cache hits and {} cache misses",
+                cache_stats.hits, cache_stats.misses
+            );
+        }
+        guard.finish(result)
     }
-}
 
-impl InnermostRewriter {
-    pub fn new(tp: Rc<RefCell<TermPool>>, spec: &RewriteSpecification) -> InnermostRewriter {
-        let apma = SetAutomaton::new(spec, AnnouncementInnermost::new, true);
+    /// Stores the normal forms of at most the given number of terms, where
+    /// the normal forms of terms that have not been used recently are
+    /// discarded. The cache is disabled when the capacity is zero, which is
+    /// the default. Every term that is rewritten, including the conditions of
+    /// rewrite rules, is looked up in the cache. Note that rewrite steps are
+    /// not traced for terms of which the normal form is in the cache.
+    pub fn set_cache_capacity(&mut self, capacity: usize) {
+        self.cache = NormalFormCache::new(capacity);
+    }
 
-        info!("ATerm pool: {}", tp.borrow());
-        InnermostRewriter {
-            apma,
-            tp: tp.clone(),
-            stack: InnermostStack::default(),
-            builder: SCCTBuilder::new(),
-        }
+    /// Returns the number of cache hits and misses since the capacity of the cache was set.
+    pub fn cache_statistics(&self) -> CacheStatistics {
+        self.cache.statistics()
     }
 
     /// Function to rewrite a term 't'. The elements of the automaton 'states'
@@ -75,17 +181,40 @@ impl InnermostRewriter {
     ///                       and places the result on the given index.
     ///     - Construct(arity, index, result):
     ///
+    /// Quantifiers are eliminated using the [Enumerator] when they are
+    /// rewritten, otherwise they are considered to be in normal form.
+    /// Operations on numbers and containers are evaluated by [Builtins] before
+    /// the rewrite rules are considered.
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn rewrite_aux(
         tp: &mut TermPool,
         stack: &mut InnermostStack,
         builder: &mut SCCTBuilder,
         stats: &mut RewritingStatistics,
+        tracer: &mut Tracer,
+        cache: &mut NormalFormCache,
+        guard: &mut Guard,
         automaton: &SetAutomaton<AnnouncementInnermost>,
+        enumerator: &Enumerator,
+        builtins: &Builtins,
+        sigma: &[(ATerm, ATerm)],
         input_term: DataExpression,
     ) -> DataExpression {
         debug_assert!(!input_term.is_default(), "Cannot rewrite the default term");
 
+        // The normal form depends on the substitution, so it cannot be cached.
+        if sigma.is_empty() {
+            if let Some(normal_form) = cache.get(&input_term) {
+                return normal_form;
+            }
+        }
+
+        if !guard.enter(&input_term) {
+            return input_term;
+        }
+
         stats.recursions += 1;
+        let (configs_length, terms_length) = (stack.configs.read().len(), stack.terms.read().len());
         {
             let mut write_terms = stack.terms.write();
             let mut write_configs = stack.configs.write();
@@ -100,6 +229,14 @@ impl InnermostRewriter {
         loop {
             trace!("{}", stack);
 
+            if guard.failed() {
+                // The result is discarded, so remove the remaining work of this invocation from the stack.
+                stack.configs.write().truncate(configs_length);
+                stack.terms.write().truncate(terms_length);
+                guard.leave();
+                return input_term;
+            }
+
             let mut write_configs = stack.configs.write();
             if let Some(config) = write_configs.pop() {
                 match config {
@@ -107,6 +244,54 @@ impl InnermostRewriter {
                         let mut write_terms = stack.terms.write();
                         let term = write_terms.pop().unwrap();
 
+                        if is_data_abstraction(&term) {
+                            // The instances of the abstraction do not contain the variables of the substitution.
+                            let t: ATerm = instantiate(tp, &term.protect(), sigma).into();
+                            let abstraction: DataAbstraction = t.into();
+                            drop(write_terms);
+                            drop(write_configs);
+
+                            let normal_form = enumerator
+                                .eliminate(tp, &abstraction.copy(), |tp, instance| {
+                                    InnermostRewriter::rewrite_aux(
+                                        tp,
+                                        stack,
+                                        builder,
+                                        stats,
+                                        tracer,
+                                        cache,
+                                        guard,
+                                        automaton,
+                                        enumerator,
+                                        builtins,
+                                        &[],
+                                        instance,
+                                    )
+                                })
+                                .unwrap_or_else(|| abstraction.into());
+
+                            let mut write_terms = stack.terms.write();
+                            let t = write_terms.protect(&normal_form);
+                            write_terms[result] = t.into();
+                            continue;
+                        }
+
+                        if is_data_machine_number(&term) {
+                            // Machine numbers are always in normal form.
+                            write_terms[result] = term;
+                            continue;
+                        }
+
+                        if is_data_variable(&term) {
+                            // The values of the substitution are in normal form.
+                            let value = match lookup(sigma, &term) {
+                                Some(value) => write_terms.protect(value),
+                                None => write_terms.protect(&term),
+                            };
+                            write_terms[result] = value.into();
+                            continue;
+                        }
+
                         let symbol = term.data_function_symbol();
                         let arguments = term.data_arguments();
 
@@ -135,6 +320,15 @@ impl InnermostRewriter {
 
                         let arguments = &write_terms[length - arity..];
 
+                        if let Some(value) = builtins.evaluate(tp, &symbol, arguments) {
+                            trace!("evaluate {}({:?}) => {}", symbol, arguments, value);
+                            write_terms.drain(length - arity..);
+                            let t = write_terms.protect(&value);
+                            write_terms[index] = t.into();
+                            stats.rewrite_steps += 1;
+                            continue;
+                        }
+
                         let term: DataExpression = if arguments.is_empty() {
                             symbol.protect().into()
                         } else {
@@ -146,7 +340,9 @@ impl InnermostRewriter {
                         drop(write_terms);
                         drop(write_configs);
 
-                        match InnermostRewriter::find_match(tp, stack, builder, stats, automaton, &term) {
+                        match InnermostRewriter::find_match(
+                            tp, stack, builder, stats, tracer, cache, guard, automaton, enumerator, builtins, &term,
+                        ) {
                             Some((announcement, annotation)) => {
                                 trace!(
                                     "rewrite {} => {} using rule {}",
@@ -154,6 +350,9 @@ impl InnermostRewriter {
                                     annotation.rhs_stack.evaluate(tp, &term),
                                     announcement.rule
                                 );
+                                tracer.rewritten(&announcement.rule, &term, None, || {
+                                    annotation.rhs_stack.evaluate(tp, &term)
+                                });
 
                                 // Reacquire the write access and add the matching RHSStack.
                                 let mut write_terms = stack.terms.write();
@@ -165,7 +364,7 @@ impl InnermostRewriter {
                                     &term,
                                     index,
                                 );
-                                stats.rewrite_steps += 1;
+                                stats.applied(&announcement.rule);
                             }
                             None => {
                                 // Add the term on the stack.
@@ -178,10 +377,18 @@ impl InnermostRewriter {
                     Config::Return() => {
                         let mut write_terms = stack.terms.write();
 
-                        return write_terms
+                        let result = write_terms
                             .pop()
                             .expect("The result should be the last element on the stack")
                             .protect();
+                        drop(write_terms);
+                        drop(write_configs);
+
+                        guard.leave();
+                        if !guard.failed() && sigma.is_empty() {
+                            cache.insert(input_term, result.clone());
+                        }
+                        return result;
                     }
                 }
 
@@ -205,12 +412,18 @@ impl InnermostRewriter {
     }
 
     /// Use the APMA to find a match for the given term.
+    #[allow(clippy::too_many_arguments)]
     fn find_match<'a>(
         tp: &mut TermPool,
         stack: &mut InnermostStack,
         builder: &mut SCCTBuilder,
         stats: &mut RewritingStatistics,
+        tracer: &mut Tracer,
+        cache: &mut NormalFormCache,
+        guard: &mut Guard,
         automaton: &'a SetAutomaton<AnnouncementInnermost>,
+        enumerator: &Enumerator,
+        builtins: &Builtins,
         t: &ATermRef<'_>,
     ) -> Option<(&'a MatchAnnouncement, &'a AnnouncementInnermost)> {
         // Start at the initial state
@@ -221,13 +434,20 @@ impl InnermostRewriter {
             // Get the symbol at the position state.label
             stats.symbol_comparisons += 1;
             let pos: DataExpressionRef<'_> = t.get_position(&state.label).into();
+            if is_data_abstraction(&pos) || is_data_machine_number(&pos) {
+                // Quantifiers that could not be eliminated and machine numbers do not match any pattern.
+                return None;
+            }
             let symbol = pos.data_function_symbol();
 
             // Get the transition for the label and check if there is a pattern match
             if let Some(transition) = automaton.transitions.get(&(state_index, symbol.operation_id())) {
                 for (announcement, annotation) in &transition.announcements {
                     if check_equivalence_classes(t, &annotation.equivalence_classes)
-                        && InnermostRewriter::check_conditions(tp, stack, builder, stats, automaton, annotation, t)
+                        && InnermostRewriter::check_conditions(
+                            tp, stack, builder, stats, tracer, cache, guard, automaton, enumerator, builtins,
+                            annotation, t,
+                        )
                     {
                         // We found a matching pattern
                         return Some((announcement, annotation));
@@ -249,27 +469,64 @@ impl InnermostRewriter {
     }
 
     /// Checks whether the condition holds for given match announcement.
+    #[allow(clippy::too_many_arguments)]
     fn check_conditions(
         tp: &mut TermPool,
         stack: &mut InnermostStack,
         builder: &mut SCCTBuilder,
         stats: &mut RewritingStatistics,
+        tracer: &mut Tracer,
+        cache: &mut NormalFormCache,
+        guard: &mut Guard,
         automaton: &SetAutomaton<AnnouncementInnermost>,
+        enumerator: &Enumerator,
+        builtins: &Builtins,
         announcement: &AnnouncementInnermost,
         t: &ATermRef<'_>,
     ) -> bool {
         for c in &announcement.conditions {
             let rhs: DataExpression = c.semi_compressed_rhs.evaluate_with(builder, t, tp).into();
             let lhs: DataExpression = c.semi_compressed_lhs.evaluate_with(builder, t, tp).into();
+            if !guard.enter_condition(&lhs, &rhs) {
+                return false;
+            }
 
-            let rhs_normal = InnermostRewriter::rewrite_aux(tp, stack, builder, stats, automaton, rhs);
+            let rhs_normal = InnermostRewriter::rewrite_aux(
+                tp,
+                stack,
+                builder,
+                stats,
+                tracer,
+                cache,
+                guard,
+                automaton,
+                enumerator,
+                builtins,
+                &[],
+                rhs,
+            );
             let lhs_normal = if &lhs == tp.true_term() {
                 // TODO: Store the conditions in a better way. REC now uses a list of equalities while mCRL2 specifications have a simple condition.
                 lhs
             } else {
-                InnermostRewriter::rewrite_aux(tp, stack, builder, stats, automaton, lhs)
+                InnermostRewriter::rewrite_aux(
+                    tp,
+                    stack,
+                    builder,
+                    stats,
+                    tracer,
+                    cache,
+                    guard,
+                    automaton,
+                    enumerator,
+                    builtins,
+                    &[],
+                    lhs,
+                )
             };
 
+            guard.leave_condition();
+
             if lhs_normal != rhs_normal && c.equality || lhs_normal == rhs_normal && !c.equality {
                 return false;
             }
@@ -281,10 +538,17 @@ impl InnermostRewriter {
 
 /// Innermost Adaptive Pattern Matching Automaton (APMA) rewrite engine.
 pub struct InnermostRewriter {
-    tp: Rc<RefCell<TermPool>>,
-    apma: SetAutomaton<AnnouncementInnermost>,
+    tp: RefCell<TermPool>,
+    apma: Arc<SetAutomaton<AnnouncementInnermost>>,
+    enumerator: Enumerator,
+    builtins: Builtins,
     stack: InnermostStack,
     builder: SCCTBuilder,
+    trace: Option<TraceCallback>,
+    cache: NormalFormCache,
+    limits: RewriteLimits,
+    statistics: RewritingStatistics,
+    single_step: SingleStep,
 }
 
 pub(crate) struct AnnouncementInnermost {
@@ -299,7 +563,7 @@ pub(crate) struct AnnouncementInnermost {
 }
 
 impl AnnouncementInnermost {
-    fn new(rule: &Rule) -> AnnouncementInnermost {
+    pub(crate) fn new(rule: &Rule) -> AnnouncementInnermost {
         AnnouncementInnermost {
             conditions: extend_conditions(rule),
             equivalence_classes: derive_equivalence_classes(rule),
@@ -310,9 +574,6 @@ impl AnnouncementInnermost {
 
 #[cfg(test)]
 mod tests {
-    use std::cell::RefCell;
-    use std::rc::Rc;
-
     use ahash::AHashSet;
     use mcrl2::aterm::random_term;
     use mcrl2::aterm::TermPool;
@@ -329,23 +590,23 @@ mod tests {
 
     #[test]
     fn test_innermost_simple() {
-        let tp = Rc::new(RefCell::new(TermPool::new()));
+        let mut tp = TermPool::new();
 
-        let spec = RewriteSpecification { rewrite_rules: vec![] };
-        let mut inner = InnermostRewriter::new(tp.clone(), &spec);
+        let spec = RewriteSpecification::default();
+        let mut inner = InnermostRewriter::new(&spec);
 
         let seed: u64 = rand::rng().random();
         println!("seed: {}", seed);
         let mut rng = StdRng::seed_from_u64(seed);
 
         let term = random_term(
-            &mut tp.borrow_mut(),
+            &mut tp,
             &mut rng,
             &[("f".to_string(), 2)],
             &["a".to_string(), "b".to_string()],
             5,
         );
-        let term = to_untyped_data_expression(&mut tp.borrow_mut(), &term, &AHashSet::new());
+        let term = to_untyped_data_expression(&mut tp, &term, &AHashSet::new());
 
         assert_eq!(
             inner.rewrite(term.clone().into()),
@@ -0,0 +1,438 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+use itertools::Itertools;
+use log::info;
+use log::trace;
+use mcrl2::aterm::ATerm;
+use mcrl2::aterm::TermPool;
+use mcrl2::data::is_data_abstraction;
+use mcrl2::data::is_data_application;
+use mcrl2::data::is_data_function_symbol;
+use mcrl2::data::is_data_variable;
+use mcrl2::data::DataAbstraction;
+use mcrl2::data::DataExpression;
+use mcrl2::data::DataExpressionRef;
+use mcrl2::data::DataFunctionSymbol;
+
+use crate::lazy_rewriter::construct;
+use crate::limits::Guard;
+use crate::single_step::apply_first;
+use crate::single_step::SingleStep;
+use crate::utilities::instantiate;
+use crate::utilities::lookup;
+use crate::utilities::match_term;
+use crate::utilities::to_aterm_substitution;
+use crate::Builtins;
+use crate::Enumerator;
+use crate::RewriteEngine;
+use crate::RewriteError;
+use crate::RewriteLimits;
+use crate::RewriteSpecification;
+use crate::RewritingStatistics;
+use crate::Rule;
+use crate::Substitution;
+use crate::TraceCallback;
+use crate::Tracer;
+
+/// A native implementation of the jitty rewriter of mCRL2.
+///
+/// For every function symbol and arity a strategy is computed from the rules
+/// that have it as head symbol. A strategy interleaves rewriting arguments to
+/// normal form with trying rules, such that a rule is tried as soon as all the
+/// arguments that it needs are in normal form. The argument that is needed by
+/// most of the remaining rules is rewritten first. Arguments that are not
+/// needed by any rule are rewritten after all rules have been tried.
+///
+/// In contrast to the jitty rewriter of mCRL2 this rewriter can also be used
+/// for specifications that are not given as a data specification, such as the
+/// REC specifications.
+pub struct JittyRewriter {
+    tp: Rc<RefCell<TermPool>>,
+    strategies: HashMap<(usize, usize), JittyStrategy>,
+    enumerator: Enumerator,
+    builtins: Builtins,
+    trace: Option<TraceCallback>,
+    limits: RewriteLimits,
+    statistics: RewritingStatistics,
+    single_step: SingleStep,
+}
+
+/// The rules for a single function symbol with a fixed arity, with the arguments of their left hand side.
+type JittyRules = Vec<(Rule, Vec<DataExpression>)>;
+
+/// The strategy for a single function symbol with a fixed arity.
+struct JittyStrategy {
+    rules: JittyRules,
+
+    steps: Vec<JittyStep>,
+}
+
+/// A single step of a strategy.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum JittyStep {
+    /// Rewrites the argument with the given index to normal form.
+    Rewrite(usize),
+
+    /// Tries the rule with the given index.
+    Try(usize),
+}
+
+impl RewriteEngine for JittyRewriter {
+    fn try_rewrite(&mut self, term: DataExpression) -> Result<DataExpression, RewriteError> {
+        self.normalise(term, &[])
+    }
+
+    fn try_rewrite_with_substitution(
+        &mut self,
+        term: &DataExpression,
+        substitution: &Substitution,
+    ) -> Result<DataExpression, RewriteError> {
+        self.normalise(term.clone(), &to_aterm_substitution(substitution))
+    }
+
+    fn set_trace(&mut self, callback: Option<TraceCallback>) {
+        self.trace = callback;
+    }
+
+    fn set_limits(&mut self, limits: RewriteLimits) {
+        self.limits = limits;
+    }
+
+    fn statistics(&self) -> &RewritingStatistics {
+        &self.statistics
+    }
+
+    fn rewrite_once(&mut self, term: &DataExpression) -> Option<DataExpression> {
+        let redexes = self.single_step.redexes(term);
+        apply_first(self, |rewriter| &rewriter.tp, term, redexes)
+    }
+}
+
+impl JittyRewriter {
+    pub fn new(tp: Rc<RefCell<TermPool>>, spec: &RewriteSpecification) -> JittyRewriter {
+        let mut rules: HashMap<(usize, usize), JittyRules> = HashMap::new();
+
+        for rule in &spec.rewrite_rules {
+            let lhs = &rule.lhs;
+            if !(is_data_function_symbol(lhs) || is_data_application(lhs) && is_data_function_symbol(&lhs.arg(0))) {
+                // Higher order left hand sides are not supported.
+                continue;
+            }
+
+            let arguments: Vec<DataExpression> = lhs.data_arguments().map(|arg| arg.protect().into()).collect();
+            rules
+                .entry((lhs.data_function_symbol().operation_id(), arguments.len()))
+                .or_default()
+                .push((rule.clone(), arguments));
+        }
+
+        let strategies = rules
+            .into_iter()
+            .map(|(key, rules)| {
+                let steps = create_strategy(&rules);
+                trace!(
+                    "strategy for {}: {}",
+                    rules[0].0.lhs.data_function_symbol(),
+                    steps.iter().format(", ")
+                );
+                (key, JittyStrategy { rules, steps })
+            })
+            .collect();
+
+        let enumerator = Enumerator::new(&mut tp.borrow_mut(), spec);
+        let builtins = Builtins::new(spec);
+
+        JittyRewriter {
+            tp,
+            strategies,
+            enumerator,
+            builtins,
+            trace: None,
+            limits: RewriteLimits::default(),
+            statistics: RewritingStatistics::default(),
+            single_step: SingleStep::new(spec),
+        }
+    }
+
+    /// Rewrites the given term to normal form, where the variables of the substitution are replaced by their values.
+    fn normalise(&mut self, term: DataExpression, sigma: &[(ATerm, ATerm)]) -> Result<DataExpression, RewriteError> {
+        let mut stats = RewritingStatistics::default();
+
+        trace!("input: {}", term);
+        let mut tracer = Tracer::new(self.trace.take());
+        let mut guard = Guard::new(self.limits);
+        let result = self.rewrite_aux(
+            &mut self.tp.clone().borrow_mut(),
+            &mut stats,
+            &mut tracer,
+            &mut guard,
+            sigma,
+            term,
+        );
+        self.trace = tracer.into_callback();
+        info!(
+            "{} rewrites, {} single steps and {} symbol comparisons",
+            stats.recursions, stats.rewrite_steps, stats.symbol_comparisons
+        );
+        self.statistics.merge(stats);
+        guard.finish(result)
+    }
+
+    /// Rewrites the given term to normal form, stops immediately when one of the limits is exceeded.
+    fn rewrite_aux(
+        &self,
+        tp: &mut TermPool,
+        stats: &mut RewritingStatistics,
+        tracer: &mut Tracer,
+        guard: &mut Guard,
+        sigma: &[(ATerm, ATerm)],
+        term: DataExpression,
+    ) -> DataExpression {
+        if !guard.enter(&term) {
+            return term;
+        }
+
+        let result = self.rewrite_term(tp, stats, tracer, guard, sigma, term);
+        guard.leave();
+        result
+    }
+
+    /// Rewrites the given term to normal form, see [Self::rewrite_aux].
+    fn rewrite_term(
+        &self,
+        tp: &mut TermPool,
+        stats: &mut RewritingStatistics,
+        tracer: &mut Tracer,
+        guard: &mut Guard,
+        sigma: &[(ATerm, ATerm)],
+        term: DataExpression,
+    ) -> DataExpression {
+        stats.recursions += 1;
+
+        let mut term = term;
+        'rewrite: loop {
+            if guard.failed() {
+                return term;
+            }
+
+            if is_data_abstraction(&term) {
+                let t: ATerm = instantiate(tp, &term, sigma).into();
+                let abstraction: DataAbstraction = t.into();
+                let position = tracer.detach();
+                let result = self
+                    .enumerator
+                    .eliminate(tp, &abstraction.copy(), |tp, instance| {
+                        self.rewrite_aux(tp, stats, tracer, guard, sigma, instance)
+                    })
+                    .unwrap_or_else(|| abstraction.into());
+                tracer.attach(position);
+                return result;
+            }
+
+            if !is_data_function_symbol(&term) && !is_data_application(&term) {
+                // Variables and machine numbers are in normal form, and so are the values of the substitution.
+                return match lookup(sigma, &term) {
+                    Some(value) => value.clone().into(),
+                    None => term,
+                };
+            }
+
+            let symbol: DataFunctionSymbol = term.data_function_symbol().protect();
+            let mut arguments: Vec<DataExpression> = term.data_arguments().map(|arg| arg.protect().into()).collect();
+            let mut normalised = vec![false; arguments.len()];
+
+            if let Some(strategy) = self.strategies.get(&(symbol.operation_id(), arguments.len())) {
+                for step in &strategy.steps {
+                    match *step {
+                        JittyStep::Rewrite(index) => {
+                            tracer.enter(index + 2);
+                            arguments[index] =
+                                self.rewrite_aux(tp, stats, tracer, guard, sigma, arguments[index].clone());
+                            tracer.leave();
+                            normalised[index] = true;
+                        }
+                        JittyStep::Try(index) => {
+                            stats.symbol_comparisons += 1;
+                            let (rule, patterns) = &strategy.rules[index];
+
+                            let mut substitution = Vec::new();
+                            if patterns
+                                .iter()
+                                .zip(&arguments)
+                                .all(|(pattern, argument)| match_term(pattern, argument, &mut substitution))
+                                && self.check_conditions(tp, stats, tracer, guard, sigma, rule, &substitution)
+                            {
+                                trace!("rewrite {} using rule {}", term, rule);
+                                stats.applied(rule);
+                                let rhs = instantiate(tp, &rule.rhs, &substitution);
+                                if tracer.is_enabled() {
+                                    let redex = construct(tp, &symbol, &arguments);
+                                    tracer.rewritten(rule, &redex, Some(&[]), || rhs.clone());
+                                }
+                                term = rhs;
+                                continue 'rewrite;
+                            }
+                        }
+                    }
+                }
+            }
+
+            // No rule matches, so the term is in normal form when all arguments are.
+            for (index, (argument, normalised)) in arguments.iter_mut().zip(&normalised).enumerate() {
+                if !normalised {
+                    tracer.enter(index + 2);
+                    *argument = self.rewrite_aux(tp, stats, tracer, guard, sigma, argument.clone());
+                    tracer.leave();
+                }
+            }
+
+            let argument_refs: Vec<DataExpressionRef<'_>> = arguments.iter().map(|argument| argument.copy()).collect();
+            if let Some(value) = self.builtins.evaluate(tp, &symbol.copy(), &argument_refs) {
+                return value;
+            }
+
+            return construct(tp, &symbol, &arguments);
+        }
+    }
+
+    /// Checks whether the conditions of the rule hold under the given substitution.
+    #[allow(clippy::too_many_arguments)]
+    fn check_conditions(
+        &self,
+        tp: &mut TermPool,
+        stats: &mut RewritingStatistics,
+        tracer: &mut Tracer,
+        guard: &mut Guard,
+        sigma: &[(ATerm, ATerm)],
+        rule: &Rule,
+        substitution: &[(ATerm, ATerm)],
+    ) -> bool {
+        // The conditions are not subterms of the term that is rewritten.
+        let position = tracer.detach();
+        let result = rule.conditions.iter().all(|condition| {
+            let lhs = instantiate(tp, &condition.lhs, substitution);
+            let rhs = instantiate(tp, &condition.rhs, substitution);
+            if !guard.enter_condition(&lhs, &rhs) {
+                return false;
+            }
+
+            let lhs = self.rewrite_aux(tp, stats, tracer, guard, sigma, lhs);
+            let rhs = self.rewrite_aux(tp, stats, tracer, guard, sigma, rhs);
+            guard.leave_condition();
+            (lhs == rhs) == condition.equality
+        });
+        tracer.attach(position);
+        result
+    }
+}
+
+/// Computes the strategy for the given rules, which all have the same head symbol and arity.
+///
+/// An argument is needed by a rule when the left hand side has no variable at
+/// that argument, or when the variable occurs more than once in the left hand
+/// side.
+fn create_strategy(rules: &[(Rule, Vec<DataExpression>)]) -> Vec<JittyStep> {
+    let needed: Vec<Vec<usize>> = rules
+        .iter()
+        .map(|(rule, arguments)| {
+            arguments
+                .iter()
+                .enumerate()
+                .filter(|(_, argument)| {
+                    !is_data_variable(argument) || rule.lhs.iter().filter(|t| *t == *argument.copy()).count() > 1
+                })
+                .map(|(index, _)| index)
+                .collect()
+        })
+        .collect();
+
+    let arity = rules.first().map_or(0, |(_, arguments)| arguments.len());
+    let mut rewritten = vec![false; arity];
+    let mut remaining: Vec<usize> = (0..rules.len()).collect();
+    let mut steps = Vec::new();
+
+    loop {
+        // Try all the rules for which the needed arguments are in normal form, in the order of the specification.
+        remaining.retain(|&rule| {
+            if needed[rule].iter().all(|&index| rewritten[index]) {
+                steps.push(JittyStep::Try(rule));
+                false
+            } else {
+                true
+            }
+        });
+
+        if remaining.is_empty() {
+            break;
+        }
+
+        // Rewrite the argument that is needed by most of the remaining rules, the first one when equal.
+        let mut count = vec![0; arity];
+        for &rule in &remaining {
+            for &index in &needed[rule] {
+                if !rewritten[index] {
+                    count[index] += 1;
+                }
+            }
+        }
+
+        let (index, _) = count
+            .iter()
+            .enumerate()
+            .rev()
+            .max_by_key(|(_, count)| **count)
+            .expect("A remaining rule needs an argument that is not rewritten");
+        rewritten[index] = true;
+        steps.push(JittyStep::Rewrite(index));
+    }
+
+    steps
+}
+
+impl fmt::Display for JittyStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JittyStep::Rewrite(index) => write!(f, "rewrite({})", index),
+            JittyStep::Try(index) => write!(f, "try({})", index),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_utility::create_rewrite_rule;
+
+    #[test]
+    fn test_create_strategy() {
+        let mut tp = TermPool::new();
+
+        let rules: JittyRules = [
+            create_rewrite_rule(&mut tp, "f(a, x, y)", "x", &["x", "y"]).unwrap(),
+            create_rewrite_rule(&mut tp, "f(x, b, y)", "y", &["x", "y"]).unwrap(),
+            create_rewrite_rule(&mut tp, "f(b, b, y)", "y", &["y"]).unwrap(),
+        ]
+        .into_iter()
+        .map(|rule| {
+            let arguments = rule.lhs.data_arguments().map(|arg| arg.protect().into()).collect();
+            (rule, arguments)
+        })
+        .collect();
+
+        // The first argument is needed by two rules, and the third argument is never needed.
+        assert_eq!(
+            create_strategy(&rules),
+            vec![
+                JittyStep::Rewrite(0),
+                JittyStep::Try(0),
+                JittyStep::Rewrite(1),
+                JittyStep::Try(1),
+                JittyStep::Try(2)
+            ]
+        );
+    }
+}
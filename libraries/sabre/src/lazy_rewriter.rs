@@ -0,0 +1,337 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use log::info;
+use log::trace;
+use mcrl2::aterm::ATerm;
+use mcrl2::aterm::TermPool;
+use mcrl2::data::is_data_abstraction;
+use mcrl2::data::is_data_application;
+use mcrl2::data::is_data_function_symbol;
+use mcrl2::data::is_data_variable;
+use mcrl2::data::DataAbstraction;
+use mcrl2::data::DataApplication;
+use mcrl2::data::DataExpression;
+use mcrl2::data::DataExpressionRef;
+use mcrl2::data::DataFunctionSymbol;
+
+use crate::limits::Guard;
+use crate::single_step::apply_first;
+use crate::single_step::SingleStep;
+use crate::utilities::instantiate;
+use crate::utilities::lookup;
+use crate::utilities::match_term;
+use crate::utilities::to_aterm_substitution;
+use crate::Builtins;
+use crate::Enumerator;
+use crate::RewriteEngine;
+use crate::RewriteError;
+use crate::RewriteLimits;
+use crate::RewriteSpecification;
+use crate::RewritingStatistics;
+use crate::Rule;
+use crate::Substitution;
+use crate::TraceCallback;
+use crate::Tracer;
+
+/// A rewriter that only rewrites the arguments of a function symbol that are
+/// needed to decide whether a rewrite rule matches, similar to the strategy
+/// annotations of the jitty rewriter of mCRL2.
+///
+/// For every rule the arguments that must be in normal form are the ones where
+/// the left hand side is not a variable, or where the variable occurs more
+/// than once. Arguments are rewritten once, in the order that the rules are
+/// tried, and the remaining arguments are only rewritten when no rule matches.
+///
+/// The implementation is recursive, and it is meant as an alternative
+/// evaluation order for specifications where rewriting all arguments first
+/// does not terminate or is too expensive.
+pub struct LazyRewriter {
+    tp: Rc<RefCell<TermPool>>,
+    rules: HashMap<usize, Vec<LazyRule>>,
+    enumerator: Enumerator,
+    builtins: Builtins,
+    trace: Option<TraceCallback>,
+    limits: RewriteLimits,
+    statistics: RewritingStatistics,
+    single_step: SingleStep,
+}
+
+/// A rewrite rule with its strategy annotation.
+struct LazyRule {
+    rule: Rule,
+
+    /// The arguments of the left hand side.
+    arguments: Vec<DataExpression>,
+
+    /// The indices of the arguments that must be rewritten before matching.
+    needed: Vec<usize>,
+}
+
+impl RewriteEngine for LazyRewriter {
+    fn try_rewrite(&mut self, term: DataExpression) -> Result<DataExpression, RewriteError> {
+        self.normalise(term, &[])
+    }
+
+    fn try_rewrite_with_substitution(
+        &mut self,
+        term: &DataExpression,
+        substitution: &Substitution,
+    ) -> Result<DataExpression, RewriteError> {
+        self.normalise(term.clone(), &to_aterm_substitution(substitution))
+    }
+
+    fn set_trace(&mut self, callback: Option<TraceCallback>) {
+        self.trace = callback;
+    }
+
+    fn set_limits(&mut self, limits: RewriteLimits) {
+        self.limits = limits;
+    }
+
+    fn statistics(&self) -> &RewritingStatistics {
+        &self.statistics
+    }
+
+    fn rewrite_once(&mut self, term: &DataExpression) -> Option<DataExpression> {
+        let redexes = self.single_step.redexes(term);
+        apply_first(self, |rewriter| &rewriter.tp, term, redexes)
+    }
+}
+
+impl LazyRewriter {
+    pub fn new(tp: Rc<RefCell<TermPool>>, spec: &RewriteSpecification) -> LazyRewriter {
+        let mut rules: HashMap<usize, Vec<LazyRule>> = HashMap::new();
+
+        for rule in &spec.rewrite_rules {
+            let lhs = &rule.lhs;
+            if !(is_data_function_symbol(lhs) || is_data_application(lhs) && is_data_function_symbol(&lhs.arg(0))) {
+                // Higher order left hand sides are not supported.
+                continue;
+            }
+
+            let arguments: Vec<DataExpression> = lhs.data_arguments().map(|arg| arg.protect().into()).collect();
+            let needed = arguments
+                .iter()
+                .enumerate()
+                .filter(|(_, argument)| {
+                    !is_data_variable(argument) || lhs.iter().filter(|t| *t == *argument.copy()).count() > 1
+                })
+                .map(|(index, _)| index)
+                .collect();
+
+            rules
+                .entry(lhs.data_function_symbol().operation_id())
+                .or_default()
+                .push(LazyRule {
+                    rule: rule.clone(),
+                    arguments,
+                    needed,
+                });
+        }
+
+        let enumerator = Enumerator::new(&mut tp.borrow_mut(), spec);
+        let builtins = Builtins::new(spec);
+
+        LazyRewriter {
+            tp,
+            rules,
+            enumerator,
+            builtins,
+            trace: None,
+            limits: RewriteLimits::default(),
+            statistics: RewritingStatistics::default(),
+            single_step: SingleStep::new(spec),
+        }
+    }
+
+    /// Rewrites the given term to normal form, where the variables of the substitution are replaced by their values.
+    fn normalise(&mut self, term: DataExpression, sigma: &[(ATerm, ATerm)]) -> Result<DataExpression, RewriteError> {
+        let mut stats = RewritingStatistics::default();
+
+        trace!("input: {}", term);
+        let mut tracer = Tracer::new(self.trace.take());
+        let mut guard = Guard::new(self.limits);
+        let result = self.rewrite_aux(
+            &mut self.tp.clone().borrow_mut(),
+            &mut stats,
+            &mut tracer,
+            &mut guard,
+            sigma,
+            term,
+        );
+        self.trace = tracer.into_callback();
+        info!(
+            "{} rewrites, {} single steps and {} symbol comparisons",
+            stats.recursions, stats.rewrite_steps, stats.symbol_comparisons
+        );
+        self.statistics.merge(stats);
+        guard.finish(result)
+    }
+
+    /// Rewrites the given term to normal form, stops immediately when one of the limits is exceeded.
+    fn rewrite_aux(
+        &self,
+        tp: &mut TermPool,
+        stats: &mut RewritingStatistics,
+        tracer: &mut Tracer,
+        guard: &mut Guard,
+        sigma: &[(ATerm, ATerm)],
+        term: DataExpression,
+    ) -> DataExpression {
+        if !guard.enter(&term) {
+            return term;
+        }
+
+        let result = self.rewrite_term(tp, stats, tracer, guard, sigma, term);
+        guard.leave();
+        result
+    }
+
+    /// Rewrites the given term to normal form, see [Self::rewrite_aux].
+    fn rewrite_term(
+        &self,
+        tp: &mut TermPool,
+        stats: &mut RewritingStatistics,
+        tracer: &mut Tracer,
+        guard: &mut Guard,
+        sigma: &[(ATerm, ATerm)],
+        term: DataExpression,
+    ) -> DataExpression {
+        stats.recursions += 1;
+
+        let mut term = term;
+        loop {
+            if guard.failed() {
+                return term;
+            }
+
+            if is_data_abstraction(&term) {
+                let t: ATerm = instantiate(tp, &term, sigma).into();
+                let abstraction: DataAbstraction = t.into();
+                let position = tracer.detach();
+                let result = self
+                    .enumerator
+                    .eliminate(tp, &abstraction.copy(), |tp, instance| {
+                        self.rewrite_aux(tp, stats, tracer, guard, sigma, instance)
+                    })
+                    .unwrap_or_else(|| abstraction.into());
+                tracer.attach(position);
+                return result;
+            }
+
+            if !is_data_function_symbol(&term) && !is_data_application(&term) {
+                // Variables and machine numbers are in normal form, and so are the values of the substitution.
+                return match lookup(sigma, &term) {
+                    Some(value) => value.clone().into(),
+                    None => term,
+                };
+            }
+
+            let symbol: DataFunctionSymbol = term.data_function_symbol().protect();
+            let mut arguments: Vec<DataExpression> = term.data_arguments().map(|arg| arg.protect().into()).collect();
+            let mut normalised = vec![false; arguments.len()];
+
+            let mut result = None;
+            for lazy_rule in self.rules.get(&symbol.operation_id()).into_iter().flatten() {
+                stats.symbol_comparisons += 1;
+                if lazy_rule.arguments.len() != arguments.len() {
+                    continue;
+                }
+
+                for &index in &lazy_rule.needed {
+                    if !normalised[index] {
+                        tracer.enter(index + 2);
+                        arguments[index] = self.rewrite_aux(tp, stats, tracer, guard, sigma, arguments[index].clone());
+                        tracer.leave();
+                        normalised[index] = true;
+                    }
+                }
+
+                let mut substitution = Vec::new();
+                if lazy_rule
+                    .arguments
+                    .iter()
+                    .zip(&arguments)
+                    .all(|(pattern, argument)| match_term(pattern, argument, &mut substitution))
+                    && self.check_conditions(tp, stats, tracer, guard, sigma, &lazy_rule.rule, &substitution)
+                {
+                    trace!("rewrite {} using rule {}", term, lazy_rule.rule);
+                    stats.applied(&lazy_rule.rule);
+                    let rhs = instantiate(tp, &lazy_rule.rule.rhs, &substitution);
+                    if tracer.is_enabled() {
+                        let redex = construct(tp, &symbol, &arguments);
+                        tracer.rewritten(&lazy_rule.rule, &redex, Some(&[]), || rhs.clone());
+                    }
+                    result = Some(rhs);
+                    break;
+                }
+            }
+
+            if let Some(rhs) = result {
+                term = rhs;
+                continue;
+            }
+
+            // No rule matches, so the term is in normal form when all arguments are.
+            for (index, (argument, normalised)) in arguments.iter_mut().zip(&normalised).enumerate() {
+                if !normalised {
+                    tracer.enter(index + 2);
+                    *argument = self.rewrite_aux(tp, stats, tracer, guard, sigma, argument.clone());
+                    tracer.leave();
+                }
+            }
+
+            let argument_refs: Vec<DataExpressionRef<'_>> = arguments.iter().map(|argument| argument.copy()).collect();
+            if let Some(value) = self.builtins.evaluate(tp, &symbol.copy(), &argument_refs) {
+                return value;
+            }
+
+            return construct(tp, &symbol, &arguments);
+        }
+    }
+
+    /// Checks whether the conditions of the rule hold under the given substitution.
+    #[allow(clippy::too_many_arguments)]
+    fn check_conditions(
+        &self,
+        tp: &mut TermPool,
+        stats: &mut RewritingStatistics,
+        tracer: &mut Tracer,
+        guard: &mut Guard,
+        sigma: &[(ATerm, ATerm)],
+        rule: &Rule,
+        substitution: &[(ATerm, ATerm)],
+    ) -> bool {
+        // The conditions are not subterms of the term that is rewritten.
+        let position = tracer.detach();
+        let result = rule.conditions.iter().all(|condition| {
+            let lhs = instantiate(tp, &condition.lhs, substitution);
+            let rhs = instantiate(tp, &condition.rhs, substitution);
+            if !guard.enter_condition(&lhs, &rhs) {
+                return false;
+            }
+
+            let lhs = self.rewrite_aux(tp, stats, tracer, guard, sigma, lhs);
+            let rhs = self.rewrite_aux(tp, stats, tracer, guard, sigma, rhs);
+            guard.leave_condition();
+            (lhs == rhs) == condition.equality
+        });
+        tracer.attach(position);
+        result
+    }
+}
+
+/// Returns the application of the symbol to the given arguments, or the symbol itself when there are no arguments.
+pub(crate) fn construct(
+    tp: &mut TermPool,
+    symbol: &DataFunctionSymbol,
+    arguments: &[DataExpression],
+) -> DataExpression {
+    if arguments.is_empty() {
+        symbol.clone().into()
+    } else {
+        DataApplication::new(tp, symbol, arguments).into()
+    }
+}
@@ -6,16 +6,49 @@
 
 //#![forbid(unsafe_code)]
 
+pub mod arithmetic;
+pub mod builtins;
+pub mod completeness;
+pub mod containers;
+pub mod critical_pairs;
+pub mod enumerator;
 pub mod innermost_rewriter;
+pub mod jitty_rewriter;
+pub mod lazy_rewriter;
+pub mod limits;
+pub mod matcher;
 pub mod matching;
+pub mod normal_form_cache;
+pub mod reducer;
 pub mod rewrite_specification;
 pub mod sabre_rewriter;
 pub mod set_automaton;
+mod single_step;
+pub mod strategy;
+pub mod termination;
+pub mod trace;
 pub mod utilities;
+pub mod validation;
 
 #[cfg(test)]
 pub mod test_utility;
 
+pub use arithmetic::*;
+pub use builtins::*;
+pub use completeness::*;
+pub use containers::*;
+pub use critical_pairs::*;
+pub use enumerator::*;
 pub use innermost_rewriter::*;
+pub use jitty_rewriter::*;
+pub use lazy_rewriter::*;
+pub use limits::*;
+pub use matcher::*;
+pub use normal_form_cache::*;
+pub use reducer::*;
 pub use rewrite_specification::*;
 pub use sabre_rewriter::*;
+pub use strategy::*;
+pub use termination::*;
+pub use trace::*;
+pub use validation::*;
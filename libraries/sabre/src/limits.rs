@@ -0,0 +1,109 @@
+use mcrl2::data::DataExpression;
+use thiserror::Error;
+
+/// The limits that are imposed on a rewriter to detect specifications for
+/// which rewriting does not terminate, see [crate::RewriteEngine::set_limits].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct RewriteLimits {
+    /// The maximum number of nested invocations of the rewriter, for example
+    /// to rewrite the conditions of rules. Unlimited when it is None.
+    pub max_depth: Option<usize>,
+
+    /// Detects when rewriting a condition requires rewriting the same condition again.
+    pub detect_cycles: bool,
+}
+
+#[derive(Error, Debug)]
+pub enum RewriteError {
+    #[error("Exceeded the recursion depth of {0} while rewriting {1}")]
+    DepthExceeded(usize, DataExpression),
+
+    #[error("Rewriting the condition {0} == {1} requires rewriting the same condition")]
+    ConditionCycle(DataExpression, DataExpression),
+}
+
+/// Keeps track of the nesting depth and the conditions that are being
+/// rewritten, and stores the error when one of the limits is exceeded. After
+/// an error the rewriter stops as soon as possible, and the result of the
+/// rewriter is discarded.
+#[derive(Default)]
+pub(crate) struct Guard {
+    limits: RewriteLimits,
+    depth: usize,
+
+    /// The conditions that are currently being rewritten.
+    conditions: Vec<(DataExpression, DataExpression)>,
+
+    error: Option<RewriteError>,
+}
+
+impl Guard {
+    pub fn new(limits: RewriteLimits) -> Guard {
+        Guard {
+            limits,
+            ..Default::default()
+        }
+    }
+
+    /// Returns true iff one of the limits has been exceeded.
+    pub fn failed(&self) -> bool {
+        self.error.is_some()
+    }
+
+    /// Indicates that the given term is rewritten by a nested invocation of the rewriter, returns false
+    /// when the rewriter must stop, in which case [Guard::leave] must not be called.
+    pub fn enter(&mut self, term: &DataExpression) -> bool {
+        if self.failed() {
+            return false;
+        }
+
+        if let Some(max_depth) = self.limits.max_depth {
+            if self.depth >= max_depth {
+                self.error = Some(RewriteError::DepthExceeded(max_depth, term.clone()));
+                return false;
+            }
+        }
+
+        self.depth += 1;
+        true
+    }
+
+    /// Indicates that the nested invocation of the rewriter has finished.
+    pub fn leave(&mut self) {
+        self.depth -= 1;
+    }
+
+    /// Indicates that the given condition is rewritten, returns false when the rewriter must stop, in which
+    /// case [Guard::leave_condition] must not be called.
+    pub fn enter_condition(&mut self, lhs: &DataExpression, rhs: &DataExpression) -> bool {
+        if self.failed() {
+            return false;
+        }
+
+        if self.limits.detect_cycles {
+            if self.conditions.iter().any(|(l, r)| l == lhs && r == rhs) {
+                self.error = Some(RewriteError::ConditionCycle(lhs.clone(), rhs.clone()));
+                return false;
+            }
+
+            self.conditions.push((lhs.clone(), rhs.clone()));
+        }
+
+        true
+    }
+
+    /// Indicates that the condition entered last has been rewritten.
+    pub fn leave_condition(&mut self) {
+        if self.limits.detect_cycles {
+            self.conditions.pop();
+        }
+    }
+
+    /// Returns the given result, or the error when one of the limits has been exceeded.
+    pub fn finish(self, result: DataExpression) -> Result<DataExpression, RewriteError> {
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(result),
+        }
+    }
+}
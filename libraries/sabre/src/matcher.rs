@@ -0,0 +1,180 @@
+use ahash::HashMap;
+use mcrl2::aterm::ATermRef;
+use mcrl2::data::is_data_abstraction;
+use mcrl2::data::is_data_application;
+use mcrl2::data::is_data_function_symbol;
+use mcrl2::data::is_data_machine_number;
+use mcrl2::data::DataExpression;
+use mcrl2::data::DataExpressionRef;
+use mcrl2::data::DataVariable;
+
+use crate::set_automaton::is_supported_rule;
+use crate::set_automaton::SetAutomaton;
+use crate::utilities::match_term;
+use crate::utilities::ExplicitPosition;
+use crate::utilities::PositionIndexed;
+use crate::utilities::PositionIterator;
+use crate::RewriteSpecification;
+use crate::Rule;
+
+/// The index of a pattern in the patterns given to [Matcher::new].
+pub type PatternId = usize;
+
+/// Assigns a term to each variable of a pattern.
+pub type Substitution = Vec<(DataVariable, DataExpression)>;
+
+/// Finds the matches of a set of patterns in a term, using the adaptive
+/// pattern matching automaton that is also used by the [crate::InnermostRewriter].
+///
+/// Patterns can be non-linear, in which case all occurrences of a variable must
+/// match the same subterm. Patterns that cannot be used as the left hand side
+/// of a rewrite rule, for example patterns that are a single variable, never
+/// match.
+pub struct Matcher {
+    automaton: SetAutomaton<Vec<PatternId>>,
+}
+
+impl Matcher {
+    /// Creates a matcher for the given patterns.
+    pub fn new(patterns: &[DataExpression]) -> Matcher {
+        // Equal patterns share a single rule in the automaton.
+        let mut ids: HashMap<DataExpression, Vec<PatternId>> = HashMap::default();
+        let mut rewrite_rules = Vec::new();
+        for (id, pattern) in patterns.iter().enumerate() {
+            let pattern_ids = ids.entry(pattern.clone()).or_default();
+            if pattern_ids.is_empty() {
+                rewrite_rules.push(Rule {
+                    conditions: vec![],
+                    lhs: pattern.clone(),
+                    rhs: pattern.clone(),
+                });
+            }
+            pattern_ids.push(id);
+        }
+        rewrite_rules.retain(is_supported_rule);
+
+        let spec = RewriteSpecification {
+            rewrite_rules,
+            constructors: vec![],
+        };
+
+        Matcher {
+            automaton: SetAutomaton::new(&spec, |rule| ids[&rule.lhs].clone(), true),
+        }
+    }
+
+    /// Returns all matches of the patterns in the given term, with the
+    /// position of the matched subterm and the substitution for the variables
+    /// of the pattern. The matches are ordered by position, breadth first.
+    pub fn matches(&self, term: &DataExpression) -> impl Iterator<Item = (PatternId, ExplicitPosition, Substitution)> {
+        let mut result = Vec::new();
+
+        for (subterm, position) in PositionIterator::new(term.copy().into()) {
+            if position.indices.last() == Some(&1) {
+                // Skip the head symbols.
+                continue;
+            }
+
+            if is_data_application(&subterm) || is_data_function_symbol(&subterm) {
+                self.matches_at(&subterm, |id, substitution| {
+                    result.push((id, position.clone(), substitution.clone()))
+                });
+            }
+        }
+
+        result.into_iter()
+    }
+
+    /// Returns true iff one of the patterns matches the given term at the top.
+    pub fn matches_top(&self, term: &DataExpression) -> bool {
+        let mut result = false;
+        self.matches_at(term, |_, _| result = true);
+        result
+    }
+
+    /// Calls the given function for every pattern that matches the term at the top.
+    fn matches_at(&self, t: &ATermRef<'_>, mut found: impl FnMut(PatternId, &Substitution)) {
+        let mut state_index = 0;
+        loop {
+            let state = &self.automaton.states[state_index];
+
+            let pos: DataExpressionRef<'_> = t.get_position(&state.label).into();
+            if is_data_abstraction(&pos) || is_data_machine_number(&pos) {
+                return;
+            }
+
+            let Some(transition) = self
+                .automaton
+                .transitions
+                .get(&(state_index, pos.data_function_symbol().operation_id()))
+            else {
+                return;
+            };
+
+            for (announcement, ids) in &transition.announcements {
+                // The automaton does not check non-linear patterns, so match the pattern again to obtain the substitution.
+                let mut substitution = Vec::new();
+                if match_term(&announcement.rule.lhs, t, &mut substitution) {
+                    let substitution: Substitution = substitution
+                        .into_iter()
+                        .map(|(variable, value)| (variable.into(), value.into()))
+                        .collect();
+
+                    for id in ids {
+                        found(*id, &substitution);
+                    }
+                }
+            }
+
+            match transition.destinations.first() {
+                Some((_, destination)) => state_index = *destination,
+                None => return,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use mcrl2::aterm::TermPool;
+
+    use crate::test_utility::create_rewrite_rule;
+
+    #[test]
+    fn test_matcher() {
+        let mut tp = TermPool::new();
+
+        let mut parse =
+            |text: &str, variables: &[&str]| create_rewrite_rule(&mut tp, text, text, variables).unwrap().lhs;
+        let patterns = [
+            parse("f(x)", &["x"]),
+            parse("f(a)", &[]),
+            parse("g(x, x)", &["x"]),
+            parse("h(x)", &["x"]),
+        ];
+        let term = parse("g(f(a), f(a))", &[]);
+
+        let mut matches: Vec<(PatternId, ExplicitPosition)> = Matcher::new(&patterns)
+            .matches(&term)
+            .map(|(id, position, _)| (id, position))
+            .collect();
+        matches.sort();
+
+        assert_eq!(
+            matches,
+            vec![
+                (0, ExplicitPosition::new(&[2])),
+                (0, ExplicitPosition::new(&[3])),
+                (1, ExplicitPosition::new(&[2])),
+                (1, ExplicitPosition::new(&[3])),
+                (2, ExplicitPosition::empty_pos()),
+            ]
+        );
+
+        // The non-linear pattern does not match when the arguments differ.
+        let term = parse("g(f(a), a)", &[]);
+        assert!(!Matcher::new(&patterns).matches_top(&term));
+    }
+}
@@ -0,0 +1,130 @@
+use std::mem;
+
+use ahash::AHashMap;
+use mcrl2::data::DataExpression;
+
+/// A bounded cache that maps terms to their normal forms.
+///
+/// The least recently used entries are approximated by two generations, new
+/// entries are added to the current generation and entries of the previous
+/// generation are moved to the current generation when they are used. When
+/// the current generation is full it becomes the previous generation, which
+/// discards the entries that have not been used since the last time.
+#[derive(Default)]
+pub struct NormalFormCache {
+    current: AHashMap<DataExpression, DataExpression>,
+    previous: AHashMap<DataExpression, DataExpression>,
+
+    /// The maximum number of entries in a single generation, the cache is disabled when it is zero.
+    generation_size: usize,
+
+    statistics: CacheStatistics,
+}
+
+/// The number of lookups that were answered by the cache.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct CacheStatistics {
+    pub hits: usize,
+    pub misses: usize,
+}
+
+impl NormalFormCache {
+    /// Creates a cache that stores at most the given number of normal forms.
+    pub fn new(capacity: usize) -> NormalFormCache {
+        NormalFormCache {
+            generation_size: capacity / 2,
+            ..Default::default()
+        }
+    }
+
+    /// Returns true iff the cache stores normal forms.
+    pub fn is_enabled(&self) -> bool {
+        self.generation_size > 0
+    }
+
+    /// Returns the normal form of the given term if it is in the cache.
+    pub fn get(&mut self, term: &DataExpression) -> Option<DataExpression> {
+        if !self.is_enabled() {
+            return None;
+        }
+
+        if let Some(normal_form) = self.current.get(term) {
+            self.statistics.hits += 1;
+            return Some(normal_form.clone());
+        }
+
+        if let Some((term, normal_form)) = self.previous.remove_entry(term) {
+            self.statistics.hits += 1;
+            self.insert(term, normal_form.clone());
+            return Some(normal_form);
+        }
+
+        self.statistics.misses += 1;
+        None
+    }
+
+    /// Stores the normal form of the given term.
+    pub fn insert(&mut self, term: DataExpression, normal_form: DataExpression) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        if self.current.len() >= self.generation_size {
+            self.previous = mem::take(&mut self.current);
+        }
+
+        self.current.insert(term, normal_form);
+    }
+
+    /// Returns the number of normal forms in the cache.
+    pub fn len(&self) -> usize {
+        self.current.len() + self.previous.len()
+    }
+
+    /// Returns true iff the cache contains no normal forms.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the hits and misses since the cache was created.
+    pub fn statistics(&self) -> CacheStatistics {
+        self.statistics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ahash::AHashSet;
+    use mcrl2::aterm::TermPool;
+
+    use crate::utilities::to_untyped_data_expression;
+
+    #[test]
+    fn test_normal_form_cache() {
+        let mut tp = TermPool::new();
+
+        let mut terms = Vec::new();
+        for text in ["a", "b", "c", "d"] {
+            let t = tp.from_string(text).unwrap();
+            terms.push(to_untyped_data_expression(&mut tp, &t, &AHashSet::new()));
+        }
+
+        let mut cache = NormalFormCache::new(4);
+        cache.insert(terms[0].clone(), terms[1].clone());
+        cache.insert(terms[1].clone(), terms[1].clone());
+        assert_eq!(cache.get(&terms[0]), Some(terms[1].clone()));
+
+        // The third entry starts a new generation, after which the first entry is used again.
+        cache.insert(terms[2].clone(), terms[2].clone());
+        assert_eq!(cache.get(&terms[0]), Some(terms[1].clone()));
+
+        // The fourth entry discards the second entry, since it was not used.
+        cache.insert(terms[3].clone(), terms[3].clone());
+        assert_eq!(cache.get(&terms[1]), None);
+        assert_eq!(cache.get(&terms[3]), Some(terms[3].clone()));
+
+        assert_eq!(cache.statistics(), CacheStatistics { hits: 3, misses: 1 });
+    }
+}
@@ -0,0 +1,192 @@
+use log::debug;
+use mcrl2::data::is_data_application;
+use mcrl2::data::DataExpression;
+use thiserror::Error;
+
+use crate::RewriteSpecification;
+use crate::Rule;
+
+#[derive(Error, Debug)]
+pub enum ReduceError {
+    #[error("The given test case for term {0} does not fail")]
+    NotFailing(String),
+}
+
+/// Minimises a specification and a term for which `fails` holds, such that
+/// `fails` still holds for the result. This is used to reduce the test cases
+/// for bugs in the rewriters, where `fails` typically checks whether a
+/// rewriter crashes or disagrees with another rewriter.
+///
+/// The rewrite rules are minimised using delta debugging, after which the term
+/// is replaced by one of its subterms for which the test case still fails.
+/// This is repeated until neither can be reduced any further, so the result is
+/// minimal in the sense that removing any single rule or replacing the term by
+/// any of its proper subterms no longer fails.
+///
+/// Returns an error when `fails` does not hold for the given test case.
+pub fn reduce_test_case(
+    spec: &RewriteSpecification,
+    term: &DataExpression,
+    mut fails: impl FnMut(&RewriteSpecification, &DataExpression) -> bool,
+) -> Result<(RewriteSpecification, DataExpression), ReduceError> {
+    if !fails(spec, term) {
+        return Err(ReduceError::NotFailing(term.to_string()));
+    }
+
+    let mut spec = spec.clone();
+    let mut term = term.clone();
+
+    loop {
+        let rules = delta_debug(spec.rewrite_rules.clone(), |rules| {
+            fails(&with_rules(&spec, rules), &term)
+        });
+        let reduced_rules = rules.len() < spec.rewrite_rules.len();
+        spec.rewrite_rules = rules;
+
+        let reduced_term = match subterms(&term).into_iter().find(|subterm| fails(&spec, subterm)) {
+            Some(subterm) => {
+                term = subterm;
+                true
+            }
+            None => false,
+        };
+
+        debug!("reduced to {} rules and term {}", spec.rewrite_rules.len(), term);
+
+        if !reduced_rules && !reduced_term {
+            return Ok((spec, term));
+        }
+    }
+}
+
+/// Returns a copy of the specification with the given rules.
+fn with_rules(spec: &RewriteSpecification, rules: &[Rule]) -> RewriteSpecification {
+    RewriteSpecification {
+        rewrite_rules: rules.to_vec(),
+        constructors: spec.constructors.clone(),
+    }
+}
+
+/// Returns all proper subterms of the given term that are data expressions, in breadth first order.
+fn subterms(term: &DataExpression) -> Vec<DataExpression> {
+    let mut result: Vec<DataExpression> = Vec::new();
+
+    let mut index = 0;
+    let mut current = term.clone();
+    loop {
+        if is_data_application(&current) {
+            for argument in current.data_arguments() {
+                let argument: DataExpression = argument.protect().into();
+                if !result.contains(&argument) {
+                    result.push(argument);
+                }
+            }
+        }
+
+        match result.get(index) {
+            Some(next) => current = next.clone(),
+            None => return result,
+        }
+        index += 1;
+    }
+}
+
+/// The ddmin algorithm by Zeller and Hildebrandt, only considering the
+/// complements of the subsets. Returns a subset of the items for which
+/// `fails` holds, such that removing any single item no longer fails.
+fn delta_debug<T: Clone>(items: Vec<T>, mut fails: impl FnMut(&[T]) -> bool) -> Vec<T> {
+    let mut items = items;
+    let mut granularity = 2;
+
+    while !items.is_empty() {
+        if items.len() == 1 {
+            // Check whether the last item can also be removed.
+            if fails(&[]) {
+                items.clear();
+            }
+            break;
+        }
+
+        let chunk_size = items.len().div_ceil(granularity);
+        let complement = (0..items.len()).step_by(chunk_size).find_map(|start| {
+            let complement: Vec<T> = items[..start]
+                .iter()
+                .chain(&items[(start + chunk_size).min(items.len())..])
+                .cloned()
+                .collect();
+
+            if fails(&complement) {
+                Some(complement)
+            } else {
+                None
+            }
+        });
+
+        match complement {
+            Some(complement) => {
+                items = complement;
+                granularity = (granularity - 1).max(2);
+            }
+            None => {
+                if granularity >= items.len() {
+                    // Every single item is needed.
+                    break;
+                }
+                granularity = (granularity * 2).min(items.len());
+            }
+        }
+    }
+
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use mcrl2::aterm::TermPool;
+
+    use crate::test_utility::create_rewrite_rule;
+
+    #[test]
+    fn test_delta_debug() {
+        let items: Vec<usize> = (0..20).collect();
+
+        let result = delta_debug(items, |items| items.contains(&3) && items.contains(&17));
+        assert_eq!(result, vec![3, 17]);
+    }
+
+    #[test]
+    fn test_reduce_test_case() {
+        let mut tp = TermPool::new();
+
+        let rules = vec![
+            create_rewrite_rule(&mut tp, "f(x)", "g(x)", &["x"]).unwrap(),
+            create_rewrite_rule(&mut tp, "g(a)", "b", &[]).unwrap(),
+            create_rewrite_rule(&mut tp, "h(x, y)", "x", &["x", "y"]).unwrap(),
+        ];
+        let spec = RewriteSpecification {
+            rewrite_rules: rules.clone(),
+            constructors: vec![],
+        };
+
+        let term = create_rewrite_rule(&mut tp, "h(c, f(a))", "c", &[]).unwrap().lhs;
+        let expected_term = create_rewrite_rule(&mut tp, "f(a)", "c", &[]).unwrap().lhs;
+
+        // The test case fails whenever the second rule is present and the term contains f(a).
+        let (reduced, reduced_term) = reduce_test_case(&spec, &term, |spec, term| {
+            spec.rewrite_rules.contains(&rules[1])
+                && (*term == expected_term || subterms(term).contains(&expected_term))
+        })
+        .unwrap();
+
+        assert_eq!(reduced.rewrite_rules, vec![rules[1].clone()]);
+        assert_eq!(reduced_term, expected_term);
+
+        // A test case that does not fail cannot be reduced.
+        assert!(matches!(
+            reduce_test_case(&spec, &expected_term, |spec, _| spec.rewrite_rules.is_empty()),
+            Err(ReduceError::NotFailing(_))
+        ));
+    }
+}
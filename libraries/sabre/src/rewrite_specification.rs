@@ -1,15 +1,27 @@
 use std::fmt;
+use std::sync::Arc;
+use std::sync::OnceLock;
 
 use itertools::Itertools;
 use mcrl2::aterm::ATerm;
+use mcrl2::aterm::ATermGlobal;
 use mcrl2::data::BoolSort;
 use mcrl2::data::DataExpression;
+use mcrl2::data::DataFunctionSymbol;
 use mcrl2::data::DataSpecification;
+use mcrl2::data::SortExpression;
+
+use crate::innermost_rewriter::AnnouncementInnermost;
+use crate::set_automaton::SetAutomaton;
+use crate::utilities::AnnouncementSabre;
 
 /// A rewrite specification contains the bare info we need for rewriting (can be untyped).
 #[derive(Debug, Default, Clone)]
 pub struct RewriteSpecification {
     pub rewrite_rules: Vec<Rule>,
+
+    /// The constructors for every sort, used to enumerate quantified variables.
+    pub constructors: Vec<(SortExpression, Vec<DataFunctionSymbol>)>,
 }
 
 /// Either lhs == rhs or lhs != rhs depending on equality being true.
@@ -57,7 +69,138 @@ impl From<DataSpecification> for RewriteSpecification {
             }
         }
 
-        RewriteSpecification { rewrite_rules }
+        let constructors = value
+            .sorts()
+            .into_iter()
+            .map(|sort| {
+                let constructors = value.constructors(&sort.copy());
+                (sort, constructors)
+            })
+            .collect();
+
+        RewriteSpecification {
+            rewrite_rules,
+            constructors,
+        }
+    }
+}
+
+/// A [RewriteSpecification] where all terms are protected on the global
+/// protection set, such that it can be shared between threads. Each thread
+/// obtains its own [RewriteSpecification] using [SharedRewriteSpecification::local].
+///
+/// The set automata of the [crate::InnermostRewriter] and [crate::SabreRewriter]
+/// are constructed once, when the first rewriter is created using their
+/// `from_shared` constructor, and are then shared by all rewriters.
+#[derive(Clone, Debug)]
+pub struct SharedRewriteSpecification {
+    rewrite_rules: Arc<Vec<SharedRule>>,
+    constructors: Arc<Vec<(ATermGlobal, Vec<ATermGlobal>)>>,
+
+    innermost_automaton: Arc<OnceLock<Arc<SetAutomaton<AnnouncementInnermost>>>>,
+    sabre_automaton: Arc<OnceLock<Arc<SetAutomaton<AnnouncementSabre>>>>,
+}
+
+#[derive(Debug)]
+struct SharedRule {
+    conditions: Vec<(ATermGlobal, ATermGlobal, bool)>,
+    lhs: ATermGlobal,
+    rhs: ATermGlobal,
+}
+
+impl RewriteSpecification {
+    /// Returns a copy of this specification that can be shared between threads.
+    pub fn share(&self) -> SharedRewriteSpecification {
+        let rewrite_rules = self
+            .rewrite_rules
+            .iter()
+            .map(|rule| SharedRule {
+                conditions: rule
+                    .conditions
+                    .iter()
+                    .map(|condition| {
+                        (
+                            condition.lhs.protect_global(),
+                            condition.rhs.protect_global(),
+                            condition.equality,
+                        )
+                    })
+                    .collect(),
+                lhs: rule.lhs.protect_global(),
+                rhs: rule.rhs.protect_global(),
+            })
+            .collect();
+
+        let constructors = self
+            .constructors
+            .iter()
+            .map(|(sort, constructors)| {
+                (
+                    sort.protect_global(),
+                    constructors.iter().map(|symbol| symbol.protect_global()).collect(),
+                )
+            })
+            .collect();
+
+        SharedRewriteSpecification {
+            rewrite_rules: Arc::new(rewrite_rules),
+            constructors: Arc::new(constructors),
+            innermost_automaton: Arc::new(OnceLock::new()),
+            sabre_automaton: Arc::new(OnceLock::new()),
+        }
+    }
+}
+
+impl SharedRewriteSpecification {
+    /// Returns the specification with its terms protected by the current thread.
+    pub fn local(&self) -> RewriteSpecification {
+        let rewrite_rules = self
+            .rewrite_rules
+            .iter()
+            .map(|rule| Rule {
+                conditions: rule
+                    .conditions
+                    .iter()
+                    .map(|(lhs, rhs, equality)| Condition {
+                        lhs: lhs.protect().into(),
+                        rhs: rhs.protect().into(),
+                        equality: *equality,
+                    })
+                    .collect(),
+                lhs: rule.lhs.protect().into(),
+                rhs: rule.rhs.protect().into(),
+            })
+            .collect();
+
+        let constructors = self
+            .constructors
+            .iter()
+            .map(|(sort, constructors)| {
+                (
+                    sort.protect().into(),
+                    constructors.iter().map(|symbol| symbol.protect().into()).collect(),
+                )
+            })
+            .collect();
+
+        RewriteSpecification {
+            rewrite_rules,
+            constructors,
+        }
+    }
+
+    /// Returns the automaton of the [crate::InnermostRewriter], which is constructed on first use.
+    pub(crate) fn innermost_automaton(&self) -> Arc<SetAutomaton<AnnouncementInnermost>> {
+        self.innermost_automaton
+            .get_or_init(|| Arc::new(SetAutomaton::new(&self.local(), AnnouncementInnermost::new, true)))
+            .clone()
+    }
+
+    /// Returns the automaton of the [crate::SabreRewriter], which is constructed on first use.
+    pub(crate) fn sabre_automaton(&self) -> Arc<SetAutomaton<AnnouncementSabre>> {
+        self.sabre_automaton
+            .get_or_init(|| Arc::new(SetAutomaton::new(&self.local(), AnnouncementSabre::new, false)))
+            .clone()
     }
 }
 
@@ -1,30 +1,117 @@
 use std::cell::RefCell;
-use std::rc::Rc;
+use std::sync::Arc;
+use std::sync::Mutex;
 
+use ahash::AHashMap;
 use log::info;
 use log::trace;
 use mcrl2::aterm::ATermRef;
 use mcrl2::aterm::TermPool;
+use mcrl2::data::is_data_abstraction;
 use mcrl2::data::DataExpression;
 use mcrl2::data::DataExpressionRef;
 
+use crate::limits::Guard;
 use crate::matching::nonlinear::check_equivalence_classes;
 use crate::set_automaton::MatchAnnouncement;
 use crate::set_automaton::SetAutomaton;
+use crate::single_step::apply_first;
+use crate::single_step::SingleStep;
+use crate::utilities::instantiate;
+use crate::utilities::to_aterm_substitution;
 use crate::utilities::AnnouncementSabre;
 use crate::utilities::ConfigurationStack;
 use crate::utilities::PositionIndexed;
 use crate::utilities::SideInfo;
 use crate::utilities::SideInfoType;
+use crate::Enumerator;
+use crate::RewriteError;
+use crate::RewriteEvent;
+use crate::RewriteLimits;
 use crate::RewriteSpecification;
+use crate::Rule;
+use crate::SharedRewriteSpecification;
+use crate::Substitution;
+use crate::TraceCallback;
+use crate::Tracer;
 
 /// A shared trait for all the rewriters
 pub trait RewriteEngine {
     /// Rewrites the given term into normal form.
-    fn rewrite(&mut self, term: DataExpression) -> DataExpression;
+    ///
+    /// # Panics
+    ///
+    /// When one of the limits set by [RewriteEngine::set_limits] is exceeded.
+    fn rewrite(&mut self, term: DataExpression) -> DataExpression {
+        self.try_rewrite(term).unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    /// Rewrites the given term into normal form, or returns an error when one
+    /// of the limits set by [RewriteEngine::set_limits] is exceeded.
+    fn try_rewrite(&mut self, term: DataExpression) -> Result<DataExpression, RewriteError>;
+
+    /// Rewrites the given term into normal form, where the variables are
+    /// replaced by their value in the substitution. The values must be in
+    /// normal form. This avoids constructing the instantiated term first, for
+    /// example to evaluate the conditions of summands in a state.
+    ///
+    /// # Panics
+    ///
+    /// When one of the limits set by [RewriteEngine::set_limits] is exceeded.
+    fn rewrite_with_substitution(&mut self, term: &DataExpression, substitution: &Substitution) -> DataExpression {
+        self.try_rewrite_with_substitution(term, substitution)
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    /// See [RewriteEngine::rewrite_with_substitution] and [RewriteEngine::try_rewrite].
+    fn try_rewrite_with_substitution(
+        &mut self,
+        term: &DataExpression,
+        substitution: &Substitution,
+    ) -> Result<DataExpression, RewriteError>;
+
+    /// Sets the limits that are used to detect non-terminating rewriting, by default there are no limits.
+    fn set_limits(&mut self, limits: RewriteLimits);
+
+    /// Sets the callback that is called for every rewrite step, tracing is disabled when it is None.
+    fn set_trace(&mut self, callback: Option<TraceCallback>);
+
+    /// Returns the statistics of all terms rewritten so far.
+    fn statistics(&self) -> &RewritingStatistics;
+
+    /// Performs a single rewrite step on the leftmost outermost redex of the
+    /// given term for which the conditions hold, where the conditions are
+    /// rewritten to normal form. Returns None when the term is in normal form.
+    /// Only the rewrite rules are considered, so the built-in evaluation of
+    /// arithmetic is not performed.
+    fn rewrite_once(&mut self, term: &DataExpression) -> Option<DataExpression>;
+
+    /// Returns true iff no rewrite rule can be applied to the given term, see [RewriteEngine::rewrite_once].
+    fn is_normal_form(&mut self, term: &DataExpression) -> bool {
+        self.rewrite_once(term).is_none()
+    }
+
+    /// Rewrites the given term into normal form and returns the rewrite steps
+    /// that were performed. Replaces the callback set by [RewriteEngine::set_trace].
+    fn rewrite_traced(&mut self, term: DataExpression) -> (DataExpression, Vec<RewriteEvent>) {
+        let events = Arc::new(Mutex::new(Vec::new()));
+
+        let events_callback = events.clone();
+        self.set_trace(Some(Box::new(move |event| {
+            events_callback
+                .lock()
+                .expect("The trace should not be poisoned")
+                .push(event.clone())
+        })));
+        let result = self.rewrite(term);
+        self.set_trace(None);
+
+        let events = events.lock().expect("The trace should not be poisoned").split_off(0);
+        (result, events)
+    }
 }
 
-#[derive(Default)]
+#[derive(Clone, Debug, Default)]
 pub struct RewritingStatistics {
     /// Count the number of rewrite rules applied
     pub rewrite_steps: usize,
@@ -32,62 +119,218 @@ pub struct RewritingStatistics {
     pub symbol_comparisons: usize,
     /// The number of times rewrite is called recursively (to rewrite conditions etc)
     pub recursions: usize,
+    /// The number of times each rewrite rule has been applied, rules that were never applied are not present.
+    pub rule_applications: AHashMap<Rule, usize>,
+}
+
+impl RewritingStatistics {
+    /// Records a rewrite step using the given rule.
+    pub fn applied(&mut self, rule: &Rule) {
+        self.rewrite_steps += 1;
+        match self.rule_applications.get_mut(rule) {
+            Some(count) => *count += 1,
+            None => {
+                self.rule_applications.insert(rule.clone(), 1);
+            }
+        }
+    }
+
+    /// Adds the given statistics to these statistics.
+    pub fn merge(&mut self, other: RewritingStatistics) {
+        self.rewrite_steps += other.rewrite_steps;
+        self.symbol_comparisons += other.symbol_comparisons;
+        self.recursions += other.recursions;
+        for (rule, count) in other.rule_applications {
+            *self.rule_applications.entry(rule).or_default() += count;
+        }
+    }
+
+    /// Returns the number of times the given rule has been applied.
+    pub fn applications(&self, rule: &Rule) -> usize {
+        self.rule_applications.get(rule).copied().unwrap_or(0)
+    }
+
+    /// Returns the rules of the specification that have never been applied, in the order of the specification.
+    pub fn unused_rules<'a>(&'a self, spec: &'a RewriteSpecification) -> impl Iterator<Item = &'a Rule> + 'a {
+        spec.rewrite_rules
+            .iter()
+            .filter(|rule| !self.rule_applications.contains_key(*rule))
+    }
 }
 
 // A set automaton based rewrite engine described in  Mark Bouwman, Rick Erkens:
 // Term Rewriting Based On Set Automaton Matching. CoRR abs/2202.08687 (2022)
 pub struct SabreRewriter {
-    term_pool: Rc<RefCell<TermPool>>,
-    automaton: SetAutomaton<AnnouncementSabre>,
+    term_pool: RefCell<TermPool>,
+    automaton: Arc<SetAutomaton<AnnouncementSabre>>,
+    enumerator: Enumerator,
+    trace: Option<TraceCallback>,
+    limits: RewriteLimits,
+    statistics: RewritingStatistics,
+    single_step: SingleStep,
 }
 
 impl RewriteEngine for SabreRewriter {
-    fn rewrite(&mut self, term: DataExpression) -> DataExpression {
+    fn try_rewrite(&mut self, term: DataExpression) -> Result<DataExpression, RewriteError> {
+        self.stack_based_normalise(term)
+    }
+
+    fn try_rewrite_with_substitution(
+        &mut self,
+        term: &DataExpression,
+        substitution: &Substitution,
+    ) -> Result<DataExpression, RewriteError> {
+        // The configuration stack requires the complete term, so the substitution is applied first.
+        let term = instantiate(
+            &mut self.term_pool.borrow_mut(),
+            term,
+            &to_aterm_substitution(substitution),
+        );
         self.stack_based_normalise(term)
     }
+
+    fn set_trace(&mut self, callback: Option<TraceCallback>) {
+        self.trace = callback;
+    }
+
+    fn set_limits(&mut self, limits: RewriteLimits) {
+        self.limits = limits;
+    }
+
+    fn statistics(&self) -> &RewritingStatistics {
+        &self.statistics
+    }
+
+    fn rewrite_once(&mut self, term: &DataExpression) -> Option<DataExpression> {
+        let redexes = self.single_step.redexes(term);
+        apply_first(self, |rewriter| &rewriter.term_pool, term, redexes)
+    }
 }
 
 impl SabreRewriter {
-    pub fn new(tp: Rc<RefCell<TermPool>>, spec: &RewriteSpecification) -> Self {
+    pub fn new(spec: &RewriteSpecification) -> Self {
         let automaton = SetAutomaton::new(spec, AnnouncementSabre::new, false);
+        SabreRewriter::with_automaton(spec, Arc::new(automaton))
+    }
 
-        info!("ATerm pool: {}", tp.borrow());
+    /// Creates a rewriter for the given shared specification, where the automaton is constructed only once and then
+    /// shared by all rewriters created from the specification.
+    pub fn from_shared(spec: &SharedRewriteSpecification) -> Self {
+        SabreRewriter::with_automaton(&spec.local(), spec.sabre_automaton())
+    }
+
+    fn with_automaton(spec: &RewriteSpecification, automaton: Arc<SetAutomaton<AnnouncementSabre>>) -> Self {
+        let mut tp = TermPool::new();
+        let enumerator = Enumerator::new(&mut tp, spec);
+
+        info!("ATerm pool: {}", tp);
         SabreRewriter {
-            term_pool: tp.clone(),
+            term_pool: RefCell::new(tp),
             automaton,
+            enumerator,
+            trace: None,
+            limits: RewriteLimits::default(),
+            statistics: RewritingStatistics::default(),
+            single_step: SingleStep::new(spec),
         }
     }
 
     /// Function to rewrite a term. See the module documentation.
-    pub fn stack_based_normalise(&mut self, t: DataExpression) -> DataExpression {
+    pub fn stack_based_normalise(&mut self, t: DataExpression) -> Result<DataExpression, RewriteError> {
         let mut stats = RewritingStatistics::default();
+        let mut tracer = Tracer::new(self.trace.take());
+        let mut guard = Guard::new(self.limits);
 
-        let result =
-            SabreRewriter::stack_based_normalise_aux(&mut self.term_pool.borrow_mut(), &self.automaton, t, &mut stats);
+        let result = SabreRewriter::stack_based_normalise_aux(
+            &mut self.term_pool.borrow_mut(),
+            &self.automaton,
+            &self.enumerator,
+            t,
+            &mut stats,
+            &mut tracer,
+            &mut guard,
+        );
+        self.trace = tracer.into_callback();
         info!(
             "{} rewrites, {} single steps and {} symbol comparisons",
             stats.recursions, stats.rewrite_steps, stats.symbol_comparisons
         );
-        result
+        self.statistics.merge(stats);
+        guard.finish(result)
     }
 
     /// The _aux function splits the [TermPool] pool and the [SetAutomaton] to make borrow checker happy.
     /// We can now mutate the term pool and read the state and transition information at the same time
+    ///
+    /// Quantifiers are considered to be in normal form during rewriting, after
+    /// which they are eliminated by the [Enumerator] and the result is
+    /// rewritten again.
     fn stack_based_normalise_aux(
         tp: &mut TermPool,
         automaton: &SetAutomaton<AnnouncementSabre>,
+        enumerator: &Enumerator,
         t: DataExpression,
         stats: &mut RewritingStatistics,
+        tracer: &mut Tracer,
+        guard: &mut Guard,
+    ) -> DataExpression {
+        if !guard.enter(&t) {
+            return t;
+        }
+
+        let mut result = SabreRewriter::normalise_configurations(tp, automaton, enumerator, t, stats, tracer, guard);
+
+        loop {
+            if guard.failed() {
+                guard.leave();
+                return result;
+            }
+
+            // The instances of the quantifiers are not subterms of the term that is rewritten.
+            let position = tracer.detach();
+            let eliminated = enumerator.eliminate_subterms(tp, &result, |tp, instance| {
+                SabreRewriter::stack_based_normalise_aux(tp, automaton, enumerator, instance, stats, tracer, guard)
+            });
+            tracer.attach(position);
+
+            match eliminated {
+                Some(term) => {
+                    result =
+                        SabreRewriter::normalise_configurations(tp, automaton, enumerator, term, stats, tracer, guard);
+                }
+                None => {
+                    guard.leave();
+                    return result;
+                }
+            }
+        }
+    }
+
+    /// Rewrites the given term using the configuration stack, see [SabreRewriter::stack_based_normalise_aux].
+    fn normalise_configurations(
+        tp: &mut TermPool,
+        automaton: &SetAutomaton<AnnouncementSabre>,
+        enumerator: &Enumerator,
+        t: DataExpression,
+        stats: &mut RewritingStatistics,
+        tracer: &mut Tracer,
+        guard: &mut Guard,
     ) -> DataExpression {
         stats.recursions += 1;
 
         // We explore the configuration tree depth first using a ConfigurationStack
+        let input = t.clone();
         let mut cs = ConfigurationStack::new(0, t);
 
         // Big loop until we know we have a normal form
         'outer: loop {
             // Inner loop so that we can easily break; to the next iteration
             'skip_point: loop {
+                if guard.failed() {
+                    // The result is discarded, so stop as soon as possible.
+                    return input;
+                }
+
                 trace!("{}", cs);
 
                 // Check if there is any configuration leaf left to explore, if not we have found a normal form
@@ -102,11 +345,18 @@ impl SabreRewriter {
                             let pos: DataExpressionRef =
                                 leaf_term.get_position(&automaton.states[leaf.state].label).into();
 
-                            let function_symbol = pos.data_function_symbol();
-                            stats.symbol_comparisons += 1;
+                            // Quantifiers are eliminated afterwards, so these do not match any pattern.
+                            let transition = if is_data_abstraction(&pos) {
+                                None
+                            } else {
+                                let function_symbol = pos.data_function_symbol();
+                                stats.symbol_comparisons += 1;
+
+                                automaton.transitions.get(&(leaf.state, function_symbol.operation_id()))
+                            };
 
                             // Get the transition belonging to the observed symbol
-                            if let Some(tr) = automaton.transitions.get(&(leaf.state, function_symbol.operation_id())) {
+                            if let Some(tr) = transition {
                                 // Loop over the match announcements of the transition
                                 for (announcement, annotation) in &tr.announcements {
                                     if annotation.conditions.is_empty() && annotation.equivalence_classes.is_empty() {
@@ -128,6 +378,7 @@ impl SabreRewriter {
                                                 leaf_index,
                                                 &mut cs,
                                                 stats,
+                                                tracer,
                                             );
                                             break 'skip_point;
                                         }
@@ -180,6 +431,7 @@ impl SabreRewriter {
                                         leaf_index,
                                         &mut cs,
                                         stats,
+                                        tracer,
                                     );
                                 }
                                 SideInfoType::EquivalenceAndConditionCheck(announcement, annotation) => {
@@ -189,10 +441,13 @@ impl SabreRewriter {
                                         && SabreRewriter::conditions_hold(
                                             tp,
                                             automaton,
+                                            enumerator,
                                             announcement,
                                             annotation,
                                             leaf_term,
                                             stats,
+                                            tracer,
+                                            guard,
                                         )
                                     {
                                         SabreRewriter::apply_rewrite_rule(
@@ -203,6 +458,7 @@ impl SabreRewriter {
                                             leaf_index,
                                             &mut cs,
                                             stats,
+                                            tracer,
                                         );
                                     }
                                 }
@@ -220,6 +476,7 @@ impl SabreRewriter {
     }
 
     /// Apply a rewrite rule and prune back
+    #[allow(clippy::too_many_arguments)]
     fn apply_rewrite_rule(
         tp: &mut TermPool,
         automaton: &SetAutomaton<AnnouncementSabre>,
@@ -228,14 +485,15 @@ impl SabreRewriter {
         leaf_index: usize,
         cs: &mut ConfigurationStack<'_>,
         stats: &mut RewritingStatistics,
+        tracer: &mut Tracer,
     ) {
-        stats.rewrite_steps += 1;
+        stats.applied(&announcement.rule);
 
         let read_terms = cs.terms.read();
         let leaf_subterm: &DataExpressionRef<'_> = &read_terms[leaf_index];
 
         // Computes the new subterm of the configuration
-        let new_subterm = annotation
+        let new_subterm: DataExpression = annotation
             .semi_compressed_rhs
             .evaluate(&leaf_subterm.get_position(&announcement.position), tp)
             .into();
@@ -247,20 +505,40 @@ impl SabreRewriter {
             announcement.rule
         );
 
+        if tracer.is_enabled() {
+            // The position of the redex is the path of configurations followed by the position of the announcement.
+            let position: Vec<usize> = cs.stack[..=leaf_index]
+                .iter()
+                .filter_map(|configuration| configuration.position)
+                .chain([&announcement.position])
+                .flat_map(|position| position.indices.iter().copied())
+                .collect();
+
+            let redex: DataExpression = leaf_subterm.get_position(&announcement.position).protect().into();
+            tracer.rewritten(&announcement.rule, &redex, Some(&position), || new_subterm.clone());
+        }
+
         // The match announcement tells us how far we need to prune back.
         let prune_point = leaf_index - announcement.symbols_seen;
         cs.prune(tp, automaton, prune_point, new_subterm);
     }
 
     /// Checks conditions and subterm equality of non-linear patterns.
+    #[allow(clippy::too_many_arguments)]
     fn conditions_hold(
         tp: &mut TermPool,
         automaton: &SetAutomaton<AnnouncementSabre>,
+        enumerator: &Enumerator,
         announcement: &MatchAnnouncement,
         annotation: &AnnouncementSabre,
         subterm: &DataExpressionRef<'_>,
         stats: &mut RewritingStatistics,
+        tracer: &mut Tracer,
+        guard: &mut Guard,
     ) -> bool {
+        // The conditions are not subterms of the term that is rewritten.
+        let position = tracer.detach();
+
         for c in &annotation.conditions {
             let subterm = subterm.get_position(&announcement.position);
 
@@ -269,21 +547,30 @@ impl SabreRewriter {
 
             // Equality => lhs == rhs.
             if !c.equality || lhs != rhs {
-                let rhs_normal = SabreRewriter::stack_based_normalise_aux(tp, automaton, rhs, stats);
+                if !guard.enter_condition(&lhs, &rhs) {
+                    tracer.attach(position);
+                    return false;
+                }
+
+                let rhs_normal =
+                    SabreRewriter::stack_based_normalise_aux(tp, automaton, enumerator, rhs, stats, tracer, guard);
                 let lhs_normal = if &lhs == tp.true_term() {
                     // TODO: Store the conditions in a better way. REC now uses a list of equalities while mCRL2 specifications have a simple condition.
                     lhs
                 } else {
-                    SabreRewriter::stack_based_normalise_aux(tp, automaton, lhs, stats)
+                    SabreRewriter::stack_based_normalise_aux(tp, automaton, enumerator, lhs, stats, tracer, guard)
                 };
+                guard.leave_condition();
 
                 // If lhs != rhs && !equality OR equality && lhs == rhs.
                 if (!c.equality && lhs_normal == rhs_normal) || (c.equality && lhs_normal != rhs_normal) {
+                    tracer.attach(position);
                     return false;
                 }
             }
         }
 
+        tracer.attach(position);
         true
     }
 }
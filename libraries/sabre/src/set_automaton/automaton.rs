@@ -7,7 +7,6 @@ use log::debug;
 use log::info;
 use log::log_enabled;
 use log::trace;
-use log::warn;
 use mcrl2::aterm::ATermRef;
 use mcrl2::data::is_data_abstraction;
 use mcrl2::data::is_data_application;
@@ -16,6 +15,7 @@ use mcrl2::data::is_data_machine_number;
 use mcrl2::data::is_data_untyped_identifier;
 use mcrl2::data::is_data_variable;
 use mcrl2::data::is_data_where_clause;
+use mcrl2::data::DataAbstractionRef;
 use mcrl2::data::DataExpression;
 use mcrl2::data::DataExpressionRef;
 use mcrl2::data::DataFunctionSymbol;
@@ -79,7 +79,13 @@ impl<M> SetAutomaton<M> {
         let supported_rules: Vec<Rule> = spec
             .rewrite_rules
             .iter()
-            .filter(|rule| is_supported_rule(rule))
+            .filter(|rule| {
+                let supported = is_supported_rule(rule);
+                if !supported {
+                    debug!("Ignoring unsupported rule {}", rule);
+                }
+                supported
+            })
             .map(Rule::clone)
             .collect();
 
@@ -226,12 +232,127 @@ impl<M> SetAutomaton<M> {
             (Instant::now() - start).as_millis()
         );
 
-        let result = SetAutomaton { states, transitions };
+        let mut result = SetAutomaton { states, transitions };
+        result.reduce_states();
         debug!("{}", result);
 
         result
     }
 
+    /// Merges the states that have the same label and the same outgoing
+    /// transitions, up to the merged destinations. The equivalent states are
+    /// computed by partition refinement, where the states are initially
+    /// partitioned by their label. The initial state remains state zero.
+    fn reduce_states(&mut self) {
+        let start = Instant::now();
+
+        // The symbols of the outgoing transitions for every state, sorted for a canonical signature.
+        let mut outgoing: Vec<Vec<usize>> = vec![Vec::new(); self.states.len()];
+        for (state, symbol) in self.transitions.keys() {
+            outgoing[*state].push(*symbol);
+        }
+        for symbols in &mut outgoing {
+            symbols.sort_unstable();
+        }
+
+        // Initially the states are only distinguished by their label.
+        let mut block = {
+            let mut labels: HashMap<&ExplicitPosition, usize> = HashMap::default();
+            self.states
+                .iter()
+                .map(|state| {
+                    let number_of_blocks = labels.len();
+                    *labels.entry(&state.label).or_insert(number_of_blocks)
+                })
+                .collect::<Vec<usize>>()
+        };
+
+        let mut number_of_blocks = 0;
+        loop {
+            // The signature consists of the current block and the outgoing transitions to the current blocks.
+            type Signature<'a> = (
+                usize,
+                Vec<(usize, Vec<&'a MatchAnnouncement>, Vec<(&'a ExplicitPosition, usize)>)>,
+            );
+            let mut signatures: HashMap<Signature<'_>, usize> = HashMap::default();
+
+            let new_block: Vec<usize> = (0..self.states.len())
+                .map(|state| {
+                    let signature: Signature<'_> = (
+                        block[state],
+                        outgoing[state]
+                            .iter()
+                            .map(|symbol| {
+                                let transition = &self.transitions[&(state, *symbol)];
+                                (
+                                    *symbol,
+                                    transition.announcements.iter().map(|(ma, _)| ma).collect(),
+                                    transition
+                                        .destinations
+                                        .iter()
+                                        .map(|(position, destination)| (position, block[*destination]))
+                                        .collect(),
+                                )
+                            })
+                            .collect(),
+                    );
+
+                    let size = signatures.len();
+                    *signatures.entry(signature).or_insert(size)
+                })
+                .collect();
+
+            block = new_block;
+            if signatures.len() == number_of_blocks {
+                // The partition is stable, note that the blocks are numbered by their first state.
+                break;
+            }
+            number_of_blocks = signatures.len();
+        }
+
+        if number_of_blocks == self.states.len() {
+            return;
+        }
+
+        // Keep the first state of every block, and redirect the transitions to these states.
+        let mut representative = vec![usize::MAX; number_of_blocks];
+        for (state, block) in block.iter().enumerate() {
+            if representative[*block] == usize::MAX {
+                representative[*block] = state;
+            }
+        }
+
+        let states: Vec<State> = self
+            .states
+            .drain(..)
+            .enumerate()
+            .filter(|(state, _)| representative[block[*state]] == *state)
+            .map(|(_, state)| state)
+            .collect();
+
+        let mut transitions = HashMap::default();
+        for ((state, symbol), mut transition) in self.transitions.drain() {
+            if representative[block[state]] != state {
+                continue;
+            }
+
+            for (_, destination) in &mut transition.destinations {
+                *destination = block[*destination];
+            }
+            transitions.insert((block[state], symbol), transition);
+        }
+
+        info!(
+            "Reduced set automaton from {} to {} states in {} ms",
+            block.len(),
+            states.len(),
+            (Instant::now() - start).as_millis()
+        );
+
+        self.states = states;
+        self.transitions = transitions;
+    }
+
     /// Returns the number of states
     pub fn num_of_states(&self) -> usize {
         self.states.len()
@@ -525,38 +646,42 @@ fn add_symbol(function_symbol: DataFunctionSymbol, arity: usize, symbols: &mut H
     }
 }
 
-/// Returns false iff this is a higher order term, of the shape t(t_0, ..., t_n), or an unknown term.
-fn is_supported_term(t: &DataExpression) -> bool {
+/// Returns the reason why the term is not supported, which is when it is a
+/// higher order term of the shape t(t_0, ..., t_n), or an unknown term.
+///
+/// Quantifiers are only allowed when `allow_quantifiers` is true, since these are eliminated by the enumerator.
+fn unsupported_term(t: &DataExpression, allow_quantifiers: bool) -> Option<String> {
     for subterm in t.iter() {
         if is_data_application(&subterm) && !is_data_function_symbol(&subterm.arg(0)) {
-            warn!("{} is higher order", &subterm);
-            return false;
-        } else if is_data_abstraction(&subterm)
-            || is_data_where_clause(&subterm)
-            || is_data_untyped_identifier(&subterm)
-        {
-            warn!("{} contains unsupported construct", subterm);
-            return false;
+            return Some(format!("{} is higher order", &subterm));
+        } else if is_data_abstraction(&subterm) {
+            let abstraction = DataAbstractionRef::from(subterm.copy());
+            if !allow_quantifiers || !(abstraction.is_forall() || abstraction.is_exists()) {
+                return Some(format!("{} contains unsupported binder", subterm));
+            }
+        } else if is_data_where_clause(&subterm) || is_data_untyped_identifier(&subterm) {
+            return Some(format!("{} contains unsupported construct", subterm));
         }
     }
 
-    true
+    None
 }
 
-/// Checks whether the set automaton can use this rule, no higher order rules or binders.
+/// Checks whether the set automaton can use this rule, no higher order rules
+/// or binders. Only the conditions can contain quantifiers.
 pub fn is_supported_rule(rule: &Rule) -> bool {
-    // There should be no terms of the shape t(t0,...,t_n)
-    if !is_supported_term(&rule.rhs) || !is_supported_term(&rule.lhs) {
-        return false;
-    }
-
-    for cond in &rule.conditions {
-        if !is_supported_term(&cond.rhs) || !is_supported_term(&cond.lhs) {
-            return false;
-        }
-    }
+    unsupported_rule(rule).is_none()
+}
 
-    true
+/// Returns the reason why the set automaton cannot use this rule, see [is_supported_rule].
+pub fn unsupported_rule(rule: &Rule) -> Option<String> {
+    unsupported_term(&rule.lhs, false)
+        .or_else(|| unsupported_term(&rule.rhs, false))
+        .or_else(|| {
+            rule.conditions
+                .iter()
+                .find_map(|cond| unsupported_term(&cond.lhs, true).or_else(|| unsupported_term(&cond.rhs, true)))
+        })
 }
 
 /// Finds all data symbols in the term and adds them to the symbol index.
@@ -576,9 +701,119 @@ fn find_symbols(t: &DataExpressionRef<'_>, symbols: &mut HashMap<DataFunctionSym
         for arg in t.data_arguments() {
             find_symbols(&arg.into(), symbols);
         }
+    } else if is_data_abstraction(t) {
+        // The bound variables are ignored, similar to the variables in the pattern.
+        let t: &ATermRef<'_> = t;
+        let abstraction = DataAbstractionRef::from(t.copy());
+        find_symbols(&abstraction.body(), symbols);
     } else if is_data_machine_number(t) {
         // Ignore machine numbers during matching?
     } else if !is_data_variable(t) {
         panic!("Unexpected term {:?}", t);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use mcrl2::aterm::TermPool;
+
+    use crate::test_utility::create_rewrite_rule;
+    use crate::utilities::PositionIndexed;
+
+    /// Returns the match announcements of the automaton for the given term,
+    /// where only the first destination of every transition is followed.
+    fn announcements(automaton: &SetAutomaton<()>, term: &DataExpression) -> Vec<MatchAnnouncement> {
+        let t: ATermRef<'_> = term.copy().into();
+
+        let mut result = Vec::new();
+        let mut state_index = 0;
+        loop {
+            let pos: DataExpressionRef<'_> = t.get_position(&automaton.states[state_index].label).into();
+            let Some(transition) = automaton
+                .transitions
+                .get(&(state_index, pos.data_function_symbol().operation_id()))
+            else {
+                return result;
+            };
+
+            result.extend(
+                transition
+                    .announcements
+                    .iter()
+                    .map(|(announcement, _)| announcement.clone()),
+            );
+            match transition.destinations.first() {
+                Some((_, destination)) => state_index = *destination,
+                None => return result,
+            }
+        }
+    }
+
+    #[test]
+    fn test_reduce_states() {
+        let mut tp = TermPool::new();
+
+        let spec = RewriteSpecification {
+            rewrite_rules: vec![
+                create_rewrite_rule(&mut tp, "f(a, g(x))", "x", &["x"]).unwrap(),
+                create_rewrite_rule(&mut tp, "f(b, x)", "x", &["x"]).unwrap(),
+                create_rewrite_rule(&mut tp, "g(h(a))", "a", &[]).unwrap(),
+            ],
+            constructors: vec![],
+        };
+
+        let terms: Vec<DataExpression> = ["f(a, g(h(a)))", "f(b, a)", "f(a, b)", "g(h(b))"]
+            .iter()
+            .map(|term| create_rewrite_rule(&mut tp, term, term, &[]).unwrap().lhs)
+            .collect();
+
+        let mut automaton = SetAutomaton::new(&spec, |_| (), true);
+        let num_of_states = automaton.num_of_states();
+        let expected: Vec<Vec<MatchAnnouncement>> = terms.iter().map(|term| announcements(&automaton, term)).collect();
+
+        // Redirect a transition to a copy of its destination, which is equivalent to the destination.
+        let (key, destination) = automaton
+            .transitions
+            .iter()
+            .find_map(|(key, transition)| {
+                transition
+                    .destinations
+                    .first()
+                    .filter(|(_, destination)| *destination != 0)
+                    .map(|(_, destination)| (*key, *destination))
+            })
+            .expect("The automaton has a transition to a state other than the initial state");
+
+        let copy = automaton.states.len();
+        let state = State {
+            label: automaton.states[destination].label.clone(),
+            match_goals: automaton.states[destination].match_goals.clone(),
+        };
+        automaton.states.push(state);
+
+        let outgoing: Vec<(usize, Transition<()>)> = automaton
+            .transitions
+            .iter()
+            .filter(|((state, _), _)| *state == destination)
+            .map(|((_, symbol), transition)| (*symbol, transition.clone()))
+            .collect();
+        for (symbol, transition) in outgoing {
+            automaton.transitions.insert((copy, symbol), transition);
+        }
+        automaton.transitions.get_mut(&key).unwrap().destinations[0].1 = copy;
+        assert_eq!(automaton.num_of_states(), num_of_states + 1);
+
+        automaton.reduce_states();
+        assert_eq!(automaton.num_of_states(), num_of_states);
+
+        for (term, expected) in terms.iter().zip(&expected) {
+            assert_eq!(
+                &announcements(&automaton, term),
+                expected,
+                "The announcements for {term} changed"
+            );
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use std::cell::OnceCell;
+use std::cell::RefCell;
+
+use mcrl2::aterm::ATerm;
+use mcrl2::aterm::TermPool;
+use mcrl2::data::DataExpression;
+
+use crate::utilities::instantiate;
+use crate::utilities::replace_at;
+use crate::utilities::to_aterm_substitution;
+use crate::utilities::ExplicitPosition;
+use crate::Matcher;
+use crate::RewriteEngine;
+use crate::RewriteSpecification;
+use crate::Rule;
+
+/// A rule that matches the subterm at the given position, with the substitution for its variables.
+pub(crate) type Redex = (Rule, ExplicitPosition, Vec<(ATerm, ATerm)>);
+
+/// Finds the redexes of a term, which is used by all rewriters to implement
+/// [crate::RewriteEngine::rewrite_once]. The matcher is only constructed when
+/// it is used for the first time.
+pub(crate) struct SingleStep {
+    rules: Vec<Rule>,
+    matcher: OnceCell<Matcher>,
+}
+
+impl SingleStep {
+    pub fn new(spec: &RewriteSpecification) -> SingleStep {
+        SingleStep {
+            rules: spec.rewrite_rules.clone(),
+            matcher: OnceCell::new(),
+        }
+    }
+
+    /// Returns the rules that match a subterm of the given term, without
+    /// checking their conditions. The redexes are in leftmost outermost order,
+    /// and the rules at the same position are in the order of the specification.
+    pub fn redexes(&self, term: &DataExpression) -> Vec<Redex> {
+        let matcher = self.matcher.get_or_init(|| {
+            let patterns: Vec<DataExpression> = self.rules.iter().map(|rule| rule.lhs.clone()).collect();
+            Matcher::new(&patterns)
+        });
+
+        let mut matches: Vec<_> = matcher.matches(term).collect();
+        matches.sort_by(|(left_id, left, _), (right_id, right, _)| (left, left_id).cmp(&(right, right_id)));
+
+        matches
+            .into_iter()
+            .map(|(id, position, substitution)| {
+                (self.rules[id].clone(), position, to_aterm_substitution(&substitution))
+            })
+            .collect()
+    }
+}
+
+/// Applies the first of the given redexes for which the conditions hold, where
+/// the conditions are rewritten using the given rewriter, which also provides
+/// the term pool. Returns None when none of the redexes can be rewritten.
+pub(crate) fn apply_first<R: RewriteEngine>(
+    rewriter: &mut R,
+    term_pool: fn(&R) -> &RefCell<TermPool>,
+    term: &DataExpression,
+    redexes: Vec<Redex>,
+) -> Option<DataExpression> {
+    'redexes: for (rule, position, substitution) in redexes {
+        for condition in &rule.conditions {
+            let lhs = instantiate(&mut term_pool(rewriter).borrow_mut(), &condition.lhs, &substitution);
+            let rhs = instantiate(&mut term_pool(rewriter).borrow_mut(), &condition.rhs, &substitution);
+            if (rewriter.rewrite(lhs) == rewriter.rewrite(rhs)) != condition.equality {
+                continue 'redexes;
+            }
+        }
+
+        let mut tp = term_pool(rewriter).borrow_mut();
+        let contractum: ATerm = instantiate(&mut tp, &rule.rhs, &substitution).into();
+        return Some(replace_at(&mut tp, &term.copy(), &position, contractum).into());
+    }
+
+    None
+}
@@ -0,0 +1,85 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+use std::str::FromStr;
+
+use mcrl2::aterm::TermPool;
+
+use crate::InnermostRewriter;
+use crate::JittyRewriter;
+use crate::LazyRewriter;
+use crate::RewriteEngine;
+use crate::RewriteSpecification;
+use crate::SabreRewriter;
+use crate::SharedRewriteSpecification;
+
+/// The order in which the subterms of a term are rewritten.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Strategy {
+    /// Rewrites all arguments to normal form before the term itself, see [InnermostRewriter].
+    #[default]
+    Innermost,
+
+    /// Rewrites the outermost redexes first, see [SabreRewriter].
+    Outermost,
+
+    /// Only rewrites the arguments that are needed to match a rule, see [LazyRewriter].
+    Lazy,
+
+    /// Rewrites the arguments following the strategy annotations of the jitty rewriter of mCRL2, see [JittyRewriter].
+    Jitty,
+}
+
+impl Strategy {
+    /// Creates a rewriter for the given specification that uses this strategy.
+    pub fn rewriter(self, tp: Rc<RefCell<TermPool>>, spec: &RewriteSpecification) -> Box<dyn RewriteEngine> {
+        match self {
+            Strategy::Innermost => Box::new(InnermostRewriter::new(spec)),
+            Strategy::Outermost => Box::new(SabreRewriter::new(spec)),
+            Strategy::Lazy => Box::new(LazyRewriter::new(tp, spec)),
+            Strategy::Jitty => Box::new(JittyRewriter::new(tp, spec)),
+        }
+    }
+
+    /// Creates a rewriter for the given shared specification that uses this
+    /// strategy, where the set automata are shared with the other rewriters
+    /// created from the same specification.
+    pub fn shared_rewriter(
+        self,
+        tp: Rc<RefCell<TermPool>>,
+        spec: &SharedRewriteSpecification,
+    ) -> Box<dyn RewriteEngine> {
+        match self {
+            Strategy::Innermost => Box::new(InnermostRewriter::from_shared(spec)),
+            Strategy::Outermost => Box::new(SabreRewriter::from_shared(spec)),
+            Strategy::Lazy | Strategy::Jitty => self.rewriter(tp, &spec.local()),
+        }
+    }
+}
+
+impl FromStr for Strategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "innermost" => Ok(Strategy::Innermost),
+            "outermost" => Ok(Strategy::Outermost),
+            "lazy" => Ok(Strategy::Lazy),
+            "jitty" => Ok(Strategy::Jitty),
+            _ => Err(format!(
+                "Unknown strategy {s}, expected one of innermost, outermost, lazy or jitty"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Strategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Strategy::Innermost => write!(f, "innermost"),
+            Strategy::Outermost => write!(f, "outermost"),
+            Strategy::Lazy => write!(f, "lazy"),
+            Strategy::Jitty => write!(f, "jitty"),
+        }
+    }
+}
@@ -0,0 +1,285 @@
+use std::fmt;
+
+use ahash::AHashMap;
+use ahash::AHashSet;
+use mcrl2::aterm::ATermRef;
+use mcrl2::data::is_data_application;
+use mcrl2::data::is_data_function_symbol;
+use mcrl2::data::is_data_machine_number;
+use mcrl2::data::is_data_variable;
+use mcrl2::data::DataFunctionSymbol;
+use mcrl2::data::DataFunctionSymbolRef;
+
+use crate::RewriteSpecification;
+use crate::Rule;
+
+/// The result of [RewriteSpecification::check_termination].
+#[derive(Clone, Debug)]
+pub struct TerminationReport {
+    /// The pairs f > g of the precedence that was used, for every f that calls g.
+    pub precedence: Vec<(DataFunctionSymbol, DataFunctionSymbol)>,
+
+    /// The rules for which the left hand side is not greater than the right hand side or one of its conditions.
+    pub offending_rules: Vec<Rule>,
+}
+
+impl TerminationReport {
+    /// Returns true iff termination has been proven.
+    pub fn is_terminating(&self) -> bool {
+        self.offending_rules.is_empty()
+    }
+}
+
+impl RewriteSpecification {
+    /// Tries to prove termination using the lexicographic path ordering, which
+    /// is the recursive path ordering where arguments are compared
+    /// lexicographically. The precedence is derived from the rules, where f > g
+    /// whenever a rule for f uses g, but g does not (indirectly) use f.
+    ///
+    /// The check is conservative, for example mutually recursive functions are
+    /// always reported, but a successful proof guarantees that every strategy
+    /// terminates. Conditional rules must also be greater than their
+    /// conditions, since these are rewritten before the rule is applied.
+    pub fn check_termination(&self) -> TerminationReport {
+        let precedence = Precedence::new(&self.rewrite_rules);
+
+        let offending_rules = self
+            .rewrite_rules
+            .iter()
+            .filter(|rule| {
+                let lhs: &ATermRef<'_> = &rule.lhs.copy();
+                !(precedence.greater(lhs, &rule.rhs.copy())
+                    && rule.conditions.iter().all(|condition| {
+                        precedence.greater(lhs, &condition.lhs.copy()) && precedence.greater(lhs, &condition.rhs.copy())
+                    }))
+            })
+            .cloned()
+            .collect();
+
+        TerminationReport {
+            precedence: precedence.pairs(),
+            offending_rules,
+        }
+    }
+}
+
+/// The precedence on function symbols, identified by their operation id.
+struct Precedence {
+    /// The symbols that occur in the right hand sides and conditions of the rules for a symbol.
+    calls: AHashMap<usize, AHashSet<usize>>,
+
+    /// The symbols that are reachable from a symbol in the call graph.
+    reachable: AHashMap<usize, AHashSet<usize>>,
+
+    symbols: AHashMap<usize, DataFunctionSymbol>,
+}
+
+impl Precedence {
+    fn new(rules: &[Rule]) -> Precedence {
+        let mut calls: AHashMap<usize, AHashSet<usize>> = AHashMap::new();
+        let mut symbols = AHashMap::new();
+
+        for rule in rules {
+            let lhs = rule.lhs.copy();
+            let Some(head) = head_symbol(&lhs) else {
+                continue;
+            };
+            symbols.insert(head.operation_id(), head.protect());
+
+            let called = calls.entry(head.operation_id()).or_default();
+            for term in [&rule.rhs].into_iter().chain(
+                rule.conditions
+                    .iter()
+                    .flat_map(|condition| [&condition.lhs, &condition.rhs]),
+            ) {
+                for subterm in term.iter() {
+                    if is_data_function_symbol(&subterm) {
+                        let symbol: DataFunctionSymbolRef<'_> = subterm.into();
+                        called.insert(symbol.operation_id());
+                        symbols.insert(symbol.operation_id(), symbol.protect());
+                    }
+                }
+            }
+        }
+
+        // Compute the transitive closure of the call graph.
+        let mut reachable: AHashMap<usize, AHashSet<usize>> = AHashMap::new();
+        for &symbol in calls.keys() {
+            let mut visited = AHashSet::new();
+            let mut stack = vec![symbol];
+            while let Some(current) = stack.pop() {
+                for &next in calls.get(&current).into_iter().flatten() {
+                    if visited.insert(next) {
+                        stack.push(next);
+                    }
+                }
+            }
+
+            reachable.insert(symbol, visited);
+        }
+
+        Precedence {
+            calls,
+            reachable,
+            symbols,
+        }
+    }
+
+    /// Returns true iff f > g in the precedence.
+    fn precedes(&self, f: usize, g: usize) -> bool {
+        let reaches = |from: usize, to: usize| {
+            self.reachable
+                .get(&from)
+                .is_some_and(|reachable| reachable.contains(&to))
+        };
+        reaches(f, g) && !reaches(g, f)
+    }
+
+    /// Returns true iff s > t in the lexicographic path ordering.
+    fn greater(&self, s: &ATermRef<'_>, t: &ATermRef<'_>) -> bool {
+        if is_data_variable(s) {
+            return false;
+        }
+
+        if is_data_variable(t) {
+            return s != t && s.iter().any(|subterm| subterm == *t);
+        }
+
+        let Some(f) = head_symbol(s) else {
+            // Only function applications can be compared.
+            return false;
+        };
+        let s_arguments = arguments(s);
+
+        // Some argument of s is greater than or equal to t.
+        if s_arguments
+            .iter()
+            .any(|argument| argument == t || self.greater(argument, t))
+        {
+            return true;
+        }
+
+        if is_data_machine_number(t) {
+            // Machine numbers are smaller than all function symbols.
+            return true;
+        }
+
+        let Some(g) = head_symbol(t) else {
+            return false;
+        };
+        let t_arguments = arguments(t);
+
+        if !t_arguments.iter().all(|argument| self.greater(s, argument)) {
+            return false;
+        }
+
+        if f == g {
+            // Compare the arguments lexicographically.
+            s_arguments.len() == t_arguments.len()
+                && s_arguments
+                    .iter()
+                    .zip(&t_arguments)
+                    .find(|(left, right)| left != right)
+                    .is_some_and(|(left, right)| self.greater(left, right))
+        } else {
+            self.precedes(f.operation_id(), g.operation_id())
+        }
+    }
+
+    /// Returns the pairs f > g where f calls g directly, ordered by name.
+    fn pairs(&self) -> Vec<(DataFunctionSymbol, DataFunctionSymbol)> {
+        let mut result: Vec<(DataFunctionSymbol, DataFunctionSymbol)> = self
+            .calls
+            .iter()
+            .flat_map(|(&f, called)| called.iter().map(move |&g| (f, g)))
+            .filter(|&(f, g)| self.precedes(f, g))
+            .map(|(f, g)| (self.symbols[&f].clone(), self.symbols[&g].clone()))
+            .collect();
+
+        result.sort_by(|(f1, g1), (f2, g2)| f1.structural_cmp(f2).then_with(|| g1.structural_cmp(g2)));
+        result
+    }
+}
+
+/// Returns the head symbol of a function symbol or an application of a function symbol.
+fn head_symbol<'a>(t: &'a ATermRef<'_>) -> Option<DataFunctionSymbolRef<'a>> {
+    if is_data_function_symbol(t) {
+        Some(t.copy().into())
+    } else if is_data_application(t) && is_data_function_symbol(&t.arg(0)) {
+        Some(t.arg(0).upgrade(t).into())
+    } else {
+        None
+    }
+}
+
+/// Returns the arguments of an application, or nothing for other terms.
+fn arguments<'a>(t: &ATermRef<'a>) -> Vec<ATermRef<'a>> {
+    if is_data_application(t) {
+        t.arguments().skip(1).map(|argument| argument.upgrade(t)).collect()
+    } else {
+        Vec::new()
+    }
+}
+
+impl fmt::Display for TerminationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_terminating() {
+            writeln!(
+                f,
+                "Terminating with the lexicographic path ordering using the precedence"
+            )?;
+            for (greater, smaller) in &self.precedence {
+                writeln!(f, "  {} > {}", greater, smaller)?;
+            }
+        } else {
+            writeln!(f, "Could not prove termination for the rules")?;
+            for rule in &self.offending_rules {
+                writeln!(f, "  {}", rule)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use mcrl2::aterm::TermPool;
+
+    use crate::test_utility::create_rewrite_rule;
+
+    #[test]
+    fn test_termination() {
+        let mut tp = TermPool::new();
+
+        let spec = RewriteSpecification {
+            rewrite_rules: vec![
+                create_rewrite_rule(&mut tp, "plus(zero, y)", "y", &["y"]).unwrap(),
+                create_rewrite_rule(&mut tp, "plus(s(x), y)", "s(plus(x, y))", &["x", "y"]).unwrap(),
+                create_rewrite_rule(&mut tp, "fib(s(s(x)))", "plus(fib(s(x)), fib(x))", &["x"]).unwrap(),
+            ],
+            constructors: vec![],
+        };
+
+        let report = spec.check_termination();
+        assert!(report.is_terminating(), "{report}");
+
+        let looping = create_rewrite_rule(&mut tp, "f(x)", "f(g(x))", &["x"]).unwrap();
+        let spec = RewriteSpecification {
+            rewrite_rules: vec![
+                spec.rewrite_rules[0].clone(),
+                looping.clone(),
+                create_rewrite_rule(&mut tp, "even(s(x))", "odd(x)", &["x"]).unwrap(),
+                create_rewrite_rule(&mut tp, "odd(s(x))", "even(x)", &["x"]).unwrap(),
+            ],
+            constructors: vec![],
+        };
+
+        // The mutually recursive even and odd are not supported.
+        let report = spec.check_termination();
+        assert_eq!(report.offending_rules.len(), 3);
+        assert_eq!(report.offending_rules[0], looping);
+    }
+}
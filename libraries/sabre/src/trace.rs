@@ -0,0 +1,125 @@
+use std::fmt;
+
+use mcrl2::data::DataExpression;
+
+use crate::utilities::ExplicitPosition;
+use crate::Rule;
+
+/// A single rewrite step performed by a rewriter.
+#[derive(Clone, Debug)]
+pub struct RewriteEvent {
+    /// The rule that was applied.
+    pub rule: Rule,
+
+    /// The position of the redex in the term that is being rewritten, where
+    /// the previous steps have already been applied. This is None when the
+    /// redex is not a subterm of that term, for example when rewriting a
+    /// condition, or when the rewriter does not keep track of positions.
+    pub position: Option<ExplicitPosition>,
+
+    /// The term that matched the left hand side of the rule.
+    pub redex: DataExpression,
+
+    /// The instantiated right hand side of the rule.
+    pub contractum: DataExpression,
+}
+
+/// The callback that is called for every rewrite step.
+pub type TraceCallback = Box<dyn FnMut(&RewriteEvent) + Send>;
+
+/// Keeps track of the trace callback and the position of the term that is
+/// currently being rewritten. All operations are cheap when there is no
+/// callback.
+#[derive(Default)]
+pub struct Tracer {
+    callback: Option<TraceCallback>,
+
+    /// The position of the current term, None when it is not a subterm of the input term.
+    position: Option<Vec<usize>>,
+}
+
+impl Tracer {
+    /// Creates a tracer for the given callback, where nothing is traced when it is None.
+    pub fn new(callback: Option<TraceCallback>) -> Tracer {
+        Tracer {
+            callback,
+            position: Some(Vec::new()),
+        }
+    }
+
+    /// Returns true iff the rewrite steps must be reported.
+    pub fn is_enabled(&self) -> bool {
+        self.callback.is_some()
+    }
+
+    /// Returns the callback of this tracer.
+    pub fn into_callback(self) -> Option<TraceCallback> {
+        self.callback
+    }
+
+    /// Indicates that the given (1 indexed) argument of the current term is rewritten.
+    pub fn enter(&mut self, index: usize) {
+        if self.is_enabled() {
+            if let Some(position) = &mut self.position {
+                position.push(index);
+            }
+        }
+    }
+
+    /// Indicates that the argument entered last has been rewritten.
+    pub fn leave(&mut self) {
+        if self.is_enabled() {
+            if let Some(position) = &mut self.position {
+                position.pop();
+            }
+        }
+    }
+
+    /// Indicates that the terms that are rewritten next are not subterms of the current term, returns the
+    /// current position that must be restored by [Tracer::attach].
+    pub fn detach(&mut self) -> Option<Vec<usize>> {
+        self.position.take()
+    }
+
+    /// Restores the position returned by [Tracer::detach].
+    pub fn attach(&mut self, position: Option<Vec<usize>>) {
+        self.position = position;
+    }
+
+    /// Reports a rewrite step, where the redex is at the given position relative to the current term. The
+    /// contractum is only computed when the step is actually reported.
+    pub fn rewritten(
+        &mut self,
+        rule: &Rule,
+        redex: &DataExpression,
+        relative: Option<&[usize]>,
+        contractum: impl FnOnce() -> DataExpression,
+    ) {
+        if let Some(callback) = &mut self.callback {
+            let position = match (&self.position, relative) {
+                (Some(position), Some(relative)) => Some(ExplicitPosition::new(
+                    &position.iter().chain(relative).copied().collect::<Vec<usize>>(),
+                )),
+                _ => None,
+            };
+
+            callback(&RewriteEvent {
+                rule: rule.clone(),
+                position,
+                redex: redex.clone(),
+                contractum: contractum(),
+            });
+        }
+    }
+}
+
+impl fmt::Display for RewriteEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.position {
+            Some(position) => write!(f, "{} at {}", self.redex, position)?,
+            None => write!(f, "{}", self.redex)?,
+        }
+
+        write!(f, " => {} using rule {}", self.contractum, self.rule)
+    }
+}
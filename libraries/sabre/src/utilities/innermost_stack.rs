@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::fmt;
 
 use itertools::Itertools;
@@ -15,7 +16,6 @@ use mcrl2::data::DataExpression;
 use mcrl2::data::DataExpressionRef;
 use mcrl2::data::DataFunctionSymbolRef;
 
-use crate::utilities::PositionIndexed;
 use crate::Rule;
 
 use super::create_var_map;
@@ -71,7 +71,7 @@ impl InnermostStack {
             first = false;
         }
         trace!(
-            "\t applied stack size: {}, substitution: {}, stack: [{}]",
+            "\t applied stack size: {}, slots: {}, stack: [{}]",
             rhs_stack.stack_size,
             rhs_stack.variables.iter().format_with(", ", |element, f| {
                 f(&format_args!("{} -> {}", element.0, element.1))
@@ -79,6 +79,14 @@ impl InnermostStack {
             rhs_stack.innermost_stack.read().iter().format("\n")
         );
 
+        // Bind the variables of the left hand side to their slots, every step takes one argument of an earlier slot.
+        let mut slots: Vec<ATermRef<'_>> = Vec::with_capacity(rhs_stack.bindings.len() + 1);
+        slots.push(term.copy().into());
+        for (parent, index) in &rhs_stack.bindings {
+            let t = slots[*parent].arg(*index).upgrade(term);
+            slots.push(t);
+        }
+
         debug_assert!(
             rhs_stack.stack_size != 1 || rhs_stack.variables.len() <= 1,
             "There can only be a single variable in the right hand side"
@@ -86,12 +94,12 @@ impl InnermostStack {
         if rhs_stack.stack_size == 1 && rhs_stack.variables.len() == 1 {
             // This is a special case where we place the result on the correct position immediately.
             // The right hand side is only a variable
-            let t: ATermRef<'_> = write_terms.protect(&term.get_position(&rhs_stack.variables[0].0));
+            let t: ATermRef<'_> = write_terms.protect(&slots[rhs_stack.variables[0].0]);
             write_terms[result_index] = t.into();
         } else {
-            for (position, index) in &rhs_stack.variables {
-                // Add the positions to the stack.
-                let t = write_terms.protect(&term.get_position(position));
+            for (slot, index) in &rhs_stack.variables {
+                // Add the bound terms to the stack.
+                let t = write_terms.protect(&slots[*slot]);
                 write_terms[top_of_stack + index - 1] = t.into();
             }
         }
@@ -189,10 +197,19 @@ impl fmt::Display for Config {
 }
 
 /// A stack for the right-hand side.
+///
+/// The variables of the left hand side are bound to slots, where slot zero is
+/// the matched term itself and every other slot is an argument of an earlier
+/// slot. The variables in the right hand side then refer to these slots, which
+/// avoids looking up the position of every variable occurrence from the root
+/// of the matched term.
 pub struct RHSStack {
-    /// The innermost rewrite stack for the right hand side and the positions that must be added to the stack.
+    /// The innermost rewrite stack for the right hand side and the slots that must be added to the stack.
     innermost_stack: Protected<Vec<Config>>,
-    variables: Vec<(ExplicitPosition, usize)>,
+    variables: Vec<(usize, usize)>,
+
+    /// For every slot, except the first, the slot of its parent and its argument index.
+    bindings: Vec<(usize, usize)>,
     stack_size: usize,
 }
 
@@ -204,6 +221,8 @@ impl RHSStack {
         // Compute the extra information for the InnermostRewriter.
         let mut innermost_stack: Protected<Vec<Config>> = Protected::new(vec![]);
         let mut variables = vec![];
+        let mut bindings = vec![];
+        let mut slots: HashMap<ExplicitPosition, usize> = HashMap::from([(ExplicitPosition::empty_pos(), 0)]);
         let mut stack_size = 0;
 
         for (term, position) in PositionIterator::new(rule.rhs.copy().into()) {
@@ -214,13 +233,10 @@ impl RHSStack {
             }
 
             if is_data_variable(&term) {
-                variables.push((
-                    var_map
-                        .get(&term.protect())
-                        .expect("All variables in the right hand side must occur in the left hand side")
-                        .clone(),
-                    stack_size,
-                ));
+                let position = var_map
+                    .get(&term.protect())
+                    .expect("All variables in the right hand side must occur in the left hand side");
+                variables.push((RHSStack::bind(&mut slots, &mut bindings, position), stack_size));
                 stack_size += 1;
             } else if is_data_machine_number(&term) {
                 // Skip SortId(@NoValue) and OpId
@@ -240,7 +256,31 @@ impl RHSStack {
             innermost_stack,
             stack_size,
             variables,
+            bindings,
+        }
+    }
+
+    /// Returns the slot for the given position, adding the bindings for the position and its prefixes when needed.
+    fn bind(
+        slots: &mut HashMap<ExplicitPosition, usize>,
+        bindings: &mut Vec<(usize, usize)>,
+        position: &ExplicitPosition,
+    ) -> usize {
+        if let Some(slot) = slots.get(position) {
+            return *slot;
         }
+
+        let (index, prefix) = position
+            .indices
+            .split_last()
+            .expect("The empty position is always bound");
+        let parent = RHSStack::bind(slots, bindings, &ExplicitPosition::new(prefix));
+
+        // Note that positions are 1 indexed.
+        bindings.push((parent, index - 1));
+        let slot = bindings.len();
+        slots.insert(position.clone(), slot);
+        slot
     }
 
     /// Evaluate the rhs stack for the given term and returns the result.
@@ -319,6 +359,7 @@ impl Clone for RHSStack {
 
         Self {
             variables: self.variables.clone(),
+            bindings: self.bindings.clone(),
             stack_size: self.stack_size,
             innermost_stack,
         }
@@ -364,6 +405,13 @@ mod tests {
 
         assert_eq!(rhs_stack.stack_size, 5, "The stack size does not match");
 
+        // Both occurrences of N share the slot of s(N).1, which is bound once.
+        assert_eq!(rhs_stack.bindings, vec![(0, 1), (1, 1)], "The bindings do not match");
+        assert!(
+            rhs_stack.variables.iter().all(|(slot, _)| *slot == 2),
+            "The variables should refer to the same slot"
+        );
+
         // Test the evaluation
         let lhs = tp.from_string("fact(s(a))").unwrap();
         let lhs_expression = to_untyped_data_expression(&mut tp, &lhs, &AHashSet::new());
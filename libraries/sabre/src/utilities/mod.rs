@@ -1,11 +1,13 @@
 mod configuration_stack;
 mod innermost_stack;
 mod position;
+mod replace;
 mod semi_compressed_tree;
 mod substitution;
 
 pub(crate) use configuration_stack::*;
 pub(crate) use innermost_stack::*;
 pub use position::*;
+pub use replace::*;
 pub use semi_compressed_tree::*;
 pub use substitution::*;
@@ -0,0 +1,114 @@
+use std::ops::Range;
+
+use mcrl2::aterm::ATerm;
+use mcrl2::aterm::ATermRef;
+use mcrl2::aterm::BottomUpBuilder;
+use mcrl2::aterm::Symbol;
+use mcrl2::aterm::TermPool;
+
+use super::substitute;
+use super::ExplicitPosition;
+
+/// Returns the term where the subterm at the given position is replaced by the new subterm.
+pub fn replace_at(tp: &mut TermPool, term: &ATermRef<'_>, position: &ExplicitPosition, new_subterm: ATerm) -> ATerm {
+    substitute(tp, term, new_subterm, &position.indices)
+}
+
+/// Returns the term where the subterms at the given positions are replaced by
+/// the corresponding new subterms, in a single traversal of the term. The
+/// positions must be independent, i.e., no position is a prefix of another.
+///
+/// Only the terms on the paths to the positions are reconstructed, all other
+/// subterms are reused as is.
+pub fn replace_all_at(tp: &mut TermPool, term: &ATermRef<'_>, replacements: &[(ExplicitPosition, ATerm)]) -> ATerm {
+    let mut replacements: Vec<&(ExplicitPosition, ATerm)> = replacements.iter().collect();
+    replacements.sort_unstable_by(|(left, _), (right, _)| left.cmp(right));
+
+    debug_assert!(
+        replacements
+            .windows(2)
+            .all(|pair| !pair[1].0.indices.starts_with(&pair[0].0.indices)),
+        "The positions of the replacements must be independent"
+    );
+
+    let mut builder = BottomUpBuilder::new();
+
+    // The subterms that must be visited, where all the replacements in the range have the position of the subterm as prefix.
+    let mut stack = vec![Work::Visit(term.copy(), 0, 0..replacements.len())];
+    while let Some(work) = stack.pop() {
+        match work {
+            Work::Visit(subterm, depth, range) => {
+                if range.is_empty() {
+                    builder.push(&subterm);
+                } else if replacements[range.start].0.len() == depth {
+                    builder.push(&replacements[range.start].1);
+                } else {
+                    let symbol = subterm.get_head_symbol();
+                    stack.push(Work::Construct(symbol.protect()));
+
+                    // Since the replacements are sorted, the ones below every argument form a consecutive range.
+                    let mut start = range.start;
+                    let mut arguments = Vec::with_capacity(symbol.arity());
+                    for (index, argument) in subterm.arguments().enumerate() {
+                        let end = start
+                            + replacements[start..range.end]
+                                .iter()
+                                .take_while(|(position, _)| position.indices[depth] == index + 1)
+                                .count();
+
+                        arguments.push(Work::Visit(argument.upgrade(term), depth + 1, start..end));
+                        start = end;
+                    }
+
+                    // Arguments are popped from the stack in order.
+                    stack.extend(arguments.into_iter().rev());
+                }
+            }
+            Work::Construct(symbol) => {
+                builder.push_symbol(tp, &symbol, symbol.arity());
+            }
+        }
+    }
+
+    builder.pop().expect("The builder should contain the result")
+}
+
+/// The work that remains to be done to construct the result of [replace_all_at].
+enum Work<'a> {
+    Visit(ATermRef<'a>, usize, Range<usize>),
+    Construct(Symbol),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replace_at() {
+        let mut tp = TermPool::new();
+
+        let t = tp.from_string("f(a, g(b, c), h(d))").unwrap();
+        let zero = tp.from_string("0").unwrap();
+        let one = tp.from_string("1").unwrap();
+
+        let result = replace_at(&mut tp, &t, &ExplicitPosition::new(&[2, 1]), zero.clone());
+        assert_eq!(result, tp.from_string("f(a, g(0, c), h(d))").unwrap());
+
+        let result = replace_all_at(
+            &mut tp,
+            &t,
+            &[
+                (ExplicitPosition::new(&[3, 1]), one.clone()),
+                (ExplicitPosition::new(&[2, 1]), zero.clone()),
+                (ExplicitPosition::new(&[1]), one.clone()),
+            ],
+        );
+        assert_eq!(result, tp.from_string("f(1, g(0, c), h(1))").unwrap());
+
+        let result = replace_all_at(&mut tp, &t, &[]);
+        assert_eq!(result, t);
+
+        let result = replace_all_at(&mut tp, &t, &[(ExplicitPosition::empty_pos(), zero.clone())]);
+        assert_eq!(result, zero);
+    }
+}
@@ -10,7 +10,9 @@ use mcrl2::aterm::Symbol;
 use mcrl2::aterm::TermBuilder;
 use mcrl2::aterm::TermPool;
 use mcrl2::aterm::Yield;
+use mcrl2::data::is_data_abstraction;
 use mcrl2::data::is_data_variable;
+use mcrl2::data::DataAbstractionRef;
 use mcrl2::data::DataVariable;
 
 /// A SemiCompressedTermTree (SCTT) is a mix between a [ATerm] and a syntax tree and is used
@@ -100,7 +102,24 @@ impl SemiCompressedTermTree {
         t: &ATermRef<'_>,
         var_map: &HashMap<DataVariable, ExplicitPosition>,
     ) -> SemiCompressedTermTree {
-        if is_data_variable(t) {
+        SemiCompressedTermTree::from_term_bound(t, var_map, &[])
+    }
+
+    /// The implementation of [SemiCompressedTermTree::from_term], where the
+    /// bound variables of quantifiers are kept as is.
+    fn from_term_bound(
+        t: &ATermRef<'_>,
+        var_map: &HashMap<DataVariable, ExplicitPosition>,
+        bound: &[DataVariable],
+    ) -> SemiCompressedTermTree {
+        if is_data_variable(t)
+            && bound.iter().any(|variable| {
+                let variable: &ATermRef<'_> = variable;
+                variable == t
+            })
+        {
+            Compressed(t.protect())
+        } else if is_data_variable(t) {
             Variable(
                 var_map
                     .get(&t.protect())
@@ -110,9 +129,14 @@ impl SemiCompressedTermTree {
         } else if t.arguments().is_empty() {
             Compressed(t.protect())
         } else {
+            let mut bound = bound.to_vec();
+            if is_data_abstraction(t) {
+                bound.extend(DataAbstractionRef::from(t.copy()).variables());
+            }
+
             let children = t
                 .arguments()
-                .map(|c| SemiCompressedTermTree::from_term(&c, var_map))
+                .map(|c| SemiCompressedTermTree::from_term_bound(&c, var_map, &bound))
                 .collect();
             let node = ExplicitNode {
                 head: t.get_head_symbol().protect(),
@@ -1,15 +1,19 @@
 use ahash::AHashSet;
 use mcrl2::aterm::ATerm;
 use mcrl2::aterm::ATermRef;
-use mcrl2::aterm::Protected;
+use mcrl2::aterm::BottomUpBuilder;
 use mcrl2::aterm::TermBuilder;
 use mcrl2::aterm::TermPool;
 use mcrl2::aterm::Yield;
+use mcrl2::data::is_data_application;
+use mcrl2::data::is_data_variable;
 use mcrl2::data::DataExpression;
 use mcrl2::data::DataFunctionSymbol;
 use mcrl2::data::DataVariable;
 
-pub type SubstitutionBuilder = Protected<Vec<ATermRef<'static>>>;
+use crate::Substitution;
+
+pub type SubstitutionBuilder = BottomUpBuilder;
 
 /// Creates a new term where a subterm is replaced with another term.
 ///
@@ -26,10 +30,12 @@ pub type SubstitutionBuilder = Protected<Vec<ATermRef<'static>>>;
 /// until we have arrived at a and replace it with 0. We then construct s(0)
 /// and then construct s(s(0)).
 pub fn substitute(tp: &mut TermPool, t: &ATermRef<'_>, new_subterm: ATerm, p: &[usize]) -> ATerm {
-    let mut args = Protected::new(vec![]);
-    substitute_rec(tp, t, new_subterm, p, &mut args, 0)
+    let mut builder = SubstitutionBuilder::new();
+    substitute_with(&mut builder, tp, t, new_subterm, p)
 }
 
+/// The same as [substitute], but reuses the given builder. Does not use
+/// recursion, such that it can be applied at deep positions.
 pub fn substitute_with(
     builder: &mut SubstitutionBuilder,
     tp: &mut TermPool,
@@ -37,47 +43,33 @@ pub fn substitute_with(
     new_subterm: ATerm,
     p: &[usize],
 ) -> ATerm {
-    substitute_rec(tp, t, new_subterm, p, builder, 0)
-}
+    builder.clear();
 
-/// The recursive implementation for subsitute
-///
-/// 'depth'         -   Used to keep track of the depth in 't'. Function should be called with
-///                     'depth' = 0.
-fn substitute_rec(
-    tp: &mut TermPool,
-    t: &ATermRef<'_>,
-    new_subterm: ATerm,
-    p: &[usize],
-    args: &mut Protected<Vec<ATermRef<'static>>>,
-    depth: usize,
-) -> ATerm {
-    if p.len() == depth {
-        // in this case we have arrived at the place where 'new_subterm' needs to be injected
-        new_subterm
-    } else {
-        // else recurse deeper into 't'
-        let new_child_index = p[depth] - 1;
-        let new_child = substitute_rec(tp, &t.arg(new_child_index), new_subterm, p, args, depth + 1);
-
-        let mut write_args = args.write();
-        for (index, arg) in t.arguments().enumerate() {
-            if index == new_child_index {
-                let t = write_args.protect(&new_child);
-                write_args.push(t);
-            } else {
-                let t = write_args.protect(&arg);
-                write_args.push(t);
-            }
+    // Push the arguments before the position for every term on the path, and remember the terms on the path.
+    let mut path = Vec::with_capacity(p.len());
+    let mut current = t.copy();
+    for index in p {
+        for argument in current.arguments().take(index - 1) {
+            builder.push(&argument);
         }
 
-        let result = tp.create(&t.get_head_symbol(), &write_args);
-        drop(write_args);
+        let next = current.arg(index - 1).upgrade(t); // Note that positions are 1 indexed.
+        path.push(current);
+        current = next;
+    }
+
+    // Construct the terms on the path bottom up, with the new subterm as the argument at the position.
+    builder.push(&new_subterm);
+    for (subterm, index) in path.iter().zip(p).rev() {
+        for argument in subterm.arguments().skip(*index) {
+            builder.push(&argument);
+        }
 
-        // TODO: When write is dropped we check whether all terms where inserted, but this clear violates that assumption.
-        args.write().clear();
-        result
+        let symbol = subterm.get_head_symbol();
+        builder.push_symbol(tp, &symbol, symbol.arity());
     }
+
+    builder.pop().expect("The builder should contain the result")
 }
 
 /// Converts an [ATerm] to an untyped data expression.
@@ -115,6 +107,96 @@ pub fn to_untyped_data_expression(tp: &mut TermPool, t: &ATerm, variables: &AHas
         .into()
 }
 
+/// Matches the pattern against the term and extends the substitution, returns false when it does not match.
+pub fn match_term(pattern: &ATermRef<'_>, term: &ATermRef<'_>, substitution: &mut Vec<(ATerm, ATerm)>) -> bool {
+    if is_data_variable(pattern) {
+        if let Some((_, value)) = substitution.iter().find(|(variable, _)| variable.copy() == *pattern) {
+            value.copy() == *term
+        } else {
+            substitution.push((pattern.protect(), term.protect()));
+            true
+        }
+    } else if is_data_application(pattern) {
+        is_data_application(term)
+            && pattern.get_head_symbol() == term.get_head_symbol()
+            && pattern
+                .arguments()
+                .zip(term.arguments())
+                .all(|(pattern, term)| match_term(&pattern, &term, substitution))
+    } else {
+        pattern == term
+    }
+}
+
+/// Returns the value of the given variable in the substitution.
+pub fn lookup<'a>(substitution: &'a [(ATerm, ATerm)], variable: &ATermRef<'_>) -> Option<&'a ATerm> {
+    substitution
+        .iter()
+        .find(|(name, _)| name.copy() == *variable)
+        .map(|(_, value)| value)
+}
+
+/// Converts a substitution into the representation that is used by [match_term] and [instantiate].
+pub fn to_aterm_substitution(substitution: &Substitution) -> Vec<(ATerm, ATerm)> {
+    substitution
+        .iter()
+        .map(|(variable, value)| (variable.clone().into(), value.clone().into()))
+        .collect()
+}
+
+/// Replaces the variables in the given term by their value in the substitution.
+///
+/// Subterms in which no variable of the substitution occurs are reused as is,
+/// so only the terms on the paths to the replaced variables are constructed,
+/// and the term itself is returned when none of the variables occur in it.
+pub fn instantiate(tp: &mut TermPool, term: &DataExpression, substitution: &[(ATerm, ATerm)]) -> DataExpression {
+    let root: &ATerm = term;
+
+    // The subterms that must be visited, and true for the subterms of which all arguments have been visited.
+    let mut stack = vec![(root.copy(), false)];
+
+    // The instantiated subterms, or None when the subterm is unchanged.
+    let mut results: Vec<Option<ATerm>> = Vec::new();
+
+    while let Some((t, visited)) = stack.pop() {
+        if visited {
+            let start = results.len() - t.get_head_symbol().arity();
+
+            let result = if results[start..].iter().all(Option::is_none) {
+                None
+            } else {
+                let arguments: Vec<ATermRef<'_>> = results[start..]
+                    .iter()
+                    .zip(t.arguments())
+                    .map(|(result, argument)| result.as_ref().map_or(argument, |result| result.copy()))
+                    .collect();
+
+                Some(tp.create(&t.get_head_symbol(), &arguments))
+            };
+
+            results.truncate(start);
+            results.push(result);
+        } else if let Some((_, value)) = substitution.iter().find(|(variable, _)| variable.copy() == t) {
+            results.push(Some(value.clone()));
+        } else if t.get_head_symbol().arity() == 0 {
+            results.push(None);
+        } else {
+            // The term is constructed after all its arguments have been visited.
+            let index = stack.len();
+            stack.push((ATermRef::default(), true));
+            for argument in t.arguments().rev() {
+                stack.push((argument.upgrade(root), false));
+            }
+            stack[index].0 = t;
+        }
+    }
+
+    match results.pop().expect("There should be exactly one result") {
+        Some(result) => result.into(),
+        None => term.clone(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::utilities::ExplicitPosition;
@@ -136,6 +218,50 @@ mod tests {
         assert_eq!(t0, result.get_position(&ExplicitPosition::new(&vec![1, 1])).protect());
     }
 
+    #[test]
+    fn test_substitute_arguments() {
+        let mut term_pool = TermPool::new();
+
+        let t = term_pool.from_string("f(a, g(b, c, d), e)").unwrap();
+        let t0 = term_pool.from_string("0").unwrap();
+
+        let result = substitute(&mut term_pool, &t, t0.clone(), &[2, 2]);
+        assert_eq!(result, term_pool.from_string("f(a, g(b, 0, d), e)").unwrap());
+
+        let result = substitute(&mut term_pool, &t, t0.clone(), &[]);
+        assert_eq!(result, t0);
+    }
+
+    #[test]
+    fn test_instantiate() {
+        let mut tp = TermPool::new();
+
+        let variables = AHashSet::from_iter(["x".to_string(), "y".to_string(), "z".to_string()]);
+        let mut parse = |text: &str| {
+            let t = tp.from_string(text).unwrap();
+            to_untyped_data_expression(&mut tp, &t, &variables)
+        };
+
+        let term = parse("f(g(x, a), h(b), y)");
+        let expected = parse("f(g(k(c), a), h(b), y)");
+        let value = parse("k(c)");
+        let x = parse("x");
+        let y = parse("y");
+        let z = parse("z");
+
+        assert_eq!(
+            instantiate(&mut tp, &term, &[(x.into(), value.clone().into())]),
+            expected
+        );
+
+        // Without occurrences of the variables the term is unchanged.
+        assert_eq!(instantiate(&mut tp, &term, &[(z.into(), value.clone().into())]), term);
+        assert_eq!(
+            instantiate(&mut tp, &y, &[(y.clone().into(), value.clone().into())]),
+            value
+        );
+    }
+
     #[test]
     fn test_to_data_expression() {
         let mut term_pool = TermPool::new();
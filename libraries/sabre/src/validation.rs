@@ -0,0 +1,229 @@
+use std::fmt;
+
+use ahash::AHashMap;
+use mcrl2::aterm::ATerm;
+use mcrl2::aterm::ATermRef;
+use mcrl2::aterm::TermPool;
+use mcrl2::data::is_data_abstraction;
+use mcrl2::data::is_data_application;
+use mcrl2::data::is_data_function_symbol;
+use mcrl2::data::is_data_variable;
+use mcrl2::data::DataAbstractionRef;
+use mcrl2::data::DataExpression;
+use mcrl2::data::DataVariable;
+
+use crate::critical_pairs::rename_variables;
+use crate::critical_pairs::unify;
+use crate::set_automaton::unsupported_rule;
+use crate::RewriteSpecification;
+use crate::Rule;
+
+/// A problem with a rewrite specification found by [RewriteSpecification::validate].
+#[derive(Clone, Debug)]
+pub enum SpecificationIssue {
+    /// The variable occurs in the right hand side or the conditions, but not in the left hand side.
+    UnboundVariable(Rule, DataVariable),
+
+    /// The left hand sides of both rules match a common term, so the result depends on the order in which they are tried.
+    OverlappingRules(Rule, Rule),
+
+    /// The function symbol or variable is applied to different numbers of arguments.
+    InconsistentArity(DataExpression, usize, usize),
+
+    /// The rule is ignored by the rewriters, for the given reason.
+    UnsupportedRule(Rule, String),
+}
+
+/// The result of [RewriteSpecification::validate].
+#[derive(Clone, Debug, Default)]
+pub struct ValidationReport {
+    pub issues: Vec<SpecificationIssue>,
+}
+
+impl ValidationReport {
+    /// Returns true iff no issues were found.
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl RewriteSpecification {
+    /// Checks the rewrite rules for common mistakes, such as variables that
+    /// are not bound by the left hand side and rules that are not supported by
+    /// the rewriters. Overlapping left hand sides are also reported, even
+    /// though they are allowed when the conditions are mutually exclusive.
+    pub fn validate(&self, tp: &mut TermPool) -> ValidationReport {
+        let mut issues = Vec::new();
+
+        for rule in &self.rewrite_rules {
+            if let Some(reason) = unsupported_rule(rule) {
+                issues.push(SpecificationIssue::UnsupportedRule(rule.clone(), reason));
+            }
+
+            let mut bound = Vec::new();
+            free_variables(&rule.lhs.copy(), &mut Vec::new(), &mut bound);
+
+            let mut used = Vec::new();
+            for term in [&rule.rhs].into_iter().chain(
+                rule.conditions
+                    .iter()
+                    .flat_map(|condition| [&condition.lhs, &condition.rhs]),
+            ) {
+                free_variables(&term.copy(), &mut Vec::new(), &mut used);
+            }
+
+            for variable in used {
+                if !bound.contains(&variable) {
+                    issues.push(SpecificationIssue::UnboundVariable(rule.clone(), variable.into()));
+                }
+            }
+        }
+
+        // Overlaps at the root of the left hand sides.
+        for (index, rule) in self.rewrite_rules.iter().enumerate() {
+            for other in &self.rewrite_rules[index + 1..] {
+                let renamed = rename_variables(tp, other);
+                if unify(tp, &rule.lhs, &renamed.lhs).is_some() {
+                    issues.push(SpecificationIssue::OverlappingRules(rule.clone(), other.clone()));
+                }
+            }
+        }
+
+        let mut arities: AHashMap<ATerm, usize> = AHashMap::new();
+        let mut inconsistent = Vec::new();
+        for rule in &self.rewrite_rules {
+            for term in [&rule.lhs, &rule.rhs].into_iter().chain(
+                rule.conditions
+                    .iter()
+                    .flat_map(|condition| [&condition.lhs, &condition.rhs]),
+            ) {
+                find_arities(&term.copy(), &mut arities, &mut inconsistent);
+            }
+        }
+
+        for (head, expected, arity) in inconsistent {
+            issues.push(SpecificationIssue::InconsistentArity(head.into(), expected, arity));
+        }
+
+        ValidationReport { issues }
+    }
+}
+
+/// Stores the number of arguments of every function symbol and variable in
+/// the term, and adds the ones that are inconsistent with the stored number to
+/// the inconsistent list, once.
+fn find_arities(t: &ATermRef<'_>, arities: &mut AHashMap<ATerm, usize>, inconsistent: &mut Vec<(ATerm, usize, usize)>) {
+    let (head, arity) = if is_data_application(t) {
+        for argument in t.arguments().skip(1) {
+            find_arities(&argument, arities, inconsistent);
+        }
+
+        (t.arg(0).protect(), t.arguments().len() - 1)
+    } else if is_data_function_symbol(t) || is_data_variable(t) {
+        (t.protect(), 0)
+    } else if is_data_abstraction(t) {
+        let abstraction = DataAbstractionRef::from(t.copy());
+        find_arities(&abstraction.body().into(), arities, inconsistent);
+        return;
+    } else {
+        return;
+    };
+
+    match arities.get(&head) {
+        Some(&expected) => {
+            if expected != arity && !inconsistent.iter().any(|(symbol, _, _)| *symbol == head) {
+                inconsistent.push((head, expected, arity));
+            }
+        }
+        None => {
+            arities.insert(head, arity);
+        }
+    }
+}
+
+/// Adds the variables of the term that are not bound by a quantifier to the result.
+fn free_variables(t: &ATermRef<'_>, bound: &mut Vec<ATerm>, result: &mut Vec<ATerm>) {
+    if is_data_variable(t) {
+        if !bound.iter().any(|variable| variable.copy() == *t) && !result.iter().any(|variable| variable.copy() == *t) {
+            result.push(t.protect());
+        }
+    } else if is_data_abstraction(t) {
+        let abstraction = DataAbstractionRef::from(t.copy());
+        let length = bound.len();
+        bound.extend(
+            abstraction
+                .variables()
+                .iter()
+                .map(|variable| DataExpression::from(variable).into()),
+        );
+        free_variables(&abstraction.body().into(), bound, result);
+        bound.truncate(length);
+    } else if is_data_application(t) {
+        for argument in t.arguments() {
+            free_variables(&argument, bound, result);
+        }
+    }
+}
+
+impl fmt::Display for SpecificationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpecificationIssue::UnboundVariable(rule, variable) => {
+                write!(
+                    f,
+                    "Variable {} is not bound by the left hand side of rule {}",
+                    variable, rule
+                )
+            }
+            SpecificationIssue::OverlappingRules(rule, other) => {
+                write!(f, "The left hand sides of rules {} and {} overlap", rule, other)
+            }
+            SpecificationIssue::InconsistentArity(head, expected, arity) => {
+                write!(f, "{} is applied to both {} and {} arguments", head, expected, arity)
+            }
+            SpecificationIssue::UnsupportedRule(rule, reason) => {
+                write!(f, "Rule {} is not supported, since {}", rule, reason)
+            }
+        }
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for issue in &self.issues {
+            writeln!(f, "{}", issue)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_utility::create_rewrite_rule;
+
+    #[test]
+    fn test_validate() {
+        let mut tp = TermPool::new();
+
+        let spec = RewriteSpecification {
+            rewrite_rules: vec![
+                create_rewrite_rule(&mut tp, "f(x)", "y", &["x", "y"]).unwrap(),
+                create_rewrite_rule(&mut tp, "f(a)", "b", &[]).unwrap(),
+                create_rewrite_rule(&mut tp, "g(a, b)", "f(a, b)", &[]).unwrap(),
+            ],
+            constructors: vec![],
+        };
+
+        let report = spec.validate(&mut tp);
+        assert_eq!(report.issues.len(), 3, "{report}");
+        assert!(matches!(report.issues[0], SpecificationIssue::UnboundVariable(_, _)));
+        assert!(matches!(report.issues[1], SpecificationIssue::OverlappingRules(_, _)));
+        assert!(matches!(
+            report.issues[2],
+            SpecificationIssue::InconsistentArity(_, 1, 2)
+        ));
+    }
+}
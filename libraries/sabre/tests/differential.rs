@@ -0,0 +1,79 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+use std::time::Instant;
+
+use mcrl2::aterm::TermPool;
+use mcrl2::data::DataExpression;
+use mcrl2::data::DataSpecification;
+use sabre::RewriteSpecification;
+use sabre::Strategy;
+use test_case::test_case;
+
+/// The engines that are compared, the first one is used as the reference.
+const STRATEGIES: [Strategy; 4] = [
+    Strategy::Innermost,
+    Strategy::Outermost,
+    Strategy::Lazy,
+    Strategy::Jitty,
+];
+
+#[test_case(include_str!("../../../examples/REC/mcrl2/benchexpr10.dataspec"), include_str!("../../../examples/REC/mcrl2/benchexpr10.expressions"), include_str!("snapshot/result_benchexpr10.txt") ; "benchexpr10")]
+#[test_case(include_str!("../../../examples/REC/mcrl2/calls.dataspec"), include_str!("../../../examples/REC/mcrl2/calls.expressions"), include_str!("snapshot/result_calls.txt") ; "calls")]
+#[test_case(include_str!("../../../examples/REC/mcrl2/check1.dataspec"), include_str!("../../../examples/REC/mcrl2/check1.expressions"), include_str!("snapshot/result_check1.txt") ; "check1")]
+#[test_case(include_str!("../../../examples/REC/mcrl2/check2.dataspec"), include_str!("../../../examples/REC/mcrl2/check2.expressions"), include_str!("snapshot/result_check2.txt") ; "check2")]
+#[test_case(include_str!("../../../examples/REC/mcrl2/confluence.dataspec"), include_str!("../../../examples/REC/mcrl2/confluence.expressions"), include_str!("snapshot/result_confluence.txt") ; "confluence")]
+#[test_case(include_str!("../../../examples/REC/mcrl2/fibonacci05.dataspec"), include_str!("../../../examples/REC/mcrl2/fibonacci05.expressions"), include_str!("snapshot/result_fibonacci05.txt") ; "fibonacci05")]
+#[test_case(include_str!("../../../examples/REC/mcrl2/logic3.dataspec"), include_str!("../../../examples/REC/mcrl2/logic3.expressions"), include_str!("snapshot/result_logic3.txt") ; "logic3")]
+#[test_case(include_str!("../../../examples/REC/mcrl2/merge.dataspec"), include_str!("../../../examples/REC/mcrl2/merge.expressions"), include_str!("snapshot/result_merge.txt") ; "merge")]
+#[test_case(include_str!("../../../examples/REC/mcrl2/mergesort10.dataspec"), include_str!("../../../examples/REC/mcrl2/mergesort10.expressions"), include_str!("snapshot/result_mergesort10.txt") ; "mergesort10")]
+#[test_case(include_str!("../../../examples/REC/mcrl2/quicksort10.dataspec"), include_str!("../../../examples/REC/mcrl2/quicksort10.expressions"), include_str!("snapshot/result_quicksort10.txt") ; "quicksort10")]
+#[test_case(include_str!("../../../examples/REC/mcrl2/revelt.dataspec"), include_str!("../../../examples/REC/mcrl2/revelt.expressions"), include_str!("snapshot/result_revelt.txt") ; "revelt")]
+#[test_case(include_str!("../../../examples/REC/mcrl2/searchinconditions.dataspec"), include_str!("../../../examples/REC/mcrl2/searchinconditions.expressions"), include_str!("snapshot/result_searchinconditions.txt") ; "searchinconditions")]
+#[test_case(include_str!("../../../examples/REC/mcrl2/soundnessofparallelengines.dataspec"), include_str!("../../../examples/REC/mcrl2/soundnessofparallelengines.expressions"), include_str!("snapshot/result_soundnessofparallelengines.txt") ; "soundnessofparallelengines")]
+fn differential_test(data_spec: &str, expressions: &str, expected_result: &str) {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let tp = Rc::new(RefCell::new(TermPool::new()));
+    let spec = DataSpecification::new(data_spec).unwrap();
+    let rewrite_spec: RewriteSpecification = spec.clone().into();
+    let terms: Vec<DataExpression> = expressions.lines().map(|text| spec.parse(text).unwrap()).collect();
+    let expected: Vec<DataExpression> = expected_result
+        .lines()
+        .take(terms.len())
+        .map(|text| spec.parse(text).unwrap())
+        .collect();
+
+    let mut rewriters: Vec<_> = STRATEGIES
+        .iter()
+        .map(|strategy| (strategy, strategy.rewriter(tp.clone(), &rewrite_spec), Duration::ZERO))
+        .collect();
+
+    for (term, expected_result) in terms.iter().zip(&expected) {
+        let mut normal_forms = Vec::new();
+        for (strategy, rewriter, time) in &mut rewriters {
+            let start = Instant::now();
+            let result = rewriter.rewrite(term.clone());
+            *time += start.elapsed();
+
+            normal_forms.push((strategy, result));
+        }
+
+        let (reference, reference_result) = &normal_forms[0];
+        assert_eq!(
+            reference_result, expected_result,
+            "The {reference} rewrite result of {term} doesn't match the expected result"
+        );
+
+        for (strategy, result) in &normal_forms[1..] {
+            assert_eq!(
+                result, reference_result,
+                "The {strategy} rewrite result of {term} differs from the {reference} rewrite result"
+            );
+        }
+    }
+
+    for (strategy, _, time) in &rewriters {
+        println!("{strategy}: {} ms", time.as_millis());
+    }
+}
@@ -1,12 +1,21 @@
-use std::cell::RefCell;
-use std::rc::Rc;
 use std::thread;
 
-use mcrl2::aterm::TermPool;
+use mcrl2::aterm::ATermGlobal;
 use mcrl2::data::DataExpression;
 use mcrl2::data::DataSpecification;
 use sabre::InnermostRewriter;
 use sabre::RewriteEngine;
+use sabre::RewriteSpecification;
+use sabre::SabreRewriter;
+
+/// The rewriters can be moved into the worker threads that use them.
+#[test]
+fn test_rewriters_are_send() {
+    fn assert_send<T: Send>() {}
+
+    assert_send::<InnermostRewriter>();
+    assert_send::<SabreRewriter>();
+}
 
 #[test]
 fn test_parallelism() {
@@ -22,12 +31,11 @@ fn test_parallelism() {
         threads.push(thread::spawn(move || {
             let (data_spec, expressions, expected_result) = test_case;
 
-            let tp = Rc::new(RefCell::new(TermPool::new()));
             let spec = DataSpecification::new(data_spec).unwrap();
             let terms: Vec<DataExpression> = expressions.lines().map(|text| spec.parse(text).unwrap()).collect();
             let mut expected = expected_result.split('\n');
 
-            let mut inner = InnermostRewriter::new(tp.clone(), &spec.clone().into());
+            let mut inner = InnermostRewriter::new(&spec.clone().into());
             for term in &terms {
                 let result = inner.rewrite(term.clone());
 
@@ -44,3 +52,76 @@ fn test_parallelism() {
         thread.join().unwrap();
     }
 }
+
+#[test]
+fn test_shared_specification() {
+    let spec = DataSpecification::new(include_str!("../../../examples/REC/mcrl2/benchexpr10.dataspec")).unwrap();
+    let rewrite_spec: RewriteSpecification = spec.clone().into();
+    let shared_spec = rewrite_spec.share();
+
+    // The terms are protected globally such that they can be used by all threads.
+    let terms: Vec<ATermGlobal> = include_str!("../../../examples/REC/mcrl2/benchexpr10.expressions")
+        .lines()
+        .map(|text| spec.parse(text).unwrap().protect_global())
+        .collect();
+    let expected: Vec<ATermGlobal> = include_str!("snapshot/result_benchexpr10.txt")
+        .lines()
+        .take(terms.len())
+        .map(|text| spec.parse(text).unwrap().protect_global())
+        .collect();
+
+    thread::scope(|s| {
+        for _ in 0..2 {
+            s.spawn(|| {
+                let mut inner = InnermostRewriter::from_shared(&shared_spec);
+
+                for (term, expected_result) in terms.iter().zip(&expected) {
+                    let result = inner.rewrite(term.protect().into());
+                    assert_eq!(
+                        result,
+                        expected_result.protect().into(),
+                        "The inner rewrite result doesn't match the expected result"
+                    );
+                }
+            });
+        }
+    });
+}
+
+#[test]
+fn test_send_rewriters() {
+    let spec = DataSpecification::new(include_str!("../../../examples/REC/mcrl2/benchexpr10.dataspec")).unwrap();
+    let shared_spec = RewriteSpecification::from(spec.clone()).share();
+
+    let terms: Vec<ATermGlobal> = include_str!("../../../examples/REC/mcrl2/benchexpr10.expressions")
+        .lines()
+        .map(|text| spec.parse(text).unwrap().protect_global())
+        .collect();
+    let expected: Vec<ATermGlobal> = include_str!("snapshot/result_benchexpr10.txt")
+        .lines()
+        .take(terms.len())
+        .map(|text| spec.parse(text).unwrap().protect_global())
+        .collect();
+
+    // The rewriters are created on this thread, and then moved into the threads that use them.
+    let rewriters: Vec<Box<dyn RewriteEngine + Send>> = vec![
+        Box::new(InnermostRewriter::from_shared(&shared_spec)),
+        Box::new(SabreRewriter::from_shared(&shared_spec)),
+    ];
+
+    thread::scope(|s| {
+        for mut rewriter in rewriters {
+            let (terms, expected) = (&terms, &expected);
+            s.spawn(move || {
+                for (term, expected_result) in terms.iter().zip(expected) {
+                    let result = rewriter.rewrite(term.protect().into());
+                    assert_eq!(
+                        result,
+                        expected_result.protect().into(),
+                        "The rewrite result doesn't match the expected result"
+                    );
+                }
+            });
+        }
+    });
+}
@@ -1,12 +1,24 @@
+use mcrl2::data::DataApplication;
 use mcrl2::data::DataExpression;
 use mcrl2::data::DataSpecification;
 use std::cell::RefCell;
 use std::rc::Rc;
 use test_case::test_case;
 
+use mcrl2::aterm::ATermRef;
 use mcrl2::aterm::TermPool;
+use sabre::utilities::ExplicitPosition;
+use sabre::Enumerator;
 use sabre::InnermostRewriter;
+use sabre::JittyRewriter;
+use sabre::LazyRewriter;
 use sabre::RewriteEngine;
+use sabre::RewriteError;
+use sabre::RewriteLimits;
+use sabre::RewriteSpecification;
+use sabre::SabreRewriter;
+use sabre::Strategy;
+use sabre::Substitution;
 
 #[test_case(include_str!("../../../examples/REC/mcrl2/benchexpr10.dataspec"), include_str!("../../../examples/REC/mcrl2/benchexpr10.expressions"), include_str!("snapshot/result_benchexpr10.txt") ; "benchexpr10")]
 #[test_case(include_str!("../../../examples/REC/mcrl2/benchsym10.dataspec"), include_str!("../../../examples/REC/mcrl2/benchsym10.expressions"), include_str!("snapshot/result_benchsym10.txt") ; "benchsym10")]
@@ -29,12 +41,11 @@ use sabre::RewriteEngine;
 fn rewriter_test(data_spec: &str, expressions: &str, expected_result: &str) {
     let _ = env_logger::builder().is_test(true).try_init();
 
-    let tp = Rc::new(RefCell::new(TermPool::new()));
     let spec = DataSpecification::new(data_spec).unwrap();
     let terms: Vec<DataExpression> = expressions.lines().map(|text| spec.parse(text).unwrap()).collect();
 
-    // let mut sa = SabreRewriter::new(tp.clone(), &spec.clone().into());
-    let mut inner = InnermostRewriter::new(tp.clone(), &spec.clone().into());
+    // let mut sa = SabreRewriter::new(&spec.clone().into());
+    let mut inner = InnermostRewriter::new(&spec.clone().into());
     let mut expected = expected_result.split('\n');
 
     for term in &terms {
@@ -98,3 +109,561 @@ fn rewriter_test_release(data_spec: &str, expressions: &str, expected_result: &s
 fn rewriter_test_release_unix(data_spec: &str, expressions: &str, expected_result: &str) {
     rewriter_test(data_spec, expressions, expected_result);
 }
+
+#[test]
+fn test_quantifier_enumeration() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let spec = DataSpecification::new(
+        "
+        sort Bit = struct x0 | x1;
+
+        map is_zero: Bit -> Bool;
+            all_zero, some_zero: Bool;
+
+        eqn is_zero(x0) = true;
+            is_zero(x1) = false;
+            (forall b: Bit. is_zero(b)) -> all_zero = true;
+            (exists b: Bit. is_zero(b)) -> some_zero = true;
+        ",
+    )
+    .unwrap();
+
+    let cases = [
+        ("forall b: Bit. is_zero(b)", "false"),
+        ("exists b: Bit. is_zero(b)", "true"),
+        ("all_zero", "all_zero"),
+        ("some_zero", "true"),
+    ];
+
+    let mut inner = InnermostRewriter::new(&spec.clone().into());
+    let mut sa = SabreRewriter::new(&spec.clone().into());
+
+    for (term, expected) in cases {
+        let term = spec.parse(term).unwrap();
+        let expected = spec.parse(expected).unwrap();
+
+        assert_eq!(
+            inner.rewrite(term.clone()),
+            expected,
+            "The inner rewrite result doesn't match the expected result"
+        );
+        assert_eq!(
+            sa.rewrite(term),
+            expected,
+            "The sabre rewrite result doesn't match the expected result"
+        );
+    }
+}
+
+#[test]
+fn test_arithmetic() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let spec = DataSpecification::new(
+        "
+        map square: Nat -> Nat;
+        var n: Nat;
+        eqn square(n) = n * n;
+        ",
+    )
+    .unwrap();
+
+    let cases = [
+        ("2 * 3 + 4", "10"),
+        ("square(12) + 1", "145"),
+        ("10 div 3", "3"),
+        ("10 mod 3", "1"),
+        ("3 < 5", "true"),
+        ("max(3, 7)", "7"),
+        ("-3 * 4", "-12"),
+        (
+            "square(4294967296) * square(4294967296)",
+            "340282366920938463463374607431768211456",
+        ),
+    ];
+
+    let mut inner = InnermostRewriter::new(&spec.clone().into());
+
+    for (term, expected) in cases {
+        let term = spec.parse(term).unwrap();
+        let expected = spec.parse(expected).unwrap();
+
+        assert_eq!(
+            inner.rewrite(term),
+            expected,
+            "The inner rewrite result doesn't match the expected result"
+        );
+    }
+}
+
+#[test]
+fn test_containers() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let spec = DataSpecification::new(
+        "
+        sort Colour = struct red | green | blue;
+
+        map colours: List(Colour);
+        eqn colours = [red, green];
+        ",
+    )
+    .unwrap();
+
+    let cases = [
+        ("colours ++ [blue]", "[red, green, blue]"),
+        ("colours <| blue", "[red, green, blue]"),
+        ("#(colours ++ colours)", "4"),
+        ("head(colours)", "red"),
+        ("tail(colours)", "[green]"),
+        ("rhead(colours)", "green"),
+        ("colours . 1", "green"),
+        ("green in colours", "true"),
+        ("blue in colours", "false"),
+        ("3 in {1, 2} + {3}", "true"),
+        ("true in {false} + {true}", "true"),
+        ("count(1, {1: 2} + {1: 3, 2: 1})", "5"),
+        ("2 in {1: 2, 2: 1} - {2: 1}", "false"),
+        ("green in {red: 1, green: 2}", "true"),
+    ];
+
+    let mut inner = InnermostRewriter::new(&spec.clone().into());
+
+    for (term, expected) in cases {
+        let term = spec.parse(term).unwrap();
+        let expected = spec.parse(expected).unwrap();
+
+        assert_eq!(
+            inner.rewrite(term),
+            expected,
+            "The inner rewrite result doesn't match the expected result"
+        );
+    }
+}
+
+#[test]
+fn test_strategies() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let tp = Rc::new(RefCell::new(TermPool::new()));
+    let spec = DataSpecification::new(
+        "
+        sort Bit = struct x0 | x1;
+
+        map flip: Bit -> Bit;
+            first: Bit # Bit -> Bit;
+            diverge: Bit;
+
+        var b, c: Bit;
+        eqn flip(x0) = x1;
+            flip(x1) = x0;
+            first(b, c) = b;
+            diverge = flip(flip(diverge));
+        ",
+    )
+    .unwrap();
+
+    let cases = [("flip(flip(x0))", "x0"), ("first(flip(x0), x0)", "x1")];
+
+    for strategy in [
+        Strategy::Innermost,
+        Strategy::Outermost,
+        Strategy::Lazy,
+        Strategy::Jitty,
+    ] {
+        let mut rewriter = strategy.rewriter(tp.clone(), &spec.clone().into());
+
+        for (term, expected) in cases {
+            let term = spec.parse(term).unwrap();
+            let expected = spec.parse(expected).unwrap();
+
+            assert_eq!(
+                rewriter.rewrite(term),
+                expected,
+                "The {strategy} rewrite result doesn't match the expected result"
+            );
+        }
+    }
+
+    // The lazy strategy never rewrites the second argument of first.
+    let mut lazy = LazyRewriter::new(tp.clone(), &spec.clone().into());
+    let term = spec.parse("first(x0, diverge)").unwrap();
+    assert_eq!(lazy.rewrite(term), spec.parse("x0").unwrap());
+}
+
+#[test]
+fn test_trace() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let tp = Rc::new(RefCell::new(TermPool::new()));
+    let spec = DataSpecification::new(
+        "
+        sort Bit = struct x0 | x1;
+
+        map flip: Bit -> Bit;
+
+        eqn flip(x0) = x1;
+            flip(x1) = x0;
+        ",
+    )
+    .unwrap();
+
+    for strategy in [
+        Strategy::Innermost,
+        Strategy::Outermost,
+        Strategy::Lazy,
+        Strategy::Jitty,
+    ] {
+        let mut rewriter = strategy.rewriter(tp.clone(), &spec.clone().into());
+
+        let (result, events) = rewriter.rewrite_traced(spec.parse("flip(flip(x0))").unwrap());
+        assert_eq!(result, spec.parse("x0").unwrap());
+        assert_eq!(events.len(), 2, "The {strategy} rewriter should perform two steps");
+        assert_eq!(events[0].redex, spec.parse("flip(x0)").unwrap());
+        assert_eq!(events[1].contractum, result);
+
+        if matches!(strategy, Strategy::Lazy | Strategy::Jitty) {
+            // The inner flip is the first argument of the outer flip.
+            assert_eq!(events[0].position, Some(ExplicitPosition::new(&[2])));
+            assert_eq!(events[1].position, Some(ExplicitPosition::empty_pos()));
+        }
+    }
+}
+
+#[test]
+fn test_normal_form_cache() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let spec = DataSpecification::new(include_str!("../../../examples/REC/mcrl2/fibonacci05.dataspec")).unwrap();
+    let terms: Vec<DataExpression> = include_str!("../../../examples/REC/mcrl2/fibonacci05.expressions")
+        .lines()
+        .map(|text| spec.parse(text).unwrap())
+        .collect();
+
+    let mut inner = InnermostRewriter::new(&spec.clone().into());
+    let mut cached = InnermostRewriter::new(&spec.clone().into());
+    cached.set_cache_capacity(1000);
+
+    for term in &terms {
+        let expected = inner.rewrite(term.clone());
+        assert_eq!(cached.rewrite(term.clone()), expected);
+
+        // The second time the normal form is taken from the cache.
+        let hits = cached.cache_statistics().hits;
+        assert_eq!(cached.rewrite(term.clone()), expected);
+        assert_eq!(cached.cache_statistics().hits, hits + 1);
+    }
+}
+
+#[test]
+fn test_limits() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let tp = Rc::new(RefCell::new(TermPool::new()));
+    let spec = DataSpecification::new(
+        "
+        sort Bit = struct x0 | x1;
+
+        map f: Bit -> Bit;
+
+        var b: Bit;
+        eqn f(b) == x0 -> f(b) = x1;
+        ",
+    )
+    .unwrap();
+
+    for strategy in [
+        Strategy::Innermost,
+        Strategy::Outermost,
+        Strategy::Lazy,
+        Strategy::Jitty,
+    ] {
+        let mut rewriter = strategy.rewriter(tp.clone(), &spec.clone().into());
+
+        // Rewriting the condition requires rewriting the condition itself.
+        rewriter.set_limits(RewriteLimits {
+            max_depth: None,
+            detect_cycles: true,
+        });
+        assert!(
+            matches!(
+                rewriter.try_rewrite(spec.parse("f(x0)").unwrap()),
+                Err(RewriteError::ConditionCycle(_, _))
+            ),
+            "The {strategy} rewriter should detect the cycle"
+        );
+
+        rewriter.set_limits(RewriteLimits {
+            max_depth: Some(100),
+            detect_cycles: false,
+        });
+        assert!(
+            matches!(
+                rewriter.try_rewrite(spec.parse("f(x0)").unwrap()),
+                Err(RewriteError::DepthExceeded(100, _))
+            ),
+            "The {strategy} rewriter should exceed the recursion depth"
+        );
+
+        // The rewriter can still be used after a failure.
+        assert_eq!(
+            rewriter.try_rewrite(spec.parse("x1").unwrap()).unwrap(),
+            spec.parse("x1").unwrap()
+        );
+    }
+}
+
+#[test]
+fn test_rule_statistics() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let tp = Rc::new(RefCell::new(TermPool::new()));
+    let spec = DataSpecification::new(
+        "
+        sort Bit = struct x0 | x1;
+
+        map flip: Bit -> Bit;
+
+        eqn flip(x0) = x1;
+            flip(x1) = x0;
+        ",
+    )
+    .unwrap();
+    let rewrite_spec: RewriteSpecification = spec.clone().into();
+
+    for strategy in [
+        Strategy::Innermost,
+        Strategy::Outermost,
+        Strategy::Lazy,
+        Strategy::Jitty,
+    ] {
+        let mut rewriter = strategy.rewriter(tp.clone(), &rewrite_spec);
+        rewriter.rewrite(spec.parse("flip(flip(flip(x0)))").unwrap());
+        rewriter.rewrite(spec.parse("flip(x0)").unwrap());
+
+        // The first rule is applied three times, the second rule once.
+        let statistics = rewriter.statistics();
+        assert_eq!(
+            statistics.rewrite_steps, 4,
+            "The {strategy} rewriter should perform four steps"
+        );
+        let mut counts: Vec<usize> = rewrite_spec
+            .rewrite_rules
+            .iter()
+            .map(|rule| statistics.applications(rule))
+            .collect();
+        counts.sort();
+        assert_eq!(counts, vec![1, 3]);
+
+        // Only the first rule is applied to rewrite flip(x0).
+        let mut rewriter = strategy.rewriter(tp.clone(), &rewrite_spec);
+        rewriter.rewrite(spec.parse("flip(x0)").unwrap());
+        assert_eq!(rewriter.statistics().unused_rules(&rewrite_spec).count(), 1);
+    }
+}
+
+#[test]
+fn test_check_confluence() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let tp = Rc::new(RefCell::new(TermPool::new()));
+    let spec = DataSpecification::new(
+        "
+        sort Bit = struct x0 | x1;
+
+        map flip, f: Bit -> Bit;
+
+        var b: Bit;
+        eqn flip(x0) = x1;
+            flip(x1) = x0;
+            f(x0) = x0;
+            f(b) = x1;
+        ",
+    )
+    .unwrap();
+    let rewrite_spec: RewriteSpecification = spec.clone().into();
+
+    // Only the overlap of the rules for f is not joinable.
+    let mut rewriter = JittyRewriter::new(tp.clone(), &rewrite_spec);
+    let pairs = rewrite_spec.check_confluence(&tp, &mut rewriter);
+    assert_eq!(pairs.len(), 1);
+    assert_eq!(pairs[0].pair.overlap, spec.parse("f(x0)").unwrap());
+}
+
+#[test]
+fn test_rewrite_with_substitution() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let tp = Rc::new(RefCell::new(TermPool::new()));
+    let spec = DataSpecification::new(
+        "
+        sort Bit = struct x0 | x1;
+
+        map flip: Bit -> Bit;
+            first: Bit # Bit -> Bit;
+
+        var b, c: Bit;
+        eqn flip(x0) = x1;
+            flip(x1) = x0;
+            first(b, c) = b;
+        ",
+    )
+    .unwrap();
+
+    // Construct first(flip(b), c), since the parser does not accept free variables.
+    let b = spec.parse_variable("b: Bit").unwrap();
+    let c = spec.parse_variable("c: Bit").unwrap();
+    let flip = spec.parse("flip(x0)").unwrap().data_function_symbol().protect();
+    let first = spec.parse("first(x0, x0)").unwrap().data_function_symbol().protect();
+    let term: DataExpression = {
+        let mut tp = tp.borrow_mut();
+        let flip_b = DataApplication::new(&mut tp, &flip.copy(), &[b.copy()]);
+        let arguments: [ATermRef<'_>; 2] = [flip_b.copy().into(), c.copy().into()];
+        DataApplication::new(&mut tp, &first.copy(), &arguments).into()
+    };
+
+    let substitution: Substitution = vec![(b, spec.parse("x0").unwrap()), (c, spec.parse("x0").unwrap())];
+
+    for strategy in [
+        Strategy::Innermost,
+        Strategy::Outermost,
+        Strategy::Lazy,
+        Strategy::Jitty,
+    ] {
+        let mut rewriter = strategy.rewriter(tp.clone(), &spec.clone().into());
+        assert_eq!(
+            rewriter.rewrite_with_substitution(&term, &substitution),
+            spec.parse("x1").unwrap(),
+            "The {strategy} rewrite result doesn't match the expected result"
+        );
+    }
+}
+
+#[test]
+fn test_rewrite_once() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let tp = Rc::new(RefCell::new(TermPool::new()));
+    let spec = DataSpecification::new(
+        "
+        sort Bit = struct x0 | x1;
+
+        map flip: Bit -> Bit;
+            first: Bit # Bit -> Bit;
+
+        var b, c: Bit;
+        eqn flip(x0) = x1;
+            flip(x1) = x0;
+            first(b, c) = b;
+        ",
+    )
+    .unwrap();
+
+    for strategy in [
+        Strategy::Innermost,
+        Strategy::Outermost,
+        Strategy::Lazy,
+        Strategy::Jitty,
+    ] {
+        let mut rewriter = strategy.rewriter(tp.clone(), &spec.clone().into());
+
+        // The leftmost outermost redex is rewritten first.
+        let term = spec.parse("first(flip(x0), flip(x1))").unwrap();
+        let step = rewriter.rewrite_once(&term);
+        assert_eq!(step, Some(spec.parse("flip(x0)").unwrap()));
+
+        let step = rewriter.rewrite_once(&spec.parse("flip(flip(x0))").unwrap());
+        assert_eq!(step, Some(spec.parse("flip(x1)").unwrap()));
+
+        assert!(rewriter.is_normal_form(&spec.parse("x0").unwrap()));
+        assert!(!rewriter.is_normal_form(&term));
+    }
+}
+
+#[test]
+fn test_check_completeness() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let spec = DataSpecification::new(
+        "
+        sort Bit = struct x0 | x1;
+
+        map f: Bit # Bit -> Bit;
+
+        var b: Bit;
+        eqn f(x0, b) = x0;
+            f(x1, x0) = x1;
+        ",
+    )
+    .unwrap();
+    let rewrite_spec: RewriteSpecification = spec.into();
+
+    // Only f(x1, x1) is not covered by the rules for f.
+    let missing: Vec<String> = rewrite_spec
+        .check_completeness()
+        .iter()
+        .filter(|case| case.symbol.name() == "f")
+        .map(|case| case.to_string())
+        .collect();
+    assert_eq!(missing, vec!["f(x1, x1)"]);
+}
+
+#[test]
+fn test_condition_solutions() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let tp = Rc::new(RefCell::new(TermPool::new()));
+    let spec = DataSpecification::new(
+        "
+        sort N = struct zero | succ(N);
+
+        map small: N -> Bool;
+
+        var n: N;
+        eqn small(zero) = true;
+            small(succ(zero)) = true;
+            small(succ(succ(zero))) = true;
+            small(succ(succ(succ(n)))) = false;
+        ",
+    )
+    .unwrap();
+    let rewrite_spec: RewriteSpecification = spec.clone().into();
+    let enumerator = Enumerator::new(&mut tp.borrow_mut(), &rewrite_spec);
+
+    // Construct small(n), since the parser does not accept free variables.
+    let n = spec.parse_variable("n: N").unwrap();
+    let small = spec.parse("small(zero)").unwrap().data_function_symbol().protect();
+    let condition: DataExpression = DataApplication::new(&mut tp.borrow_mut(), &small.copy(), &[n.copy()]).into();
+    assert!(
+        enumerator.values(&n.sort().protect()).is_none(),
+        "The recursive sort N cannot be enumerated"
+    );
+
+    let mut expected: Vec<Vec<DataExpression>> = ["zero", "succ(zero)", "succ(succ(zero))"]
+        .iter()
+        .map(|value| vec![spec.parse(value).unwrap()])
+        .collect();
+    expected.sort();
+
+    for strategy in [
+        Strategy::Innermost,
+        Strategy::Outermost,
+        Strategy::Lazy,
+        Strategy::Jitty,
+    ] {
+        let mut rewriter = strategy.rewriter(tp.clone(), &rewrite_spec);
+        // The rewriter borrows the shared term pool, so the enumerator uses its own.
+        let mut solutions = enumerator
+            .solutions(&mut TermPool::new(), std::slice::from_ref(&n), &condition, |term| {
+                rewriter.rewrite(term)
+            })
+            .expect("The solutions of small(n) can be enumerated");
+        solutions.sort();
+
+        assert_eq!(
+            solutions, expected,
+            "The {strategy} solutions don't match the expected solutions"
+        );
+    }
+}
@@ -4,6 +4,8 @@
 
 mod counting_allocator;
 mod index_edge;
+mod memory_map;
 
 pub use counting_allocator::*;
 pub use index_edge::*;
+pub use memory_map::*;
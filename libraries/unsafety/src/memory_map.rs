@@ -0,0 +1,14 @@
+use std::fs::File;
+use std::io;
+
+use memmap2::Mmap;
+
+/// Maps the contents of the given file into memory, which can be read as a byte slice.
+///
+/// The mapping is only safe when the file is not modified while it is mapped,
+/// which cannot be enforced. This is typically the case for input files that
+/// are only read by a single tool.
+pub fn map_file(file: &File) -> io::Result<Mmap> {
+    // Safe under the assumption that the file is not modified, see above.
+    unsafe { Mmap::map(file) }
+}
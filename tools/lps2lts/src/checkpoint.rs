@@ -0,0 +1,228 @@
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fs;
+use std::fs::File;
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::path::Path;
+
+use io::io_baf::BinaryATermReader;
+use io::io_baf::BinaryATermWriter;
+use io::io_baf::BinaryTerm;
+use log::info;
+
+use lps::StateStorage;
+use lts::StateIndex;
+use mcrl2::aterm::ATerm;
+use mcrl2::aterm::TermPool;
+use mcrl2::data::DataExpression;
+
+use crate::explore::StateSpace;
+
+/// The name of the checkpoint file in the checkpoint directory.
+const CHECKPOINT_FILE: &str = "checkpoint.baf";
+
+/// The constant at the start of a checkpoint.
+const CHECKPOINT_MARK: &str = "lps2lts_checkpoint";
+
+/// Writes the explored part of the state space and the states that remain to
+/// be explored to the checkpoint file in the given directory, in the binary
+/// aterm format.
+///
+/// The checkpoint is first written to a temporary file that replaces the
+/// previous checkpoint afterwards, such that an interruption while writing
+/// leaves the previous checkpoint intact.
+pub fn write_checkpoint(
+    directory: &Path,
+    space: &StateSpace,
+    queue: &VecDeque<StateIndex>,
+) -> Result<(), Box<dyn Error>> {
+    fs::create_dir_all(directory)?;
+    let path = directory.join(CHECKPOINT_FILE);
+    let tmp_path = path.with_extension("tmp");
+
+    let mut stream: BinaryATermWriter<_, ATerm> = BinaryATermWriter::new(BufWriter::new(File::create(&tmp_path)?))?;
+    stream.write_constant(CHECKPOINT_MARK)?;
+
+    // The labels are written as constants, since they are arbitrary strings.
+    stream.write_int(space.labels.len() as u64)?;
+    for label in &space.labels {
+        stream.write_constant(label)?;
+    }
+    stream.write_container(&space.multi_actions)?;
+
+    stream.write_int(space.states.len() as u64)?;
+    for state_index in 0..space.states.len() {
+        let values: Vec<ATerm> = space
+            .states
+            .get(state_index)
+            .into_iter()
+            .map(|value| value.into())
+            .collect();
+        stream.write_container(&values)?;
+    }
+
+    stream.write_int(space.transitions.len() as u64)?;
+    for &(from, label_index, to) in &space.transitions {
+        stream.write_int(from as u64)?;
+        stream.write_int(label_index as u64)?;
+        stream.write_int(to as u64)?;
+    }
+
+    // The initial state has no parent, which is written as zero.
+    for parent in &space.parents {
+        match parent {
+            Some((from, label_index)) => {
+                stream.write_int(*from as u64 + 1)?;
+                stream.write_int(*label_index as u64)?;
+            }
+            None => stream.write_int(0)?,
+        }
+    }
+
+    stream.write_int(queue.len() as u64)?;
+    for &state_index in queue {
+        stream.write_int(state_index as u64)?;
+    }
+    stream.finish()?;
+
+    fs::rename(&tmp_path, &path)?;
+    info!(
+        "Written checkpoint with {} states and {} queued states to {}",
+        space.states.len(),
+        queue.len(),
+        path.display()
+    );
+    Ok(())
+}
+
+/// Reads the checkpoint written by [write_checkpoint] from the given
+/// directory, where the states are inserted into the given empty storage.
+/// Returns the state space and the states that remain to be explored.
+pub fn read_checkpoint(
+    directory: &Path,
+    tp: &mut TermPool,
+    mut storage: Box<dyn StateStorage>,
+) -> Result<(StateSpace, VecDeque<StateIndex>), Box<dyn Error>> {
+    let path = directory.join(CHECKPOINT_FILE);
+    let mut stream: BinaryATermReader<_, TermPool> = BinaryATermReader::new(BufReader::new(File::open(&path)?))?;
+
+    let header = stream.read(tp)?.ok_or_else(|| invalid_checkpoint(&path))?;
+    if header.symbol() != (CHECKPOINT_MARK.to_string(), 0) {
+        return Err(invalid_checkpoint(&path).into());
+    }
+
+    let num_of_labels = stream.read_int(tp)?;
+    let mut labels = Vec::new();
+    for _ in 0..num_of_labels {
+        let label = stream.read(tp)?.ok_or_else(|| invalid_checkpoint(&path))?;
+        labels.push(label.symbol().0);
+    }
+    let multi_actions = stream.read_container(tp)?;
+
+    let num_of_states = stream.read_int(tp)? as usize;
+    for _ in 0..num_of_states {
+        let values: Vec<DataExpression> = stream
+            .read_container(tp)?
+            .into_iter()
+            .map(DataExpression::from)
+            .collect();
+        storage.insert(&values);
+    }
+
+    // The states were written in the order of their indices, so every distinct state keeps its index.
+    if storage.len() != num_of_states {
+        return Err(invalid_checkpoint(&path).into());
+    }
+
+    let num_of_transitions = stream.read_int(tp)?;
+    let mut transitions = Vec::new();
+    for _ in 0..num_of_transitions {
+        transitions.push((
+            stream.read_int(tp)? as StateIndex,
+            stream.read_int(tp)? as usize,
+            stream.read_int(tp)? as StateIndex,
+        ));
+    }
+
+    let mut parents = Vec::with_capacity(num_of_states);
+    for _ in 0..num_of_states {
+        parents.push(match stream.read_int(tp)? {
+            0 => None,
+            from => Some((from as StateIndex - 1, stream.read_int(tp)? as usize)),
+        });
+    }
+
+    let num_of_queued = stream.read_int(tp)?;
+    let mut queue = VecDeque::new();
+    for _ in 0..num_of_queued {
+        queue.push_back(stream.read_int(tp)? as StateIndex);
+    }
+
+    info!(
+        "Read checkpoint with {num_of_states} states and {} queued states from {}",
+        queue.len(),
+        path.display()
+    );
+
+    let space = StateSpace {
+        states: storage,
+        labels,
+        multi_actions,
+        transitions,
+        parents,
+        detected: None,
+    };
+    Ok((space, queue))
+}
+
+fn invalid_checkpoint(path: &Path) -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        format!("{} is not a valid checkpoint", path.display()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use lps::HashStorage;
+    use lps::NextStateGenerator;
+    use mcrl2::lps::LinearProcessSpecification;
+    use sabre::Strategy;
+
+    use crate::explore::explore;
+    use crate::explore::ExploreOptions;
+
+    use super::*;
+
+    use test_log::test;
+
+    #[test]
+    fn test_checkpoint_round_trip() {
+        let lps = LinearProcessSpecification::read("../../examples/lps/abp.lps").unwrap();
+
+        let tp = Rc::new(RefCell::new(TermPool::new()));
+        let mut generator = NextStateGenerator::new(&lps, tp.clone(), Strategy::Outermost).unwrap();
+        let space = explore(&mut generator, &tp, &ExploreOptions::default()).unwrap();
+
+        let directory = std::env::temp_dir().join("test_checkpoint_round_trip");
+        let queue = VecDeque::from([1, 0]);
+        write_checkpoint(&directory, &space, &queue).unwrap();
+
+        let (result, result_queue) =
+            read_checkpoint(&directory, &mut tp.borrow_mut(), Box::new(HashStorage::new())).unwrap();
+        assert_eq!(result_queue, queue);
+        assert_eq!(result.labels, space.labels);
+        assert_eq!(result.multi_actions, space.multi_actions);
+        assert_eq!(result.transitions, space.transitions);
+        assert_eq!(result.parents, space.parents);
+
+        assert_eq!(result.states.len(), space.states.len());
+        for state_index in 0..space.states.len() {
+            assert_eq!(result.states.get(state_index), space.states.get(state_index));
+        }
+    }
+}
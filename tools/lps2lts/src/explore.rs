@@ -0,0 +1,477 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::error::Error;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+use ahash::AHashMap;
+use clap::ValueEnum;
+use log::debug;
+use log::info;
+
+use lps::confluent_representative;
+use lps::confluent_summands;
+use lps::CollapsedStorage;
+use lps::DiskStorage;
+use lps::HashStorage;
+use lps::NextStateGenerator;
+use lps::StateStorage;
+use lps::TreeStorage;
+use lts::LabelIndex;
+use lts::LabelledTransitionSystem;
+use lts::StateIndex;
+use lts::Trace;
+use mcrl2::aterm::ATerm;
+use mcrl2::aterm::TermPool;
+
+use crate::checkpoint::read_checkpoint;
+use crate::checkpoint::write_checkpoint;
+
+/// The order in which the states are explored.
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+pub enum SearchStrategy {
+    #[default]
+    Breadth,
+    Depth,
+}
+
+/// The way in which the visited states are stored, see [StateStorage].
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+pub enum StorageKind {
+    /// Stores every state as a vector of values, see [HashStorage].
+    #[default]
+    Hash,
+
+    /// Stores every state as a vector of indices of values, see [CollapsedStorage].
+    Collapsed,
+
+    /// Stores the states by tree compression, see [TreeStorage].
+    Tree,
+
+    /// Stores the states in a temporary file, see [DiskStorage].
+    Disk,
+}
+
+impl StorageKind {
+    /// Creates an empty storage for states with the given number of process parameters.
+    pub fn create(self, num_of_parameters: usize) -> io::Result<Box<dyn StateStorage>> {
+        Ok(match self {
+            StorageKind::Hash => Box::new(HashStorage::new()),
+            StorageKind::Collapsed => Box::new(CollapsedStorage::new(num_of_parameters)),
+            StorageKind::Tree => Box::new(TreeStorage::new(num_of_parameters)),
+            StorageKind::Disk => {
+                // Every storage has its own file, since several storages can exist at the same time.
+                static NUM_OF_FILES: AtomicUsize = AtomicUsize::new(0);
+                let path = std::env::temp_dir().join(format!(
+                    "lps2lts_{}_{}.states",
+                    std::process::id(),
+                    NUM_OF_FILES.fetch_add(1, Ordering::Relaxed)
+                ));
+                Box::new(DiskStorage::new(num_of_parameters, &path)?)
+            }
+        })
+    }
+}
+
+/// The options that determine how the state space is explored.
+#[derive(Clone, Debug, Default)]
+pub struct ExploreOptions {
+    pub strategy: SearchStrategy,
+    pub storage: StorageKind,
+
+    /// The maximum number of states, the transitions to other states are ignored.
+    pub max_states: Option<usize>,
+
+    /// Stops the exploration at the first state without outgoing transitions.
+    pub deadlock: bool,
+
+    /// Stops the exploration at the first transition with one of these actions.
+    pub actions: Vec<String>,
+
+    /// Replaces every state by its representative w.r.t. the confluent tau-summands, see [confluent_representative].
+    pub confluence: bool,
+
+    /// The directory to which a checkpoint is written periodically, see [write_checkpoint].
+    pub checkpoint: Option<PathBuf>,
+
+    /// The number of explored states after which a checkpoint is written.
+    pub checkpoint_interval: usize,
+}
+
+/// The state or transition at which the exploration was stopped, see [ExploreOptions].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Detected {
+    Deadlock(StateIndex),
+    Action(StateIndex, LabelIndex, StateIndex),
+}
+
+/// The reachable state space of a linear process.
+pub struct StateSpace {
+    /// The value of every process parameter for every state, where the first state is the initial state.
+    pub states: Box<dyn StateStorage>,
+
+    /// The name of every label, see [lps::Transition::label].
+    pub labels: Vec<String>,
+
+    /// The multi-action term `TimedMultAct(actions, time)` of every label.
+    pub multi_actions: Vec<ATerm>,
+
+    pub transitions: Vec<(StateIndex, LabelIndex, StateIndex)>,
+
+    /// The transition by which every state was first reached, which is none for the initial state.
+    pub parents: Vec<Option<(StateIndex, LabelIndex)>>,
+
+    /// The reason why the exploration was stopped before all states were explored.
+    pub detected: Option<Detected>,
+}
+
+impl StateSpace {
+    /// Returns the state space as a labelled transition system, where the label tau is hidden.
+    pub fn to_lts(&self) -> LabelledTransitionSystem {
+        LabelledTransitionSystem::new(
+            0,
+            Some(self.states.len()),
+            || self.transitions.iter().cloned(),
+            self.labels.clone(),
+            vec!["tau".to_string()],
+        )
+    }
+
+    /// Returns the trace to the detected deadlock or action in the given
+    /// labelled transition system, which must be the result of [Self::to_lts].
+    pub fn trace(&self, lts: &LabelledTransitionSystem) -> Option<Trace> {
+        match self.detected? {
+            Detected::Deadlock(state_index) => Some(self.trace_to(lts, state_index)),
+            Detected::Action(from, label_index, to) => {
+                let mut steps = self.path_to(from);
+                steps.push((from, label_index, to));
+                Some(self.to_trace(lts, &steps))
+            }
+        }
+    }
+
+    /// Returns the trace along which the given state was first reached.
+    pub fn trace_to(&self, lts: &LabelledTransitionSystem, state_index: StateIndex) -> Trace {
+        self.to_trace(lts, &self.path_to(state_index))
+    }
+
+    /// Returns the transitions from the initial state to the given state by following the parents.
+    fn path_to(&self, mut state_index: StateIndex) -> Vec<(StateIndex, LabelIndex, StateIndex)> {
+        let mut steps = Vec::new();
+        while let Some((from, label_index)) = self.parents[state_index] {
+            steps.push((from, label_index, state_index));
+            state_index = from;
+        }
+
+        steps.reverse();
+        steps
+    }
+
+    /// Converts the given transitions to a trace of the given LTS, in which the labels are renumbered.
+    fn to_trace(&self, lts: &LabelledTransitionSystem, steps: &[(StateIndex, LabelIndex, StateIndex)]) -> Trace {
+        let mut trace = Trace::new(0);
+        for &(from, label_index, to) in steps {
+            let (lts_label_index, _) = lts
+                .outgoing_transitions(from)
+                .find(|&(lts_label_index, other)| {
+                    other == to && lts.labels()[lts_label_index] == self.labels[label_index]
+                })
+                .expect("Every transition of the state space is a transition of the LTS");
+            trace.push(lts_label_index, to);
+        }
+
+        trace
+    }
+}
+
+/// Explores the state space of a linear process from its initial state, where
+/// the outgoing transitions are computed by the given generator. The term pool
+/// is only borrowed to create the multi-action terms, since it is shared with
+/// the rewriter of the generator.
+///
+/// When the maximum number of states is reached the transitions to new states
+/// are ignored. The exploration stops as soon as a deadlock or one of the
+/// actions of the options is detected. With confluence reduction the result
+/// is only branching bisimilar to the full state space.
+pub fn explore(
+    generator: &mut NextStateGenerator,
+    tp: &RefCell<TermPool>,
+    options: &ExploreOptions,
+) -> Result<StateSpace, Box<dyn Error>> {
+    let mut space = StateSpace {
+        states: options.storage.create(generator.parameters().len())?,
+        labels: Vec::new(),
+        multi_actions: Vec::new(),
+        transitions: Vec::new(),
+        parents: Vec::new(),
+        detected: None,
+    };
+
+    let confluent = confluent_tau_summands(generator, options);
+    let initial_state = generator.initial_state();
+    let initial_state = confluent_representative(generator, initial_state, &confluent)?;
+    space.states.insert(&initial_state);
+    space.parents.push(None);
+
+    explore_from(generator, tp, options, &confluent, space, VecDeque::from([0]))
+}
+
+/// Continues the exploration from the checkpoint in the given directory, which
+/// must have been written for the same linear process, see [explore].
+pub fn explore_resume(
+    generator: &mut NextStateGenerator,
+    tp: &RefCell<TermPool>,
+    options: &ExploreOptions,
+    directory: &Path,
+) -> Result<StateSpace, Box<dyn Error>> {
+    let storage = options.storage.create(generator.parameters().len())?;
+    let (space, queue) = read_checkpoint(directory, &mut tp.borrow_mut(), storage)?;
+    let confluent = confluent_tau_summands(generator, options);
+    explore_from(generator, tp, options, &confluent, space, queue)
+}
+
+/// Returns the confluent tau-summands when confluence reduction is enabled in the options.
+pub fn confluent_tau_summands(generator: &mut NextStateGenerator, options: &ExploreOptions) -> Vec<usize> {
+    if options.confluence {
+        confluent_summands(generator)
+    } else {
+        Vec::new()
+    }
+}
+
+/// Explores the states in the queue, where the given state space contains the
+/// states that have been found so far and every target state is replaced by
+/// its representative w.r.t. the given confluent summands.
+fn explore_from(
+    generator: &mut NextStateGenerator,
+    tp: &RefCell<TermPool>,
+    options: &ExploreOptions,
+    confluent: &[usize],
+    mut space: StateSpace,
+    mut queue: VecDeque<StateIndex>,
+) -> Result<StateSpace, Box<dyn Error>> {
+    let mut label_indices: AHashMap<String, LabelIndex> = space
+        .labels
+        .iter()
+        .enumerate()
+        .map(|(label_index, label)| (label.clone(), label_index))
+        .collect();
+
+    let mut num_of_explored = 0usize;
+    'explore: while let Some(state_index) = match options.strategy {
+        SearchStrategy::Breadth => queue.pop_front(),
+        SearchStrategy::Depth => queue.pop_back(),
+    } {
+        let transitions = generator.transitions(&space.states.get(state_index))?;
+        if options.deadlock && transitions.is_empty() {
+            info!("Found a deadlock in state {state_index}");
+            space.detected = Some(Detected::Deadlock(state_index));
+            break;
+        }
+
+        for transition in transitions {
+            let label = transition.label();
+            let found = transition
+                .actions
+                .iter()
+                .any(|action| options.actions.contains(&action.name));
+            let label_index = match label_indices.get(&label) {
+                Some(&label_index) => label_index,
+                None => {
+                    space
+                        .multi_actions
+                        .push(transition.multi_action_term(&mut tp.borrow_mut()));
+                    space.labels.push(label.clone());
+                    label_indices.insert(label, space.labels.len() - 1);
+                    space.labels.len() - 1
+                }
+            };
+
+            let target = confluent_representative(generator, transition.target, confluent)?;
+            let to = if options
+                .max_states
+                .is_some_and(|max_states| space.states.len() >= max_states)
+            {
+                match space.states.find(&target) {
+                    Some(to) => to,
+                    None => continue,
+                }
+            } else {
+                let (to, inserted) = space.states.insert(&target);
+                if inserted {
+                    space.parents.push(Some((state_index, label_index)));
+                    queue.push_back(to);
+                }
+                to
+            };
+
+            space.transitions.push((state_index, label_index, to));
+
+            if found {
+                info!("Found action {} in state {state_index}", space.labels[label_index]);
+                space.detected = Some(Detected::Action(state_index, label_index, to));
+                break 'explore;
+            }
+        }
+
+        num_of_explored += 1;
+        if num_of_explored % 100_000 == 0 {
+            debug!("Explored {num_of_explored} states, {} states are queued", queue.len());
+        }
+
+        // The checkpoint is only written between states, such that every queued state is unexplored.
+        if let Some(directory) = &options.checkpoint {
+            if num_of_explored % options.checkpoint_interval.max(1) == 0 {
+                write_checkpoint(directory, &space, &queue)?;
+            }
+        }
+    }
+
+    info!(
+        "Explored {} states and {} transitions",
+        space.states.len(),
+        space.transitions.len()
+    );
+    Ok(space)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use mcrl2::lps::LinearProcessSpecification;
+    use sabre::Strategy;
+
+    use super::*;
+
+    use test_log::test;
+
+    #[test]
+    fn test_explore_abp() {
+        let lps = LinearProcessSpecification::read("../../examples/lps/abp.lps").unwrap();
+
+        let tp = Rc::new(RefCell::new(TermPool::new()));
+        let mut generator = NextStateGenerator::new(&lps, tp.clone(), Strategy::Outermost).unwrap();
+
+        let mut sizes = Vec::new();
+        for (strategy, storage) in [
+            (SearchStrategy::Breadth, StorageKind::Hash),
+            (SearchStrategy::Depth, StorageKind::Collapsed),
+            (SearchStrategy::Breadth, StorageKind::Tree),
+            (SearchStrategy::Depth, StorageKind::Disk),
+        ] {
+            let options = ExploreOptions {
+                strategy,
+                storage,
+                ..Default::default()
+            };
+            let space = explore(&mut generator, &tp, &options).unwrap();
+
+            let lts = space.to_lts();
+            assert_eq!(lts.num_of_states(), space.states.len());
+            assert_eq!(lts.num_of_transitions(), space.transitions.len());
+            sizes.push((space.states.len(), space.transitions.len()));
+        }
+
+        // The search strategy and storage only change the order of the states.
+        assert!(sizes.iter().all(|size| *size == sizes[0]));
+
+        let options = ExploreOptions {
+            max_states: Some(10),
+            ..Default::default()
+        };
+        let space = explore(&mut generator, &tp, &options).unwrap();
+        assert_eq!(space.states.len(), 10);
+    }
+
+    #[test]
+    fn test_explore_detect() {
+        let lps = LinearProcessSpecification::read("../../examples/lps/abp.lps").unwrap();
+
+        let tp = Rc::new(RefCell::new(TermPool::new()));
+        let mut generator = NextStateGenerator::new(&lps, tp.clone(), Strategy::Outermost).unwrap();
+
+        // The alternating bit protocol has no deadlocks.
+        let options = ExploreOptions {
+            deadlock: true,
+            ..Default::default()
+        };
+        let space = explore(&mut generator, &tp, &options).unwrap();
+        assert_eq!(space.detected, None);
+
+        let label = space.labels.iter().find(|label| *label != "tau").unwrap();
+        let name = label.split('(').next().unwrap().to_string();
+        let options = ExploreOptions {
+            actions: vec![name.clone()],
+            ..Default::default()
+        };
+        let space = explore(&mut generator, &tp, &options).unwrap();
+
+        let lts = space.to_lts();
+        let trace = space.trace(&lts).unwrap();
+        assert!(trace.actions(&lts).last().unwrap().starts_with(&name));
+
+        // The breadth first search yields a trace along the parents.
+        let Some(Detected::Action(from, _, _)) = space.detected else {
+            panic!("Expected the action to be detected");
+        };
+        assert_eq!(trace.len(), space.trace_to(&lts, from).len() + 1);
+    }
+
+    #[test]
+    fn test_explore_confluence() {
+        let lps = LinearProcessSpecification::read("../../examples/lps/abp.lps").unwrap();
+
+        let tp = Rc::new(RefCell::new(TermPool::new()));
+        let mut generator = NextStateGenerator::new(&lps, tp.clone(), Strategy::Outermost).unwrap();
+        let expected = explore(&mut generator, &tp, &ExploreOptions::default()).unwrap();
+
+        let options = ExploreOptions {
+            confluence: true,
+            ..Default::default()
+        };
+        let space = explore(&mut generator, &tp, &options).unwrap();
+        assert!(space.states.len() <= expected.states.len());
+
+        // The reduction preserves the visible actions.
+        let visible = |labels: &[String]| {
+            let mut result: Vec<String> = labels.iter().filter(|label| *label != "tau").cloned().collect();
+            result.sort();
+            result
+        };
+        assert_eq!(visible(&space.labels), visible(&expected.labels));
+    }
+
+    #[test]
+    fn test_explore_resume() {
+        let lps = LinearProcessSpecification::read("../../examples/lps/abp.lps").unwrap();
+
+        let tp = Rc::new(RefCell::new(TermPool::new()));
+        let mut generator = NextStateGenerator::new(&lps, tp.clone(), Strategy::Outermost).unwrap();
+        let expected = explore(&mut generator, &tp, &ExploreOptions::default()).unwrap();
+
+        // The exploration is interrupted after the first checkpoint by detecting the action of the last label.
+        let directory = std::env::temp_dir().join("test_explore_resume");
+        let _ = std::fs::remove_dir_all(&directory);
+        let name = expected.labels.last().unwrap().split('(').next().unwrap().to_string();
+        let options = ExploreOptions {
+            actions: vec![name],
+            checkpoint: Some(directory.clone()),
+            checkpoint_interval: 1,
+            ..Default::default()
+        };
+        explore(&mut generator, &tp, &options).unwrap();
+
+        let options = ExploreOptions {
+            checkpoint: Some(directory.clone()),
+            checkpoint_interval: 1,
+            ..Default::default()
+        };
+        let space = explore_resume(&mut generator, &tp, &options, &directory).unwrap();
+        assert_eq!(space.states.len(), expected.states.len());
+        assert_eq!(space.transitions.len(), expected.transitions.len());
+    }
+}
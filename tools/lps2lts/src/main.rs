@@ -0,0 +1,229 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::rc::Rc;
+
+use clap::Parser;
+use io::formats::FormatError;
+use io::formats::LtsFormat;
+use io::io_aut::write_aut;
+use io::io_lts::write_lts;
+use io::io_lts::LtsInfo;
+use io::io_lts::ProbabilisticState;
+use io::io_trc::write_trc;
+use io::io_trc::TrcTrace;
+use lps::NextStateGenerator;
+use mcrl2::aterm::ATerm;
+use mcrl2::aterm::TermPool;
+use mcrl2::lps::LinearProcessSpecification;
+use sabre::Strategy;
+use utilities::Timing;
+
+use crate::explore::explore;
+use crate::explore::explore_resume;
+use crate::explore::Detected;
+use crate::explore::ExploreOptions;
+use crate::explore::SearchStrategy;
+use crate::explore::StateSpace;
+use crate::explore::StorageKind;
+use crate::parallel::explore_parallel;
+
+mod checkpoint;
+mod explore;
+mod parallel;
+
+#[cfg(not(target_env = "msvc"))]
+#[global_allocator]
+static ALLOC: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
+
+#[derive(clap::Parser, Debug)]
+#[command(
+    name = "Maurice Laveaux",
+    about = "Generates the labelled transition system of a linear process specification"
+)]
+struct Cli {
+    /// The linear process specification, in the .lps format.
+    filename: String,
+
+    /// The output LTS, in the .aut or .lts format.
+    output: Option<String>,
+
+    /// The order in which the states are explored.
+    #[arg(short, long, value_enum, default_value_t = SearchStrategy::Breadth)]
+    strategy: SearchStrategy,
+
+    /// The way in which the visited states are stored, where the compressed and disk storages require less memory.
+    #[arg(long, value_enum, default_value_t = StorageKind::Hash)]
+    storage: StorageKind,
+
+    /// The rewrite strategy that is used to evaluate the data expressions.
+    #[arg(short, long, default_value_t = Strategy::Outermost)]
+    rewriter: Strategy,
+
+    /// The maximum number of states, the transitions to other states are ignored.
+    #[arg(long)]
+    max_states: Option<usize>,
+
+    /// Stops the exploration at the first state without outgoing transitions.
+    #[arg(short = 'D', long)]
+    deadlock: bool,
+
+    /// Stops the exploration at the first transition with one of the given actions.
+    #[arg(short, long, value_delimiter = ',')]
+    action: Vec<String>,
+
+    /// Applies confluence reduction, where the states are replaced by their representative w.r.t. the confluent
+    /// tau-summands. The resulting LTS is branching bisimilar to the full LTS.
+    #[arg(short, long)]
+    confluence: bool,
+
+    /// Writes the trace to a detected deadlock or action to a file in the .trc format.
+    #[arg(short, long)]
+    trace: bool,
+
+    /// The number of threads that explore the state space, where every thread has its own rewriter.
+    #[arg(long, default_value_t = 1)]
+    threads: usize,
+
+    /// Periodically writes the visited states and the states that remain to be explored to the given directory.
+    #[arg(long)]
+    checkpoint: Option<PathBuf>,
+
+    /// The number of explored states after which a checkpoint is written.
+    #[arg(long, default_value_t = 1_000_000)]
+    checkpoint_interval: usize,
+
+    /// Resumes the exploration from the checkpoint in the checkpoint directory.
+    #[arg(long, requires = "checkpoint")]
+    resume: bool,
+
+    #[arg(long)]
+    time: bool,
+}
+
+fn main() -> Result<ExitCode, Box<dyn Error>> {
+    env_logger::init();
+
+    let cli = Cli::parse();
+    if cli.threads > 1 && cli.checkpoint.is_some() {
+        return Err("Checkpoints are only supported by the sequential exploration".into());
+    }
+
+    let mut timing = Timing::new();
+
+    let mut read_time = timing.start("read");
+    let lps = LinearProcessSpecification::read(&cli.filename)?;
+    read_time.finish();
+
+    let mut explore_time = timing.start("explore");
+    let tp = Rc::new(RefCell::new(TermPool::new()));
+    let options = ExploreOptions {
+        strategy: cli.strategy,
+        storage: cli.storage,
+        max_states: cli.max_states,
+        deadlock: cli.deadlock,
+        actions: cli.action.clone(),
+        confluence: cli.confluence,
+        checkpoint: cli.checkpoint.clone(),
+        checkpoint_interval: cli.checkpoint_interval,
+    };
+    let space = if cli.threads > 1 {
+        explore_parallel(&lps, cli.rewriter, &options, cli.threads)?
+    } else {
+        let mut generator = NextStateGenerator::new(&lps, tp.clone(), cli.rewriter)?;
+        match &cli.checkpoint {
+            Some(directory) if cli.resume => explore_resume(&mut generator, &tp, &options, directory)?,
+            _ => explore(&mut generator, &tp, &options)?,
+        }
+    };
+    let lts = space.to_lts();
+    explore_time.finish();
+
+    println!(
+        "Generated {} states and {} transitions",
+        lts.num_of_states(),
+        lts.num_of_transitions()
+    );
+
+    if let Some(detected) = space.detected {
+        let suffix = match detected {
+            Detected::Deadlock(state_index) => {
+                println!("Deadlock found in state {state_index}");
+                "dlk"
+            }
+            Detected::Action(_, label_index, _) => {
+                println!("Action {} found", space.labels[label_index]);
+                "act"
+            }
+        };
+
+        if cli.trace {
+            // The trace is stored next to the input, as is done by the mCRL2 toolset.
+            let path = Path::new(&cli.filename).with_extension("");
+            let path = format!("{}_{suffix}.trc", path.display());
+
+            let trace = space.trace(&lts).expect("A trace exists to every detected state");
+            let trc = TrcTrace::from_trace(&lts, &trace, &lts_info(&lps, &mut tp.borrow_mut(), &space))?;
+            write_trc(BufWriter::new(File::create(&path)?), &trc)?;
+            println!("Written trace of length {} to {path}", trace.len());
+        }
+    }
+
+    if let Some(output) = &cli.output {
+        let mut write_time = timing.start("write");
+        let path = Path::new(output);
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        match LtsFormat::from_path(path) {
+            Some(LtsFormat::Aut) => write_aut(&mut writer, &lts)?,
+            Some(LtsFormat::Lts) => write_lts(writer, &lts, &lts_info(&lps, &mut tp.borrow_mut(), &space))?,
+            _ => return Err(FormatError::UnknownFormat(output.clone()).into()),
+        }
+        write_time.finish();
+    }
+
+    if cli.time {
+        timing.print();
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Returns the information that is stored in an .lts file next to the transitions.
+fn lts_info(lps: &LinearProcessSpecification, tp: &mut TermPool, space: &StateSpace) -> LtsInfo<ATerm> {
+    let parameters: Vec<ATerm> = lps
+        .process_parameters()
+        .into_iter()
+        .map(|parameter| parameter.into())
+        .collect();
+
+    LtsInfo {
+        data_specification: lps.data_specification_term(),
+        process_parameters: tp.create_list(&parameters),
+        action_labels: lps.action_labels(),
+        multi_actions: space
+            .labels
+            .iter()
+            .cloned()
+            .zip(space.multi_actions.iter().cloned())
+            .collect::<HashMap<_, _>>(),
+        state_labels: (0..space.states.len())
+            .map(|state_index| {
+                let values: Vec<ATerm> = space
+                    .states
+                    .get(state_index)
+                    .into_iter()
+                    .map(|value| value.into())
+                    .collect();
+                tp.create_list(&values)
+            })
+            .collect(),
+        probabilistic_states: Vec::new(),
+        initial_state: ProbabilisticState::Single(0),
+    }
+}
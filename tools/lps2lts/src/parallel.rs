@@ -0,0 +1,381 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::error::Error;
+use std::rc::Rc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Barrier;
+use std::sync::Mutex;
+use std::thread;
+
+use dashmap::mapref::entry::Entry;
+use dashmap::DashMap;
+use log::debug;
+use log::info;
+
+use lps::confluent_representative;
+use lps::NextStateError;
+use lps::NextStateGenerator;
+use lps::SharedLinearProcess;
+use lts::LabelIndex;
+use lts::StateIndex;
+use mcrl2::aterm::ATerm;
+use mcrl2::aterm::ATermGlobal;
+use mcrl2::aterm::ATermList;
+use mcrl2::aterm::TermPool;
+use mcrl2::data::DataExpression;
+use mcrl2::lps::LinearProcessSpecification;
+use sabre::Strategy;
+
+use crate::explore::confluent_tau_summands;
+use crate::explore::Detected;
+use crate::explore::ExploreOptions;
+use crate::explore::SearchStrategy;
+use crate::explore::StateSpace;
+
+/// A state as the list of its values, which is protected globally such that
+/// it can be shared between threads. Since it is a single term, the states can
+/// be looked up before they are protected.
+type GlobalState = ATermGlobal;
+
+/// The transitions that are found by a single thread.
+type Transitions = Vec<(StateIndex, LabelIndex, StateIndex)>;
+
+/// The data that is shared by all the exploring threads.
+struct Shared<'a> {
+    options: &'a ExploreOptions,
+
+    /// Maps every state to its index and the transition by which it was first reached.
+    states: DashMap<GlobalState, (StateIndex, Option<(StateIndex, LabelIndex)>)>,
+    num_of_states: AtomicUsize,
+
+    /// Maps every label to its index and multi-action term.
+    labels: DashMap<String, (LabelIndex, ATermGlobal)>,
+    num_of_labels: AtomicUsize,
+
+    /// The states that remain to be explored by every thread.
+    queues: Vec<Mutex<VecDeque<(StateIndex, GlobalState)>>>,
+
+    /// The number of states that have been discovered, but not yet explored.
+    pending: AtomicUsize,
+
+    /// Set when a deadlock or action has been detected, or a thread failed.
+    stop: AtomicBool,
+    detected: Mutex<Option<Detected>>,
+}
+
+/// Explores the state space of the given linear process with the given number
+/// of threads, where every thread creates its own rewriter with the given
+/// strategy from the shared process.
+///
+/// Every thread explores the states in its own queue and steals half of the
+/// queue of another thread when its queue is empty. The states are stored in
+/// a concurrent map, so the numbering of the states depends on the scheduling
+/// of the threads. Otherwise, this behaves the same as [crate::explore::explore].
+pub fn explore_parallel(
+    lps: &LinearProcessSpecification,
+    rewriter: Strategy,
+    options: &ExploreOptions,
+    num_of_threads: usize,
+) -> Result<StateSpace, Box<dyn Error>> {
+    assert!(num_of_threads > 0, "At least one thread is required");
+
+    let process = SharedLinearProcess::new(lps);
+    let shared = Shared {
+        options,
+        states: DashMap::new(),
+        num_of_states: AtomicUsize::new(0),
+        labels: DashMap::new(),
+        num_of_labels: AtomicUsize::new(0),
+        queues: (0..num_of_threads).map(|_| Mutex::new(VecDeque::new())).collect(),
+        pending: AtomicUsize::new(0),
+        stop: AtomicBool::new(false),
+        detected: Mutex::new(None),
+    };
+
+    // The threads only start exploring once the initial state has been inserted.
+    let barrier = Barrier::new(num_of_threads);
+    let results: Vec<Result<Transitions, NextStateError>> = thread::scope(|s| {
+        let handles: Vec<_> = (0..num_of_threads)
+            .map(|thread_index| {
+                let shared = &shared;
+                let barrier = &barrier;
+                let process = &process;
+                s.spawn(move || {
+                    let tp = Rc::new(RefCell::new(TermPool::new()));
+                    let mut generator = NextStateGenerator::from_shared(process, tp.clone(), rewriter);
+                    let confluent = generator
+                        .as_mut()
+                        .map(|generator| confluent_tau_summands(generator, options))
+                        .unwrap_or_default();
+
+                    if thread_index == 0 {
+                        if let Ok(generator) = &mut generator {
+                            // An error is reported again when the transitions of the initial state are computed.
+                            let initial_state = generator.initial_state();
+                            let initial_state = confluent_representative(generator, initial_state.clone(), &confluent)
+                                .unwrap_or(initial_state);
+                            let initial_state = state_term(&mut tp.borrow_mut(), &initial_state).protect_global();
+                            shared.states.insert(initial_state.clone(), (0, None));
+                            shared.num_of_states.store(1, Ordering::SeqCst);
+                            shared.pending.store(1, Ordering::SeqCst);
+                            shared.queues[0].lock().unwrap().push_back((0, initial_state));
+                        }
+                    }
+                    barrier.wait();
+
+                    let result = generator.and_then(|mut generator| {
+                        explore_thread(thread_index, shared, &mut generator, &tp, &confluent)
+                    });
+
+                    if result.is_err() {
+                        shared.stop.store(true, Ordering::SeqCst);
+                    }
+                    result
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("The exploring thread panicked"))
+            .collect()
+    });
+
+    let mut transitions = Vec::new();
+    for result in results {
+        transitions.extend(result?);
+    }
+
+    // The states are inserted in the order of their indices, such that the storage assigns the same indices.
+    let num_of_states = shared.num_of_states.load(Ordering::SeqCst);
+    let mut states = vec![None; num_of_states];
+    let mut parents = vec![None; num_of_states];
+    for (state, (state_index, parent)) in shared.states {
+        states[state_index] = Some(state_values(&state));
+        parents[state_index] = parent;
+    }
+
+    let states: Vec<Vec<DataExpression>> = states
+        .into_iter()
+        .map(|state| state.expect("Every state index has a state"))
+        .collect();
+
+    let mut space = StateSpace {
+        states: options.storage.create(states[0].len())?,
+        labels: vec![String::new(); shared.num_of_labels.load(Ordering::SeqCst)],
+        multi_actions: Vec::new(),
+        transitions,
+        parents,
+        detected: shared.detected.into_inner().unwrap(),
+    };
+
+    for state in states {
+        space.states.insert(&state);
+    }
+
+    let mut multi_actions = vec![None; space.labels.len()];
+    for (label, (label_index, multi_action)) in shared.labels {
+        space.labels[label_index] = label;
+        multi_actions[label_index] = Some(multi_action.protect());
+    }
+    space.multi_actions = multi_actions
+        .into_iter()
+        .map(|multi_action| multi_action.expect("Every label has a multi-action"))
+        .collect();
+
+    info!(
+        "Explored {} states and {} transitions with {num_of_threads} threads",
+        space.states.len(),
+        space.transitions.len()
+    );
+    Ok(space)
+}
+
+/// Explores the states in the queue of the given thread until all states have
+/// been explored, and returns the transitions that were found. Every target
+/// state is replaced by its representative w.r.t. the confluent summands.
+fn explore_thread(
+    thread_index: usize,
+    shared: &Shared,
+    generator: &mut NextStateGenerator,
+    tp: &RefCell<TermPool>,
+    confluent: &[usize],
+) -> Result<Transitions, NextStateError> {
+    let options = shared.options;
+    let mut transitions = Vec::new();
+    let mut num_of_explored = 0usize;
+
+    while !shared.stop.load(Ordering::Relaxed) {
+        let Some((state_index, state)) = pop(thread_index, shared) else {
+            if shared.pending.load(Ordering::SeqCst) == 0 {
+                break;
+            }
+
+            thread::yield_now();
+            continue;
+        };
+
+        let outgoing = generator.transitions(&state_values(&state))?;
+        if options.deadlock && outgoing.is_empty() {
+            detect(shared, Detected::Deadlock(state_index));
+        }
+
+        for transition in outgoing {
+            let label = transition.label();
+            let found = transition
+                .actions
+                .iter()
+                .any(|action| options.actions.contains(&action.name));
+
+            // Only new labels and states are protected globally, which requires a lock on the protection set.
+            let existing = shared.labels.get(&label).map(|entry| entry.0);
+            let label_index = match existing {
+                Some(label_index) => label_index,
+                None => {
+                    shared
+                        .labels
+                        .entry(label)
+                        .or_insert_with(|| {
+                            let multi_action = transition.multi_action_term(&mut tp.borrow_mut()).protect_global();
+                            (shared.num_of_labels.fetch_add(1, Ordering::SeqCst), multi_action)
+                        })
+                        .0
+                }
+            };
+
+            let target = confluent_representative(generator, transition.target, confluent)?;
+            let target = state_term(&mut tp.borrow_mut(), &target);
+            let existing = shared.states.get(&target.copy()).map(|entry| entry.0);
+            let to = match existing {
+                Some(to) => to,
+                None => match shared.states.entry(target.protect_global()) {
+                    Entry::Occupied(entry) => entry.get().0,
+                    Entry::Vacant(entry) => {
+                        let to = shared.num_of_states.fetch_add(1, Ordering::SeqCst);
+                        if options.max_states.is_some_and(|max_states| to >= max_states) {
+                            shared.num_of_states.fetch_sub(1, Ordering::SeqCst);
+                            continue;
+                        }
+
+                        let target = entry.key().clone();
+                        entry.insert((to, Some((state_index, label_index))));
+                        shared.pending.fetch_add(1, Ordering::SeqCst);
+                        shared.queues[thread_index].lock().unwrap().push_back((to, target));
+                        to
+                    }
+                },
+            };
+
+            transitions.push((state_index, label_index, to));
+
+            if found {
+                detect(shared, Detected::Action(state_index, label_index, to));
+                break;
+            }
+        }
+
+        // The state is only finished after its successors have been queued.
+        shared.pending.fetch_sub(1, Ordering::SeqCst);
+
+        num_of_explored += 1;
+        if num_of_explored % 100_000 == 0 {
+            debug!("Thread {thread_index} explored {num_of_explored} states");
+        }
+    }
+
+    Ok(transitions)
+}
+
+/// Returns the next state from the queue of the given thread, or steals half
+/// of the queue of another thread when it is empty.
+fn pop(thread_index: usize, shared: &Shared) -> Option<(StateIndex, GlobalState)> {
+    {
+        let mut queue = shared.queues[thread_index].lock().unwrap();
+        let next = match shared.options.strategy {
+            SearchStrategy::Breadth => queue.pop_front(),
+            SearchStrategy::Depth => queue.pop_back(),
+        };
+
+        if next.is_some() {
+            return next;
+        }
+    }
+
+    let num_of_threads = shared.queues.len();
+    for offset in 1..num_of_threads {
+        let mut other = shared.queues[(thread_index + offset) % num_of_threads].lock().unwrap();
+        if other.is_empty() {
+            continue;
+        }
+
+        // Steal the oldest states, which are explored last by the other thread for a depth first search.
+        let half = other.len().div_ceil(2);
+        let mut stolen: VecDeque<_> = other.drain(..half).collect();
+        drop(other);
+
+        let next = stolen.pop_front();
+        shared.queues[thread_index].lock().unwrap().extend(stolen);
+        return next;
+    }
+
+    None
+}
+
+/// Records the first deadlock or action that is detected and stops all threads.
+fn detect(shared: &Shared, detected: Detected) {
+    let mut result = shared.detected.lock().unwrap();
+    if result.is_none() {
+        match detected {
+            Detected::Deadlock(state_index) => info!("Found a deadlock in state {state_index}"),
+            Detected::Action(state_index, _, _) => info!("Found an action in state {state_index}"),
+        }
+
+        *result = Some(detected);
+    }
+
+    shared.stop.store(true, Ordering::SeqCst);
+}
+
+/// Returns the list term `[d_0, ..., d_n]` of the values of the given state.
+fn state_term(tp: &mut TermPool, state: &[DataExpression]) -> ATerm {
+    let values: Vec<ATerm> = state.iter().map(|value| value.clone().into()).collect();
+    tp.create_list(&values)
+}
+
+/// Returns the values of the given state, protected by the current thread.
+fn state_values(state: &GlobalState) -> Vec<DataExpression> {
+    ATermList::<DataExpression>::from(state.protect()).iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::explore::explore;
+
+    use super::*;
+
+    use test_log::test;
+
+    #[test]
+    fn test_explore_parallel_abp() {
+        let filename = "../../examples/lps/abp.lps";
+        let lps = LinearProcessSpecification::read(filename).unwrap();
+
+        let tp = Rc::new(RefCell::new(TermPool::new()));
+        let mut generator = NextStateGenerator::new(&lps, tp.clone(), Strategy::Outermost).unwrap();
+        let expected = explore(&mut generator, &tp, &ExploreOptions::default()).unwrap();
+
+        for num_of_threads in [1, 4] {
+            let space =
+                explore_parallel(&lps, Strategy::Outermost, &ExploreOptions::default(), num_of_threads).unwrap();
+            assert_eq!(space.states.len(), expected.states.len());
+            assert_eq!(space.transitions.len(), expected.transitions.len());
+            assert_eq!(space.labels.len(), expected.labels.len());
+
+            // Every state except the initial state is reached from a state that was found earlier.
+            assert!(space.parents[0].is_none());
+            assert!(space.parents[1..].iter().all(|parent| parent.is_some()));
+        }
+    }
+}
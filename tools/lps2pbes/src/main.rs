@@ -0,0 +1,48 @@
+use std::error::Error;
+use std::fs;
+use std::process::ExitCode;
+
+use clap::Parser;
+use lts::StateFormula;
+use mcrl2::aterm::TermPool;
+use mcrl2::lps::LinearProcessSpecification;
+use pbes::lps2pbes;
+
+#[cfg(not(target_env = "msvc"))]
+#[global_allocator]
+static ALLOC: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
+
+#[derive(clap::Parser, Debug)]
+#[command(
+    name = "Maurice Laveaux",
+    about = "Translates a linear process specification and a modal formula into a parameterised boolean equation system"
+)]
+struct Cli {
+    /// The linear process specification, in the .lps format.
+    filename: String,
+
+    /// The file containing the state formula, typically with the .mcf extension.
+    #[arg(short, long)]
+    formula: String,
+
+    /// The output .pbes file, when omitted the equation system is printed in the textual format.
+    output: Option<String>,
+}
+
+fn main() -> Result<ExitCode, Box<dyn Error>> {
+    env_logger::init();
+
+    let cli = Cli::parse();
+    let lps = LinearProcessSpecification::read(&cli.filename)?;
+    let formula = StateFormula::parse(&fs::read_to_string(&cli.formula)?)?;
+
+    let mut tp = TermPool::new();
+    let pbes = lps2pbes(&mut tp, &lps, &formula)?;
+
+    match cli.output {
+        Some(output) => pbes.write(&mut tp, &output)?,
+        None => println!("{pbes}"),
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,97 @@
+use std::error::Error;
+use std::process::ExitCode;
+
+use clap::Parser;
+use lps::DependencyMatrix;
+use lps::LpsStatistics;
+use mcrl2::lps::LinearProcessSpecification;
+
+#[cfg(not(target_env = "msvc"))]
+#[global_allocator]
+static ALLOC: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
+
+#[derive(clap::Parser, Debug)]
+#[command(
+    name = "Maurice Laveaux",
+    about = "Prints statistics of a linear process specification"
+)]
+struct Cli {
+    /// The linear process specification, in the .lps format.
+    filename: String,
+
+    /// Also print the names of the used functions.
+    #[arg(long)]
+    functions: bool,
+
+    /// Print the linear process in the mCRL2 syntax instead of the statistics.
+    #[arg(long)]
+    print: bool,
+
+    /// Include the data specification when printing the linear process.
+    #[arg(long, requires = "print")]
+    data: bool,
+
+    /// Print for every summand whether it reads (r), writes (w), both (+) or neither (-) every process parameter.
+    #[arg(long)]
+    dependencies: bool,
+
+    /// Print the variable order computed by the FORCE heuristic, which can be used for symbolic exploration.
+    #[arg(long)]
+    order: bool,
+
+    /// The maximum number of iterations of the FORCE heuristic.
+    #[arg(long, default_value_t = 100, requires = "order")]
+    order_iterations: usize,
+}
+
+fn main() -> Result<ExitCode, Box<dyn Error>> {
+    env_logger::init();
+
+    let cli = Cli::parse();
+    let lps = LinearProcessSpecification::read(&cli.filename)?;
+
+    if cli.print {
+        if cli.data {
+            println!("{lps}");
+        } else {
+            println!("{}", lps.pretty_print_process());
+        }
+
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if cli.dependencies || cli.order {
+        let matrix = DependencyMatrix::new(&lps);
+        if cli.dependencies {
+            println!("{matrix}");
+        }
+
+        if cli.order {
+            let initial: Vec<usize> = (0..matrix.parameters().len()).collect();
+            let order = matrix.force_order(cli.order_iterations);
+            let names: Vec<&str> = order
+                .iter()
+                .map(|&parameter_index| matrix.parameters()[parameter_index].name())
+                .collect();
+
+            println!("{}", names.join(" "));
+            println!(
+                "Total span of the summands: {} (initially {})",
+                matrix.span(&order),
+                matrix.span(&initial)
+            );
+        }
+
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let statistics = LpsStatistics::new(&lps);
+    println!("{statistics}");
+    if cli.functions {
+        for name in &statistics.used_functions {
+            println!("  {name}");
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,99 @@
+use std::error::Error;
+use std::process::ExitCode;
+
+use clap::Parser;
+use log::info;
+use mcrl2::lps::LinearProcessSpecification;
+
+#[cfg(not(target_env = "msvc"))]
+#[global_allocator]
+static ALLOC: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
+
+#[derive(clap::Parser, Debug)]
+#[command(name = "Maurice Laveaux", about = "Transformations on linear process specifications")]
+enum Cli {
+    Constelm(ConstelmArgs),
+    Parelm(ParelmArgs),
+    Sumelm(SumelmArgs),
+    Suminst(SuminstArgs),
+}
+
+#[derive(clap::Args, Debug)]
+#[command(about = "Removes the process parameters that are constant in every reachable state (lpsconstelm)")]
+struct ConstelmArgs {
+    /// The linear process specification, in the .lps format.
+    input: String,
+
+    /// The resulting linear process specification, in the .lps format.
+    output: String,
+
+    /// Replaces the global variables by arbitrary values, which can result in more constant parameters.
+    #[arg(short = 'f', long)]
+    instantiate_free_variables: bool,
+}
+
+#[derive(clap::Args, Debug)]
+#[command(about = "Removes the process parameters that do not influence the behaviour (lpsparelm)")]
+struct ParelmArgs {
+    /// The linear process specification, in the .lps format.
+    input: String,
+
+    /// The resulting linear process specification, in the .lps format.
+    output: String,
+}
+
+#[derive(clap::Args, Debug)]
+#[command(about = "Removes the sum variables that are determined by the condition (lpssumelm)")]
+struct SumelmArgs {
+    /// The linear process specification, in the .lps format.
+    input: String,
+
+    /// The resulting linear process specification, in the .lps format.
+    output: String,
+}
+
+#[derive(clap::Args, Debug)]
+#[command(about = "Replaces the sum variables of finite sorts by a summand for every value (lpssuminst)")]
+struct SuminstArgs {
+    /// The linear process specification, in the .lps format.
+    input: String,
+
+    /// The resulting linear process specification, in the .lps format.
+    output: String,
+
+    /// Only instantiates the sum variables of the summands without actions.
+    #[arg(short, long)]
+    tau: bool,
+}
+
+fn main() -> Result<ExitCode, Box<dyn Error>> {
+    env_logger::init();
+
+    let cli = Cli::parse();
+    let (input, output) = match &cli {
+        Cli::Constelm(args) => (&args.input, &args.output),
+        Cli::Parelm(args) => (&args.input, &args.output),
+        Cli::Sumelm(args) => (&args.input, &args.output),
+        Cli::Suminst(args) => (&args.input, &args.output),
+    };
+
+    let mut lps = LinearProcessSpecification::read(input)?;
+    let num_of_parameters = lps.process_parameters().len();
+    let num_of_summands = lps.action_summands().len();
+
+    match &cli {
+        Cli::Constelm(args) => lps.remove_constant_parameters(args.instantiate_free_variables),
+        Cli::Parelm(_) => lps.remove_unused_parameters(),
+        Cli::Sumelm(_) => lps.eliminate_sum_variables(),
+        Cli::Suminst(args) => lps.instantiate_sum_variables(args.tau),
+    }
+
+    info!(
+        "The result has {} of the {num_of_parameters} process parameters and {} instead of {num_of_summands} summands",
+        lps.process_parameters().len(),
+        lps.action_summands().len()
+    );
+    lps.write(output)?;
+
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,245 @@
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+use clap::ValueEnum;
+use io::formats::read_lts_file;
+use lts::branching_bisim_sigref;
+use lts::disjoint_union;
+use lts::distinguishing_formula;
+use lts::is_coupled_simulated_by;
+use lts::is_simulated_by;
+use lts::refinement_counterexample;
+use lts::strong_bisim_on_the_fly;
+use lts::strong_bisim_sigref;
+use lts::weak_bisim_sigref;
+use lts::IndexedPartition;
+use lts::LabelledTransitionSystem;
+use lts::Partition;
+use lts::RefinementType;
+use utilities::Timing;
+
+#[cfg(not(target_env = "msvc"))]
+#[global_allocator]
+static ALLOC: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Equivalence {
+    StrongBisim,
+    StrongBisimOnTheFly,
+    BranchingBisim,
+    WeakBisim,
+    Trace,
+    Failures,
+    FailuresDivergences,
+    Simulation,
+    CoupledSimulation,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Preorder {
+    Trace,
+    Failures,
+    FailuresDivergences,
+    Simulation,
+    CoupledSimulation,
+}
+
+#[derive(clap::Parser, Debug)]
+#[command(
+    name = "Maurice Laveaux",
+    about = "Compares two labelled transition systems with respect to an equivalence or preorder"
+)]
+struct Cli {
+    /// The left LTS, in the .aut, .lts or .fsm format.
+    left: String,
+
+    /// The right LTS, in the .aut, .lts or .fsm format.
+    right: String,
+
+    #[arg(
+        short,
+        long,
+        value_enum,
+        conflicts_with = "preorder",
+        required_unless_present = "preorder"
+    )]
+    equivalence: Option<Equivalence>,
+
+    /// Checks whether the left LTS is smaller than the right LTS, i.e., whether the left LTS refines or is simulated by the right LTS.
+    #[arg(short, long, value_enum)]
+    preorder: Option<Preorder>,
+
+    #[arg(short, long)]
+    tau: Option<Vec<String>>,
+
+    /// Prints a counterexample when the comparison fails, or writes it to the given file.
+    #[arg(long, num_args = 0..=1)]
+    counterexample: Option<Option<PathBuf>>,
+
+    #[arg(long)]
+    time: bool,
+}
+
+fn main() -> Result<ExitCode, Box<dyn Error>> {
+    env_logger::init();
+
+    let cli = Cli::parse();
+
+    let mut timing = Timing::new();
+    let tau = cli.tau.unwrap_or_default();
+
+    let mut read_time = timing.start("read");
+    let left = read_lts_file(Path::new(&cli.left), tau.clone())?;
+    let right = read_lts_file(Path::new(&cli.right), tau)?;
+    read_time.finish();
+
+    let mut compare_time = timing.start("compare");
+    let (related, counterexample) = if let Some(equivalence) = cli.equivalence {
+        let (related, counterexample) = compare_equivalence(&left, &right, equivalence, &mut timing);
+
+        let name = value_name(equivalence);
+        if related {
+            println!("The LTSs are {name} equivalent");
+        } else {
+            println!("The LTSs are not {name} equivalent");
+        }
+        (related, counterexample)
+    } else if let Some(preorder) = cli.preorder {
+        let (related, counterexample) = compare_preorder(&left, &right, preorder);
+
+        let name = value_name(preorder);
+        if related {
+            println!("The left LTS is smaller than the right LTS for the {name} preorder");
+        } else {
+            println!("The left LTS is not smaller than the right LTS for the {name} preorder");
+        }
+        (related, counterexample)
+    } else {
+        (true, None)
+    };
+
+    if let (Some(output), Some(counterexample)) = (&cli.counterexample, counterexample) {
+        match output {
+            Some(path) => fs::write(path, counterexample + "\n")?,
+            None => println!("{counterexample}"),
+        }
+    }
+    compare_time.finish();
+
+    if cli.time {
+        timing.print();
+    }
+
+    if related {
+        Ok(ExitCode::SUCCESS)
+    } else {
+        Ok(ExitCode::FAILURE)
+    }
+}
+
+/// Returns true iff the initial states of both LTSs are equivalent, and
+/// otherwise the counterexample when it can be computed for the equivalence.
+fn compare_equivalence(
+    left: &LabelledTransitionSystem,
+    right: &LabelledTransitionSystem,
+    equivalence: Equivalence,
+    timing: &mut Timing,
+) -> (bool, Option<String>) {
+    let refinement = match equivalence {
+        Equivalence::StrongBisim | Equivalence::StrongBisimOnTheFly => {
+            let related = if matches!(equivalence, Equivalence::StrongBisim) {
+                is_bisimilar(left, right, strong_bisim_sigref, timing)
+            } else {
+                strong_bisim_on_the_fly(left, right)
+            };
+
+            if related {
+                return (true, None);
+            }
+
+            return (
+                false,
+                distinguishing_formula(left, right)
+                    .map(|formula| format!("The formula {formula} holds in the left LTS, but not in the right LTS")),
+            );
+        }
+        Equivalence::BranchingBisim => {
+            return (is_bisimilar(left, right, branching_bisim_sigref, timing), None);
+        }
+        Equivalence::WeakBisim => {
+            return (is_bisimilar(left, right, weak_bisim_sigref, timing), None);
+        }
+        Equivalence::Simulation => {
+            return (is_simulated_by(left, right) && is_simulated_by(right, left), None);
+        }
+        Equivalence::CoupledSimulation => {
+            return (
+                is_coupled_simulated_by(left, right) && is_coupled_simulated_by(right, left),
+                None,
+            );
+        }
+        Equivalence::Trace => RefinementType::Trace,
+        Equivalence::Failures => RefinementType::Failures,
+        Equivalence::FailuresDivergences => RefinementType::FailuresDivergences,
+    };
+
+    // The behaviour of the left LTS that the right LTS does not have, or vice versa.
+    let counterexample = refinement_counterexample(right, left, refinement)
+        .map(|counterexample| format!("{counterexample} (implementation: left, specification: right)"))
+        .or_else(|| {
+            refinement_counterexample(left, right, refinement)
+                .map(|counterexample| format!("{counterexample} (implementation: right, specification: left)"))
+        });
+
+    (counterexample.is_none(), counterexample)
+}
+
+/// Returns true iff the left LTS is smaller than the right LTS, and otherwise
+/// the counterexample when it can be computed for the preorder.
+fn compare_preorder(
+    left: &LabelledTransitionSystem,
+    right: &LabelledTransitionSystem,
+    preorder: Preorder,
+) -> (bool, Option<String>) {
+    let refinement = match preorder {
+        Preorder::Simulation => return (is_simulated_by(left, right), None),
+        Preorder::CoupledSimulation => return (is_coupled_simulated_by(left, right), None),
+        Preorder::Trace => RefinementType::Trace,
+        Preorder::Failures => RefinementType::Failures,
+        Preorder::FailuresDivergences => RefinementType::FailuresDivergences,
+    };
+
+    let counterexample =
+        refinement_counterexample(right, left, refinement).map(|counterexample| counterexample.to_string());
+    (counterexample.is_none(), counterexample)
+}
+
+/// Returns true iff the initial states of both LTSs are in the same block of
+/// the partition that is computed for their disjoint union.
+fn is_bisimilar<F>(
+    left: &LabelledTransitionSystem,
+    right: &LabelledTransitionSystem,
+    reduce: F,
+    timing: &mut Timing,
+) -> bool
+where
+    F: Fn(&LabelledTransitionSystem, &mut Timing) -> IndexedPartition,
+{
+    let (lts, offset) = disjoint_union(left, right);
+    let partition = reduce(&lts, timing);
+
+    partition.block_number(left.initial_state_index()) == partition.block_number(right.initial_state_index() + offset)
+}
+
+/// Returns the name of the value as it is given on the command line.
+fn value_name(value: impl ValueEnum) -> String {
+    value
+        .to_possible_value()
+        .expect("Values are not skipped")
+        .get_name()
+        .to_string()
+}
@@ -0,0 +1,313 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::BufWriter;
+use std::io::Write;
+use std::path::Path;
+
+use glam::Vec2;
+use glam::Vec3Swizzles;
+use log::info;
+
+use crate::Viewer;
+
+/// The space around the graph in the exported images.
+const MARGIN: f32 = 10.0;
+
+/// Writes the current layout of the viewer to the given path, where the
+/// extension determines whether it is written as SVG or PNG. The scale
+/// determines the number of pixels per unit of the PNG image.
+pub fn export_image(
+    viewer: &mut Viewer,
+    path: &Path,
+    draw_actions: bool,
+    state_radius: f32,
+    label_text_size: f32,
+    scale: f32,
+) -> Result<(), Box<dyn Error>> {
+    let extension = path.extension().and_then(|extension| extension.to_str());
+    if !matches!(extension, Some("svg") | Some("png")) {
+        return Err(format!(
+            "Unsupported image format {}, expected .svg or .png",
+            path.to_string_lossy()
+        )
+        .into());
+    }
+
+    let mut writer = BufWriter::new(File::create(path)?);
+    if extension == Some("svg") {
+        write_svg(viewer, &mut writer, draw_actions, state_radius, label_text_size)?;
+    } else {
+        write_png(viewer, &mut writer, draw_actions, state_radius, label_text_size, scale)?;
+    }
+
+    writer.flush()?;
+    info!("Exported the graph to {}", path.to_string_lossy());
+    Ok(())
+}
+
+/// Writes the current layout of the viewer as a scalable vector graphic, in
+/// which the labels are text elements. Contrary to [Viewer::render] the
+/// handles of the edges are omitted.
+pub fn write_svg(
+    viewer: &mut Viewer,
+    writer: &mut impl Write,
+    draw_actions: bool,
+    state_radius: f32,
+    label_text_size: f32,
+) -> Result<(), Box<dyn Error>> {
+    let (min, max) = bounds(viewer, draw_actions, state_radius, label_text_size);
+    let size = max - min;
+
+    writeln!(
+        writer,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="{} {} {} {}">"#,
+        size.x, size.y, min.x, min.y, size.x, size.y
+    )?;
+    writeln!(
+        writer,
+        r#"<rect x="{}" y="{}" width="{}" height="{}" fill="white"/>"#,
+        min.x, min.y, size.x, size.y
+    )?;
+
+    let mut edges = String::new();
+    let mut arrows = String::new();
+    let mut labels = String::new();
+
+    let lts = viewer.lts.clone();
+    for state_index in lts.iter_states() {
+        let from = viewer.view_states[state_index].position.xy();
+
+        for (transition_index, (label, to)) in lts.outgoing_transitions(state_index).enumerate() {
+            let to_position = viewer.view_states[to].position.xy();
+            let handle_offset = viewer.view_states[state_index].outgoing[transition_index]
+                .handle_offset
+                .xy();
+
+            // The colored edges are drawn wider.
+            let color = viewer.transition_color(state_index, transition_index);
+            let style = color.map_or(String::new(), |color| {
+                format!(r#" stroke="{}" stroke-width="2""#, svg_color(color))
+            });
+
+            let label_position = if to != state_index {
+                edges += &format!(
+                    r#"<line x1="{}" y1="{}" x2="{}" y2="{}"{}/>"#,
+                    from.x, from.y, to_position.x, to_position.y, style
+                );
+                edges += "\n";
+
+                // The arrow points at the border of the target state.
+                let direction = (from - to_position).normalize_or_zero();
+                let tip = to_position + direction * (state_radius + 0.5);
+                let base = to_position + direction * (state_radius + 5.5);
+                let left = base + direction.perp() * 2.0;
+                let right = base - direction.perp() * 2.0;
+                arrows += &format!(
+                    r#"<polygon points="{},{} {},{} {},{}"{}/>"#,
+                    tip.x,
+                    tip.y,
+                    left.x,
+                    left.y,
+                    right.x,
+                    right.y,
+                    color.map_or(String::new(), |color| format!(r#" fill="{}""#, svg_color(color)))
+                );
+                arrows += "\n";
+
+                (from + to_position) / 2.0
+            } else {
+                let middle = from + handle_offset / 2.0;
+                edges += &format!(
+                    r#"<circle cx="{}" cy="{}" r="{}"{}/>"#,
+                    middle.x,
+                    middle.y,
+                    handle_offset.length() / 2.0,
+                    style
+                );
+                edges += "\n";
+
+                from + handle_offset
+            };
+
+            if draw_actions {
+                let (_, baseline) = viewer.label_extents(label, label_text_size);
+                labels += &format!(
+                    r#"<text x="{}" y="{}">{}</text>"#,
+                    label_position.x,
+                    label_position.y + baseline,
+                    escape(&lts.labels()[label])
+                );
+                labels += "\n";
+            }
+        }
+    }
+
+    writeln!(writer, r#"<g stroke="black" fill="none">"#)?;
+    write!(writer, "{edges}")?;
+    writeln!(writer, "</g>")?;
+
+    writeln!(writer, r#"<g fill="black">"#)?;
+    write!(writer, "{arrows}")?;
+    writeln!(writer, "</g>")?;
+
+    writeln!(
+        writer,
+        r#"<g font-family="sans-serif" font-size="{label_text_size}" fill="black">"#
+    )?;
+    write!(writer, "{labels}")?;
+    writeln!(writer, "</g>")?;
+
+    // Draw the states on top.
+    writeln!(writer, r#"<g stroke="black" fill="white">"#)?;
+    for (index, state_view) in viewer.view_states.iter().enumerate() {
+        let fill = viewer
+            .state_color(index)
+            .map_or(String::new(), |color| format!(r#" fill="{}""#, svg_color(color)));
+
+        writeln!(
+            writer,
+            r#"<circle cx="{}" cy="{}" r="{}"{}/>"#,
+            state_view.position.x, state_view.position.y, state_radius, fill
+        )?;
+    }
+    writeln!(writer, "</g>")?;
+
+    writeln!(writer, "</svg>")?;
+    Ok(())
+}
+
+/// Writes the current layout of the viewer as a PNG image with the given
+/// number of pixels per unit, rendered by [Viewer::render].
+pub fn write_png(
+    viewer: &mut Viewer,
+    writer: &mut impl Write,
+    draw_actions: bool,
+    state_radius: f32,
+    label_text_size: f32,
+    scale: f32,
+) -> Result<(), Box<dyn Error>> {
+    let (min, max) = bounds(viewer, draw_actions, state_radius, label_text_size);
+    let width = ((max.x - min.x) * scale).ceil() as u32;
+    let height = ((max.y - min.y) * scale).ceil() as u32;
+
+    let mut pixmap = tiny_skia::Pixmap::new(width, height)
+        .ok_or_else(|| format!("Cannot create an image of {width} by {height} pixels"))?;
+
+    // The view is centered on the middle of the bounds.
+    let center = (min + max) / 2.0;
+    viewer.render(
+        &mut pixmap.as_mut(),
+        draw_actions,
+        state_radius,
+        -center.x,
+        -center.y,
+        width,
+        height,
+        scale,
+        label_text_size,
+    );
+
+    writer.write_all(&pixmap.encode_png()?)?;
+    Ok(())
+}
+
+/// Writes the position of every state of the current layout of the viewer as comma separated values.
+pub fn write_positions(viewer: &Viewer, writer: &mut impl Write) -> Result<(), Box<dyn Error>> {
+    writeln!(writer, "state,x,y")?;
+    for (index, state_view) in viewer.view_states.iter().enumerate() {
+        writeln!(writer, "{},{},{}", index, state_view.position.x, state_view.position.y)?;
+    }
+
+    Ok(())
+}
+
+/// Returns the top left and bottom right corner of the area covered by the
+/// states, the self loops and the labels of the graph, including a margin.
+fn bounds(viewer: &mut Viewer, draw_actions: bool, state_radius: f32, label_text_size: f32) -> (Vec2, Vec2) {
+    let mut min = Vec2::splat(f32::MAX);
+    let mut max = Vec2::splat(f32::MIN);
+
+    let lts = viewer.lts.clone();
+    for state_index in lts.iter_states() {
+        let position = viewer.view_states[state_index].position.xy();
+        min = min.min(position - state_radius);
+        max = max.max(position + state_radius);
+
+        for (transition_index, (label, to)) in lts.outgoing_transitions(state_index).enumerate() {
+            let handle_offset = viewer.view_states[state_index].outgoing[transition_index]
+                .handle_offset
+                .xy();
+
+            let label_position = if to != state_index {
+                (position + viewer.view_states[to].position.xy()) / 2.0
+            } else {
+                let middle = position + handle_offset / 2.0;
+                let radius = handle_offset.length() / 2.0;
+                min = min.min(middle - radius);
+                max = max.max(middle + radius);
+                position + handle_offset
+            };
+
+            if draw_actions {
+                let (size, _) = viewer.label_extents(label, label_text_size);
+                min = min.min(label_position);
+                max = max.max(label_position + size);
+            }
+        }
+    }
+
+    if lts.num_of_states() == 0 {
+        return (Vec2::ZERO, Vec2::splat(2.0 * MARGIN));
+    }
+
+    (min - MARGIN, max + MARGIN)
+}
+
+/// Returns the given RGB color in the notation of SVG.
+fn svg_color([red, green, blue]: [u8; 3]) -> String {
+    format!("rgb({red},{green},{blue})")
+}
+
+/// Escapes the characters that have a special meaning in XML.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use io::io_aut::read_aut;
+
+    use crate::GraphLayout;
+
+    use super::*;
+
+    #[test]
+    fn test_export() {
+        let file = include_str!("../../../../examples/lts/abp.aut");
+        let lts = Arc::new(read_aut(file.as_bytes(), vec![]).unwrap());
+
+        let layout = GraphLayout::new(&lts);
+        let mut viewer = Viewer::new(&lts);
+        viewer.update(&layout);
+
+        let mut svg: Vec<u8> = Vec::new();
+        write_svg(&mut viewer, &mut svg, true, 5.0, 14.0).unwrap();
+
+        let svg = String::from_utf8(svg).unwrap();
+        assert!(svg.starts_with("<svg"));
+        assert_eq!(svg.matches("<text").count(), lts.num_of_transitions());
+
+        let mut png: Vec<u8> = Vec::new();
+        write_png(&mut viewer, &mut png, true, 5.0, 14.0, 2.0).unwrap();
+        assert!(png.starts_with(b"\x89PNG"));
+
+        let mut csv: Vec<u8> = Vec::new();
+        write_positions(&viewer, &mut csv).unwrap();
+        assert_eq!(String::from_utf8(csv).unwrap().lines().count(), lts.num_of_states() + 1);
+    }
+}
@@ -0,0 +1,836 @@
+use std::collections::HashMap;
+use std::sync::mpsc;
+
+use bytemuck::Pod;
+use bytemuck::Zeroable;
+use glam::Vec2;
+use glam::Vec3Swizzles;
+use log::debug;
+use log::info;
+use thiserror::Error;
+use wgpu::util::DeviceExt;
+
+use crate::Viewer;
+
+#[derive(Error, Debug)]
+pub enum GpuError {
+    #[error("No suitable graphics adapter is available")]
+    NoAdapter,
+
+    #[error("Failed to create the graphics device: {0}")]
+    RequestDevice(#[from] wgpu::RequestDeviceError),
+
+    #[error("Failed to read back the rendered image: {0}")]
+    Readback(#[from] wgpu::BufferAsyncError),
+
+    #[error("The labels do not fit into a texture of {0} by {0} pixels")]
+    AtlasTooLarge(u32),
+}
+
+/// The number of pixels around every glyph in the signed distance field, which
+/// determines how far a label can be scaled before its edges become blurry.
+const SDF_SPREAD: i32 = 4;
+
+/// The width of the texture that contains the glyphs of all labels.
+const ATLAS_WIDTH: u32 = 1024;
+
+/// The number of line segments used to draw the self loops.
+const SELFLOOP_SEGMENTS: usize = 32;
+
+/// The number of line segments used to draw the edge handles.
+const HANDLE_SEGMENTS: usize = 8;
+
+/// The format of the render target, which matches the pixel buffers of the software renderer.
+const TARGET_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+
+/// The uniforms of the shaders, which must match `View` in gpu_renderer.wgsl.
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+struct ViewUniform {
+    translation: [f32; 2],
+    screen: [f32; 2],
+    zoom_level: f32,
+    state_radius: f32,
+    padding: [f32; 2],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+struct ColorVertex {
+    position: [f32; 2],
+    color: [f32; 4],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+struct StateInstance {
+    center: [f32; 2],
+    color: [f32; 4],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+struct GlyphInstance {
+    /// The position of the label in world coordinates.
+    origin: [f32; 2],
+
+    /// The top left corner and size of the glyph w.r.t. the origin.
+    offset: [f32; 2],
+    size: [f32; 2],
+
+    /// The top left and bottom right corner of the glyph in the atlas.
+    uv: [f32; 4],
+}
+
+const BLACK: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
+const WHITE: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+
+/// Renders the graph of a [Viewer] with the GPU, as an alternative to
+/// [Viewer::render] that remains interactive for graphs with hundreds of
+/// thousands of states.
+///
+/// The states are drawn as instanced quads that are cut into circles by the
+/// fragment shader, the edges and arrows as batches of lines and triangles,
+/// and the labels as instanced quads for every glyph that sample a signed
+/// distance field, such that they stay sharp when zooming in. The image is
+/// rendered into an offscreen texture that is copied into a pixel buffer,
+/// which makes it a drop-in replacement for the software renderer.
+///
+/// The edges are drawn one pixel wide independent of the zoom level.
+pub struct GpuRenderer {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+
+    view_buffer: wgpu::Buffer,
+    view_bind_group: wgpu::BindGroup,
+    atlas_layout: wgpu::BindGroupLayout,
+    atlas_sampler: wgpu::Sampler,
+
+    line_pipeline: wgpu::RenderPipeline,
+    triangle_pipeline: wgpu::RenderPipeline,
+    state_pipeline: wgpu::RenderPipeline,
+    text_pipeline: wgpu::RenderPipeline,
+
+    /// The texture that is rendered into, which is recreated when the size changes.
+    target: Option<RenderTarget>,
+
+    /// The glyphs of the labels, which are recreated when the text size changes.
+    labels: Option<LabelAtlas>,
+}
+
+struct RenderTarget {
+    width: u32,
+    height: u32,
+    texture: wgpu::Texture,
+
+    /// The rows of the image are padded to the alignment required for copying textures.
+    readback: wgpu::Buffer,
+    padded_bytes_per_row: u32,
+}
+
+struct LabelAtlas {
+    label_text_size: f32,
+    bind_group: wgpu::BindGroup,
+
+    /// For every label the glyphs relative to the position of the label.
+    glyphs: Vec<Vec<GlyphInstance>>,
+}
+
+impl GpuRenderer {
+    /// Creates a renderer on the default graphics adapter, which does not need a window.
+    pub fn new() -> Result<GpuRenderer, GpuError> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
+
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            compatible_surface: None,
+            force_fallback_adapter: false,
+        }))
+        .ok_or(GpuError::NoAdapter)?;
+        info!("Rendering with {:?}", adapter.get_info());
+
+        let (device, queue) = pollster::block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label: Some("ltsgraph device"),
+                required_features: wgpu::Features::empty(),
+                required_limits: wgpu::Limits::downlevel_defaults().using_resolution(adapter.limits()),
+                memory_hints: wgpu::MemoryHints::default(),
+            },
+            None,
+        ))?;
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("ltsgraph shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("gpu_renderer.wgsl").into()),
+        });
+
+        let view_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("ltsgraph view"),
+            size: std::mem::size_of::<ViewUniform>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let view_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("ltsgraph view layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+        let view_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("ltsgraph view"),
+            layout: &view_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: view_buffer.as_entire_binding(),
+            }],
+        });
+
+        let atlas_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("ltsgraph atlas layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        let atlas_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("ltsgraph atlas sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let geometry_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("ltsgraph geometry layout"),
+            bind_group_layouts: &[&view_layout],
+            push_constant_ranges: &[],
+        });
+
+        let text_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("ltsgraph text layout"),
+            bind_group_layouts: &[&view_layout, &atlas_layout],
+            push_constant_ranges: &[],
+        });
+
+        let color_attributes = wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x4];
+        let color_vertex = wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<ColorVertex>() as u64,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &color_attributes,
+        };
+
+        let state_instance = wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<StateInstance>() as u64,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &color_attributes,
+        };
+
+        let glyph_attributes = wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2, 2 => Float32x2, 3 => Float32x4];
+        let glyph_instance = wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<GlyphInstance>() as u64,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &glyph_attributes,
+        };
+
+        let create_pipeline = |name: &str,
+                               layout: &wgpu::PipelineLayout,
+                               entry_point: &str,
+                               buffer: wgpu::VertexBufferLayout,
+                               topology: wgpu::PrimitiveTopology| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(name),
+                layout: Some(layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: Some(&format!("vs_{entry_point}")),
+                    buffers: &[buffer],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: Some(&format!("fs_{entry_point}")),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: TARGET_FORMAT,
+                        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology,
+                    ..Default::default()
+                },
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+                cache: None,
+            })
+        };
+
+        let line_pipeline = create_pipeline(
+            "ltsgraph lines",
+            &geometry_layout,
+            "geometry",
+            color_vertex.clone(),
+            wgpu::PrimitiveTopology::LineList,
+        );
+        let triangle_pipeline = create_pipeline(
+            "ltsgraph triangles",
+            &geometry_layout,
+            "geometry",
+            color_vertex,
+            wgpu::PrimitiveTopology::TriangleList,
+        );
+        let state_pipeline = create_pipeline(
+            "ltsgraph states",
+            &geometry_layout,
+            "state",
+            state_instance,
+            wgpu::PrimitiveTopology::TriangleList,
+        );
+        let text_pipeline = create_pipeline(
+            "ltsgraph text",
+            &text_layout,
+            "text",
+            glyph_instance,
+            wgpu::PrimitiveTopology::TriangleList,
+        );
+
+        Ok(GpuRenderer {
+            device,
+            queue,
+            view_buffer,
+            view_bind_group,
+            atlas_layout,
+            atlas_sampler,
+            line_pipeline,
+            triangle_pipeline,
+            state_pipeline,
+            text_pipeline,
+            target: None,
+            labels: None,
+        })
+    }
+
+    /// Render the current state of the viewer into the given RGBA pixel
+    /// buffer of the given screen size, with the same settings as [Viewer::render].
+    #[allow(clippy::too_many_arguments)]
+    pub fn render(
+        &mut self,
+        viewer: &mut Viewer,
+        pixels: &mut [u8],
+        draw_actions: bool,
+        state_radius: f32,
+        view_x: f32,
+        view_y: f32,
+        screen_x: u32,
+        screen_y: u32,
+        zoom_level: f32,
+        label_text_size: f32,
+    ) -> Result<(), GpuError> {
+        debug_assert_eq!(pixels.len(), (screen_x * screen_y * 4) as usize);
+
+        if self
+            .target
+            .as_ref()
+            .map_or(true, |target| target.width != screen_x || target.height != screen_y)
+        {
+            self.target = Some(self.create_target(screen_x, screen_y));
+        }
+
+        if draw_actions
+            && self
+                .labels
+                .as_ref()
+                .map_or(true, |labels| labels.label_text_size != label_text_size)
+        {
+            self.labels = Some(self.create_atlas(viewer, label_text_size)?);
+        }
+
+        self.queue.write_buffer(
+            &self.view_buffer,
+            0,
+            bytemuck::bytes_of(&ViewUniform {
+                translation: [view_x, view_y],
+                screen: [screen_x as f32, screen_y as f32],
+                zoom_level,
+                state_radius,
+                padding: [0.0; 2],
+            }),
+        );
+
+        // Collect the geometry of the edges, arrows and labels.
+        let mut lines: Vec<ColorVertex> = Vec::new();
+        let mut triangles: Vec<ColorVertex> = Vec::new();
+        let mut glyphs: Vec<GlyphInstance> = Vec::new();
+
+        // The colored edges are drawn on top of the others.
+        let mut colored_lines: Vec<ColorVertex> = Vec::new();
+        let mut colored_triangles: Vec<ColorVertex> = Vec::new();
+
+        for state_index in viewer.lts.iter_states() {
+            let state_view = &viewer.view_states[state_index];
+            let from = state_view.position.xy();
+
+            for (transition_index, (label, to)) in viewer.lts.outgoing_transitions(state_index).enumerate() {
+                let to_position = viewer.view_states[to].position.xy();
+                let transition_view = &state_view.outgoing[transition_index];
+                let handle_offset = transition_view.handle_offset.xy();
+
+                let (lines, triangles, color) = match viewer.transition_color(state_index, transition_index) {
+                    Some(color) => (&mut colored_lines, &mut colored_triangles, to_rgba(color)),
+                    None => (&mut lines, &mut triangles, BLACK),
+                };
+
+                let label_position = if to != state_index {
+                    lines.push(color_vertex(from, color));
+                    lines.push(color_vertex(to_position, color));
+
+                    // The arrow points at the border of the target state, as in the software renderer.
+                    let direction = (from - to_position).normalize_or_zero();
+                    let base = to_position + direction * (state_radius + 5.5);
+                    triangles.push(color_vertex(to_position + direction * (state_radius + 0.5), color));
+                    triangles.push(color_vertex(base + direction.perp() * 2.0, color));
+                    triangles.push(color_vertex(base - direction.perp() * 2.0, color));
+
+                    let middle = (from + to_position) / 2.0;
+                    push_circle(lines, middle + handle_offset, 1.0, HANDLE_SEGMENTS, color);
+                    middle
+                } else {
+                    // This is a self loop so draw a circle around the middle of the position and the handle.
+                    push_circle(
+                        lines,
+                        from + handle_offset / 2.0,
+                        handle_offset.length() / 2.0,
+                        SELFLOOP_SEGMENTS,
+                        color,
+                    );
+                    push_circle(lines, from + handle_offset, 1.0, HANDLE_SEGMENTS, color);
+                    from + handle_offset
+                };
+
+                if let Some(labels) = self.labels.as_ref().filter(|_| draw_actions) {
+                    glyphs.extend(labels.glyphs[label].iter().map(|glyph| GlyphInstance {
+                        origin: label_position.into(),
+                        ..*glyph
+                    }));
+                }
+            }
+        }
+
+        lines.extend(colored_lines);
+        triangles.extend(colored_triangles);
+
+        let states: Vec<StateInstance> = viewer
+            .view_states
+            .iter()
+            .enumerate()
+            .map(|(index, state_view)| StateInstance {
+                center: state_view.position.xy().into(),
+                color: viewer.state_color(index).map_or(WHITE, to_rgba),
+            })
+            .collect();
+
+        let target = self.target.as_ref().expect("The render target has been created");
+        let target_view = target.texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("ltsgraph frame"),
+        });
+
+        // The buffers must outlive the render pass.
+        let lines = self.create_vertex_buffer("ltsgraph lines", bytemuck::cast_slice(&lines));
+        let triangles = self.create_vertex_buffer("ltsgraph triangles", bytemuck::cast_slice(&triangles));
+        let glyphs = self.create_vertex_buffer("ltsgraph glyphs", bytemuck::cast_slice(&glyphs));
+        let states = self.create_vertex_buffer("ltsgraph states", bytemuck::cast_slice(&states));
+
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("ltsgraph graph"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &target_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::WHITE),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            pass.set_bind_group(0, &self.view_bind_group, &[]);
+
+            // The states are drawn on top of the edges and labels.
+            if let Some((buffer, count)) = &lines {
+                pass.set_pipeline(&self.line_pipeline);
+                pass.set_vertex_buffer(0, buffer.slice(..));
+                pass.draw(0..(*count / std::mem::size_of::<ColorVertex>()) as u32, 0..1);
+            }
+
+            if let Some((buffer, count)) = &triangles {
+                pass.set_pipeline(&self.triangle_pipeline);
+                pass.set_vertex_buffer(0, buffer.slice(..));
+                pass.draw(0..(*count / std::mem::size_of::<ColorVertex>()) as u32, 0..1);
+            }
+
+            if let (Some((buffer, count)), Some(labels)) = (&glyphs, &self.labels) {
+                pass.set_pipeline(&self.text_pipeline);
+                pass.set_bind_group(1, &labels.bind_group, &[]);
+                pass.set_vertex_buffer(0, buffer.slice(..));
+                pass.draw(0..6, 0..(*count / std::mem::size_of::<GlyphInstance>()) as u32);
+            }
+
+            if let Some((buffer, count)) = &states {
+                pass.set_pipeline(&self.state_pipeline);
+                pass.set_vertex_buffer(0, buffer.slice(..));
+                pass.draw(0..6, 0..(*count / std::mem::size_of::<StateInstance>()) as u32);
+            }
+        }
+
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                texture: &target.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyBuffer {
+                buffer: &target.readback,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(target.padded_bytes_per_row),
+                    rows_per_image: Some(target.height),
+                },
+            },
+            wgpu::Extent3d {
+                width: target.width,
+                height: target.height,
+                depth_or_array_layers: 1,
+            },
+        );
+        self.queue.submit(Some(encoder.finish()));
+
+        // Wait for the image and copy it without the padding of the rows.
+        let slice = target.readback.slice(..);
+        let (sender, receiver) = mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        self.device.poll(wgpu::Maintain::Wait);
+        receiver
+            .recv()
+            .expect("The mapping callback is called by polling the device")?;
+
+        {
+            let data = slice.get_mapped_range();
+            let bytes_per_row = (target.width * 4) as usize;
+            for (row, padded_row) in pixels
+                .chunks_exact_mut(bytes_per_row)
+                .zip(data.chunks(target.padded_bytes_per_row as usize))
+            {
+                row.copy_from_slice(&padded_row[..bytes_per_row]);
+            }
+        }
+        target.readback.unmap();
+
+        Ok(())
+    }
+
+    /// Creates a vertex buffer with the given contents, or None when it is empty since empty buffers cannot be bound.
+    fn create_vertex_buffer(&self, label: &str, contents: &[u8]) -> Option<(wgpu::Buffer, usize)> {
+        if contents.is_empty() {
+            return None;
+        }
+
+        let buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(label),
+            contents,
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        Some((buffer, contents.len()))
+    }
+
+    fn create_target(&self, width: u32, height: u32) -> RenderTarget {
+        debug!("Creating render target of {width} by {height} pixels");
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("ltsgraph target"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: TARGET_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+
+        let padded_bytes_per_row = (width * 4).next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+        let readback = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("ltsgraph readback"),
+            size: (padded_bytes_per_row * height) as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        RenderTarget {
+            width,
+            height,
+            texture,
+            readback,
+            padded_bytes_per_row,
+        }
+    }
+
+    /// Packs the signed distance fields of the glyphs of all labels into a single texture.
+    fn create_atlas(&self, viewer: &mut Viewer, label_text_size: f32) -> Result<LabelAtlas, GpuError> {
+        let labels = viewer.label_glyphs(label_text_size);
+        let max_height = self.device.limits().max_texture_dimension_2d;
+
+        // Every distinct glyph is placed once, on shelves from top to bottom.
+        let mut placements: HashMap<cosmic_text::CacheKey, (u32, u32)> = HashMap::new();
+        let mut fields: Vec<(u32, u32, u32, u32, Vec<u8>)> = Vec::new();
+        let (mut x, mut y, mut shelf_height) = (0, 0, 0);
+
+        for mask in labels.iter().flatten() {
+            if placements.contains_key(&mask.key) {
+                continue;
+            }
+
+            let width = mask.width + 2 * SDF_SPREAD as u32;
+            let height = mask.height + 2 * SDF_SPREAD as u32;
+            if x + width > ATLAS_WIDTH {
+                x = 0;
+                y += shelf_height;
+                shelf_height = 0;
+            }
+
+            if width > ATLAS_WIDTH || y + height > max_height {
+                return Err(GpuError::AtlasTooLarge(max_height));
+            }
+
+            placements.insert(mask.key, (x, y));
+            fields.push((
+                x,
+                y,
+                width,
+                height,
+                signed_distance_field(mask.width, mask.height, &mask.coverage),
+            ));
+            x += width;
+            shelf_height = shelf_height.max(height);
+        }
+
+        let atlas_height = (y + shelf_height).max(1);
+        let mut data = vec![0; (ATLAS_WIDTH * atlas_height) as usize];
+        for (x, y, width, height, field) in &fields {
+            for row in 0..*height {
+                let start = ((y + row) * ATLAS_WIDTH + x) as usize;
+                data[start..start + *width as usize]
+                    .copy_from_slice(&field[(row * width) as usize..((row + 1) * width) as usize]);
+            }
+        }
+
+        let texture = self.device.create_texture_with_data(
+            &self.queue,
+            &wgpu::TextureDescriptor {
+                label: Some("ltsgraph atlas"),
+                size: wgpu::Extent3d {
+                    width: ATLAS_WIDTH,
+                    height: atlas_height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::R8Unorm,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            },
+            wgpu::util::TextureDataOrder::LayerMajor,
+            &data,
+        );
+
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("ltsgraph atlas"),
+            layout: &self.atlas_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(
+                        &texture.create_view(&wgpu::TextureViewDescriptor::default()),
+                    ),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.atlas_sampler),
+                },
+            ],
+        });
+
+        let glyphs = labels
+            .iter()
+            .map(|masks| {
+                masks
+                    .iter()
+                    .map(|mask| {
+                        let (x, y) = placements[&mask.key];
+                        let width = mask.width + 2 * SDF_SPREAD as u32;
+                        let height = mask.height + 2 * SDF_SPREAD as u32;
+
+                        GlyphInstance {
+                            origin: [0.0; 2],
+                            offset: [(mask.x - SDF_SPREAD) as f32, (mask.y - SDF_SPREAD) as f32],
+                            size: [width as f32, height as f32],
+                            uv: [
+                                x as f32 / ATLAS_WIDTH as f32,
+                                y as f32 / atlas_height as f32,
+                                (x + width) as f32 / ATLAS_WIDTH as f32,
+                                (y + height) as f32 / atlas_height as f32,
+                            ],
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        debug!(
+            "Created glyph atlas of {ATLAS_WIDTH} by {atlas_height} pixels with {} glyphs",
+            fields.len()
+        );
+        Ok(LabelAtlas {
+            label_text_size,
+            bind_group,
+            glyphs,
+        })
+    }
+}
+
+/// Converts the given RGB color to the normalised RGBA color of the shaders.
+fn to_rgba([red, green, blue]: [u8; 3]) -> [f32; 4] {
+    [red as f32 / 255.0, green as f32 / 255.0, blue as f32 / 255.0, 1.0]
+}
+
+fn color_vertex(position: Vec2, color: [f32; 4]) -> ColorVertex {
+    ColorVertex {
+        position: position.into(),
+        color,
+    }
+}
+
+/// Adds the line segments of a circle with the given center and radius.
+fn push_circle(lines: &mut Vec<ColorVertex>, center: Vec2, radius: f32, segments: usize, color: [f32; 4]) {
+    let point = |index: usize| {
+        let angle = index as f32 / segments as f32 * 2.0 * std::f32::consts::PI;
+        color_vertex(center + Vec2::from_angle(angle) * radius, color)
+    };
+
+    for index in 0..segments {
+        lines.push(point(index));
+        lines.push(point(index + 1));
+    }
+}
+
+/// Returns the signed distance field of the given coverage mask of a glyph
+/// with a border of [SDF_SPREAD] pixels, where the edge of the glyph has
+/// value 128 and the values decrease outside of the glyph.
+fn signed_distance_field(mask_width: u32, mask_height: u32, coverage: &[u8]) -> Vec<u8> {
+    let (mask_width, mask_height) = (mask_width as i32, mask_height as i32);
+    let width = mask_width + 2 * SDF_SPREAD;
+    let height = mask_height + 2 * SDF_SPREAD;
+
+    let inside = |x: i32, y: i32| -> bool {
+        let (x, y) = (x - SDF_SPREAD, y - SDF_SPREAD);
+        x >= 0 && y >= 0 && x < mask_width && y < mask_height && coverage[(y * mask_width + x) as usize] >= 128
+    };
+
+    let mut result = Vec::with_capacity((width * height) as usize);
+    for y in 0..height {
+        for x in 0..width {
+            // The distance to the nearest pixel on the other side of the edge, limited by the spread.
+            let is_inside = inside(x, y);
+            let mut distance = SDF_SPREAD as f32;
+            for dy in -SDF_SPREAD..=SDF_SPREAD {
+                for dx in -SDF_SPREAD..=SDF_SPREAD {
+                    if inside(x + dx, y + dy) != is_inside {
+                        distance = distance.min(((dx * dx + dy * dy) as f32).sqrt() - 0.5);
+                    }
+                }
+            }
+
+            let signed = if is_inside { distance } else { -distance };
+            result.push(((0.5 + signed / (2.0 * SDF_SPREAD as f32)).clamp(0.0, 1.0) * 255.0) as u8);
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use io::io_aut::read_aut;
+
+    use super::*;
+
+    #[test]
+    fn test_signed_distance_field() {
+        // A single square of three by three pixels.
+        let field = signed_distance_field(3, 3, &[255; 9]);
+        let width = 3 + 2 * SDF_SPREAD as usize;
+        assert_eq!(field.len(), width * width);
+
+        // The center is inside, the corners are outside and the edge is halfway.
+        let center = SDF_SPREAD as usize + 1;
+        assert!(field[center * width + center] > 128);
+        assert_eq!(field[0], 0);
+        assert!(field[center * width + SDF_SPREAD as usize - 1] < 128);
+        assert!(field[center * width + SDF_SPREAD as usize] >= 128);
+    }
+
+    #[test]
+    fn test_gpu_renderer() {
+        // Rendering requires a graphics adapter, which is not available on every machine.
+        let Ok(mut renderer) = GpuRenderer::new() else {
+            return;
+        };
+
+        let file = include_str!("../../../../examples/lts/abp.aut");
+        let lts = Arc::new(read_aut(file.as_bytes(), vec![]).unwrap());
+        let mut viewer = Viewer::new(&lts);
+
+        let mut pixels = vec![0; 800 * 600 * 4];
+        renderer
+            .render(&mut viewer, &mut pixels, true, 5.0, 0.0, 0.0, 800, 600, 1.0, 14.0)
+            .unwrap();
+
+        // Something else than the white background has been drawn.
+        assert!(pixels.iter().any(|&value| value != 255));
+    }
+}
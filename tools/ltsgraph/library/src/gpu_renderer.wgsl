@@ -0,0 +1,133 @@
+// The shaders of the GPU renderer of ltsgraph, see gpu_renderer.rs.
+
+struct View {
+    // The translation of the view, in world coordinates.
+    translation: vec2<f32>,
+
+    // The size of the screen in pixels.
+    screen: vec2<f32>,
+
+    zoom_level: f32,
+    state_radius: f32,
+    padding: vec2<f32>,
+}
+
+@group(0) @binding(0) var<uniform> view: View;
+
+// Applies the same view transformation as the software renderer and converts the result to clip space.
+fn to_clip(world: vec2<f32>) -> vec4<f32> {
+    let screen = (world + view.translation) * view.zoom_level + view.screen / 2.0;
+    return vec4<f32>(screen.x / view.screen.x * 2.0 - 1.0, 1.0 - screen.y / view.screen.y * 2.0, 0.0, 1.0);
+}
+
+// Lines and triangles with a color per vertex, used for the edges and arrows.
+
+struct GeometryOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+}
+
+@vertex
+fn vs_geometry(@location(0) position: vec2<f32>, @location(1) color: vec4<f32>) -> GeometryOutput {
+    var out: GeometryOutput;
+    out.position = to_clip(position);
+    out.color = color;
+    return out;
+}
+
+@fragment
+fn fs_geometry(in: GeometryOutput) -> @location(0) vec4<f32> {
+    return in.color;
+}
+
+// The states are instanced quads that are cut into circles with an outline.
+
+struct StateOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) local: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+@vertex
+fn vs_state(
+    @builtin(vertex_index) index: u32,
+    @location(0) center: vec2<f32>,
+    @location(1) color: vec4<f32>,
+) -> StateOutput {
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(-1.0, -1.0),
+        vec2<f32>(1.0, -1.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(-1.0, -1.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(-1.0, 1.0),
+    );
+
+    // Leave room for the outline, which is centered on the radius.
+    let local = corners[index] * (view.state_radius + 1.0);
+
+    var out: StateOutput;
+    out.position = to_clip(center + local);
+    out.local = local;
+    out.color = color;
+    return out;
+}
+
+@fragment
+fn fs_state(in: StateOutput) -> @location(0) vec4<f32> {
+    let distance = length(in.local);
+    let smoothing = max(fwidth(distance), 0.001);
+
+    // The outline is one unit wide, and the edges are anti-aliased over a single pixel.
+    let outside = smoothstep(view.state_radius + 0.5 - smoothing, view.state_radius + 0.5, distance);
+    let outline = smoothstep(view.state_radius - 0.5 - smoothing, view.state_radius - 0.5, distance);
+    if outside >= 1.0 {
+        discard;
+    }
+
+    return vec4<f32>(mix(in.color.rgb, vec3<f32>(0.0), outline), 1.0 - outside);
+}
+
+// The labels are instanced quads for every glyph, which sample a signed distance field.
+
+@group(1) @binding(0) var atlas: texture_2d<f32>;
+@group(1) @binding(1) var atlas_sampler: sampler;
+
+struct TextOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+@vertex
+fn vs_text(
+    @builtin(vertex_index) index: u32,
+    @location(0) origin: vec2<f32>,
+    @location(1) offset: vec2<f32>,
+    @location(2) size: vec2<f32>,
+    @location(3) uv: vec4<f32>,
+) -> TextOutput {
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(0.0, 0.0),
+        vec2<f32>(1.0, 0.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(0.0, 0.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(0.0, 1.0),
+    );
+    let corner = corners[index];
+
+    var out: TextOutput;
+    out.position = to_clip(origin + offset + corner * size);
+    out.uv = mix(uv.xy, uv.zw, corner);
+    return out;
+}
+
+@fragment
+fn fs_text(in: TextOutput) -> @location(0) vec4<f32> {
+    // The edge of the glyph is at 0.5, which stays sharp when the label is scaled.
+    let distance = textureSample(atlas, atlas_sampler, in.uv).r;
+    let smoothing = max(fwidth(distance), 0.001);
+    let alpha = smoothstep(0.5 - smoothing, 0.5 + smoothing, distance);
+
+    return vec4<f32>(0.0, 0.0, 0.0, alpha);
+}
@@ -19,6 +19,9 @@ pub struct GraphLayout {
 pub struct StateLayout {
     pub position: Vec3,
     pub force: Vec3,
+
+    /// A pinned state keeps its position, for example after it has been moved by the user.
+    pub pinned: bool,
 }
 
 impl GraphLayout {
@@ -70,7 +73,7 @@ impl GraphLayout {
             // Accumulate forces over all connected edges.
             for (_, to_index) in self.lts.outgoing_transitions(state_index) {
                 // Index an edge in the graph.
-                match index_edge(&mut self.layout_states, state_index, *to_index) {
+                match index_edge(&mut self.layout_states, state_index, to_index) {
                     Edge::Selfloop(_) => {
                         // Handle self loop, but we apply no forces in this case.
                     }
@@ -89,8 +92,10 @@ impl GraphLayout {
 
         for state_layout in &mut self.layout_states {
             // Integrate the forces.
-            state_layout.position += state_layout.force * delta;
-            displacement += (state_layout.force * delta).length_squared();
+            if !state_layout.pinned {
+                state_layout.position += state_layout.force * delta;
+                displacement += (state_layout.force * delta).length_squared();
+            }
 
             // Reset the force.
             state_layout.force = Vec3::default();
@@ -154,4 +159,17 @@ mod tests {
         layout.update(5.0, 1.0, 0.01);
         layout.update(5.0, 1.0, 0.01);
     }
+
+    #[test]
+    fn test_pinned_state() {
+        let file = include_str!("../../../../examples/lts/abp.aut");
+        let lts = Arc::new(read_aut(file.as_bytes(), vec![]).unwrap());
+
+        let mut layout = GraphLayout::new(&lts);
+        layout.layout_states[0].pinned = true;
+        let position = layout.layout_states[0].position;
+
+        layout.update(5.0, 1.0, 0.01);
+        assert_eq!(layout.layout_states[0].position, position);
+    }
 }
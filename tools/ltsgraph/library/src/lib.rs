@@ -3,9 +3,13 @@
 //!
 //!
 
+mod export;
+mod gpu_renderer;
 mod graph_layout;
 mod text_cache;
 mod viewer;
 
+pub use export::*;
+pub use gpu_renderer::*;
 pub use graph_layout::*;
 pub use viewer::*;
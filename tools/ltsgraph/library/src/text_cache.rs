@@ -1,14 +1,32 @@
 use cosmic_text::Attrs;
 use cosmic_text::Buffer;
+use cosmic_text::CacheKey;
 use cosmic_text::FontSystem;
 use cosmic_text::Metrics;
 use cosmic_text::Shaping;
 use cosmic_text::SwashCache;
+use cosmic_text::SwashContent;
 use tiny_skia::PathBuilder;
 use tiny_skia::PixmapMut;
 use tiny_skia::PixmapPaint;
 use tiny_skia::Transform;
 
+/// The coverage of a single rasterised glyph of a text buffer.
+pub struct GlyphMask {
+    /// Identifies the glyph, including its font and size.
+    pub key: CacheKey,
+
+    /// The offset of the top left corner w.r.t. the origin of the buffer, in pixels.
+    pub x: i32,
+    pub y: i32,
+
+    pub width: u32,
+    pub height: u32,
+
+    /// The coverage of every pixel, row by row.
+    pub coverage: Vec<u8>,
+}
+
 pub struct TextCache {
     /// A FontSystem provides access to detected system fonts, create one per application
     font_system: FontSystem,
@@ -128,6 +146,42 @@ impl TextCache {
             }
         }
     }
+
+    /// Rasterises the glyphs of the given cached text into coverage masks,
+    /// positioned in the same way as [TextCache::draw] does.
+    pub fn glyph_masks(&mut self, buffer: &Buffer) -> Vec<GlyphMask> {
+        let mut result = Vec::new();
+
+        for run in buffer.layout_runs() {
+            for glyph in run.glyphs.iter() {
+                let physical_glyph = glyph.physical((0., 0.), 1.0);
+
+                if let Some(image) = self
+                    .swash_cache
+                    .get_image(&mut self.font_system, physical_glyph.cache_key)
+                {
+                    // Colored glyphs, such as emoji, are only drawn by their alpha channel.
+                    let coverage = match image.content {
+                        SwashContent::Mask => image.data.clone(),
+                        SwashContent::Color | SwashContent::SubpixelMask => {
+                            image.data.chunks_exact(4).map(|pixel| pixel[3]).collect()
+                        }
+                    };
+
+                    result.push(GlyphMask {
+                        key: physical_glyph.cache_key,
+                        x: physical_glyph.x + image.placement.left,
+                        y: physical_glyph.y - image.placement.top,
+                        width: image.placement.width,
+                        height: image.placement.height,
+                        coverage,
+                    });
+                }
+            }
+        }
+
+        result
+    }
 }
 
 #[cfg(test)]
@@ -150,5 +204,10 @@ mod tests {
             &mut PixmapMut::from_bytes(pixel_buffer.data_mut(), 800, 600).unwrap(),
             Transform::default(),
         );
+
+        // Every glyph has a coverage value for every pixel.
+        for mask in cache.glyph_masks(&buffer) {
+            assert_eq!(mask.coverage.len(), (mask.width * mask.height) as usize);
+        }
     }
 }
@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
 use cosmic_text::Metrics;
@@ -6,13 +7,23 @@ use glam::Vec2;
 use glam::Vec3;
 use glam::Vec3Swizzles;
 use lts::LabelledTransitionSystem;
+use lts::StateIndex;
+use lts::Trace;
 use tiny_skia::Shader;
 use tiny_skia::Stroke;
 use tiny_skia::Transform;
 
 use crate::graph_layout::GraphLayout;
+use crate::text_cache::GlyphMask;
 use crate::text_cache::TextCache;
 
+/// The colors of the states and transitions that stand out, in RGB.
+const INITIAL_STATE_COLOR: [u8; 3] = [100, 255, 100];
+const HIGHLIGHT_COLOR: [u8; 3] = [230, 80, 50];
+const TRACE_COLOR: [u8; 3] = [60, 120, 230];
+const CURRENT_STATE_COLOR: [u8; 3] = [255, 200, 0];
+const SELECTED_STATE_COLOR: [u8; 3] = [200, 100, 230];
+
 pub struct Viewer {
     /// A cache used to cache strings and font information.
     text_cache: TextCache,
@@ -21,22 +32,40 @@ pub struct Viewer {
     labels_cache: Vec<cosmic_text::Buffer>,
 
     /// The underlying LTS being displayed.
-    lts: Arc<LabelledTransitionSystem>,
+    pub(crate) lts: Arc<LabelledTransitionSystem>,
 
     /// Stores a local copy of the state positions.
-    view_states: Vec<StateView>,
+    pub(crate) view_states: Vec<StateView>,
+
+    /// The last state of the trace that is being simulated.
+    current_state: Option<StateIndex>,
+
+    /// The state that has been selected by the user.
+    selected_state: Option<StateIndex>,
 }
 
 #[derive(Clone, Default)]
-struct StateView {
+pub(crate) struct StateView {
     pub position: Vec3,
     pub outgoing: Vec<TransitionView>,
+
+    /// Whether the state matches the current search.
+    pub highlighted: bool,
+
+    /// Whether the state is visited by the simulated trace.
+    pub on_trace: bool,
 }
 
 #[derive(Clone, Default)]
 pub struct TransitionView {
     /// The offset of the handle w.r.t. the 'from' state.
     pub handle_offset: Vec3,
+
+    /// Whether the transition matches the current search.
+    pub highlighted: bool,
+
+    /// Whether the transition is taken by the simulated trace.
+    pub on_trace: bool,
 }
 
 impl Viewer {
@@ -74,7 +103,7 @@ impl Viewer {
             for (transition_index, (_, to)) in lts.outgoing_transitions(state_index).enumerate() {
                 let transition_view = &mut state_view.outgoing[transition_index];
 
-                if state_index == *to {
+                if state_index == to {
                     // This is a self loop so compute a rotation around the state for its handle.
                     let rotation_mat = Mat3::from_euler(
                         glam::EulerRot::XYZ,
@@ -88,7 +117,7 @@ impl Viewer {
                 } else {
                     // Determine whether any of the outgoing edges from the reached state point back.
                     let has_backtransition = lts
-                        .outgoing_transitions(*to)
+                        .outgoing_transitions(to)
                         .filter(|(_, other_to)| *other_to == state_index)
                         .count()
                         > 0;
@@ -117,6 +146,8 @@ impl Viewer {
             labels_cache,
             lts: lts.clone(),
             view_states,
+            current_state: None,
+            selected_state: None,
         }
     }
 
@@ -132,6 +163,163 @@ impl Viewer {
         self.view_states.iter().map(|x| x.position).sum::<Vec3>() / self.view_states.len() as f32
     }
 
+    /// Highlights the state with the given index when the query is a number,
+    /// and otherwise the transitions of which the label contains the query. An
+    /// empty query removes the highlighting. Returns the number of highlighted
+    /// states and transitions.
+    pub fn search(&mut self, query: &str) -> usize {
+        let query = query.trim();
+        let state_index: Option<usize> = query.parse().ok();
+
+        let mut num_of_matches = 0;
+        for (index, state_view) in self.view_states.iter_mut().enumerate() {
+            state_view.highlighted = state_index == Some(index);
+            num_of_matches += state_view.highlighted as usize;
+
+            for (transition_view, (label, _)) in
+                state_view.outgoing.iter_mut().zip(self.lts.outgoing_transitions(index))
+            {
+                transition_view.highlighted =
+                    state_index.is_none() && !query.is_empty() && self.lts.labels()[label].contains(query);
+                num_of_matches += transition_view.highlighted as usize;
+            }
+        }
+
+        num_of_matches
+    }
+
+    /// Returns the center of the highlighted states and the states of the highlighted transitions.
+    pub fn highlighted_center(&self) -> Option<Vec3> {
+        let mut sum = Vec3::ZERO;
+        let mut count = 0;
+
+        for (index, state_view) in self.view_states.iter().enumerate() {
+            if state_view.highlighted {
+                sum += state_view.position;
+                count += 1;
+            }
+
+            for (transition_view, (_, to)) in state_view.outgoing.iter().zip(self.lts.outgoing_transitions(index)) {
+                if transition_view.highlighted {
+                    sum += state_view.position + self.view_states[to].position;
+                    count += 2;
+                }
+            }
+        }
+
+        (count > 0).then(|| sum / count as f32)
+    }
+
+    /// Shows the path of the given trace through the LTS, or removes it when no trace is given.
+    pub fn set_trace(&mut self, trace: Option<&Trace>) {
+        for state_view in &mut self.view_states {
+            state_view.on_trace = false;
+            for transition_view in &mut state_view.outgoing {
+                transition_view.on_trace = false;
+            }
+        }
+
+        self.current_state = trace.map(|trace| trace.last_state());
+        if let Some(trace) = trace {
+            let mut from = trace.initial_state();
+            self.view_states[from].on_trace = true;
+
+            for &(label, to) in trace.steps() {
+                // Mark the first transition with the same label and target state.
+                if let Some(transition_index) = self
+                    .lts
+                    .outgoing_transitions(from)
+                    .position(|transition| transition == (label, to))
+                {
+                    self.view_states[from].outgoing[transition_index].on_trace = true;
+                }
+
+                self.view_states[to].on_trace = true;
+                from = to;
+            }
+        }
+    }
+
+    /// Shows the given state as selected, or removes the selection.
+    pub fn set_selected(&mut self, state_index: Option<StateIndex>) {
+        self.selected_state = state_index;
+    }
+
+    /// Returns the state closest to the given position that is at most the given distance away.
+    pub fn state_at(&self, position: Vec2, max_distance: f32) -> Option<StateIndex> {
+        self.view_states
+            .iter()
+            .enumerate()
+            .map(|(index, state_view)| (index, state_view.position.xy().distance(position)))
+            .filter(|(_, distance)| *distance <= max_distance)
+            .min_by(|(_, left), (_, right)| left.total_cmp(right))
+            .map(|(index, _)| index)
+    }
+
+    /// Returns the position of the given state.
+    pub fn position(&self, state_index: StateIndex) -> Vec3 {
+        self.view_states[state_index].position
+    }
+
+    /// Returns the color in which the given state is filled, or None when it is white.
+    pub(crate) fn state_color(&self, state_index: StateIndex) -> Option<[u8; 3]> {
+        let state_view = &self.view_states[state_index];
+        if self.selected_state == Some(state_index) {
+            Some(SELECTED_STATE_COLOR)
+        } else if self.current_state == Some(state_index) {
+            Some(CURRENT_STATE_COLOR)
+        } else if state_view.highlighted {
+            Some(HIGHLIGHT_COLOR)
+        } else if state_view.on_trace {
+            Some(TRACE_COLOR)
+        } else if state_index == self.lts.initial_state_index() {
+            Some(INITIAL_STATE_COLOR)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the color in which the given transition is drawn, or None when it is black.
+    pub(crate) fn transition_color(&self, state_index: StateIndex, transition_index: usize) -> Option<[u8; 3]> {
+        let transition_view = &self.view_states[state_index].outgoing[transition_index];
+        if transition_view.highlighted {
+            Some(HIGHLIGHT_COLOR)
+        } else if transition_view.on_trace {
+            Some(TRACE_COLOR)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the rasterised glyphs of every label at the given text size.
+    pub(crate) fn label_glyphs(&mut self, label_text_size: f32) -> Vec<Vec<GlyphMask>> {
+        self.labels_cache
+            .iter_mut()
+            .map(|buffer| {
+                self.text_cache
+                    .resize(buffer, Metrics::new(label_text_size, label_text_size));
+                self.text_cache.glyph_masks(buffer)
+            })
+            .collect()
+    }
+
+    /// Returns the size and the baseline of the first line of the given label at the given text size.
+    pub(crate) fn label_extents(&mut self, label: usize, label_text_size: f32) -> (Vec2, f32) {
+        let buffer = &mut self.labels_cache[label];
+        self.text_cache
+            .resize(buffer, Metrics::new(label_text_size, label_text_size));
+
+        let mut size = Vec2::ZERO;
+        let mut baseline = None;
+        for run in buffer.layout_runs() {
+            size.x = size.x.max(run.line_w);
+            size.y = size.y.max(run.line_top + run.line_height);
+            baseline.get_or_insert(run.line_y);
+        }
+
+        (size, baseline.unwrap_or(label_text_size))
+    }
+
     /// Render the current state of the simulation into the pixmap.
     pub fn render(
         &mut self,
@@ -157,10 +345,6 @@ impl Viewer {
             shader: Shader::SolidColor(tiny_skia::Color::from_rgba8(255, 255, 255, 255)),
             ..Default::default()
         };
-        let initial_state_paint = tiny_skia::Paint {
-            shader: Shader::SolidColor(tiny_skia::Color::from_rgba8(100, 255, 100, 255)),
-            ..Default::default()
-        };
         let state_outer = tiny_skia::Paint {
             shader: Shader::SolidColor(tiny_skia::Color::from_rgba8(0, 0, 0, 255)),
             ..Default::default()
@@ -191,9 +375,10 @@ impl Viewer {
                 .resize(buffer, Metrics::new(label_text_size, label_text_size));
         }
 
-        // Draw the edges and the arrows on them
+        // Draw the edges and the arrows on them, the colored ones are drawn separately.
         let mut edge_builder = tiny_skia::PathBuilder::new();
         let mut arrow_builder = tiny_skia::PathBuilder::new();
+        let mut colored_builders: BTreeMap<[u8; 3], (tiny_skia::PathBuilder, tiny_skia::PathBuilder)> = BTreeMap::new();
 
         for state_index in self.lts.iter_states() {
             let state_view = &self.view_states[state_index];
@@ -202,10 +387,17 @@ impl Viewer {
             debug_assert!(state_view.position.z.abs() < 0.01);
 
             for (transition_index, (label, to)) in self.lts.outgoing_transitions(state_index).enumerate() {
-                let to_state_view = &self.view_states[*to];
+                let to_state_view = &self.view_states[to];
                 let transition_view = &state_view.outgoing[transition_index];
+                let (edge_builder, arrow_builder) = match self.transition_color(state_index, transition_index) {
+                    Some(color) => {
+                        let (edge_builder, arrow_builder) = colored_builders.entry(color).or_default();
+                        (edge_builder, arrow_builder)
+                    }
+                    None => (&mut edge_builder, &mut arrow_builder),
+                };
 
-                let label_position = if *to != state_index {
+                let label_position = if to != state_index {
                     // Draw the transition
                     edge_builder.move_to(state_view.position.x, state_view.position.y);
                     edge_builder.line_to(to_state_view.position.x, to_state_view.position.y);
@@ -247,7 +439,7 @@ impl Viewer {
 
                 // Draw the text label
                 if draw_actions {
-                    let buffer = &self.labels_cache[*label];
+                    let buffer = &self.labels_cache[label];
                     self.text_cache.draw(
                         buffer,
                         pixmap,
@@ -266,26 +458,42 @@ impl Viewer {
             pixmap.stroke_path(&path, &edge_paint, &Stroke::default(), view_transform, None);
         }
 
+        // Draw the colored edges wider and on top of the others.
+        for (color, (edge_builder, arrow_builder)) in colored_builders {
+            let paint = solid_paint(color);
+            if let Some(path) = arrow_builder.finish() {
+                pixmap.fill_path(&path, &paint, tiny_skia::FillRule::Winding, view_transform, None);
+            }
+
+            if let Some(path) = edge_builder.finish() {
+                let stroke = Stroke {
+                    width: 2.0,
+                    ..Default::default()
+                };
+                pixmap.stroke_path(&path, &paint, &stroke, view_transform, None);
+            }
+        }
+
         // Draw the states on top.
         let mut state_path_builder = tiny_skia::PathBuilder::new();
 
         for (index, state_view) in self.view_states.iter().enumerate() {
-            if index != self.lts.initial_state_index() {
-                state_path_builder.push_circle(state_view.position.x, state_view.position.y, state_radius);
-            } else {
+            if let Some(color) = self.state_color(index) {
                 // Draw the colored states individually
                 let transform =
                     Transform::from_translate(state_view.position.x, state_view.position.y).post_concat(view_transform);
 
                 pixmap.fill_path(
                     &circle,
-                    &initial_state_paint,
+                    &solid_paint(color),
                     tiny_skia::FillRule::Winding,
                     transform,
                     None,
                 );
 
                 pixmap.stroke_path(&circle, &state_outer, &Stroke::default(), transform, None);
+            } else {
+                state_path_builder.push_circle(state_view.position.x, state_view.position.y, state_radius);
             }
         }
 
@@ -304,6 +512,14 @@ impl Viewer {
     }
 }
 
+/// Returns a paint that fills with the given color.
+fn solid_paint([red, green, blue]: [u8; 3]) -> tiny_skia::Paint<'static> {
+    tiny_skia::Paint {
+        shader: Shader::SolidColor(tiny_skia::Color::from_rgba8(red, green, blue, 255)),
+        ..Default::default()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use io::io_aut::read_aut;
@@ -333,4 +549,61 @@ mod tests {
             14.0,
         );
     }
+
+    #[test]
+    fn test_search() {
+        let file = include_str!("../../../../examples/lts/abp.aut");
+        let lts = Arc::new(read_aut(file.as_bytes(), vec![]).unwrap());
+
+        let mut viewer = Viewer::new(&lts);
+        assert_eq!(viewer.search("3"), 1);
+        assert!(viewer.view_states[3].highlighted);
+
+        let num_of_matches = lts
+            .iter_states()
+            .flat_map(|state_index| lts.outgoing_transitions(state_index))
+            .filter(|(label, _)| lts.labels()[*label].contains("r1"))
+            .count();
+        assert_eq!(viewer.search("r1"), num_of_matches);
+        assert!(viewer.highlighted_center().is_some());
+
+        assert_eq!(viewer.search(""), 0);
+        assert!(viewer.highlighted_center().is_none());
+    }
+
+    #[test]
+    fn test_set_trace() {
+        let file = include_str!("../../../../examples/lts/abp.aut");
+        let lts = Arc::new(read_aut(file.as_bytes(), vec![]).unwrap());
+
+        let mut viewer = Viewer::new(&lts);
+        let mut trace = Trace::new(lts.initial_state_index());
+        let (label, to) = lts.outgoing_transitions(lts.initial_state_index()).next().unwrap();
+        trace.push(label, to);
+
+        viewer.set_trace(Some(&trace));
+        assert!(viewer.view_states[lts.initial_state_index()].outgoing[0].on_trace);
+        assert_eq!(viewer.state_color(to), Some(CURRENT_STATE_COLOR));
+        assert_eq!(viewer.state_color(lts.initial_state_index()), Some(TRACE_COLOR));
+
+        viewer.set_trace(None);
+        assert_eq!(viewer.state_color(to), None);
+        assert_eq!(viewer.transition_color(lts.initial_state_index(), 0), None);
+    }
+
+    #[test]
+    fn test_state_at() {
+        let file = include_str!("../../../../examples/lts/abp.aut");
+        let lts = Arc::new(read_aut(file.as_bytes(), vec![]).unwrap());
+
+        let mut viewer = Viewer::new(&lts);
+        viewer.update(&GraphLayout::new(&lts));
+
+        let position = viewer.position(3).xy();
+        assert_eq!(viewer.state_at(position, 5.0), Some(3));
+        assert_eq!(viewer.state_at(Vec2::splat(1000.0), 5.0), None);
+
+        viewer.set_selected(Some(3));
+        assert_eq!(viewer.state_color(3), Some(SELECTED_STATE_COLOR));
+    }
 }
@@ -0,0 +1,59 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::BufWriter;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+
+use log::info;
+use log::warn;
+use lts::LabelledTransitionSystem;
+use ltsgraph_lib::export_image;
+use ltsgraph_lib::write_positions;
+use ltsgraph_lib::GraphLayout;
+use ltsgraph_lib::Viewer;
+
+// The settings of the layout and the viewer, which are the defaults of the user interface.
+const HANDLE_LENGTH: f32 = 50.0;
+const REPULSION_STRENGTH: f32 = 5.0;
+const TIMESTEP: f32 = 15.0;
+const STATE_RADIUS: f32 = 5.0;
+const LABEL_TEXT_SIZE: f32 = 14.0;
+
+/// The layout of some graphs never becomes stable, so it is stopped after this number of steps.
+const MAX_LAYOUT_STEPS: usize = 100_000;
+
+/// Runs the layout of the given LTS until it is stable and writes the result
+/// to the output, without opening a window. The output is an image when it
+/// has the .svg or .png extension, and otherwise the positions of the states
+/// are written as comma separated values.
+pub fn run_headless(lts: LabelledTransitionSystem, output: &Path, scale: f32) -> Result<(), Box<dyn Error>> {
+    let lts = Arc::new(lts);
+    let mut layout = GraphLayout::new(&lts);
+
+    let mut steps = 0;
+    while !layout.update(HANDLE_LENGTH, REPULSION_STRENGTH, TIMESTEP) {
+        steps += 1;
+
+        if steps == MAX_LAYOUT_STEPS {
+            warn!("Layout is not stable after {steps} steps, writing the current layout");
+            break;
+        }
+    }
+    info!("Layout took {steps} steps");
+
+    let mut viewer = Viewer::new(&lts);
+    viewer.update(&layout);
+
+    if output
+        .extension()
+        .is_some_and(|extension| extension == "svg" || extension == "png")
+    {
+        export_image(&mut viewer, output, true, STATE_RADIUS, LABEL_TEXT_SIZE, scale)
+    } else {
+        let mut writer = BufWriter::new(File::create(output)?);
+        write_positions(&viewer, &mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
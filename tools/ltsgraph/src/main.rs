@@ -7,6 +7,7 @@ use std::error::Error;
 use std::fs::File;
 use std::ops::Deref;
 use std::path::Path;
+use std::path::PathBuf;
 use std::process::ExitCode;
 use std::sync::Arc;
 use std::sync::Mutex;
@@ -16,34 +17,84 @@ use std::time::Duration;
 use std::time::Instant;
 
 use clap::Parser;
+use clap::ValueEnum;
 
+use glam::Vec2;
 use gui::console;
 use log::debug;
 use log::info;
+use log::warn;
 use slint::invoke_from_event_loop;
 use slint::Image;
+use slint::ModelRc;
 use slint::Rgba8Pixel;
 use slint::SharedPixelBuffer;
-
-use io::io_aut::read_aut;
+use slint::SharedString;
+use slint::VecModel;
+
+use io::formats::read_lts_file;
+use io::io_trc::write_plain_trace;
+use io::project::Project;
+use lts::LabelledTransitionSystem;
+use lts::Simulator;
+use lts::StateIndex;
+use lts::Trace;
+use ltsgraph_lib::export_image;
+use ltsgraph_lib::GpuRenderer;
 use ltsgraph_lib::GraphLayout;
 use ltsgraph_lib::Viewer;
 use pauseable_thread::PauseableThread;
 
 mod error_dialog;
+mod headless;
 mod pauseable_thread;
 
+/// The default number of pixels per unit of the exported PNG images, which is higher than the screen for use in documents.
+const EXPORT_SCALE: f32 = 4.0;
+
 #[derive(Parser, Debug)]
 #[command(name = "Maurice Laveaux", about = "A lts viewing tool")]
 pub struct Cli {
     #[arg(value_name = "FILE")]
     labelled_transition_system: Option<String>,
+
+    /// Selects how the graph is rendered, the software renderer is used when no graphics adapter is available.
+    #[arg(long, value_enum, default_value_t = Renderer::Gpu)]
+    renderer: Renderer,
+
+    /// Computes the layout without opening a window and writes it to the output.
+    #[arg(long, requires_all = ["labelled_transition_system", "output"])]
+    headless: bool,
+
+    /// The output of the headless mode, an .svg or .png image or otherwise the positions of the states.
+    #[arg(short, long, requires = "headless")]
+    output: Option<PathBuf>,
+
+    /// The number of pixels per unit of the exported .png images.
+    #[arg(long, default_value_t = EXPORT_SCALE)]
+    scale: f32,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Renderer {
+    /// The software renderer.
+    Cpu,
+
+    /// Renders with the graphics adapter, which is considerably faster for large graphs.
+    Gpu,
 }
 
 /// Contains all the GUI related state information.
 struct GuiState {
+    lts: Arc<LabelledTransitionSystem>,
     graph_layout: Mutex<GraphLayout>,
     viewer: Mutex<(Viewer, SharedPixelBuffer<Rgba8Pixel>)>,
+
+    /// The trace that is being simulated, if the simulation is enabled.
+    simulation: Mutex<Option<Trace>>,
+
+    /// The state that is shown in the selection panel.
+    selection: Mutex<Option<StateIndex>>,
 }
 
 #[derive(Clone, Default)]
@@ -89,6 +140,14 @@ async fn main() -> Result<ExitCode, Box<dyn Error>> {
 
     let cli = Cli::parse();
 
+    if cli.headless {
+        if let (Some(path), Some(output)) = (&cli.labelled_transition_system, &cli.output) {
+            headless::run_headless(read_lts(Path::new(path))?, output, cli.scale)?;
+        }
+
+        return Ok(ExitCode::SUCCESS);
+    }
+
     // Stores the shared state of the GUI components.
     let state = Arc::new(RwLock::new(None::<GuiState>));
     let settings = Arc::new(Mutex::new(GuiSettings::new()));
@@ -122,12 +181,25 @@ async fn main() -> Result<ExitCode, Box<dyn Error>> {
     // Trigger it once to set the default values.
     app.invoke_settings_changed();
 
+    // The GPU renderer is only used by the render thread, but must be created beforehand to fall back when it fails.
+    let gpu_renderer = match cli.renderer {
+        Renderer::Cpu => None,
+        Renderer::Gpu => match GpuRenderer::new() {
+            Ok(renderer) => Some(renderer),
+            Err(x) => {
+                warn!("{x}, falling back to the software renderer");
+                None
+            }
+        },
+    };
+
     // Render the view continuously, but only update the canvas when necessary
     let render_handle = {
         let state = state.clone();
         let app_weak: slint::Weak<Application> = app.as_weak();
         let settings = settings.clone();
         let canvas = canvas.clone();
+        let gpu_renderer = Mutex::new(gpu_renderer);
 
         Arc::new(PauseableThread::new("ltsgraph canvas worker", move || {
             if let Some(state) = state.read().unwrap().deref() {
@@ -141,22 +213,49 @@ async fn main() -> Result<ExitCode, Box<dyn Error>> {
                     *pixel_buffer = SharedPixelBuffer::<Rgba8Pixel>::new(settings_clone.width, settings_clone.height);
                 }
 
-                viewer.render(
-                    &mut tiny_skia::PixmapMut::from_bytes(
+                let mut gpu_renderer = gpu_renderer.lock().unwrap();
+                let rendered = if let Some(renderer) = gpu_renderer.as_mut() {
+                    match renderer.render(
+                        viewer,
                         pixel_buffer.make_mut_bytes(),
+                        settings_clone.draw_action_labels,
+                        settings_clone.state_radius,
+                        settings_clone.view_x,
+                        settings_clone.view_y,
                         settings_clone.width,
                         settings_clone.height,
-                    )
-                    .unwrap(),
-                    settings_clone.draw_action_labels,
-                    settings_clone.state_radius,
-                    settings_clone.view_x,
-                    settings_clone.view_y,
-                    settings_clone.width,
-                    settings_clone.height,
-                    settings_clone.zoom_level,
-                    settings_clone.label_text_size,
-                );
+                        settings_clone.zoom_level,
+                        settings_clone.label_text_size,
+                    ) {
+                        Ok(()) => true,
+                        Err(x) => {
+                            warn!("{x}, falling back to the software renderer");
+                            *gpu_renderer = None;
+                            false
+                        }
+                    }
+                } else {
+                    false
+                };
+
+                if !rendered {
+                    viewer.render(
+                        &mut tiny_skia::PixmapMut::from_bytes(
+                            pixel_buffer.make_mut_bytes(),
+                            settings_clone.width,
+                            settings_clone.height,
+                        )
+                        .unwrap(),
+                        settings_clone.draw_action_labels,
+                        settings_clone.state_radius,
+                        settings_clone.view_x,
+                        settings_clone.view_y,
+                        settings_clone.width,
+                        settings_clone.height,
+                        settings_clone.zoom_level,
+                        settings_clone.label_text_size,
+                    );
+                }
 
                 debug!(
                     "Rendering step ({} by {}) took {} ms",
@@ -225,40 +324,39 @@ async fn main() -> Result<ExitCode, Box<dyn Error>> {
         let state = state.clone();
         let layout_handle = layout_handle.clone();
         let render_handle = render_handle.clone();
+        let app_weak = app.as_weak();
 
-        move |path: &Path| {
-            debug!("Loading LTS {} ...", path.to_string_lossy());
-
-            match File::open(path) {
-                Ok(file) => {
-                    match read_aut(file, vec![]) {
-                        Ok(lts) => {
-                            let lts = Arc::new(lts);
-                            info!("Loaded lts {}", lts);
+        move |path: &Path| match read_lts(path) {
+            Ok(lts) => {
+                let lts = Arc::new(lts);
 
-                            // Create the layout and viewer separately to make the initial state sensible.
-                            let layout = GraphLayout::new(&lts);
-                            let mut viewer = Viewer::new(&lts);
+                // Create the layout and viewer separately to make the initial state sensible.
+                let layout = GraphLayout::new(&lts);
+                let mut viewer = Viewer::new(&lts);
 
-                            viewer.update(&layout);
+                viewer.update(&layout);
 
-                            *state.write().unwrap() = Some(GuiState {
-                                graph_layout: Mutex::new(layout),
-                                viewer: Mutex::new((viewer, SharedPixelBuffer::new(1, 1))),
-                            });
+                *state.write().unwrap() = Some(GuiState {
+                    lts,
+                    graph_layout: Mutex::new(layout),
+                    viewer: Mutex::new((viewer, SharedPixelBuffer::new(1, 1))),
+                    simulation: Mutex::new(None),
+                    selection: Mutex::new(None),
+                });
 
-                            // Enable the layout and rendering threads.
-                            layout_handle.resume();
-                            render_handle.resume();
-                        }
-                        Err(x) => {
-                            error_dialog::show_error_dialog("Failed to load LTS!", &format!("{}", x));
-                        }
-                    }
-                }
-                Err(x) => {
-                    error_dialog::show_error_dialog("Failed to load LTS!", &format!("{}", x));
+                // The simulation and selection of the previous LTS are removed.
+                if let Some(app) = app_weak.upgrade() {
+                    app.set_simulating(false);
+                    app.set_enabled_transitions(ModelRc::default());
+                    app.set_has_selection(false);
                 }
+
+                // Enable the layout and rendering threads.
+                layout_handle.resume();
+                render_handle.resume();
+            }
+            Err(x) => {
+                error_dialog::show_error_dialog("Failed to load LTS!", &format!("{}", x));
             }
         }
     };
@@ -318,7 +416,11 @@ async fn main() -> Result<ExitCode, Box<dyn Error>> {
 
             invoke_from_event_loop(move || {
                 slint::spawn_local(async move {
-                    if let Some(handle) = rfd::AsyncFileDialog::new().add_filter("", &["aut"]).pick_file().await {
+                    if let Some(handle) = rfd::AsyncFileDialog::new()
+                        .add_filter("", &["aut", "lts", "fsm", "toml"])
+                        .pick_file()
+                        .await
+                    {
                         load_lts(handle.path());
                     }
                 })
@@ -328,6 +430,48 @@ async fn main() -> Result<ExitCode, Box<dyn Error>> {
         });
     }
 
+    // Open the file dialog and export the current layout to the chosen image.
+    {
+        let state = state.clone();
+        let settings = settings.clone();
+        let scale = cli.scale;
+
+        app.on_export_filedialog(move || {
+            let state = state.clone();
+            let settings = settings.clone();
+
+            invoke_from_event_loop(move || {
+                slint::spawn_local(async move {
+                    if let Some(handle) = rfd::AsyncFileDialog::new()
+                        .add_filter("SVG", &["svg"])
+                        .add_filter("PNG", &["png"])
+                        .set_file_name("lts.svg")
+                        .save_file()
+                        .await
+                    {
+                        if let Some(state) = state.read().unwrap().deref() {
+                            let settings = settings.lock().unwrap().clone();
+                            let (ref mut viewer, _) = *state.viewer.lock().unwrap();
+
+                            if let Err(x) = export_image(
+                                viewer,
+                                handle.path(),
+                                settings.draw_action_labels,
+                                settings.state_radius,
+                                settings.label_text_size,
+                                scale,
+                            ) {
+                                error_dialog::show_error_dialog("Failed to export image!", &format!("{}", x));
+                            }
+                        }
+                    }
+                })
+                .unwrap();
+            })
+            .unwrap();
+        });
+    }
+
     // Focus on the graph
     {
         let settings = settings.clone();
@@ -345,20 +489,211 @@ async fn main() -> Result<ExitCode, Box<dyn Error>> {
 
                     let center = viewer.center();
 
-                    // Change the view to show the LTS in full.
-                    app.global::<Settings>().set_view_x(center.x);
-                    app.global::<Settings>().set_view_y(center.y);
+                    // Change the view to show the LTS in full, the view is translated such that the center is in the middle.
+                    app.global::<Settings>().set_view_x(-center.x);
+                    app.global::<Settings>().set_view_y(-center.y);
 
                     let mut settings = settings.lock().unwrap();
-                    settings.view_x = center.x;
-                    settings.view_y = center.y;
+                    settings.view_x = -center.x;
+                    settings.view_y = -center.y;
+
+                    render_handle.resume();
+                }
+            }
+        });
+    }
+
+    // Highlight the search results and center the view on them.
+    {
+        let settings = settings.clone();
+        let state = state.clone();
+        let render_handle = render_handle.clone();
+        let app_weak = app.as_weak();
+
+        app.on_search(move |query| {
+            if let Some(app) = app_weak.upgrade() {
+                if let Some(state) = state.read().unwrap().deref() {
+                    let (ref mut viewer, _) = *state.viewer.lock().unwrap();
+
+                    let num_of_matches = viewer.search(&query);
+                    debug!("Search for {query} found {num_of_matches} matches");
+                    app.set_search_result(format!("{num_of_matches} matches").into());
+
+                    if let Some(center) = viewer.highlighted_center() {
+                        app.global::<Settings>().set_view_x(-center.x);
+                        app.global::<Settings>().set_view_y(-center.y);
+
+                        let mut settings = settings.lock().unwrap();
+                        settings.view_x = -center.x;
+                        settings.view_y = -center.y;
+                    }
+
+                    render_handle.resume();
+                }
+            }
+        });
+    }
+
+    // Start or stop the simulation from the initial state.
+    {
+        let settings = settings.clone();
+        let state = state.clone();
+        let render_handle = render_handle.clone();
+        let app_weak = app.as_weak();
+
+        app.on_simulate(move |enabled| {
+            if let (Some(app), Some(state)) = (app_weak.upgrade(), state.read().unwrap().deref()) {
+                let mut simulation = state.simulation.lock().unwrap();
+                *simulation = enabled.then(|| Trace::new(state.lts.initial_state_index()));
+
+                show_trace(&app, state, &settings, simulation.as_ref());
+                render_handle.resume();
+            }
+        });
+    }
+
+    // Take a transition of the current state, or undo the last one.
+    {
+        let settings = settings.clone();
+        let state = state.clone();
+        let render_handle = render_handle.clone();
+        let app_weak = app.as_weak();
+
+        let simulate_step = move |step: &dyn Fn(&mut Simulator)| {
+            if let (Some(app), Some(state)) = (app_weak.upgrade(), state.read().unwrap().deref()) {
+                let mut simulation = state.simulation.lock().unwrap();
+                if let Some(trace) = simulation.take() {
+                    let mut simulator = Simulator::with_trace(&state.lts, trace);
+                    step(&mut simulator);
+                    *simulation = Some(simulator.into_trace());
+                }
+
+                show_trace(&app, state, &settings, simulation.as_ref());
+                render_handle.resume();
+            }
+        };
+
+        let simulate_undo = simulate_step.clone();
+        app.on_take_transition(move |index| {
+            simulate_step(&|simulator| {
+                simulator.select(index as usize);
+            })
+        });
+        app.on_undo_transition(move || {
+            simulate_undo(&|simulator| {
+                simulator.undo();
+            })
+        });
+    }
+
+    // Open the file dialog and write the simulated trace, with one action per line.
+    {
+        let state = state.clone();
+
+        app.on_export_trace(move || {
+            let state = state.clone();
+
+            invoke_from_event_loop(move || {
+                slint::spawn_local(async move {
+                    if let Some(handle) = rfd::AsyncFileDialog::new()
+                        .add_filter("Trace", &["trace", "txt"])
+                        .set_file_name("trace.txt")
+                        .save_file()
+                        .await
+                    {
+                        if let Some(state) = state.read().unwrap().deref() {
+                            if let Some(trace) = state.simulation.lock().unwrap().as_ref() {
+                                if let Err(x) = File::create(handle.path())
+                                    .map_err(|x| x.into())
+                                    .and_then(|file| write_plain_trace(file, &state.lts, trace))
+                                {
+                                    error_dialog::show_error_dialog("Failed to export trace!", &format!("{}", x));
+                                }
+                            }
+                        }
+                    }
+                })
+                .unwrap();
+            })
+            .unwrap();
+        });
+    }
+
+    // Select the state under the mouse, or remove the selection when there is no state.
+    {
+        let settings = settings.clone();
+        let state = state.clone();
+        let render_handle = render_handle.clone();
+        let app_weak = app.as_weak();
+
+        app.on_select_state(move |x, y| {
+            if let (Some(app), Some(state)) = (app_weak.upgrade(), state.read().unwrap().deref()) {
+                let settings = settings.lock().unwrap().clone();
+                let layout = state.graph_layout.lock().unwrap();
+                let (ref mut viewer, _) = *state.viewer.lock().unwrap();
+
+                let selected = viewer.state_at(to_world(&settings, x, y), settings.state_radius);
+                viewer.set_selected(selected);
+                *state.selection.lock().unwrap() = selected;
 
+                show_selection(&app, state, &layout, selected);
+                render_handle.resume();
+
+                // Otherwise the view is panned.
+                return selected.is_some();
+            }
+
+            false
+        });
+    }
+
+    // Move the selected state to the mouse and pin it there.
+    {
+        let settings = settings.clone();
+        let state = state.clone();
+        let layout_handle = layout_handle.clone();
+        let render_handle = render_handle.clone();
+        let app_weak = app.as_weak();
+
+        app.on_drag_state(move |x, y| {
+            if let (Some(app), Some(state)) = (app_weak.upgrade(), state.read().unwrap().deref()) {
+                let selection = *state.selection.lock().unwrap();
+                if let Some(selected) = selection {
+                    let settings = settings.lock().unwrap().clone();
+                    let mut layout = state.graph_layout.lock().unwrap();
+
+                    let state_layout = &mut layout.layout_states[selected];
+                    state_layout.position = to_world(&settings, x, y).extend(0.0);
+                    state_layout.pinned = true;
+
+                    let (ref mut viewer, _) = *state.viewer.lock().unwrap();
+                    viewer.update(&layout);
+                    show_selection(&app, state, &layout, Some(selected));
+
+                    // The other states are moved by the layout to follow the pinned state.
+                    layout_handle.resume();
                     render_handle.resume();
                 }
             }
         });
     }
 
+    // Pin the selected state at its current position, or let the layout move it again.
+    {
+        let state = state.clone();
+        let layout_handle = layout_handle.clone();
+
+        app.on_pin_state(move |pinned| {
+            if let Some(state) = state.read().unwrap().deref() {
+                let selection = *state.selection.lock().unwrap();
+                if let Some(selected) = selection {
+                    state.graph_layout.lock().unwrap().layout_states[selected].pinned = pinned;
+                    layout_handle.resume();
+                }
+            }
+        });
+    }
+
     // Loads the LTS given on the command line.
     if let Some(path) = &cli.labelled_transition_system {
         load_lts(Path::new(path));
@@ -372,3 +707,106 @@ async fn main() -> Result<ExitCode, Box<dyn Error>> {
 
     Ok(ExitCode::SUCCESS)
 }
+
+/// Reads the LTS from the given path, for a project the model is read with the actions hidden by the reduction.
+fn read_lts(path: &Path) -> Result<LabelledTransitionSystem, Box<dyn Error>> {
+    let (path, hidden_labels) = if path.extension().is_some_and(|ext| ext == "toml") {
+        let project = Project::read(path)?;
+        (project.model, project.reduction.tau)
+    } else {
+        (path.to_path_buf(), vec![])
+    };
+
+    debug!("Loading LTS {} ...", path.to_string_lossy());
+    let lts = read_lts_file(&path, hidden_labels)?;
+    info!("Loaded lts {}", lts);
+
+    Ok(lts)
+}
+
+/// Shows the simulated trace in the viewer and the transitions that are
+/// enabled at its end in the user interface, and centers the view on the
+/// current state.
+fn show_trace(app: &Application, state: &GuiState, settings: &Mutex<GuiSettings>, trace: Option<&Trace>) {
+    let (ref mut viewer, _) = *state.viewer.lock().unwrap();
+    viewer.set_trace(trace);
+
+    app.set_simulating(trace.is_some());
+    let enabled: Vec<SharedString> = trace
+        .map(|trace| {
+            state
+                .lts
+                .outgoing_transitions(trace.last_state())
+                .map(|(label, to)| format!("{} → {}", state.lts.labels()[label], to).into())
+                .collect()
+        })
+        .unwrap_or_default();
+    app.set_enabled_transitions(ModelRc::new(VecModel::from(enabled)));
+
+    if let Some(trace) = trace {
+        let position = viewer.position(trace.last_state());
+        app.global::<Settings>().set_view_x(-position.x);
+        app.global::<Settings>().set_view_y(-position.y);
+
+        let mut settings = settings.lock().unwrap();
+        settings.view_x = -position.x;
+        settings.view_y = -position.y;
+    }
+}
+
+/// Converts the given position on the canvas to a position in the graph, which is the inverse of the view transformation.
+fn to_world(settings: &GuiSettings, x: f32, y: f32) -> Vec2 {
+    (Vec2::new(x, y) - Vec2::new(settings.width as f32, settings.height as f32) / 2.0) / settings.zoom_level
+        - Vec2::new(settings.view_x, settings.view_y)
+}
+
+/// Shows the identifier, the state labels, the degrees and the outgoing
+/// transitions of the selected state in the selection panel.
+fn show_selection(app: &Application, state: &GuiState, layout: &GraphLayout, selected: Option<StateIndex>) {
+    app.set_has_selection(selected.is_some());
+
+    if let Some(selected) = selected {
+        let lts = &state.lts;
+        let in_degree = lts
+            .iter_states()
+            .flat_map(|state_index| lts.outgoing_transitions(state_index))
+            .filter(|(_, to)| *to == selected)
+            .count();
+
+        app.set_selection_title(format!("State {selected}").into());
+        app.set_selection_degrees(
+            format!(
+                "In degree: {in_degree}, out degree: {}",
+                lts.outgoing_transitions(selected).count()
+            )
+            .into(),
+        );
+
+        // A state can have multiple state vectors after a reduction.
+        let labels = match lts.state_labels() {
+            Some(state_labels) => state_labels
+                .state_label(selected)
+                .iter()
+                .map(|vector| {
+                    state_labels
+                        .parameters()
+                        .iter()
+                        .zip(vector)
+                        .map(|(parameter, value)| format!("{parameter} = {value}"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            None => "No state labels".to_string(),
+        };
+        app.set_selection_labels(labels.into());
+
+        let outgoing: Vec<SharedString> = lts
+            .outgoing_transitions(selected)
+            .map(|(label, to)| format!("{} → {}", lts.labels()[label], to).into())
+            .collect();
+        app.set_selection_outgoing(ModelRc::new(VecModel::from(outgoing)));
+        app.set_selection_pinned(layout.layout_states[selected].pinned);
+    }
+}
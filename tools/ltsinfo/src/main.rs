@@ -2,18 +2,27 @@ use std::error::Error;
 use std::fs::File;
 use std::io::stdout;
 use std::io::BufWriter;
+use std::path::Path;
 use std::process::ExitCode;
 
 use clap::Parser;
 use clap::ValueEnum;
-use io::io_aut::read_aut;
+use io::formats::read_lts_file;
 use io::io_aut::write_aut;
+use io::io_graphml::write_graphml;
+use io::io_json::write_json;
+use lts::branching_bisim_constellation;
 use lts::branching_bisim_sigref;
 use lts::branching_bisim_sigref_naive;
+use lts::branching_bisim_sigref_parallel;
 use lts::quotient_lts;
+use lts::simulation_equivalence;
+use lts::strong_bisim_paige_tarjan;
 use lts::strong_bisim_sigref;
 use lts::strong_bisim_sigref_naive;
+use lts::strong_bisim_sigref_parallel;
 use lts::IndexedPartition;
+use lts::LtsStatistics;
 
 #[cfg(feature = "measure-allocs")]
 #[global_allocator]
@@ -32,17 +41,43 @@ static ALLOC: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
 enum Equivalence {
     StrongBisim,
     StrongBisimNaive,
+    StrongBisimParallel,
+    StrongBisimPaigeTarjan,
     BranchingBisim,
     BranchingBisimNaive,
+    BranchingBisimParallel,
+    BranchingBisimConstellation,
+    Simulation,
 }
 
 #[derive(clap::Parser, Debug)]
-#[command(name = "Maurice Laveaux", about = "A command line rewriting tool")]
+#[command(name = "Maurice Laveaux", about = "A command line tool for labelled transition systems")]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Cli {
-    equivalence: Equivalence,
+    #[command(subcommand)]
+    command: Option<Command>,
 
-    filename: String,
+    #[command(flatten)]
+    reduce: ReduceArgs,
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum Command {
+    Info(InfoArgs),
+}
 
+/// The arguments of the default command, which reduces an LTS modulo the given equivalence.
+#[derive(clap::Args, Debug)]
+struct ReduceArgs {
+    #[arg(required = true)]
+    equivalence: Option<Equivalence>,
+
+    /// The input LTS, in the .aut, .lts or .fsm format.
+    #[arg(required = true)]
+    filename: Option<String>,
+
+    /// The output file, which is written in the GraphML or JSON format for the
+    /// .graphml and .json extensions and in the .aut format otherwise.
     output: Option<String>,
 
     #[arg(short, long)]
@@ -52,44 +87,101 @@ struct Cli {
     time: bool,
 }
 
+#[derive(clap::Args, Debug)]
+#[command(about = "Print statistics of an LTS")]
+struct InfoArgs {
+    /// The input LTS, in the .aut, .lts or .fsm format.
+    filename: String,
+
+    #[arg(short, long)]
+    tau: Option<Vec<String>>,
+
+    /// Also compute the diameter, which requires a breadth first search from every state.
+    #[arg(long)]
+    diameter: bool,
+
+    /// Print the statistics as JSON.
+    #[arg(long)]
+    json: bool,
+}
+
 fn main() -> Result<ExitCode, Box<dyn Error>> {
     env_logger::init();
 
     let cli = Cli::parse();
 
-    let file = File::open(cli.filename)?;
+    match cli.command {
+        None => reduce(cli.reduce)?,
+        Some(Command::Info(args)) => {
+            let lts = read_lts_file(Path::new(&args.filename), args.tau.unwrap_or_default())?;
+
+            let mut statistics = LtsStatistics::new(&lts);
+            if args.diameter {
+                statistics = statistics.with_diameter(&lts);
+            }
+
+            if args.json {
+                println!("{}", serde_json::to_string_pretty(&statistics)?);
+            } else {
+                println!("{statistics}");
+            }
+        }
+    }
 
+    #[cfg(feature = "measure-allocs")]
+    eprintln!("allocations: {}", MEASURE_ALLOC.number_of_allocations());
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Reduces the input LTS modulo the given equivalence and writes the quotient.
+fn reduce(args: ReduceArgs) -> Result<(), Box<dyn Error>> {
     let mut timing = Timing::new();
-    let lts = read_aut(&file, cli.tau.unwrap_or_default())?;
+    let equivalence = args
+        .equivalence
+        .expect("The equivalence is required without a subcommand");
+    let filename = args.filename.expect("The filename is required without a subcommand");
+    let lts = read_lts_file(Path::new(&filename), args.tau.unwrap_or_default())?;
 
-    let partition: IndexedPartition = match cli.equivalence {
+    let partition: IndexedPartition = match equivalence {
         Equivalence::StrongBisim => strong_bisim_sigref(&lts, &mut timing),
         Equivalence::StrongBisimNaive => strong_bisim_sigref_naive(&lts, &mut timing),
+        Equivalence::StrongBisimParallel => strong_bisim_sigref_parallel(&lts, &mut timing),
+        Equivalence::StrongBisimPaigeTarjan => strong_bisim_paige_tarjan(&lts, &mut timing),
         Equivalence::BranchingBisim => branching_bisim_sigref(&lts, &mut timing),
         Equivalence::BranchingBisimNaive => branching_bisim_sigref_naive(&lts, &mut timing),
+        Equivalence::BranchingBisimParallel => branching_bisim_sigref_parallel(&lts, &mut timing),
+        Equivalence::BranchingBisimConstellation => branching_bisim_constellation(&lts, &mut timing),
+        Equivalence::Simulation => simulation_equivalence(&lts, &mut timing),
     };
 
     let mut quotient_time = timing.start("quotient");
     let quotient_lts = quotient_lts(
         &lts,
         &partition,
-        matches!(cli.equivalence, Equivalence::BranchingBisim)
-            || matches!(cli.equivalence, Equivalence::BranchingBisimNaive),
+        matches!(
+            equivalence,
+            Equivalence::BranchingBisim
+                | Equivalence::BranchingBisimNaive
+                | Equivalence::BranchingBisimParallel
+                | Equivalence::BranchingBisimConstellation
+        ),
     );
-    if let Some(file) = cli.output {
-        let mut writer = BufWriter::new(File::create(file)?);
-        write_aut(&mut writer, &quotient_lts)?;
+    if let Some(file) = args.output {
+        let mut writer = BufWriter::new(File::create(&file)?);
+        match Path::new(&file).extension().and_then(|extension| extension.to_str()) {
+            Some("graphml") => write_graphml(&mut writer, &quotient_lts)?,
+            Some("json") => write_json(&mut writer, &quotient_lts)?,
+            _ => write_aut(&mut writer, &quotient_lts)?,
+        }
     } else {
         write_aut(&mut stdout(), &quotient_lts)?;
     }
     quotient_time.finish();
 
-    if cli.time {
+    if args.time {
         timing.print();
     }
 
-    #[cfg(feature = "measure-allocs")]
-    eprintln!("allocations: {}", MEASURE_ALLOC.number_of_allocations());
-
-    Ok(ExitCode::SUCCESS)
+    Ok(())
 }
@@ -4,22 +4,25 @@ use std::fs::File;
 use std::fs::{self};
 use std::io::BufRead;
 use std::io::BufReader;
+use std::panic::AssertUnwindSafe;
+use std::panic::{self};
 use std::rc::Rc;
 use std::time::Instant;
 
 use ahash::AHashSet;
+use anyhow::anyhow;
 use anyhow::bail;
 use clap::ValueEnum;
 use mcrl2::aterm::TermPool;
 use mcrl2::data::DataExpression;
 use mcrl2::data::DataSpecification;
-use mcrl2::data::JittyRewriter;
 use rec_tests::load_REC_from_file;
+use sabre::reduce_test_case;
 use sabre::utilities::to_untyped_data_expression;
-use sabre::InnermostRewriter;
-use sabre::RewriteEngine;
 use sabre::RewriteSpecification;
-use sabre::SabreRewriter;
+use sabre::RewritingStatistics;
+use sabre::Rule;
+use sabre::Strategy;
 
 #[derive(ValueEnum, Debug, Clone)]
 pub enum Rewriter {
@@ -28,13 +31,34 @@ pub enum Rewriter {
     Sabre,
 }
 
+/// Returns the strategy of the rewriter, where the given strategy overrides the one of the rewriter.
+fn native_strategy(rewriter: Rewriter, strategy: Option<Strategy>) -> Strategy {
+    match rewriter {
+        Rewriter::Jitty => strategy.unwrap_or(Strategy::Jitty),
+        Rewriter::Innermost => strategy.unwrap_or(Strategy::Innermost),
+        Rewriter::Sabre => strategy.unwrap_or(Strategy::Outermost),
+    }
+}
+
+/// The name of the rewriter used for the given strategy when printing the timing.
+fn rewriter_name(strategy: Strategy) -> &'static str {
+    match strategy {
+        Strategy::Innermost => "Innermost",
+        Strategy::Outermost => "Sabre",
+        Strategy::Lazy => "Lazy",
+        Strategy::Jitty => "Jitty",
+    }
+}
+
 /// Rewrites the given expressions with the given data specification and optionally prints the result.
 pub fn rewrite_data_spec(
     tp: Rc<RefCell<TermPool>>,
     rewriter: Rewriter,
+    strategy: Option<Strategy>,
     filename_dataspec: &str,
     filename_terms: &str,
     output: bool,
+    statistics: bool,
 ) -> anyhow::Result<()> {
     // Read the data specification
     let data_spec_text = fs::read_to_string(filename_dataspec)?;
@@ -49,91 +73,161 @@ pub fn rewrite_data_spec(
         .map(|x| data_spec.parse(&x.unwrap()).unwrap())
         .collect();
 
-    match rewriter {
-        Rewriter::Jitty => {
-            // Create a jitty rewriter;
-            let mut jitty_rewriter = JittyRewriter::new(&data_spec);
-
-            // Read the file line by line, and return an iterator of the lines of the file.
-            let now = Instant::now();
-            for term in &terms {
-                let result = jitty_rewriter.rewrite(term.clone());
-                if output {
-                    println!("{}", result)
-                }
-            }
-            println!("Jitty rewrite took {} ms", now.elapsed().as_millis());
-        }
-        Rewriter::Innermost => {
-            let rewrite_spec = RewriteSpecification::from(data_spec.clone());
-            let mut inner_rewriter = InnermostRewriter::new(tp.clone(), &rewrite_spec);
-
-            // Read the file line by line, and return an iterator of the lines of the file.
-            let now = Instant::now();
-            for term in &terms {
-                let result = inner_rewriter.rewrite(term.clone());
-                if output {
-                    println!("{}", result)
-                }
-            }
-            println!("Innermost rewrite took {} ms", now.elapsed().as_millis());
-        }
-        Rewriter::Sabre => {
-            let rewrite_spec = RewriteSpecification::from(data_spec.clone());
-            let mut sabre_rewriter = SabreRewriter::new(tp.clone(), &rewrite_spec);
-
-            let now = Instant::now();
-            for term in &terms {
-                let result = sabre_rewriter.rewrite(term.clone());
-                if output {
-                    println!("{}", result)
-                }
-            }
-            println!("Sabre rewrite took {} ms", now.elapsed().as_millis());
+    let strategy = native_strategy(rewriter, strategy);
+    let spec = RewriteSpecification::from(data_spec.clone());
+    let mut rewriter = strategy.rewriter(tp.clone(), &spec);
+
+    let now = Instant::now();
+    for term in &terms {
+        let result = rewriter.rewrite(term.clone());
+        if output {
+            println!("{}", result)
         }
     }
+    println!(
+        "{} rewrite took {} ms",
+        rewriter_name(strategy),
+        now.elapsed().as_millis()
+    );
+
+    if statistics {
+        print_statistics(&spec, rewriter.statistics());
+    }
 
     Ok(())
 }
 
 /// Rewrites the given REC specification.
-pub fn rewrite_rec(rewriter: Rewriter, filename_specification: &str, output: bool) -> anyhow::Result<()> {
+pub fn rewrite_rec(
+    rewriter: Rewriter,
+    strategy: Option<Strategy>,
+    filename_specification: &str,
+    output: bool,
+    statistics: bool,
+) -> anyhow::Result<()> {
     let tp = Rc::new(RefCell::new(TermPool::new()));
 
     let (syntax_spec, syntax_terms) = load_REC_from_file(&mut tp.borrow_mut(), filename_specification.into()).unwrap();
 
     let spec = syntax_spec.to_rewrite_spec(&mut tp.borrow_mut());
 
-    match rewriter {
-        Rewriter::Innermost => {
-            let mut inner = InnermostRewriter::new(tp.clone(), &spec);
-
-            let now = Instant::now();
-            for term in &syntax_terms {
-                let term = to_untyped_data_expression(&mut tp.borrow_mut(), term, &AHashSet::new());
-                let result = inner.rewrite(term);
-                if output {
-                    println!("{}", result)
-                }
-            }
-            println!("Innermost rewrite took {} ms", now.elapsed().as_millis());
+    let strategy = native_strategy(rewriter, strategy);
+    let mut rewriter = strategy.rewriter(tp.clone(), &spec);
+
+    let now = Instant::now();
+    for term in &syntax_terms {
+        let term = to_untyped_data_expression(&mut tp.borrow_mut(), term, &AHashSet::new());
+        let result = rewriter.rewrite(term);
+        if output {
+            println!("{}", result)
         }
-        Rewriter::Sabre => {
-            let mut sa = SabreRewriter::new(tp.clone(), &spec);
-
-            let now = Instant::now();
-            for term in &syntax_terms {
-                let term = to_untyped_data_expression(&mut tp.borrow_mut(), term, &AHashSet::new());
-                let result = sa.rewrite(term);
-                if output {
-                    println!("{}", result)
-                }
-            }
-            println!("Sabre rewrite took {} ms", now.elapsed().as_millis());
+    }
+    println!(
+        "{} rewrite took {} ms",
+        rewriter_name(strategy),
+        now.elapsed().as_millis()
+    );
+
+    if statistics {
+        print_statistics(&spec, rewriter.statistics());
+    }
+
+    Ok(())
+}
+
+/// Prints the number of times every rule has been applied, followed by the rules that were never applied.
+fn print_statistics(spec: &RewriteSpecification, statistics: &RewritingStatistics) {
+    let mut applied: Vec<(&Rule, usize)> = statistics
+        .rule_applications
+        .iter()
+        .map(|(rule, count)| (rule, *count))
+        .collect();
+    // Rules that are applied equally often are ordered by structure, so the output does not depend on the hash map.
+    applied.sort_by(|(left_rule, left), (right_rule, right)| {
+        right.cmp(left).then_with(|| {
+            left_rule
+                .lhs
+                .structural_cmp(&right_rule.lhs)
+                .then_with(|| left_rule.rhs.structural_cmp(&right_rule.rhs))
+        })
+    });
+
+    println!("{} rewrite steps", statistics.rewrite_steps);
+    for (rule, count) in applied {
+        println!("{count:>10}  {rule}");
+    }
+
+    let unused: Vec<&Rule> = statistics.unused_rules(spec).collect();
+    if !unused.is_empty() {
+        println!(
+            "{} of {} rules were never applied:",
+            unused.len(),
+            spec.rewrite_rules.len()
+        );
+        for rule in unused {
+            println!("            {rule}");
         }
-        Rewriter::Jitty => {
-            bail!("Cannot use REC specifications with mCRL2's jitty rewriter");
+    }
+}
+
+/// Finds the first term for which the given rewriter panics or disagrees with
+/// the reference strategy, and prints a minimal specification and term for
+/// which this still happens.
+///
+/// Only panics that are raised in Rust code, such as the assertions in the
+/// rewriters and exceeded [sabre::RewriteLimits], are caught. Failures in the
+/// C++ term library abort the process, since unwinding cannot cross the FFI
+/// boundary, so such crashes cannot be reduced by this function.
+pub fn reduce_data_spec(
+    tp: Rc<RefCell<TermPool>>,
+    rewriter: Rewriter,
+    strategy: Option<Strategy>,
+    reference: Strategy,
+    filename_dataspec: &str,
+    filename_terms: &str,
+) -> anyhow::Result<()> {
+    let data_spec_text = fs::read_to_string(filename_dataspec)?;
+    let data_spec = DataSpecification::new(&data_spec_text)?;
+
+    let terms: Vec<DataExpression> = BufReader::new(File::open(filename_terms)?)
+        .lines()
+        .map(|line| data_spec.parse(&line?).map_err(|error| anyhow!("{error}")))
+        .collect::<anyhow::Result<_>>()?;
+
+    let strategy = native_strategy(rewriter, strategy);
+    let rewrite_spec = RewriteSpecification::from(data_spec.clone());
+
+    // The panics are expected, so the default messages are suppressed during
+    // the reduction. Note that catch_unwind only recovers from Rust panics.
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+
+    let mut fails = |spec: &RewriteSpecification, term: &DataExpression| {
+        let Ok(expected) = panic::catch_unwind(AssertUnwindSafe(|| {
+            reference.rewriter(tp.clone(), spec).rewrite(term.clone())
+        })) else {
+            // Only failures of the rewriter under test are of interest.
+            return false;
+        };
+
+        panic::catch_unwind(AssertUnwindSafe(|| {
+            strategy.rewriter(tp.clone(), spec).rewrite(term.clone())
+        }))
+        .map_or(true, |result| result != expected)
+    };
+
+    let result = terms
+        .iter()
+        .find(|term| fails(&rewrite_spec, term))
+        .map(|term| reduce_test_case(&rewrite_spec, term, &mut fails));
+    panic::set_hook(hook);
+
+    match result.transpose()? {
+        Some((spec, term)) => {
+            println!("{}", spec);
+            println!("{}", term);
         }
+        None => bail!("The {strategy} rewriter agrees with the {reference} rewriter for all terms"),
     }
 
     Ok(())
@@ -12,10 +12,12 @@ use log::info;
 use log::warn;
 use mcrl2::aterm::TermPool;
 use mcrl2::data::DataSpecification;
+use mcrl2rewrite::reduce_data_spec;
 use mcrl2rewrite::rewrite_data_spec;
 use mcrl2rewrite::rewrite_rec;
 use mcrl2rewrite::Rewriter;
 use sabre::RewriteSpecification;
+use sabre::Strategy;
 
 use crate::trs_format::TrsFormatter;
 
@@ -35,6 +37,7 @@ static ALLOC: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
 pub(crate) enum Cli {
     Rewrite(RewriteArgs),
     Convert(ConvertArgs),
+    Reduce(ReduceArgs),
 }
 
 #[derive(clap::Args, Debug)]
@@ -50,6 +53,22 @@ struct RewriteArgs {
 
     #[arg(long = "output", default_value_t = false, help = "Print the rewritten term(s)")]
     output: bool,
+
+    #[arg(
+        long,
+        help = "The rewrite strategy, either innermost, outermost, lazy or jitty. Overrides the strategy of the rewriter"
+    )]
+    strategy: Option<Strategy>,
+
+    #[arg(
+        long,
+        default_value_t = false,
+        help = "Print the number of times every rewrite rule was applied and the rules that were never applied"
+    )]
+    stats: bool,
+
+    #[arg(long, default_value_t = false, help = "Print the metrics of the term pool as JSON")]
+    metrics: bool,
 }
 
 #[derive(clap::Args, Debug)]
@@ -61,6 +80,24 @@ struct ConvertArgs {
     output: String,
 }
 
+#[derive(clap::Args, Debug)]
+#[command(about = "Reduce a test case for which the rewriter panics or disagrees with a reference rewriter")]
+struct ReduceArgs {
+    rewriter: Rewriter,
+
+    #[arg(value_name = "SPEC")]
+    specification: String,
+
+    #[arg(help = "File containing the terms, the first term that fails is reduced.")]
+    terms: String,
+
+    #[arg(long, help = "The rewrite strategy, overrides the strategy of the rewriter")]
+    strategy: Option<Strategy>,
+
+    #[arg(long, default_value_t = Strategy::Jitty, help = "The strategy used to compute the expected normal forms")]
+    reference: Strategy,
+}
+
 fn main() -> Result<ExitCode, Box<dyn Error>> {
     env_logger::init();
 
@@ -71,17 +108,35 @@ fn main() -> Result<ExitCode, Box<dyn Error>> {
         Cli::Rewrite(args) => {
             if args.specification.ends_with(".rec") {
                 assert!(args.terms.is_none());
-                rewrite_rec(args.rewriter, &args.specification, args.output)?;
+                rewrite_rec(
+                    args.rewriter,
+                    args.strategy,
+                    &args.specification,
+                    args.output,
+                    args.stats,
+                )?;
             } else {
                 match &args.terms {
                     Some(terms) => {
-                        rewrite_data_spec(tp.clone(), args.rewriter, &args.specification, terms, args.output)?;
+                        rewrite_data_spec(
+                            tp.clone(),
+                            args.rewriter,
+                            args.strategy,
+                            &args.specification,
+                            terms,
+                            args.output,
+                            args.stats,
+                        )?;
                     }
                     None => {
                         warn!("No expressions given to rewrite!");
                     }
                 }
             }
+
+            if args.metrics {
+                println!("{}", serde_json::to_string(&tp.borrow().metrics())?);
+            }
         }
         Cli::Convert(args) => {
             // Read the data specification
@@ -100,6 +155,16 @@ fn main() -> Result<ExitCode, Box<dyn Error>> {
             let mut output = File::create(args.output)?;
             write!(output, "{}", TrsFormatter::new(&spec))?;
         }
+        Cli::Reduce(args) => {
+            reduce_data_spec(
+                tp.clone(),
+                args.rewriter,
+                args.strategy,
+                args.reference,
+                &args.specification,
+                &args.terms,
+            )?;
+        }
     }
 
     info!("ATerm pool: {}", tp.borrow());
@@ -95,7 +95,10 @@ impl fmt::Display for TrsFormatter<'_> {
             variables
         };
 
-        // Print the list of variables.
+        // Print the list of variables, sorted to obtain the same output every time.
+        let mut variables: Vec<String> = variables.into_iter().collect();
+        variables.sort_unstable();
+
         writeln!(f, "(VAR ")?;
         for var in variables {
             writeln!(f, "\t {} ", var)?;
@@ -0,0 +1,48 @@
+use std::cell::RefCell;
+use std::error::Error;
+use std::process::ExitCode;
+use std::rc::Rc;
+
+use clap::Parser;
+use mcrl2::aterm::TermPool;
+use pbes::simplify_pbes;
+use pbes::Pbes;
+use sabre::Strategy;
+
+#[cfg(not(target_env = "msvc"))]
+#[global_allocator]
+static ALLOC: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
+
+#[derive(clap::Parser, Debug)]
+#[command(
+    name = "Maurice Laveaux",
+    about = "Simplifies a parameterised boolean equation system by rewriting, quantifier elimination and constant propagation"
+)]
+struct Cli {
+    /// The parameterised boolean equation system, in the .pbes format.
+    filename: String,
+
+    /// The rewrite strategy that is used to rewrite the data expressions.
+    #[arg(short, long, default_value_t = Strategy::Outermost)]
+    rewriter: Strategy,
+
+    /// The output .pbes file, when omitted the equation system is printed in the textual format.
+    output: Option<String>,
+}
+
+fn main() -> Result<ExitCode, Box<dyn Error>> {
+    env_logger::init();
+
+    let cli = Cli::parse();
+    let pbes = Pbes::read(&cli.filename)?;
+
+    let tp = Rc::new(RefCell::new(TermPool::new()));
+    let pbes = simplify_pbes(&pbes, tp.clone(), cli.rewriter);
+
+    match cli.output {
+        Some(output) => pbes.write(&mut tp.borrow_mut(), &output)?,
+        None => println!("{pbes}"),
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
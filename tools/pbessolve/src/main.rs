@@ -0,0 +1,93 @@
+use std::cell::RefCell;
+use std::error::Error;
+use std::fs::File;
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::path::Path;
+use std::process::ExitCode;
+use std::rc::Rc;
+
+use clap::Parser;
+use log::info;
+use mcrl2::aterm::TermPool;
+use pbes::instantiate_pbes;
+use pbes::read_pg;
+use pbes::simplify_pbes;
+use pbes::solve_pbes_local;
+use pbes::write_pg;
+use pbes::Pbes;
+use pbes::Player;
+use pbes::SolverKind;
+use sabre::Strategy;
+
+#[cfg(not(target_env = "msvc"))]
+#[global_allocator]
+static ALLOC: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
+
+#[derive(clap::Parser, Debug)]
+#[command(
+    name = "Maurice Laveaux",
+    about = "Solves a parameterised boolean equation system by instantiating it into a parity game"
+)]
+struct Cli {
+    /// The parameterised boolean equation system, in the .pbes format, or a
+    /// parity game in the PGSolver format when it has the .pg extension.
+    filename: String,
+
+    /// The rewrite strategy that is used to evaluate the data expressions.
+    #[arg(short, long, default_value_t = Strategy::Outermost)]
+    rewriter: Strategy,
+
+    /// The algorithm that is used to solve the parity game, one of zielonka, spm or priority-promotion.
+    #[arg(short, long, default_value_t = SolverKind::Zielonka)]
+    solver: SolverKind,
+
+    /// Simplifies the equation system before instantiating it, see pbesrewr.
+    #[arg(long)]
+    simplify: bool,
+
+    /// Instantiates the parity game on-the-fly, and stops as soon as the solution of the initial state is known.
+    #[arg(long, conflicts_with = "game")]
+    local: bool,
+
+    /// Writes the parity game to the given file in the PGSolver format, for example to solve it with another solver.
+    #[arg(short, long)]
+    game: Option<String>,
+}
+
+fn main() -> Result<ExitCode, Box<dyn Error>> {
+    env_logger::init();
+
+    let cli = Cli::parse();
+    let game = if Path::new(&cli.filename).extension().is_some_and(|ext| ext == "pg") {
+        read_pg(BufReader::new(File::open(&cli.filename)?))?
+    } else {
+        let mut pbes = Pbes::read(&cli.filename)?;
+
+        let tp = Rc::new(RefCell::new(TermPool::new()));
+        if cli.simplify {
+            pbes = simplify_pbes(&pbes, tp.clone(), cli.rewriter);
+        }
+
+        if cli.local {
+            println!("{}", solve_pbes_local(&pbes, tp, cli.rewriter, cli.solver)?);
+            return Ok(ExitCode::SUCCESS);
+        }
+
+        instantiate_pbes(&pbes, tp, cli.rewriter)?
+    };
+    info!(
+        "The parity game has {} vertices and {} edges",
+        game.num_of_vertices(),
+        game.num_of_edges()
+    );
+
+    if let Some(filename) = &cli.game {
+        write_pg(&mut BufWriter::new(File::create(filename)?), &game)?;
+    }
+
+    let winners = cli.solver.solver().solve(&game);
+    println!("{}", winners[game.initial_vertex()] == Player::Even);
+
+    Ok(ExitCode::SUCCESS)
+}
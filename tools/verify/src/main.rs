@@ -0,0 +1,324 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::rc::Rc;
+
+use clap::Parser;
+use clap::ValueEnum;
+use io::io_aut::read_aut_file;
+use io::io_aut::write_aut;
+use io::project;
+use io::project::Engine;
+use io::project::Project;
+use io::project::Property;
+use io::project::Reduction;
+use log::info;
+use lps::HashStorage;
+use lps::NextStateGenerator;
+use lps::StateStorage;
+use lts::branching_bisim_sigref;
+use lts::check_ctl;
+use lts::check_formula;
+use lts::check_ltl;
+use lts::quotient;
+use lts::quotient_lts;
+use lts::strong_bisim_sigref;
+use lts::CtlFormula;
+use lts::LabelIndex;
+use lts::LabelledTransitionSystem;
+use lts::LtlFormula;
+use lts::StateFormula;
+use mcrl2::aterm::TermPool;
+use mcrl2::lps::LinearProcessSpecification;
+use sabre::Strategy;
+use utilities::Timing;
+
+use crate::workspace::file_key;
+use crate::workspace::stage_key;
+use crate::workspace::Workspace;
+
+mod workspace;
+
+#[cfg(feature = "measure-allocs")]
+#[global_allocator]
+static MEASURE_ALLOC: unsafety::AllocCounter = unsafety::AllocCounter;
+
+#[cfg(not(target_env = "msvc"))]
+#[cfg(not(feature = "measure-allocs"))]
+#[global_allocator]
+static ALLOC: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Equivalence {
+    None,
+    StrongBisim,
+    BranchingBisim,
+}
+
+impl From<Equivalence> for project::Equivalence {
+    fn from(value: Equivalence) -> Self {
+        match value {
+            Equivalence::None => project::Equivalence::None,
+            Equivalence::StrongBisim => project::Equivalence::StrongBisim,
+            Equivalence::BranchingBisim => project::Equivalence::BranchingBisim,
+        }
+    }
+}
+
+#[derive(clap::Parser, Debug)]
+#[command(
+    name = "Maurice Laveaux",
+    about = "Verifies a specification by chaining exploration, reduction and model checking"
+)]
+struct Cli {
+    #[arg(help = "The input, either a .toml project, an .lps or an .aut file")]
+    input: PathBuf,
+
+    #[arg(
+        short,
+        long,
+        help = "The modal formula that must be checked, or an LTL or CTL formula for the .ltl and .ctl extensions"
+    )]
+    formula: Option<PathBuf>,
+
+    #[arg(short, long, value_enum, default_value_t = Equivalence::BranchingBisim)]
+    equivalence: Equivalence,
+
+    #[arg(
+        long,
+        default_value = ".verify",
+        help = "The directory in which intermediate results are cached, unless specified by the project"
+    )]
+    workspace: PathBuf,
+
+    #[arg(long, help = "Recompute all stages, ignoring the cached results")]
+    force: bool,
+
+    #[arg(short, long)]
+    tau: Option<Vec<String>>,
+
+    #[arg(
+        long,
+        help = "The rewriter used to explore an .lps file, one of innermost, outermost, lazy or jitty"
+    )]
+    rewriter: Option<String>,
+
+    #[arg(long)]
+    time: bool,
+}
+
+fn main() -> Result<ExitCode, Box<dyn Error>> {
+    env_logger::init();
+
+    let cli = Cli::parse();
+
+    // A project file describes the complete verification run, otherwise it is given by the command line.
+    let is_project = cli.input.extension().is_some_and(|ext| ext == "toml");
+    let project = if is_project {
+        Project::read(&cli.input)?
+    } else {
+        Project {
+            model: cli.input.clone(),
+            properties: cli
+                .formula
+                .iter()
+                .map(|formula| Property {
+                    name: formula.display().to_string(),
+                    formula: formula.clone(),
+                })
+                .collect(),
+            reduction: Reduction {
+                equivalence: cli.equivalence.into(),
+                tau: cli.tau.clone().unwrap_or_default(),
+            },
+            engine: Engine {
+                rewriter: cli.rewriter.clone(),
+                workspace: None,
+            },
+            ..Default::default()
+        }
+    };
+
+    let workspace = Workspace::new(project.engine.workspace.as_ref().unwrap_or(&cli.workspace))?;
+    let mut timing = Timing::new();
+
+    let extension = project
+        .model
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default();
+    let (lts_path, lts_key) = match extension {
+        "lps" => {
+            // The same default as lps2lts.
+            let rewriter = match &project.engine.rewriter {
+                Some(rewriter) => rewriter.parse()?,
+                None => Strategy::Outermost,
+            };
+
+            explore(&workspace, &project.model, rewriter, cli.force, &mut timing)?
+        }
+        "aut" => (project.model.clone(), file_key(&project.model)?),
+        _ => {
+            return Err(format!(
+                "Unknown input format {}, expected an .lps or .aut file",
+                project.model.display()
+            )
+            .into())
+        }
+    };
+
+    let reduced_path = reduce(
+        &workspace,
+        &lts_path,
+        lts_key,
+        &project.reduction,
+        cli.force,
+        &mut timing,
+    )?;
+
+    println!("Result: {}", reduced_path.display());
+
+    if !project.properties.is_empty() {
+        let lts = read_lts(&reduced_path, &[], &mut timing)?;
+
+        for property in &project.properties {
+            let text = fs::read_to_string(&property.formula)
+                .map_err(|error| format!("Cannot read {}: {error}", property.formula.display()))?;
+            let parse_error = |error| format!("Cannot parse {}: {error}", property.formula.display());
+
+            // The logic of the property is determined by the extension of the formula file.
+            let mut time = timing.start("check");
+            let result = match property.formula.extension().and_then(|ext| ext.to_str()) {
+                Some("ltl") => check_ltl(&lts, &LtlFormula::parse(&text).map_err(parse_error)?),
+                Some("ctl") => check_ctl(&lts, &CtlFormula::parse(&text).map_err(parse_error)?),
+                _ => check_formula(&lts, &StateFormula::parse(&text).map_err(parse_error)?),
+            };
+            time.finish();
+
+            println!("Property {}: {result}", property.name);
+        }
+    }
+
+    if cli.time {
+        timing.print();
+    }
+
+    #[cfg(feature = "measure-allocs")]
+    eprintln!("allocations: {}", MEASURE_ALLOC.number_of_allocations());
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Explores the state space of the linear process in the given .lps file with
+/// the given rewriter, and returns the path to the resulting LTS in the
+/// workspace together with its key.
+fn explore(
+    workspace: &Workspace,
+    lps_path: &Path,
+    rewriter: Strategy,
+    force: bool,
+    timing: &mut Timing,
+) -> Result<(PathBuf, u64), Box<dyn Error>> {
+    // The rewriter does not influence the state space, so it is not part of the key.
+    let key = stage_key(file_key(lps_path)?, &[]);
+    if !force {
+        if let Some(path) = workspace.cached("explore", key, "aut") {
+            info!("Reusing the explored LTS {}", path.display());
+            return Ok((path, key));
+        }
+    }
+
+    let mut time = timing.start("explore");
+    let lps = LinearProcessSpecification::read(&lps_path.to_string_lossy())
+        .map_err(|error| format!("Cannot read {}: {error}", lps_path.display()))?;
+    let tp = Rc::new(RefCell::new(TermPool::new()));
+    let mut generator = NextStateGenerator::new(&lps, tp, rewriter)?;
+
+    // Explore the states in breadth-first order, where the initial state has index zero.
+    let mut states = HashStorage::new();
+    let mut labels: Vec<String> = Vec::new();
+    let mut label_indices: HashMap<String, LabelIndex> = HashMap::new();
+    let mut transitions = Vec::new();
+
+    states.insert(&generator.initial_state());
+    let mut queue = VecDeque::from([0]);
+    while let Some(state_index) = queue.pop_front() {
+        for transition in generator.transitions(&states.get(state_index))? {
+            let label_index = *label_indices.entry(transition.label()).or_insert_with_key(|label| {
+                labels.push(label.clone());
+                labels.len() - 1
+            });
+
+            let (to, inserted) = states.insert(&transition.target);
+            if inserted {
+                queue.push_back(to);
+            }
+            transitions.push((state_index, label_index, to));
+        }
+    }
+
+    let lts = LabelledTransitionSystem::new(
+        0,
+        Some(states.len()),
+        || transitions.iter().cloned(),
+        labels,
+        vec!["tau".to_string()],
+    );
+    time.finish();
+    info!("Explored LTS:\n{}", lts);
+
+    let path = workspace.write("explore", key, "aut", |writer| write_aut(writer, &lts))?;
+    Ok((path, key))
+}
+
+/// Reduces the LTS stored in the given file modulo the given equivalence, and
+/// returns the path to the reduced LTS in the workspace.
+fn reduce(
+    workspace: &Workspace,
+    lts_path: &Path,
+    lts_key: u64,
+    reduction: &Reduction,
+    force: bool,
+    timing: &mut Timing,
+) -> Result<PathBuf, Box<dyn Error>> {
+    let mut options = vec![format!("{:?}", reduction.equivalence)];
+    options.extend(reduction.tau.iter().cloned());
+
+    let key = stage_key(lts_key, &options);
+    if !force {
+        if let Some(path) = workspace.cached("reduce", key, "aut") {
+            info!("Reusing the reduced LTS {}", path.display());
+            return Ok(path);
+        }
+    }
+
+    let lts = read_lts(lts_path, &reduction.tau, timing)?;
+    let reduced = match reduction.equivalence {
+        project::Equivalence::None => lts,
+        project::Equivalence::StrongBisim => {
+            let partition = strong_bisim_sigref(&lts, timing);
+            quotient(&lts, &partition)
+        }
+        project::Equivalence::BranchingBisim => {
+            let partition = branching_bisim_sigref(&lts, timing);
+            quotient_lts(&lts, &partition, true)
+        }
+    };
+    info!("Reduced LTS:\n{}", reduced);
+
+    workspace.write("reduce", key, "aut", |writer| write_aut(writer, &reduced))
+}
+
+/// Reads the LTS from the given .aut file.
+fn read_lts(path: &Path, tau: &[String], timing: &mut Timing) -> Result<LabelledTransitionSystem, Box<dyn Error>> {
+    let mut time = timing.start("read_aut");
+    let lts = read_aut_file(path, tau.to_vec()).map_err(|error| format!("Cannot read {}: {error}", path.display()))?;
+    time.finish();
+
+    Ok(lts)
+}
@@ -0,0 +1,123 @@
+use std::error::Error;
+use std::fs;
+use std::fs::File;
+use std::io::BufWriter;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+use log::debug;
+
+/// A directory in which the results of every stage of the verification are
+/// stored, such that these can be reused when the verification is repeated.
+///
+/// Every result is identified by the name of the stage and a key that is
+/// derived from all the inputs of that stage. This means that a changed input,
+/// or changed options, automatically result in a different file. The keys are
+/// computed by [fnv1a], which does not depend on the version of Rust, such
+/// that the results remain valid when the tool is rebuilt.
+pub struct Workspace {
+    directory: PathBuf,
+}
+
+impl Workspace {
+    /// Opens the workspace in the given directory, which is created when it does not exist.
+    pub fn new(directory: &Path) -> Result<Workspace, Box<dyn Error>> {
+        fs::create_dir_all(directory)
+            .map_err(|error| format!("Cannot create workspace directory {}: {error}", directory.display()))?;
+
+        debug!("Using workspace {}", directory.display());
+        Ok(Workspace {
+            directory: directory.to_path_buf(),
+        })
+    }
+
+    /// Returns the path of the result of the given stage.
+    pub fn path(&self, stage: &str, key: u64, extension: &str) -> PathBuf {
+        self.directory.join(format!("{stage}-{key:016x}.{extension}"))
+    }
+
+    /// Returns the path of the result of the given stage if it has been computed before.
+    pub fn cached(&self, stage: &str, key: u64, extension: &str) -> Option<PathBuf> {
+        let path = self.path(stage, key, extension);
+        if path.is_file() {
+            debug!("Found cached result {} for stage {stage}", path.display());
+            Some(path)
+        } else {
+            None
+        }
+    }
+
+    /// Writes the result of the given stage with the given function, and returns its path.
+    ///
+    /// The result is first written to a temporary file that is renamed
+    /// afterwards, such that an interrupted stage does not leave a partial
+    /// result that would be reused by [Workspace::cached].
+    pub fn write(
+        &self,
+        stage: &str,
+        key: u64,
+        extension: &str,
+        write: impl FnOnce(&mut BufWriter<File>) -> Result<(), Box<dyn Error>>,
+    ) -> Result<PathBuf, Box<dyn Error>> {
+        let path = self.path(stage, key, extension);
+        let tmp_path = path.with_extension("tmp");
+
+        let mut writer = BufWriter::new(File::create(&tmp_path)?);
+        write(&mut writer)?;
+        writer.flush()?;
+        drop(writer);
+
+        fs::rename(&tmp_path, &path)?;
+        Ok(path)
+    }
+}
+
+/// Computes the key of a stage from the key of its input and the options of the stage.
+pub fn stage_key(input_key: u64, options: &[String]) -> u64 {
+    let mut hash = fnv1a(FNV_OFFSET_BASIS, &input_key.to_le_bytes());
+    for option in options {
+        // The length separates the options, such that different options never result in the same bytes.
+        hash = fnv1a(hash, &(option.len() as u64).to_le_bytes());
+        hash = fnv1a(hash, option.as_bytes());
+    }
+
+    hash
+}
+
+/// Computes the key of an input file based on its contents.
+pub fn file_key(path: &Path) -> Result<u64, Box<dyn Error>> {
+    let contents = fs::read(path).map_err(|error| format!("Cannot read {}: {error}", path.display()))?;
+    Ok(fnv1a(FNV_OFFSET_BASIS, &contents))
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Continues the 64-bit FNV-1a hash with the given bytes, starting from [FNV_OFFSET_BASIS].
+///
+/// Contrary to the hashers of the standard library this hash is specified,
+/// so the keys of the workspace are the same for every build of the tool.
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes
+        .iter()
+        .fold(hash, |hash, &byte| (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fnv1a() {
+        // The test vectors of the FNV-1a specification.
+        assert_eq!(fnv1a(FNV_OFFSET_BASIS, b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(FNV_OFFSET_BASIS, b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(fnv1a(FNV_OFFSET_BASIS, b"foobar"), 0x8594_4171_f739_67e8);
+
+        assert_ne!(
+            stage_key(0, &["ab".to_string(), "c".to_string()]),
+            stage_key(0, &["a".to_string(), "bc".to_string()])
+        );
+    }
+}